@@ -0,0 +1,147 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that
+//! the proposers with backpressure (see Proposer::with_backpressure) stop starting new instances
+//! while the learners, which report their progress (see Learner::report_progress), are too far
+//! behind, here because the sink of one of the two learners is broken (see Learner::with_sink).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example backpressure -- [num_of_values]
+//!
+//! 1. Without backpressure, each of the num_of_values values is decided, although one learner
+//!    delivers none of them.
+//! 2. With backpressure which waits for the slowest learner, only MAX_LAG values are
+//!    decided while one learner delivers none of them.
+//! 3. With backpressure which waits for a quorum of one learner, each value is decided, as the
+//!    other learner keeps up.
+//! 4. With backpressure which waits for the slowest learner, each value is decided, and delivered
+//!    by both learners, once the sink of the stuck learner is repaired.
+
+extern crate multi_paxos;
+
+use std::cell::Cell;
+use std::io;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use multi_paxos::multi_paxos::{BackpressurePolicy, SinkFailurePolicy};
+use multi_paxos::testkit::{arg_or, report, Cluster};
+
+/// The maximum number of started instances which the learners can have left to deliver.
+const MAX_LAG: usize = 10;
+
+/// The number of learners of each cluster, the first of which has a sink which can be broken.
+const NUM_OF_LEARNERS: usize = 2;
+
+/// Returns a cluster whose first learner has a sink which blocks the delivery while broken is true,
+/// and whose proposer has the given backpressure, if any.
+fn cluster(broken: &Arc<AtomicBool>, backpressure: Option<BackpressurePolicy>) -> Cluster<usize> {
+    let index = Cell::new(0);
+
+    Cluster::<usize>::new(1, 1, 3, NUM_OF_LEARNERS)
+        .with_learners(|l| {
+            index.set(index.get() + 1);
+
+            if index.get() > 1 {
+                return l;
+            }
+
+            let broken = broken.clone();
+            l.with_sink(
+                move |_: usize, _: &usize| {
+                    if broken.load(Ordering::SeqCst) {
+                        Err(io::Error::other("the sink is broken"))
+                    } else {
+                        Ok(())
+                    }
+                },
+                SinkFailurePolicy::Block,
+            )
+        })
+        .with_proposers(|p| match backpressure {
+            Some(policy) => p.with_backpressure(MAX_LAG, policy),
+            None => p,
+        })
+}
+
+/// Returns the number of values delivered by each learner of cluster.
+fn num_of_delivered(cluster: &mut Cluster<usize>) -> Vec<usize> {
+    (0..NUM_OF_LEARNERS)
+        .map(|i| cluster.learner(i).total_order_prefix().len())
+        .collect()
+}
+
+/// Delivers the messages until there are none, and makes the learners report their progress,
+/// which may resume the proposer, until the learners deliver no more values.
+fn run(cluster: &mut Cluster<usize>) {
+    loop {
+        cluster.run_until_quiet();
+
+        let delivered = num_of_delivered(cluster);
+
+        (0..NUM_OF_LEARNERS).for_each(|i| cluster.learner(i).report_progress());
+        cluster.run_until_quiet();
+
+        if num_of_delivered(cluster) == delivered {
+            break;
+        }
+    }
+}
+
+/// Requests the values from 1 to num_of_values with the client of cluster, once the learners
+/// reported their progress, and runs the cluster.
+fn request_all(cluster: &mut Cluster<usize>, num_of_values: usize) {
+    cluster.run_until_quiet();
+
+    (0..NUM_OF_LEARNERS).for_each(|i| cluster.learner(i).report_progress());
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.client(0).request(v));
+
+    run(cluster);
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let broken = Arc::new(AtomicBool::new(true));
+
+    let mut unthrottled = cluster(&broken, None);
+    request_all(&mut unthrottled, num_of_values);
+
+    let ok1 = report(
+        "Test 1 - Without backpressure, each value is decided, although a learner is stuck",
+        num_of_delivered(&mut unthrottled) == vec![0, num_of_values],
+    );
+
+    let mut slowest = cluster(&broken, Some(BackpressurePolicy::Slowest));
+    request_all(&mut slowest, num_of_values);
+
+    let ok2 = report(
+        "Test 2 - With backpressure, only max_lag values are decided while the slowest learner is stuck",
+        num_of_delivered(&mut slowest) == vec![0, MAX_LAG],
+    );
+
+    let mut quorum = cluster(&broken, Some(BackpressurePolicy::Quorum(1)));
+    request_all(&mut quorum, num_of_values);
+
+    let ok3 = report(
+        "Test 3 - With backpressure which waits for a quorum of learners, each value is decided",
+        num_of_delivered(&mut quorum) == vec![0, num_of_values],
+    );
+
+    broken.store(false, Ordering::SeqCst);
+    slowest.learner(0).retry_sinks();
+    run(&mut slowest);
+
+    let prefix = slowest.learner(1).total_order_prefix().to_vec();
+
+    let ok4 = report(
+        "Test 4 - With backpressure, each value is decided once the slowest learner catches up",
+        num_of_delivered(&mut slowest) == vec![num_of_values; NUM_OF_LEARNERS]
+            && slowest.learner(0).total_order_prefix() == &prefix[..],
+    );
+
+    if !(ok1 && ok2 && ok3 && ok4) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,132 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! proposer which merges the queued client requests into batches (see Proposer::with_batching)
+//! decides several of them in a single instance, and that their commands are delivered in order.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example batching -- [num_of_values]
+//!
+//! In each test, the proposer has at most 1 instance in flight, so the requests which arrive in the
+//! meantime are queued, and a batch holds at most CAPACITY commands.
+//! 1. The num_of_values requests of a client are decided in about num_of_values / CAPACITY
+//!    instances, and the commands of the batches, in the order of their instances, are the values of
+//!    the requests, in the order they were sent.
+//! 2. The clients of all the requests merged into a batch, among 3 clients, are told the instance
+//!    where it is decided, which holds their values.
+//! 3. The clients of all the requests merged into a batch which the validator of the proposer
+//!    rejects are told so, whereas the other batches are decided.
+
+extern crate multi_paxos;
+
+use std::process;
+
+use multi_paxos::batch::CommandBatch;
+use multi_paxos::multi_paxos::Batch;
+use multi_paxos::testkit::{arg_or, report, Cluster};
+
+/// The maximum number of commands of a batch.
+const CAPACITY: usize = 4;
+
+type Commands = CommandBatch<usize, CAPACITY>;
+
+/// Returns a cluster of the given number of clients, whose proposer merges the queued requests
+/// into batches, and has at most 1 instance in flight.
+fn cluster_with(num_of_clients: usize) -> Cluster<Commands> {
+    let mut cluster = Cluster::<Commands>::new(num_of_clients, 1, 3, 1)
+        .with_proposers(|p| p.with_max_in_flight(1).with_batching());
+    cluster.run_until_quiet();
+
+    cluster
+}
+
+/// Returns the batches delivered by the learner of cluster, in the order of their instances.
+fn batches(cluster: &mut Cluster<Commands>) -> Vec<Commands> {
+    cluster
+        .learner(0)
+        .total_order_prefix()
+        .iter()
+        .map(|&(_, batch)| batch)
+        .collect()
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let mut cluster = cluster_with(1);
+
+    (1..=num_of_values).for_each(|v| cluster.client(0).request(Commands::new(v)));
+    cluster.run_until_quiet();
+
+    let delivered = batches(&mut cluster);
+    let commands: Vec<usize> = delivered.iter().flat_map(Batch::commands).collect();
+
+    // The first request is proposed alone, as the proposer is idle when it arrives.
+    let expected_instances = 1 + (num_of_values - 1).div_ceil(CAPACITY);
+
+    let ok1 = report(
+        "Test 1 - The queued requests are decided in batches, whose commands are in the order of the requests",
+        commands == (1..=num_of_values).collect::<Vec<usize>>()
+            && delivered.len() == expected_instances
+            && delivered.iter().all(|batch| batch.len() <= CAPACITY),
+    );
+
+    let mut cluster = cluster_with(3);
+
+    let handles: Vec<(usize, usize, u64)> = (1..=num_of_values)
+        .map(|v| {
+            let client = v % 3;
+            (
+                client,
+                v,
+                cluster.client(client).request_tracked(Commands::new(v)),
+            )
+        })
+        .collect();
+    cluster.run_until_quiet();
+
+    let num_of_instances = batches(&mut cluster).len();
+
+    let ok2 = report(
+        "Test 2 - The clients of all the requests of a batch are told the instance where it is decided",
+        num_of_instances < num_of_values
+            && handles.iter().all(|&(client, v, handle)| {
+                let client = cluster.client(client);
+
+                client
+                    .instance_of(handle)
+                    .and_then(|instance| client.query(instance))
+                    .is_some_and(|batch| batch.iter().any(|c| c == v))
+            }),
+    );
+
+    let mut cluster = Cluster::<Commands>::new(1, 1, 3, 1).with_proposers(|p| {
+        p.with_max_in_flight(1)
+            .with_batching()
+            .with_value_validator(|_instance: usize, batch: &Commands| {
+                !batch.iter().any(|c| c == 2)
+            })
+    });
+    cluster.run_until_quiet();
+
+    let handles: Vec<u64> = (1..=num_of_values)
+        .map(|v| cluster.client(0).request_tracked(Commands::new(v)))
+        .collect();
+    cluster.run_until_quiet();
+
+    let client = cluster.client(0);
+
+    // The second batch holds the values from 2 to CAPACITY + 1.
+    let ok3 = report(
+        "Test 3 - The clients of all the requests of a rejected batch are told so",
+        handles.iter().zip(1..).all(|(&handle, v)| {
+            if (2..=CAPACITY + 1).contains(&v) {
+                client.is_rejected(handle)
+            } else {
+                client.instance_of(handle).is_some()
+            }
+        }),
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
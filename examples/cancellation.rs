@@ -0,0 +1,114 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! client can give up on its requests (see Client::cancel), and that the proposer then stops
+//! trying to decide their values, without leaving a gap in the log.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example cancellation -- [num_of_values]
+//!
+//! 1. Among num_of_values requests queued by a proposer with at most 1 instance in flight, the
+//!    cancelled ones (the even ones) are dropped, whereas the others are decided, in order.
+//! 2. The requests cancelled while their values are being proposed get the no-op in their
+//!    instances instead, so the log has no gap, and their clients are not told any instance.
+//! 3. The requests cancelled once their values were voted for by a majority of the acceptors (but
+//!    not learned by the proposer) are decided anyway, and their clients are told where.
+
+extern crate multi_paxos;
+
+use std::process;
+
+use multi_paxos::message::Message;
+use multi_paxos::testkit::{arg_or, report, Cluster, Role};
+
+/// The value which the proposer proposes instead of the values of the cancelled requests.
+const NOOP: usize = 0;
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1).with_proposers(|p| p.with_max_in_flight(1));
+    cluster.run_until_quiet();
+
+    let handles: Vec<u64> = (1..=num_of_values)
+        .map(|v| cluster.client(0).request_tracked(v))
+        .collect();
+
+    // The first request is proposed as soon as it arrives, so only the next ones are queued.
+    handles
+        .iter()
+        .zip(1..)
+        .filter(|&(_, v)| v % 2 == 0)
+        .for_each(|(&handle, _)| cluster.client(0).cancel(handle));
+    cluster.run_until_quiet();
+
+    let expected: Vec<usize> = (1..=num_of_values).filter(|v| v % 2 == 1).collect();
+
+    let ok1 = report(
+        "Test 1 - The cancelled requests which are queued are dropped, and the other ones are decided",
+        cluster.delivered_values(0) == expected
+            && cluster.proposer(0).num_of_cancelled_requests() == num_of_values / 2
+            && handles.iter().zip(1..).all(|(&handle, v)| {
+                cluster.client(0).instance_of(handle).is_some() == (v % 2 == 1)
+            }),
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1).with_proposers(|p| p.with_noop(NOOP));
+    cluster.run_until_quiet();
+
+    let handles: Vec<u64> = (1..=num_of_values)
+        .map(|v| {
+            let handle = cluster.client(0).request_tracked(v);
+
+            // The request is delivered to the proposer, which starts its instance, before it is
+            // cancelled.
+            cluster.step();
+            cluster.client(0).cancel(handle);
+            cluster.run_until_quiet();
+
+            handle
+        })
+        .collect();
+
+    let ok2 = report(
+        "Test 2 - The cancelled requests which are being proposed get the no-op in their instances",
+        cluster.delivered_values(0) == vec![NOOP; num_of_values]
+            && cluster.proposer(0).num_of_cancelled_requests() == num_of_values
+            && handles
+                .iter()
+                .all(|&handle| cluster.client(0).instance_of(handle).is_none()),
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1).with_proposers(|p| p.with_noop(NOOP));
+    cluster.run_until_quiet();
+
+    let handles: Vec<u64> = (1..=num_of_values)
+        .map(|v| {
+            let handle = cluster.client(0).request_tracked(v);
+
+            // The votes of the acceptors are lost on their way to the proposer.
+            while {
+                cluster.drop_pending(|receiver, m| {
+                    receiver == Role::Proposers && matches!(m, Message::Phase2b(_))
+                });
+                cluster.step()
+            } {}
+
+            cluster.client(0).cancel(handle);
+            cluster.run_until_quiet();
+
+            handle
+        })
+        .collect();
+
+    let ok3 = report(
+        "Test 3 - The cancelled requests whose values were already voted for are decided anyway",
+        cluster.delivered_values(0) == (1..=num_of_values).collect::<Vec<usize>>()
+            && handles.iter().zip(1..).all(|(&handle, v)| {
+                let client = cluster.client(0);
+                client.instance_of(handle).and_then(|i| client.query(i)) == Some(v)
+            }),
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,183 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that
+//! the nodes truncate their logs below a checkpoint decided in the log, but only once it is stable,
+//! i.e. once all the previous instances are decided and a majority of the learners acknowledged it
+//! (see Proposer::with_checkpoint).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example checkpoint -- [num_of_values]
+//!
+//! 1. After num_of_values values and the checkpoint are decided, the proposers, the acceptors and
+//!    the learner truncate their logs below the checkpoint, and a learner which joins later skips
+//!    the truncated instances.
+//! 2. While the first instance is not decided, the checkpoint is not stable, and nothing is
+//!    truncated, until the instance is completed.
+//! 3. The checkpoint is not stable until the learner acknowledged its delivery.
+//! 4. The checkpoint is stable once a majority of the learners acknowledged it, and a learner which
+//!    lags behind (here because its sink is broken) still delivers every value once it catches up.
+
+extern crate multi_paxos;
+
+use std::cell::Cell;
+use std::io;
+use std::process;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use multi_paxos::message::Message;
+use multi_paxos::multi_paxos::SinkFailurePolicy;
+use multi_paxos::testkit::{arg_or, report, Cluster, Role};
+
+/// The value which marks a checkpoint of the application.
+const CHECKPOINT: usize = usize::MAX;
+
+/// Returns the instances of the values delivered by the i-th learner of cluster, in total order.
+fn delivered(cluster: &mut Cluster<usize>, i: usize) -> Vec<usize> {
+    cluster
+        .learner(i)
+        .total_order_prefix()
+        .iter()
+        .map(|&(instance, _)| instance)
+        .collect()
+}
+
+/// Returns whether the proposers and the acceptors of cluster truncated their logs below
+/// instance, and only below it.
+fn truncated_below(cluster: &mut Cluster<usize>, proposers: usize, instance: usize) -> bool {
+    (0..proposers).all(|i| cluster.proposer(i).truncated_below() == instance)
+        && (0..3).all(|i| cluster.acceptor(i).truncated_below() == instance)
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let checkpoint = num_of_values + 1;
+    let last = 2 * num_of_values + 1;
+
+    let mut cluster = Cluster::<usize>::new(1, 2, 3, 1).with_checkpoint(CHECKPOINT);
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.request(0, v));
+    cluster.request(0, CHECKPOINT);
+    (checkpoint + 1..=last).for_each(|v| cluster.request(0, v));
+
+    let i = cluster.add_learner();
+    cluster.run_until_quiet();
+
+    let expected: Vec<usize> = (checkpoint..=last).collect();
+    let log = cluster.proposer(0).export_log();
+
+    let ok1 = report(
+        "Test 1 - The nodes truncate their logs below the checkpoint, and a late learner skips the truncated instances",
+        truncated_below(&mut cluster, 2, checkpoint)
+            && log.keys().cloned().collect::<Vec<usize>>() == expected
+            && log[&checkpoint] == CHECKPOINT
+            && delivered(&mut cluster, 0) == expected
+            && delivered(&mut cluster, i) == expected,
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1).with_checkpoint(CHECKPOINT);
+    cluster.run_until_quiet();
+
+    // The votes of the first instance are lost, so the proposer does not know it is decided.
+    cluster.client(0).request(1);
+    while {
+        cluster.drop_pending(|receiver, m| match m {
+            Message::Phase2b(acceptance) => {
+                receiver == Role::Proposers && acceptance.instance.0 == 1
+            }
+            _ => false,
+        });
+        cluster.step()
+    } {}
+
+    (2..=num_of_values).for_each(|v| cluster.request(0, v));
+    cluster.request(0, CHECKPOINT);
+
+    let not_stable = truncated_below(&mut cluster, 1, 1);
+
+    cluster.proposer(0).complete_instance(1);
+    cluster.run_until_quiet();
+
+    let ok2 = report(
+        "Test 2 - The checkpoint is not stable while a previous instance is not decided",
+        not_stable && truncated_below(&mut cluster, 1, checkpoint),
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1).with_checkpoint(CHECKPOINT);
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.request(0, v));
+
+    // The acknowledgment of the checkpoint is lost.
+    cluster.client(0).request(CHECKPOINT);
+    while {
+        cluster.drop_pending(|_, m| match m {
+            Message::LearnAck(learn_ack) => learn_ack.instance.0 == checkpoint,
+            _ => false,
+        });
+        cluster.step()
+    } {}
+
+    let not_acknowledged =
+        truncated_below(&mut cluster, 1, 1) && delivered(&mut cluster, 0) == vec![checkpoint];
+
+    // The Learning message of the checkpoint is sent again, and acknowledged again.
+    cluster.proposer(0).complete_instance(checkpoint);
+    cluster.run_until_quiet();
+
+    let ok3 = report(
+        "Test 3 - The checkpoint is not stable until the learner acknowledged it",
+        not_acknowledged && truncated_below(&mut cluster, 1, checkpoint),
+    );
+
+    // The sink of the last learner blocks its deliveries while it is broken.
+    let broken = Arc::new(AtomicBool::new(true));
+    let consumed = Arc::new(AtomicUsize::new(0));
+    let index = Cell::new(0);
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 3)
+        .with_checkpoint(CHECKPOINT)
+        .with_learners(|l| {
+            index.set(index.get() + 1);
+
+            if index.get() < 3 {
+                return l;
+            }
+
+            let (broken, consumed) = (broken.clone(), consumed.clone());
+            l.with_sink(
+                move |_: usize, _: &usize| {
+                    if broken.load(Ordering::SeqCst) {
+                        return Err(io::Error::other("the sink is broken"));
+                    }
+
+                    consumed.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                },
+                SinkFailurePolicy::Block,
+            )
+        });
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.request(0, v));
+    cluster.request(0, CHECKPOINT);
+    (checkpoint + 1..=last).for_each(|v| cluster.request(0, v));
+
+    let lagging =
+        truncated_below(&mut cluster, 1, checkpoint) && delivered(&mut cluster, 2).is_empty();
+
+    broken.store(false, Ordering::SeqCst);
+    cluster.learner(2).retry_sinks();
+    cluster.run_until_quiet();
+
+    let ok4 = report(
+        "Test 4 - The checkpoint is stable once a majority of the learners acknowledged it, and a lagging learner still catches up",
+        lagging
+            && consumed.load(Ordering::SeqCst) == last
+            && delivered(&mut cluster, 2) == expected,
+    );
+
+    if !(ok1 && ok2 && ok3 && ok4) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,99 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that
+//! the proposers tell a client, with the acknowledgment of its request, the watermark of the
+//! chosen prefix of the log and, if it asked for them (see Client::request_with_prefix), the chosen
+//! values of that prefix, up to the instance of its own value.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example chosen_prefix -- [num_of_values]
+//!
+//! 1. After another client requested num_of_values values, a tracked request is acknowledged with
+//!    the watermark of its own instance, but without the values of the prefix.
+//! 2. A client which is behind gets the whole chosen prefix, in bounded parts, with the
+//!    acknowledgments of its requests, and it is the same as the log delivered by the learner.
+//! 3. The prefixes of concurrent requests, which overlap, are only kept once.
+
+extern crate multi_paxos;
+
+use std::process;
+
+use multi_paxos::testkit::{arg_or, report, Cluster};
+
+/// The maximum number of values of the prefix sent with an acknowledgment (see
+/// Client::request_with_prefix).
+const PREFIX_MAX_VALUES: usize = 64;
+
+/// The first value requested by the client which asks for the chosen prefix, so that its values
+/// differ from the ones of the other client.
+const FIRST_VALUE: usize = 1_000_000;
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let mut cluster = Cluster::<usize>::new(2, 1, 3, 1);
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.client(1).request(v));
+    cluster.run_until_quiet();
+
+    let handle = cluster.client(0).request_tracked(FIRST_VALUE);
+    cluster.run_until_quiet();
+
+    let client = cluster.client(0);
+
+    let ok1 = report(
+        "Test 1 - A tracked request is acknowledged with the watermark of the chosen prefix, but without its values",
+        client.instance_of(handle) == Some(num_of_values + 1)
+            && client.watermark_of(handle) == Some(num_of_values + 1)
+            && client.chosen_prefix().is_empty(),
+    );
+
+    let mut value = FIRST_VALUE;
+    let mut bounded = true;
+
+    loop {
+        value += 1;
+
+        let known = cluster.client(0).chosen_prefix().len();
+        let handle = cluster.client(0).request_with_prefix(value);
+        cluster.run_until_quiet();
+
+        let client = cluster.client(0);
+        bounded &= client.chosen_prefix().len() - known <= PREFIX_MAX_VALUES;
+
+        // The whole prefix, up to the instance of the last value, is known.
+        if client.instance_of(handle) == Some(client.chosen_prefix().len()) {
+            break;
+        }
+    }
+
+    let log = cluster.delivered_values(0);
+
+    let ok2 = report(
+        "Test 2 - A client which is behind gets the whole chosen prefix, in bounded parts",
+        bounded && cluster.client(0).chosen_prefix() == &log[..],
+    );
+
+    let known = cluster.client(0).chosen_prefix().len();
+
+    (1..=num_of_values).for_each(|v| cluster.client(1).request(v));
+    cluster.run_until_quiet();
+
+    // The three requests ask for the same part of the prefix, which ends with the instance of the
+    // last of them, unless it is bounded before.
+    for i in 1..=3 {
+        cluster.client(0).request_with_prefix(value + i);
+    }
+    cluster.run_until_quiet();
+
+    let prefix = cluster.client(0).chosen_prefix().to_vec();
+
+    let ok3 = report(
+        "Test 3 - The overlapping prefixes of concurrent requests are only kept once",
+        prefix.len() == known + (num_of_values + 3).min(PREFIX_MAX_VALUES)
+            && cluster.delivered_values(0).starts_with(&prefix),
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,196 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that
+//! two proposers which serve the requests of their own clients concurrently never decide the
+//! values of two requests in the same instance, even if the requests have equal values.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example concurrent_proposers -- [num_of_values]
+//!
+//! 1. If each of the 2 proposers handles num_of_values requests of its own client at once, so that
+//!    they compete for the same instances, each request is decided in its own instance, i.e. no two
+//!    requests are decided in the same instance, and each value is delivered once.
+//! 2. The same holds in num_of_values random schedules.
+//! 3. A late proposer which adopts, in the instance of its request, the vote for an equal value of
+//!    the request of another proposer moves its request to a new instance.
+//! 4. A late proposer which learns that an equal value was decided in the instance of its request,
+//!    before it proposed its own value there, moves its request to a new instance.
+//! 5. In num_of_values random schedules, the requests which a client sends to both proposers, so
+//!    that they both propose them, are each decided once.
+
+extern crate multi_paxos;
+extern crate uuid;
+
+use std::collections::{HashMap, HashSet};
+use std::process;
+
+use multi_paxos::message::{Message, Request};
+use multi_paxos::testkit::{arg_or, report, Cluster, Role};
+use uuid::Uuid;
+
+/// The number of values requested by each client in each random schedule.
+const NUM_OF_RANDOM_VALUES: usize = 5;
+
+/// The value requested by the clients of both proposers, in the tests with equal values.
+const VALUE: usize = 7;
+
+/// Makes the i-th proposer of cluster handle the requests of the given values, sent by a client
+/// with the given uuid, without delivering their messages yet, so that it starts all of them
+/// concurrently with the other proposer.
+fn propose<I>(cluster: &mut Cluster<usize>, i: usize, client_uuid: Uuid, values: I)
+where
+    I: IntoIterator<Item = usize>,
+{
+    for value in values {
+        cluster.proposer(i).handle(Message::Phase0a(Request {
+            value,
+            sender_uuid: client_uuid,
+            request_seq: value,
+            priority: None,
+            group_id: 0,
+            prefix_from: None,
+        }));
+    }
+}
+
+/// Returns whether each instance of cluster is decided for a single request, according to the
+/// Decided messages delivered to the clients, and whether num_of_requests requests are decided.
+fn one_request_per_instance(cluster: &Cluster<usize>, num_of_requests: usize) -> bool {
+    let mut requests: HashMap<usize, HashSet<(Uuid, usize)>> = HashMap::new();
+
+    for entry in cluster.trace_collector().unwrap().entries() {
+        if let Message::Decided(ref decided) = entry.message {
+            requests
+                .entry(decided.instance.0)
+                .or_default()
+                .insert((decided.receiver_uuid, decided.request_seq));
+        }
+    }
+
+    let decided_requests: HashSet<&(Uuid, usize)> = requests.values().flatten().collect();
+
+    requests.values().all(|r| r.len() == 1) && decided_requests.len() == num_of_requests
+}
+
+/// Returns the values delivered by the learner of cluster, sorted.
+fn delivered(cluster: &mut Cluster<usize>) -> Vec<usize> {
+    let mut values: Vec<usize> = cluster
+        .learner(0)
+        .total_order_prefix()
+        .iter()
+        .map(|&(_, v)| v)
+        .collect();
+    values.sort_unstable();
+    values
+}
+
+/// Returns a cluster, created with seed, with 2 proposers, which handled the requests of the
+/// values from 1 to num_of_values, and from num_of_values + 1 to 2 * num_of_values, respectively,
+/// of their own clients, at once, and whose messages are not delivered yet.
+fn concurrent_requests(seed: u64, num_of_values: usize) -> Cluster<usize> {
+    let mut cluster = Cluster::<usize>::with_seed(seed, 1, 2, 3, 1).with_trace_collector();
+    cluster.run_until_quiet();
+
+    propose(&mut cluster, 0, Uuid::new_v4(), 1..=num_of_values);
+    propose(
+        &mut cluster,
+        1,
+        Uuid::new_v4(),
+        num_of_values + 1..=2 * num_of_values,
+    );
+
+    cluster
+}
+
+/// Returns whether the requests of concurrent_requests(seed, num_of_values), once all the messages
+/// are delivered, randomly if random is true, are each decided in its own instance.
+fn concurrent(seed: u64, num_of_values: usize, random: bool) -> bool {
+    let mut cluster = concurrent_requests(seed, num_of_values);
+
+    if random {
+        while cluster.step_random(0.0).is_some() {}
+    } else {
+        cluster.run_until_quiet();
+    }
+
+    one_request_per_instance(&cluster, 2 * num_of_values)
+        && delivered(&mut cluster) == (1..=2 * num_of_values).collect::<Vec<usize>>()
+}
+
+/// Returns whether the request of VALUE of a late proposer, which starts the instance 1 where the
+/// first proposer already decided VALUE for its own client, is decided in another instance, once
+/// the messages for which withheld returns true, if any, are dropped.
+fn equal_values<P>(withheld: P) -> bool
+where
+    P: Fn(Role, &Message<usize>) -> bool,
+{
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1).with_trace_collector();
+    cluster.run_until_quiet();
+
+    propose(&mut cluster, 0, Uuid::new_v4(), vec![VALUE]);
+    cluster.run_until_quiet();
+
+    // The late proposer does not catch up, so it does not know that the instance 1 is decided.
+    let late = cluster.add_proposer();
+    cluster.proposer(late).finish_catch_up();
+    cluster.drop_pending(|_, _| true);
+
+    propose(&mut cluster, late, Uuid::new_v4(), vec![VALUE]);
+    while {
+        cluster.drop_pending(&withheld);
+        cluster.step()
+    } {}
+
+    // The late proposer learns the decision from the gossip of the first one, if it did not decide
+    // the instance itself.
+    cluster.proposer(late).gossip();
+    cluster.run_until_quiet();
+
+    one_request_per_instance(&cluster, 2) && delivered(&mut cluster) == vec![VALUE, VALUE]
+}
+
+/// Returns whether the requests of the values from 1 to NUM_OF_RANDOM_VALUES, which a client sends
+/// to both proposers of a cluster created with seed, are each decided once, in a random schedule.
+fn same_requests(seed: u64) -> bool {
+    let mut cluster = Cluster::<usize>::with_seed(seed, 1, 2, 3, 1).with_trace_collector();
+    cluster.run_until_quiet();
+
+    let client_uuid = Uuid::new_v4();
+    propose(&mut cluster, 0, client_uuid, 1..=NUM_OF_RANDOM_VALUES);
+    propose(&mut cluster, 1, client_uuid, 1..=NUM_OF_RANDOM_VALUES);
+    while cluster.step_random(0.0).is_some() {}
+
+    one_request_per_instance(&cluster, NUM_OF_RANDOM_VALUES)
+        && delivered(&mut cluster) == (1..=NUM_OF_RANDOM_VALUES).collect::<Vec<usize>>()
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let ok1 = report(
+        "Test 1 - The requests of the clients of two proposers are decided in distinct instances",
+        concurrent(0, num_of_values, false),
+    );
+
+    let ok2 = report(
+        "Test 2 - In random schedules, the requests of the clients of two proposers are decided in distinct instances",
+        (1..=num_of_values as u64).all(|seed| concurrent(seed, NUM_OF_RANDOM_VALUES, true)),
+    );
+
+    let ok3 = report(
+        "Test 3 - A late proposer which adopts the vote for an equal value of another request moves its own request",
+        equal_values(|_, _| false),
+    );
+
+    let ok4 = report(
+        "Test 4 - A late proposer which learns that an equal value was decided before it proposed moves its own request",
+        equal_values(|receiver, m| receiver == Role::Acceptors && matches!(m, Message::Phase1a(_))),
+    );
+
+    let ok5 = report(
+        "Test 5 - In random schedules, the requests sent to both proposers are each decided once",
+        (1..=num_of_values as u64).all(same_requests),
+    );
+
+    if !(ok1 && ok2 && ok3 && ok4 && ok5) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,81 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that
+//! the learners learn the chosen values directly from the Acceptance messages of the acceptors,
+//! when the acceptors also send them to the learners (see Acceptor::with_learners_address and
+//! Learner::with_direct_learning).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example direct_learning -- [num_of_values]
+//!
+//! 1. By default, the acceptors send their Acceptance messages only to the proposers.
+//! 2. With direct learning, a learner learns the value of an instance before any Learning message
+//!    is delivered to it.
+//! 3. With direct learning, the learners learn every one of num_of_values values exactly once, in
+//!    the same order as the proposer, although each value is known from both the acceptors and the
+//!    proposer.
+
+extern crate multi_paxos;
+
+use std::process;
+
+use multi_paxos::message::Message;
+use multi_paxos::testkit::{arg_or, report, Cluster, Role};
+
+/// Returns the number of the Acceptance messages which have been delivered to the learners of
+/// cluster.
+fn acceptances_to_learners(cluster: &Cluster<usize>) -> usize {
+    cluster
+        .trace_collector()
+        .unwrap()
+        .count(|e| e.receiver == Role::Learners && matches!(e.message, Message::Phase2b(_)))
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 2).with_trace_collector();
+    cluster.run_until_quiet();
+
+    cluster.request(0, 1);
+
+    let ok1 = report(
+        "Test 1 - By default, the acceptors do not send their Acceptance messages to the learners",
+        acceptances_to_learners(&cluster) == 0 && cluster.learner(0).learned_value(1) == Some(1),
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 2)
+        .with_direct_learning()
+        .with_trace_collector();
+    cluster.run_until_quiet();
+
+    cluster.client(0).request(1);
+    while cluster.learner(0).learned_value(1).is_none() && cluster.step() {}
+
+    let num_of_learnings = cluster
+        .trace_collector()
+        .unwrap()
+        .count(|e| e.receiver == Role::Learners && matches!(e.message, Message::Phase3(_)));
+
+    let ok2 = report(
+        "Test 2 - A learner learns a value from the acceptors, before the Learning message",
+        cluster.learner(0).learned_value(1) == Some(1)
+            && num_of_learnings == 0
+            && acceptances_to_learners(&cluster) > 0,
+    );
+
+    cluster.run_until_quiet();
+
+    (2..=num_of_values).for_each(|v| cluster.client(0).request(v));
+    cluster.run_until_quiet();
+
+    let expected: Vec<(usize, usize)> = cluster.proposer(0).export_log().into_iter().collect();
+
+    let ok3 = report(
+        "Test 3 - The learners learn every value exactly once, in the same order",
+        expected.len() == num_of_values
+            && (0..2).all(|i| cluster.learner(i).total_order_prefix() == &expected[..]),
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,114 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! draining proposer ignores the new client requests, but decides its instances in flight before it
+//! stops, and that it stops anyway, reporting them, if they cannot be decided within its drain
+//! timeout (see Proposer::drain).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example drain -- [num_of_values]
+//!
+//! 1. The num_of_values instances in flight when the proposer drains are decided before it stops,
+//!    whereas a request received meanwhile is ignored.
+//! 2. The requests queued by a busy proposer when it drains are dropped, and only its instances in
+//!    flight are decided before it stops.
+//! 3. Without a majority of the acceptors, the proposer stops once its drain timeout elapses, and
+//!    the instances which it could not drain are reported.
+
+extern crate multi_paxos;
+extern crate uuid;
+
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use multi_paxos::message::{Message, Request};
+use multi_paxos::testkit::{arg_or, report, Cluster};
+use uuid::Uuid;
+
+/// The maximum number of instances in flight of the proposer in the test 2.
+const MAX_IN_FLIGHT: usize = 10;
+
+/// The drain timeout of the proposer in the test 3.
+const DRAIN_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Makes the proposer of cluster handle the requests of the given values, sent by the client with
+/// the given uuid, without delivering their messages.
+fn request(cluster: &mut Cluster<usize>, client_uuid: Uuid, values: Vec<usize>) {
+    for value in values {
+        cluster.proposer(0).handle(Message::Phase0a(Request {
+            value,
+            sender_uuid: client_uuid,
+            request_seq: value,
+            priority: None,
+            group_id: 0,
+            prefix_from: None,
+        }));
+    }
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let client_uuid = Uuid::new_v4();
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1);
+    cluster.run_until_quiet();
+
+    request(&mut cluster, client_uuid, (1..=num_of_values).collect());
+
+    cluster.proposer(0).drain();
+    let stopped_early = cluster.proposer(0).is_stopped();
+
+    request(&mut cluster, client_uuid, vec![num_of_values + 1]);
+    cluster.run_until_quiet();
+
+    let ok1 = report(
+        "Test 1 - The instances in flight are decided before the proposer stops, and the new requests are ignored",
+        !stopped_early
+            && cluster.proposer(0).is_stopped()
+            && cluster.proposer(0).in_flight_instances().is_empty()
+            && cluster.delivered_values(0) == (1..=num_of_values).collect::<Vec<usize>>(),
+    );
+
+    let mut cluster =
+        Cluster::<usize>::new(1, 1, 3, 1).with_proposers(|p| p.with_max_in_flight(MAX_IN_FLIGHT));
+    cluster.run_until_quiet();
+
+    let num_of_requests = num_of_values.max(MAX_IN_FLIGHT + 1);
+    request(&mut cluster, client_uuid, (1..=num_of_requests).collect());
+
+    cluster.proposer(0).drain();
+    cluster.run_until_quiet();
+
+    let ok2 = report(
+        "Test 2 - The queued requests are dropped, and only the instances in flight are decided",
+        cluster.proposer(0).is_stopped()
+            && cluster.delivered_values(0) == (1..=MAX_IN_FLIGHT).collect::<Vec<usize>>(),
+    );
+
+    let mut cluster =
+        Cluster::<usize>::new(1, 1, 3, 1).with_proposers(|p| p.with_drain_timeout(DRAIN_TIMEOUT));
+    cluster.run_until_quiet();
+
+    cluster.crash_acceptor(1);
+    cluster.crash_acceptor(2);
+
+    request(&mut cluster, client_uuid, (1..=num_of_values).collect());
+
+    cluster.proposer(0).drain();
+    cluster.run_until_quiet();
+
+    let stopped_before_timeout = cluster.proposer(0).is_stopped();
+    thread::sleep(2 * DRAIN_TIMEOUT);
+
+    let ok3 = report(
+        "Test 3 - Without a majority, the proposer stops after the drain timeout, and reports the undrained instances",
+        !stopped_before_timeout
+            && cluster.proposer(0).is_stopped()
+            && cluster.proposer(0).in_flight_instances() == (1..=num_of_values).collect::<Vec<usize>>()
+            && cluster.delivered_values(0).is_empty(),
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,111 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! proposer counts each acceptor once towards the majority, even if it receives the same Promise
+//! or Acceptance message of an acceptor many times (e.g. because of retransmissions over UDP).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example duplicate_acceptances -- [num_of_duplicates]
+//!
+//! 1. The Promise of the only acceptor (of 3) which answers a Preparation, received
+//!    num_of_duplicates more times, does not make the proposer send a Proposal.
+//! 2. The Acceptance of the only acceptor (of 3) which answers a Proposal, received
+//!    num_of_duplicates more times, does not make the proposer decide the value.
+
+extern crate multi_paxos;
+
+use std::process;
+
+use multi_paxos::message::Message;
+use multi_paxos::multi_paxos::DecisionState;
+use multi_paxos::testkit::{arg_or, report, Cluster};
+
+/// The value requested by the client in each test.
+const VALUE: usize = 42;
+
+/// Returns a cluster with 1 client, 1 proposer, 3 acceptors and 1 learner, which records the
+/// delivered messages, and whose CatchUp messages are already delivered.
+fn new_cluster() -> Cluster<usize> {
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1).with_trace_collector();
+    cluster.run_until_quiet();
+    cluster
+}
+
+/// Returns the first delivered message of cluster for which matches returns true.
+fn first_delivered<F>(cluster: &Cluster<usize>, matches: F) -> Option<Message<usize>>
+where
+    F: Fn(&Message<usize>) -> bool,
+{
+    let trace_collector = cluster.trace_collector()?;
+    let position = trace_collector.position(|e| matches(&e.message))?;
+    Some(trace_collector.entries()[position].message.clone())
+}
+
+/// The first test: duplicate Promise messages of a single acceptor do not form a majority.
+fn duplicate_promises(num_of_duplicates: usize) -> bool {
+    let mut cluster = new_cluster();
+
+    cluster.crash_acceptor(1);
+    cluster.crash_acceptor(2);
+
+    cluster.request(0, VALUE);
+
+    let promise = match first_delivered(&cluster, |m| matches!(m, Message::Phase1b(_))) {
+        Some(promise) => promise,
+        None => return false,
+    };
+
+    for _ in 0..num_of_duplicates {
+        cluster.proposer(0).handle(promise.clone());
+        cluster.run_until_quiet();
+    }
+
+    cluster.count_delivered(|m| matches!(m, Message::Phase2a(_))) == 0
+}
+
+/// The second test: duplicate Acceptance messages of a single acceptor do not form a majority.
+fn duplicate_acceptances(num_of_duplicates: usize) -> bool {
+    let mut cluster = new_cluster();
+
+    // Once a majority of the acceptors has promised, the Proposal is sent, and only one acceptor
+    // is left to receive it.
+    cluster.client(0).request(VALUE);
+    while cluster.count_delivered(|m| matches!(m, Message::Phase1b(_))) < 2 && cluster.step() {}
+
+    cluster.crash_acceptor(1);
+    cluster.crash_acceptor(2);
+    cluster.run_until_quiet();
+
+    let acceptance = match first_delivered(&cluster, |m| matches!(m, Message::Phase2b(_))) {
+        Some(acceptance) => acceptance,
+        None => return false,
+    };
+
+    for _ in 0..num_of_duplicates {
+        cluster.proposer(0).handle(acceptance.clone());
+        cluster.run_until_quiet();
+    }
+
+    let decided = matches!(
+        cluster.proposer(0).decision_state(1),
+        DecisionState::Chosen(_)
+    );
+
+    !decided && cluster.learner(0).learned_value(1).is_none()
+}
+
+fn main() {
+    let num_of_duplicates = arg_or("number of duplicates", 100);
+
+    let ok1 = report(
+        "Test 1 - The duplicates of the Promise of a single acceptor do not form a majority",
+        duplicate_promises(num_of_duplicates),
+    );
+
+    let ok2 = report(
+        "Test 2 - The duplicates of the Acceptance of a single acceptor do not form a majority",
+        duplicate_acceptances(num_of_duplicates),
+    );
+
+    if !(ok1 && ok2) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,122 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! proposer which establishes an epoch (see Proposer::establish_epoch) runs phase 1 once for the
+//! tail of the log, proposes the values of its next instances in phase 2 only, and goes back to
+//! phase 1 once another proposer promised a higher round to the acceptors.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example epochs -- [num_of_values]
+//!
+//! 1. A proposer which establishes an epoch completes, in the epoch, an instance where the
+//!    acceptors voted for the value of another proposer, whose Acceptance messages were lost.
+//! 2. The num_of_values requests of a client are all decided in the epoch of the proposer, with a
+//!    single TailPreparation message, and without any Preparation message.
+//! 3. The epoch of a proposer is invalidated once the acceptors promised the epoch of another
+//!    proposer, and its value, rejected by the acceptors, is decided once it bumped its epoch.
+
+extern crate multi_paxos;
+
+use std::process;
+
+use multi_paxos::message::Message;
+use multi_paxos::testkit::{arg_or, report, Cluster, Role};
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let mut cluster = Cluster::<usize>::new(1, 2, 3, 1).with_leader(0);
+    cluster.run_until_quiet();
+
+    // The acceptors vote for the value of the first proposer, which never knows it.
+    cluster.client(0).request(7);
+    while cluster.step() {
+        cluster.drop_pending(|_, m| matches!(m, Message::Phase2b(_)));
+    }
+
+    let undecided = cluster.learner(0).learned_value(1).is_none();
+
+    let rnd = cluster.proposer(1).establish_epoch();
+    cluster.run_until_quiet();
+
+    let ok1 = report(
+        "Test 1 - An established epoch completes the instances of the tail with the values voted in them",
+        undecided
+            && rnd.is_ok()
+            && cluster.proposer(1).epoch() == rnd.ok()
+            && cluster.learner(0).learned_value(1) == Some(7),
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1).with_trace_collector();
+    cluster.run_until_quiet();
+
+    let rnd = cluster.proposer(0).establish_epoch();
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.client(0).request(v));
+    cluster.run_until_quiet();
+
+    let num_of_tail_preparations =
+        cluster.count_delivered(|m| matches!(m, Message::TailPreparation(_)));
+    let num_of_preparations = cluster.count_delivered(|m| matches!(m, Message::Phase1a(_)));
+    let in_epoch = rnd.is_ok_and(|rnd| {
+        cluster
+            .trace_collector()
+            .unwrap()
+            .filter(|e| e.receiver == Role::Acceptors)
+            .iter()
+            .all(|e| match e.message {
+                Message::Phase2a(ref proposal) => proposal.c_rnd == rnd,
+                _ => true,
+            })
+    });
+
+    let ok2 = report(
+        "Test 2 - The values of the next instances are proposed in the epoch, in phase 2 only",
+        cluster.proposer(0).epoch() == rnd.ok()
+            && num_of_tail_preparations == 1
+            && num_of_preparations == 0
+            && in_epoch
+            && cluster.learner(0).total_order_prefix().len() == num_of_values,
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 2, 3, 1)
+        .with_leader(0)
+        .with_trace_collector();
+    cluster.run_until_quiet();
+
+    let first = cluster.proposer(0).establish_epoch();
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.client(0).request(v));
+    cluster.run_until_quiet();
+
+    // The epoch of the second proposer preempts the one of the first.
+    let second = cluster.proposer(1).establish_epoch();
+    cluster.run_until_quiet();
+
+    cluster.request(0, num_of_values + 1);
+
+    let invalidated = cluster.proposer(0).epoch().is_none()
+        && cluster.proposer(0).num_of_invalidated_epochs() == 1
+        && cluster.learner(0).total_order_prefix().len() == num_of_values;
+
+    let bumped = cluster.proposer(0).bump_epoch();
+    cluster.run_until_quiet();
+
+    cluster.proposer(0).complete_instance(num_of_values + 1);
+    cluster.run_until_quiet();
+
+    let ok3 = report(
+        "Test 3 - The epoch of a proposer is invalidated by a higher epoch, and its value is decided once it bumped its epoch",
+        first.is_ok()
+            && second.ok() > first.ok()
+            && bumped.ok() > second.ok()
+            && invalidated
+            && cluster.proposer(0).epoch() == bumped.ok()
+            && cluster.learner(0).learned_value(num_of_values + 1) == Some(num_of_values + 1)
+            && cluster.count_delivered(|m| matches!(m, Message::Phase1a(_))) == 0,
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,144 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! proposer which takes over an instance from a failed leader (see Proposer::repropose) never has
+//! two different values chosen in it, when the Proposal of the failed leader reached a single
+//! acceptor before the leader died.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example failover -- [num_of_runs]
+//!
+//! In each test, the leader proposed the value of the client in the instance 1, but only one of the
+//! 3 acceptors voted for it, and the leader died right after.
+//! 1. If the new leader drives the same value again into the instance, it is decided once, in the
+//!    instance.
+//! 2. If the new leader drives another value into the instance, the value voted for is adopted and
+//!    decided in the instance, and the other value is decided in a new one.
+//! 3. In num_of_runs random schedules, the new leader which drives another value into the instance
+//!    has a single value decided in it, and its own value is decided exactly once.
+
+extern crate multi_paxos;
+extern crate uuid;
+
+use std::process;
+
+use multi_paxos::message::Message;
+use multi_paxos::testkit::{arg_or, report, Cluster, Role};
+use uuid::Uuid;
+
+/// The value requested by the client from the old leader.
+const OLD_VALUE: usize = 7;
+
+/// Another value, which the new leader drives into the instance of OLD_VALUE.
+const NEW_VALUE: usize = 8;
+
+/// Returns a cluster, created with seed, of 2 proposers, whose leader, the first one, proposed
+/// OLD_VALUE in the instance 1, but whose Proposal only reached the first acceptor, before the
+/// leader died. Also returns the uuid of the leader.
+fn one_vote(seed: u64) -> (Cluster<usize>, Option<Uuid>) {
+    let mut cluster = Cluster::<usize>::with_seed(seed, 1, 2, 3, 1).with_leader(0);
+    cluster.run_until_quiet();
+
+    cluster.client(0).request(OLD_VALUE);
+
+    let mut proposal = None;
+
+    while {
+        cluster.drop_pending(|receiver, m| match m {
+            Message::Phase2a(_) if receiver == Role::Acceptors => {
+                proposal = Some(m.clone());
+                true
+            }
+            _ => false,
+        });
+        cluster.step()
+    } {}
+
+    let leader_uuid = proposal.as_ref().map(Message::sender_uuid);
+
+    if let Some(proposal) = proposal {
+        cluster.acceptor(0).handle(proposal);
+    }
+
+    // The leader dies before it receives the Acceptance of the acceptor.
+    cluster.drop_pending(|_, _| true);
+
+    (cluster, leader_uuid)
+}
+
+/// Delivers the messages of cluster until there are none, in random order if random is true, but
+/// drops those sent by the failed proposer with the given uuid.
+fn step_without(cluster: &mut Cluster<usize>, failed_uuid: Option<Uuid>, random: bool) {
+    while {
+        cluster.drop_pending(|_, m| Some(m.sender_uuid()) == failed_uuid);
+
+        if random {
+            cluster.step_random(0.0).is_some()
+        } else {
+            cluster.step()
+        }
+    } {}
+}
+
+/// The first test: the new leader drives the value of the failed leader again.
+fn same_value() -> bool {
+    let (mut cluster, leader_uuid) = one_vote(0);
+
+    cluster.proposer(1).repropose(1, OLD_VALUE);
+    step_without(&mut cluster, leader_uuid, false);
+
+    leader_uuid.is_some() && cluster.delivered_values(0) == vec![OLD_VALUE]
+}
+
+/// The second test: the new leader drives another value into the instance of the failed leader.
+fn other_value() -> bool {
+    let (mut cluster, leader_uuid) = one_vote(0);
+
+    cluster.proposer(1).repropose(1, NEW_VALUE);
+    step_without(&mut cluster, leader_uuid, false);
+
+    leader_uuid.is_some() && cluster.delivered_values(0) == vec![OLD_VALUE, NEW_VALUE]
+}
+
+/// The third test: the second test, in the random schedule of seed.
+fn random_schedule(seed: u64) -> bool {
+    let (mut cluster, leader_uuid) = one_vote(seed);
+
+    cluster.proposer(1).repropose(1, NEW_VALUE);
+    step_without(&mut cluster, leader_uuid, true);
+
+    let log = cluster.delivered_values(0);
+    let decided_once = log.iter().filter(|&&v| v == NEW_VALUE).count() == 1;
+
+    let consistent = match log.first() {
+        Some(&OLD_VALUE) => log.len() == 2,
+        Some(&NEW_VALUE) => log.len() == 1,
+        _ => false,
+    };
+
+    leader_uuid.is_some()
+        && decided_once
+        && consistent
+        && cluster.proposer(1).export_log().get(&1) == log.first()
+}
+
+fn main() {
+    let num_of_runs: u64 = arg_or("number of runs", 100);
+
+    let ok1 = report(
+        "Test 1 - A new leader which drives the value of the failed leader again has it decided once",
+        same_value(),
+    );
+
+    let ok2 = report(
+        "Test 2 - A new leader which drives another value adopts the value voted for by a single acceptor",
+        other_value(),
+    );
+
+    let ok3 = report(
+        "Test 3 - In random schedules, a single value is decided in the instance, and the value of the new leader exactly once",
+        (1..=num_of_runs).all(random_schedule),
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,72 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! busy proposer (see Proposer::with_max_in_flight) does not starve a client whose request arrives
+//! after the many requests of another client, if it orders its queued requests fairly (see
+//! OrderingPolicy).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example fairness -- [num_of_values]
+//!
+//! 1. In the order of arrival, the request of the second client is only proposed after all the
+//!    queued requests of the first client.
+//! 2. Round-robin among the clients, the request of the second client is proposed right after the
+//!    request of the first client which is being decided.
+//! 3. By sequence number, the request of the second client, which is its first one, is proposed
+//!    before all the queued requests of the first client too.
+//!
+//! In all cases, every value is eventually decided.
+
+extern crate multi_paxos;
+
+use std::process;
+
+use multi_paxos::multi_paxos::OrderingPolicy;
+use multi_paxos::testkit::{arg_or, report, Cluster};
+
+/// The value requested by the second client. The first client requests the values from 1 on.
+const STARVED_VALUE: usize = 0;
+
+/// Makes the first client request num_of_values values, and then the second client request
+/// STARVED_VALUE, to a proposer which decides one instance at a time, with the given policy.
+/// Returns the instance where STARVED_VALUE is decided, if every value is decided.
+fn starved_instance(policy: OrderingPolicy, num_of_values: usize) -> Option<usize> {
+    let mut cluster = Cluster::<usize>::new(2, 1, 3, 1)
+        .with_proposers(|proposer| proposer.with_max_in_flight(1).with_ordering_policy(policy));
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.client(0).request(v));
+    cluster.request(1, STARVED_VALUE);
+
+    let delivered = cluster.learner(0).total_order_prefix().to_vec();
+
+    if delivered.len() != num_of_values + 1 {
+        return None;
+    }
+
+    delivered
+        .iter()
+        .find(|&&(_, v)| v == STARVED_VALUE)
+        .map(|&(instance, _)| instance)
+}
+
+fn main() {
+    let num_of_values = arg_or("number of values", 100);
+
+    let ok1 = report(
+        "Test 1 - In the order of arrival, the other client waits for all the requests of the first",
+        starved_instance(OrderingPolicy::Arrival, num_of_values) == Some(num_of_values + 1),
+    );
+
+    let ok2 = report(
+        "Test 2 - Round-robin among the clients, the other client is not starved",
+        starved_instance(OrderingPolicy::ByClient, num_of_values) == Some(2),
+    );
+
+    let ok3 = report(
+        "Test 3 - By sequence number, the first request of the other client is not starved",
+        starved_instance(OrderingPolicy::BySequence, num_of_values) == Some(2),
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
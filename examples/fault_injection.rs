@@ -0,0 +1,108 @@
+//! A script which injects the failure of an acceptor into a cluster, over the deterministic
+//! in-memory transport of the module testkit, to show that Multi-Paxos tolerates the failure of a
+//! minority of the acceptors, and that a restarted acceptor safely recovers its stored state.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example fault_injection -- [num_of_values]
+//!
+//! 1. One of the 3 acceptors crashes while the values of a stream of client requests are being
+//!    decided, and the other 2 (i.e. a majority) still decide all of them.
+//! 2. The crashed acceptor is restarted, with the state it durably stored before the crash.
+//! 3. Another acceptor crashes, and the restarted one, together with the last one, still decides
+//!    all the values. The learners learn every value exactly once, in the same order.
+//! 4. An acceptor which is restarted again still knows the highest instance which it saw, even if it
+//!    only saw it in a Preparation which it rejected.
+
+extern crate multi_paxos;
+extern crate uuid;
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::process;
+
+use multi_paxos::message::{Ballot, InstanceId, Message, Preparation};
+use multi_paxos::testkit::{arg_or, report, Cluster};
+use uuid::Uuid;
+
+/// Requests the given values and delivers all the messages which follow.
+fn request(cluster: &mut Cluster<usize>, values: &[usize]) {
+    values.iter().for_each(|&v| cluster.client(0).request(v));
+    cluster.run_until_quiet();
+}
+
+fn main() {
+    let num_of_values = arg_or("number of values", 100);
+
+    let storage_dir = env::temp_dir().join(format!("fault_injection{}", process::id()));
+    fs::create_dir_all(&storage_dir).expect("Could not create the storage directory");
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 2)
+        .with_leader(0)
+        .with_acceptor_storage(&storage_dir);
+    cluster.run_until_quiet();
+
+    let values: Vec<usize> = (1..=3 * num_of_values).collect();
+    let (before_restart, after_restart) = values.split_at(2 * num_of_values);
+    let (first, second) = before_restart.split_at(num_of_values);
+
+    // The third acceptor crashes in the middle of the first stream, i.e. once it has participated
+    // in about half of the instances of the first stream.
+    first.iter().for_each(|&v| cluster.client(0).request(v));
+    while cluster.acceptor(2).max_instance_seen() < (first.len() / 2).max(1) && cluster.step() {}
+    cluster.crash_acceptor(2);
+    cluster.run_until_quiet();
+
+    request(&mut cluster, second);
+
+    let learned: HashSet<usize> = cluster.delivered_values(0).into_iter().collect();
+    let ok1 = report(
+        "Test 1 - A majority of the acceptors decides every value while one of them is crashed",
+        learned == before_restart.iter().cloned().collect(),
+    );
+
+    let max_instance_seen = cluster.acceptor(2).max_instance_seen();
+    cluster.restart_acceptor(2);
+
+    let ok2 = report(
+        "Test 2 - A restarted acceptor recovers the state which it stored before the crash",
+        max_instance_seen > 0 && cluster.acceptor(2).max_instance_seen() == max_instance_seen,
+    );
+
+    cluster.crash_acceptor(0);
+    request(&mut cluster, after_restart);
+
+    let (learned1, learned2) = (cluster.delivered_values(0), cluster.delivered_values(1));
+    let distinct: HashSet<usize> = learned1.iter().cloned().collect();
+
+    let ok3 = report(
+        "Test 3 - The restarted acceptor and the last one decide every value exactly once",
+        learned1 == learned2
+            && learned1.len() == values.len()
+            && distinct == values.iter().cloned().collect(),
+    );
+
+    // No round precedes Ballot::ZERO, so the Preparation is rejected, i.e. nothing is promised.
+    let rejected_instance = cluster.acceptor(2).max_instance_seen() + 100;
+    cluster.acceptor(2).handle(Message::Phase1a(Preparation {
+        c_rnd: Ballot::ZERO,
+        sender_uuid: Uuid::nil(),
+        instance: InstanceId(rejected_instance),
+        reply_address: None,
+        group_id: 0,
+        summaries: false,
+    }));
+    cluster.run_until_quiet();
+    cluster.restart_acceptor(2);
+
+    let ok4 = report(
+        "Test 4 - A restarted acceptor recovers the highest instance seen in a rejected Preparation",
+        cluster.acceptor(2).max_instance_seen() == rejected_instance,
+    );
+
+    fs::remove_dir_all(&storage_dir).expect("Could not remove the storage directory");
+
+    if !(ok1 && ok2 && ok3 && ok4) {
+        process::exit(1);
+    }
+}
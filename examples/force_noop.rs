@@ -0,0 +1,201 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that
+//! forcing a no-op in an instance (see Proposer::force_noop) is always safe: it never overrides a
+//! value which is already chosen in the instance, and it never loses the value of a client.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example force_noop -- [num_of_runs]
+//!
+//! 1. If a value is chosen in an instance, but neither the proposers nor the learner know it, a
+//!    proposer which forces the no-op in the instance has the chosen value decided instead.
+//! 2. In num_of_runs random schedules (with lost messages), in which a proposer forces the no-op in
+//!    random instances at random times, each value which a majority of the acceptors voted for in
+//!    a round is the one decided in its instance.
+//! 3. If a proposer forces the no-op in an instance where it proposes the value of a client, the
+//!    no-op is decided in the instance, and the value of the client in a new one.
+//! 4. A proposer without a no-op cannot force it in an instance whose value it does not know, but
+//!    it can in an instance whose value it knows, which is then sent again to the learners.
+
+extern crate multi_paxos;
+extern crate rand;
+
+use std::collections::{HashMap, HashSet};
+use std::process;
+
+use multi_paxos::message::{Ballot, Message};
+use multi_paxos::multi_paxos::ForceNoopError;
+use multi_paxos::testkit::{arg_or, report, Cluster, Role};
+use rand::Rng;
+
+/// The no-op of the proposers, which is never requested by the client.
+const NOOP: usize = 0;
+
+/// The number of values requested by the client in each random schedule.
+const NUM_OF_VALUES: usize = 3;
+
+/// Returns a cluster, created with seed, with 1 client, 2 proposers (the first one of which is the
+/// leader, and both of which have a no-op), 3 acceptors and 1 learner, which records the delivered
+/// messages, and whose CatchUp messages are already delivered.
+fn new_cluster(seed: u64) -> Cluster<usize> {
+    let mut cluster = Cluster::<usize>::with_seed(seed, 1, 2, 3, 1)
+        .with_leader(0)
+        .with_proposers(|p| p.with_noop(NOOP))
+        .with_trace_collector();
+    cluster.run_until_quiet();
+    cluster
+}
+
+/// Returns the values which, according to the delivered Acceptance messages of cluster, a majority
+/// of its 3 acceptors voted for in the same round of an instance, by instance.
+fn chosen_values(cluster: &Cluster<usize>) -> HashMap<usize, HashSet<usize>> {
+    let mut votes: HashMap<(usize, Ballot, usize), HashSet<_>> = HashMap::new();
+
+    for entry in cluster.trace_collector().unwrap().entries() {
+        if let Message::Phase2b(ref acceptance) = entry.message {
+            if let Some(v_val) = acceptance.v_val {
+                votes
+                    .entry((acceptance.instance.0, acceptance.v_rnd, v_val))
+                    .or_default()
+                    .insert(acceptance.sender_uuid);
+            }
+        }
+    }
+
+    let mut chosen: HashMap<usize, HashSet<usize>> = HashMap::new();
+
+    for ((instance, _, v_val), voters) in votes {
+        if voters.len() >= 2 {
+            chosen.entry(instance).or_default().insert(v_val);
+        }
+    }
+
+    chosen
+}
+
+/// The first test: a forced no-op does not override a chosen value, which nobody knows.
+fn chosen_value_kept() -> bool {
+    let mut cluster = new_cluster(0);
+
+    // All the acceptors vote for the value, but neither the proposers nor the learner learn it.
+    cluster.client(0).request(7);
+    while {
+        cluster.drop_pending(|receiver, m| {
+            (receiver == Role::Proposers && matches!(m, Message::Phase2b(_)))
+                || (receiver == Role::Learners && matches!(m, Message::Phase3(_)))
+        });
+        cluster.step()
+    } {}
+
+    let unknown = cluster.learner(0).learned_value(1).is_none()
+        && (0..2).all(|i| cluster.proposer(i).export_log().is_empty());
+
+    let forced = cluster.proposer(1).force_noop(1).is_ok();
+    cluster.run_until_quiet();
+
+    unknown
+        && forced
+        && cluster.proposer(1).export_log().get(&1) == Some(&7)
+        && cluster.learner(0).learned_value(1) == Some(7)
+}
+
+/// The second test: in the random schedule of seed, the forced no-ops never override a value
+/// chosen by a majority of the acceptors.
+fn random_schedule(seed: u64) -> bool {
+    let mut cluster = new_cluster(seed);
+
+    (1..=NUM_OF_VALUES).for_each(|v| cluster.client(0).request(v));
+
+    while cluster.step_random(0.2).is_some() {
+        if cluster.rng().gen_bool(0.05) {
+            let instance = cluster.rng().gen_range(1, NUM_OF_VALUES + 1);
+            let _ = cluster.proposer(1).force_noop(instance);
+        }
+    }
+
+    // In the end, every instance is resolved, without losing any message.
+    for instance in 1..=NUM_OF_VALUES {
+        let _ = cluster.proposer(1).force_noop(instance);
+        cluster.run_until_quiet();
+    }
+
+    let chosen = chosen_values(&cluster);
+
+    (1..=NUM_OF_VALUES).all(|instance| {
+        match (
+            cluster.learner(0).learned_value(instance),
+            chosen.get(&instance),
+        ) {
+            (Some(v), Some(values)) => values.len() == 1 && values.contains(&v),
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    })
+}
+
+/// The third test: a forced no-op does not make the value of a client, which the proposer was
+/// proposing in the instance, get lost.
+fn displaced_value_kept() -> bool {
+    let mut cluster = new_cluster(0);
+
+    // The proposer proposes the value of the client, but no acceptor receives the Proposal.
+    cluster.client(0).request(7);
+    while {
+        cluster.drop_pending(|receiver, m| {
+            receiver == Role::Acceptors && matches!(m, Message::Phase2a(_))
+        });
+        cluster.step()
+    } {}
+
+    let forced = cluster.proposer(0).force_noop(1).is_ok();
+    cluster.run_until_quiet();
+
+    let delivered: Vec<usize> = cluster
+        .learner(0)
+        .total_order_prefix()
+        .iter()
+        .map(|&(_, v)| v)
+        .collect();
+
+    forced && delivered == vec![NOOP, 7]
+}
+
+/// The fourth test: a proposer without a no-op only forces it in the instances it knows.
+fn without_noop() -> bool {
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1);
+    cluster.run_until_quiet();
+
+    let refused = cluster.proposer(0).force_noop(1) == Err(ForceNoopError::NoNoop);
+
+    cluster.request(0, 7);
+
+    refused
+        && cluster.proposer(0).force_noop(1).is_ok()
+        && cluster.learner(0).learned_value(1) == Some(7)
+}
+
+fn main() {
+    let num_of_runs: u64 = arg_or("number of runs", 100);
+
+    let ok1 = report(
+        "Test 1 - A forced no-op does not override a chosen value, even if nobody knows it",
+        chosen_value_kept(),
+    );
+
+    let ok2 = report(
+        "Test 2 - In random schedules, the forced no-ops never override a chosen value",
+        (1..=num_of_runs).all(random_schedule),
+    );
+
+    let ok3 = report(
+        "Test 3 - A forced no-op makes the value of a client, proposed in the instance, move to a new one",
+        displaced_value_kept(),
+    );
+
+    let ok4 = report(
+        "Test 4 - A proposer without a no-op only forces it in the instances whose value it knows",
+        without_noop(),
+    );
+
+    if !(ok1 && ok2 && ok3 && ok4) {
+        process::exit(1);
+    }
+}
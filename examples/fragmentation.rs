@@ -0,0 +1,116 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that
+//! the values which do not fit in a single datagram are sent in fragments, which are reassembled by
+//! the receivers, and that a message whose fragment is lost is dropped as a whole (see
+//! Proposer::with_max_datagram_size).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example fragmentation -- [num_of_values]
+//!
+//! 1. Each of the num_of_values values, of 32 KiB, i.e. larger than a datagram, is sent in several
+//!    datagrams, and decided.
+//! 2. With a maximum datagram size of 1472 bytes (i.e. the payload of an Ethernet frame), the
+//!    requests are sent in more fragments, and the values are still decided.
+//! 3. A request whose first fragment is lost is dropped as a whole, and its value is decided once
+//!    it is sent again.
+
+extern crate multi_paxos;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+
+use std::fmt;
+use std::process;
+
+use multi_paxos::testkit::{arg_or, report, Choice, Cluster};
+
+/// The maximum datagram size of the nodes in the test 2.
+const MAX_DATAGRAM_SIZE: usize = 1472;
+
+/// A value of 32 KiB, generated from a seed.
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq)]
+struct Blob([[[u8; 32]; 32]; 32]);
+
+impl Blob {
+    fn new(seed: usize) -> Self {
+        let mut blob = [[[0; 32]; 32]; 32];
+
+        for (i, plane) in blob.iter_mut().enumerate() {
+            for (j, row) in plane.iter_mut().enumerate() {
+                for (k, byte) in row.iter_mut().enumerate() {
+                    *byte = (seed + i * 1024 + j * 32 + k) as u8;
+                }
+            }
+        }
+
+        Blob(blob)
+    }
+}
+
+// The learners print the values which they deliver, so only their first bytes are printed.
+impl fmt::Debug for Blob {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Blob({:?}...)", &self.0[0][0][..4])
+    }
+}
+
+/// Makes the client of cluster send the requests of the given values, one at a time, and delivers
+/// the messages until there are none. Returns the smallest number of datagrams of a request.
+fn request_counting_datagrams(cluster: &mut Cluster<Blob>, values: &[Blob]) -> usize {
+    let mut num_of_datagrams = usize::MAX;
+
+    for &value in values {
+        cluster.client(0).request(value);
+        num_of_datagrams = num_of_datagrams.min(cluster.num_of_pending());
+        cluster.run_until_quiet();
+    }
+
+    num_of_datagrams
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let values: Vec<Blob> = (1..=num_of_values).map(Blob::new).collect();
+
+    let mut cluster = Cluster::<Blob>::new(1, 1, 3, 1);
+    cluster.run_until_quiet();
+
+    let num_of_datagrams = request_counting_datagrams(&mut cluster, &values);
+
+    let ok1 = report(
+        "Test 1 - The values larger than a datagram are sent in fragments, and decided",
+        num_of_datagrams > 1 && cluster.delivered_values(0) == values,
+    );
+
+    let mut cluster = Cluster::<Blob>::new(1, 1, 3, 1)
+        .with_clients(|c| c.with_max_datagram_size(MAX_DATAGRAM_SIZE))
+        .with_proposers(|p| p.with_max_datagram_size(MAX_DATAGRAM_SIZE))
+        .with_learners(|l| l.with_max_datagram_size(MAX_DATAGRAM_SIZE));
+    cluster.run_until_quiet();
+
+    let num_of_small_datagrams = request_counting_datagrams(&mut cluster, &values);
+
+    let ok2 = report(
+        "Test 2 - With a smaller maximum datagram size, the requests are sent in more fragments, and the values are decided",
+        num_of_small_datagrams > num_of_datagrams && cluster.delivered_values(0) == values,
+    );
+
+    let mut cluster = Cluster::<Blob>::new(1, 1, 3, 1);
+    cluster.run_until_quiet();
+
+    cluster.client(0).request(values[0]);
+    cluster.step_with(Choice::Drop(0));
+    cluster.run_until_quiet();
+
+    let lost = cluster.delivered_values(0).is_empty();
+    cluster.request(0, values[0]);
+
+    let ok3 = report(
+        "Test 3 - A request whose fragment is lost is dropped as a whole, and decided once it is sent again",
+        lost && cluster.delivered_values(0) == values[..1],
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,88 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, the
+//! frontiers of the log which a proposer exposes: the next instance which it would allocate, and
+//! the last instance of the prefix which it knows to be decided (see Proposer::next_instance and
+//! Proposer::committed_watermark).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example frontiers -- [num_of_values]
+//!
+//! 1. After num_of_values values are decided, the next instance follows them, and the whole log is
+//!    committed.
+//! 2. While the first instance is not known to be decided, the committed watermark stays behind,
+//!    although the next instance follows the log, until the instance is completed.
+//! 3. Two proposers which share an atomic counter agree on the next instance, which is not
+//!    allocated yet.
+
+extern crate multi_paxos;
+
+use std::process;
+
+use multi_paxos::message::Message;
+use multi_paxos::multi_paxos::InstanceAllocator;
+use multi_paxos::testkit::{arg_or, report, Cluster, Role};
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1);
+    cluster.run_until_quiet();
+
+    let empty =
+        cluster.proposer(0).next_instance() == 1 && cluster.proposer(0).committed_watermark() == 0;
+
+    (1..=num_of_values).for_each(|v| cluster.request(0, v));
+
+    let ok1 = report(
+        "Test 1 - After the values are decided, the next instance follows them, and the log is committed",
+        empty
+            && cluster.proposer(0).next_instance() == num_of_values + 1
+            && cluster.proposer(0).committed_watermark() == num_of_values,
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1);
+    cluster.run_until_quiet();
+
+    // The votes of the first instance are lost, so the proposer does not know it is decided.
+    cluster.client(0).request(1);
+    while {
+        cluster.drop_pending(|receiver, m| match m {
+            Message::Phase2b(acceptance) => {
+                receiver == Role::Proposers && acceptance.instance.0 == 1
+            }
+            _ => false,
+        });
+        cluster.step()
+    } {}
+
+    (2..=num_of_values).for_each(|v| cluster.request(0, v));
+
+    let behind = cluster.proposer(0).next_instance() == num_of_values + 1
+        && cluster.proposer(0).committed_watermark() == 0;
+
+    cluster.proposer(0).complete_instance(1);
+    cluster.run_until_quiet();
+
+    let ok2 = report(
+        "Test 2 - The committed watermark stays behind a gap, until it is filled",
+        behind && cluster.proposer(0).committed_watermark() == num_of_values,
+    );
+
+    let allocator = InstanceAllocator::local_atomic();
+    let mut cluster = Cluster::<usize>::new(1, 2, 3, 1)
+        .with_leader(0)
+        .with_proposers(|p| p.with_instance_allocator(allocator.clone()));
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.request(0, v));
+
+    let ok3 = report(
+        "Test 3 - Two proposers which share a counter agree on the next instance",
+        cluster.proposer(0).next_instance() == num_of_values + 1
+            && cluster.proposer(1).next_instance() == num_of_values + 1
+            && cluster.proposer(1).committed_watermark() == num_of_values,
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,115 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! proposer drops the Acceptance messages of the instances which it does not know, and which are
+//! implausibly far beyond the highest instance it knows, unless its policy says otherwise (see
+//! Proposer::with_future_instance_policy).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example future_instance -- [num_of_values]
+//!
+//! 1. After num_of_values values are decided, the forged votes of a majority of the acceptors, in
+//!    an instance far ahead, are dropped by default, and the next value is decided as usual.
+//! 2. With FutureInstancePolicy::CreateOnDemand, the same votes are counted, as for any other
+//!    instance, so the proposer learns the forged value.
+//! 3. With FutureInstancePolicy::TriggerCatchUp, a proposer which restarted, and which does not
+//!    know the num_of_values decided instances, drops the votes of the next instance, but catches
+//!    up with the other proposer.
+
+extern crate multi_paxos;
+extern crate uuid;
+
+use std::process;
+
+use multi_paxos::message::{Acceptance, Ballot, InstanceId, Message};
+use multi_paxos::multi_paxos::FutureInstancePolicy;
+use multi_paxos::testkit::{arg_or, report, Cluster};
+use uuid::Uuid;
+
+/// How far beyond the log the forged votes of the tests 1 and 2 are.
+const DISTANCE: usize = 1 << 20;
+
+/// The value of the forged votes.
+const FORGED_VALUE: usize = usize::MAX;
+
+/// Makes the first proposer of cluster handle the votes for FORGED_VALUE of 3 acceptors, i.e. a
+/// majority, in instance, which no proposer started.
+fn forge_votes(cluster: &mut Cluster<usize>, instance: usize) {
+    let round = Ballot {
+        epoch: 1,
+        proposer_id: 0,
+    };
+
+    for _ in 0..3 {
+        cluster.proposer(0).handle(Message::Phase2b(Acceptance {
+            v_rnd: round,
+            v_val: Some(FORGED_VALUE),
+            v_request: None,
+            sender_uuid: Uuid::new_v4(),
+            receiver_uuid: Uuid::new_v4(),
+            instance: InstanceId(instance),
+            group_id: 0,
+        }));
+    }
+
+    cluster.run_until_quiet();
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let forged_instance = num_of_values + DISTANCE;
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1);
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.request(0, v));
+    forge_votes(&mut cluster, forged_instance);
+    cluster.request(0, num_of_values + 1);
+
+    let ok1 = report(
+        "Test 1 - The votes of an instance far ahead are dropped by default",
+        cluster.proposer(0).num_of_future_messages_dropped() == 3
+            && !cluster
+                .proposer(0)
+                .export_log()
+                .contains_key(&forged_instance)
+            && cluster.learner(0).learned_value(num_of_values + 1) == Some(num_of_values + 1),
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1)
+        .with_proposers(|p| p.with_future_instance_policy(FutureInstancePolicy::CreateOnDemand));
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.request(0, v));
+    forge_votes(&mut cluster, forged_instance);
+
+    let ok2 = report(
+        "Test 2 - With CreateOnDemand, the votes of an instance far ahead are counted",
+        cluster.proposer(0).num_of_future_messages_dropped() == 0
+            && cluster.proposer(0).export_log().get(&forged_instance) == Some(&FORGED_VALUE),
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 2, 3, 1).with_leader(0);
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.request(0, v));
+
+    // The restarted proposer knows no instance, and its CatchUp message is lost.
+    cluster.restart_proposer(1, |p| {
+        p.with_future_instance_policy(FutureInstancePolicy::TriggerCatchUp(0))
+    });
+    cluster.drop_pending(|_, _| true);
+
+    cluster.request(0, num_of_values + 1);
+
+    let expected: Vec<usize> = (1..=num_of_values + 1).collect();
+    let log: Vec<usize> = cluster.proposer(1).export_log().into_values().collect();
+
+    let ok3 = report(
+        "Test 3 - With TriggerCatchUp, a proposer which is behind drops the votes of the next instance, but catches up",
+        cluster.proposer(1).num_of_future_messages_dropped() > 0 && log == expected,
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,125 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! learner with a gap timeout (see Learner::with_gap_timeout) recovers from a withheld instance,
+//! which blocks the delivery of the later ones.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example gap_timeout -- [num_of_values]
+//!
+//! 1. If the Learning messages of a decided instance are withheld from the learner, it learns the
+//!    value from the proposers once the gap timeout expires, and delivers every value, without
+//!    asking for a no-op.
+//! 2. If an instance is never decided (i.e. its Proposal messages are withheld from the
+//!    acceptors), the learner does not ask for a no-op as soon as a proposer reports that it does
+//!    not know the value, but only if the instance is still stuck a gap timeout later. The
+//!    proposers then decide the no-op in it, and the learner delivers every value.
+
+extern crate multi_paxos;
+
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use multi_paxos::message::{InstanceId, Message};
+use multi_paxos::testkit::{arg_or, report, Cluster, Role};
+
+/// The gap timeout of the learner.
+const GAP_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// The no-op of the proposers, which is never requested by the client.
+const NOOP: usize = 0;
+
+/// Returns a cluster with 1 client, 2 proposers (the first one of which is the leader, and both of
+/// which have a no-op), 3 acceptors and 1 learner with a gap timeout, which records the delivered
+/// messages.
+fn new_cluster() -> Cluster<usize> {
+    let mut cluster = Cluster::<usize>::new(1, 2, 3, 1)
+        .with_leader(0)
+        .with_proposers(|p| p.with_noop(NOOP))
+        .with_learners(|l| l.with_gap_timeout(GAP_TIMEOUT))
+        .with_trace_collector();
+    cluster.run_until_quiet();
+    cluster
+}
+
+/// Requests the values from 1 to num_of_values, and delivers all the messages which follow, except
+/// the ones for which withheld returns true, which are dropped.
+fn request_withholding<P>(cluster: &mut Cluster<usize>, num_of_values: usize, withheld: P)
+where
+    P: Fn(Role, &Message<usize>) -> bool,
+{
+    (1..=num_of_values).for_each(|v| cluster.client(0).request(v));
+
+    loop {
+        cluster.drop_pending(&withheld);
+
+        if !cluster.step() {
+            break;
+        }
+    }
+}
+
+/// Waits for the gap timeout of the learner to expire, makes it check its gap, and delivers all
+/// the messages which follow.
+fn expire_gap_timeout(cluster: &mut Cluster<usize>) {
+    thread::sleep(2 * GAP_TIMEOUT);
+    cluster.learner(0).check_gap();
+    cluster.run_until_quiet();
+}
+
+/// Returns the number of NoopRequest messages delivered in cluster.
+fn num_of_noop_requests(cluster: &Cluster<usize>) -> usize {
+    cluster.count_delivered(|m| matches!(m, Message::NoopRequest(_)))
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    // The withheld instance is in the middle of the log, so that it blocks the later ones.
+    let num_of_values = num_of_values.max(2);
+    let withheld = InstanceId(num_of_values / 2);
+
+    let mut cluster = new_cluster();
+    request_withholding(&mut cluster, num_of_values, |role, m| match m {
+        Message::Phase3(learning) => role == Role::Learners && learning.instance == withheld,
+        _ => false,
+    });
+
+    let blocked = cluster.learner(0).total_order_prefix().len() == withheld.0 - 1;
+    expire_gap_timeout(&mut cluster);
+
+    let ok1 = report(
+        "Test 1 - A learner learns a withheld value from the proposers once its gap timeout expires",
+        blocked
+            && cluster.learner(0).total_order_prefix().len() == num_of_values
+            && cluster.learner(0).learned_value(withheld.0) == Some(withheld.0)
+            && num_of_noop_requests(&cluster) == 0,
+    );
+
+    let mut cluster = new_cluster();
+    request_withholding(&mut cluster, num_of_values, |role, m| match m {
+        Message::Phase2a(proposal) => role == Role::Acceptors && proposal.instance == withheld,
+        _ => false,
+    });
+
+    let blocked = cluster.learner(0).total_order_prefix().len() == withheld.0 - 1;
+
+    // The proposers report that they do not know the value, which is not enough to ask for a no-op.
+    expire_gap_timeout(&mut cluster);
+    let no_early_noop = num_of_noop_requests(&cluster) == 0
+        && cluster.learner(0).total_order_prefix().len() == withheld.0 - 1;
+
+    expire_gap_timeout(&mut cluster);
+
+    let ok2 = report(
+        "Test 2 - A learner asks for a no-op in an instance which is still stuck a gap timeout after it caught up",
+        blocked
+            && no_early_noop
+            && num_of_noop_requests(&cluster) > 0
+            && cluster.learner(0).learned_value(withheld.0) == Some(NOOP)
+            && cluster.learner(0).total_order_prefix().len() >= num_of_values,
+    );
+
+    if !(ok1 && ok2) {
+        process::exit(1);
+    }
+}
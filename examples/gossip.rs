@@ -0,0 +1,99 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! proposer which learns the decided values from the gossip of another proposer (see
+//! Proposer::gossip) treats them like the values of the Learning messages.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example gossip
+//!
+//! 1. If a proposer was proposing the value of its client in an instance where another value was
+//!    decided, which it only learns from the gossip of the other proposer, its value is proposed
+//!    again, and decided, in a new instance.
+//! 2. A gossiped value which differs from the one that the proposer knows is dropped, without
+//!    making the proposer panic.
+
+extern crate multi_paxos;
+extern crate uuid;
+
+use std::collections::HashMap;
+use std::process;
+
+use multi_paxos::message::{GossipValues, Message, Request};
+use multi_paxos::testkit::{report, Cluster, Role};
+use uuid::Uuid;
+
+/// The value requested by the client from the leader.
+const LEADER_VALUE: usize = 7;
+
+/// The value requested by another client from the second proposer.
+const OTHER_VALUE: usize = 8;
+
+fn main() {
+    let mut cluster = Cluster::<usize>::new(1, 2, 3, 1).with_leader(0);
+    cluster.run_until_quiet();
+
+    // The second proposer starts the instance 1 for the value of another client, but its
+    // Preparation is lost.
+    cluster.proposer(1).handle(Message::Phase0a(Request {
+        value: OTHER_VALUE,
+        sender_uuid: Uuid::new_v4(),
+        request_seq: 1,
+        priority: None,
+        group_id: 0,
+        prefix_from: None,
+    }));
+
+    let mut proposer_uuid = None;
+
+    cluster.drop_pending(|_, m| {
+        proposer_uuid = Some(m.sender_uuid());
+        true
+    });
+
+    // The leader decides the value of its client in the instance 1, but the second proposer misses
+    // its Learning message.
+    cluster.client(0).request(LEADER_VALUE);
+    while {
+        cluster.drop_pending(|receiver, m| {
+            receiver == Role::Proposers && matches!(m, Message::Phase3(_))
+        });
+        cluster.step()
+    } {}
+
+    let missed = cluster.proposer(1).export_log().is_empty();
+
+    cluster.proposer(1).gossip();
+    cluster.run_until_quiet();
+
+    let ok1 = report(
+        "Test 1 - The value of a proposer which lost its instance, as it learns from the gossip, is proposed again",
+        missed
+            && cluster.proposer(1).export_log().get(&1) == Some(&LEADER_VALUE)
+            && cluster.delivered_values(0) == vec![LEADER_VALUE, OTHER_VALUE],
+    );
+
+    let mut learned_values = HashMap::new();
+    learned_values.insert(1, LEADER_VALUE + OTHER_VALUE);
+
+    if let Some(receiver_uuid) = proposer_uuid {
+        cluster
+            .proposer(1)
+            .handle(Message::GossipValues(GossipValues {
+                learned_values,
+                sender_uuid: Uuid::new_v4(),
+                receiver_uuid,
+                group_id: 0,
+            }));
+    }
+    cluster.run_until_quiet();
+
+    let ok2 = report(
+        "Test 2 - A gossiped value which differs from the known one is dropped",
+        proposer_uuid.is_some()
+            && cluster.proposer(1).export_log().get(&1) == Some(&LEADER_VALUE)
+            && cluster.delivered_values(0) == vec![LEADER_VALUE, OTHER_VALUE],
+    );
+
+    if !(ok1 && ok2) {
+        process::exit(1);
+    }
+}
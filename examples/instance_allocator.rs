@@ -0,0 +1,144 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, how two
+//! proposers which start proposing values concurrently allocate their instances (see
+//! InstanceAllocator).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example instance_allocator -- [num_of_values]
+//!
+//! 1. With the default allocator, the two proposers allocate the same instances, so some values
+//!    are proposed again, in new instances, but each of the 2 * num_of_values values is decided
+//!    once.
+//! 2. With a shared atomic counter, the two proposers never allocate the same instance: each
+//!    instance is prepared once, and each value is decided once.
+//! 3. With a shared atomic counter, the two proposers skip the instances taken by a third
+//!    proposer, which does not share the counter.
+
+extern crate multi_paxos;
+extern crate uuid;
+
+use std::cell::Cell;
+use std::process;
+
+use multi_paxos::message::{Message, Request};
+use multi_paxos::multi_paxos::InstanceAllocator;
+use multi_paxos::testkit::{arg_or, report, Cluster, Role};
+use uuid::Uuid;
+
+/// Returns the request of value, sent by a client with the given uuid.
+fn request(value: usize, client_uuid: Uuid) -> Message<usize> {
+    Message::Phase0a(Request {
+        value,
+        sender_uuid: client_uuid,
+        request_seq: value,
+        priority: None,
+        group_id: 0,
+        prefix_from: None,
+    })
+}
+
+/// Makes the i-th proposer of cluster handle the requests of the given values, without delivering
+/// their messages yet, so that it starts all of them concurrently with the other proposers.
+fn propose(cluster: &mut Cluster<usize>, i: usize, values: Vec<usize>) {
+    let client_uuid = Uuid::new_v4();
+
+    values
+        .into_iter()
+        .for_each(|v| cluster.proposer(i).handle(request(v, client_uuid)));
+}
+
+/// Returns the number of the Preparation messages delivered to the acceptors of cluster.
+fn num_of_preparations(cluster: &Cluster<usize>) -> usize {
+    cluster
+        .trace_collector()
+        .unwrap()
+        .count(|e| e.receiver == Role::Acceptors && matches!(e.message, Message::Phase1a(_)))
+}
+
+/// Returns whether the learner of cluster delivered each value from 1 to num_of_values once.
+fn is_decided_once(cluster: &mut Cluster<usize>, num_of_values: usize) -> bool {
+    let mut values: Vec<usize> = cluster
+        .learner(0)
+        .total_order_prefix()
+        .iter()
+        .map(|&(_, v)| v)
+        .collect();
+    values.sort_unstable();
+
+    values == (1..=num_of_values).collect::<Vec<usize>>()
+}
+
+/// Makes the first two proposers of cluster start proposing num_of_values values each,
+/// concurrently, and delivers the messages until there are none.
+fn propose_concurrently(cluster: &mut Cluster<usize>, num_of_values: usize) {
+    propose(cluster, 0, (1..=num_of_values).collect());
+    propose(
+        cluster,
+        1,
+        (num_of_values + 1..=2 * num_of_values).collect(),
+    );
+
+    cluster.run_until_quiet();
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let mut cluster = Cluster::<usize>::new(1, 2, 3, 1).with_trace_collector();
+    cluster.run_until_quiet();
+
+    propose_concurrently(&mut cluster, num_of_values);
+
+    let ok1 = report(
+        "Test 1 - With the default allocator, the proposers compete for the same instances, but each value is decided once",
+        num_of_preparations(&cluster) > 2 * num_of_values
+            && is_decided_once(&mut cluster, 2 * num_of_values),
+    );
+
+    let allocator = InstanceAllocator::local_atomic();
+
+    let mut cluster = Cluster::<usize>::new(1, 2, 3, 1)
+        .with_proposers(|p| p.with_instance_allocator(allocator.clone()))
+        .with_trace_collector();
+    cluster.run_until_quiet();
+
+    propose_concurrently(&mut cluster, num_of_values);
+
+    let ok2 = report(
+        "Test 2 - With a shared counter, each instance is prepared once, and each value is decided once",
+        num_of_preparations(&cluster) == 2 * num_of_values
+            && is_decided_once(&mut cluster, 2 * num_of_values),
+    );
+
+    // Only the first two proposers share the counter.
+    let allocator = InstanceAllocator::local_atomic();
+    let index = Cell::new(0);
+
+    let mut cluster = Cluster::<usize>::new(1, 3, 3, 1)
+        .with_proposers(|p| {
+            index.set(index.get() + 1);
+
+            if index.get() > 2 {
+                return p;
+            }
+
+            p.with_instance_allocator(allocator.clone())
+        })
+        .with_trace_collector();
+    cluster.run_until_quiet();
+
+    let others = 2 * num_of_values + 1..=3 * num_of_values;
+    propose(&mut cluster, 2, others.collect());
+    cluster.run_until_quiet();
+
+    propose_concurrently(&mut cluster, num_of_values);
+
+    let ok3 = report(
+        "Test 3 - With a shared counter, the instances taken by a proposer which does not share it are skipped",
+        num_of_preparations(&cluster) == 3 * num_of_values
+            && is_decided_once(&mut cluster, 3 * num_of_values),
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,181 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that
+//! the value which a proposer proposes in a round is picked once, from the Promise messages of the
+//! first majority of the acceptors, and that a Promise which arrives later in the round, even with
+//! a higher v_rnd, does not make the proposer propose another value in the same round.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example late_promise -- [num_of_values]
+//!
+//! In each test, one of the 3 acceptors voted, in an earlier round, for a value which was not
+//! decided, in each instance.
+//! 1. If the Promise messages of that acceptor arrive after those of the others, which voted for
+//!    nothing, the values of the client are decided in the num_of_values instances, and a single
+//!    value is proposed in each round.
+//! 2. If they arrive first, the values voted for are adopted and decided, and the values of the
+//!    client are moved to new instances.
+//! 3. In num_of_values random schedules, the value decided in the instance is the one voted for
+//!    exactly when the Promise of that acceptor is among the first majority.
+
+extern crate multi_paxos;
+extern crate uuid;
+
+use std::collections::{HashMap, HashSet};
+use std::process;
+
+use multi_paxos::message::{Ballot, InstanceId, Message, Proposal};
+use multi_paxos::testkit::{arg_or, report, Cluster, Role};
+use uuid::Uuid;
+
+/// The round of a former proposer, in which the third acceptor voted. It precedes the rounds of
+/// the proposers of the cluster, whose epochs start from 1.
+const EARLIER_ROUND: Ballot = Ballot {
+    epoch: 0,
+    proposer_id: 1,
+};
+
+/// The value voted for in the instance i, in EARLIER_ROUND, is EARLIER_VALUES + i.
+const EARLIER_VALUES: usize = 1_000_000;
+
+/// Returns a cluster, created with seed, in which the third acceptor voted, in EARLIER_ROUND, for
+/// the value EARLIER_VALUES + i in each instance i from 1 to num_of_instances. The votes were not
+/// counted by anyone.
+fn earlier_votes(seed: u64, num_of_instances: usize) -> Cluster<usize> {
+    let mut cluster = Cluster::<usize>::with_seed(seed, 1, 1, 3, 1).with_trace_collector();
+    cluster.run_until_quiet();
+
+    let former_proposer_uuid = Uuid::new_v4();
+
+    for i in 1..=num_of_instances {
+        cluster.acceptor(2).handle(Message::Phase2a(Proposal {
+            c_rnd: EARLIER_ROUND,
+            c_val: Some(EARLIER_VALUES + i),
+            c_request: None,
+            sender_uuid: former_proposer_uuid,
+            instance: InstanceId(i),
+            group_id: 0,
+        }));
+    }
+
+    cluster.drop_pending(|_, _| true);
+
+    cluster
+}
+
+/// Makes the client of cluster send the requests of the values from 1 to num_of_values, at once,
+/// and delivers the messages until there are none, except the Promise messages for which held
+/// returns true, which are delivered to the proposer only afterwards.
+fn request<P>(cluster: &mut Cluster<usize>, num_of_values: usize, held: P)
+where
+    P: Fn(Ballot) -> bool,
+{
+    (1..=num_of_values).for_each(|v| cluster.client(0).request(v));
+
+    let mut late_promises = Vec::new();
+
+    while {
+        cluster.drop_pending(|receiver, m| match m {
+            Message::Phase1b(promise) if receiver == Role::Proposers && held(promise.v_rnd) => {
+                late_promises.push(m.clone());
+                true
+            }
+            _ => false,
+        });
+        cluster.step()
+    } {}
+
+    for m in late_promises {
+        cluster.proposer(0).handle(m);
+    }
+
+    cluster.run_until_quiet();
+}
+
+/// Returns whether the proposer of cluster proposed a single value in each of its rounds.
+fn one_value_per_round(cluster: &Cluster<usize>) -> bool {
+    let mut values: HashMap<(usize, Ballot), HashSet<Option<usize>>> = HashMap::new();
+
+    for entry in cluster.trace_collector().unwrap().entries() {
+        if let Message::Phase2a(ref proposal) = entry.message {
+            values
+                .entry((proposal.instance.0, proposal.c_rnd))
+                .or_default()
+                .insert(proposal.c_val);
+        }
+    }
+
+    !values.is_empty() && values.values().all(|v| v.len() == 1)
+}
+
+/// Returns the values delivered by the learner of cluster, together with their instances, in total
+/// order.
+fn delivered(cluster: &mut Cluster<usize>) -> Vec<(usize, usize)> {
+    cluster.learner(0).total_order_prefix().to_vec()
+}
+
+/// Returns whether the Promise of the third acceptor, the only one with a v_rnd, is among the first
+/// 2 (i.e. a majority of 3) Promise messages which the proposer of cluster received in instance 1.
+fn voted_promise_first(cluster: &Cluster<usize>) -> bool {
+    cluster
+        .trace_collector()
+        .unwrap()
+        .filter(|e| {
+            matches!(e.message, Message::Phase1b(ref p) if p.instance == InstanceId(1))
+                && e.receiver == Role::Proposers
+        })
+        .iter()
+        .take(2)
+        .any(|e| matches!(e.message, Message::Phase1b(ref p) if p.v_rnd == EARLIER_ROUND))
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let mut cluster = earlier_votes(0, num_of_values);
+    request(&mut cluster, num_of_values, |v_rnd| v_rnd == EARLIER_ROUND);
+
+    let expected: Vec<(usize, usize)> = (1..=num_of_values).map(|v| (v, v)).collect();
+
+    let ok1 = report(
+        "Test 1 - A Promise with a higher v_rnd which arrives after the majority does not change the proposed value",
+        one_value_per_round(&cluster) && delivered(&mut cluster) == expected,
+    );
+
+    let mut cluster = earlier_votes(0, num_of_values);
+    request(&mut cluster, num_of_values, |v_rnd| v_rnd == Ballot::ZERO);
+
+    let log = delivered(&mut cluster);
+    let adopted = (1..=num_of_values).all(|i| log.get(i - 1) == Some(&(i, EARLIER_VALUES + i)));
+    let mut moved: Vec<usize> = log[num_of_values.min(log.len())..]
+        .iter()
+        .map(|&(_, v)| v)
+        .collect();
+    moved.sort_unstable();
+
+    let ok2 = report(
+        "Test 2 - A Promise with a higher v_rnd among the majority makes the proposer adopt its value",
+        one_value_per_round(&cluster)
+            && adopted
+            && moved == (1..=num_of_values).collect::<Vec<usize>>(),
+    );
+
+    let ok3 = report(
+        "Test 3 - Whatever the order of the Promise messages, the value voted for is adopted only if it is among the majority",
+        (1..=num_of_values as u64).all(|seed| {
+            let mut cluster = earlier_votes(seed, 1);
+            cluster.client(0).request(1);
+            while cluster.step_random(0.0).is_some() {}
+
+            let expected = if voted_promise_first(&cluster) {
+                EARLIER_VALUES + 1
+            } else {
+                1
+            };
+
+            one_value_per_round(&cluster) && cluster.learner(0).learned_value(1) == Some(expected)
+        }),
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,127 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! proposer which joins late (i.e. after some instances have already been decided) does not start
+//! the instances which were already decided.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example late_proposer -- [num_of_values]
+//!
+//! 1. A late proposer, which catches up before it serves the client requests, only starts new
+//!    instances, even if the client requests are delivered to it before the Report of the other
+//!    proposer.
+//! 2. A late proposer which does not wait for the Report starts the instances which were already
+//!    decided (but, thanks to phase 1, it does not override their values).
+//! 3. A late proposer which does not wait for the Report, but which receives the Learning messages
+//!    of the other proposer, adopts their values, and only starts new instances.
+
+extern crate multi_paxos;
+
+use std::collections::HashSet;
+use std::process;
+
+use multi_paxos::message::{InstanceId, Message};
+use multi_paxos::testkit::{arg_or, report, Cluster};
+
+/// Decides num_of_values values, then adds a late proposer, which is made to stop catching up
+/// right away if wait is false, and decides num_of_values more values. Returns whether the late
+/// proposer started an already decided instance, and whether all values were delivered once.
+fn join_late(num_of_values: usize, wait: bool) -> (bool, bool) {
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1).with_trace_collector();
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.client(0).request(v));
+    cluster.run_until_quiet();
+
+    let joined_at = cluster.trace_collector().unwrap().entries().len();
+
+    let late = cluster.add_proposer();
+    if !wait {
+        cluster.proposer(late).finish_catch_up();
+    }
+
+    // These requests are delivered to the late proposer before the Report of the other one.
+    (num_of_values + 1..=2 * num_of_values).for_each(|v| cluster.client(0).request(v));
+    cluster.run_until_quiet();
+
+    let last = InstanceId(num_of_values);
+    let restarted = cluster.trace_collector().unwrap().entries()[joined_at..]
+        .iter()
+        .any(|e| matches!(e.message, Message::Phase1a(ref p) if p.instance <= last));
+
+    let delivered: Vec<usize> = cluster
+        .learner(0)
+        .total_order_prefix()
+        .iter()
+        .map(|&(_, v)| v)
+        .collect();
+    let distinct: HashSet<usize> = delivered.iter().cloned().collect();
+
+    let all_delivered =
+        distinct == (1..=2 * num_of_values).collect() && distinct.len() == delivered.len();
+
+    (restarted, all_delivered)
+}
+
+/// Decides num_of_values values, then adds a late proposer, which stops catching up right away,
+/// but which is handed the Learning messages sent by the other proposer, and decides num_of_values
+/// more values. Returns whether the late proposer adopted the values of those Learning messages,
+/// and whether it started an already decided instance.
+fn learn_late(num_of_values: usize) -> (bool, bool) {
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1).with_trace_collector();
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.client(0).request(v));
+    cluster.run_until_quiet();
+
+    let learnings: Vec<Message<usize>> = cluster
+        .trace_collector()
+        .unwrap()
+        .filter(|e| matches!(e.message, Message::Phase3(_)))
+        .into_iter()
+        .map(|e| e.message.clone())
+        .collect();
+    let joined_at = cluster.trace_collector().unwrap().entries().len();
+
+    let late = cluster.add_proposer();
+    cluster.proposer(late).finish_catch_up();
+    learnings
+        .into_iter()
+        .for_each(|m| cluster.proposer(late).handle(m));
+
+    let adopted = cluster.proposer(late).export_log() == cluster.proposer(0).export_log();
+
+    (num_of_values + 1..=2 * num_of_values).for_each(|v| cluster.client(0).request(v));
+    cluster.run_until_quiet();
+
+    let last = InstanceId(num_of_values);
+    let restarted = cluster.trace_collector().unwrap().entries()[joined_at..]
+        .iter()
+        .any(|e| matches!(e.message, Message::Phase1a(ref p) if p.instance <= last));
+
+    (adopted, restarted)
+}
+
+fn main() {
+    let num_of_values = arg_or("number of values", 100);
+
+    let (restarted, all_delivered) = join_late(num_of_values, true);
+    let ok_1 = report(
+        "Test 1 - A late proposer which catches up first does not start a decided instance",
+        !restarted && all_delivered,
+    );
+
+    let (restarted, all_delivered) = join_late(num_of_values, false);
+    let ok_2 = report(
+        "Test 2 - A late proposer which does not catch up first starts a decided instance",
+        restarted && all_delivered,
+    );
+
+    let (adopted, restarted) = learn_late(num_of_values);
+    let ok_3 = report(
+        "Test 3 - A late proposer which receives the Learning messages adopts the decided values",
+        adopted && !restarted,
+    );
+
+    if !(ok_1 && ok_2 && ok_3) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,131 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that
+//! the proposers send their Learning messages again until enough learners acknowledged them, when
+//! the learners acknowledge their deliveries (see Proposer::with_learn_acks and
+//! Learner::with_learn_acks).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example learn_acks -- [num_of_values]
+//!
+//! 1. By default, a lost Learning message is never sent again, so the learners do not learn its
+//!    value from the proposer.
+//! 2. With acknowledgments, a lost Learning message is sent again, until both learners
+//!    acknowledged it.
+//! 3. With acknowledgments, if the LearnAck messages are lost, the learners acknowledge the
+//!    Learning messages sent again, without delivering any of the num_of_values values twice.
+
+extern crate multi_paxos;
+
+use std::process;
+use std::time::Duration;
+
+use multi_paxos::message::Message;
+use multi_paxos::testkit::{arg_or, report, Cluster, Role};
+
+/// How often the proposers send the unacknowledged Learning messages again. The messages are sent
+/// again explicitly (see Proposer::retransmit_learnings), as the nodes of a cluster do not run.
+const RETRANSMIT_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Returns a cluster with 1 client, 1 proposer, 3 acceptors and 2 learners, which acknowledge their
+/// deliveries if learn_acks.
+fn new_cluster(learn_acks: bool) -> Cluster<usize> {
+    let cluster = Cluster::<usize>::new(1, 1, 3, 2).with_trace_collector();
+
+    if learn_acks {
+        cluster
+            .with_proposers(|p| p.with_learn_acks(2, RETRANSMIT_INTERVAL))
+            .with_learners(|l| l.with_learn_acks())
+    } else {
+        cluster
+    }
+}
+
+/// Delivers the pending messages of cluster, until there are none, dropping the ones for which
+/// lost returns true.
+fn run<P: Fn(Role, &Message<usize>) -> bool>(cluster: &mut Cluster<usize>, lost: P) {
+    while {
+        cluster.drop_pending(&lost);
+        cluster.step()
+    } {}
+}
+
+/// Returns true if m is a Learning message sent to the learners.
+fn is_learning(receiver: Role, m: &Message<usize>) -> bool {
+    receiver == Role::Learners && matches!(m, Message::Phase3(_))
+}
+
+/// Returns the number of the LearnAck messages which have been delivered to the proposers of
+/// cluster.
+fn learn_acks(cluster: &Cluster<usize>) -> usize {
+    cluster
+        .trace_collector()
+        .unwrap()
+        .count(|e| e.receiver == Role::Proposers && matches!(e.message, Message::LearnAck(_)))
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let mut cluster = new_cluster(false);
+    run(&mut cluster, |_, _| false);
+
+    cluster.client(0).request(1);
+    run(&mut cluster, is_learning);
+
+    cluster.proposer(0).retransmit_learnings();
+    run(&mut cluster, |_, _| false);
+
+    let ok1 = report(
+        "Test 1 - By default, a lost Learning message is not sent again",
+        cluster.proposer(0).export_log().get(&1) == Some(&1)
+            && (0..2).all(|i| cluster.learner(i).learned_value(1).is_none())
+            && cluster.proposer(0).unacknowledged_instances().is_empty()
+            && learn_acks(&cluster) == 0,
+    );
+
+    let mut cluster = new_cluster(true);
+    run(&mut cluster, |_, _| false);
+
+    cluster.client(0).request(1);
+    run(&mut cluster, is_learning);
+
+    let unacknowledged = cluster.proposer(0).unacknowledged_instances();
+
+    cluster.proposer(0).retransmit_learnings();
+    run(&mut cluster, |_, _| false);
+
+    let ok2 = report(
+        "Test 2 - With acknowledgments, a lost Learning message is sent again, until it is acknowledged",
+        unacknowledged == vec![1]
+            && (0..2).all(|i| cluster.learner(i).total_order_prefix() == [(1, 1)])
+            && cluster.proposer(0).unacknowledged_instances().is_empty()
+            && learn_acks(&cluster) == 2,
+    );
+
+    let mut cluster = new_cluster(true);
+    run(&mut cluster, |_, _| false);
+
+    (1..=num_of_values).for_each(|v| cluster.client(0).request(v));
+    run(&mut cluster, |receiver, m| {
+        receiver == Role::Proposers && matches!(m, Message::LearnAck(_))
+    });
+
+    let unacknowledged = cluster.proposer(0).unacknowledged_instances();
+
+    cluster.proposer(0).retransmit_learnings();
+    run(&mut cluster, |_, _| false);
+
+    let expected: Vec<(usize, usize)> = cluster.proposer(0).export_log().into_iter().collect();
+
+    let ok3 = report(
+        "Test 3 - With acknowledgments, the Learning messages whose acknowledgments were lost are acknowledged again",
+        unacknowledged == (1..=num_of_values).collect::<Vec<usize>>()
+            && expected.len() == num_of_values
+            && (0..2).all(|i| cluster.learner(i).total_order_prefix() == &expected[..])
+            && cluster.proposer(0).unacknowledged_instances().is_empty()
+            && learn_acks(&cluster) == 2 * num_of_values,
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,371 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! register replicated with Multi-Paxos (see ReplicatedStateMachine) is linearizable: several
+//! clients concurrently write values to it and read it, every read and write being a value decided
+//! in the log, the history of the operations (when each one was invoked, and when, and with which
+//! result, a replica applied it) is recorded, and a linearizability checker (after Wing and Gong,
+//! with the memoization of Lowe) searches for a sequential order of the operations which respects
+//! their real-time order and explains every result.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example linearizability -- [num_of_operations]
+//!
+//! 1. With the messages delivered in the order they are sent, the num_of_operations operations of
+//!    3 clients complete, the history is linearizable, and the 2 replicas reach the same state.
+//! 2. In num_of_operations random schedules, where the messages are delivered out of order and
+//!    some of them are lost, the history of the operations of 3 clients is linearizable.
+//! 3. The checker rejects a history with a stale read, and one with a read of a value which was
+//!    never written.
+
+extern crate multi_paxos;
+extern crate rand;
+
+use std::collections::{HashMap, HashSet};
+use std::process;
+
+use multi_paxos::state_machine::{Apply, ReplicatedStateMachine};
+use multi_paxos::testkit::{arg_or, report, Cluster, Role};
+use rand::Rng;
+
+/// The number of clients which operate on the register concurrently.
+const NUM_OF_CLIENTS: usize = 3;
+
+/// The probability that an idle client invokes its next operation before a step of the cluster.
+const INVOKE_PROBABILITY: f64 = 0.3;
+
+/// The probability that a message is lost, in the random schedules of the test 2.
+const DROP_PROBABILITY: f64 = 0.05;
+
+/// The number of operations of each client, in the random schedules of the test 2.
+const OPERATIONS_PER_CLIENT: usize = 5;
+
+/// The value of the register, before any write. The operations are numbered from 1.
+const INITIAL_VALUE: usize = 0;
+
+/// An operation on the register, as proposed to the cluster: the operation n writes the value n to
+/// the register if it is 2n, and reads the register if it is 2n + 1, so that every operation, and
+/// every written value, is unique.
+fn encode(n: usize, read: bool) -> usize {
+    n << 1 | read as usize
+}
+
+/// The state of a replica of the register: its value, and the result of each operation which it
+/// applied (i.e. the value which the operation wrote, or read).
+#[derive(Default)]
+struct Register {
+    value: usize,
+
+    results: HashMap<usize, usize>,
+}
+
+/// Applies an operation to a replica of the register. An operation which was decided more than
+/// once (e.g. if its request was proposed by two proposers) is only applied the first time.
+fn apply(register: &mut Register, &op: &usize) {
+    let n = op >> 1;
+
+    if register.results.contains_key(&n) {
+        return;
+    }
+
+    if op & 1 == 0 {
+        register.value = n;
+    }
+
+    register.results.insert(n, register.value);
+}
+
+/// An operation of a history: when it was invoked and when it completed, if it did, on a logical
+/// clock, which ticks at each event, and what it did.
+#[derive(Debug, Copy, Clone)]
+struct Operation {
+    invoke: usize,
+
+    response: Option<usize>,
+
+    kind: Kind,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Kind {
+    Write(usize),
+    Read(usize),
+}
+
+/// Runs the given number of operations of each of the NUM_OF_CLIENTS clients of cluster on a
+/// register, which has 2 replicas, delivering the messages with step (e.g. Cluster::step), until
+/// there are none. Each client invokes its operations one after the other: it reads or writes, at random, once its
+/// previous operation completed, i.e. was applied by a replica. A client whose operation never
+/// completes (e.g. because its request was lost) invokes no other one. Returns the history of the
+/// operations, and the replicas.
+fn run<F>(
+    cluster: &mut Cluster<usize>,
+    operations_per_client: usize,
+    mut step: F,
+) -> (Vec<Operation>, Vec<ReplicatedStateMachine<Register, usize>>)
+where
+    F: FnMut(&mut Cluster<usize>) -> bool,
+{
+    let mut replicas: Vec<_> = (0..2)
+        .map(|_| cluster.new_replica(Register::default(), Box::new(apply) as Apply<_, _>))
+        .collect();
+
+    let mut history: Vec<Operation> = Vec::new();
+    let mut clock = 0;
+
+    // The operation of each client which did not complete yet, if any, and the number of
+    // operations which it invoked.
+    let mut outstanding: Vec<Option<usize>> = vec![None; NUM_OF_CLIENTS];
+    let mut invoked = vec![0; outstanding.len()];
+
+    // The number of messages delivered to the learners, which were handed over to the replicas.
+    let mut fed = 0;
+
+    loop {
+        for client in 0..outstanding.len() {
+            let idle = outstanding[client].is_none() && invoked[client] < operations_per_client;
+
+            if idle && cluster.rng().gen_bool(INVOKE_PROBABILITY) {
+                let n = history.len() + 1;
+                let read = cluster.rng().gen_bool(0.5);
+
+                clock += 1;
+                history.push(Operation {
+                    invoke: clock,
+                    response: None,
+                    kind: if read { Kind::Read(0) } else { Kind::Write(n) },
+                });
+
+                cluster.client(client).request(encode(n, read));
+                outstanding[client] = Some(n);
+                invoked[client] += 1;
+            }
+        }
+
+        let stepped = step(cluster);
+
+        let trace = cluster.trace_collector().unwrap().entries();
+
+        for entry in trace[fed..].iter().filter(|e| e.receiver == Role::Learners) {
+            replicas
+                .iter_mut()
+                .for_each(|replica| replica.handle(entry.message.clone()));
+        }
+        fed = trace.len();
+
+        for operation_of_client in outstanding.iter_mut() {
+            let completed = operation_of_client.and_then(|n| {
+                replicas
+                    .iter()
+                    .find_map(|replica| replica.state().results.get(&n))
+                    .map(|&result| (n, result))
+            });
+
+            if let Some((n, result)) = completed {
+                clock += 1;
+
+                let operation = &mut history[n - 1];
+                operation.response = Some(clock);
+
+                if let Kind::Read(ref mut value) = operation.kind {
+                    *value = result;
+                }
+
+                *operation_of_client = None;
+            }
+        }
+
+        let done = outstanding
+            .iter()
+            .zip(&invoked)
+            .all(|(n, &i)| n.is_some() || i == operations_per_client);
+
+        if !stepped && done {
+            break;
+        }
+    }
+
+    (history, replicas)
+}
+
+/// Returns whether history is linearizable, i.e. whether there is a sequential order of its
+/// operations, in which each operation takes effect at once, between its invocation and its
+/// response, where each read returns the value of the last write before it (or INITIAL_VALUE).
+/// The operations which did not complete may take effect at any time after their invocation, or
+/// never. The search tries, at each point, each operation which could take effect first, and
+/// backtracks (Wing and Gong), but never explores twice the same set of operations which took
+/// effect with the same value of the register (Lowe).
+fn is_linearizable(history: &[Operation]) -> bool {
+    let mut operations: Vec<Operation> = history
+        .iter()
+        .copied()
+        .filter(|o| o.response.is_some() || matches!(o.kind, Kind::Write(_)))
+        .collect();
+    operations.sort_by_key(|o| o.invoke);
+
+    let num_of_completed = operations.iter().filter(|o| o.response.is_some()).count();
+
+    let mut linearized = vec![false; operations.len()];
+    let mut explored = HashSet::new();
+
+    search(
+        &operations,
+        &mut linearized,
+        INITIAL_VALUE,
+        num_of_completed,
+        &mut explored,
+    )
+}
+
+/// Searches for a linearization of the operations which are not linearized yet, from a register
+/// whose value is value, where num_of_completed of them completed.
+fn search(
+    operations: &[Operation],
+    linearized: &mut Vec<bool>,
+    value: usize,
+    num_of_completed: usize,
+    explored: &mut HashSet<(Vec<bool>, usize)>,
+) -> bool {
+    if num_of_completed == 0 {
+        return true;
+    }
+
+    // An operation can take effect first only if it was invoked before all the other ones
+    // completed.
+    let first_response = operations
+        .iter()
+        .zip(linearized.iter())
+        .filter(|(_, &l)| !l)
+        .filter_map(|(o, _)| o.response)
+        .min()
+        .unwrap_or(usize::MAX);
+
+    for i in 0..operations.len() {
+        let operation = operations[i];
+
+        if operation.invoke > first_response {
+            break;
+        }
+
+        if linearized[i] {
+            continue;
+        }
+
+        let next_value = match operation.kind {
+            Kind::Write(v) => v,
+            Kind::Read(v) if v == value => value,
+            Kind::Read(_) => continue,
+        };
+
+        linearized[i] = true;
+
+        if explored.insert((linearized.clone(), next_value))
+            && search(
+                operations,
+                linearized,
+                next_value,
+                num_of_completed - operation.response.is_some() as usize,
+                explored,
+            )
+        {
+            return true;
+        }
+
+        linearized[i] = false;
+    }
+
+    false
+}
+
+/// Returns whether the replicas applied the same operations, with the same results, and reached
+/// the same value.
+fn same_state(replicas: &[ReplicatedStateMachine<Register, usize>]) -> bool {
+    replicas.windows(2).all(|pair| {
+        let (a, b) = (pair[0].state(), pair[1].state());
+        a.value == b.value && a.results == b.results
+    })
+}
+
+fn main() {
+    let num_of_operations: usize = arg_or("number of operations", 100);
+
+    let operations_per_client = num_of_operations.div_ceil(NUM_OF_CLIENTS);
+
+    let mut cluster = Cluster::<usize>::new(NUM_OF_CLIENTS, 1, 3, 0).with_trace_collector();
+    let (history, replicas) = run(&mut cluster, operations_per_client, |c| c.step());
+
+    let ok1 = report(
+        "Test 1 - With the messages delivered in order, all the operations complete, and the history is linearizable",
+        history.len() == NUM_OF_CLIENTS * operations_per_client
+            && history.iter().all(|o| o.response.is_some())
+            && is_linearizable(&history)
+            && same_state(&replicas),
+    );
+
+    let failing_seed = (1..=num_of_operations as u64).find(|&seed| {
+        let mut cluster = Cluster::<usize>::with_seed(seed, NUM_OF_CLIENTS, 2, 3, 0)
+            .with_leader(0)
+            .with_trace_collector();
+        let (history, _) = run(&mut cluster, OPERATIONS_PER_CLIENT, |c| {
+            c.step_random(DROP_PROBABILITY).is_some()
+        });
+
+        !is_linearizable(&history)
+    });
+
+    if let Some(seed) = failing_seed {
+        println!("The history of the seed {} is not linearizable", seed);
+    }
+
+    let ok2 = report(
+        "Test 2 - With the messages delivered out of order, or lost, the history is linearizable",
+        failing_seed.is_none(),
+    );
+
+    // The write of 1 completes before the write of 2, which completes before a read of 1.
+    let stale_read = [
+        Operation {
+            invoke: 1,
+            response: Some(2),
+            kind: Kind::Write(1),
+        },
+        Operation {
+            invoke: 3,
+            response: Some(4),
+            kind: Kind::Write(2),
+        },
+        Operation {
+            invoke: 5,
+            response: Some(6),
+            kind: Kind::Read(1),
+        },
+    ];
+
+    // The read, concurrent with the writes, returns a value which no one wrote.
+    let unwritten_read = [
+        Operation {
+            invoke: 1,
+            response: Some(4),
+            kind: Kind::Write(1),
+        },
+        Operation {
+            invoke: 2,
+            response: Some(5),
+            kind: Kind::Read(3),
+        },
+        Operation {
+            invoke: 3,
+            response: None,
+            kind: Kind::Write(2),
+        },
+    ];
+
+    let ok3 = report(
+        "Test 3 - The checker rejects a stale read, and a read of a value which was never written",
+        !is_linearizable(&stale_read)
+            && !is_linearizable(&unwritten_read)
+            && is_linearizable(&stale_read[..2]),
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
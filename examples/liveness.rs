@@ -0,0 +1,112 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, the
+//! boundary of the liveness of Multi-Paxos, which is only guaranteed once there is a single leader
+//! (i.e. a distinguished proposer).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example liveness -- [num_of_values]
+//!
+//! 1. A single leader, without competing proposers, decides every value of a stream of client
+//!    requests, within a bounded number of delivered messages (i.e. no livelock, no stall).
+//! 2. Two proposers which keep starting new rounds of the same instance (e.g. when their timeouts
+//!    expire), without a leader, can preempt each other forever, so the instance is never decided,
+//!    until one of them gives up.
+
+extern crate multi_paxos;
+
+use std::collections::HashSet;
+use std::process;
+
+use multi_paxos::testkit::{arg_or, report, Choice, Cluster};
+
+/// The maximum number of messages which can be delivered, per value, by a single leader.
+const MAX_STEPS_PER_VALUE: usize = 32;
+
+/// The number of times the two proposers of the second test preempt each other.
+const NUM_OF_DUELS: usize = 100;
+
+/// The first test: a single leader decides every value within a bounded number of steps.
+fn single_leader(num_of_values: usize) -> bool {
+    let mut cluster = Cluster::<usize>::new(1, 3, 3, 1).with_leader(0);
+    cluster.run_until_quiet();
+
+    let values: Vec<usize> = (1..=num_of_values).collect();
+    values.iter().for_each(|&v| cluster.client(0).request(v));
+
+    let mut num_of_steps = 0;
+    while num_of_steps < num_of_values * MAX_STEPS_PER_VALUE && cluster.step() {
+        num_of_steps += 1;
+    }
+
+    let delivered: Vec<usize> = cluster
+        .learner(0)
+        .total_order_prefix()
+        .iter()
+        .map(|&(_, v)| v)
+        .collect();
+    let distinct: HashSet<usize> = delivered.iter().cloned().collect();
+
+    report(
+        "Test 1 - A single leader decides every value within a bounded number of steps",
+        cluster.num_of_pending() == 0
+            && delivered.len() == num_of_values
+            && distinct == values.iter().cloned().collect(),
+    )
+}
+
+/// The second test: two proposers, without a leader, preempt each other, so the instance they
+/// compete for is not decided, until one of them stops.
+fn dueling_proposers() -> bool {
+    let mut cluster = Cluster::<usize>::new(0, 2, 3, 1);
+    cluster.run_until_quiet();
+
+    // Proposer 0 starts a round of instance 1, which is delivered to the acceptors, whose
+    // Promise messages are now pending.
+    cluster.proposer(0).repropose(1, 10);
+    cluster.step();
+
+    let mut current = 0;
+
+    for _ in 0..NUM_OF_DUELS {
+        let other = 1 - current;
+
+        // Before the current proposer sends its Proposal, the other one starts a higher round
+        // (e.g. because its timeout expired), whose Preparation reaches the acceptors first, so
+        // the Proposal is rejected, with the Nack messages of the acceptors, which are delivered
+        // before the Promises of the other round, and the other proposer is now waiting for them.
+        let num_of_promises = cluster.num_of_pending();
+        cluster.proposer(other).repropose(1, 10 + other);
+
+        (0..num_of_promises).for_each(|_| {
+            cluster.step();
+        });
+        cluster.step();
+        cluster.step();
+
+        while cluster.num_of_pending() > num_of_promises {
+            cluster.step_with(Choice::Deliver(num_of_promises));
+        }
+
+        current = other;
+    }
+
+    let stalled = cluster.learner(0).learned_value(1).is_none();
+
+    // Once the proposers stop competing, the instance is decided.
+    cluster.run_until_quiet();
+    let decided = cluster.learner(0).learned_value(1).is_some();
+
+    report(
+        "Test 2 - Dueling proposers, without a leader, stall until one of them stops",
+        stalled && decided,
+    )
+}
+
+fn main() {
+    let num_of_values = arg_or("number of values", 100);
+
+    let ok = single_leader(num_of_values) & dueling_proposers();
+
+    if !ok {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,173 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! proposer which takes over from a failed proposer finalizes the gaps of the log, adopting the
+//! values voted for by the acceptors and proposing the no-op in the others, before it serves new
+//! client requests (see Proposer::recover_log and Proposer::with_log_recovery).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example log_recovery -- [num_of_values]
+//!
+//! 1. After the leader failed, with a log of num_of_values instances in which a third of the values
+//!    were decided, a third were voted for but not decided, and a third were not voted for, the new
+//!    leader decides the voted values, and the no-op in the others, and then a new value.
+//! 2. Without gaps, no instance is recovered, and the client requests are served at once.
+//! 3. Without a majority of the acceptors, the log is not recovered, and the client requests stay
+//!    postponed.
+
+extern crate multi_paxos;
+extern crate uuid;
+
+use std::process;
+
+use multi_paxos::message::{Message, Request};
+use multi_paxos::testkit::{arg_or, report, Cluster, Role};
+use uuid::Uuid;
+
+/// The no-op of the proposers, which the application ignores.
+const NOOP: usize = 0;
+
+/// Makes the i-th proposer of cluster handle the request of value, whose sequence number is value,
+/// sent by the client with the given uuid, without delivering its messages.
+fn request(cluster: &mut Cluster<usize>, i: usize, client_uuid: Uuid, value: usize) {
+    cluster.proposer(i).handle(Message::Phase0a(Request {
+        value,
+        sender_uuid: client_uuid,
+        request_seq: value,
+        priority: None,
+        group_id: 0,
+        prefix_from: None,
+    }));
+}
+
+/// Returns the instance of m, if m is a Preparation, an Acceptance or a Learning message.
+fn instance_of(m: &Message<usize>) -> Option<usize> {
+    match m {
+        Message::Phase1a(preparation) => Some(preparation.instance.0),
+        Message::Phase2b(acceptance) => Some(acceptance.instance.0),
+        Message::Phase3(learning) => Some(learning.instance.0),
+        _ => None,
+    }
+}
+
+/// Returns a cluster of 2 proposers, with the no-op and the log recovery, whose leader, the first
+/// one, started the instances of the values from 1 to num_of_values, but only the instances 3k + 2
+/// were decided, whereas the acceptors voted for the values of the instances 3k + 1, which were not
+/// learned, and did not vote in the instances 3k. Also returns the uuid of the leader.
+fn gappy_log(num_of_values: usize, client_uuid: Uuid) -> (Cluster<usize>, Option<Uuid>) {
+    let mut cluster = Cluster::<usize>::new(1, 2, 3, 1)
+        .with_leader(0)
+        .with_proposers(|p| p.with_noop(NOOP).with_log_recovery());
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| request(&mut cluster, 0, client_uuid, v));
+
+    let mut leader_uuid = None;
+
+    while {
+        cluster.drop_pending(|receiver, m| {
+            if let Message::Phase1a(preparation) = m {
+                leader_uuid = Some(preparation.sender_uuid);
+            }
+
+            match instance_of(m) {
+                Some(instance) if instance % 3 == 0 => {
+                    receiver == Role::Acceptors && matches!(m, Message::Phase1a(_))
+                }
+                Some(instance) if instance % 3 == 1 => {
+                    matches!(m, Message::Phase2b(_) | Message::Phase3(_))
+                }
+                _ => false,
+            }
+        });
+        cluster.step()
+    } {}
+
+    (cluster, leader_uuid)
+}
+
+/// Delivers the messages of cluster until there are none, but drops those sent by the failed
+/// proposer with the given uuid.
+fn step_without(cluster: &mut Cluster<usize>, failed_uuid: Option<Uuid>) {
+    while {
+        cluster.drop_pending(|_, m| Some(m.sender_uuid()) == failed_uuid);
+        cluster.step()
+    } {}
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    // There must be instances of each kind.
+    let num_of_values = num_of_values.max(3);
+    let client_uuid = Uuid::new_v4();
+
+    let (mut cluster, leader_uuid) = gappy_log(num_of_values, client_uuid);
+    let blocked = cluster.delivered_values(0).is_empty();
+
+    // The second proposer catches up with the leader, which fails right after its Report, and then
+    // takes over. Its client request must be decided after the log.
+    cluster.proposer(1).start_catch_up();
+    request(&mut cluster, 1, client_uuid, num_of_values + 1);
+
+    while !cluster.proposer(1).is_recovering_log() && cluster.step() {}
+    step_without(&mut cluster, leader_uuid);
+
+    let expected: Vec<usize> = (1..=num_of_values + 1)
+        .map(|v| {
+            if v % 3 == 0 && v <= num_of_values {
+                NOOP
+            } else {
+                v
+            }
+        })
+        .collect();
+
+    let ok1 = report(
+        "Test 1 - The new leader decides the voted values, and the no-op in the empty instances, before a new value",
+        blocked && !cluster.proposer(1).is_recovering_log() && cluster.delivered_values(0) == expected,
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 2, 3, 1)
+        .with_leader(0)
+        .with_proposers(|p| p.with_noop(NOOP).with_log_recovery());
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| request(&mut cluster, 0, client_uuid, v));
+    cluster.run_until_quiet();
+
+    let recovered = cluster.proposer(0).recover_log();
+    request(&mut cluster, 0, client_uuid, num_of_values + 1);
+    let served = cluster.proposer(0).in_flight_instances() == vec![num_of_values + 1];
+    cluster.run_until_quiet();
+
+    let ok2 = report(
+        "Test 2 - Without gaps, no instance is recovered, and the requests are served at once",
+        recovered.is_empty()
+            && served
+            && cluster.delivered_values(0) == (1..=num_of_values + 1).collect::<Vec<usize>>(),
+    );
+
+    let (mut cluster, _) = gappy_log(num_of_values, client_uuid);
+
+    cluster.crash_acceptor(1);
+    cluster.crash_acceptor(2);
+
+    let recovered = cluster.proposer(0).recover_log();
+    request(&mut cluster, 0, client_uuid, num_of_values + 1);
+    cluster.run_until_quiet();
+
+    let ok3 = report(
+        "Test 3 - Without a majority, the log is not recovered, and the requests stay postponed",
+        recovered.iter().all(|instance| instance % 3 != 2)
+            && recovered.len() == num_of_values - (num_of_values + 1) / 3
+            && cluster.proposer(0).is_recovering_log()
+            && !cluster
+                .proposer(0)
+                .in_flight_instances()
+                .contains(&(num_of_values + 1))
+            && cluster.delivered_values(0).is_empty(),
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
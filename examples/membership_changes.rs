@@ -0,0 +1,145 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! proposer starts at most alpha instances beyond the chosen prefix of the log, and that the
+//! membership changes of the acceptors decided in the log take effect exactly alpha instances later
+//! (see Proposer::with_alpha and Proposer::with_membership_changes).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example membership_changes -- [num_of_values]
+//!
+//! 1. With alpha, only alpha of the num_of_values requests are started at once, and all of them
+//!    are decided.
+//! 2. After a change from 3 to 5 acceptors in the instance 1, with one of the 3 acceptors crashed,
+//!    the instances before 1 + alpha are decided by the old majority, whereas the instance 1 + alpha
+//!    is not.
+//! 3. After a change from 3 to 1 acceptor in the instance 1, with two of the 3 acceptors crashed
+//!    once the instances before 1 + alpha are decided, the remaining instances are decided by the
+//!    last acceptor alone.
+
+extern crate multi_paxos;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate uuid;
+
+use std::process;
+
+use multi_paxos::message::{Message, Request};
+use multi_paxos::multi_paxos::Reconfigure;
+use multi_paxos::testkit::{arg_or, report, Cluster};
+use uuid::Uuid;
+
+/// The window of the pipelining of the proposers.
+const ALPHA: usize = 4;
+
+/// The values of the log: either a value of the application, or a membership change.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+enum Value {
+    Write(usize),
+    Membership(usize),
+}
+
+impl Reconfigure for Value {
+    fn membership(&self) -> Option<usize> {
+        match *self {
+            Value::Membership(num_of_acceptors) => Some(num_of_acceptors),
+            Value::Write(_) => None,
+        }
+    }
+}
+
+/// Makes the proposer of cluster handle the request of value, whose sequence number is seq, sent by
+/// the client with the given uuid, without delivering its messages.
+fn request(cluster: &mut Cluster<Value>, client_uuid: Uuid, seq: usize, value: Value) {
+    cluster.proposer(0).handle(Message::Phase0a(Request {
+        value,
+        sender_uuid: client_uuid,
+        request_seq: seq,
+        priority: None,
+        group_id: 0,
+        prefix_from: None,
+    }));
+}
+
+/// Makes the proposer of cluster handle the requests of the values Write(v), for v in values, and
+/// delivers the messages until there are none.
+fn write(cluster: &mut Cluster<Value>, client_uuid: Uuid, values: Vec<usize>) {
+    values
+        .into_iter()
+        .for_each(|v| request(cluster, client_uuid, v, Value::Write(v)));
+
+    cluster.run_until_quiet();
+}
+
+/// Returns a cluster of 1 proposer, with alpha and the membership changes, and 3 acceptors, whose
+/// membership was changed to num_of_acceptors in the instance 1.
+fn reconfigured(num_of_acceptors: usize) -> Cluster<Value> {
+    let mut cluster = Cluster::<Value>::new(1, 1, 3, 1)
+        .with_proposers(|p| p.with_alpha(ALPHA).with_membership_changes());
+    cluster.run_until_quiet();
+
+    request(
+        &mut cluster,
+        Uuid::new_v4(),
+        0,
+        Value::Membership(num_of_acceptors),
+    );
+    cluster.run_until_quiet();
+
+    cluster
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    // There must be values beyond the window.
+    let num_of_values = num_of_values.max(2 * ALPHA);
+    let client_uuid = Uuid::new_v4();
+
+    let mut cluster = Cluster::<Value>::new(1, 1, 3, 1).with_proposers(|p| p.with_alpha(ALPHA));
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| request(&mut cluster, client_uuid, v, Value::Write(v)));
+
+    // Only the Preparation messages of the instances in the window are sent.
+    let num_of_started = cluster.num_of_pending();
+    cluster.run_until_quiet();
+
+    let writes: Vec<Value> = (1..=num_of_values).map(Value::Write).collect();
+
+    let ok1 = report(
+        "Test 1 - With alpha, at most alpha instances are started beyond the chosen prefix, and all the values are decided",
+        num_of_started == ALPHA && cluster.delivered_values(0) == writes,
+    );
+
+    let mut cluster = reconfigured(5);
+    cluster.crash_acceptor(2);
+
+    write(&mut cluster, client_uuid, (1..=num_of_values).collect());
+
+    let log = cluster.delivered_values(0);
+
+    let ok2 = report(
+        "Test 2 - A membership change decided in the instance 1 takes effect in the instance 1 + alpha, not before",
+        log.len() == ALPHA && log[1..] == writes[..ALPHA - 1],
+    );
+
+    let mut cluster = reconfigured(1);
+
+    write(&mut cluster, client_uuid, (1..ALPHA).collect());
+
+    cluster.crash_acceptor(1);
+    cluster.crash_acceptor(2);
+
+    write(&mut cluster, client_uuid, (ALPHA..=num_of_values).collect());
+
+    let log = cluster.delivered_values(0);
+
+    let ok3 = report(
+        "Test 3 - From the instance 1 + alpha on, the values are decided by a majority of the new membership",
+        log.len() == num_of_values + 1 && log[1..] == writes[..],
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
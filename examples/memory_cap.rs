@@ -0,0 +1,114 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that
+//! the acceptors report the memory held by the state of the instances they track, and that an
+//! acceptor above its soft cap asks the proposers to truncate its log (see
+//! Acceptor::with_memory_soft_cap).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example memory_cap -- [num_of_values]
+//!
+//! 1. After num_of_values values are decided, each acceptor tracks num_of_values instances, and
+//!    estimates that they hold at least as many bytes as their instances and votes.
+//! 2. The Truncation message of a stable checkpoint is lost, so the acceptors keep the instances
+//!    below it, until they go above their soft cap: they then ask the proposer to send it again,
+//!    and truncate their logs.
+//! 3. The acceptors which do not truncate their logs go above their soft cap without asking the
+//!    proposers to truncate them, and keep all their instances.
+
+extern crate multi_paxos;
+
+use std::mem;
+use std::process;
+
+use multi_paxos::message::Message;
+use multi_paxos::testkit::{arg_or, report, Cluster, Role};
+
+/// The value which marks a checkpoint of the application.
+const CHECKPOINT: usize = usize::MAX;
+
+/// Makes the client of cluster send the requests of the given values, one at a time, and delivers
+/// the messages until there are none, except the Truncation messages sent to the acceptors, which
+/// are lost.
+fn request_losing_truncations(cluster: &mut Cluster<usize>, values: impl Iterator<Item = usize>) {
+    for value in values {
+        cluster.client(0).request(value);
+        while {
+            cluster.drop_pending(|receiver, m| {
+                receiver == Role::Acceptors && matches!(m, Message::Truncation(_))
+            });
+            cluster.step()
+        } {}
+    }
+}
+
+/// Returns the number of the TruncationRequest messages delivered in cluster.
+fn num_of_truncation_requests(cluster: &Cluster<usize>) -> usize {
+    cluster.count_delivered(|m| matches!(m, Message::TruncationRequest(_)))
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1);
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.request(0, v));
+
+    let ok1 = report(
+        "Test 1 - The acceptors report the instances they track, and the bytes they hold",
+        (0..3).all(|i| {
+            let memory = cluster.acceptor(i).memory_report();
+
+            memory.num_of_instances == num_of_values
+                && memory.estimated_bytes >= num_of_values * 2 * mem::size_of::<usize>()
+        }),
+    );
+
+    // The soft cap is only exceeded 2 instances after the checkpoint.
+    let checkpoint = num_of_values + 1;
+    let soft_cap = checkpoint + 1;
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1)
+        .with_checkpoint(CHECKPOINT)
+        .with_acceptors(|a| a.with_memory_soft_cap(soft_cap))
+        .with_trace_collector();
+    cluster.run_until_quiet();
+
+    request_losing_truncations(&mut cluster, 1..=num_of_values);
+    request_losing_truncations(&mut cluster, Some(CHECKPOINT).into_iter());
+    request_losing_truncations(&mut cluster, checkpoint + 1..=soft_cap);
+
+    let below_soft_cap = num_of_truncation_requests(&cluster) == 0
+        && (0..3).all(|i| cluster.acceptor(i).truncated_below() == 1);
+
+    cluster.request(0, soft_cap + 1);
+
+    let ok2 = report(
+        "Test 2 - The acceptors above their soft cap ask the proposer to truncate their logs again",
+        below_soft_cap
+            && num_of_truncation_requests(&cluster) > 0
+            && (0..3).all(|i| {
+                let acceptor = cluster.acceptor(i);
+
+                acceptor.truncated_below() == checkpoint
+                    && acceptor.memory_report().num_of_instances == 3
+            }),
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1)
+        .with_acceptors(|a| a.with_memory_soft_cap(1))
+        .with_trace_collector();
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.request(0, v));
+
+    let ok3 = report(
+        "Test 3 - The acceptors which do not truncate their logs keep all their instances above their soft cap",
+        num_of_truncation_requests(&cluster) == 0
+            && cluster.learner(0).total_order_prefix().len() == num_of_values
+            && (0..3).all(|i| cluster.acceptor(i).memory_report().num_of_instances == num_of_values),
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
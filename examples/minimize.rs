@@ -0,0 +1,120 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! random schedule (i.e. messages delivered out of order, or dropped) which makes a test fail can be
+//! replayed exactly, and shrunk to a minimal schedule which still makes it fail (see
+//! testkit::minimize_schedule).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example minimize -- [num_of_values]
+//!
+//! The test which fails is that a learner learns all the num_of_values values requested by a
+//! client, which the random schedules can prevent by dropping messages.
+//!
+//! 1. Replaying the same schedule twice delivers the same messages, in the same order.
+//! 2. The minimized schedule is shorter than the random one, it still makes the test fail, and
+//!    removing any of its choices makes the test pass.
+
+extern crate multi_paxos;
+
+use std::process;
+
+use multi_paxos::testkit::{arg_or, minimize_schedule, report, Choice, Cluster};
+
+/// The probability that a random schedule drops a message.
+const DROP_PROBABILITY: f64 = 0.02;
+
+/// Creates a cluster with the given seed, whose client requests the values 1..=num_of_values.
+fn start(seed: u64, num_of_values: usize) -> Cluster<usize> {
+    let mut cluster = Cluster::<usize>::with_seed(seed, 1, 1, 3, 1).with_trace_collector();
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.client(0).request(v));
+
+    cluster
+}
+
+/// Replays schedule on a new cluster with the given seed, delivers the remaining messages in
+/// order, and returns the cluster.
+fn replay(seed: u64, num_of_values: usize, schedule: &[Choice]) -> Cluster<usize> {
+    let mut cluster = start(seed, num_of_values);
+
+    schedule.iter().for_each(|&choice| {
+        cluster.step_with(choice);
+    });
+    cluster.run_until_quiet();
+
+    cluster
+}
+
+/// Returns true if the learner of cluster has not learned all the num_of_values values.
+fn fails(cluster: &mut Cluster<usize>, num_of_values: usize) -> bool {
+    cluster.learner(0).total_order_prefix().len() < num_of_values
+}
+
+/// Returns the messages delivered in cluster, in delivery order.
+fn trace(cluster: &Cluster<usize>) -> Vec<String> {
+    cluster
+        .trace_collector()
+        .unwrap()
+        .entries()
+        .iter()
+        .map(|e| format!("{:?} {:?}", e.receiver, e.message))
+        .collect()
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    // Looks for a seed whose random schedule makes the test fail.
+    let (seed, schedule) = (0..)
+        .map(|seed| {
+            let mut cluster = start(seed, num_of_values);
+
+            let mut schedule = Vec::new();
+            while let Some(choice) = cluster.step_random(DROP_PROBABILITY) {
+                schedule.push(choice);
+            }
+
+            (seed, schedule, fails(&mut cluster, num_of_values))
+        })
+        .find(|&(_, _, failed)| failed)
+        .map(|(seed, schedule, _)| (seed, schedule))
+        .unwrap();
+
+    let ok1 = report(
+        "Test 1 - Replaying a schedule twice delivers the same messages",
+        trace(&replay(seed, num_of_values, &schedule))
+            == trace(&replay(seed, num_of_values, &schedule)),
+    );
+
+    let replay_fails =
+        |schedule: &[Choice]| fails(&mut replay(seed, num_of_values, schedule), num_of_values);
+
+    let minimized = minimize_schedule(&schedule, replay_fails);
+
+    println!(
+        "The schedule of {} choices (seed {}) was minimized to {:?}, which delivers {} messages.",
+        schedule.len(),
+        seed,
+        minimized,
+        replay(seed, num_of_values, &minimized)
+            .trace_collector()
+            .unwrap()
+            .entries()
+            .len()
+    );
+
+    let is_minimal = (0..minimized.len()).all(|i| {
+        let mut shorter = minimized.clone();
+        shorter.remove(i);
+        !replay_fails(&shorter)
+    });
+
+    let ok2 = report(
+        "Test 2 - A failing schedule is minimized to a shorter one which still fails",
+        minimized.len() < schedule.len() && replay_fails(&minimized) && is_minimal,
+    );
+
+    if !(ok1 && ok2) {
+        process::exit(1);
+    }
+}
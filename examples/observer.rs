@@ -0,0 +1,116 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that an
+//! observer (see Observer) taps the live decisions of a cluster, without catching up with the
+//! previous ones, and without sending any message.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example observer -- [num_of_values]
+//!
+//! 1. An observer which joins after num_of_values values were decided observes none of them, but
+//!    it observes the num_of_values values decided afterwards, as the learner delivers them.
+//! 2. An observer sends no message when it joins, unlike a late learner, and it ignores the Report
+//!    which the proposer then sends to the learners.
+//! 3. The Learning messages which the proposer sends again, because the LearnAck messages of the
+//!    learner were lost, are not observed twice.
+
+extern crate multi_paxos;
+
+use std::process;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use multi_paxos::message::Message;
+use multi_paxos::testkit::{arg_or, report, Cluster, Role};
+
+/// How often the proposer sends the unacknowledged Learning messages again (see
+/// Proposer::with_learn_acks).
+const RETRANSMIT_INTERVAL: Duration = Duration::from_millis(200);
+
+/// The values observed by an observer, together with their instances, in the order they were
+/// observed.
+type Observed = Arc<Mutex<Vec<(usize, usize)>>>;
+
+/// Adds an observer to cluster, and returns its index, and the values which it observes.
+fn add_observer(cluster: &mut Cluster<usize>) -> (usize, Observed) {
+    let observed: Observed = Arc::new(Mutex::new(Vec::new()));
+    let sink = observed.clone();
+
+    let i = cluster.add_observer(Box::new(move |instance, &v| {
+        sink.lock().unwrap().push((instance, v))
+    }));
+
+    (i, observed)
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1);
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.request(0, v));
+    let (observer, observed) = add_observer(&mut cluster);
+    (num_of_values + 1..=2 * num_of_values).for_each(|v| cluster.request(0, v));
+
+    let ok1 = report(
+        "Test 1 - An observer observes the values decided after it joined, but not the previous ones",
+        observed.lock().unwrap()[..] == cluster.learner(0).total_order_prefix()[num_of_values..]
+            && cluster.observer(observer).num_of_observed() == num_of_values
+            && cluster.observer(observer).highest_observed() == Some(2 * num_of_values),
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1);
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.request(0, v));
+    let (observer, observed) = add_observer(&mut cluster);
+    let silent = cluster.num_of_pending() == 0;
+
+    let learner = cluster.add_learner();
+    cluster.run_until_quiet();
+
+    let ok2 = report(
+        "Test 2 - An observer sends no message, and ignores the Report sent to a late learner",
+        silent
+            && cluster.learner(learner).total_order_prefix().len() == num_of_values
+            && observed.lock().unwrap().is_empty()
+            && cluster.observer(observer).highest_observed().is_none(),
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1)
+        .with_proposers(|p| p.with_learn_acks(1, RETRANSMIT_INTERVAL))
+        .with_learners(|l| l.with_learn_acks())
+        .with_trace_collector();
+    cluster.run_until_quiet();
+
+    let (observer, observed) = add_observer(&mut cluster);
+
+    (1..=num_of_values).for_each(|v| cluster.client(0).request(v));
+    while {
+        cluster.drop_pending(|receiver, m| {
+            receiver == Role::Proposers && matches!(m, Message::LearnAck(_))
+        });
+        cluster.step()
+    } {}
+
+    cluster.proposer(0).retransmit_learnings();
+    cluster.run_until_quiet();
+
+    let num_of_learnings = cluster
+        .trace_collector()
+        .unwrap()
+        .count(|e| e.receiver == Role::Learners && matches!(e.message, Message::Phase3(_)));
+
+    let mut sorted = observed.lock().unwrap().clone();
+    sorted.sort_unstable();
+
+    let ok3 = report(
+        "Test 3 - The Learning messages sent again are not observed twice",
+        num_of_learnings > num_of_values
+            && sorted == cluster.learner(0).total_order_prefix()
+            && cluster.observer(observer).num_of_observed() == num_of_values,
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
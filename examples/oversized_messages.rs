@@ -0,0 +1,96 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that
+//! the nodes whose fragmentation is disabled (see Proposer::with_fragmentation) refuse to send the
+//! messages which do not fit in a datagram, instead of sending them, and losing them silently.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example oversized_messages -- [num_of_values]
+//!
+//! In each test, the values are large, so that a datagram of MAX_DATAGRAM_SIZE bytes carries a
+//! single one, but not two.
+//! 1. The Report of num_of_values + 1 values, which a proposer whose fragmentation is disabled
+//!    sends to a late proposer, is refused (and counted), so the late proposer does not catch up,
+//!    whereas the num_of_values + 1 values were decided.
+//! 2. The same Report, sent by a proposer whose fragmentation is enabled, is sent in fragments, and
+//!    the late proposer catches up.
+//! 3. The requests of a client whose fragmentation is disabled, with a maximum datagram size
+//!    smaller than a value, are refused, so none of them is decided.
+
+extern crate multi_paxos;
+
+use std::process;
+
+use multi_paxos::message::Message;
+use multi_paxos::testkit::{arg_or, report, Cluster};
+
+/// The number of words of a value.
+const VALUE_SIZE: usize = 32;
+
+/// The maximum size of the datagrams of the nodes.
+const MAX_DATAGRAM_SIZE: usize = 512;
+
+type Value = [usize; VALUE_SIZE];
+
+/// Returns a cluster whose proposer has the given fragmentation, in which num_of_values values are
+/// decided, and to which a late proposer is added, once the messages are delivered. Returns the
+/// index of the late proposer too.
+fn cluster_with(fragmentation: bool, num_of_values: usize) -> (Cluster<Value>, usize) {
+    let mut cluster = Cluster::<Value>::new(1, 1, 3, 1).with_proposers(|p| {
+        p.with_max_datagram_size(MAX_DATAGRAM_SIZE)
+            .with_fragmentation(fragmentation)
+    });
+    cluster.run_until_quiet();
+
+    for v in 1..=num_of_values {
+        cluster.client(0).request([v; VALUE_SIZE]);
+        cluster.run_until_quiet();
+    }
+
+    let late = cluster.add_proposer();
+    cluster.run_until_quiet();
+
+    (cluster, late)
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let (mut cluster, late) = cluster_with(false, num_of_values + 1);
+
+    let ok1 = report(
+        "Test 1 - The Report which does not fit in a datagram is refused, when the fragmentation is disabled",
+        cluster.learner(0).total_order_prefix().len() == num_of_values + 1
+            && cluster.proposer(0).num_of_oversized_messages() > 0
+            && cluster.proposer(late).committed_watermark() == 0,
+    );
+
+    let (mut cluster, late) = cluster_with(true, num_of_values + 1);
+
+    let ok2 = report(
+        "Test 2 - The Report which does not fit in a datagram is sent in fragments, when the fragmentation is enabled",
+        cluster.proposer(0).num_of_oversized_messages() == 0
+            && cluster.proposer(late).committed_watermark() == num_of_values + 1,
+    );
+
+    let mut cluster = Cluster::<Value>::new(1, 1, 3, 1)
+        .with_clients(|c| {
+            c.with_max_datagram_size(MAX_DATAGRAM_SIZE / 2)
+                .with_fragmentation(false)
+        })
+        .with_trace_collector();
+    cluster.run_until_quiet();
+
+    for v in 1..=num_of_values {
+        cluster.client(0).request([v; VALUE_SIZE]);
+        cluster.run_until_quiet();
+    }
+
+    let ok3 = report(
+        "Test 3 - The requests which do not fit in a datagram are refused, when the fragmentation is disabled",
+        cluster.learner(0).total_order_prefix().is_empty()
+            && cluster.count_delivered(|m| matches!(m, Message::Phase0a(_))) == 0,
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
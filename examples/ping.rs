@@ -0,0 +1,93 @@
+//! A script used to measure the round-trip time (RTT) to the nodes of a role, which answer the Ping
+//! messages it sends with Pong messages. This can be used to distinguish slow nodes from a network
+//! which drops packets.
+//!
+//! You can run this example as follows
+//!     RUST_LOG=multi_paxos=info cargo run --example ping -- <role> Config [count]
+//! where <role> is either clients, proposers, acceptors or learners and [count] is the number of
+//! Ping messages to send (by default, 10), one per second.
+//!
+//! Note that the pinger listens for the Pong messages on the address of the clients.
+
+extern crate env_logger;
+#[macro_use]
+extern crate log;
+extern crate multi_paxos;
+
+use std::env;
+use std::time::Duration;
+
+use multi_paxos::configurations::{get_cluster_id, get_config, get_socket_dir};
+use multi_paxos::ping::Pinger;
+use multi_paxos::unix_transport::use_unix_sockets;
+
+fn main() {
+    env_logger::init();
+
+    let args: Vec<String> = env::args().collect();
+    info!("{:?}", args);
+
+    match args.len() {
+        3 | 4 => {
+            let role = &args[1];
+
+            if let Some(socket_dir) = get_socket_dir(&args[2]) {
+                use_unix_sockets(socket_dir);
+            }
+
+            let config = get_config(&args[2]);
+
+            let (_, destination_address) = match config.get(role) {
+                Some(&role_config) => role_config,
+                None => {
+                    eprintln!("Error: unknown role {}", role);
+                    return;
+                }
+            };
+
+            let count: u64 = match args.get(3).map(|count| count.parse()) {
+                None => 10,
+                Some(Ok(n)) => n,
+                Some(Err(_)) => {
+                    eprintln!("Error: third argument not an u64");
+                    return;
+                }
+            };
+
+            let (_, clients_address) = config["clients"];
+
+            let mut pinger = Pinger::<usize>::new(clients_address);
+
+            if let Some(cluster_id) = get_cluster_id(&args[2]) {
+                pinger = pinger.with_cluster_id(&cluster_id);
+            }
+
+            #[cfg(feature = "encryption")]
+            {
+                if let Some(secret) = multi_paxos::configurations::get_secret(&args[2]) {
+                    pinger = pinger.with_encryption_key(&secret);
+                }
+            }
+
+            for _ in 0..count {
+                let rtts = pinger.ping(destination_address, Duration::from_secs(1));
+                println!("{} node(s) answered: {:?}", rtts.len(), rtts);
+            }
+
+            for (uuid, stats) in pinger.rtts() {
+                println!(
+                    "{}: {}/{} pongs, RTT min/avg/max = {:?}/{:?}/{:?}",
+                    uuid,
+                    stats.num_of_pongs,
+                    pinger.num_of_pings(),
+                    stats.min,
+                    stats.avg,
+                    stats.max
+                );
+            }
+        }
+        _ => {
+            panic!("Expected 2 or 3 arguments (excluding file name)");
+        }
+    }
+}
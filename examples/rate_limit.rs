@@ -0,0 +1,92 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that
+//! the maximum send rate of a node (see Proposer::with_max_send_rate) bounds the messages which it
+//! sends, but not the essential ones.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example rate_limit -- [num_of_values]
+//!
+//! 1. A client which requests num_of_values values at once, but whose burst is half of them, only
+//!    sends the first half of its requests (all in less than a second), and only their values are
+//!    decided.
+//! 2. A proposer whose burst is only enough for its start and the Preparation and the Proposal of an
+//!    instance still sends the Learning message of the instance, so the learners learn its value.
+//! 3. A proposer whose tokens are exhausted queues its messages, and, once tokens are added, sends
+//!    the reply to a client whose value is decided before the Proposal of another instance, which
+//!    was queued earlier.
+
+extern crate multi_paxos;
+
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use multi_paxos::message::{InstanceId, Message};
+use multi_paxos::testkit::{arg_or, report, Cluster};
+
+/// A rate of messages per second low enough that no token is added during a test.
+const LOW_RATE: u32 = 1;
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+    let burst = (num_of_values / 2).max(1);
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1)
+        .with_clients(|client| client.with_max_send_rate(LOW_RATE, burst))
+        .with_trace_collector();
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.client(0).request(v));
+    cluster.run_until_quiet();
+
+    let num_of_requests = cluster.count_delivered(|m| matches!(m, Message::Phase0a(_)));
+
+    let ok1 = report(
+        "Test 1 - A client sends at most a burst of requests at once",
+        num_of_requests == burst && cluster.delivered_values(0) == (1..=burst).collect::<Vec<_>>(),
+    );
+
+    // The CatchUp and the Report sent by the proposer when it starts, then the Preparation and the
+    // Proposal of the instance, take all its tokens.
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1)
+        .with_proposers(|proposer| proposer.with_max_send_rate(LOW_RATE, 4));
+    cluster.run_until_quiet();
+
+    cluster.request(0, num_of_values);
+
+    let ok2 = report(
+        "Test 2 - A proposer whose tokens are exhausted still sends its Learning messages",
+        cluster.delivered_values(0) == vec![num_of_values],
+    );
+
+    // The Report sent by the proposer when it starts, the Preparations of the instances 1 and 2,
+    // and the Proposal of the instance 1 take all its tokens, so the Proposal of the instance 2 is
+    // queued. The Learning messages of the instance 1 are sent anyway (and paid back with the next
+    // tokens), but the reply to the client is queued, after the Proposal of the instance 2.
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1)
+        .with_proposers(|proposer| proposer.with_max_send_rate(LOW_RATE, 4))
+        .with_trace_collector();
+    cluster.run_until_quiet();
+
+    cluster.client(0).request(1);
+    cluster.request(0, 2);
+
+    // Once the tokens are paid back, the next message sent by the proposer (the Preparation of the
+    // instance 3) flushes the queue.
+    thread::sleep(3 * Duration::from_secs(1) / LOW_RATE);
+    cluster.request(0, 3);
+
+    let trace_collector = cluster.trace_collector().unwrap();
+    let decided = trace_collector
+        .position(|e| matches!(e.message, Message::Decided(ref d) if d.instance == InstanceId(1)));
+    let proposal = trace_collector
+        .position(|e| matches!(e.message, Message::Phase2a(ref p) if p.instance == InstanceId(2)));
+
+    let ok3 = report(
+        "Test 3 - A proposer whose tokens are exhausted replies to its clients before it proposes",
+        decided.is_some_and(|decided| proposal.is_none_or(|proposal| decided < proposal)),
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,110 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that
+//! the logs of different learners can be merged after the fact (see storage::merge_logs), and that
+//! the instances where two logs diverge are pinpointed.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example reconciliation -- [num_of_values]
+//!
+//! 1. The logs of the learners of a cluster agree, and their merge is the log of the proposer.
+//! 2. The logs of two observers which each missed some instances, written to files and read back,
+//!    are merged into the complete log.
+//! 3. If two logs have different values in some instances, each of these instances is returned,
+//!    with its two values.
+
+extern crate multi_paxos;
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::process;
+
+use multi_paxos::storage::{merge_logs, read_log, write_log, Conflict};
+use multi_paxos::testkit::{arg_or, report, Cluster};
+
+/// Writes log to a file named name in the temporary directory, and reads it back.
+fn write_and_read(name: &str, log: &BTreeMap<usize, usize>) -> BTreeMap<usize, usize> {
+    let path = env::temp_dir().join(name);
+
+    write_log(&path, log).expect("Could not write the log");
+    let log = read_log(&path).expect("Could not read the log");
+    let _ = fs::remove_file(&path);
+
+    log
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 2);
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.client(0).request(v));
+    cluster.run_until_quiet();
+
+    let expected = cluster.proposer(0).export_log();
+    let log0 = cluster.learner(0).export_log();
+    let log1 = cluster.learner(1).export_log();
+
+    let ok1 = report(
+        "Test 1 - The logs of the learners agree, and their merge is the log of the proposer",
+        expected.len() == num_of_values && merge_logs(&log0, &log1).as_ref() == Ok(&expected),
+    );
+
+    // The first observer missed the last third of the instances, and the second one the first
+    // third, so they only overlap in the middle.
+    let first: BTreeMap<usize, usize> = log0
+        .iter()
+        .take(num_of_values * 2 / 3)
+        .map(|(&instance, &v)| (instance, v))
+        .collect();
+    let second: BTreeMap<usize, usize> = log1
+        .iter()
+        .skip(num_of_values / 3)
+        .map(|(&instance, &v)| (instance, v))
+        .collect();
+
+    let first = write_and_read("multi_paxos_reconciliation_first.log", &first);
+    let second = write_and_read("multi_paxos_reconciliation_second.log", &second);
+
+    let ok2 = report(
+        "Test 2 - The partial logs of two observers are merged into the complete log",
+        first.len() < num_of_values
+            && second.len() < num_of_values
+            && merge_logs(&first, &second).as_ref() == Ok(&expected),
+    );
+
+    // Two instances of the log of the second learner are corrupted, as if the learners had
+    // decided different values.
+    let mut divergent = log1.clone();
+    let divergent_instances: Vec<usize> =
+        vec![*log1.keys().last().unwrap(), *log1.keys().next().unwrap()];
+
+    for &instance in &divergent_instances {
+        divergent.insert(instance, log1[&instance] + num_of_values);
+    }
+
+    let mut expected_conflicts: Vec<Conflict<usize>> = divergent_instances
+        .iter()
+        .map(|&instance| Conflict {
+            instance,
+            ours: log0[&instance],
+            theirs: log0[&instance] + num_of_values,
+        })
+        .collect();
+    expected_conflicts.sort_by_key(|c| c.instance);
+
+    let result = merge_logs(&log0, &divergent);
+
+    if let Err(conflicts) = &result {
+        conflicts.iter().for_each(|c| println!("Conflict: {}", c));
+    }
+
+    let ok3 = report(
+        "Test 3 - The instances where two logs diverge are returned, with their two values",
+        result == Err(expected_conflicts),
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
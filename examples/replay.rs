@@ -0,0 +1,115 @@
+//! A script used to replay a message log, previously captured by a proposer, an acceptor or a
+//! learner (see e.g. the example start_proposer), by feeding the received messages, in order, to
+//! the handlers of a new node with the same role. This can be used to reproduce a bug.
+//!
+//! You can run this example as follows
+//!     RUST_LOG=multi_paxos=info cargo run --example replay -- <role> <message_log> Config
+//! where <role> is either proposer, acceptor or learner and <message_log> is the file containing
+//! the captured messages. The resulting state transitions are printed as logging messages.
+//!
+//! Note that the replaying node uses the sockets associated with its role in the configuration
+//! file, so the messages it sends in response to the replayed ones are actually sent.
+
+extern crate env_logger;
+#[macro_use]
+extern crate log;
+extern crate multi_paxos;
+extern crate serde;
+
+use std::env;
+
+use multi_paxos::configurations::get_config;
+use multi_paxos::message::Message;
+use multi_paxos::message_log::{Direction, MessageLog};
+use multi_paxos::multi_paxos::{Acceptor, DeliveryPolicy, Learner, LearningMode, Proposer};
+
+fn main() {
+    env_logger::init();
+
+    let args: Vec<String> = env::args().collect();
+    info!("{:?}", args);
+
+    match args.len() {
+        4 => {
+            let role = &args[1];
+
+            let message_log_file_name = &args[2];
+            let entries = match MessageLog::read::<usize, _>(message_log_file_name) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    eprintln!("Error: could not read {}: {}", message_log_file_name, e);
+                    return;
+                }
+            };
+
+            // The replaying node needs to impersonate the node which captured the log, otherwise it
+            // would ignore the messages addressed to the latter.
+            let (uuid, group_id) = match entries
+                .iter()
+                .find(|entry| entry.direction == Direction::Sent)
+            {
+                Some(entry) => (entry.message.sender_uuid(), entry.message.group_id()),
+                None => {
+                    eprintln!("Error: the message log contains no message sent by the node");
+                    return;
+                }
+            };
+
+            // Only the messages that the node received drive its state transitions.
+            let messages: Vec<Message<usize>> = entries
+                .into_iter()
+                .filter(|entry| entry.direction == Direction::Received)
+                .map(|entry| {
+                    println!("[{:?}] Replaying {:?}", entry.timestamp, entry.message);
+                    entry.message
+                })
+                .collect();
+
+            let config_file_name = &args[3];
+            let config = get_config(config_file_name);
+
+            let (_, proposers_address) = config["proposers"];
+            let (num_of_acceptors, acceptors_address) = config["acceptors"];
+            let (_, learners_address) = config["learners"];
+
+            match role.as_str() {
+                "proposer" => {
+                    let mut proposer = Proposer::<usize>::new(
+                        0,
+                        proposers_address,
+                        acceptors_address,
+                        learners_address,
+                        num_of_acceptors,
+                    )
+                    .with_uuid(uuid)
+                    .with_group_id(group_id);
+                    messages.into_iter().for_each(|m| proposer.handle(m));
+                }
+                "acceptor" => {
+                    let mut acceptor =
+                        Acceptor::<usize>::new(0, acceptors_address, proposers_address)
+                            .with_uuid(uuid)
+                            .with_group_id(group_id);
+                    messages.into_iter().for_each(|m| acceptor.handle(m));
+                }
+                "learner" => {
+                    let mut learner = Learner::<usize>::new(
+                        0,
+                        learners_address,
+                        proposers_address,
+                        LearningMode::Push,
+                        DeliveryPolicy::TotalOrder,
+                        None,
+                    )
+                    .with_uuid(uuid)
+                    .with_group_id(group_id);
+                    messages.into_iter().for_each(|m| learner.handle(m));
+                }
+                _ => eprintln!("Error: the role must be either proposer, acceptor or learner"),
+            }
+        }
+        _ => {
+            panic!("Expected 3 arguments (excluding file name)");
+        }
+    }
+}
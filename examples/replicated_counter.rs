@@ -0,0 +1,89 @@
+//! A script used to start one replica of a replicated counter, i.e. a learner embedded in a
+//! ReplicatedStateMachine, which adds each decided value (which is a number) to a running total.
+//! All the replicas of the same cluster print the same totals, in the same order.
+//!
+//! You can run this example as follows
+//!     cargo run --example replicated_counter -- <learner_uid> Config
+//! where <learner_uid> is a non-negative number which should be unique (among all nodes).
+//!
+//! For each decided value, the replica prints a line `<value> total=<total>`, where <total> is
+//! the sum of all the values applied so far, including this one.
+
+extern crate env_logger;
+#[macro_use]
+extern crate log;
+extern crate multi_paxos;
+
+use std::env;
+
+use multi_paxos::configurations::{
+    get_cluster_id, get_config_with_tunables, get_direct_learning, get_log_verbosity,
+    get_socket_dir,
+};
+use multi_paxos::multi_paxos::DeliveryPolicy;
+use multi_paxos::multi_paxos::Learner;
+use multi_paxos::multi_paxos::LearningMode;
+use multi_paxos::multi_paxos::Runnable;
+use multi_paxos::state_machine::ReplicatedStateMachine;
+use multi_paxos::unix_transport::use_unix_sockets;
+use multi_paxos::verbosity::set_log_verbosity;
+
+/// Adds value to total, and prints both.
+fn add(total: &mut usize, value: &usize) {
+    *total += value;
+    println!("{} total={}", value, total);
+}
+
+fn main() {
+    env_logger::init();
+
+    let args: Vec<String> = env::args().collect();
+    info!("{:?}", args);
+
+    if args.len() != 3 {
+        panic!("Expected 2 arguments (excluding file name)");
+    }
+
+    let uid: usize = match args[1].parse() {
+        Ok(n) => n,
+        Err(_) => {
+            eprintln!("Error: second argument not an usize");
+            return;
+        }
+    };
+
+    let config_file_name = &args[2];
+    if let Some(socket_dir) = get_socket_dir(config_file_name) {
+        use_unix_sockets(socket_dir);
+    }
+
+    let (config, tunables) = get_config_with_tunables(config_file_name);
+
+    let (_, learners_address) = config["learners"];
+    let (_, proposers_address) = config["proposers"];
+    let (num_of_acceptors, acceptors_address) = config["acceptors"];
+
+    let mut learner = Learner::<usize>::new(
+        uid,
+        learners_address,
+        proposers_address,
+        LearningMode::Push,
+        DeliveryPolicy::TotalOrder,
+        tunables.gap_timeout,
+    )
+    .with_acceptors(acceptors_address, num_of_acceptors);
+
+    if get_direct_learning(config_file_name) {
+        learner = learner.with_direct_learning();
+    }
+
+    if let Some(verbosity) = get_log_verbosity(config_file_name) {
+        set_log_verbosity(verbosity);
+    }
+
+    if let Some(cluster_id) = get_cluster_id(config_file_name) {
+        learner = learner.with_cluster_id(&cluster_id);
+    }
+
+    ReplicatedStateMachine::new(learner, 0, Box::new(add)).run();
+}
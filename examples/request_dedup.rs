@@ -0,0 +1,132 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! proposer ignores the duplicates of the client requests which it handled recently, and that it
+//! forgets the requests beyond the capacity, or the window, of its dedup cache (see
+//! Proposer::with_request_dedup).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example request_dedup -- [num_of_values]
+//!
+//! 1. Each of the num_of_values requests, sent twice, is decided once.
+//! 2. With a capacity of 10 requests, the duplicate of the last request is ignored, whereas the
+//!    duplicate of the first one, which was forgotten, is decided again.
+//! 3. With a window of 50 milliseconds, the duplicate of a request which arrives later is decided
+//!    again.
+//! 4. Each of the num_of_values requests, delivered twice in a row (e.g. a multicast datagram which
+//!    is looped back), before its value is decided, is decided once.
+
+extern crate multi_paxos;
+extern crate uuid;
+
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use multi_paxos::message::{Message, Request};
+use multi_paxos::testkit::{arg_or, report, Cluster};
+use uuid::Uuid;
+
+/// The capacity of the dedup cache of the proposer in the test 2.
+const CAPACITY: usize = 10;
+
+/// The window of the dedup cache of the proposer in the test 3.
+const WINDOW: Duration = Duration::from_millis(50);
+
+/// Makes the proposer of cluster handle the request of value, whose sequence number is value, sent
+/// by the client with the given uuid, without delivering the messages which it sends.
+fn handle_request(cluster: &mut Cluster<usize>, client_uuid: Uuid, value: usize) {
+    cluster.proposer(0).handle(Message::Phase0a(Request {
+        value,
+        sender_uuid: client_uuid,
+        request_seq: value,
+        priority: None,
+        group_id: 0,
+        prefix_from: None,
+    }));
+}
+
+/// Makes the proposer of cluster handle the request of value (see handle_request), and delivers
+/// the messages until there are none.
+fn request(cluster: &mut Cluster<usize>, client_uuid: Uuid, value: usize) {
+    handle_request(cluster, client_uuid, value);
+
+    cluster.run_until_quiet();
+}
+
+/// Returns the number of times value was delivered by the learner of cluster.
+fn num_of_decisions(cluster: &mut Cluster<usize>, value: usize) -> usize {
+    cluster
+        .learner(0)
+        .total_order_prefix()
+        .iter()
+        .filter(|&&(_, v)| v == value)
+        .count()
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let client_uuid = Uuid::new_v4();
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1);
+    cluster.run_until_quiet();
+
+    for v in 1..=num_of_values {
+        request(&mut cluster, client_uuid, v);
+        request(&mut cluster, client_uuid, v);
+    }
+
+    let ok1 = report(
+        "Test 1 - Each request, sent twice, is decided once",
+        cluster.learner(0).total_order_prefix().len() == num_of_values
+            && (1..=num_of_values).all(|v| num_of_decisions(&mut cluster, v) == 1),
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1)
+        .with_proposers(|p| p.with_request_dedup(Duration::from_secs(60), CAPACITY));
+    cluster.run_until_quiet();
+
+    let last = num_of_values.max(CAPACITY + 1);
+    (1..=last).for_each(|v| request(&mut cluster, client_uuid, v));
+
+    request(&mut cluster, client_uuid, last);
+    request(&mut cluster, client_uuid, 1);
+
+    let ok2 = report(
+        "Test 2 - Beyond the capacity of the dedup cache, the duplicate of a forgotten request is decided again",
+        num_of_decisions(&mut cluster, last) == 1 && num_of_decisions(&mut cluster, 1) == 2,
+    );
+
+    let mut cluster =
+        Cluster::<usize>::new(1, 1, 3, 1).with_proposers(|p| p.with_request_dedup(WINDOW, 1024));
+    cluster.run_until_quiet();
+
+    request(&mut cluster, client_uuid, 1);
+    request(&mut cluster, client_uuid, 1);
+
+    thread::sleep(2 * WINDOW);
+    request(&mut cluster, client_uuid, 1);
+
+    let ok3 = report(
+        "Test 3 - Beyond the window of the dedup cache, the duplicate of a forgotten request is decided again",
+        num_of_decisions(&mut cluster, 1) == 2,
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1);
+    cluster.run_until_quiet();
+
+    for v in 1..=num_of_values {
+        handle_request(&mut cluster, client_uuid, v);
+        handle_request(&mut cluster, client_uuid, v);
+    }
+    cluster.run_until_quiet();
+
+    let ok4 = report(
+        "Test 4 - Each request, delivered twice before it is decided, is decided once",
+        cluster.learner(0).total_order_prefix().len() == num_of_values
+            && (1..=num_of_values).all(|v| num_of_decisions(&mut cluster, v) == 1),
+    );
+
+    if !(ok1 && ok2 && ok3 && ok4) {
+        process::exit(1);
+    }
+}
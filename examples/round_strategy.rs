@@ -0,0 +1,177 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, how a
+//! proposer computes the first round which it starts in an instance (see
+//! Proposer::with_round_strategy), in particular across its restarts. The acceptors of the cluster
+//! are crashed, so that the instances are never decided, and a restarted proposer, which lost its
+//! state, starts the same instance again.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example round_strategy -- [num_of_restarts]
+//!
+//! 1. By default, a restarted proposer starts again the round which it started before the restart.
+//! 2. With a starting epoch, the first round of an instance has that epoch.
+//! 3. With a generation, a proposer restarted num_of_restarts times never starts a round twice:
+//!    each of its rounds is above all the rounds it started before.
+//! 4. With a generation, a proposer which was preempted by a round of a later generation starts
+//!    above that round, after a restart.
+
+extern crate multi_paxos;
+extern crate uuid;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use multi_paxos::message::{Ballot, InstanceId, Message, Promise};
+use multi_paxos::multi_paxos::RoundStrategy;
+use multi_paxos::testkit::{arg_or, report, Cluster};
+use uuid::Uuid;
+
+/// The identifier of the (fake) proposer which starts the rounds of a later generation.
+const OTHER_PROPOSER_ID: usize = 9;
+
+/// Returns the path of a new file, in the temporary directory, where the generation of the
+/// proposer of a test is stored.
+fn generation_path(test: usize) -> PathBuf {
+    let path = env::temp_dir().join(format!("multi_paxos_round_strategy_{}.generation", test));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+/// Returns a cluster whose acceptors are crashed, and whose proposer has the round strategy given
+/// by strategy (which is called again at each restart, see restart).
+fn new_cluster<S: Fn() -> RoundStrategy>(strategy: &S) -> Cluster<usize> {
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1)
+        .with_trace_collector()
+        .with_proposers(|p| {
+            p.with_round_strategy(strategy())
+                .expect("Could not set the round strategy")
+        });
+    cluster.run_until_quiet();
+
+    (0..3).for_each(|i| cluster.crash_acceptor(i));
+    cluster
+}
+
+/// Restarts the proposer of cluster, with the round strategy given by strategy.
+fn restart<S: Fn() -> RoundStrategy>(cluster: &mut Cluster<usize>, strategy: &S) {
+    cluster.restart_proposer(0, |p| {
+        p.with_round_strategy(strategy())
+            .expect("Could not set the round strategy")
+    });
+    cluster.run_until_quiet();
+}
+
+/// Returns the rounds of the Preparation messages sent so far in the instance 1.
+fn rounds(cluster: &Cluster<usize>) -> Vec<Ballot> {
+    cluster
+        .trace_collector()
+        .unwrap()
+        .entries()
+        .iter()
+        .filter_map(|e| match e.message {
+            Message::Phase1a(ref p) if p.instance == InstanceId(1) => Some(p.c_rnd),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns true if rounds are in strictly increasing order, i.e. no round follows a higher or equal
+/// one.
+fn strictly_increasing(rounds: &[Ballot]) -> bool {
+    rounds.windows(2).all(|pair| pair[0] < pair[1])
+}
+
+fn main() {
+    let num_of_restarts: usize = arg_or("number of restarts", 10);
+
+    let strategy = || RoundStrategy::Sequential;
+    let mut cluster = new_cluster(&strategy);
+    cluster.request(0, 1);
+    restart(&mut cluster, &strategy);
+    cluster.request(0, 2);
+
+    let rounds1 = rounds(&cluster);
+
+    let ok1 = report(
+        "Test 1 - By default, a restarted proposer starts again the round it started before",
+        rounds1.len() == 2 && rounds1[0] == rounds1[1],
+    );
+
+    let strategy = || RoundStrategy::StartingAt(100);
+    let mut cluster = new_cluster(&strategy);
+    cluster.request(0, 1);
+
+    let rounds2 = rounds(&cluster);
+
+    let ok2 = report(
+        "Test 2 - With a starting epoch, the first round of an instance has that epoch",
+        rounds2.len() == 1 && rounds2[0].epoch == 100,
+    );
+
+    let path3 = generation_path(3);
+    let strategy = || RoundStrategy::Generation(path3.clone());
+    let mut cluster = new_cluster(&strategy);
+    cluster.request(0, 0);
+
+    for value in 1..=num_of_restarts {
+        restart(&mut cluster, &strategy);
+        cluster.request(0, value);
+    }
+
+    let rounds3 = rounds(&cluster);
+
+    let ok3 = report(
+        "Test 3 - With a generation, a restarted proposer starts above all the rounds it used before",
+        rounds3.len() == num_of_restarts + 1 && strictly_increasing(&rounds3),
+    );
+
+    let path4 = generation_path(4);
+    let strategy = || RoundStrategy::Generation(path4.clone());
+    let mut cluster = new_cluster(&strategy);
+    cluster.request(0, 1);
+
+    // A proposer of a later generation preempts the round of the proposer, which then starts a
+    // round above it.
+    let c_rnd = rounds(&cluster)[0];
+    let later_rnd = Ballot {
+        epoch: c_rnd.epoch * 3 + 5,
+        proposer_id: OTHER_PROPOSER_ID,
+    };
+
+    let proposer_uuid = cluster
+        .trace_collector()
+        .unwrap()
+        .filter(|e| matches!(e.message, Message::Phase1a(_)))[0]
+        .sender_uuid;
+    cluster.proposer(0).handle(Message::Phase1b(Promise {
+        rnd: later_rnd,
+        v_rnd: Ballot::ZERO,
+        v_val: None,
+        sender_uuid: Uuid::from_bytes([1; 16]),
+        receiver_uuid: proposer_uuid,
+        instance: InstanceId(1),
+        group_id: 0,
+        v_summary: None,
+        v_request: None,
+    }));
+    cluster.proposer(0).complete_instance(1);
+    cluster.run_until_quiet();
+
+    restart(&mut cluster, &strategy);
+    cluster.request(0, 2);
+
+    let rounds4 = rounds(&cluster);
+
+    let ok4 = report(
+        "Test 4 - With a generation, a restarted proposer starts above the later generations it used",
+        rounds4.len() == 3 && rounds4[1] > later_rnd && strictly_increasing(&rounds4),
+    );
+
+    let _ = fs::remove_file(&path3);
+    let _ = fs::remove_file(&path4);
+
+    if !(ok1 && ok2 && ok3 && ok4) {
+        process::exit(1);
+    }
+}
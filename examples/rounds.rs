@@ -0,0 +1,232 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, how a
+//! proposer classifies the rounds of the Promise and Acceptance messages of an instance, with
+//! respect to the round which it drives. The acceptors of the cluster are crashed, so that the
+//! proposer only receives the messages injected by this script, on behalf of 3 fake acceptors.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example rounds
+//!
+//! 1. The messages of a stale (i.e. lower) round are ignored: they neither stall the round of the
+//!    proposer, nor make it decide the value voted in the stale round.
+//! 2. The messages of the current round are counted, once per acceptor.
+//! 3. The messages of a higher round preempt the proposer, which backs off: it stops driving its
+//!    round, it learns the value decided in the higher round, and its next round starts after it.
+//! 4. A proposer preempted by the last round (i.e. whose next round would overflow) does not wrap
+//!    around to a lower round: it stops driving the instance, and, once another value is decided in
+//!    it, it proposes the value of its client again in a new instance.
+
+extern crate multi_paxos;
+extern crate uuid;
+
+use std::process;
+
+use multi_paxos::message::{Acceptance, Ballot, InstanceId, Message, Promise};
+use multi_paxos::multi_paxos::DecisionState;
+use multi_paxos::testkit::{report, Cluster};
+use uuid::Uuid;
+
+/// The identifier of the (fake) proposer which starts the higher rounds.
+const OTHER_PROPOSER_ID: usize = 9;
+
+/// Returns the unique identifier of the i-th fake acceptor.
+fn acceptor_uuid(i: u8) -> Uuid {
+    Uuid::from_bytes([i; 16])
+}
+
+/// Returns the Promise of the i-th fake acceptor, in round rnd of instance, to the proposer.
+fn promise(i: u8, rnd: Ballot, proposer_uuid: Uuid, instance: InstanceId) -> Message<usize> {
+    Message::Phase1b(Promise {
+        rnd,
+        v_rnd: Ballot::ZERO,
+        v_val: None,
+        sender_uuid: acceptor_uuid(i),
+        receiver_uuid: proposer_uuid,
+        instance,
+        group_id: 0,
+        v_summary: None,
+        v_request: None,
+    })
+}
+
+/// Returns the Acceptance of the i-th fake acceptor, which voted for v_val in round v_rnd of
+/// instance.
+fn acceptance(i: u8, v_rnd: Ballot, v_val: usize, instance: InstanceId) -> Message<usize> {
+    Message::Phase2b(Acceptance {
+        v_rnd,
+        v_val: Some(v_val),
+        v_request: None,
+        sender_uuid: acceptor_uuid(i),
+        receiver_uuid: Uuid::nil(),
+        instance,
+        group_id: 0,
+    })
+}
+
+/// Returns the rounds of the Preparation messages sent so far in instance, with the unique
+/// identifier of the proposer which sent them.
+fn preparations(cluster: &Cluster<usize>, instance: InstanceId) -> Vec<(Ballot, Uuid)> {
+    cluster
+        .trace_collector()
+        .unwrap()
+        .entries()
+        .iter()
+        .filter_map(|e| match e.message {
+            Message::Phase1a(ref p) if p.instance == instance => Some((p.c_rnd, p.sender_uuid)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the number of the Proposal messages sent so far in instance.
+fn num_of_proposals(cluster: &Cluster<usize>, instance: InstanceId) -> usize {
+    cluster.count_delivered(|m| matches!(m, Message::Phase2a(ref p) if p.instance == instance))
+}
+
+/// Makes the proposer of cluster handle the messages, then delivers the messages it sends.
+fn inject(cluster: &mut Cluster<usize>, messages: Vec<Message<usize>>) {
+    messages
+        .into_iter()
+        .for_each(|m| cluster.proposer(0).handle(m));
+    cluster.run_until_quiet();
+}
+
+fn main() {
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1).with_trace_collector();
+    cluster.run_until_quiet();
+
+    (0..3).for_each(|i| cluster.crash_acceptor(i));
+
+    cluster.request(0, 1);
+
+    let (c_rnd, proposer_uuid) = preparations(&cluster, InstanceId(1))[0];
+    let stale_rnd = Ballot {
+        epoch: c_rnd.epoch - 1,
+        proposer_id: OTHER_PROPOSER_ID,
+    };
+
+    inject(
+        &mut cluster,
+        vec![
+            promise(1, stale_rnd, proposer_uuid, InstanceId(1)),
+            promise(2, stale_rnd, proposer_uuid, InstanceId(1)),
+            acceptance(1, stale_rnd, 99, InstanceId(1)),
+            acceptance(2, stale_rnd, 99, InstanceId(1)),
+        ],
+    );
+
+    let stale_decision = cluster.proposer(0).decision_state(1);
+
+    inject(
+        &mut cluster,
+        vec![
+            promise(1, c_rnd, proposer_uuid, InstanceId(1)),
+            promise(3, c_rnd, proposer_uuid, InstanceId(1)),
+        ],
+    );
+
+    let ok1 = report(
+        "Test 1 - The Promise and Acceptance messages of a stale round are ignored",
+        stale_decision == DecisionState::Unknown && num_of_proposals(&cluster, InstanceId(1)) == 1,
+    );
+
+    inject(
+        &mut cluster,
+        vec![
+            acceptance(1, c_rnd, 1, InstanceId(1)),
+            acceptance(1, c_rnd, 1, InstanceId(1)),
+        ],
+    );
+
+    let one_vote = cluster.proposer(0).decision_state(1);
+
+    inject(&mut cluster, vec![acceptance(3, c_rnd, 1, InstanceId(1))]);
+
+    let ok2 = report(
+        "Test 2 - The messages of the current round are counted once per acceptor",
+        one_vote == DecisionState::Accepted { count: 1 }
+            && cluster.proposer(0).decision_state(1) == DecisionState::Chosen(1)
+            && cluster.learner(0).learned_value(1) == Some(1),
+    );
+
+    // The proposer is preempted in instance 2 by the Acceptance of a higher round, and then in
+    // instance 3 (where it proposes its value again) by a Promise of a higher round.
+    cluster.request(0, 2);
+
+    let (c_rnd, _) = preparations(&cluster, InstanceId(2))[0];
+    let higher_rnd = Ballot {
+        epoch: c_rnd.epoch + 4,
+        proposer_id: OTHER_PROPOSER_ID,
+    };
+
+    inject(
+        &mut cluster,
+        vec![
+            acceptance(1, higher_rnd, 42, InstanceId(2)),
+            promise(1, c_rnd, proposer_uuid, InstanceId(2)),
+            promise(2, c_rnd, proposer_uuid, InstanceId(2)),
+            acceptance(2, higher_rnd, 42, InstanceId(2)),
+        ],
+    );
+
+    let backed_off = num_of_proposals(&cluster, InstanceId(2)) == 0
+        && cluster.proposer(0).decision_state(2) == DecisionState::Chosen(42);
+
+    let (c_rnd, _) = preparations(&cluster, InstanceId(3))[0];
+    let higher_rnd = Ballot {
+        epoch: c_rnd.epoch + 4,
+        proposer_id: OTHER_PROPOSER_ID,
+    };
+
+    inject(
+        &mut cluster,
+        vec![promise(1, higher_rnd, proposer_uuid, InstanceId(3))],
+    );
+    cluster.proposer(0).complete_instance(3);
+    cluster.run_until_quiet();
+
+    let next_rnd = preparations(&cluster, InstanceId(3))
+        .last()
+        .map(|&(rnd, _)| rnd);
+
+    let ok3 = report(
+        "Test 3 - The messages of a higher round preempt the proposer, which backs off",
+        backed_off && next_rnd.is_some_and(|rnd| rnd > higher_rnd),
+    );
+
+    // The proposer is preempted again in instance 3, by a Promise of the last round, after which it
+    // has no round left.
+    let last_rnd = Ballot {
+        epoch: usize::MAX,
+        proposer_id: OTHER_PROPOSER_ID,
+    };
+
+    let num_of_preparations = preparations(&cluster, InstanceId(3)).len();
+
+    inject(
+        &mut cluster,
+        vec![promise(1, last_rnd, proposer_uuid, InstanceId(3))],
+    );
+    cluster.proposer(0).complete_instance(3);
+    cluster.run_until_quiet();
+
+    let no_wrap = preparations(&cluster, InstanceId(3)).len() == num_of_preparations;
+
+    inject(
+        &mut cluster,
+        vec![
+            acceptance(1, last_rnd, 77, InstanceId(3)),
+            acceptance(2, last_rnd, 77, InstanceId(3)),
+        ],
+    );
+
+    let ok4 = report(
+        "Test 4 - A proposer whose next round would overflow stops driving the instance",
+        no_wrap
+            && cluster.proposer(0).decision_state(3) == DecisionState::Chosen(77)
+            && !preparations(&cluster, InstanceId(4)).is_empty(),
+    );
+
+    if !(ok1 && ok2 && ok3 && ok4) {
+        process::exit(1);
+    }
+}
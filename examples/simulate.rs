@@ -18,7 +18,7 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 
 use multi_paxos::configurations::get_config;
-use multi_paxos::multi_paxos::{Acceptor, Client, Learner, Proposer};
+use multi_paxos::multi_paxos::{Acceptor, Client, DeliveryPolicy, Learner, LearningMode, Proposer};
 use multi_paxos::multi_paxos::Runnable;
 
 fn main() {
@@ -105,7 +105,14 @@ fn simulate<T>(value: T)
     for _ in 0..num_of_learners {
         let c = barrier.clone();
         let learner_thread: thread::JoinHandle<_> = thread::spawn(move || {
-            let mut learner = Learner::<T>::new(uid, learners_address, proposers_address);
+            let mut learner = Learner::<T>::new(
+                uid,
+                learners_address,
+                proposers_address,
+                LearningMode::Push,
+                DeliveryPolicy::TotalOrder,
+                None,
+            );
             c.wait();
             learner.run();
         });
@@ -0,0 +1,136 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, the
+//! degenerate sizes of a cluster: with a single acceptor, which is a majority by itself, and with no
+//! acceptor at all, which is rejected.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example single_acceptor -- [num_of_values]
+//!
+//! 1. With a single acceptor, each of the num_of_values values is decided, the first one after a
+//!    single Promise and a single Acceptance.
+//! 2. With a single acceptor, a proposer which completes an instance adopts the value which the
+//!    acceptor voted for, although no node learned it, instead of its no-op.
+//! 3. With a single acceptor, which crashed, no value is decided anymore, i.e. a single acceptor
+//!    tolerates no failure.
+//! 4. A cluster, or a configuration file, without acceptors is rejected.
+
+extern crate multi_paxos;
+
+use std::env;
+use std::fs;
+use std::panic;
+use std::process;
+use std::time::Duration;
+
+use multi_paxos::configurations::try_get_config_with_tunables;
+use multi_paxos::message::Message;
+use multi_paxos::testkit::{arg_or, report, Cluster, Role};
+
+/// How long to wait for a decision, which is much longer than needed, as the messages are delivered
+/// in memory.
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The no-op of the proposers, which the clients never request.
+const NOOP: usize = 0;
+
+/// Returns the number of the messages delivered to the proposers of cluster, for which is_phase
+/// returns true.
+fn count_replies<P: Fn(&Message<usize>) -> bool>(cluster: &Cluster<usize>, is_phase: P) -> usize {
+    cluster
+        .trace_collector()
+        .unwrap()
+        .count(|e| e.receiver == Role::Proposers && is_phase(&e.message))
+}
+
+/// Returns true if a configuration file with size acceptors is rejected.
+fn is_rejected(size: usize) -> bool {
+    let path = env::temp_dir().join(format!("multi_paxos_single_acceptor_{}.toml", size));
+    let config = fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/Config.toml"))
+        .expect("Could not read the configuration file")
+        .replace("size = 3", &format!("size = {}", size));
+    fs::write(&path, config).expect("Could not write the configuration file");
+
+    let rejected = try_get_config_with_tunables(path.to_str().unwrap()).is_err();
+    let _ = fs::remove_file(&path);
+    rejected
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 1, 1).with_trace_collector();
+    cluster.run_until_quiet();
+
+    cluster.client(0).request(1);
+    let first = cluster.wait_for_decision(1, TIMEOUT);
+    let promises = count_replies(&cluster, |m| matches!(m, Message::Phase1b(_)));
+    let acceptances = count_replies(&cluster, |m| matches!(m, Message::Phase2b(_)));
+
+    (2..=num_of_values).for_each(|v| cluster.client(0).request(v));
+    cluster.run_until_quiet();
+
+    let expected: Vec<(usize, usize)> = (1..=num_of_values).map(|v| (v, v)).collect();
+
+    let ok1 = report(
+        "Test 1 - With a single acceptor, each value is decided, the first one after a single promise and acceptance",
+        first == Some(1)
+            && promises == 1
+            && acceptances == 1
+            && cluster.learner(0).total_order_prefix() == &expected[..],
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 2, 1, 1)
+        .with_leader(0)
+        .with_proposers(|p| p.with_noop(NOOP));
+    cluster.run_until_quiet();
+
+    // The acceptor votes for the value, but neither the proposers nor the learner learn it.
+    cluster.client(0).request(7);
+    while {
+        cluster.drop_pending(|receiver, m| {
+            (receiver == Role::Proposers && matches!(m, Message::Phase2b(_)))
+                || (receiver == Role::Learners && matches!(m, Message::Phase3(_)))
+        });
+        cluster.step()
+    } {}
+
+    let unknown = cluster.learner(0).learned_value(1).is_none()
+        && (0..2).all(|i| cluster.proposer(i).export_log().is_empty());
+
+    let forced = cluster.proposer(1).force_noop(1).is_ok();
+    cluster.run_until_quiet();
+
+    let ok2 = report(
+        "Test 2 - With a single acceptor, a proposer adopts the value voted for by the acceptor",
+        unknown
+            && forced
+            && cluster.proposer(1).export_log().get(&1) == Some(&7)
+            && cluster.learner(0).learned_value(1) == Some(7),
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 1, 1);
+    cluster.run_until_quiet();
+
+    cluster.crash_acceptor(0);
+    cluster.client(0).request(1);
+
+    let ok3 = report(
+        "Test 3 - With a single acceptor, which crashed, no value is decided",
+        cluster.wait_for_decision(1, TIMEOUT).is_none()
+            && cluster.learner(0).learned_value(1).is_none(),
+    );
+
+    // The panic is expected, so it is not printed.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let no_acceptors = panic::catch_unwind(|| Cluster::<usize>::new(1, 1, 0, 1));
+    panic::set_hook(hook);
+
+    let ok4 = report(
+        "Test 4 - A cluster, or a configuration file, without acceptors is rejected",
+        no_acceptors.is_err() && is_rejected(0) && !is_rejected(1),
+    );
+
+    if !(ok1 && ok2 && ok3 && ok4) {
+        process::exit(1);
+    }
+}
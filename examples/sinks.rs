@@ -0,0 +1,140 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! learner delivers the decided values to all its sinks (see Learner::with_sink), in the same total
+//! order, and that a failing sink either misses the values which it cannot consume, or blocks the
+//! delivery until it consumes them, depending on its SinkFailurePolicy.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example sinks -- [num_of_values]
+//!
+//! 1. Two sinks consume each of the num_of_values values once, in the order of their instances.
+//! 2. A sink which fails to consume the values of the even instances, and skips them, misses only
+//!    these values, whereas the delivery to the other sink goes on.
+//! 3. A sink which fails to consume the values, and blocks the delivery, stops it at the first
+//!    value, for all the sinks, until it is repaired, after which every sink consumes each value
+//!    once, in order.
+
+extern crate multi_paxos;
+
+use std::io;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use multi_paxos::multi_paxos::{DeliverySink, SinkFailurePolicy};
+use multi_paxos::testkit::{arg_or, report, Cluster};
+
+/// The values consumed by a sink, together with their instances, in the order they were consumed.
+type Consumed = Arc<Mutex<Vec<(usize, usize)>>>;
+
+/// Returns a sink which records the values it consumes in consumed, unless fails returns true for
+/// their instances, in which case it fails to consume them.
+fn recorder<F: Fn(usize) -> bool>(consumed: &Consumed, fails: F) -> impl DeliverySink<usize> {
+    let consumed = consumed.clone();
+
+    move |instance: usize, &value: &usize| {
+        if fails(instance) {
+            return Err(io::Error::other("the sink is broken"));
+        }
+
+        consumed.lock().unwrap().push((instance, value));
+        Ok(())
+    }
+}
+
+/// Requests the values from 1 to num_of_values with the client of cluster, and delivers the
+/// messages until there are none.
+fn request_all(cluster: &mut Cluster<usize>, num_of_values: usize) {
+    cluster.run_until_quiet();
+
+    (1..=num_of_values).for_each(|v| cluster.client(0).request(v));
+
+    cluster.run_until_quiet();
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let (first, second) = (Consumed::default(), Consumed::default());
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1).with_learners(|l| {
+        l.with_sink(recorder(&first, |_| false), SinkFailurePolicy::Block)
+            .with_sink(recorder(&second, |_| false), SinkFailurePolicy::Block)
+    });
+    request_all(&mut cluster, num_of_values);
+
+    let prefix = cluster.learner(0).total_order_prefix().to_vec();
+
+    let ok1 = report(
+        "Test 1 - Two sinks consume each value once, in total order",
+        prefix.len() == num_of_values
+            && *first.lock().unwrap() == prefix
+            && *second.lock().unwrap() == prefix,
+    );
+
+    let (flaky, steady) = (Consumed::default(), Consumed::default());
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1).with_learners(|l| {
+        l.with_sink(
+            recorder(&flaky, |instance| instance % 2 == 0),
+            SinkFailurePolicy::Skip,
+        )
+        .with_sink(recorder(&steady, |_| false), SinkFailurePolicy::Block)
+    });
+    request_all(&mut cluster, num_of_values);
+
+    let prefix = cluster.learner(0).total_order_prefix().to_vec();
+    let odd: Vec<(usize, usize)> = prefix
+        .iter()
+        .copied()
+        .filter(|&(instance, _)| instance % 2 == 1)
+        .collect();
+
+    let ok2 = report(
+        "Test 2 - A failing sink which skips the values misses them, without stopping the delivery",
+        prefix.len() == num_of_values
+            && *flaky.lock().unwrap() == odd
+            && *steady.lock().unwrap() == prefix,
+    );
+
+    let (before, blocking, after) = (
+        Consumed::default(),
+        Consumed::default(),
+        Consumed::default(),
+    );
+    let broken = Arc::new(AtomicBool::new(true));
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1).with_learners(|l| {
+        let broken = broken.clone();
+
+        l.with_sink(recorder(&before, |_| false), SinkFailurePolicy::Block)
+            .with_sink(
+                recorder(&blocking, move |_| broken.load(Ordering::SeqCst)),
+                SinkFailurePolicy::Block,
+            )
+            .with_sink(recorder(&after, |_| false), SinkFailurePolicy::Block)
+    });
+    request_all(&mut cluster, num_of_values);
+
+    let blocked = cluster.learner(0).total_order_prefix().is_empty()
+        && before.lock().unwrap().len() == 1
+        && blocking.lock().unwrap().is_empty()
+        && after.lock().unwrap().is_empty();
+
+    broken.store(false, Ordering::SeqCst);
+    cluster.learner(0).retry_sinks();
+
+    let prefix = cluster.learner(0).total_order_prefix().to_vec();
+
+    let ok3 = report(
+        "Test 3 - A failing sink which blocks the delivery stops it, until it consumes the value",
+        blocked
+            && prefix.len() == num_of_values
+            && [&before, &blocking, &after]
+                .iter()
+                .all(|consumed| *consumed.lock().unwrap() == prefix),
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
@@ -2,8 +2,15 @@
 //! proposers).
 //!
 //! You can run this example as follows
-//!     RUST_LOG=multi_paxos=info cargo run --example start_acceptor -- <acceptor_uid> Config
+//!     RUST_LOG=multi_paxos=info cargo run --example start_acceptor -- [--dry-run] <acceptor_uid> Config [message_log]
 //! where <acceptor_uid> is a non-negative number which should be unique (among all nodes).
+//!
+//! If the optional file name [message_log] is given, all messages sent and received by the
+//! acceptor are written to that file, so that they can later be replayed (see the example replay).
+//!
+//! With --dry-run, the acceptor only validates the configuration file and its network setup (i.e.
+//! it binds its sockets and joins its multicast group), reports the first problem found, if any,
+//! and exits, instead of listening to incoming messages.
 
 extern crate env_logger;
 #[macro_use]
@@ -12,19 +19,30 @@ extern crate multi_paxos;
 extern crate serde;
 
 use std::env;
+use std::process;
 
-use multi_paxos::configurations::get_config;
+use multi_paxos::configurations::{
+    check_node, get_admin_addresses, get_cluster_id, get_config, get_direct_learning,
+    get_log_verbosity, get_max_send_rates, get_memory_soft_cap, get_sender_addresses,
+    get_socket_dir,
+};
+use multi_paxos::message_log::MessageLog;
 use multi_paxos::multi_paxos::Acceptor;
 use multi_paxos::multi_paxos::Runnable;
+use multi_paxos::unix_transport::use_unix_sockets;
+use multi_paxos::verbosity::set_log_verbosity;
 
 fn main() {
     env_logger::init();
 
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
     info!("{:?}", args);
 
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+    args.retain(|arg| arg != "--dry-run");
+
     match args.len() {
-        3 => {
+        3 | 4 => {
             let uid = &args[1];
             let uid: usize = match uid.parse() {
                 Ok(n) => n,
@@ -35,16 +53,81 @@ fn main() {
             };
 
             let config_file_name = &args[2];
+
+            if dry_run {
+                match check_node(config_file_name, "acceptors") {
+                    Ok(()) => println!(
+                        "The configuration and the network setup of acceptor {} are valid.",
+                        uid
+                    ),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        process::exit(1);
+                    }
+                }
+
+                return;
+            }
+
+            if let Some(socket_dir) = get_socket_dir(config_file_name) {
+                use_unix_sockets(socket_dir);
+            }
+
             let config = get_config(config_file_name);
 
             let (_, proposers_address) = config["proposers"];
             let (_, acceptors_address) = config["acceptors"];
+            let (_, learners_address) = config["learners"];
 
             let mut acceptor = Acceptor::<usize>::new(uid, acceptors_address, proposers_address);
+
+            if get_direct_learning(config_file_name) {
+                acceptor = acceptor.with_learners_address(learners_address);
+            }
+
+            if let Some(max_instances) = get_memory_soft_cap(config_file_name) {
+                acceptor = acceptor.with_memory_soft_cap(max_instances);
+            }
+
+            if let Some(&sender_address) = get_sender_addresses(config_file_name).get("acceptors") {
+                acceptor = acceptor.with_sender_address(sender_address);
+            }
+
+            if let Some(&(rate, burst)) = get_max_send_rates(config_file_name).get("acceptors") {
+                acceptor = acceptor.with_max_send_rate(rate, burst);
+            }
+
+            if let Some(&admin_address) = get_admin_addresses(config_file_name).get("acceptors") {
+                acceptor = acceptor
+                    .with_admin_address(admin_address)
+                    .expect("Could not bind the control socket");
+            }
+
+            if let Some(verbosity) = get_log_verbosity(config_file_name) {
+                set_log_verbosity(verbosity);
+            }
+
+            if let Some(cluster_id) = get_cluster_id(config_file_name) {
+                acceptor = acceptor.with_cluster_id(&cluster_id);
+            }
+
+            #[cfg(feature = "encryption")]
+            {
+                if let Some(secret) = multi_paxos::configurations::get_secret(config_file_name) {
+                    acceptor = acceptor.with_encryption_key(&secret);
+                }
+            }
+
+            if let Some(message_log_file_name) = args.get(3) {
+                let message_log = MessageLog::create(message_log_file_name)
+                    .expect("Could not create the message log");
+                acceptor = acceptor.with_message_log(message_log);
+            }
+
             acceptor.run();
         }
         _ => {
-            panic!("Expected 2 arguments (excluding file name)");
+            panic!("Expected 2 or 3 arguments (excluding file name)");
         }
     }
 }
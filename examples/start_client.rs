@@ -20,8 +20,13 @@ use std::env;
 use std::io;
 use std::io::prelude::*;
 
-use multi_paxos::configurations::get_config;
+use multi_paxos::configurations::{
+    get_cluster_id, get_config, get_log_verbosity, get_max_send_rates, get_requests_address,
+    get_sender_addresses, get_socket_dir,
+};
 use multi_paxos::multi_paxos::Client;
+use multi_paxos::unix_transport::use_unix_sockets;
+use multi_paxos::verbosity::set_log_verbosity;
 
 fn main() {
     env_logger::init();
@@ -41,17 +46,48 @@ fn main() {
             };
 
             let config_file_name = &args[2];
+            if let Some(socket_dir) = get_socket_dir(config_file_name) {
+                use_unix_sockets(socket_dir);
+            }
+
             let config = get_config(config_file_name);
 
             let (_, clients_address) = config["clients"];
             let (_, proposers_address) = config["proposers"];
 
-            let client = Client::new(uid, clients_address, proposers_address);
+            let mut client = Client::new(uid, clients_address, proposers_address);
+
+            if let Some(requests_address) = get_requests_address(config_file_name) {
+                client = client.with_requests_address(requests_address);
+            }
+
+            if let Some(&sender_address) = get_sender_addresses(config_file_name).get("clients") {
+                client = client.with_sender_address(sender_address);
+            }
+
+            if let Some(&(rate, burst)) = get_max_send_rates(config_file_name).get("clients") {
+                client = client.with_max_send_rate(rate, burst);
+            }
+
+            if let Some(verbosity) = get_log_verbosity(config_file_name) {
+                set_log_verbosity(verbosity);
+            }
+
+            if let Some(cluster_id) = get_cluster_id(config_file_name) {
+                client = client.with_cluster_id(&cluster_id);
+            }
+
+            #[cfg(feature = "encryption")]
+            {
+                if let Some(secret) = multi_paxos::configurations::get_secret(config_file_name) {
+                    client = client.with_encryption_key(&secret);
+                }
+            }
 
             if len == 3 {
                 loop {
                     print!("Enter the proposal: ");
-                    io::stdout().flush().ok().expect("Could not flush stdout"); // print! is not very clever.
+                    io::stdout().flush().expect("Could not flush stdout"); // print! is not very clever.
                     let value: usize = read!();
                     client.request(value);
                 }
@@ -0,0 +1,190 @@
+//! A script used to start one proposer and one acceptor in the same process, which share the same
+//! socket, and will infinitely listen to incoming messages (from clients, proposers or acceptors),
+//! until the proposer is drained with the command drain of the control socket (see
+//! Proposer::drain). It exits with an error if some instances could not be drained.
+//!
+//! You can run this example as follows
+//!     RUST_LOG=multi_paxos=info cargo run --example start_colocated -- <uid> Config [message_log]
+//! where <uid> is a non-negative number which should be unique (among all nodes).
+//!
+//! The proposers and the acceptors must have the same address (i.e. host and port) in Config.
+//!
+//! If the optional file name [message_log] is given, all messages sent and received by the
+//! proposer and the acceptor are written to that file, so that they can later be replayed (see the
+//! example replay).
+
+extern crate env_logger;
+#[macro_use]
+extern crate log;
+extern crate multi_paxos;
+extern crate serde;
+
+use std::env;
+use std::process;
+
+use multi_paxos::configurations::{
+    get_admin_addresses, get_advertised_addresses, get_alpha, get_backpressure, get_cluster_id,
+    get_config_with_tunables, get_direct_learning, get_learn_acks, get_log_verbosity,
+    get_max_send_rates, get_memory_soft_cap, get_noop, get_prepare_once_per_epoch,
+    get_requests_address, get_sender_addresses, get_socket_dir, get_starvation_threshold,
+    get_unicast_addresses,
+};
+use multi_paxos::message_log::MessageLog;
+use multi_paxos::multi_paxos::CoLocated;
+use multi_paxos::multi_paxos::Runnable;
+use multi_paxos::multi_paxos::StarvationPolicy;
+use multi_paxos::unix_transport::use_unix_sockets;
+use multi_paxos::verbosity::set_log_verbosity;
+
+fn main() {
+    env_logger::init();
+
+    let args: Vec<String> = env::args().collect();
+    info!("{:?}", args);
+
+    match args.len() {
+        3 | 4 => {
+            let uid = &args[1];
+            let uid: usize = match uid.parse() {
+                Ok(n) => n,
+                Err(_) => {
+                    eprintln!("Error: second argument not an usize");
+                    return;
+                }
+            };
+
+            let config_file_name = &args[2];
+            if let Some(socket_dir) = get_socket_dir(config_file_name) {
+                use_unix_sockets(socket_dir);
+            }
+
+            let (config, tunables) = get_config_with_tunables(config_file_name);
+
+            let (_, clients_address) = config["clients"];
+            let (_, proposers_address) = config["proposers"];
+            let (num_of_acceptors, acceptors_address) = config["acceptors"];
+            let (_, learners_address) = config["learners"];
+
+            if proposers_address != acceptors_address {
+                eprintln!("Error: the proposers and the acceptors must have the same address");
+                return;
+            }
+
+            let mut node =
+                CoLocated::<usize>::new(uid, proposers_address, learners_address, num_of_acceptors)
+                    .with_proposer(|proposer| {
+                        proposer
+                            .with_clients_address(clients_address)
+                            .with_gossip_interval(tunables.gossip_interval)
+                            .with_drain_timeout(tunables.drain_timeout)
+                    });
+
+            if let Some(num_of_acks) = get_learn_acks(config_file_name) {
+                node = node.with_proposer(|proposer| {
+                    proposer.with_learn_acks(num_of_acks, tunables.retransmit)
+                });
+            }
+
+            if let Some((max_lag, policy)) = get_backpressure(config_file_name) {
+                node = node.with_proposer(|proposer| proposer.with_backpressure(max_lag, policy));
+            }
+
+            if let Some(alpha) = get_alpha(config_file_name) {
+                node = node.with_proposer(|proposer| proposer.with_alpha(alpha));
+            }
+
+            if let Some(threshold) = get_starvation_threshold(config_file_name) {
+                let policy = StarvationPolicy::BackOff {
+                    backoff_base: tunables.backoff_base,
+                    backoff_cap: tunables.backoff_cap,
+                };
+                node = node
+                    .with_proposer(|proposer| proposer.with_starvation_policy(threshold, policy));
+            }
+
+            if let Some(noop) = get_noop(config_file_name) {
+                node = node.with_proposer(|proposer| proposer.with_noop(noop));
+            }
+
+            if get_prepare_once_per_epoch(config_file_name) {
+                node = node.with_proposer(|proposer| proposer.with_prepare_once_per_epoch());
+            }
+
+            if let Some(max_instances) = get_memory_soft_cap(config_file_name) {
+                node = node.with_acceptor(|acceptor| acceptor.with_memory_soft_cap(max_instances));
+            }
+
+            if get_direct_learning(config_file_name) {
+                node =
+                    node.with_acceptor(|acceptor| acceptor.with_learners_address(learners_address));
+            }
+
+            if let Some(&advertised_address) =
+                get_advertised_addresses(config_file_name).get("proposers")
+            {
+                node = node
+                    .with_proposer(|proposer| proposer.with_advertised_address(advertised_address));
+            }
+
+            if let Some(&unicast_address) = get_unicast_addresses(config_file_name).get("proposers")
+            {
+                node = node.with_unicast_address(unicast_address);
+            }
+
+            if let Some(requests_address) = get_requests_address(config_file_name) {
+                node = node.with_requests_address(requests_address);
+            }
+
+            if let Some(&sender_address) = get_sender_addresses(config_file_name).get("proposers") {
+                node = node.with_sender_address(sender_address);
+            }
+
+            if let Some(&(rate, burst)) = get_max_send_rates(config_file_name).get("proposers") {
+                node = node.with_max_send_rate(rate, burst);
+            }
+
+            if let Some(&admin_address) = get_admin_addresses(config_file_name).get("proposers") {
+                node = node
+                    .with_admin_address(admin_address)
+                    .expect("Could not bind the control socket");
+            }
+
+            if let Some(verbosity) = get_log_verbosity(config_file_name) {
+                set_log_verbosity(verbosity);
+            }
+
+            if let Some(cluster_id) = get_cluster_id(config_file_name) {
+                node = node.with_cluster_id(&cluster_id);
+            }
+
+            #[cfg(feature = "encryption")]
+            {
+                if let Some(secret) = multi_paxos::configurations::get_secret(config_file_name) {
+                    node = node.with_encryption_key(&secret);
+                }
+            }
+
+            if let Some(message_log_file_name) = args.get(3) {
+                let message_log = MessageLog::create(message_log_file_name)
+                    .expect("Could not create the message log");
+                node = node.with_message_log(message_log);
+            }
+
+            // The node only stops once its proposer drained (see the command drain of the
+            // control socket).
+            node.run();
+
+            let undrained = node.proposer().in_flight_instances();
+            if !undrained.is_empty() {
+                eprintln!("Error: the instances {:?} could not be drained", undrained);
+
+                // The answer to the command drain must still be written.
+                drop(node);
+                process::exit(1);
+            }
+        }
+        _ => {
+            panic!("Expected 2 or 3 arguments (excluding file name)");
+        }
+    }
+}
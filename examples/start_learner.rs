@@ -1,8 +1,18 @@
 //! A script used to start one learner, which will infinitely listen to incoming messages.
 //!
 //! You can run this example as follows
-//!     RUST_LOG=multi_paxos=info cargo run --example start_learner -- <learner_uid> Config
+//!     RUST_LOG=multi_paxos=info cargo run --example start_learner -- [--dry-run] <learner_uid> Config [message_log [watermark]]
 //! where <learner_uid> is a non-negative number which should be unique (among all nodes).
+//!
+//! If the optional file name [message_log] is given, all messages sent and received by the
+//! learner are written to that file, so that they can later be replayed (see the example replay).
+//!
+//! If the optional file name [watermark] is also given, the learner stores there the last instance
+//! whose learned value it printed, so that, if it is restarted, it does not print any value twice.
+//!
+//! With --dry-run, the learner only validates the configuration file and its network setup (i.e.
+//! it binds its sockets and joins its multicast group), reports the first problem found, if any,
+//! and exits, instead of listening to incoming messages.
 
 extern crate env_logger;
 #[macro_use]
@@ -11,19 +21,32 @@ extern crate multi_paxos;
 extern crate serde;
 
 use std::env;
+use std::process;
 
-use multi_paxos::configurations::get_config;
+use multi_paxos::configurations::{
+    check_node, get_admin_addresses, get_advertised_addresses, get_backpressure, get_cluster_id,
+    get_config_with_tunables, get_direct_learning, get_learn_acks, get_log_verbosity,
+    get_max_send_rates, get_sender_addresses, get_socket_dir, get_unicast_addresses,
+};
+use multi_paxos::message_log::MessageLog;
+use multi_paxos::multi_paxos::DeliveryPolicy;
 use multi_paxos::multi_paxos::Learner;
+use multi_paxos::multi_paxos::LearningMode;
 use multi_paxos::multi_paxos::Runnable;
+use multi_paxos::unix_transport::use_unix_sockets;
+use multi_paxos::verbosity::set_log_verbosity;
 
 fn main() {
     env_logger::init();
 
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
     info!("{:?}", args);
 
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+    args.retain(|arg| arg != "--dry-run");
+
     match args.len() {
-        3 => {
+        3..=5 => {
             let uid = &args[1];
             let uid: usize = match uid.parse() {
                 Ok(n) => n,
@@ -34,16 +57,110 @@ fn main() {
             };
 
             let config_file_name = &args[2];
-            let config = get_config(config_file_name);
+
+            if dry_run {
+                match check_node(config_file_name, "learners") {
+                    Ok(()) => println!(
+                        "The configuration and the network setup of learner {} are valid.",
+                        uid
+                    ),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        process::exit(1);
+                    }
+                }
+
+                return;
+            }
+
+            if let Some(socket_dir) = get_socket_dir(config_file_name) {
+                use_unix_sockets(socket_dir);
+            }
+
+            let (config, tunables) = get_config_with_tunables(config_file_name);
 
             let (_, learners_address) = config["learners"];
             let (_, proposers_address) = config["proposers"];
+            let (num_of_acceptors, acceptors_address) = config["acceptors"];
+
+            let mut learner = Learner::<usize>::new(
+                uid,
+                learners_address,
+                proposers_address,
+                LearningMode::Push,
+                DeliveryPolicy::TotalOrder,
+                tunables.gap_timeout,
+            )
+            .with_acceptors(acceptors_address, num_of_acceptors);
+
+            if get_direct_learning(config_file_name) {
+                learner = learner.with_direct_learning();
+            }
+
+            if get_learn_acks(config_file_name).is_some() {
+                learner = learner.with_learn_acks();
+            }
+
+            if get_backpressure(config_file_name).is_some() {
+                learner = learner.with_progress_reports(tunables.heartbeat);
+            }
+
+            if let Some(&advertised_address) =
+                get_advertised_addresses(config_file_name).get("learners")
+            {
+                learner = learner.with_advertised_address(advertised_address);
+            }
+
+            if let Some(&unicast_address) = get_unicast_addresses(config_file_name).get("learners")
+            {
+                learner = learner.with_unicast_address(unicast_address);
+            }
+
+            if let Some(&sender_address) = get_sender_addresses(config_file_name).get("learners") {
+                learner = learner.with_sender_address(sender_address);
+            }
+
+            if let Some(&(rate, burst)) = get_max_send_rates(config_file_name).get("learners") {
+                learner = learner.with_max_send_rate(rate, burst);
+            }
+
+            if let Some(&admin_address) = get_admin_addresses(config_file_name).get("learners") {
+                learner = learner
+                    .with_admin_address(admin_address)
+                    .expect("Could not bind the control socket");
+            }
+
+            if let Some(verbosity) = get_log_verbosity(config_file_name) {
+                set_log_verbosity(verbosity);
+            }
+
+            if let Some(cluster_id) = get_cluster_id(config_file_name) {
+                learner = learner.with_cluster_id(&cluster_id);
+            }
+
+            #[cfg(feature = "encryption")]
+            {
+                if let Some(secret) = multi_paxos::configurations::get_secret(config_file_name) {
+                    learner = learner.with_encryption_key(&secret);
+                }
+            }
+
+            if let Some(message_log_file_name) = args.get(3) {
+                let message_log = MessageLog::create(message_log_file_name)
+                    .expect("Could not create the message log");
+                learner = learner.with_message_log(message_log);
+            }
+
+            if let Some(watermark_file_name) = args.get(4) {
+                learner = learner
+                    .with_delivery_watermark(watermark_file_name)
+                    .expect("Could not open the delivery watermark");
+            }
 
-            let mut learner = Learner::<usize>::new(uid, learners_address, proposers_address);
             learner.run();
         }
         _ => {
-            panic!("Expected 2 arguments (excluding file name)");
+            panic!("Expected 2, 3 or 4 arguments (excluding file name)");
         }
     }
 }
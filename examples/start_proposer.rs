@@ -1,9 +1,17 @@
 //! A script used to start one proposer, which will infinitely listen to incoming messages (either
-//! from clients or acceptors.
+//! from clients or acceptors), until it is drained with the command drain of its control socket
+//! (see Proposer::drain). It exits with an error if some instances could not be drained.
 //!
 //! You can run this example as follows
-//!     RUST_LOG=multi_paxos=info cargo run --example start_proposer -- <proposer_uid> Config
+//!     RUST_LOG=multi_paxos=info cargo run --example start_proposer -- [--dry-run] <proposer_uid> Config [message_log]
 //! where <proposer_uid> is a non-negative number which should be unique (among all nodes).
+//!
+//! If the optional file name [message_log] is given, all messages sent and received by the
+//! proposer are written to that file, so that they can later be replayed (see the example replay).
+//!
+//! With --dry-run, the proposer only validates the configuration file and its network setup (i.e.
+//! it binds its sockets and joins its multicast group), reports the first problem found, if any,
+//! and exits, instead of listening to incoming messages.
 
 extern crate env_logger;
 #[macro_use]
@@ -12,19 +20,32 @@ extern crate multi_paxos;
 extern crate serde;
 
 use std::env;
+use std::process;
 
-use multi_paxos::configurations::get_config;
+use multi_paxos::configurations::{
+    check_node, get_admin_addresses, get_advertised_addresses, get_alpha, get_backpressure,
+    get_cluster_id, get_config_with_tunables, get_learn_acks, get_log_verbosity,
+    get_max_send_rates, get_noop, get_prepare_once_per_epoch, get_requests_address,
+    get_sender_addresses, get_socket_dir, get_starvation_threshold, get_unicast_addresses,
+};
+use multi_paxos::message_log::MessageLog;
 use multi_paxos::multi_paxos::Proposer;
 use multi_paxos::multi_paxos::Runnable;
+use multi_paxos::multi_paxos::StarvationPolicy;
+use multi_paxos::unix_transport::use_unix_sockets;
+use multi_paxos::verbosity::set_log_verbosity;
 
 fn main() {
     env_logger::init();
 
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
     info!("{:?}", args);
 
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+    args.retain(|arg| arg != "--dry-run");
+
     match args.len() {
-        3 => {
+        3 | 4 => {
             let uid = &args[1];
             let uid: usize = match uid.parse() {
                 Ok(n) => n,
@@ -35,8 +56,29 @@ fn main() {
             };
 
             let config_file_name = &args[2];
-            let config = get_config(config_file_name);
 
+            if dry_run {
+                match check_node(config_file_name, "proposers") {
+                    Ok(()) => println!(
+                        "The configuration and the network setup of proposer {} are valid.",
+                        uid
+                    ),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        process::exit(1);
+                    }
+                }
+
+                return;
+            }
+
+            if let Some(socket_dir) = get_socket_dir(config_file_name) {
+                use_unix_sockets(socket_dir);
+            }
+
+            let (config, tunables) = get_config_with_tunables(config_file_name);
+
+            let (_, clients_address) = config["clients"];
             let (_, proposers_address) = config["proposers"];
             let (num_of_acceptors, acceptors_address) = config["acceptors"];
             let (_, learners_address) = config["learners"];
@@ -47,11 +89,106 @@ fn main() {
                 acceptors_address,
                 learners_address,
                 num_of_acceptors,
-            );
+            )
+            .with_clients_address(clients_address)
+            .with_gossip_interval(tunables.gossip_interval)
+            .with_drain_timeout(tunables.drain_timeout);
+
+            if let Some(num_of_acks) = get_learn_acks(config_file_name) {
+                proposer = proposer.with_learn_acks(num_of_acks, tunables.retransmit);
+            }
+
+            if let Some((max_lag, policy)) = get_backpressure(config_file_name) {
+                proposer = proposer.with_backpressure(max_lag, policy);
+            }
+
+            if let Some(alpha) = get_alpha(config_file_name) {
+                proposer = proposer.with_alpha(alpha);
+            }
+
+            if let Some(threshold) = get_starvation_threshold(config_file_name) {
+                proposer = proposer.with_starvation_policy(
+                    threshold,
+                    StarvationPolicy::BackOff {
+                        backoff_base: tunables.backoff_base,
+                        backoff_cap: tunables.backoff_cap,
+                    },
+                );
+            }
+
+            if let Some(noop) = get_noop(config_file_name) {
+                proposer = proposer.with_noop(noop);
+            }
+
+            if get_prepare_once_per_epoch(config_file_name) {
+                proposer = proposer.with_prepare_once_per_epoch();
+            }
+
+            if let Some(&advertised_address) =
+                get_advertised_addresses(config_file_name).get("proposers")
+            {
+                proposer = proposer.with_advertised_address(advertised_address);
+            }
+
+            if let Some(&unicast_address) = get_unicast_addresses(config_file_name).get("proposers")
+            {
+                proposer = proposer.with_unicast_address(unicast_address);
+            }
+
+            if let Some(requests_address) = get_requests_address(config_file_name) {
+                proposer = proposer.with_requests_address(requests_address);
+            }
+
+            if let Some(&sender_address) = get_sender_addresses(config_file_name).get("proposers") {
+                proposer = proposer.with_sender_address(sender_address);
+            }
+
+            if let Some(&(rate, burst)) = get_max_send_rates(config_file_name).get("proposers") {
+                proposer = proposer.with_max_send_rate(rate, burst);
+            }
+
+            if let Some(&admin_address) = get_admin_addresses(config_file_name).get("proposers") {
+                proposer = proposer
+                    .with_admin_address(admin_address)
+                    .expect("Could not bind the control socket");
+            }
+
+            if let Some(verbosity) = get_log_verbosity(config_file_name) {
+                set_log_verbosity(verbosity);
+            }
+
+            if let Some(cluster_id) = get_cluster_id(config_file_name) {
+                proposer = proposer.with_cluster_id(&cluster_id);
+            }
+
+            #[cfg(feature = "encryption")]
+            {
+                if let Some(secret) = multi_paxos::configurations::get_secret(config_file_name) {
+                    proposer = proposer.with_encryption_key(&secret);
+                }
+            }
+
+            if let Some(message_log_file_name) = args.get(3) {
+                let message_log = MessageLog::create(message_log_file_name)
+                    .expect("Could not create the message log");
+                proposer = proposer.with_message_log(message_log);
+            }
+
+            // The proposer only stops once it drained (see the command drain of the control
+            // socket).
             proposer.run();
+
+            let undrained = proposer.in_flight_instances();
+            if !undrained.is_empty() {
+                eprintln!("Error: the instances {:?} could not be drained", undrained);
+
+                // The answer to the command drain must still be written.
+                drop(proposer);
+                process::exit(1);
+            }
         }
         _ => {
-            panic!("Expected 2 arguments (excluding file name)");
+            panic!("Expected 2 or 3 arguments (excluding file name)");
         }
     }
 }
@@ -0,0 +1,148 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! proposer whose rounds of an instance keep being preempted by another proposer (i.e. which
+//! starves in it) stops competing for the instance, according to its policy (see
+//! Proposer::with_starvation_policy).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example starvation -- [num_of_rounds]
+//!
+//! In each test, two proposers start in turn a new round of the same instance, for at most
+//! num_of_rounds rounds, each of which preempts the previous one (see the example liveness), but
+//! the second proposer starves after THRESHOLD preempted rounds.
+//! 1. With StarvationPolicy::Yield, the second proposer starts no round after its THRESHOLD
+//!    preempted ones, so the value of the first one is decided, and its own value is decided in the
+//!    next instance.
+//! 2. With StarvationPolicy::BackOff, and a long backoff, the second proposer does not start its
+//!    next round, even when asked to complete the instance again.
+//! 3. With StarvationPolicy::BackOff, and a short backoff, the second proposer starts its next
+//!    round once the backoff is over.
+
+extern crate multi_paxos;
+
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use multi_paxos::multi_paxos::StarvationPolicy;
+use multi_paxos::testkit::{arg_or, report, Choice, Cluster};
+
+/// The number of consecutive preempted rounds after which the second proposer starves.
+const THRESHOLD: usize = 3;
+
+/// The backoff of the test 2, which is long enough for the next round not to start while the test
+/// runs, and the one of the test 3.
+const LONG_BACKOFF: Duration = Duration::from_secs(3600);
+const SHORT_BACKOFF: Duration = Duration::from_millis(200);
+
+/// Returns a cluster whose second proposer starves, according to policy, in an instance where its
+/// last THRESHOLD rounds were preempted.
+fn cluster_with(policy: StarvationPolicy) -> Cluster<usize> {
+    let mut cluster = Cluster::<usize>::new(0, 2, 3, 1);
+    cluster.restart_proposer(1, |p| p.with_starvation_policy(THRESHOLD, policy));
+    cluster.run_until_quiet();
+
+    cluster
+}
+
+/// Makes the two proposers of cluster start in turn a new round of instance 1, in which the i-th
+/// proposer wants to decide 10 + i, until num_of_rounds rounds are started, or until a proposer
+/// does not start its round. Each round is started before the Proposal of the previous one reaches
+/// the acceptors, so it preempts it. Returns the number of rounds started by each proposer.
+fn duel(cluster: &mut Cluster<usize>, num_of_rounds: usize) -> [usize; 2] {
+    let mut started = [1, 0];
+
+    cluster.proposer(0).repropose(1, 10);
+    cluster.step();
+
+    let mut current = 0;
+
+    for _ in 1..num_of_rounds {
+        let other = 1 - current;
+
+        let num_of_promises = cluster.num_of_pending();
+        cluster.proposer(other).repropose(1, 10 + other);
+
+        if cluster.num_of_pending() == num_of_promises {
+            break;
+        }
+        started[other] += 1;
+
+        // The Promises of the current round are delivered, then the Preparation of the other
+        // round, which makes the acceptors reject the Proposal of the current one, and then the
+        // Nack messages of the acceptors, before the Promises of the other round.
+        (0..num_of_promises).for_each(|_| {
+            cluster.step();
+        });
+        cluster.step();
+        cluster.step();
+
+        while cluster.num_of_pending() > num_of_promises {
+            cluster.step_with(Choice::Deliver(num_of_promises));
+        }
+
+        current = other;
+    }
+
+    started
+}
+
+fn main() {
+    let num_of_rounds: usize = arg_or("number of rounds", 100);
+
+    // The second proposer must be able to start more rounds than THRESHOLD.
+    let num_of_rounds = num_of_rounds.max(2 * THRESHOLD + 2);
+
+    let mut cluster = cluster_with(StarvationPolicy::Yield);
+    let started = duel(&mut cluster, num_of_rounds);
+    cluster.run_until_quiet();
+
+    let ok1 = report(
+        "Test 1 - A starving proposer which yields stops competing, and its value is decided in the next instance",
+        started == [THRESHOLD + 1, THRESHOLD]
+            && cluster.proposer(1).consecutive_failures(1) == THRESHOLD
+            && cluster.proposer(1).num_of_starved_instances() == 1
+            && cluster.proposer(0).num_of_starved_instances() == 0
+            && cluster.learner(0).learned_value(1) == Some(10)
+            && cluster.learner(0).learned_value(2) == Some(11),
+    );
+
+    let mut cluster = cluster_with(StarvationPolicy::BackOff {
+        backoff_base: LONG_BACKOFF,
+        backoff_cap: LONG_BACKOFF,
+    });
+    let started = duel(&mut cluster, num_of_rounds);
+
+    // The first proposer, which has no policy, would have kept competing.
+    let held_back = started == [THRESHOLD + 1, THRESHOLD]
+        && cluster.proposer(0).consecutive_failures(1) == THRESHOLD;
+
+    let num_of_pending = cluster.num_of_pending();
+    cluster.proposer(1).complete_instance(1);
+
+    let ok2 = report(
+        "Test 2 - A starving proposer which backs off does not start its next round before the backoff is over",
+        held_back
+            && cluster.num_of_pending() == num_of_pending
+            && cluster.proposer(1).num_of_starved_instances() == 1,
+    );
+
+    let mut cluster = cluster_with(StarvationPolicy::BackOff {
+        backoff_base: SHORT_BACKOFF,
+        backoff_cap: LONG_BACKOFF,
+    });
+    let started = duel(&mut cluster, num_of_rounds);
+
+    thread::sleep(2 * SHORT_BACKOFF);
+
+    let num_of_pending = cluster.num_of_pending();
+    cluster.proposer(1).complete_instance(1);
+
+    let ok3 = report(
+        "Test 3 - A starving proposer which backs off starts its next round once the backoff is over",
+        started == [THRESHOLD + 1, THRESHOLD] && cluster.num_of_pending() > num_of_pending,
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,131 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! proposer which steps down ignores the new client requests, which another proposer serves
+//! instead, but decides its instances in flight, and keeps running (see Proposer::step_down).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example step_down -- [num_of_values]
+//!
+//! 1. The num_of_values instances in flight when the proposer steps down, in the middle of their
+//!    rounds, are decided, whereas a request received afterwards is ignored, and decided by the
+//!    other proposer.
+//! 2. The requests queued by a busy proposer when it steps down are dropped, and only its
+//!    instances in flight are decided.
+//! 3. A proposer which joins after the only proposer stepped down catches up with it, and serves
+//!    the new requests after the values it decided.
+
+extern crate multi_paxos;
+extern crate uuid;
+
+use std::process;
+
+use multi_paxos::message::{Message, Request};
+use multi_paxos::testkit::{arg_or, report, Cluster};
+use uuid::Uuid;
+
+/// The maximum number of instances in flight of the proposer in the test 2.
+const MAX_IN_FLIGHT: usize = 10;
+
+/// Makes the i-th proposer of cluster handle the requests of the given values, sent by the client
+/// with the given uuid, without delivering their messages.
+fn request(cluster: &mut Cluster<usize>, i: usize, client_uuid: Uuid, values: Vec<usize>) {
+    for value in values {
+        cluster.proposer(i).handle(Message::Phase0a(Request {
+            value,
+            sender_uuid: client_uuid,
+            request_seq: value,
+            priority: None,
+            group_id: 0,
+            prefix_from: None,
+        }));
+    }
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let client_uuid = Uuid::new_v4();
+    let values: Vec<usize> = (1..=num_of_values + 1).collect();
+
+    let mut cluster = Cluster::<usize>::new(1, 2, 3, 1);
+    cluster.run_until_quiet();
+
+    request(
+        &mut cluster,
+        0,
+        client_uuid,
+        values[..num_of_values].to_vec(),
+    );
+
+    // Some of the rounds are half-way through when the proposer steps down.
+    for _ in 0..cluster.num_of_pending() {
+        cluster.step();
+    }
+
+    cluster.proposer(0).step_down();
+
+    let in_flight = cluster.proposer(0).in_flight_instances();
+    request(&mut cluster, 0, client_uuid, vec![num_of_values + 1]);
+    let ignored = cluster.proposer(0).in_flight_instances() == in_flight;
+    cluster.run_until_quiet();
+
+    request(&mut cluster, 1, client_uuid, vec![num_of_values + 1]);
+    cluster.run_until_quiet();
+
+    let ok1 = report(
+        "Test 1 - The instances in flight are decided after the proposer steps down, and the new requests are served by the other proposer",
+        ignored
+            && cluster.proposer(0).is_stepped_down()
+            && !cluster.proposer(0).is_stopped()
+            && cluster.proposer(0).in_flight_instances().is_empty()
+            && cluster.delivered_values(0) == values,
+    );
+
+    let mut cluster =
+        Cluster::<usize>::new(1, 1, 3, 1).with_proposers(|p| p.with_max_in_flight(MAX_IN_FLIGHT));
+    cluster.run_until_quiet();
+
+    let num_of_requests = num_of_values.max(MAX_IN_FLIGHT + 1);
+    request(
+        &mut cluster,
+        0,
+        client_uuid,
+        (1..=num_of_requests).collect(),
+    );
+
+    cluster.proposer(0).step_down();
+    cluster.run_until_quiet();
+
+    let ok2 = report(
+        "Test 2 - The queued requests are dropped, and only the instances in flight are decided",
+        cluster.proposer(0).in_flight_instances().is_empty()
+            && cluster.delivered_values(0) == (1..=MAX_IN_FLIGHT).collect::<Vec<usize>>(),
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1);
+    cluster.run_until_quiet();
+
+    request(
+        &mut cluster,
+        0,
+        client_uuid,
+        values[..num_of_values].to_vec(),
+    );
+    cluster.proposer(0).step_down();
+    cluster.run_until_quiet();
+
+    let i = cluster.add_proposer();
+    cluster.run_until_quiet();
+
+    request(&mut cluster, i, client_uuid, vec![num_of_values + 1]);
+    cluster.run_until_quiet();
+
+    let ok3 = report(
+        "Test 3 - A proposer which joins after the proposer stepped down catches up with it, and takes over",
+        cluster.proposer(i).export_log().len() == num_of_values + 1
+            && cluster.delivered_values(0) == values,
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
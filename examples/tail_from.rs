@@ -0,0 +1,107 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! learner which joins late and tails the log from a given instance delivers the values from that
+//! instance on, and only them, whether it is behind or beyond the log (see Learner::tail_from).
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example tail_from -- [num_of_values]
+//!
+//! 1. A learner which joins after num_of_values values were decided, and tails the log from the
+//!    middle, catches up with the values from the middle on, and then delivers the new ones.
+//! 2. A learner which tails the log from an instance beyond it waits for it, and delivers the
+//!    values from that instance on, skipping the previous ones.
+//! 3. A learner which tails the log from an instance which it already delivered does not deliver
+//!    its values again, and goes on with the new ones.
+
+extern crate multi_paxos;
+
+use std::process;
+
+use multi_paxos::testkit::{arg_or, report, Cluster};
+
+/// The number of values decided after the learner of the test 2 tails the log.
+const NUM_OF_NEW_VALUES: usize = 5;
+
+/// How far beyond the log the learner of the test 2 tails it.
+const DISTANCE: usize = 3;
+
+/// Makes the client of cluster send the requests of the given values, one at a time, and delivers
+/// the messages until there are none.
+fn request(cluster: &mut Cluster<usize>, values: impl Iterator<Item = usize>) {
+    for value in values {
+        cluster.request(0, value);
+    }
+}
+
+/// Returns the values delivered by the i-th learner of cluster, together with their instances, in
+/// total order.
+fn delivered(cluster: &mut Cluster<usize>, i: usize) -> Vec<(usize, usize)> {
+    cluster.learner(i).total_order_prefix().to_vec()
+}
+
+/// Returns the values from first to last, together with their instances, i.e. the value of each
+/// instance is the instance itself.
+fn log(first: usize, last: usize) -> Vec<(usize, usize)> {
+    (first..=last).map(|v| (v, v)).collect()
+}
+
+fn main() {
+    let num_of_values: usize = arg_or("number of values", 100);
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1);
+    cluster.run_until_quiet();
+
+    request(&mut cluster, 1..=num_of_values);
+
+    let start = num_of_values / 2 + 1;
+    let i = cluster.add_learner();
+    cluster.learner(i).tail_from(start);
+    cluster.run_until_quiet();
+
+    let caught_up = delivered(&mut cluster, i) == log(start, num_of_values);
+    request(&mut cluster, num_of_values + 1..=num_of_values + 1);
+
+    let ok1 = report(
+        "Test 1 - A learner which tails the log from the middle catches up from there, and delivers the new values",
+        caught_up && delivered(&mut cluster, i) == log(start, num_of_values + 1),
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1);
+    cluster.run_until_quiet();
+
+    request(&mut cluster, 1..=num_of_values);
+
+    let start = num_of_values + DISTANCE;
+    let i = cluster.add_learner();
+    cluster.learner(i).tail_from(start);
+    cluster.run_until_quiet();
+
+    let waiting = delivered(&mut cluster, i).is_empty();
+
+    let last = num_of_values + NUM_OF_NEW_VALUES;
+    request(&mut cluster, num_of_values + 1..=last);
+
+    let ok2 = report(
+        "Test 2 - A learner which tails the log from beyond it waits, and delivers the values from there on",
+        waiting && delivered(&mut cluster, i) == log(start, last),
+    );
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1);
+    cluster.run_until_quiet();
+
+    request(&mut cluster, 1..=num_of_values);
+
+    cluster.learner(0).tail_from(1);
+    cluster.run_until_quiet();
+
+    let not_again = delivered(&mut cluster, 0) == log(1, num_of_values);
+    request(&mut cluster, num_of_values + 1..=num_of_values + 1);
+
+    let ok3 = report(
+        "Test 3 - A learner which tails the log from a delivered instance does not deliver it again",
+        not_again && delivered(&mut cluster, 0) == log(1, num_of_values + 1),
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,65 @@
+//! A script which checks, over the deterministic in-memory transport of the module testkit, that a
+//! proposer with a ValueValidator never proposes the values which it does not validate, and that
+//! it tells their clients so.
+//!
+//! You can run this example as follows
+//!     cargo run --features testkit --example value_validation -- [num_of_values]
+//!
+//! The client requests the values from 1 to 2 * num_of_values, and the proposer only validates the
+//! even ones (an invariant of the application).
+//!
+//! 1. No odd value is ever sent in a Proposal message.
+//! 2. Every even value is decided, in the order it was requested, without gaps.
+//! 3. The client is told that the request of each odd value was rejected.
+
+extern crate multi_paxos;
+
+use std::process;
+
+use multi_paxos::message::Message;
+use multi_paxos::testkit::{arg_or, report, Cluster};
+
+fn main() {
+    let num_of_values = arg_or("number of values", 100);
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1)
+        .with_proposers(|proposer| {
+            proposer.with_value_validator(|_instance: usize, value: &usize| value.is_multiple_of(2))
+        })
+        .with_trace_collector();
+    cluster.run_until_quiet();
+
+    let handles: Vec<(usize, u64)> = (1..=2 * num_of_values)
+        .map(|v| (v, cluster.client(0).request_tracked(v)))
+        .collect();
+    cluster.run_until_quiet();
+
+    let trace = cluster
+        .trace_collector()
+        .expect("Expected a trace collector");
+    let ok1 = report(
+        "Test 1 - The values which are not valid are never proposed",
+        trace.count(
+            |e| matches!(e.message, Message::Phase2a(ref p) if p.c_val.is_some_and(|v| !v.is_multiple_of(2))),
+        ) == 0,
+    );
+
+    let delivered: Vec<(usize, usize)> = cluster.learner(0).total_order_prefix().to_vec();
+    let expected: Vec<(usize, usize)> = (1..=num_of_values).map(|i| (i, 2 * i)).collect();
+    let ok2 = report(
+        "Test 2 - The valid values are all decided, without gaps",
+        delivered == expected,
+    );
+
+    let client = cluster.client(0);
+    let ok3 = report(
+        "Test 3 - The client is told that the values which are not valid are rejected",
+        handles
+            .iter()
+            .all(|&(v, handle)| client.is_rejected(handle) != v.is_multiple_of(2)),
+    );
+
+    if !(ok1 && ok2 && ok3) {
+        process::exit(1);
+    }
+}
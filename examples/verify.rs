@@ -0,0 +1,213 @@
+//! A script used to verify the agreement of a running cluster, i.e. that its proposers and learners
+//! have the same decided value in every instance which several of them know, through their control
+//! sockets (see the key admin_port of the configuration file, and the command log).
+//!
+//! You can run this example as follows
+//!     cargo run --example verify -- <admin_address> <admin_address> ...
+//! where each <admin_address> is the address of the control socket of a proposer, a colocated node
+//! or a learner (e.g. 127.0.0.1:9000). It prints the first instance where two of the nodes diverge,
+//! with what each of them knows about it, and exits with the status 1, or confirms that the nodes
+//! agree. It exits with the status 2 if a node cannot be queried.
+//!
+//! The values are compared as encoded by the nodes, so this works whatever the type of the values.
+//! The nodes may learn new instances while their logs are fetched, but, as a decided value never
+//! changes, this does not affect the verdict.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::process;
+use std::time::Duration;
+
+use multi_paxos::storage::merge_logs;
+
+// The number of instances requested at once, which is the maximum accepted by the nodes.
+const PAGE: usize = 1000;
+
+// How long to wait for an answer, which is longer than the nodes wait for themselves to answer.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A connection to the control socket of a node.
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn open(address: &str) -> io::Result<Self> {
+        let writer = TcpStream::connect(address)?;
+        writer.set_read_timeout(Some(TIMEOUT))?;
+
+        Ok(Connection {
+            reader: BufReader::new(writer.try_clone()?),
+            writer,
+        })
+    }
+
+    /// Sends command, and returns the answer of the node, without the leading `ok`, or the
+    /// description of the problem.
+    fn query(&mut self, command: &str) -> Result<String, String> {
+        writeln!(self.writer, "{}", command).map_err(|e| e.to_string())?;
+
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => return Err("the connection was closed".to_string()),
+            Ok(_) => {}
+            Err(e) => return Err(e.to_string()),
+        }
+
+        let line = line.trim_end();
+        match line.strip_prefix("ok") {
+            Some(answer) => Ok(answer.trim_start().to_string()),
+            None => Err(line.strip_prefix("error ").unwrap_or(line).to_string()),
+        }
+    }
+
+    /// Returns the decided values known by the node, keyed by their instances, in the encoding of
+    /// the command log.
+    fn fetch_log(&mut self) -> Result<BTreeMap<usize, String>, String> {
+        let mut log = BTreeMap::new();
+        let mut from = 0;
+
+        loop {
+            let answer = self.query(&format!("log {} {}", from, PAGE))?;
+            let mut highest = 0;
+
+            for pair in answer.split_whitespace() {
+                let (key, value) = match pair.split_once('=') {
+                    Some(pair) => pair,
+                    None => return Err(format!("unexpected answer {:?}", answer)),
+                };
+
+                match (key, key.parse::<usize>()) {
+                    ("highest", _) => {
+                        highest = value
+                            .parse()
+                            .map_err(|_| format!("unexpected answer {:?}", answer))?
+                    }
+                    (_, Ok(instance)) => {
+                        log.insert(instance, value.to_string());
+                    }
+                    _ => {}
+                }
+            }
+
+            from += PAGE;
+            if from > highest {
+                return Ok(log);
+            }
+        }
+    }
+}
+
+/// A node of the cluster, and the decided values which it knows.
+struct Node {
+    address: String,
+    role: String,
+    connection: Connection,
+    log: BTreeMap<usize, String>,
+}
+
+/// Connects to the node at address, and fetches its role and its log.
+fn fetch(address: &str) -> Result<Node, String> {
+    let mut connection = Connection::open(address).map_err(|e| e.to_string())?;
+
+    let role = connection.query("role")?;
+    let role = role
+        .split_whitespace()
+        .find_map(|pair| pair.strip_prefix("role="))
+        .unwrap_or("unknown")
+        .to_string();
+
+    let log = connection.fetch_log()?;
+
+    Ok(Node {
+        address: address.to_string(),
+        role,
+        connection,
+        log,
+    })
+}
+
+fn main() {
+    let addresses: Vec<String> = env::args().skip(1).collect();
+
+    if addresses.is_empty() {
+        eprintln!("Usage: verify <admin_address> <admin_address> ...");
+        process::exit(2);
+    }
+
+    let mut nodes = Vec::new();
+    for address in &addresses {
+        match fetch(address) {
+            Ok(node) => {
+                println!(
+                    "{} ({}) knows {} decided values.",
+                    node.address,
+                    node.role,
+                    node.log.len()
+                );
+                nodes.push(node);
+            }
+            Err(e) => {
+                eprintln!("Error: could not query {}: {}", address, e);
+                process::exit(2);
+            }
+        }
+    }
+
+    // The first conflict between any two nodes, and all the instances where two nodes diverge.
+    let mut first = None;
+    let mut diverging = BTreeSet::new();
+
+    for i in 0..nodes.len() {
+        for j in i + 1..nodes.len() {
+            if let Err(conflicts) = merge_logs(&nodes[i].log, &nodes[j].log) {
+                diverging.extend(conflicts.iter().map(|conflict| conflict.instance));
+
+                let instance = conflicts[0].instance;
+                if first.is_none_or(|(first, _, _)| instance < first) {
+                    first = Some((instance, i, j));
+                }
+            }
+        }
+    }
+
+    match first {
+        Some((instance, i, j)) => {
+            println!(
+                "The nodes diverge in {} instances, the first of which is the instance {}:",
+                diverging.len(),
+                instance
+            );
+
+            for &k in &[i, j] {
+                let node = &mut nodes[k];
+                let answer = node
+                    .connection
+                    .query(&format!("instance {}", instance))
+                    .unwrap_or_else(|e| format!("error {}", e));
+
+                println!("  {} ({}): {}", node.address, node.role, answer);
+            }
+
+            process::exit(1);
+        }
+        None => {
+            let mut known_by = BTreeMap::new();
+            for node in &nodes {
+                for &instance in node.log.keys() {
+                    *known_by.entry(instance).or_insert(0) += 1;
+                }
+            }
+
+            println!(
+                "The {} nodes agree: {} instances are decided, {} of which are known by several nodes.",
+                nodes.len(),
+                known_by.len(),
+                known_by.values().filter(|&&count| count > 1).count()
+            );
+        }
+    }
+}
@@ -0,0 +1,271 @@
+//! A module which contains the definition of the control socket of a node: an optional TCP
+//! listener, which operators can connect to (e.g. with `nc 127.0.0.1 <port>`) in order to inspect
+//! and nudge a running node, without reading its logs or restarting it.
+//!
+//! The protocol is line-based: each command is a line of text, and it is answered with a single
+//! line, which starts with `ok`, followed by the answer as `key=value` pairs separated by spaces, or
+//! with `error`, followed by a description of the problem. The commands are
+//!
+//! - `role`: the role of the node, and its identifiers.
+//! - `status`: the progress of the node (e.g. the number of instances started or learned).
+//! - `instance <n>`: what the node knows about the instance n (e.g. its decided value).
+//! - `metrics`: the statistics collected by the node (e.g. the latencies of a proposer, or the load
+//!   of an acceptor).
+//! - `force-noop <n>`: makes a proposer propose its no-op in the instance n (see
+//!   Proposer::force_noop). The other roles answer it with an error.
+//! - `log <from> <count>`: the decided values known by a proposer or a learner in the instances
+//!   from, from + 1, ..., from + count - 1 (at most MAX_LOG_PAGE of them), as `<instance>=<value>`
+//!   pairs, where each value is its binary encoding, in hexadecimal, so that the values of
+//!   different nodes can be compared exactly, whatever their type (see examples/verify.rs). The
+//!   pair `highest=<n>` gives the highest instance known by the node, up to which the log can be
+//!   paged through. The acceptors answer it with an error, since they do not know what is decided.
+//! - `drain`: makes a proposer (or the proposer of a CoLocated node) ignore the new client
+//!   requests, and stop once its instances in flight are decided, or once its drain timeout
+//!   elapses (see Proposer::drain). The other roles answer it with an error.
+//! - `step-down`: makes a proposer (or the proposer of a CoLocated node) ignore the new client
+//!   requests, but keep running, e.g. to learn the decided values, once its instances in flight are
+//!   decided (see Proposer::step_down). The other roles answer it with an error.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddrV4, TcpListener, TcpStream};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::Level;
+use serde::Serialize;
+
+/// How long a node with a control socket can go without answering the received commands, at
+/// most, e.g. while it waits for messages.
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long the control socket waits for the node to answer a command.
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The maximum number of instances whose values are given by the answer to the command log.
+pub(crate) const MAX_LOG_PAGE: usize = 1000;
+
+/// A command received by the control socket (see the module documentation).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum Command {
+    Role,
+    Status,
+    Instance(usize),
+    Metrics,
+    ForceNoop(usize),
+    Log { from: usize, count: usize },
+    Drain,
+    StepDown,
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        let instance = |command: &str| -> Result<usize, String> {
+            match words.get(1).map(|n| n.parse()) {
+                Some(Ok(instance)) if words.len() == 2 => Ok(instance),
+                _ => Err(format!("usage: {} <instance>", command)),
+            }
+        };
+
+        match words.first() {
+            Some(&"role") => Ok(Command::Role),
+            Some(&"status") => Ok(Command::Status),
+            Some(&"instance") => Ok(Command::Instance(instance("instance")?)),
+            Some(&"metrics") => Ok(Command::Metrics),
+            Some(&"force-noop") => Ok(Command::ForceNoop(instance("force-noop")?)),
+            Some(&"log") => match (
+                words.get(1).map(|n| n.parse()),
+                words.get(2).map(|n| n.parse()),
+            ) {
+                (Some(Ok(from)), Some(Ok(count))) if words.len() == 3 => {
+                    Ok(Command::Log { from, count })
+                }
+                _ => Err("usage: log <from> <count>".to_string()),
+            },
+            Some(&"drain") => Ok(Command::Drain),
+            Some(&"step-down") => Ok(Command::StepDown),
+            _ => Err(format!("unknown command {:?}", line)),
+        }
+    }
+}
+
+/// The answer to a command: the `key=value` pairs, or a description of the problem.
+pub(crate) type Answer = Result<String, String>;
+
+/// The control socket of a node. The connections are served by other threads, which hand the
+/// received commands over to the node, which answers them when it polls this listener.
+pub(crate) struct AdminListener {
+    commands: Receiver<(Command, Sender<Answer>)>,
+
+    // The number of answers which the node handed over to the connections, but which they have not
+    // written yet.
+    unwritten: Arc<AtomicUsize>,
+}
+
+impl AdminListener {
+    /// Binds the control socket to address (e.g. 127.0.0.1:9000, so that only the local operators
+    /// can connect to it).
+    pub(crate) fn bind(address: SocketAddrV4) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let (sender, commands) = mpsc::channel();
+        let unwritten = Arc::new(AtomicUsize::new(0));
+        let unwritten_answers = Arc::clone(&unwritten);
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let sender = sender.clone();
+                        let unwritten = Arc::clone(&unwritten_answers);
+                        thread::spawn(move || serve(stream, sender, unwritten));
+                    }
+                    Err(e) => warn!("Could not accept a connection to {:?}: {}.", address, e),
+                }
+            }
+        });
+
+        Ok(AdminListener {
+            commands,
+            unwritten,
+        })
+    }
+
+    /// Answers, with answer, the commands received since the last poll, without blocking.
+    pub(crate) fn poll<F: FnMut(Command) -> Answer>(&self, mut answer: F) {
+        while let Ok((command, answer_sender)) = self.commands.try_recv() {
+            if log_enabled!(Level::Info) {
+                info!("I will answer the command {:?}.", command);
+            }
+
+            self.unwritten.fetch_add(1, Ordering::SeqCst);
+
+            // The connection may have been closed in the meantime.
+            if answer_sender.send(answer(command)).is_err() {
+                self.unwritten.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+    }
+}
+
+impl Drop for AdminListener {
+    /// Waits, at most ANSWER_TIMEOUT, for the connections to write the answers of the node, so that
+    /// the answer to its last command (e.g. drain, after which it stops) is not lost.
+    fn drop(&mut self) {
+        let start = Instant::now();
+
+        while self.unwritten.load(Ordering::SeqCst) > 0 && start.elapsed() < ANSWER_TIMEOUT {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+/// Returns the answer to the command log (see the module documentation), given the highest
+/// instance known by the node, and get, which returns the decided value of an instance, if known.
+pub(crate) fn describe_log<'a, T, F>(from: usize, count: usize, highest: usize, get: F) -> Answer
+where
+    T: Serialize + 'a,
+    F: Fn(usize) -> Option<&'a T>,
+{
+    let mut answer = format!("from={} highest={}", from, highest);
+
+    let end = from
+        .saturating_add(count.min(MAX_LOG_PAGE))
+        .min(highest.saturating_add(1));
+
+    for instance in from..end {
+        if let Some(value) = get(instance) {
+            let bytes = bincode::serialize(value).map_err(|e| {
+                format!("could not encode the value of instance {}: {}", instance, e)
+            })?;
+
+            answer.push_str(&format!(" {}=", instance));
+            for byte in bytes {
+                answer.push_str(&format!("{:02x}", byte));
+            }
+        }
+    }
+
+    Ok(answer)
+}
+
+/// Returns timeout, bounded by POLL_INTERVAL if there is a control socket, i.e. how long a node
+/// can wait for a message before it must poll its control socket (if any).
+pub(crate) fn receive_timeout(
+    admin: &Option<AdminListener>,
+    timeout: Option<Duration>,
+) -> Option<Duration> {
+    match (admin, timeout) {
+        (None, _) => timeout,
+        (Some(_), Some(timeout)) => Some(timeout.min(POLL_INTERVAL)),
+        (Some(_), None) => Some(POLL_INTERVAL),
+    }
+}
+
+/// Reads the commands sent on stream, one per line, hands them over to the node, through
+/// commands, and writes back its answers, until the connection is closed. unwritten counts the
+/// answers of the node which are not written yet.
+fn serve(
+    stream: TcpStream,
+    commands: Sender<(Command, Sender<Answer>)>,
+    unwritten: Arc<AtomicUsize>,
+) {
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(e) => {
+            warn!(
+                "Could not serve the connection to the control socket: {}.",
+                e
+            );
+            return;
+        }
+    };
+
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => return,
+        };
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let mut answered = false;
+
+        let answer = line.parse::<Command>().and_then(|command| {
+            let (answer_sender, answer_receiver) = mpsc::channel();
+
+            commands
+                .send((command, answer_sender))
+                .map_err(|_| "the node stopped".to_string())?;
+
+            match answer_receiver.recv_timeout(ANSWER_TIMEOUT) {
+                Ok(answer) => {
+                    answered = true;
+                    answer
+                }
+                Err(_) => Err("the node did not answer in time".to_string()),
+            }
+        });
+
+        let written = match answer {
+            Ok(pairs) => writeln!(writer, "ok {}", pairs),
+            Err(problem) => writeln!(writer, "error {}", problem),
+        };
+
+        if answered {
+            unwritten.fetch_sub(1, Ordering::SeqCst);
+        }
+
+        if written.is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! A module which contains the definition of CommandBatch, a batch of at most N commands, which a
+//! proposer can decide in a single instance, merged from several client requests (see
+//! multi_paxos::Batch and Proposer::with_batching).
+//!
+//! As every value, a batch is Copy, so its commands are kept in an array of N slots, and N bounds
+//! both the size of a batch in memory and the size of the messages which carry it. Only the
+//! commands of a batch are serialized, i.e. not its empty slots.
+
+use std::fmt::{self, Debug, Formatter};
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use crate::multi_paxos::Batch;
+
+/// A batch of at most N commands, in the order they were merged.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CommandBatch<C, const N: usize> {
+    len: usize,
+
+    // The commands of the batch are in the first len slots.
+    commands: [Option<C>; N],
+}
+
+impl<C: Copy, const N: usize> CommandBatch<C, N> {
+    /// Creates a batch of the single command command, e.g. the value of a client request.
+    ///
+    /// Panics if N is zero.
+    pub fn new(command: C) -> Self {
+        assert!(N > 0, "Expected a batch of at least 1 command");
+
+        let mut batch = CommandBatch::default();
+        batch.push(command);
+        batch
+    }
+
+    /// Returns the number of commands of this batch.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether this batch has no command, e.g. if it is a no-op (see
+    /// Proposer::with_noop).
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the commands of this batch, in order.
+    pub fn iter(&self) -> impl Iterator<Item = C> + '_ {
+        self.commands[..self.len].iter().map(|c| c.unwrap())
+    }
+
+    /// Appends command to this batch, if it is not full.
+    fn push(&mut self, command: C) -> bool {
+        if self.len == N {
+            return false;
+        }
+
+        self.commands[self.len] = Some(command);
+        self.len += 1;
+        true
+    }
+}
+
+impl<C: Copy, const N: usize> Default for CommandBatch<C, N> {
+    /// Returns the empty batch.
+    fn default() -> Self {
+        CommandBatch {
+            len: 0,
+            commands: [None; N],
+        }
+    }
+}
+
+impl<C: Copy + Debug, const N: usize> Batch for CommandBatch<C, N> {
+    type Command = C;
+
+    fn merge(&self, other: &Self) -> Option<Self> {
+        if self.len + other.len > N {
+            return None;
+        }
+
+        let mut merged = *self;
+        other.iter().for_each(|c| {
+            merged.push(c);
+        });
+
+        Some(merged)
+    }
+
+    fn commands(&self) -> Vec<C> {
+        self.iter().collect()
+    }
+}
+
+impl<C: Copy + Debug, const N: usize> Debug for CommandBatch<C, N> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<C: Copy + Serialize, const N: usize> Serialize for CommandBatch<C, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, C: Copy + Deserialize<'de>, const N: usize> Deserialize<'de> for CommandBatch<C, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(CommandBatchVisitor(PhantomData))
+    }
+}
+
+/// Deserializes a CommandBatch from the sequence of its commands.
+struct CommandBatchVisitor<C, const N: usize>(PhantomData<C>);
+
+impl<'de, C: Copy + Deserialize<'de>, const N: usize> Visitor<'de> for CommandBatchVisitor<C, N> {
+    type Value = CommandBatch<C, N>;
+
+    fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "a sequence of at most {} commands", N)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut batch = CommandBatch::default();
+
+        while let Some(command) = seq.next_element()? {
+            if !batch.push(command) {
+                return Err(de::Error::invalid_length(N + 1, &self));
+            }
+        }
+
+        Ok(batch)
+    }
+}
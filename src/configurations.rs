@@ -1,36 +1,652 @@
 //! A module that contains functions required to read, parse and return the configuration settings
 //! from the file `Config.toml` at the root of this crate.
 
-// TODO: handle errors more appropriately.
-
 use std::collections::HashMap;
-use std::net::{Ipv4Addr, SocketAddrV4};
+use std::env;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddrV4, TcpListener};
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
+
+use config::{Config, ConfigError, File, FileFormat};
+
+use crate::multi_paxos::BackpressurePolicy;
+use crate::net_node::check_sockets;
+use crate::unix_transport::check_socket;
+use crate::verbosity::LogVerbosity;
+
+/// The name which stands for the built-in configuration, instead of the name of a configuration
+/// file, e.g. `cargo run --example start_acceptor -- 1 builtin`, so that the examples can be run
+/// without any configuration file.
+pub const BUILTIN_CONFIG_NAME: &str = "builtin";
+
+/// The built-in configuration: a cluster whose nodes all run on the same host, i.e. the file
+/// `Config.toml`, which is embedded in the binary.
+const BUILTIN_CONFIG: &str = include_str!("../Config.toml");
+
+/// The name of the optional section of the configuration file which contains the tunables.
+const TUNABLES_SECTION: &str = "tunables";
+
+/// The name of the optional section of the configuration file which contains the name of the
+/// cluster, with the key `id`, the secret which its messages are encrypted with, with the key
+/// `secret`, and the transport of the messages, with the keys `transport` and `socket_dir`.
+const CLUSTER_SECTION: &str = "cluster";
+
+/// The name of the optional section of the configuration file which contains the verbosity of the
+/// logs of the messages, with the key `messages`.
+const LOGGING_SECTION: &str = "logging";
+
+/// The host which the control sockets bind to, unless another one is given with the key
+/// `admin_host`, so that only the local operators can connect to them.
+const DEFAULT_ADMIN_HOST: &str = "127.0.0.1";
 
-use config::{Config, File};
+/// The roles whose sections the configuration file must contain.
+const ROLES: [&str; 4] = ["clients", "proposers", "acceptors", "learners"];
+
+/// The size and the (multicast) address of each role, keyed by role.
+type RolesConfig = HashMap<String, (usize, SocketAddrV4)>;
+
+/// The timeouts and the other tunable parameters of the nodes. Each of them can be specified, in
+/// milliseconds, in the optional section `[tunables]` of the configuration file, with the key given
+/// in its comment. The default value is used for every tunable which is not specified.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Tunables {
+    // retransmit_ms (default: 200). How long a node waits for an answer before sending a message
+    // again.
+    pub retransmit: Duration,
+
+    // heartbeat_ms (default: 100). How often a leader tells the other nodes that it is alive.
+    pub heartbeat: Duration,
+
+    // election_timeout_ms (default: 1000). How long a node waits for a heartbeat before
+    // considering the leader dead. It must be greater than heartbeat.
+    pub election_timeout: Duration,
+
+    // backoff_base_ms (default: 50). The initial delay of an exponential backoff.
+    pub backoff_base: Duration,
+
+    // backoff_cap_ms (default: 2000). The maximum delay of an exponential backoff. It must not be
+    // smaller than backoff_base.
+    pub backoff_cap: Duration,
+
+    // gap_timeout_ms (default: none). How long a learner waits for a missing learned value before
+    // recovering it (see Learner::new). Without it, a learner waits for a missing value
+    // indefinitely.
+    pub gap_timeout: Option<Duration>,
+
+    // gossip_interval_ms (default: 1000). How often a proposer gossips with the other proposers
+    // (see Proposer::with_gossip_interval).
+    pub gossip_interval: Duration,
+
+    // drain_timeout_ms (default: 10000). How long a draining proposer waits for its instances in
+    // flight to be decided before it stops anyway (see Proposer::drain).
+    pub drain_timeout: Duration,
+}
+
+impl Default for Tunables {
+    fn default() -> Self {
+        Tunables {
+            retransmit: Duration::from_millis(200),
+            heartbeat: Duration::from_millis(100),
+            election_timeout: Duration::from_millis(1000),
+            backoff_base: Duration::from_millis(50),
+            backoff_cap: Duration::from_millis(2000),
+            gap_timeout: None,
+            gossip_interval: Duration::from_millis(1000),
+            drain_timeout: Duration::from_millis(10000),
+        }
+    }
+}
 
 pub fn get_config(file_name: &str) -> HashMap<String, (usize, SocketAddrV4)> {
-    let c = read_config(file_name);
-    parse_config(&c)
+    get_config_with_tunables(file_name).0
+}
+
+/// Like get_config, but it also returns the tunables specified in the configuration file.
+pub fn get_config_with_tunables(file_name: &str) -> (HashMap<String, (usize, SocketAddrV4)>, Tunables) {
+    expect_valid(file_name, try_get_config_with_tunables(file_name))
+}
+
+/// Like get_config_with_tunables, but it returns a description of the first problem found in the
+/// configuration file (e.g. a missing section or key, or an invalid address, number or tunable),
+/// instead of panicking.
+pub fn try_get_config_with_tunables(file_name: &str) -> Result<(RolesConfig, Tunables), String> {
+    let c = read_config(file_name)?;
+    Ok((parse_config(&c)?, parse_tunables(&c)?))
+}
+
+/// Returns the advertised addresses specified in the configuration file, keyed by role. The
+/// advertised address of a role is optional and is specified by the keys `advertised_host` and
+/// `advertised_port`. It is only needed if the nodes of that role are not reachable at the address
+/// they bind to (e.g. because they run in a container, or behind a NAT, with port mapping).
+pub fn get_advertised_addresses(file_name: &str) -> HashMap<String, SocketAddrV4> {
+    let c = expect_valid(file_name, read_config(file_name));
+    expect_valid(file_name, parse_addresses(&c, "advertised"))
+}
+
+/// Returns the unicast addresses specified in the configuration file, keyed by role. The unicast
+/// address of a role is optional and is specified by the keys `unicast_host` and `unicast_port`. It
+/// is the address which the nodes of that role also receive their messages at, besides the
+/// multicast address of the role, so that the replies addressed to one of them are only sent to it
+/// (see NetNode::set_unicast_address).
+pub fn get_unicast_addresses(file_name: &str) -> HashMap<String, SocketAddrV4> {
+    let c = expect_valid(file_name, read_config(file_name));
+    expect_valid(file_name, parse_addresses(&c, "unicast"))
+}
+
+/// Returns the multicast group of the client requests, as specified in the section `[proposers]` of
+/// the configuration file, with the optional keys `requests_host` and `requests_port`, if any. If
+/// so, the clients send their requests there, instead of to the multicast group of the proposers,
+/// which the proposers also join (see Proposer::with_requests_address). Its port must differ from
+/// the port of the proposers.
+pub fn get_requests_address(file_name: &str) -> Option<SocketAddrV4> {
+    let c = expect_valid(file_name, read_config(file_name));
+    expect_valid(file_name, parse_requests_address(&c))
+}
+
+/// Returns the sender addresses specified in the configuration file, keyed by role. The sender
+/// address of a role is optional and is specified by the key `sender_host`. It is the local address
+/// which the nodes of that role send their messages from, which determines the network interface
+/// the multicast messages leave through (e.g. on hosts with several network interfaces).
+pub fn get_sender_addresses(file_name: &str) -> HashMap<String, Ipv4Addr> {
+    let c = expect_valid(file_name, read_config(file_name));
+    expect_valid(file_name, parse_sender_addresses(&c))
+}
+
+/// Returns the addresses of the control sockets specified in the configuration file, keyed by role.
+/// The control socket of a role is optional and is specified by the key `admin_port`, and by the
+/// key `admin_host`, which is 127.0.0.1 if it is not given. The nodes of that role answer the
+/// commands of the operators which connect to it (see Proposer::with_admin_address).
+pub fn get_admin_addresses(file_name: &str) -> HashMap<String, SocketAddrV4> {
+    let c = expect_valid(file_name, read_config(file_name));
+    expect_valid(file_name, parse_admin_addresses(&c))
+}
+
+/// Returns the maximum send rates specified in the configuration file, keyed by role, as pairs of a
+/// rate, in messages per second, and a burst, in messages. The maximum send rate of a role is
+/// optional and is specified by the key `max_send_rate`, and by the key `send_burst`, which is the
+/// rate itself (i.e. the messages of one second) if it is not given. The nodes of that role drop the
+/// messages which they send beyond this rate (see NetNode::set_max_send_rate).
+pub fn get_max_send_rates(file_name: &str) -> HashMap<String, (u32, usize)> {
+    let c = expect_valid(file_name, read_config(file_name));
+    expect_valid(file_name, parse_max_send_rates(&c))
+}
+
+/// Returns whether the learners learn the chosen values directly from the acceptors, as specified
+/// in the section `[learners]` of the configuration file, with the optional key `direct_learning`
+/// (by default, "false"). If so, the acceptors also send their Acceptance messages to the learners
+/// (see Acceptor::with_learners_address and Learner::with_direct_learning).
+pub fn get_direct_learning(file_name: &str) -> bool {
+    let c = expect_valid(file_name, read_config(file_name));
+    expect_valid(file_name, parse_direct_learning(&c))
+}
+
+/// Returns the number of learners which must acknowledge the delivery of each learned value, as
+/// specified in the section `[learners]` of the configuration file, with the optional key
+/// `learn_acks`, if any. If so, the learners acknowledge their deliveries, and the proposers send
+/// their Learning messages again, every retransmit interval (see Tunables), until that many
+/// learners acknowledged them (see Proposer::with_learn_acks and Learner::with_learn_acks).
+pub fn get_learn_acks(file_name: &str) -> Option<usize> {
+    let c = expect_valid(file_name, read_config(file_name));
+    expect_valid(file_name, parse_learn_acks(&c))
+}
+
+/// Returns the maximum number of started instances which the learners can have left to deliver,
+/// and which of the learners the proposers wait for, as specified in the section `[learners]` of
+/// the configuration file, with the optional keys `max_lag` and `lag_quorum` (by default, all the
+/// learners are waited for), if any. If so, the learners report their progress, every heartbeat
+/// (see Tunables), and the proposers stop starting new instances while the learners are too far
+/// behind (see Proposer::with_backpressure and Learner::with_progress_reports).
+pub fn get_backpressure(file_name: &str) -> Option<(usize, BackpressurePolicy)> {
+    let c = expect_valid(file_name, read_config(file_name));
+    expect_valid(file_name, parse_backpressure(&c))
+}
+
+/// Returns the window of the pipelining of the proposers, as specified in the section `[proposers]`
+/// of the configuration file, with the optional key `alpha`, if any. If so, a proposer starts at
+/// most alpha instances beyond the chosen prefix of the log, and the membership changes take
+/// effect alpha instances after the instance where they are decided (see Proposer::with_alpha).
+pub fn get_alpha(file_name: &str) -> Option<usize> {
+    let c = expect_valid(file_name, read_config(file_name));
+    expect_valid(file_name, parse_alpha(&c))
+}
+
+/// Returns the number of consecutive preempted rounds of an instance after which a proposer
+/// starves in it, as specified in the section `[proposers]` of the configuration file, with the
+/// optional key `starvation_threshold`, if any. If so, the proposers wait before their next rounds
+/// of such an instance, from backoff_base to backoff_cap (see Tunables and
+/// Proposer::with_starvation_policy).
+pub fn get_starvation_threshold(file_name: &str) -> Option<usize> {
+    let c = expect_valid(file_name, read_config(file_name));
+    expect_valid(file_name, parse_starvation_threshold(&c))
+}
+
+/// Returns the no-op of the proposers, as specified in the section `[proposers]` of the
+/// configuration file, with the optional key `noop`, if any. It must be a value which the clients
+/// never request. If it is given, the proposers can fill a stuck instance with it, e.g. when an
+/// operator sends the command force-noop to their control socket (see Proposer::with_noop and
+/// Proposer::force_noop).
+pub fn get_noop(file_name: &str) -> Option<usize> {
+    let c = expect_valid(file_name, read_config(file_name));
+    expect_valid(file_name, parse_noop(&c))
+}
+
+/// Returns whether the proposers run phase 1 once per epoch, for the whole tail of the log, instead
+/// of once per instance, as specified in the section `[proposers]` of the configuration file, with
+/// the optional key `prepare_once_per_epoch` (by default, "false") (see
+/// Proposer::with_prepare_once_per_epoch).
+pub fn get_prepare_once_per_epoch(file_name: &str) -> bool {
+    let c = expect_valid(file_name, read_config(file_name));
+    expect_valid(file_name, parse_prepare_once_per_epoch(&c))
+}
+
+/// Returns the number of tracked instances above which the acceptors warn that their memory grows,
+/// as specified in the section `[acceptors]` of the configuration file, with the optional key
+/// `memory_soft_cap`, if any (see Acceptor::with_memory_soft_cap).
+pub fn get_memory_soft_cap(file_name: &str) -> Option<usize> {
+    let c = expect_valid(file_name, read_config(file_name));
+    expect_valid(file_name, parse_memory_soft_cap(&c))
+}
+
+/// Returns the verbosity of the logs of the messages (i.e. `off`, `summary` or `full`) specified in
+/// the optional section `[logging]` of the configuration file, with the key `messages`, if any
+/// (see the module verbosity).
+pub fn get_log_verbosity(file_name: &str) -> Option<LogVerbosity> {
+    let c = expect_valid(file_name, read_config(file_name));
+    expect_valid(file_name, parse_log_verbosity(&c))
+}
+
+/// Returns the name of the cluster (e.g. a UUID) specified in the optional section `[cluster]` of
+/// the configuration file, with the key `id`, if any. The nodes of a cluster ignore the messages of
+/// the nodes of the other clusters (see NetNode::set_cluster_id), e.g. of a cluster whose
+/// configuration file was copied, and which thus uses the same multicast addresses.
+pub fn get_cluster_id(file_name: &str) -> Option<String> {
+    let c = expect_valid(file_name, read_config(file_name));
+    c.get(CLUSTER_SECTION).and_then(|section| section.get("id").cloned())
+}
+
+/// Returns the directory of the Unix datagram sockets of the nodes, if they send their messages
+/// over Unix datagram sockets instead of UDP multicast, as specified in the optional section
+/// `[cluster]` of the configuration file, with the key `transport` (i.e. "udp", the default, or
+/// "unix"), and the optional key `socket_dir` (by default, the directory multi-paxos under the
+/// temporary directory). If so, the nodes must all run on the same host, and the addresses of the
+/// configuration file only name the multicast groups (see unix_transport::use_unix_sockets).
+pub fn get_socket_dir(file_name: &str) -> Option<PathBuf> {
+    let c = expect_valid(file_name, read_config(file_name));
+    expect_valid(file_name, parse_socket_dir(&c))
+}
+
+/// Validates the whole configuration file, and the network setup of a node of role (e.g.
+/// "acceptors"), without starting it: the sockets which the node would use are created (i.e.
+/// bound, and joined to the multicast group of role) and closed right away. It returns a
+/// description of the first problem found, if any, e.g. an address which is already in use, or a
+/// multicast group which cannot be joined.
+pub fn check_node(file_name: &str, role: &str) -> Result<(), String> {
+    let c = read_config(file_name)?;
+    let config = parse_config(&c)?;
+
+    parse_tunables(&c)?;
+    parse_addresses(&c, "advertised")?;
+    let unicast_addresses = parse_addresses(&c, "unicast")?;
+    let requests_address = parse_requests_address(&c)?;
+    let sender_addresses = parse_sender_addresses(&c)?;
+    let admin_addresses = parse_admin_addresses(&c)?;
+    parse_max_send_rates(&c)?;
+    parse_direct_learning(&c)?;
+    parse_learn_acks(&c)?;
+    parse_backpressure(&c)?;
+    parse_alpha(&c)?;
+    parse_starvation_threshold(&c)?;
+    parse_noop(&c)?;
+    parse_prepare_once_per_epoch(&c)?;
+    parse_memory_soft_cap(&c)?;
+    parse_log_verbosity(&c)?;
+    let socket_dir = parse_socket_dir(&c)?;
+
+    let (_, address) = config.get(role).ok_or_else(|| format!("Missing section [{}]", role))?;
+
+    // The proposers also join the multicast group of the client requests, if any.
+    let requests_address = requests_address.filter(|_| role == "proposers");
+
+    match socket_dir {
+        Some(socket_dir) => {
+            let unicast_address = unicast_addresses.get(role);
+
+            for group in Some(address).into_iter().chain(unicast_address).chain(requests_address.as_ref()) {
+                check_socket(&socket_dir, group)
+                    .map_err(|e| format!("Could not bind a Unix socket under {:?}: {}", socket_dir, e))?;
+            }
+        }
+        None => {
+            check_sockets(address, sender_addresses.get(role).cloned(), unicast_addresses.get(role).cloned())
+                .map_err(|e| e.to_string())?;
+
+            if let Some(requests_address) = requests_address {
+                check_sockets(&requests_address, None, None).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
+    match admin_addresses.get(role) {
+        Some(admin_address) => TcpListener::bind(admin_address)
+            .map(|_| ())
+            .map_err(|e| format!("Could not bind the control socket to {}: {}", admin_address, e)),
+        None => Ok(()),
+    }
+}
+
+/// Returns the value of result, or panics with its error, which is a problem of the configuration
+/// file file_name.
+fn expect_valid<V>(file_name: &str, result: Result<V, String>) -> V {
+    result.unwrap_or_else(|e| panic!("Invalid configuration file {}: {}", file_name, e))
+}
+
+/// Returns the secret specified in the optional section `[cluster]` of the configuration file, with
+/// the key `secret`, if any. The nodes of the cluster encrypt their messages with a key derived
+/// from it (see NetNode::set_encryption_key), so it must be hard to guess and kept confidential.
+#[cfg(feature = "encryption")]
+pub fn get_secret(file_name: &str) -> Option<String> {
+    let c = expect_valid(file_name, read_config(file_name));
+    c.get(CLUSTER_SECTION).and_then(|section| section.get("secret").cloned())
 }
 
-fn read_config(file_name: &str) -> HashMap<String, HashMap<String, String>> {
+/// Reads the configuration file file_name, or the built-in configuration if file_name is
+/// BUILTIN_CONFIG_NAME.
+fn read_config(file_name: &str) -> Result<HashMap<String, HashMap<String, String>>, String> {
     let mut c = Config::default();
-    c.merge(File::with_name(file_name)).unwrap();
-    c.try_into::<HashMap<String, HashMap<String, String>>>().expect("Could not try_into")
-}
-
-fn parse_config(c: &HashMap<String, HashMap<String, String>>) -> HashMap<String, (usize, SocketAddrV4)> {
-    c.iter().map(|(key, value)| {
-        (
-            key.clone(),
-            (
-                value["size"].parse().unwrap(),
-                SocketAddrV4::new(
-                    Ipv4Addr::from_str(&value["host"]).unwrap(),
-                    value["port"].parse().unwrap(),
-                ),
+
+    let merged = if file_name == BUILTIN_CONFIG_NAME {
+        c.merge(File::from_str(BUILTIN_CONFIG, FileFormat::Toml))
+            .map(|_| ())
+    } else {
+        c.merge(File::with_name(file_name)).map(|_| ())
+    };
+
+    merged.map_err(|e| match e {
+        ConfigError::Foreign(ref cause)
+            if cause
+                .downcast_ref::<io::Error>()
+                .is_some_and(|e| e.kind() == io::ErrorKind::NotFound) =>
+        {
+            format!(
+                "Config file '{}' not found (the name {} stands for the built-in configuration of a cluster on a single host)",
+                file_name, BUILTIN_CONFIG_NAME
+            )
+        }
+        e => e.to_string(),
+    })?;
+
+    c.try_into::<HashMap<String, HashMap<String, String>>>()
+        .map_err(|e| e.to_string())
+}
+
+fn parse_config(c: &HashMap<String, HashMap<String, String>>) -> Result<RolesConfig, String> {
+    if let Some(role) = ROLES.iter().find(|role| !c.contains_key(**role)) {
+        return Err(format!("Missing section [{}]", role));
+    }
+
+    // The nodes must not silently send their messages in clear.
+    if cfg!(not(feature = "encryption")) && c.get(CLUSTER_SECTION).is_some_and(|section| section.contains_key("secret")) {
+        return Err(format!("A secret is given in section [{}], but encryption is not enabled (see the feature encryption)", CLUSTER_SECTION));
+    }
+
+    c.iter().filter(|(key, _)| key.as_str() != TUNABLES_SECTION && key.as_str() != CLUSTER_SECTION).map(|(key, value)| {
+        let get = |k: &str| value.get(k).ok_or_else(|| format!("Missing key {} in section [{}]", k, key));
+
+        let size = get("size")?.parse().map_err(|_| {
+            format!("Expected a number for size in section [{}], but got {:?}", key, value["size"])
+        })?;
+
+        // A value is chosen by a majority of the acceptors, so there must be at least one.
+        if key == "acceptors" && size == 0 {
+            return Err("Expected at least 1 acceptor, but got size 0 in section [acceptors]".to_string());
+        }
+
+        Ok((key.clone(), (size, parse_address(key, "host", get("host")?, "port", get("port")?)?)))
+    }).collect()
+}
+
+/// Returns the addresses specified by the keys `<kind>_host` and `<kind>_port`, keyed by role.
+fn parse_addresses(c: &HashMap<String, HashMap<String, String>>, kind: &str) -> Result<HashMap<String, SocketAddrV4>, String> {
+    let (host_key, port_key) = (format!("{}_host", kind), format!("{}_port", kind));
+
+    c.iter().filter_map(|(key, value)| {
+        match (value.get(&host_key), value.get(&port_key)) {
+            (Some(host), Some(port)) => Some(
+                parse_address(key, &host_key, host, &port_key, port).map(|address| (key.clone(), address)),
             ),
-        )
+            _ => None,
+        }
     }).collect()
 }
+
+fn parse_requests_address(c: &HashMap<String, HashMap<String, String>>) -> Result<Option<SocketAddrV4>, String> {
+    let requests_address = match parse_addresses(c, "requests")?.remove("proposers") {
+        Some(requests_address) => requests_address,
+        None => return Ok(None),
+    };
+
+    // The sockets bind to all the interfaces, so the groups of the same port would not be filtered.
+    match c["proposers"].get("port") {
+        Some(port) if *port == requests_address.port().to_string() => Err(format!(
+            "Expected a requests_port different from the port in section [proposers], but got {:?}", port
+        )),
+        _ => Ok(Some(requests_address)),
+    }
+}
+
+fn parse_admin_addresses(c: &HashMap<String, HashMap<String, String>>) -> Result<HashMap<String, SocketAddrV4>, String> {
+    c.iter().filter_map(|(key, value)| {
+        value.get("admin_port").map(|port| {
+            let host = value.get("admin_host").map_or(DEFAULT_ADMIN_HOST, String::as_str);
+            parse_address(key, "admin_host", host, "admin_port", port).map(|address| (key.clone(), address))
+        })
+    }).collect()
+}
+
+fn parse_max_send_rates(c: &HashMap<String, HashMap<String, String>>) -> Result<HashMap<String, (u32, usize)>, String> {
+    c.iter().filter_map(|(key, value)| {
+        value.get("max_send_rate").map(|rate| {
+            let invalid = |k: &str, v: &str| format!("Expected a positive number for {} in section [{}], but got {:?}", k, key, v);
+
+            let rate: u32 = rate.parse().ok().filter(|&rate| rate > 0).ok_or_else(|| invalid("max_send_rate", rate))?;
+            let burst: usize = match value.get("send_burst") {
+                Some(burst) => burst.parse().ok().filter(|&burst| burst > 0).ok_or_else(|| invalid("send_burst", burst))?,
+                None => rate as usize,
+            };
+
+            Ok((key.clone(), (rate, burst)))
+        })
+    }).collect()
+}
+
+fn parse_direct_learning(c: &HashMap<String, HashMap<String, String>>) -> Result<bool, String> {
+    match c.get("learners").and_then(|section| section.get("direct_learning")) {
+        Some(direct_learning) => direct_learning.parse().map_err(|_| {
+            format!("Expected true or false for direct_learning in section [learners], but got {:?}", direct_learning)
+        }),
+        None => Ok(false),
+    }
+}
+
+fn parse_learn_acks(c: &HashMap<String, HashMap<String, String>>) -> Result<Option<usize>, String> {
+    match c.get("learners").and_then(|section| section.get("learn_acks")) {
+        Some(learn_acks) => learn_acks.parse().ok().filter(|&learn_acks| learn_acks > 0).map(Some).ok_or_else(|| {
+            format!("Expected a positive number for learn_acks in section [learners], but got {:?}", learn_acks)
+        }),
+        None => Ok(None),
+    }
+}
+
+fn parse_backpressure(c: &HashMap<String, HashMap<String, String>>) -> Result<Option<(usize, BackpressurePolicy)>, String> {
+    let section = match c.get("learners") {
+        Some(section) => section,
+        None => return Ok(None),
+    };
+
+    let positive = |key: &str, value: &String| {
+        value.parse().ok().filter(|&value: &usize| value > 0).ok_or_else(|| {
+            format!("Expected a positive number for {} in section [learners], but got {:?}", key, value)
+        })
+    };
+
+    match (section.get("max_lag"), section.get("lag_quorum")) {
+        (Some(max_lag), Some(lag_quorum)) => Ok(Some((positive("max_lag", max_lag)?, BackpressurePolicy::Quorum(positive("lag_quorum", lag_quorum)?)))),
+        (Some(max_lag), None) => Ok(Some((positive("max_lag", max_lag)?, BackpressurePolicy::Slowest))),
+        (None, Some(_)) => Err("A lag_quorum is given in section [learners], but no max_lag".to_string()),
+        (None, None) => Ok(None),
+    }
+}
+
+fn parse_alpha(c: &HashMap<String, HashMap<String, String>>) -> Result<Option<usize>, String> {
+    match c.get("proposers").and_then(|section| section.get("alpha")) {
+        Some(alpha) => alpha.parse().ok().filter(|&alpha| alpha > 0).map(Some).ok_or_else(|| {
+            format!("Expected a positive number for alpha in section [proposers], but got {:?}", alpha)
+        }),
+        None => Ok(None),
+    }
+}
+
+fn parse_starvation_threshold(c: &HashMap<String, HashMap<String, String>>) -> Result<Option<usize>, String> {
+    match c.get("proposers").and_then(|section| section.get("starvation_threshold")) {
+        Some(threshold) => threshold.parse().ok().filter(|&threshold| threshold > 0).map(Some).ok_or_else(|| {
+            format!("Expected a positive number for starvation_threshold in section [proposers], but got {:?}", threshold)
+        }),
+        None => Ok(None),
+    }
+}
+
+fn parse_noop(c: &HashMap<String, HashMap<String, String>>) -> Result<Option<usize>, String> {
+    match c.get("proposers").and_then(|section| section.get("noop")) {
+        Some(noop) => noop.parse().map(Some).map_err(|_| {
+            format!("Expected a non-negative number for noop in section [proposers], but got {:?}", noop)
+        }),
+        None => Ok(None),
+    }
+}
+
+fn parse_prepare_once_per_epoch(c: &HashMap<String, HashMap<String, String>>) -> Result<bool, String> {
+    match c.get("proposers").and_then(|section| section.get("prepare_once_per_epoch")) {
+        Some(prepare_once_per_epoch) => prepare_once_per_epoch.parse().map_err(|_| {
+            format!("Expected true or false for prepare_once_per_epoch in section [proposers], but got {:?}", prepare_once_per_epoch)
+        }),
+        None => Ok(false),
+    }
+}
+
+fn parse_memory_soft_cap(c: &HashMap<String, HashMap<String, String>>) -> Result<Option<usize>, String> {
+    match c.get("acceptors").and_then(|section| section.get("memory_soft_cap")) {
+        Some(max_instances) => max_instances.parse().ok().filter(|&max_instances| max_instances > 0).map(Some).ok_or_else(|| {
+            format!("Expected a positive number for memory_soft_cap in section [acceptors], but got {:?}", max_instances)
+        }),
+        None => Ok(None),
+    }
+}
+
+fn parse_log_verbosity(c: &HashMap<String, HashMap<String, String>>) -> Result<Option<LogVerbosity>, String> {
+    match c.get(LOGGING_SECTION).and_then(|section| section.get("messages")) {
+        Some(verbosity) => verbosity.parse().map(Some),
+        None => Ok(None),
+    }
+}
+
+fn parse_socket_dir(c: &HashMap<String, HashMap<String, String>>) -> Result<Option<PathBuf>, String> {
+    let section = match c.get(CLUSTER_SECTION) {
+        Some(section) => section,
+        None => return Ok(None),
+    };
+
+    match section.get("transport").map(String::as_str) {
+        None | Some("udp") if section.contains_key("socket_dir") => Err(format!(
+            "A socket_dir is given in section [{}], but the transport is not \"unix\"", CLUSTER_SECTION
+        )),
+        None | Some("udp") => Ok(None),
+        Some("unix") => Ok(Some(section.get("socket_dir").map_or_else(
+            || env::temp_dir().join("multi-paxos"),
+            PathBuf::from,
+        ))),
+        Some(transport) => Err(format!(
+            "Expected \"udp\" or \"unix\" for transport in section [{}], but got {:?}", CLUSTER_SECTION, transport
+        )),
+    }
+}
+
+fn parse_sender_addresses(c: &HashMap<String, HashMap<String, String>>) -> Result<HashMap<String, Ipv4Addr>, String> {
+    c.iter().filter_map(|(key, value)| {
+        value.get("sender_host").map(|host| parse_host(key, "sender_host", host).map(|host| (key.clone(), host)))
+    }).collect()
+}
+
+/// Returns the address whose host and port are the values of the keys host_key and port_key of
+/// section.
+fn parse_address(section: &str, host_key: &str, host: &str, port_key: &str, port: &str) -> Result<SocketAddrV4, String> {
+    let port = port.parse().map_err(|_| {
+        format!("Expected a port for {} in section [{}], but got {:?}", port_key, section, port)
+    })?;
+
+    Ok(SocketAddrV4::new(parse_host(section, host_key, host)?, port))
+}
+
+fn parse_host(section: &str, key: &str, host: &str) -> Result<Ipv4Addr, String> {
+    Ipv4Addr::from_str(host).map_err(|_| {
+        format!("Expected an IPv4 address for {} in section [{}], but got {:?}", key, section, host)
+    })
+}
+
+fn parse_tunables(c: &HashMap<String, HashMap<String, String>>) -> Result<Tunables, String> {
+    let mut tunables = Tunables::default();
+
+    if let Some(section) = c.get(TUNABLES_SECTION) {
+        let parse = |key: &str, default: Duration| -> Result<Duration, String> {
+            match section.get(key) {
+                Some(ms) => ms.parse().map(Duration::from_millis).map_err(|_| {
+                    format!("Expected a number of milliseconds for {}, but got {:?}", key, ms)
+                }),
+                None => Ok(default),
+            }
+        };
+
+        tunables = Tunables {
+            retransmit: parse("retransmit_ms", tunables.retransmit)?,
+            heartbeat: parse("heartbeat_ms", tunables.heartbeat)?,
+            election_timeout: parse("election_timeout_ms", tunables.election_timeout)?,
+            backoff_base: parse("backoff_base_ms", tunables.backoff_base)?,
+            backoff_cap: parse("backoff_cap_ms", tunables.backoff_cap)?,
+            gap_timeout: match section.get("gap_timeout_ms") {
+                Some(_) => Some(parse("gap_timeout_ms", Duration::from_millis(0))?),
+                None => None,
+            },
+            gossip_interval: parse("gossip_interval_ms", tunables.gossip_interval)?,
+            drain_timeout: parse("drain_timeout_ms", tunables.drain_timeout)?,
+        };
+    }
+
+    validate_tunables(&tunables)?;
+
+    Ok(tunables)
+}
+
+fn validate_tunables(tunables: &Tunables) -> Result<(), String> {
+    let all = [
+        tunables.retransmit,
+        tunables.heartbeat,
+        tunables.election_timeout,
+        tunables.backoff_base,
+        tunables.backoff_cap,
+        tunables.gossip_interval,
+        tunables.drain_timeout,
+    ];
+
+    if !all.iter().chain(tunables.gap_timeout.as_ref()).all(|d| *d > Duration::from_millis(0)) {
+        return Err(format!("All tunables must be positive: {:?}", tunables));
+    }
+    if tunables.election_timeout <= tunables.heartbeat {
+        return Err(format!("election_timeout_ms must be greater than heartbeat_ms: {:?}", tunables));
+    }
+    if tunables.backoff_cap < tunables.backoff_base {
+        return Err(format!("backoff_cap_ms must not be smaller than backoff_base_ms: {:?}", tunables));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,196 @@
+//! A module which contains the definition of the fragmentation of the messages which do not fit in
+//! a single datagram (e.g. because their values are large), into numbered fragments, each of which
+//! is sent in a datagram of its own, and of their reassembly by the receivers (see
+//! NetNode::set_max_datagram_size).
+//!
+//! Each fragment starts with FRAGMENT_MARKER, followed by the identifier of its message (which is
+//! random), its index, and the number of fragments of its message. A message is only reassembled
+//! once all its fragments are received: if any of them is lost, the others are discarded after
+//! REASSEMBLY_TIMEOUT, i.e. the message is lost as a whole, as if it had been sent in a single
+//! datagram, so the nodes rely on their retransmissions, as usual.
+
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryInto;
+use std::time::{Duration, Instant};
+
+/// The first bytes of every fragment. A whole message never starts with them: its first bytes are
+/// the index of its variant (see the module message), or the random nonce of its encryption, which
+/// is drawn again if it starts with them.
+pub(crate) const FRAGMENT_MARKER: [u8; 4] = [0xff; 4];
+
+/// The number of bytes of the header of a fragment: the marker, the identifier of its message, its
+/// index and the number of fragments of its message.
+pub(crate) const FRAGMENT_HEADER_SIZE: usize = 16;
+
+/// How long the fragments of an incomplete message are kept, waiting for the missing ones.
+const REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The maximum number of incomplete messages whose fragments are kept at once. Beyond it, the
+/// oldest ones are discarded, so that the lost fragments cannot exhaust the memory.
+const MAX_INCOMPLETE_MESSAGES: usize = 1024;
+
+/// The size of the largest message which is reassembled. The fragments of a message which would be
+/// larger (according to their number and their size) are malformed.
+const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Returns whether payload (i.e. the content of a datagram, after the identifier of the cluster)
+/// is a fragment, rather than a whole message.
+pub(crate) fn is_fragment(payload: &[u8]) -> bool {
+    payload.starts_with(&FRAGMENT_MARKER)
+}
+
+/// Splits payload into fragments, each of which carries at most chunk_size bytes of it.
+///
+/// Panics if payload needs more than u16::MAX fragments.
+pub(crate) fn fragment(payload: &[u8], chunk_size: usize) -> Vec<Vec<u8>> {
+    let message_id: u64 = rand::random();
+    let chunks: Vec<&[u8]> = payload.chunks(chunk_size).collect();
+    let num_of_fragments: u16 = chunks
+        .len()
+        .try_into()
+        .expect("The message has too many fragments");
+
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let mut fragment = FRAGMENT_MARKER.to_vec();
+            fragment.extend_from_slice(&message_id.to_le_bytes());
+            fragment.extend_from_slice(&(index as u16).to_le_bytes());
+            fragment.extend_from_slice(&num_of_fragments.to_le_bytes());
+            fragment.extend_from_slice(chunk);
+            fragment
+        })
+        .collect()
+}
+
+/// What a Reassembler did with a received fragment.
+pub(crate) enum Reassembly {
+    /// The fragment was the last missing one of its message, whose payload is given.
+    Complete(Vec<u8>),
+
+    /// The fragment was kept, until the other fragments of its message are received.
+    Incomplete,
+
+    /// The fragment is malformed (e.g. its index is beyond the number of fragments of its
+    /// message, or its message would be larger than MAX_MESSAGE_SIZE), and it was dropped.
+    Malformed,
+}
+
+/// The fragments received so far of a message, by index. They are only allocated as they are
+/// received, so a forged number of fragments does not allocate anything.
+struct IncompleteMessage {
+    fragments: BTreeMap<usize, Vec<u8>>,
+    num_of_fragments: usize,
+    size: usize,
+    first_received_at: Instant,
+}
+
+/// The fragments of the messages which are not complete yet, by message.
+pub(crate) struct Reassembler {
+    messages: HashMap<u64, IncompleteMessage>,
+
+    // The number of incomplete messages discarded by add, because there were too many of them,
+    // since the last call to expire.
+    num_of_evicted: usize,
+}
+
+impl Reassembler {
+    pub(crate) fn new() -> Self {
+        Reassembler {
+            messages: HashMap::new(),
+            num_of_evicted: 0,
+        }
+    }
+
+    /// Adds fragment (see is_fragment), received at now, to its message, and returns the payload
+    /// of its message, if it was the last missing fragment. The fragment is checked before it is
+    /// kept, and, if it is the first one of its message, while MAX_INCOMPLETE_MESSAGES messages are
+    /// already incomplete, the oldest of them is discarded.
+    pub(crate) fn add(&mut self, fragment: &[u8], now: Instant) -> Reassembly {
+        if fragment.len() <= FRAGMENT_HEADER_SIZE {
+            return Reassembly::Malformed;
+        }
+
+        let (header, chunk) = fragment.split_at(FRAGMENT_HEADER_SIZE);
+        let message_id = u64::from_le_bytes(header[4..12].try_into().unwrap());
+        let index = u16::from_le_bytes(header[12..14].try_into().unwrap()) as usize;
+        let num_of_fragments = u16::from_le_bytes(header[14..16].try_into().unwrap()) as usize;
+
+        // All the fragments of a message, but the last one, carry as many bytes.
+        let is_last = index + 1 == num_of_fragments;
+        if index >= num_of_fragments
+            || (!is_last && num_of_fragments > MAX_MESSAGE_SIZE / chunk.len())
+        {
+            return Reassembly::Malformed;
+        }
+
+        match self.messages.get(&message_id) {
+            Some(message)
+                if message.num_of_fragments != num_of_fragments
+                    || message.size + chunk.len() > MAX_MESSAGE_SIZE =>
+            {
+                return Reassembly::Malformed;
+            }
+            Some(_) => {}
+            None => {
+                if self.messages.len() >= MAX_INCOMPLETE_MESSAGES {
+                    self.evict_oldest();
+                }
+            }
+        }
+
+        let message = self
+            .messages
+            .entry(message_id)
+            .or_insert_with(|| IncompleteMessage {
+                fragments: BTreeMap::new(),
+                num_of_fragments,
+                size: 0,
+                first_received_at: now,
+            });
+
+        if let Entry::Vacant(entry) = message.fragments.entry(index) {
+            entry.insert(chunk.to_vec());
+            message.size += chunk.len();
+        }
+
+        if message.fragments.len() < num_of_fragments {
+            return Reassembly::Incomplete;
+        }
+
+        let message = self.messages.remove(&message_id).unwrap();
+        Reassembly::Complete(message.fragments.into_values().flatten().collect())
+    }
+
+    /// Discards the incomplete message whose first fragment was received first.
+    fn evict_oldest(&mut self) {
+        let oldest = self
+            .messages
+            .iter()
+            .min_by_key(|(_, message)| message.first_received_at)
+            .map(|(&message_id, _)| message_id);
+
+        if let Some(message_id) = oldest {
+            self.messages.remove(&message_id);
+            self.num_of_evicted += 1;
+        }
+    }
+
+    /// Discards the messages whose first fragment was received more than REASSEMBLY_TIMEOUT before
+    /// now, and returns their number, together with the number of the ones discarded by add since
+    /// the last call.
+    pub(crate) fn expire(&mut self, now: Instant) -> usize {
+        let num_of_messages = self.messages.len();
+
+        self.messages.retain(|_, message| {
+            now.saturating_duration_since(message.first_received_at) <= REASSEMBLY_TIMEOUT
+        });
+
+        let num_of_evicted = self.num_of_evicted;
+        self.num_of_evicted = 0;
+
+        num_of_messages - self.messages.len() + num_of_evicted
+    }
+}
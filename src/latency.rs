@@ -0,0 +1,97 @@
+//! A module which contains the definition of a histogram of latencies, e.g. of the time which a
+//! proposer takes to decide an instance. Unlike an average, it reveals the tail latencies (e.g.
+//! caused by retries or by competing proposers).
+
+use std::time::Duration;
+
+/// The number of buckets of a histogram. The last bucket also contains all the latencies which are
+/// greater than its upper bound (i.e. about 36 minutes).
+const NUM_OF_BUCKETS: usize = 32;
+
+/// A histogram of latencies, with buckets whose bounds (in microseconds) are powers of 2, so that
+/// the relative error of a percentile is at most a factor of 2, whatever the latencies are.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LatencyHistogram {
+    // The number of latencies in each bucket. The bucket i contains the latencies which are smaller
+    // than 2^i microseconds, but not smaller than 2^(i-1) microseconds.
+    buckets: [usize; NUM_OF_BUCKETS],
+
+    // The number of latencies recorded so far, their sum and their maximum.
+    count: usize,
+    sum: Duration,
+    max: Duration,
+}
+
+impl LatencyHistogram {
+    /// Returns the number of latencies recorded so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the mean of the latencies recorded so far (zero if there are none).
+    pub fn mean(&self) -> Duration {
+        if self.count == 0 {
+            return Duration::from_secs(0);
+        }
+
+        self.sum.div_f64(self.count as f64)
+    }
+
+    /// Returns the maximum of the latencies recorded so far (zero if there are none).
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// Returns an upper bound of the latency which p percent (between 0 and 100) of the recorded
+    /// latencies do not exceed, i.e. the upper bound of the bucket of that percentile, but at most
+    /// the maximum recorded latency.
+    pub fn percentile(&self, p: f64) -> Duration {
+        assert!(
+            (0.0..=100.0).contains(&p),
+            "Expected a percentile between 0 and 100"
+        );
+
+        let rank = ((p / 100.0) * self.count as f64).ceil() as usize;
+        let mut num_of_latencies = 0;
+
+        for (i, &n) in self.buckets.iter().enumerate() {
+            num_of_latencies += n;
+
+            if num_of_latencies >= rank.max(1) {
+                return upper_bound(i).min(self.max);
+            }
+        }
+
+        self.max
+    }
+
+    /// Returns the non-empty buckets, as pairs of the upper bound of the bucket and the number of
+    /// latencies in it, from the smallest to the largest bound.
+    pub fn buckets(&self) -> Vec<(Duration, usize)> {
+        self.buckets
+            .iter()
+            .enumerate()
+            .filter(|&(_, &n)| n > 0)
+            .map(|(i, &n)| (upper_bound(i), n))
+            .collect()
+    }
+
+    /// Records the latency.
+    pub(crate) fn record(&mut self, latency: Duration) {
+        let micros = latency.as_micros();
+
+        // The number of bits of micros, i.e. the bucket of micros (e.g. 0 for 0, 1 for 1, 2 for 2
+        // and 3, 3 for 4 to 7).
+        let i = (128 - micros.leading_zeros()) as usize;
+
+        self.buckets[i.min(NUM_OF_BUCKETS - 1)] += 1;
+        self.count += 1;
+        self.sum += latency;
+        self.max = self.max.max(latency);
+    }
+}
+
+/// Returns the (exclusive) upper bound of the bucket i.
+fn upper_bound(i: usize) -> Duration {
+    Duration::from_micros(1 << i)
+}
@@ -1,16 +1,41 @@
+#[cfg(feature = "encryption")]
+extern crate aes_gcm;
 extern crate bincode;
 extern crate config;
+extern crate crc32fast;
 extern crate env_logger;
 #[macro_use]
 extern crate log;
+#[cfg(feature = "metrics")]
+extern crate metrics;
 extern crate net2;
 extern crate rand;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+#[cfg(feature = "encryption")]
+extern crate sha2;
 extern crate uuid;
 
+mod admin;
+mod fragmentation;
 mod net_node;
+mod rate_limit;
+mod recent_requests;
+mod reply_cache;
 pub mod multi_paxos;
+pub mod batch;
 pub mod configurations;
-pub mod message;
\ No newline at end of file
+pub mod latency;
+pub mod load;
+pub mod message;
+pub mod message_log;
+pub mod ping;
+pub mod state_machine;
+pub mod storage;
+#[cfg(feature = "metrics")]
+pub mod telemetry;
+pub mod unix_transport;
+pub mod verbosity;
+#[cfg(feature = "testkit")]
+pub mod testkit;
\ No newline at end of file
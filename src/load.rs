@@ -0,0 +1,99 @@
+//! A module which contains the definition of the statistics which a node keeps about its load, in
+//! order to estimate whether it keeps up with the messages it receives. If it does not, the
+//! datagrams queue up in the buffer of its socket, until the kernel (invisibly) drops them. It also
+//! contains the definition of the report of the memory which an acceptor uses for the state of the
+//! instances it tracks.
+
+use std::time::Duration;
+
+/// The weight of the most recent sample in the moving averages.
+const SMOOTHING: f64 = 0.1;
+
+/// The utilization above which a node is considered overloaded.
+const OVERLOAD_UTILIZATION: f64 = 0.9;
+
+/// The load statistics of a node. The averages are exponentially weighted moving averages, so
+/// they mostly reflect the recent load.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct LoadStats {
+    // The number of messages received so far.
+    pub num_of_received: usize,
+
+    // The number of received messages which were dropped by the admission control, without being
+    // processed.
+    pub num_of_shed: usize,
+
+    // The average time between the receipt of two consecutive messages.
+    pub avg_interarrival_time: Duration,
+
+    // The average time spent processing a message.
+    pub avg_processing_time: Duration,
+}
+
+impl LoadStats {
+    /// Returns the estimated fraction of the time which is spent processing messages. If it is
+    /// close to (or above) 1, the node cannot keep up with the messages it receives.
+    pub fn utilization(&self) -> f64 {
+        let interarrival_time = self.avg_interarrival_time.as_secs_f64();
+
+        if interarrival_time == 0.0 {
+            return 0.0;
+        }
+
+        self.avg_processing_time.as_secs_f64() / interarrival_time
+    }
+
+    /// Returns the estimated number of messages waiting to be processed (as in an M/M/1 queue),
+    /// which is infinite if the node cannot keep up with the messages it receives.
+    pub fn estimated_queue_depth(&self) -> f64 {
+        let utilization = self.utilization();
+
+        if utilization >= 1.0 {
+            f64::INFINITY
+        } else {
+            utilization / (1.0 - utilization)
+        }
+    }
+
+    /// Returns true if the node is (almost) not able to keep up with the messages it receives.
+    pub fn is_overloaded(&self) -> bool {
+        self.utilization() >= OVERLOAD_UTILIZATION
+    }
+
+    /// Records the receipt of a message, which arrived interarrival_time after the previous one
+    /// (None if it is the first one).
+    pub(crate) fn record_arrival(&mut self, interarrival_time: Option<Duration>) {
+        self.num_of_received += 1;
+
+        if let Some(interarrival_time) = interarrival_time {
+            self.avg_interarrival_time = average(self.avg_interarrival_time, interarrival_time);
+        }
+    }
+
+    /// Records that a received message was dropped by the admission control.
+    pub(crate) fn record_shed(&mut self) {
+        self.num_of_shed += 1;
+    }
+
+    /// Records that a message was processed in processing_time.
+    pub(crate) fn record_processing(&mut self, processing_time: Duration) {
+        self.avg_processing_time = average(self.avg_processing_time, processing_time);
+    }
+}
+
+/// The memory used by an acceptor for the state of the instances it tracks (see
+/// Acceptor::memory_report). As an acceptor keeps the state of every instance until its log is
+/// truncated, this grows with the log.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    // The number of instances whose state the acceptor keeps.
+    pub num_of_instances: usize,
+
+    // An estimate, in bytes, of the memory held by the state of these instances, including the
+    // overhead of the map which holds them (but not the memory which the values point to, if any).
+    pub estimated_bytes: usize,
+}
+
+fn average(avg: Duration, sample: Duration) -> Duration {
+    avg.mul_f64(1.0 - SMOOTHING) + sample.mul_f64(SMOOTHING)
+}
@@ -1,23 +1,146 @@
 //! A module which contains the definition of the messages used in the Multi-Paxos algorithm.
+//!
+//! The wire format of the messages is the following contract:
+//!
+//! - Every variant of the enums below has an explicit (snake case) name, which does not depend on
+//!   the name of the Rust variant, so that the messages encoded with a self-describing format
+//!   (e.g. JSON, when inspecting or debugging them) are readable and keep their meaning even if
+//!   the variants are renamed or reordered.
+//! - The nodes themselves encode the messages with bincode, which identifies a variant by its
+//!   position (not by its name) and does not support the internally tagged representation
+//!   (i.e. `#[serde(tag = "...")]`). So, in that format, new variants and new fields must only be
+//!   appended at the end, and existing ones must never be reordered or removed, otherwise nodes
+//!   with different versions cannot decode the messages of each other (nor the existing message
+//!   logs).
 
 // TODO: can the messages be structured in a cleaner (and still flexible) way?
 
 use std::collections::HashMap;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::net::SocketAddrV4;
 
 use uuid::Uuid;
 
 /// An enum which contains all types of messages which nodes, in the Paxos algorithm, can exchange.
+/// New variants must be appended at the end (see the wire format, above).
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Message<T> {
+    #[serde(rename = "request")]
     Phase0a(Request<T>),
+    #[serde(rename = "catch_up")]
     Phase0b(CatchUp),
+    #[serde(rename = "report")]
     Phase0c(Report<T>),
+    #[serde(rename = "preparation")]
     Phase1a(Preparation),
+    #[serde(rename = "promise")]
     Phase1b(Promise<T>),
+    #[serde(rename = "nack")]
     Phase1c(Nack),
+    #[serde(rename = "proposal")]
     Phase2a(Proposal<T>),
+    #[serde(rename = "acceptance")]
     Phase2b(Acceptance<T>),
+    #[serde(rename = "learning")]
     Phase3(Learning<T>),
+    #[serde(rename = "noop_request")]
+    NoopRequest(NoopRequest),
+    #[serde(rename = "gossip_summary")]
+    GossipSummary(GossipSummary),
+    #[serde(rename = "gossip_values")]
+    GossipValues(GossipValues<T>),
+    #[serde(rename = "decided")]
+    Decided(Decided<T>),
+    #[serde(rename = "ping")]
+    Ping(Ping),
+    #[serde(rename = "pong")]
+    Pong(Pong),
+    #[serde(rename = "value_request")]
+    ValueRequest(ValueRequest),
+    #[serde(rename = "value_reply")]
+    ValueReply(ValueReply<T>),
+    #[serde(rename = "rejected")]
+    Rejected(Rejected<T>),
+    #[serde(rename = "learn_ack")]
+    LearnAck(LearnAck),
+    #[serde(rename = "progress")]
+    Progress(Progress),
+    #[serde(rename = "truncation")]
+    Truncation(Truncation),
+    #[serde(rename = "tail_preparation")]
+    TailPreparation(TailPreparation),
+    #[serde(rename = "tail_promise")]
+    TailPromise(TailPromise<T>),
+    #[serde(rename = "truncation_request")]
+    TruncationRequest(TruncationRequest),
+    #[serde(rename = "cancel_request")]
+    CancelRequest(CancelRequest),
+}
+
+impl<T> Message<T> {
+    /// Returns the Paxos group (i.e. the replicated log) which this message belongs to, which every
+    /// message carries in its field group_id. Nodes ignore messages which belong to a group
+    /// different from their own.
+    pub fn group_id(&self) -> usize {
+        match self {
+            Message::Phase0a(m) => m.group_id,
+            Message::Phase0b(m) => m.group_id,
+            Message::Phase0c(m) => m.group_id,
+            Message::Phase1a(m) => m.group_id,
+            Message::Phase1b(m) => m.group_id,
+            Message::Phase1c(m) => m.group_id,
+            Message::Phase2a(m) => m.group_id,
+            Message::Phase2b(m) => m.group_id,
+            Message::Phase3(m) => m.group_id,
+            Message::NoopRequest(m) => m.group_id,
+            Message::GossipSummary(m) => m.group_id,
+            Message::GossipValues(m) => m.group_id,
+            Message::Decided(m) => m.group_id,
+            Message::Ping(m) => m.group_id,
+            Message::Pong(m) => m.group_id,
+            Message::ValueRequest(m) => m.group_id,
+            Message::ValueReply(m) => m.group_id,
+            Message::Rejected(m) => m.group_id,
+            Message::LearnAck(m) => m.group_id,
+            Message::Progress(m) => m.group_id,
+            Message::Truncation(m) => m.group_id,
+            Message::TailPreparation(m) => m.group_id,
+            Message::TailPromise(m) => m.group_id,
+            Message::TruncationRequest(m) => m.group_id,
+            Message::CancelRequest(m) => m.group_id,
+        }
+    }
+
+    /// Returns the unique identifier of the node which sent this message.
+    pub fn sender_uuid(&self) -> Uuid {
+        match self {
+            Message::Phase0a(m) => m.sender_uuid,
+            Message::Phase0b(m) => m.sender_uuid,
+            Message::Phase0c(m) => m.sender_uuid,
+            Message::Phase1a(m) => m.sender_uuid,
+            Message::Phase1b(m) => m.sender_uuid,
+            Message::Phase1c(m) => m.sender_uuid,
+            Message::Phase2a(m) => m.sender_uuid,
+            Message::Phase2b(m) => m.sender_uuid,
+            Message::Phase3(m) => m.sender_uuid,
+            Message::NoopRequest(m) => m.sender_uuid,
+            Message::GossipSummary(m) => m.sender_uuid,
+            Message::GossipValues(m) => m.sender_uuid,
+            Message::Decided(m) => m.sender_uuid,
+            Message::Ping(m) => m.sender_uuid,
+            Message::Pong(m) => m.sender_uuid,
+            Message::ValueRequest(m) => m.sender_uuid,
+            Message::ValueReply(m) => m.sender_uuid,
+            Message::Rejected(m) => m.sender_uuid,
+            Message::LearnAck(m) => m.sender_uuid,
+            Message::Progress(m) => m.sender_uuid,
+            Message::Truncation(m) => m.sender_uuid,
+            Message::TailPreparation(m) => m.sender_uuid,
+            Message::TailPromise(m) => m.sender_uuid,
+            Message::TruncationRequest(m) => m.sender_uuid,
+            Message::CancelRequest(m) => m.sender_uuid,
+        }
+    }
 }
 
 /// In phase 0, a client sends a proposal to a proposer, which needs to start the Paxos algorithm.
@@ -28,18 +151,111 @@ pub struct Request<T> {
 
     // The unique identifier of the sender of this message (which is a client).
     pub sender_uuid: Uuid,
+
+    // The sequence number of this request among the requests of the client which sent it. The pair
+    // (sender_uuid, request_seq) thus uniquely identifies a request, even if it is retransmitted.
+    pub request_seq: usize,
+
+    // How urgently the value must be proposed, if the proposer has to queue this request. If None,
+    // the priority is Priority::Normal.
+    pub priority: Option<Priority>,
+
+    pub group_id: usize,
+
+    // If set, the client wants the chosen values of the instances from this one on, up to the
+    // instance where value is decided, in the Decided message (see Client::request_with_prefix).
+    pub prefix_from: Option<InstanceId>,
+}
+
+/// The identifier of a client request, i.e. the pair (sender_uuid, request_seq) (see
+/// Request::request_seq).
+pub type RequestId = (Uuid, usize);
+
+/// The priority levels of the client requests. When a proposer cannot start new instances (see
+/// Proposer::with_max_in_flight), it proposes the queued values with a higher priority first.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    Low,
+    Normal,
+    High,
+    // E.g. reconfigurations or no-ops which fill a gap in the log.
+    Urgent,
+}
+
+/// A set of instances, encoded as the (inclusive) ranges of consecutive instances which it contains,
+/// sorted and disjoint, so that its size depends on the number of gaps, rather than on the number of
+/// instances (e.g. all the instances known by a node which is only slightly behind are 1 range).
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct InstanceRanges(Vec<(usize, usize)>);
+
+impl InstanceRanges {
+    /// Returns the set of the given instances, which can be in any order.
+    pub fn from_instances<I: IntoIterator<Item = usize>>(instances: I) -> Self {
+        let mut instances: Vec<usize> = instances.into_iter().collect();
+        instances.sort_unstable();
+
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+
+        for instance in instances {
+            match ranges.last_mut() {
+                Some(&mut (_, ref mut last)) if instance <= *last + 1 => {
+                    *last = instance.max(*last)
+                }
+                _ => ranges.push((instance, instance)),
+            }
+        }
+
+        InstanceRanges(ranges)
+    }
+
+    /// Returns true if this set contains instance.
+    pub fn contains(&self, instance: usize) -> bool {
+        match self.0.binary_search_by_key(&instance, |&(first, _)| first) {
+            Ok(_) => true,
+            Err(0) => false,
+            Err(i) => instance <= self.0[i - 1].1,
+        }
+    }
+
+    /// Returns the ranges of this set, as pairs of the first and the last instance of each range.
+    pub fn ranges(&self) -> &[(usize, usize)] {
+        &self.0
+    }
+}
+
+/// The role of the sender of a CatchUp message, which tells the proposers where to send their
+/// Report, i.e. to the learners or to the proposers.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SenderType {
+    Learner,
+    Proposer,
 }
 
 /// When a learner starts, it sends this message to the proposers to know about previously executed
 /// Paxos instances.
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CatchUp {
     // The unique identifier of the Learner which sends this message.
     pub sender_uuid: Uuid,
 
-    // 'l' for learner
-    // 'p' for proposer
-    pub sender_type: char,
+    // The role of the sender, whose address the Report is sent to, unless reply_address is set.
+    pub sender_type: SenderType,
+
+    // The address where the sender wants the Report to be sent to, if it is not reachable at the
+    // address of its role (e.g. because it is behind a NAT). None means the address of its role.
+    pub reply_address: Option<SocketAddrV4>,
+
+    // The first instance whose learned value the sender wants to know. The learned values of the
+    // previous instances, which the sender already knows, are not reported.
+    pub from_instance: InstanceId,
+
+    pub group_id: usize,
+
+    // The instances, from self.from_instance on, whose learned values the sender already knows (e.g.
+    // because it missed only a few of them), which are not reported either.
+    pub known_instances: InstanceRanges,
 }
 
 /// The answer message to a CatchUp message.
@@ -59,36 +275,130 @@ pub struct Report<T> {
 
     // The unique identifier of the Learner which receives this message.
     pub receiver_uuid: Uuid,
+
+    pub group_id: usize,
+
+    // The instance of the last stable checkpoint known to the sender (see
+    // Proposer::with_checkpoint), below which it truncated its log, if any. The learned values of
+    // the previous instances are lost, so the receiver skips them.
+    pub checkpoint: Option<InstanceId>,
+}
+
+/// A round (or ballot) of a basic Paxos instance. The ballots are totally ordered, first by their
+/// epoch and then by the id of the proposer which started them, so the ballots started by distinct
+/// proposers are always distinct (and comparable).
+#[derive(
+    Serialize, Deserialize, Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub struct Ballot {
+    pub epoch: usize,
+
+    pub proposer_id: usize,
+}
+
+impl Ballot {
+    /// The ballot which precedes all the others. The rnd and v_rnd of an acceptor are initially
+    /// equal to it, that is, before it participates in (or votes in) any ballot.
+    pub const ZERO: Ballot = Ballot {
+        epoch: 0,
+        proposer_id: 0,
+    };
+
+    /// The ballot of the values which are preloaded into the acceptors (see
+    /// Acceptor::preload_log). It follows all the ballots which a proposer can start, so such
+    /// values can never be overwritten.
+    pub const PRELOADED: Ballot = Ballot {
+        epoch: usize::MAX,
+        proposer_id: usize::MAX,
+    };
+
+    /// Returns the ballot, started by the proposer with id proposer_id, of the epoch after the one
+    /// of this ballot, or None if the epoch would overflow.
+    pub fn next(self, proposer_id: usize) -> Option<Ballot> {
+        Some(Ballot {
+            epoch: self.epoch.checked_add(1)?,
+            proposer_id,
+        })
+    }
+}
+
+/// The number of a basic Paxos instance, i.e. the position of its value in the replicated log. It
+/// is distinct from the other numbers carried by the messages (e.g. the sequence numbers of the
+/// requests, or the epochs of the rounds, which are Ballots), so that an instance cannot be passed
+/// for one of them by mistake, and vice versa. On the wire, it is encoded as a bare number, and it is
+/// also formatted as one (even with Debug), so the logs of the messages are unchanged.
+#[derive(Serialize, Deserialize, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InstanceId(pub usize);
+
+impl InstanceId {
+    /// Returns the instance which follows this one.
+    pub fn next(self) -> InstanceId {
+        InstanceId(self.0 + 1)
+    }
+}
+
+impl From<usize> for InstanceId {
+    fn from(instance: usize) -> Self {
+        InstanceId(instance)
+    }
+}
+
+impl From<InstanceId> for usize {
+    fn from(instance: InstanceId) -> Self {
+        instance.0
+    }
+}
+
+impl Display for InstanceId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Debug for InstanceId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
 }
 
 /// In phase 1a, c_rnd is sent from 1 proposer to ALL acceptors.
 #[derive(Serialize, Deserialize, Debug, Copy, Clone)]
 pub struct Preparation {
     // The highest-numbered round the proposer has started.
-    pub c_rnd: usize,
+    pub c_rnd: Ballot,
 
     // The unique identifier of the sender of this message (which is a proposer).
     pub sender_uuid: Uuid,
 
     // The Paxos instance (or iteration) associated with this message.
-    pub instance: usize,
+    pub instance: InstanceId,
+
+    // The address where the proposer wants the Promise to be sent to, if it is not reachable at
+    // the address of the proposers (e.g. because it is behind a NAT). None means the latter.
+    pub reply_address: Option<SocketAddrV4>,
+
+    pub group_id: usize,
+
+    // Whether the proposer accepts the summary of the voted value, instead of the value itself, in
+    // the Promise (see Summarize).
+    pub summaries: bool,
 }
 
 /// In phase 1b, rnd, v_rnd and v_val is sent from 1 acceptor to 1 or more proposers.
 #[derive(Serialize, Deserialize, Debug, Copy, Clone)]
 pub struct Promise<T> {
-    // The highest-numbered round the acceptor has PARTICIPATED in. It is initially 0. rnd is then
-    // set to the c_rnd, sent in a Preparation message by some Proposer, such that c_rnd > rnd. So,
-    // here, by "participate" we mean to send a Promise message to the proposals.
-    pub rnd: usize,
-
-    // The highest-numbered round the acceptor has CAST a vote. It is initially 0, but it eventually
-    // corresponds to some c_rnd sent by a Proposer in a Proposal message, such that
-    // c_rnd > self.rnd. In other words, v_rnd will be a number which is greater than any round the
+    // The highest-numbered round the acceptor has PARTICIPATED in. It is initially Ballot::ZERO.
+    // rnd is then set to the c_rnd, sent in a Preparation message by some Proposer, such that
+    // c_rnd > rnd. So, here, by "participate" we mean to send a Promise message to the proposals.
+    pub rnd: Ballot,
+
+    // The highest-numbered round the acceptor has CAST a vote. It is initially Ballot::ZERO, but it
+    // eventually corresponds to some c_rnd sent by a Proposer in a Proposal message, such that
+    // c_rnd > self.rnd. In other words, v_rnd will be a round which is greater than any round the
     // acceptor has participated in. v_rnd is thus set only when the acceptor wants to send a Accept
     // message to the proposers, after having received enough Proposals. So, here, by casting a vote
     // we mean to send a Accept message to the proposers.
-    pub v_rnd: usize,
+    pub v_rnd: Ballot,
 
     // The value voted by the acceptor in round v_rnd. It is initially None.
     pub v_val: Option<T>,
@@ -100,7 +410,16 @@ pub struct Promise<T> {
     // It should match the field sender_uid of the Phase1a message.
     pub receiver_uuid: Uuid,
 
-    pub instance: usize,
+    pub instance: InstanceId,
+
+    pub group_id: usize,
+
+    // If set, v_val is None and this is the summary of the value voted in round v_rnd, which was
+    // sent instead of the value itself (see Summarize).
+    pub v_summary: Option<u64>,
+
+    // The client request whose value was voted in round v_rnd, if any (see Proposal::c_request).
+    pub v_request: Option<RequestId>,
 }
 
 /// NACKs are optional in Paxos, but they can be used to inform other nodes of rejections.
@@ -108,7 +427,7 @@ pub struct Promise<T> {
 pub struct Nack {
     // The v_rnd which caused the rejection of a c_rnd sent from a proposer to an acceptor in a
     // Preparation message.
-    pub v_rnd: usize,
+    pub v_rnd: Ballot,
 
     // The unique identifier of the acceptor which rejects the c_rnd.
     pub sender_uuid: Uuid,
@@ -116,26 +435,35 @@ pub struct Nack {
     // The unique identifier of the proposer to which this Nack message should be sent.
     pub receiver_uuid: Uuid,
 
-    pub instance: usize,
+    pub instance: InstanceId,
+
+    pub group_id: usize,
 }
 
 /// In phase 2a, c_rnd and c_val is sent from 1 proposer to ALL acceptors.
 #[derive(Serialize, Deserialize, Debug, Copy, Clone)]
 pub struct Proposal<T> {
-    pub c_rnd: usize,
+    pub c_rnd: Ballot,
 
     // The value that the proposer has picked for round c_rnd.
     pub c_val: Option<T>,
 
     pub sender_uuid: Uuid,
 
-    pub instance: usize,
+    pub instance: InstanceId,
+
+    pub group_id: usize,
+
+    // The client request whose value is c_val, if any. Two requests can have equal values, so the
+    // proposers use it to tell whether the value voted for in an instance is the one of their own
+    // request.
+    pub c_request: Option<RequestId>,
 }
 
 /// In phase 2b, v_rnd and v_val is sent from 1 acceptor to 1 or more proposers.
 #[derive(Serialize, Deserialize, Debug, Copy, Clone)]
 pub struct Acceptance<T> {
-    pub v_rnd: usize,
+    pub v_rnd: Ballot,
 
     pub v_val: Option<T>,
 
@@ -144,7 +472,12 @@ pub struct Acceptance<T> {
     // It should match the field sender_uid of the Phase2a message.
     pub receiver_uuid: Uuid,
 
-    pub instance: usize,
+    pub instance: InstanceId,
+
+    pub group_id: usize,
+
+    // The client request whose value is v_val, if any (see Proposal::c_request).
+    pub v_request: Option<RequestId>,
 }
 
 /// In phase 3, the proposers send the decided value to the learners.
@@ -154,5 +487,292 @@ pub struct Learning<T> {
 
     pub sender_uuid: Uuid,
 
-    pub instance: usize,
-}
\ No newline at end of file
+    pub instance: InstanceId,
+
+    pub group_id: usize,
+}
+
+/// A learner, whose delivery of the learned values is blocked by an instance which was apparently
+/// never decided, sends this message to the proposers, to ask them to decide a no-op in it.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct NoopRequest {
+    // The instance which blocks the delivery of the learner.
+    pub instance: InstanceId,
+
+    // The unique identifier of the learner which sends this message.
+    pub sender_uuid: Uuid,
+
+    pub group_id: usize,
+}
+
+/// A proposer periodically sends this message to the other proposers, to tell them which instances
+/// it knows the learned values of, so that they can send it the ones it is missing.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GossipSummary {
+    // The instances whose learned values are known by the sender.
+    pub known_instances: InstanceRanges,
+
+    // The unique identifier of the proposer which sends this message.
+    pub sender_uuid: Uuid,
+
+    pub group_id: usize,
+}
+
+/// The answer message to a GossipSummary message, which contains (some of) the learned values which
+/// the receiver is missing.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GossipValues<T> {
+    // A map between the Paxos instance numbers and the associated learned values.
+    pub learned_values: HashMap<usize, T>,
+
+    // The unique identifier of the proposer which sends this message.
+    pub sender_uuid: Uuid,
+
+    // The unique identifier of the proposer which receives this message.
+    pub receiver_uuid: Uuid,
+
+    pub group_id: usize,
+}
+
+/// A proposer sends this message to a client, once the value of one of its requests is decided, to
+/// tell it the instance where the value was decided, and how far the chosen prefix of the log goes.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Decided<T> {
+    // The value requested by the client.
+    pub value: T,
+
+    // The sequence number of the request (see Request::request_seq).
+    pub request_seq: usize,
+
+    // The Paxos instance (or iteration) where value was decided.
+    pub instance: InstanceId,
+
+    // The unique identifier of the proposer which sends this message.
+    pub sender_uuid: Uuid,
+
+    // The unique identifier of the client which receives this message.
+    pub receiver_uuid: Uuid,
+
+    pub group_id: usize,
+
+    // The last instance of the contiguous chosen prefix of the log known by the sender, i.e. all
+    // the instances from 1 up to this one are chosen (0 if the value of the instance 1 is unknown).
+    pub watermark: InstanceId,
+
+    // The chosen values of the consecutive instances from prefix_from on, in order, up to the
+    // instance where value was decided or the watermark, whichever comes first. It is empty unless
+    // the client asked for them (see Request::prefix_from), and its length is bounded, so a client
+    // which is far behind gets it in several parts.
+    pub prefix_from: InstanceId,
+    pub prefix: Vec<T>,
+}
+
+/// A probe, which any node answers with a Pong message, used to measure the round-trip time to
+/// the nodes (see the module ping). It is not part of the Paxos algorithm.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct Ping {
+    // A number which identifies this probe, and which is sent back in the Pong messages.
+    pub nonce: u64,
+
+    // The address where the Pong messages must be sent to.
+    pub reply_address: SocketAddrV4,
+
+    // The unique identifier of the node which sends this message.
+    pub sender_uuid: Uuid,
+
+    pub group_id: usize,
+}
+
+/// The answer message to a Ping message.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct Pong {
+    // The nonce of the Ping message which this message answers.
+    pub nonce: u64,
+
+    // The unique identifier of the node which sends this message.
+    pub sender_uuid: Uuid,
+
+    // The unique identifier of the node which sent the Ping message.
+    pub receiver_uuid: Uuid,
+
+    pub group_id: usize,
+}
+
+/// A learner sends this message to the acceptors, to ask them which value they accepted in an
+/// instance whose learned value it is missing (e.g. because the proposers forgot it or are down).
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct ValueRequest {
+    // The Paxos instance (or iteration) whose accepted value is requested.
+    pub instance: InstanceId,
+
+    // The address where the ValueReply messages must be sent to.
+    pub reply_address: SocketAddrV4,
+
+    // The unique identifier of the learner which sends this message.
+    pub sender_uuid: Uuid,
+
+    pub group_id: usize,
+}
+
+/// The answer message to a ValueRequest message, which contains the last round in which the
+/// acceptor accepted a value, in the requested instance, and that value (if any).
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct ValueReply<T> {
+    // The Paxos instance (or iteration) which this message refers to.
+    pub instance: InstanceId,
+
+    // The highest-numbered round the acceptor has cast a vote in (see Promise::v_rnd).
+    pub v_rnd: Ballot,
+
+    // The value voted by the acceptor in round v_rnd. It is None if it has not voted yet.
+    pub v_val: Option<T>,
+
+    // The unique identifier of the acceptor which sends this message.
+    pub sender_uuid: Uuid,
+
+    // The unique identifier of the learner which sent the ValueRequest message.
+    pub receiver_uuid: Uuid,
+
+    pub group_id: usize,
+}
+
+/// A proposer sends this message to a client, instead of proposing the value of one of its
+/// requests, if the value is not valid (see ValueValidator).
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct Rejected<T> {
+    // The value requested by the client.
+    pub value: T,
+
+    // The sequence number of the request (see Request::request_seq).
+    pub request_seq: usize,
+
+    // The Paxos instance (or iteration) where value would have been proposed.
+    pub instance: InstanceId,
+
+    // The unique identifier of the proposer which sends this message.
+    pub sender_uuid: Uuid,
+
+    // The unique identifier of the client which receives this message.
+    pub receiver_uuid: Uuid,
+
+    pub group_id: usize,
+}
+
+/// A learner which acknowledges its deliveries (see Learner::with_learn_acks) sends this message to
+/// the proposers once it has delivered the learned value of an instance, so that they stop sending
+/// the Learning message of that instance again (see Proposer::with_learn_acks).
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct LearnAck {
+    // The instance whose learned value was delivered.
+    pub instance: InstanceId,
+
+    // The unique identifier of the learner which sends this message.
+    pub sender_uuid: Uuid,
+
+    pub group_id: usize,
+}
+
+/// A learner which reports its progress (see Learner::with_progress_reports) periodically sends
+/// this message to the proposers, so that they stop starting new instances while the learners are
+/// too far behind (see Proposer::with_backpressure).
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct Progress {
+    // The next instance whose learned value the learner must deliver, i.e. its delivery watermark.
+    pub next_to_deliver: InstanceId,
+
+    // The unique identifier of the learner which sends this message.
+    pub sender_uuid: Uuid,
+
+    pub group_id: usize,
+}
+
+/// A proposer sends this message to the acceptors, and to the other proposers, once the checkpoint
+/// decided in an instance is stable (see Proposer::with_checkpoint), so that they drop their state
+/// of the previous instances, which are never started again (see Acceptor::with_log_truncation).
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct Truncation {
+    // The instance of the stable checkpoint, below which the log is truncated.
+    pub instance: InstanceId,
+
+    // The unique identifier of the proposer which sends this message.
+    pub sender_uuid: Uuid,
+
+    pub group_id: usize,
+}
+
+/// A proposer which establishes an epoch (see Proposer::establish_epoch) sends this message to ALL
+/// acceptors, to run phase 1 once for all the instances from from_instance on, i.e. the tail of the
+/// log, instead of once per instance.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct TailPreparation {
+    // The round of the epoch, which the proposer wants the acceptors to promise in all the
+    // instances of the tail.
+    pub c_rnd: Ballot,
+
+    // The first instance of the tail, i.e. the first one which the proposer does not know to be
+    // chosen.
+    pub from_instance: InstanceId,
+
+    // The unique identifier of the sender of this message (which is a proposer).
+    pub sender_uuid: Uuid,
+
+    // The address where the proposer wants the TailPromise to be sent to, if it is not reachable
+    // at the address of the proposers. None means the latter.
+    pub reply_address: Option<SocketAddrV4>,
+
+    pub group_id: usize,
+}
+
+/// The answer of an acceptor to a TailPreparation message, once it promised rnd in all the
+/// instances of the tail, with the votes which it already cast in them.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TailPromise<T> {
+    // The round of the epoch, which the acceptor promised.
+    pub rnd: Ballot,
+
+    // The first instance of the tail, as in the TailPreparation message.
+    pub from_instance: InstanceId,
+
+    // The instances of the tail where the acceptor voted, with the round of its vote (v_rnd) and
+    // the voted value (v_val), in increasing order of instance.
+    pub votes: Vec<(InstanceId, Ballot, T)>,
+
+    // The unique identifier of the sender of this message (which is an acceptor).
+    pub sender_uuid: Uuid,
+
+    // The unique identifier of the proposer which sent the TailPreparation message.
+    pub receiver_uuid: Uuid,
+
+    pub group_id: usize,
+
+    // The client request of each vote of votes, if any (see Proposal::c_request), in the same
+    // order.
+    pub v_requests: Vec<Option<RequestId>>,
+}
+
+/// An acceptor which tracks more instances than its soft cap (see Acceptor::with_memory_soft_cap)
+/// sends this message to the proposers, so that the ones whose log is truncated further than its
+/// own send their Truncation message again (e.g. if the acceptor missed it).
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct TruncationRequest {
+    // The instance below which the log of the acceptor is truncated (or 1, if it is not).
+    pub truncated_below: InstanceId,
+
+    // The unique identifier of the sender of this message (which is an acceptor).
+    pub sender_uuid: Uuid,
+
+    pub group_id: usize,
+}
+
+/// A client which gives up on one of its requests (e.g. after a timeout) sends this message to the
+/// proposers, so that they stop trying to decide its value (see Client::cancel).
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct CancelRequest {
+    // The sequence number of the cancelled request (see Request::request_seq).
+    pub request_seq: usize,
+
+    // The unique identifier of the sender of this message (which is a client).
+    pub sender_uuid: Uuid,
+
+    pub group_id: usize,
+}
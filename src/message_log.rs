@@ -0,0 +1,94 @@
+//! A module which contains the definition of a log of the messages sent and received by a node. Such
+//! a log can be captured while a node runs and later be fed back into a node (see the example
+//! `replay`), in order to reproduce a bug.
+
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bincode::{deserialize_from, serialize_into, ErrorKind};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::message::Message;
+
+/// Whether a logged message was sent or received by the node which logged it.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+/// An entry of a MessageLog.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LoggedMessage<T> {
+    // The number of milliseconds elapsed since the UNIX epoch, when the message was logged.
+    pub timestamp: u64,
+
+    pub direction: Direction,
+
+    pub message: Message<T>,
+}
+
+/// A file where every message sent or received by a node is appended, together with a timestamp.
+pub struct MessageLog {
+    writer: BufWriter<File>,
+}
+
+impl MessageLog {
+    /// Creates (or truncates) the file at path, where the messages will be written.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(MessageLog {
+            writer: BufWriter::new(File::create(path)?),
+        })
+    }
+
+    /// Appends the message m to this log. The log is flushed after every message, so that it is
+    /// complete even if the node crashes.
+    pub fn record<T>(&mut self, direction: Direction, m: &Message<T>)
+    where
+        T: Serialize + Clone + Debug,
+    {
+        let entry = LoggedMessage {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            direction,
+            message: m.clone(),
+        };
+
+        let result = serialize_into(&mut self.writer, &entry)
+            .map_err(|e| e.to_string())
+            .and_then(|_| self.writer.flush().map_err(|e| e.to_string()));
+
+        if let Err(e) = result {
+            warn!("Could not write {:?} to the message log: {}.", m, e);
+        }
+    }
+
+    /// Reads all the messages previously written, using a MessageLog, to the file at path.
+    pub fn read<T, P>(path: P) -> io::Result<Vec<LoggedMessage<T>>>
+    where
+        T: DeserializeOwned,
+        P: AsRef<Path>,
+    {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut entries = Vec::new();
+
+        loop {
+            match deserialize_from(&mut reader) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => match *e {
+                    // The end of the log has been reached.
+                    ErrorKind::Io(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                        return Ok(entries);
+                    }
+                    _ => return Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+                },
+            }
+        }
+    }
+}
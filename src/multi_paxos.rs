@@ -1,23 +1,506 @@
 //! The module that contains the structs representing clients, proposers, acceptors and learners of
 //! the Multi-Paxos algorithm. It also contains the main logic of the algorithm.
-use std::collections::HashMap;
-use std::fmt::Debug;
-use std::net::SocketAddrV4;
+use std::any;
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::io;
+use std::iter;
+use std::mem;
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{self, AtomicUsize};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use log::Level;
+use rand::rngs::StdRng;
+use rand::{FromEntropy, Rng};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use uuid::Uuid;
 
+use crate::admin::{self, AdminListener, Answer, Command};
+use crate::latency::LatencyHistogram;
+use crate::load::{LoadStats, MemoryReport};
 use crate::message::{
-    Acceptance, CatchUp, Learning, Message, Preparation, Promise, Proposal, Report, Request,
+    Acceptance, Ballot, CancelRequest, CatchUp, Decided, GossipSummary, GossipValues, InstanceId,
+    InstanceRanges, LearnAck, Learning, Message, Nack, NoopRequest, Ping, Preparation, Priority,
+    Progress, Promise, Proposal, Rejected, Report, Request, RequestId, SenderType, TailPreparation,
+    TailPromise, Truncation, TruncationRequest, ValueReply, ValueRequest,
 };
+use crate::message_log::MessageLog;
 use crate::net_node::NetNode;
+use crate::ping::pong;
+use crate::recent_requests::RecentRequests;
+use crate::reply_cache::{ReplyCache, RequestKey, RequestKind};
+use crate::storage::{DeliveryWatermark, GenerationCounter, Storage};
+#[cfg(feature = "metrics")]
+use crate::telemetry;
+use crate::verbosity::{logs_messages, Logged};
+
+/// How long, by default, a proposer remembers the client requests which it handled, in order to
+/// recognize (and ignore) duplicates of them (see Proposer::with_request_dedup).
+const RECENT_REQUESTS_WINDOW: Duration = Duration::from_secs(60);
+
+/// The number of the most recent client requests which a proposer remembers, by default (see
+/// Proposer::with_request_dedup).
+const RECENT_REQUESTS_CAPACITY: usize = 1024;
+
+/// The maximum number of client requests which a busy proposer (see Proposer::with_max_in_flight)
+/// queues. The requests received while this queue is full are dropped.
+const PENDING_REQUESTS_CAPACITY: usize = 1024;
+
+/// The maximum number of learned values which a proposer sends in a GossipValues message. The
+/// missing values exceeding it are sent in the next rounds of gossip.
+const GOSSIP_MAX_VALUES: usize = 64;
+
+/// The maximum number of chosen values which a proposer sends in a Decided message, to a client
+/// which asked for the prefix of the log (see Client::request_with_prefix), so that the message
+/// fits in a datagram.
+const PREFIX_MAX_VALUES: usize = 64;
+
+/// The number of the most recent instances whose decided values an observer remembers, so that it
+/// recognizes (and ignores) the duplicates of their Learning messages (see Observer).
+const OBSERVED_INSTANCES_CAPACITY: usize = 4096;
+
+/// How long a starting proposer waits for a Report, after each CatchUp message it sends (see
+/// Proposer::catch_up_blocking).
+const CATCH_UP_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// The number of CatchUp messages which a starting proposer sends before it assumes that there are
+/// no other proposers to catch up with.
+const CATCH_UP_ATTEMPTS: usize = 5;
+
+/// How often a starting proposer pings the acceptors, until a majority of them answers (see
+/// Proposer::wait_for_quorum).
+const QUORUM_PING_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The number of the low bits of the epochs of the rounds which are left to the rounds of each
+/// generation of a proposer, above which its generation is folded in (see
+/// RoundStrategy::Generation).
+const GENERATION_SHIFT: u32 = usize::BITS / 2;
+
+/// The number of the most recent values (e.g. requested by the clients) which a proposer with
+/// summaries (see Summarize) remembers, in order to materialize the summarized values.
+const KNOWN_VALUES_WINDOW: usize = 1024;
+
+/// How long a proposer with backpressure (see Proposer::with_backpressure) takes into account the
+/// last progress reported by a learner, so that a learner which stopped reporting it (e.g. because
+/// it crashed) does not throttle the proposer forever.
+const PROGRESS_EXPIRY: Duration = Duration::from_secs(10);
+
+/// How often a learner offers again a value to a sink which failed to consume it, and which blocks
+/// the delivery until it does (see SinkFailurePolicy::Block).
+const SINK_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a draining proposer waits, by default, for its instances in flight to be decided,
+/// before it stops anyway (see Proposer::drain).
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How far beyond the highest instance it knows a proposer accepts, by default, the Promise and the
+/// Acceptance messages of the instances which it does not know (see FutureInstancePolicy).
+const FUTURE_INSTANCES_WINDOW: usize = 1 << 16;
+
+/// The gossip interval of a proposer (see Proposer::with_gossip_interval) is lengthened each time
+/// by a random jitter of up to 1 / GOSSIP_JITTER_FRACTION of it, so that the proposers which start
+/// together do not keep gossiping at the same time.
+const GOSSIP_JITTER_FRACTION: u32 = 10;
+
+/// Implement this trait for the (large) values which can be summarized, e.g. by a hash or an id,
+/// so that the acceptors can send the summary of the value they voted for, instead of the value
+/// itself, in their Promise messages (see Acceptor::with_summaries). The proposer then
+/// materializes the value from the summary, if it knows the value (see Proposer::with_summaries),
+/// otherwise it starts a new round of the instance, in which it asks for the value itself. The
+/// other messages (e.g. Proposal and Learning) always carry the value itself.
+pub trait Summarize {
+    /// Returns the summary of this value. Different values must have different summaries.
+    fn summarize(&self) -> u64;
+}
+
+/// Implement this trait for the values which change the membership of the acceptors, so that the
+/// reconfigurations are decided in the log, like any other value (see
+/// Proposer::with_membership_changes). A change decided in instance i only takes effect in instance
+/// i + alpha (see Proposer::with_alpha), so that all the proposers agree on the membership of the
+/// instances which are in flight meanwhile.
+pub trait Reconfigure {
+    /// Returns the number of acceptors of the new membership, if this value changes it.
+    fn membership(&self) -> Option<usize>;
+}
+
+/// Implement this trait for the values which are batches of commands (e.g. batch::CommandBatch),
+/// so that a proposer decides several client requests, each of which is a batch of its command, in
+/// a single instance (see Proposer::with_batching), and that a learner delivers the commands of
+/// each decided batch one after the other, before the ones of the next instance (see
+/// Learner::with_batching). This is distinct from pipelining (see Proposer::with_max_in_flight),
+/// where several instances are in flight at the same time, and both can be combined.
+pub trait Batch: Sized {
+    /// The type of the commands of a batch.
+    type Command: Debug;
+
+    /// Returns the batch of the commands of this batch followed by the ones of other, or None if
+    /// they do not fit in a single batch.
+    fn merge(&self, other: &Self) -> Option<Self>;
+
+    /// Returns the commands of this batch, in order.
+    fn commands(&self) -> Vec<Self::Command>;
+}
+
+/// Implement this trait (or use a closure with the same signature) to make a proposer check the
+/// values of the client requests before proposing them (see Proposer::with_value_validator), e.g.
+/// to enforce invariants of the application, such as monotonic values. The values which are not
+/// valid are never proposed by that proposer, and their clients are told so with a Rejected
+/// message. Note that this is only a filter at the entry point of the proposer, not an agreement
+/// of the nodes on the validity of the values: a proposer still adopts (and has decided) any value
+/// which the acceptors voted for, as required by Paxos, and the proposers with another validator
+/// (or none) can propose any value.
+pub trait ValueValidator<T> {
+    /// Returns whether value can be proposed in the given instance.
+    fn validate(&self, instance: usize, value: &T) -> bool;
+}
+
+impl<T, F> ValueValidator<T> for F
+where
+    F: Fn(usize, &T) -> bool,
+{
+    fn validate(&self, instance: usize, value: &T) -> bool {
+        self(instance, value)
+    }
+}
+
+/// The source of the random numbers of a proposer (e.g. the jitter of its gossip), which is a
+/// generator of the crate rand, seeded from the entropy of the system, by default. Any generator of
+/// the crate rand is a source, so a test can give a seeded generator to each proposer (see
+/// Proposer::with_random_source), to make the runs reproducible. Each proposer must have its own
+/// seed, otherwise their random numbers (and so their timings) would be correlated.
+pub trait RandomSource {
+    /// Returns a random number, uniformly distributed from 0 (included) to bound (excluded).
+    ///
+    /// Panics if bound is zero.
+    fn next_below(&mut self, bound: u64) -> u64;
+}
+
+impl<R: Rng> RandomSource for R {
+    fn next_below(&mut self, bound: u64) -> u64 {
+        self.gen_range(0, bound)
+    }
+}
+
+/// What a proposer proposes in an instance which it must complete (see
+/// Proposer::complete_instance), but for which it has no value of its own (e.g. because the value
+/// of the client request was lost with the crashed proposer which started the instance), if no
+/// acceptor has voted in the instance yet. Otherwise, the value voted by the acceptors is always
+/// adopted instead, as usual in phase 2a, whatever the policy is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UnknownValuePolicy<T> {
+    /// Nothing, i.e. the instance stays undecided, until some proposer completes it with a value.
+    Stall,
+
+    /// The no-op of the proposer (see Proposer::with_noop), if it has one, otherwise nothing.
+    Noop,
+
+    /// The given value.
+    Default(T),
+}
+
+/// What a proposer does with a Promise or an Acceptance message of an instance which it does not
+/// know, i.e. which it neither started nor saw before, and which is beyond the highest instance it
+/// knows (see Proposer::with_future_instance_policy). Such a message may be sent by a proposer
+/// which is ahead of this one, but an instance implausibly far ahead rather reveals a bug, or a
+/// forged message, and the state created for it would never be reclaimed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FutureInstancePolicy {
+    /// The state of the instance is created on demand, as for any other instance, however far
+    /// ahead it is.
+    CreateOnDemand,
+
+    /// The messages of the instances which are more than the given number of instances beyond the
+    /// highest instance known to the proposer are dropped, and logged. This is the default, with
+    /// a window of 65536 instances.
+    RejectBeyondWatermark(usize),
+
+    /// As RejectBeyondWatermark, but the proposer also catches up with the other proposers, as it
+    /// may be the one which is behind (e.g. after a partition).
+    TriggerCatchUp(usize),
+}
+
+/// What a proposer does with an instance where it starves, i.e. where its last rounds were all
+/// preempted by the rounds of other proposers (see Proposer::with_starvation_policy), instead of
+/// starting new rounds which are likely to be preempted as well.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StarvationPolicy {
+    /// The proposer keeps competing for the instance, but it waits before each new round, from
+    /// backoff_base after the preemption of the round which made it starve, twice as long after
+    /// each next preempted round, up to backoff_cap. The rounds requested in the meantime (e.g.
+    /// with Proposer::complete_instance) are started once the wait is over.
+    BackOff {
+        backoff_base: Duration,
+        backoff_cap: Duration,
+    },
+
+    /// The proposer stops competing for the instance, which it yields to the proposer of the
+    /// highest round which preempted it: it starts no new round of the instance, but it still
+    /// learns its decision, and its own value, if requested by a client, is then proposed again in
+    /// a new instance.
+    Yield,
+}
+
+/// Why a proposer cannot establish an epoch (see Proposer::establish_epoch).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EpochError {
+    /// The proposer is draining (see Proposer::drain), so it does not start new instances anymore.
+    Draining,
+
+    /// The proposer stepped down (see Proposer::step_down), so it does not start new instances
+    /// anymore.
+    SteppedDown,
+
+    /// The round of the epoch would be after the last round of the proposer, i.e. its epoch would
+    /// overflow (see Ballot::next).
+    RoundOverflow,
+}
+
+impl Display for EpochError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            EpochError::Draining => write!(f, "The proposer is draining"),
+            EpochError::SteppedDown => write!(f, "The proposer stepped down"),
+            EpochError::RoundOverflow => write!(f, "The round of the epoch would overflow"),
+        }
+    }
+}
+
+impl Error for EpochError {}
+
+/// Why a proposer cannot force a no-op in an instance (see Proposer::force_noop).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ForceNoopError {
+    /// The proposer has no no-op (see Proposer::with_noop), and it does not know the value decided
+    /// in the instance.
+    NoNoop,
+}
+
+impl Display for ForceNoopError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ForceNoopError::NoNoop => write!(f, "The proposer has no no-op"),
+        }
+    }
+}
+
+impl Error for ForceNoopError {}
+
+/// A function which an acceptor consults, with its current load, before processing a received
+/// message. If it returns false, the message is dropped without being processed (and persisted). It
+/// is Send, like the acceptor, which can thus run on its own thread.
+pub type AdmissionControl<T> = Box<dyn Fn(&Message<T>, &LoadStats) -> bool + Send>;
+
+/// The order in which a proposer proposes the client requests of the same priority which it queued
+/// because it was busy (see Proposer::with_max_in_flight and Proposer::with_ordering_policy).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OrderingPolicy {
+    /// In the order they were received (i.e. by their timestamp of receipt), which, over UDP, is
+    /// not predictable, and lets a client which sends many requests delay the other clients.
+    Arrival,
+
+    /// Round-robin among the clients, by their unique identifiers: a request of each client which
+    /// has queued requests is proposed before the next request of the same client, so no client is
+    /// starved by the others. The requests of each client are proposed in the order they were
+    /// received.
+    ByClient,
+
+    /// By the sequence numbers which the clients assign to their requests (see
+    /// Request::request_seq), and then by client, so that the requests of the clients which have
+    /// sent fewer requests are proposed first, whatever the order they were received in.
+    BySequence,
+}
+
+/// What a proposer knows about the decision of an instance (see Proposer::decision_state).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DecisionState<T> {
+    /// No acceptor is known to have voted in the instance.
+    Unknown,
+
+    /// Some acceptors, but less than a majority of them, are known to have voted in the instance,
+    /// so the value they voted for may still be replaced by another one in a later round.
+    Accepted { count: usize },
+
+    /// A majority of the acceptors voted for the given value in the same round, so it is chosen:
+    /// no other value can ever be decided in the instance.
+    Chosen(T),
+}
+
+/// How a proposer computes the first round (i.e. c_rnd) which it starts in an instance (see
+/// Proposer::with_round_strategy). Its next rounds of the instance follow the highest round it
+/// knows of in the instance. Whatever the strategy is, the rounds started by distinct proposers are
+/// distinct, as they contain the ids of the proposers (see Ballot).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoundStrategy {
+    /// The first round has the epoch 1. A proposer which restarts, and loses its state, can thus
+    /// start again a round which it already started before the restart, in the same instance.
+    Sequential,
+
+    /// The first round has the given (positive) epoch, e.g. to reserve the lower rounds.
+    StartingAt(usize),
+
+    /// The generation of the proposer, i.e. the number of times it has started, which is durably
+    /// incremented in the file at the given path when the strategy is set, is folded into the high
+    /// bits of the epoch of the first round, so that a restarted proposer always starts above any
+    /// round it previously used.
+    Generation(PathBuf),
+}
+
+/// Which learners a proposer with backpressure waits for, when they fall behind (see
+/// Proposer::with_backpressure). Only the learners which reported their progress recently (see
+/// Learner::with_progress_reports) are taken into account.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BackpressurePolicy {
+    /// The proposer is throttled to the slowest learner, i.e. as long as any learner is too far
+    /// behind.
+    Slowest,
+
+    /// The proposer is throttled as long as fewer than the given (positive) number of learners, or
+    /// of the learners which reported their progress, if they are fewer, are close enough, i.e.
+    /// it waits for the fastest learners, but not for the slower ones (e.g. a learner with a slow
+    /// sink which is allowed to lag).
+    Quorum(usize),
+}
+
+/// How a proposer allocates the instance of each value which it starts proposing (see
+/// Proposer::with_instance_allocator). Whatever the allocator is, phase 1 still reserves the
+/// instance: if it turns out to be taken by another proposer, the value is proposed again in a new
+/// instance, so the allocator only tells how often this happens.
+#[derive(Debug, Clone)]
+pub enum InstanceAllocator {
+    /// The instance after the highest instance known to the proposer, which may be allocated
+    /// concurrently by the other proposers, until their messages tell it that the instance is
+    /// taken. This is the default, and it works whatever the deployment is.
+    Reservation,
+
+    /// The instance after the one taken from the given counter, which is shared (cloned) between
+    /// the proposers of the same process, e.g. several CoLocated nodes run by threads, which thus
+    /// never allocate the same instance. The proposers of other processes must not share it, as
+    /// they would still allocate the same instances as these ones.
+    LocalAtomic(Arc<AtomicUsize>),
+}
+
+impl InstanceAllocator {
+    /// Returns an allocator with a new counter, whose clones share it (see LocalAtomic).
+    pub fn local_atomic() -> Self {
+        InstanceAllocator::LocalAtomic(Arc::new(AtomicUsize::new(0)))
+    }
+}
 
 /// Implement this trait if you are a process which needs to run in a infinite loop, while receiving
 /// and sending messages.
 pub trait Runnable {
     fn run(&mut self);
+
+    /// Runs this process like run, but, if it panics (e.g. because of a bug in the handler of some
+    /// message), it logs the panic and runs it again, up to max_restarts times, after which the
+    /// panic is propagated. This keeps a long-running node available despite transient bugs. The
+    /// process is restarted with its state as it was when it panicked (including its durable
+    /// state, e.g. see Acceptor::with_storage), and the message which was being handled is lost.
+    fn run_supervised(&mut self, max_restarts: usize) {
+        let mut num_of_restarts = 0;
+
+        while let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| self.run())) {
+            let reason = payload
+                .downcast_ref::<&str>()
+                .map(|reason| reason.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown reason".to_string());
+
+            if num_of_restarts == max_restarts {
+                error!(
+                    "[{}] The run loop panicked ({}) after {} restarts: giving up.",
+                    any::type_name::<Self>(),
+                    reason,
+                    num_of_restarts
+                );
+                panic::resume_unwind(payload);
+            }
+
+            num_of_restarts += 1;
+            error!(
+                "[{}] The run loop panicked ({}): restarting it ({} of {}).",
+                any::type_name::<Self>(),
+                reason,
+                num_of_restarts,
+                max_restarts
+            );
+        }
+    }
+}
+
+/// The run loop shared by the nodes, which handles the messages which they receive and, whenever
+/// no message is received within their read timeout (see NetNode::set_read_timeout), runs their
+/// periodic maintenance (see on_idle), i.e. everything which must happen even if no message comes:
+/// the retransmissions, the gap fills, the polls, the gossip and the commands of the control socket.
+trait EventLoop<T>
+where
+    T: Serialize + DeserializeOwned + Clone + Debug,
+{
+    /// Returns the NetNode on which this node receives its messages.
+    fn net_node(&mut self) -> &mut NetNode<T>;
+
+    /// Returns how long this node can wait for a message before its periodic maintenance is due,
+    /// or None if it has no periodic maintenance, in which case it waits until a message comes.
+    fn read_timeout(&self) -> Option<Duration>;
+
+    /// Logs that this node waits for a message.
+    fn log_waiting(&self);
+
+    /// Handles the message m, which was received by this node.
+    fn handle_message(&mut self, m: Message<T>);
+
+    /// Returns whether this node must stop running (e.g. see Proposer::drain).
+    fn is_stopped(&self) -> bool {
+        false
+    }
+
+    /// Runs the periodic maintenance of this node. Each part of it checks by itself whether it is
+    /// due, so this can be called more often than needed.
+    fn on_idle(&mut self);
+
+    /// Runs this node until it stops (see is_stopped), and calls after_each after each message it
+    /// handles, or each time it is idle. The maintenance is also run after a message, if it is
+    /// overdue, so that a steady stream of messages (e.g. the values past a gap) does not postpone
+    /// it forever.
+    fn serve<F: FnMut(&mut Self)>(&mut self, mut after_each: F)
+    where
+        Self: Sized,
+    {
+        // When the periodic maintenance is due, if it is.
+        let mut due: Option<Instant> = None;
+
+        while !self.is_stopped() {
+            self.log_waiting();
+
+            let now = Instant::now();
+            if let Some(timeout) = self.read_timeout() {
+                due = Some(due.map_or(now + timeout, |due| due.min(now + timeout)));
+            }
+
+            let timeout = due.map(|due| due.saturating_duration_since(now));
+            self.net_node().set_read_timeout(timeout);
+
+            if let Some(m) = self.net_node().receive_or_idle() {
+                self.handle_message(m);
+            }
+
+            if due.is_some_and(|due| Instant::now() >= due) {
+                self.on_idle();
+                due = None;
+            }
+
+            after_each(self);
+        }
+    }
 }
 
 /// The struct representing the client in the Paxos algorithm.
@@ -28,9 +511,39 @@ pub struct Client<T> {
 
     id: usize,
 
+    // The Paxos group (i.e. the replicated log) this node participates in. Messages belonging to
+    // other groups are ignored, so that several groups can share the same multicast addresses.
+    group_id: usize,
+
+    // The number of requests sent so far by this client. It is used as the sequence number of the
+    // next request. It is a Cell, so that requests can be sent through a shared reference.
+    num_of_requests: Cell<usize>,
+
+    // A map between the sequence numbers of the requests of this client which have been decided
+    // and the instances where they were decided, and a map between those instances and the
+    // decided values, as told by the proposers with Decided messages.
+    decided_requests: HashMap<usize, usize>,
+    decided_values: HashMap<usize, T>,
+
+    // A map between the sequence numbers of the requests of this client which have been decided
+    // and the watermarks told with them (see Decided::watermark).
+    watermarks: HashMap<usize, usize>,
+
+    // The values of the contiguous chosen prefix of the log known by this client, i.e. of the
+    // instances from 1 to its length, as told by the proposers (see request_with_prefix).
+    chosen_prefix: Vec<T>,
+
+    // The sequence numbers of the requests of this client which a proposer rejected, as told by
+    // the proposers with Rejected messages (see ValueValidator).
+    rejected_requests: HashSet<usize>,
+
     node: NetNode<T>,
 
     proposers_address: SocketAddrV4,
+
+    // If set, the multicast group where the requests are sent, instead of the one of the proposers
+    // (see with_requests_address).
+    requests_address: Option<SocketAddrV4>,
 }
 
 impl<T> Client<T>
@@ -38,29 +551,327 @@ where
     T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
 {
     pub fn new(id: usize, clients_address: SocketAddrV4, proposers_address: SocketAddrV4) -> Self {
+        Client::with_node(NetNode::new(&clients_address), id, proposers_address)
+    }
+
+    /// Creates a client which sends its messages using node.
+    pub(crate) fn with_node(node: NetNode<T>, id: usize, proposers_address: SocketAddrV4) -> Self {
+        #[cfg(feature = "metrics")]
+        let node = node.with_role("client");
+
         Client {
             uuid: Uuid::new_v4(),
             id,
-            node: NetNode::new(&clients_address),
+            group_id: 0,
+            num_of_requests: Cell::new(0),
+            decided_requests: HashMap::new(),
+            decided_values: HashMap::new(),
+            watermarks: HashMap::new(),
+            chosen_prefix: Vec::new(),
+            rejected_requests: HashSet::new(),
+            node,
             proposers_address,
+            requests_address: None,
         }
     }
 
+    /// Replaces the (randomly generated) unique identifier of this client with uuid.
+    pub fn with_uuid(mut self, uuid: Uuid) -> Self {
+        self.uuid = uuid;
+        self
+    }
+
+    /// Makes this client participate in the Paxos group group_id (by default, 0).
+    pub fn with_group_id(mut self, group_id: usize) -> Self {
+        self.group_id = group_id;
+        self
+    }
+
+    /// Writes every message sent or received by this client to message_log.
+    pub fn with_message_log(mut self, message_log: MessageLog) -> Self {
+        self.node.set_message_log(message_log);
+        self
+    }
+
+    /// Makes this client send its messages from the local address sender_address (e.g. to choose
+    /// the network interface which the multicast messages leave through).
+    pub fn with_sender_address(mut self, sender_address: Ipv4Addr) -> Self {
+        self.node.set_sender_address(sender_address);
+        self
+    }
+
+    /// Makes this client send its requests to the multicast group requests_address, which the
+    /// proposers also join (see Proposer::with_requests_address), instead of to the multicast group
+    /// of the proposers.
+    pub fn with_requests_address(mut self, requests_address: SocketAddrV4) -> Self {
+        self.requests_address = Some(requests_address);
+        self
+    }
+
+    /// Limits the rate of the messages sent by this client to rate messages per second, with
+    /// bursts of at most burst messages (see Proposer::with_max_send_rate).
+    pub fn with_max_send_rate(mut self, rate: u32, burst: usize) -> Self {
+        self.node.set_max_send_rate(rate, burst);
+        self
+    }
+
+    /// Makes this client send the messages larger than max_datagram_size bytes in fragments (see
+    /// Proposer::with_max_datagram_size).
+    pub fn with_max_datagram_size(mut self, max_datagram_size: usize) -> Self {
+        self.node.set_max_datagram_size(max_datagram_size);
+        self
+    }
+
+    /// Makes this client refuse to send the messages which do not fit in a datagram, if fragmentation
+    /// is false, instead of fragmenting them (see Proposer::with_fragmentation).
+    pub fn with_fragmentation(mut self, fragmentation: bool) -> Self {
+        self.node.set_fragmentation(fragmentation);
+        self
+    }
+
+    /// Makes this client belong to the cluster named cluster_name (by default, the empty name), so
+    /// that it ignores the messages of the nodes of other clusters (see NetNode::set_cluster_id).
+    pub fn with_cluster_id(mut self, cluster_name: &str) -> Self {
+        self.node.set_cluster_id(cluster_name);
+        self
+    }
+
+    /// Makes this client encrypt its messages with a key derived from secret, which must be shared by
+    /// all the nodes of the cluster, and drop the ones which it cannot decrypt (see
+    /// NetNode::set_encryption_key).
+    #[cfg(feature = "encryption")]
+    pub fn with_encryption_key(mut self, secret: &str) -> Self {
+        self.node.set_encryption_key(secret);
+        self
+    }
+
     pub fn request(&self, value: T) {
+        self.send_request(value, None, None);
+    }
+
+    /// Like request, but, if the proposers have to queue the request, its value is proposed
+    /// before the queued values with a lower priority.
+    pub fn request_with_priority(&self, value: T, priority: Priority) {
+        self.send_request(value, Some(priority), None);
+    }
+
+    /// Like request_tracked, but the proposer which acknowledges the request also sends the chosen
+    /// values which this client does not know yet, up to the instance where value is decided, so
+    /// that, with a single round trip, this client knows what was ordered before its value (see
+    /// chosen_prefix). By default, only the watermark of the chosen prefix is sent (see
+    /// watermark_of). At most PREFIX_MAX_VALUES values are sent at once, so a client which is far
+    /// behind needs several requests to know the whole prefix.
+    pub fn request_with_prefix(&self, value: T) -> u64 {
+        let prefix_from = InstanceId(self.chosen_prefix.len() + 1);
+        self.send_request(value, None, Some(prefix_from)) as u64
+    }
+
+    /// Sends a request of value to the proposers, and returns its sequence number.
+    fn send_request(
+        &self,
+        value: T,
+        priority: Option<Priority>,
+        prefix_from: Option<InstanceId>,
+    ) -> usize {
+        let request_seq = self.num_of_requests.get() + 1;
+        self.num_of_requests.set(request_seq);
+
         let m = Message::Phase0a::<T>(Request {
             value,
             sender_uuid: self.uuid,
+            request_seq,
+            priority,
+            group_id: self.group_id,
+            prefix_from,
         });
 
-        self.node.send(m.clone(), &self.proposers_address);
+        let requests_address = self.requests_address.unwrap_or(self.proposers_address);
+        self.node.send(m.clone(), &requests_address);
 
-        if log_enabled!(Level::Info) {
+        if logs_messages() {
+            info!(
+                "[C={:?}] {:?} sent to {:?}.",
+                self.id,
+                Logged(&m),
+                requests_address
+            );
+        }
+
+        request_seq
+    }
+
+    /// Like request, but it also returns a handle of the request, which can later be used to know
+    /// the instance where value is decided (see instance_of and wait_for_instance). The proposers
+    /// must know the address of the clients (see Proposer::with_clients_address).
+    pub fn request_tracked(&self, value: T) -> u64 {
+        self.send_request(value, None, None) as u64
+    }
+
+    /// Gives up on the request with the given handle (e.g. once wait_for_instance timed out), by
+    /// telling the proposers, with a CancelRequest message, to stop trying to decide its value. A
+    /// queued request is dropped, and the instance where the value is being proposed gets the
+    /// no-op instead, if the proposer has one (see Proposer::with_noop), so that no gap is left in
+    /// the log. If the value was already chosen, or voted for, when the cancellation arrives, it is
+    /// decided anyway, and this client is told where as usual (see instance_of): the cancellation
+    /// then has no effect. A request merged with others into a batch (see Proposer::with_batching)
+    /// cannot be cancelled.
+    pub fn cancel(&self, handle: u64) {
+        let m = Message::CancelRequest::<T>(CancelRequest {
+            request_seq: handle as usize,
+            sender_uuid: self.uuid,
+            group_id: self.group_id,
+        });
+
+        let requests_address = self.requests_address.unwrap_or(self.proposers_address);
+        self.node.send(m.clone(), &requests_address);
+
+        if logs_messages() {
             info!(
                 "[C={:?}] {:?} sent to {:?}.",
-                self.id, m, self.proposers_address
+                self.id,
+                Logged(&m),
+                requests_address
             );
         }
     }
+
+    /// Returns the instance where the value of the request with the given handle was decided, if
+    /// this client has already been told so.
+    pub fn instance_of(&self, handle: u64) -> Option<usize> {
+        self.decided_requests.get(&(handle as usize)).cloned()
+    }
+
+    /// Returns whether the value of the request with the given handle was rejected by a proposer
+    /// (see ValueValidator), i.e. it was not proposed, if this client has already been told so.
+    pub fn is_rejected(&self, handle: u64) -> bool {
+        self.rejected_requests.contains(&(handle as usize))
+    }
+
+    /// Waits until this client is told the instance where the value of the request with the given
+    /// handle was decided, and returns it. None is returned if timeout elapses before, e.g. because
+    /// the value was never chosen (in which case the request can be sent again), or if the request
+    /// was rejected (see is_rejected).
+    pub fn wait_for_instance(&mut self, handle: u64, timeout: Duration) -> Option<usize> {
+        let start = Instant::now();
+
+        loop {
+            if let Some(instance) = self.instance_of(handle) {
+                return Some(instance);
+            }
+
+            if self.is_rejected(handle) {
+                return None;
+            }
+
+            let remaining = timeout.checked_sub(start.elapsed())?;
+
+            // Note that a timeout of zero is not allowed.
+            if let Some(m) = self
+                .node
+                .receive_timeout(Some(remaining.max(Duration::from_millis(1))))
+            {
+                self.handle(m);
+            }
+        }
+    }
+
+    /// Returns the watermark of the chosen prefix of the log, i.e. the last instance up to which
+    /// all the instances are chosen, told by the proposer which acknowledged the request with the
+    /// given handle, if this client has already been told so. If it is not lower than the instance
+    /// of the request (see instance_of), all the values ordered before its value are chosen.
+    pub fn watermark_of(&self, handle: u64) -> Option<usize> {
+        self.watermarks.get(&(handle as usize)).cloned()
+    }
+
+    /// Returns the values of the contiguous chosen prefix of the log known by this client, i.e. of
+    /// the instances from 1 to its length, in order (see request_with_prefix).
+    pub fn chosen_prefix(&self) -> &[T] {
+        &self.chosen_prefix
+    }
+
+    /// Returns the value decided in the given instance, if this client knows it, i.e. if it was the
+    /// value of one of its requests, or if it is in the chosen prefix known by this client.
+    pub fn query(&self, instance: usize) -> Option<T> {
+        match instance
+            .checked_sub(1)
+            .and_then(|i| self.chosen_prefix.get(i))
+        {
+            Some(&v) => Some(v),
+            None => self.decided_values.get(&instance).cloned(),
+        }
+    }
+
+    /// Handles the message m, which was received by this client. Messages of other Paxos groups,
+    /// and Decided (and Rejected) messages for other clients, are ignored.
+    pub fn handle(&mut self, m: Message<T>) {
+        match m {
+            Message::Decided::<T>(decided)
+                if decided.group_id == self.group_id && decided.receiver_uuid == self.uuid =>
+            {
+                if logs_messages() {
+                    info!("[C={:?}] I will handle {:?}.", self.id, Logged(&decided));
+                }
+
+                self.decided_requests
+                    .insert(decided.request_seq, decided.instance.0);
+                self.decided_values
+                    .insert(decided.instance.0, decided.value);
+                self.watermarks
+                    .insert(decided.request_seq, decided.watermark.0);
+
+                // Only the values which extend the known prefix are kept, as the prefixes of
+                // concurrent requests can overlap.
+                let known = self.chosen_prefix.len() + 1;
+                if decided.prefix_from.0 <= known {
+                    let skipped = known - decided.prefix_from.0;
+                    self.chosen_prefix
+                        .extend(decided.prefix.into_iter().skip(skipped));
+                }
+            }
+            Message::Rejected::<T>(rejected)
+                if rejected.group_id == self.group_id && rejected.receiver_uuid == self.uuid =>
+            {
+                if logs_messages() {
+                    info!("[C={:?}] I will handle {:?}.", self.id, Logged(&rejected));
+                }
+
+                self.rejected_requests.insert(rejected.request_seq);
+            }
+            Message::Ping(ping) if ping.group_id == self.group_id => {
+                pong(&self.node, self.uuid, ping)
+            }
+            _ => {
+                if logs_messages() {
+                    info!("[C={:?}] I will ignore {:?}.", self.id, Logged(&m));
+                }
+            }
+        }
+    }
+
+    /// Handles the message contained in datagram, which was delivered to this client by the owner
+    /// of its in-memory channel. Corrupted datagrams are dropped.
+    #[cfg(feature = "testkit")]
+    pub(crate) fn deliver(&mut self, datagram: &[u8]) {
+        if let Some(m) = self.node.decode(datagram) {
+            self.handle(m);
+        }
+    }
+}
+
+/// How the round of a Promise or an Acceptance compares with the round which a proposer drives in
+/// the same instance (see ProposerState::classify).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum RoundClass {
+    // A round before the driven one, e.g. a straggler of a previous round: it is ignored.
+    Stale,
+
+    // The driven round, or any round if the proposer does not drive the instance (e.g. because it
+    // only observes the Acceptance messages of another proposer): it is counted.
+    Current,
+
+    // A round after the driven one, i.e. started by another proposer: this proposer is preempted,
+    // so it backs off (see ProposerState::preempted_by).
+    Higher,
 }
 
 /// In the Multi-Paxos algorithm, a proposer can participate in several instances of the basic Paxos
@@ -72,34 +883,169 @@ struct ProposerState<T> {
     // The value that this proposer initially wants to propose.
     value: Option<T>,
 
-    // The highest-numbered round the proposer has started. This number is incremented in phase 1a.
-    c_rnd: usize,
+    // Whether self.value was requested by a client (rather than being a no-op). In that case, if
+    // another value is decided in this instance, self.value is proposed again in a new instance,
+    // so that it is not lost.
+    client_request: bool,
+
+    // The identifier, i.e. the pair (sender_uuid, request_seq), of the client request of
+    // self.value, if known, so that the client can be told where self.value is decided.
+    request_id: Option<(Uuid, usize)>,
+
+    // The highest-numbered round the proposer has started. Its epoch is incremented in phase 1a.
+    c_rnd: Ballot,
 
     // The value that the proposer has picked for round self.c_rnd. This value can be self.value or
     // a value which is sent from one acceptor, in a Promise message, to this Proposer.
     c_val: Option<T>,
 
+    // The client request whose value is self.c_val, if any, which is the one of the vote adopted
+    // from the acceptors, if any. The requests of two clients can have equal values, so it tells
+    // whether self.c_val is the value of self.request_id (see send_proposal).
+    c_val_request: Option<RequestId>,
+
+    // The round in which this proposer sent its Proposal, if any. The value of a round is picked
+    // once, from the Promise messages of the first majority of the acceptors (which is enough for
+    // Paxos to be safe), so the Promise messages which arrive later in the round (e.g. out of order)
+    // must not change it, even if their v_rnd is higher: two distinct values proposed in the same
+    // round could both be voted for.
+    proposed_in: Ballot,
+
     // In order to send a Proposal to the acceptors, the majority of the acceptors must have
     // responded, to the initial Preparation message, with a Promise message, which contains a rnd
     // field (which is the highest-numbered round the corresponding acceptor has PARTICIPATED in).
     // rnd_received is thus used to keep track of the rnd received from the acceptors. In order to
-    // send a Proposal message to the acceptors, all rnd received must be equal to self.c_rnd.
-    rnd_received: Vec<usize>,
+    // send a Proposal message to the acceptors, all rnd received must be equal to self.c_rnd. The
+    // rnd are keyed by the unique identifier of the acceptor which sent them, so that an acceptor
+    // which sends its Promise more than once (e.g. because of a retransmission) is counted once.
+    rnd_received: HashMap<Uuid, Ballot>,
 
     // A Proposer needs to propose the v_val with the associated highest v_rnd received. This field
     // is thus used to keep track of such v_rnd.
-    highest_v_rnd_received: usize,
+    highest_v_rnd_received: Ballot,
 
-    // The v_val associated with self.highest_v_rnd_received. If self.highest_v_rnd_received == 0,
-    // then this will be set to self.value, because, if self.highest_v_rnd_received == 0, it means
+    // The v_val associated with self.highest_v_rnd_received. If self.highest_v_rnd_received is
+    // Ballot::ZERO, then this will be set to self.value, because, in that case, it means
     // that acceptors are in the first round and have not yet received any proposal.
     associated_v_val_received: Option<T>,
 
+    // The summary of the v_val associated with self.highest_v_rnd_received, if the acceptor sent
+    // it instead of the value itself (see Summarize), and whether this proposer asks for the
+    // values themselves in the next rounds, because it could not materialize such a summary.
+    associated_v_summary_received: Option<u64>,
+    full_values: bool,
+
+    // The client request of the v_val associated with self.highest_v_rnd_received, if any.
+    associated_v_request_received: Option<RequestId>,
+
     // In order to send a Learning message to the learners, the majority of the acceptors must have
     // responded, to the Proposal message, with an Acceptance message, which contains a v_rnd and
     // the corresponding v_val. More specifically, to send a Learning message to the learners, all
-    // v_rnd in self.v_rnd_received must be equal to self.c_rnd.
-    v_rnd_received: Vec<usize>,
+    // v_rnd in self.v_rnd_received must be equal to self.c_rnd. As for self.rnd_received, the v_rnd
+    // are keyed by the unique identifier of the acceptor which sent them, so that each acceptor is
+    // counted once.
+    v_rnd_received: HashMap<Uuid, Ballot>,
+
+    // The highest round of another proposer which preempted the round driven by this proposer, if
+    // any. This proposer then backs off: it stops driving its round, which cannot succeed anymore,
+    // and it leaves the instance to the other proposer, instead of competing with it, but its next
+    // round of the instance, if any, starts after this one (see prepare_instance).
+    preempted_by: Ballot,
+
+    // The number of consecutive rounds of this instance, driven by this proposer, which were
+    // preempted, and when the last of them was, whether this proposer starves in this instance
+    // (see Proposer::with_starvation_policy).
+    consecutive_failures: usize,
+    preempted_at: Option<Instant>,
+    starved: bool,
+
+    // When this proposer started the first round of this instance, until the instance is decided
+    // (see Proposer::latency_histogram).
+    started_at: Option<Instant>,
+
+    // The value of the client request of this instance, if its client cancelled it (see
+    // Client::cancel). self.value is then the no-op, if any, but the cancelled value may still be
+    // decided (e.g. if an acceptor already voted for it), in which case the client is told so.
+    cancelled: Option<T>,
+
+    // The value of the client request of this instance, with its request, if this proposer forced
+    // the no-op in the instance (see Proposer::force_noop). self.value is then the no-op, and the
+    // displaced value is proposed again in a new instance, unless it is decided in this one.
+    displaced: Option<(T, Option<RequestId>)>,
+}
+
+impl<T: Copy + PartialEq> ProposerState<T> {
+    /// Settles the client request of self.value, once v_val is decided in this instance, where
+    /// for_request tells whether v_val was decided for that request (an equal value may be the one
+    /// of another request). If another value (e.g. one adopted from the acceptors) has been
+    /// decided, the value requested by the client is returned first, with its request, as it must
+    /// be proposed again in a new instance. Otherwise, the request is returned second, as its
+    /// client must be told where its value was decided (only once).
+    fn settle(
+        &mut self,
+        v_val: T,
+        for_request: bool,
+    ) -> (Option<(T, Option<RequestId>)>, Option<RequestId>) {
+        if let Some(cancelled) = self.cancelled {
+            let request_id = self.request_id.take();
+            return (None, request_id.filter(|_| cancelled == v_val));
+        }
+
+        if let Some((displaced, request_id)) = self.displaced.take() {
+            return if displaced == v_val {
+                (None, request_id)
+            } else {
+                (Some((displaced, request_id)), None)
+            };
+        }
+
+        match self.value {
+            Some(value) if self.client_request && (value != v_val || !for_request) => {
+                self.client_request = false;
+                (Some((value, self.request_id.take())), None)
+            }
+            Some(value) if value == v_val => (None, self.request_id.take()),
+            _ => (None, None),
+        }
+    }
+
+    /// Returns whether the value of v_request, the client request of a value decided in this
+    /// instance, if known, is the one of self.request_id. If either of them is unknown, the values
+    /// tell the requests apart (see settle).
+    fn is_for_request(&self, v_request: Option<RequestId>) -> bool {
+        match (self.request_id, v_request) {
+            (Some(request_id), Some(v_request)) => request_id == v_request,
+            _ => true,
+        }
+    }
+
+    /// Classifies rnd, the round of a Promise or an Acceptance of this instance, with respect to
+    /// self.c_rnd, the round driven by this proposer, if any (i.e. unless it is Ballot::ZERO).
+    fn classify(&self, rnd: Ballot) -> RoundClass {
+        if self.c_rnd == Ballot::ZERO {
+            return RoundClass::Current;
+        }
+
+        match rnd.cmp(&self.c_rnd) {
+            Ordering::Less => RoundClass::Stale,
+            Ordering::Equal => RoundClass::Current,
+            Ordering::Greater => RoundClass::Higher,
+        }
+    }
+
+    /// Makes this proposer back off from this instance, as it was preempted by rnd (see
+    /// self.preempted_by).
+    fn preempt(&mut self, rnd: Ballot) {
+        // The round driven by this proposer fails once, however many messages of higher rounds it
+        // receives.
+        if self.preempted_by <= self.c_rnd {
+            self.consecutive_failures += 1;
+            self.preempted_at = Some(Instant::now());
+        }
+
+        self.preempted_by = self.preempted_by.max(rnd);
+        self.rnd_received.clear();
+    }
 }
 
 // I had to implement Default manually. See https://github.com/rust-lang/rust/issues/45036.
@@ -107,22 +1053,69 @@ impl<T> Default for ProposerState<T> {
     fn default() -> Self {
         ProposerState {
             value: None,
-            c_rnd: 0,
+            client_request: false,
+            request_id: None,
+            c_rnd: Ballot::ZERO,
             c_val: None,
-            rnd_received: Vec::new(),
-            highest_v_rnd_received: 0,
+            c_val_request: None,
+            proposed_in: Ballot::ZERO,
+            rnd_received: HashMap::new(),
+            highest_v_rnd_received: Ballot::ZERO,
             associated_v_val_received: None,
-            v_rnd_received: Vec::new(),
+            associated_v_request_received: None,
+            associated_v_summary_received: None,
+            full_values: false,
+            v_rnd_received: HashMap::new(),
+            preempted_by: Ballot::ZERO,
+            consecutive_failures: 0,
+            preempted_at: None,
+            starved: false,
+            started_at: None,
+            cancelled: None,
+            displaced: None,
         }
     }
 }
 
+/// A Learning message which a proposer sent, and which has not been acknowledged by enough
+/// learners yet (see Proposer::with_learn_acks).
+struct UnacknowledgedLearning<T> {
+    learned_value: T,
+
+    // The unique identifiers of the learners which acknowledged it so far.
+    acknowledged_by: HashSet<Uuid>,
+
+    // When it was sent last.
+    sent_at: Instant,
+}
+
+/// An epoch of a proposer (see Proposer::establish_epoch), i.e. a round of all the instances of
+/// the tail of the log, from from_instance on, whose phase 1 is run once for all of them.
+struct Epoch<T> {
+    rnd: Ballot,
+
+    from_instance: usize,
+
+    // The acceptors which promised rnd in the tail so far, and whether they are a majority, i.e.
+    // whether the epoch is established.
+    promised_by: HashSet<Uuid>,
+    established: bool,
+
+    // The highest vote (v_rnd, v_val and its client request, if any) reported by these acceptors
+    // in each instance of the tail, whose value must be proposed in it (as usual in phase 2a).
+    votes: BTreeMap<usize, (Ballot, T, Option<RequestId>)>,
+}
+
 /// The struct representing the proposer in the Paxos algorithm.
 pub struct Proposer<T> {
     uuid: Uuid,
 
     id: usize,
 
+    // The Paxos group (i.e. the replicated log) this node participates in. Messages belonging to
+    // other groups are ignored, so that several groups can share the same multicast addresses.
+    group_id: usize,
+
     // Each instance of the Paxos algorithm, in the Multi-Paxos algorithm, is associated with 1
     // ProposerState<T>. This is a map from each instance (of a basic Paxos algorithm), which is a
     // number, to the corresponding ProposerState<T> needed to complete that instance.
@@ -138,6 +1131,187 @@ pub struct Proposer<T> {
     // proposer starts, this map is empty.
     learned_values: HashMap<usize, T>,
 
+    // How the instances of the values which this proposer starts proposing are allocated.
+    instance_allocator: InstanceAllocator,
+
+    // The identifiers, i.e. the pairs (sender_uuid, request_seq), of the requests recently
+    // handled by this proposer (see with_request_dedup). A request with one of these identifiers
+    // is a duplicate (e.g. a retransmission) and does not start a new instance.
+    recent_requests: RecentRequests,
+
+    // The first instance of the chosen prefix which the clients asked for with the recent requests
+    // (see Client::request_with_prefix), keyed by the identifiers of these requests, until they
+    // are acknowledged (or evicted from self.recent_requests).
+    prefix_requests: HashMap<RequestId, usize>,
+
+    // The last instance of the contiguous prefix of self.learned_values, i.e. all the instances
+    // from 1 up to it are learned (see Decided::watermark). It is only advanced when needed.
+    chosen_watermark: usize,
+
+    // If set, the acceptors may send the summaries of their voted values (see Summarize), which are
+    // materialized from the values known to this proposer, i.e. the most recent ones it has seen,
+    // keyed by their summaries, from the oldest to the newest.
+    summarize: Option<fn(&T) -> u64>,
+    known_values: HashMap<u64, T>,
+    known_summaries: VecDeque<u64>,
+
+    // The value which this proposer proposes, in an instance which was apparently never decided,
+    // when a learner asks for it with a NoopRequest message. If None, such requests are ignored.
+    noop: Option<T>,
+
+    // What this proposer proposes in the instances which it completes without a value of its own
+    // (see complete_instance).
+    on_unknown_value: UnknownValuePolicy<T>,
+
+    // What this proposer does with the Promise and the Acceptance messages of the instances which
+    // it does not know, and the number of those which it dropped (see FutureInstancePolicy). With
+    // FutureInstancePolicy::TriggerCatchUp, only the dropped messages beyond the highest instance
+    // which already made this proposer catch up make it catch up again.
+    on_future_instance: FutureInstancePolicy,
+    num_of_future_messages_dropped: usize,
+    highest_future_instance: usize,
+
+    // If set, the number of consecutive preempted rounds of an instance after which this proposer
+    // starves in it, and what it does then (see with_starvation_policy), the instances whose next
+    // round waits until the given time, and the number of instances where it starved so far.
+    starvation: Option<(usize, StarvationPolicy)>,
+    deferred_rounds: BTreeMap<usize, Instant>,
+    num_of_starved_instances: usize,
+
+    // The epoch of this proposer, if it established one, or is establishing it (see
+    // establish_epoch), the highest round of its previous epochs, and of the rounds which
+    // invalidated them, after which its next epoch starts, and the number of its epochs which were
+    // invalidated so far. If prepare_once_per_epoch is set, it establishes an epoch when it runs.
+    epoch: Option<Epoch<T>>,
+    last_epoch_rnd: Ballot,
+    num_of_invalidated_epochs: usize,
+    prepare_once_per_epoch: bool,
+
+    // If set, the values of the client requests which it does not validate are not proposed (see
+    // with_value_validator).
+    validator: Option<Box<dyn ValueValidator<T> + Send>>,
+
+    // The address, if any, where the other nodes must send the replies addressed to this proposer,
+    // instead of self.proposers_address (see with_advertised_address).
+    advertised_address: Option<SocketAddrV4>,
+
+    // The instances started by this proposer which have not been decided yet.
+    in_flight: HashSet<usize>,
+
+    // The times this proposer took to decide the instances it started (see latency_histogram).
+    latencies: LatencyHistogram,
+
+    // If set, this proposer does not start a new instance while self.in_flight contains this many
+    // instances. The values of the client requests received in the meantime are queued in
+    // self.pending_requests, by priority and, within the same priority, in the order they were
+    // received.
+    max_in_flight: Option<usize>,
+    pending_requests: BTreeMap<Priority, VecDeque<Request<T>>>,
+
+    // The order in which the queued requests of the same priority are proposed, and the client of
+    // the request which was proposed last (see OrderingPolicy::ByClient).
+    ordering_policy: OrderingPolicy,
+    last_served_client: Option<Uuid>,
+
+    // If set, this proposer gossips (see gossip) with the other proposers at this interval, so
+    // that the learned values known by all proposers eventually converge.
+    gossip_interval: Option<Duration>,
+
+    // When this proposer gossiped last, and the random jitter added to the gossip interval until it
+    // gossips next (see GOSSIP_JITTER_FRACTION).
+    last_gossip: Instant,
+    gossip_jitter: Duration,
+
+    // The source of the random numbers of this proposer (see with_random_source).
+    random: Box<dyn RandomSource + Send>,
+
+    // The epoch of the first round which this proposer starts in an instance, and its durably
+    // stored generation, if the epoch depends on it (see with_round_strategy).
+    first_epoch: usize,
+    generation: Option<GenerationCounter>,
+
+    // If set, the Learning messages sent by this proposer are sent again, every retransmit
+    // interval, until the given number of learners acknowledged them (see with_learn_acks). The
+    // instances whose Learning messages are not acknowledged enough yet are kept in
+    // self.unacknowledged.
+    learn_acks: Option<(usize, Duration)>,
+    unacknowledged: BTreeMap<usize, UnacknowledgedLearning<T>>,
+
+    // If set, this proposer does not start new instances while the learners (according to the
+    // policy) have more than the given number of started instances left to deliver (see
+    // with_backpressure). The last progress reported by each learner, i.e. the next instance it
+    // must deliver, is kept, with when it was reported, in self.learner_progress.
+    backpressure: Option<(usize, BackpressurePolicy)>,
+    learner_progress: HashMap<Uuid, (usize, Instant)>,
+
+    // If set, this proposer does not start an instance more than alpha instances after the chosen
+    // prefix of the log (see with_alpha). The values which it would propose beyond the window
+    // (e.g. the values which lost their instance) wait in self.beyond_window.
+    alpha: Option<usize>,
+    beyond_window: VecDeque<(T, Option<RequestId>)>,
+
+    // If set, the decided values which change the membership of the acceptors (see
+    // with_membership_changes). The majority of each membership is kept in self.memberships, by
+    // the first instance in which it takes effect, whereas the instances before the first change
+    // need self.majority_of_acceptors.
+    membership: Option<fn(&T) -> Option<usize>>,
+    memberships: BTreeMap<usize, usize>,
+
+    // If set, the values of the queued client requests are merged into batches (see
+    // with_batching). The requests merged into the value of another request, whose clients are
+    // told where it is decided along with its own client, are kept in self.batched_requests, by
+    // the identifier of that request.
+    merge: Option<fn(&T, &T) -> Option<T>>,
+    batched_requests: HashMap<RequestId, Vec<RequestId>>,
+
+    // The number of the client requests which this proposer cancelled so far, i.e. which it
+    // dropped, or stopped proposing, when their clients gave up on them (see Client::cancel).
+    num_of_cancelled_requests: usize,
+
+    // If set, the clients are told, with a Decided message, where the values of their requests are
+    // decided.
+    clients_address: Option<SocketAddrV4>,
+
+    // Whether this proposer has received a Report from another proposer, and whether it starts
+    // serving without waiting for one (see with_bootstrap).
+    caught_up: bool,
+    bootstrap: bool,
+
+    // While this proposer is catching up (see start_catch_up), the client requests (and the
+    // NoopRequest messages), which would make it start new instances, are postponed here.
+    catching_up: bool,
+    postponed: Vec<Message<T>>,
+
+    // The instances which this proposer is recovering (see recover_log), and whether it recovers
+    // the log each time it has caught up (see with_log_recovery). While it is recovering, the client
+    // requests (and the NoopRequest messages) are postponed too, like while it is catching up.
+    recovering: BTreeSet<usize>,
+    log_recovery: bool,
+
+    // If set, this proposer waits, for this long, for a majority of the acceptors to be reachable,
+    // when it runs (see with_quorum_wait).
+    quorum_wait: Option<Duration>,
+
+    // How long this proposer waits for its instances in flight to be decided, once it drains (see
+    // drain), and until when it waits, if it is draining.
+    drain_timeout: Duration,
+    drain_deadline: Option<Instant>,
+
+    // Whether this proposer stepped down (see step_down), i.e. whether it ignores the client
+    // requests, but keeps running.
+    stepped_down: bool,
+
+    // If set, the value which marks a checkpoint of the application in the log (see
+    // with_checkpoint). The decided checkpoints which are not stable yet are kept in
+    // self.checkpoints, with the learners which acknowledged their delivery so far, and the log is
+    // truncated below self.truncated_below, i.e. the instance of the last stable checkpoint.
+    checkpoint: Option<T>,
+    checkpoints: BTreeMap<usize, HashSet<Uuid>>,
+    truncated_below: usize,
+
+    // If set, the control socket whose commands this proposer answers (see with_admin_address).
+    admin: Option<AdminListener>,
+
     node: NetNode<T>,
 
     proposers_address: SocketAddrV4,
@@ -151,6 +1325,11 @@ impl<T> Proposer<T>
 where
     T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
 {
+    /// Creates a proposer, which needs the votes of a majority of the num_of_acceptors acceptors
+    /// at acceptors_address to choose a value. With 1 acceptor, the majority is that acceptor,
+    /// which decides alone, but whose failure stops every instance (see the README).
+    ///
+    /// Panics if num_of_acceptors is zero.
     pub fn new(
         id: usize,
         proposers_address: SocketAddrV4,
@@ -158,667 +1337,6820 @@ where
         learners_address: SocketAddrV4,
         num_of_acceptors: usize,
     ) -> Self {
+        Proposer::with_node(
+            NetNode::new(&proposers_address),
+            id,
+            proposers_address,
+            acceptors_address,
+            learners_address,
+            num_of_acceptors,
+        )
+    }
+
+    /// Creates a proposer which sends and receives its messages using node.
+    ///
+    /// Panics if num_of_acceptors is zero, as no value could ever be chosen.
+    pub(crate) fn with_node(
+        node: NetNode<T>,
+        id: usize,
+        proposers_address: SocketAddrV4,
+        acceptors_address: SocketAddrV4,
+        learners_address: SocketAddrV4,
+        num_of_acceptors: usize,
+    ) -> Self {
+        assert!(num_of_acceptors > 0, "Expected at least 1 acceptor");
+
+        #[cfg(feature = "metrics")]
+        let node = node.with_role("proposer");
+
         Proposer {
             uuid: Uuid::new_v4(),
             id,
+            group_id: 0,
             proposer_states: HashMap::new(),
             majority_of_acceptors: num_of_acceptors / 2 + 1,
             num_of_instances: 0,
             learned_values: HashMap::new(),
-            node: NetNode::new(&proposers_address),
+            instance_allocator: InstanceAllocator::Reservation,
+            recent_requests: RecentRequests::new(RECENT_REQUESTS_WINDOW, RECENT_REQUESTS_CAPACITY),
+            prefix_requests: HashMap::new(),
+            chosen_watermark: 0,
+            summarize: None,
+            known_values: HashMap::new(),
+            known_summaries: VecDeque::new(),
+            noop: None,
+            on_unknown_value: UnknownValuePolicy::Stall,
+            on_future_instance: FutureInstancePolicy::RejectBeyondWatermark(
+                FUTURE_INSTANCES_WINDOW,
+            ),
+            num_of_future_messages_dropped: 0,
+            highest_future_instance: 0,
+            starvation: None,
+            deferred_rounds: BTreeMap::new(),
+            num_of_starved_instances: 0,
+            epoch: None,
+            last_epoch_rnd: Ballot::ZERO,
+            num_of_invalidated_epochs: 0,
+            prepare_once_per_epoch: false,
+            validator: None,
+            advertised_address: None,
+            in_flight: HashSet::new(),
+            latencies: LatencyHistogram::default(),
+            max_in_flight: None,
+            pending_requests: BTreeMap::new(),
+            ordering_policy: OrderingPolicy::Arrival,
+            last_served_client: None,
+            gossip_interval: None,
+            last_gossip: Instant::now(),
+            gossip_jitter: Duration::from_millis(0),
+            random: Box::new(StdRng::from_entropy()),
+            first_epoch: 1,
+            generation: None,
+            learn_acks: None,
+            unacknowledged: BTreeMap::new(),
+            backpressure: None,
+            learner_progress: HashMap::new(),
+            alpha: None,
+            beyond_window: VecDeque::new(),
+            membership: None,
+            memberships: BTreeMap::new(),
+            merge: None,
+            batched_requests: HashMap::new(),
+            num_of_cancelled_requests: 0,
+            clients_address: None,
+            caught_up: false,
+            bootstrap: false,
+            catching_up: false,
+            postponed: Vec::new(),
+            recovering: BTreeSet::new(),
+            log_recovery: false,
+            quorum_wait: None,
+            drain_timeout: DRAIN_TIMEOUT,
+            drain_deadline: None,
+            stepped_down: false,
+            checkpoint: None,
+            checkpoints: BTreeMap::new(),
+            truncated_below: 1,
+            admin: None,
+            node,
             proposers_address,
             acceptors_address,
             learners_address,
         }
     }
 
-    // Handlers
+    /// Replaces the (randomly generated) unique identifier of this proposer with uuid.
+    pub fn with_uuid(mut self, uuid: Uuid) -> Self {
+        self.uuid = uuid;
+        self
+    }
 
-    /// Handles the Request message sent by a client to this proposer.
-    fn handle_request(&mut self, request: Request<T>) {
-        if log_enabled!(Level::Info) {
-            info!("[P={:?}] I will handle {:?}.", self.id, request);
-        }
+    /// Makes this proposer participate in the Paxos group group_id (by default, 0).
+    pub fn with_group_id(mut self, group_id: usize) -> Self {
+        self.group_id = group_id;
+        self
+    }
 
-        self.prepare(request.value);
+    /// Writes every message sent or received by this proposer to message_log.
+    pub fn with_message_log(mut self, message_log: MessageLog) -> Self {
+        self.node.set_message_log(message_log);
+        self
     }
 
-    /// Handles the CatchUp messages sent by the learners.
-    fn handle_catch_up(&mut self, catch_up: CatchUp) {
-        // If it was another proposer or a learner that sent the CatchUp message, then I will
-        // report, otherwise, because the sender is self, nothing is done. So, this avoids
-        // responding to a CatchUp message sent by itself: of course, this would be a useless
-        // operation, and actually it would only mess up with the answers from the other proposers.
-        if catch_up.sender_uuid != self.uuid {
-            if log_enabled!(Level::Info) {
-                info!("[P={:?}] I will handle {:?}.", self.id, catch_up);
-            }
+    /// Makes this proposer send its messages from the local address sender_address (e.g. to choose
+    /// the network interface which the multicast messages leave through).
+    pub fn with_sender_address(mut self, sender_address: Ipv4Addr) -> Self {
+        self.node.set_sender_address(sender_address);
+        self
+    }
 
-            self.report(catch_up.sender_uuid, catch_up.sender_type);
-        } else {
-            if log_enabled!(Level::Info) {
-                info!("[P={:?}] I will NOT handle {:?}.", self.id, catch_up);
-            }
-        }
+    /// Limits the rate of the messages sent by this proposer to rate messages per second, on
+    /// average, with bursts of at most burst messages, e.g. so that a retry storm does not flood
+    /// the multicast groups of the cluster. The messages sent beyond this rate wait for tokens, by
+    /// priority (e.g. the Preparation and Proposal messages come last), in a queue of at most burst
+    /// messages, beyond which they are dropped (and counted), as if they had been lost, except the
+    /// Learning messages, which announce the decisions (see NetNode::set_max_send_rate).
+    pub fn with_max_send_rate(mut self, rate: u32, burst: usize) -> Self {
+        self.node.set_max_send_rate(rate, burst);
+        self
     }
 
-    /// Handles the Report message sent by a proposer to this proposer.
-    fn handle_report(&mut self, report: Report<T>) {
-        // If the destination of the Report message, i.e. report.receiver_uid, is equal to self.uuid,
-        // then it means that this Report message was sent to this proposer.
-        if report.receiver_uuid == self.uuid {
-            if log_enabled!(Level::Info) {
-                info!("[P={:?}] I will handle {:?}.", self.id, report);
-            }
+    /// Makes this proposer send the messages which do not fit in a datagram of max_datagram_size
+    /// bytes (e.g. the Proposal and Learning messages of large values) in several datagrams, which
+    /// the receivers reassemble. By default, the datagrams sent over UDP have at most 1400 bytes,
+    /// to fit in the MTU of most networks, and the other ones at most 16 KiB, the size of the
+    /// receive buffer of the nodes. A message whose fragment is lost is lost as a whole (see
+    /// NetNode::set_max_datagram_size).
+    ///
+    /// Panics if max_datagram_size is too small to carry a fragment, or larger than 16 KiB.
+    pub fn with_max_datagram_size(mut self, max_datagram_size: usize) -> Self {
+        self.node.set_max_datagram_size(max_datagram_size);
+        self
+    }
 
-            self.num_of_instances = report.num_of_instances;
-            self.learned_values = report.learned_values;
-        } else {
-            if log_enabled!(Level::Info) {
-                info!("[P={:?}] I will NOT handle {:?}.", self.id, report);
-            }
-        }
+    /// Makes this proposer refuse to send the messages which do not fit in a datagram (see
+    /// with_max_datagram_size), if fragmentation is false, instead of fragmenting them, which is
+    /// the default. A refused message (e.g. the Report of a long log) is dropped, and logged as an
+    /// error, with its size, at the time it is sent, rather than lost silently by IP (see
+    /// num_of_oversized_messages).
+    pub fn with_fragmentation(mut self, fragmentation: bool) -> Self {
+        self.node.set_fragmentation(fragmentation);
+        self
     }
 
-    /// Handles the Promise message sent by an acceptor to this proposer.
-    fn handle_promise(&mut self, promise: Promise<T>) {
-        if promise.receiver_uuid == self.uuid {
-            if log_enabled!(Level::Info) {
-                info!("[P={:?}] I will handle {:?}.", self.id, promise);
+    /// Makes this proposer belong to the cluster named cluster_name (by default, the empty name), so
+    /// that it ignores the messages of the nodes of other clusters (see NetNode::set_cluster_id).
+    pub fn with_cluster_id(mut self, cluster_name: &str) -> Self {
+        self.node.set_cluster_id(cluster_name);
+        self
+    }
+
+    /// Makes this proposer encrypt its messages with a key derived from secret, which must be shared by
+    /// all the nodes of the cluster, and drop the ones which it cannot decrypt (see
+    /// NetNode::set_encryption_key).
+    #[cfg(feature = "encryption")]
+    pub fn with_encryption_key(mut self, secret: &str) -> Self {
+        self.node.set_encryption_key(secret);
+        self
+    }
+
+    /// Makes a separate thread receive the messages of this proposer, as soon as they arrive, and
+    /// queue them, up to capacity messages, until this proposer handles them, so that a slow handler
+    /// does not make the socket drop the messages which arrive in the meantime.
+    pub fn with_receive_queue(mut self, capacity: usize) -> Self {
+        self.node.set_receive_queue(capacity);
+        self
+    }
+
+    /// Remembers value, so that it can be materialized from its summary (see Summarize), if this
+    /// proposer has summaries.
+    fn remember_value(&mut self, value: T) {
+        if let Some(summarize) = self.summarize {
+            let summary = summarize(&value);
+
+            if self.known_values.insert(summary, value).is_none() {
+                self.known_summaries.push_back(summary);
+
+                if self.known_summaries.len() > KNOWN_VALUES_WINDOW {
+                    if let Some(oldest) = self.known_summaries.pop_front() {
+                        self.known_values.remove(&oldest);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Makes this proposer propose noop in the instances which the learners report as stuck (see
+    /// Learner::with_gap_timeout), and in the instances of the client requests which are cancelled
+    /// (see Client::cancel). noop must be a value which the application ignores.
+    pub fn with_noop(mut self, noop: T) -> Self {
+        self.noop = Some(noop);
+        self
+    }
+
+    /// Makes this proposer treat checkpoint, once it is decided in an instance i, as a tombstone of
+    /// the log below i. The application must only propose it once the state which results from the
+    /// values of the previous instances can be restored without them, e.g. because its learners
+    /// snapshot that state when they deliver the checkpoint (see Learner::with_checkpoint).
+    ///
+    /// The checkpoint of i becomes stable, and this proposer truncates its log below i (i.e. it
+    /// drops the learned values and the state of the previous instances), only once every instance
+    /// up to i is decided and known to this proposer. So no instance below i is left to decide, and
+    /// the checkpoint was voted for by a majority of the acceptors, which stored their votes durably
+    /// if they have storage (see Acceptor::with_storage), so it is never undone. Moreover, the
+    /// checkpoint is only stable once as many learners as the Learning messages need acknowledged
+    /// it (see with_learn_acks), which should be a majority of them, so that their snapshots are
+    /// durable before the values which they replace are dropped.
+    ///
+    /// This proposer then tells the acceptors (see Acceptor::with_log_truncation) and the other
+    /// proposers, with a Truncation message, to truncate their logs below i too, so all the nodes
+    /// agree on the truncation point, which is in the log itself. The learners which are behind it
+    /// skip the truncated instances (see Report::checkpoint). All the proposers (and the learners)
+    /// must use the same checkpoint, which must differ from the no-op (see with_noop).
+    ///
+    /// Panics if this proposer does not wait for the acknowledgments of the learners, i.e. if
+    /// with_learn_acks was not called before, as no checkpoint could be stable without them.
+    pub fn with_checkpoint(mut self, checkpoint: T) -> Self {
+        assert!(
+            self.learn_acks.is_some(),
+            "Expected the acknowledgments of the learners (see with_learn_acks) in order to truncate the log"
+        );
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+
+    /// Returns the instance of the last stable checkpoint (see with_checkpoint), below which the
+    /// log of this proposer is truncated, or 1 if it is not truncated.
+    pub fn truncated_below(&self) -> usize {
+        self.truncated_below
+    }
+
+    /// Returns the last instance of the contiguous prefix of the log which this proposer knows to be
+    /// decided, i.e. all the instances from 1 up to it are decided, or 0 if it knows no such prefix.
+    /// Unlike next_instance, it only advances once the gaps before it are filled.
+    pub fn committed_watermark(&self) -> usize {
+        let mut watermark = self.chosen_watermark;
+
+        while self.learned_values.contains_key(&(watermark + 1)) {
+            watermark += 1;
+        }
+
+        watermark
+    }
+
+    /// Returns the number of the Promise and the Acceptance messages which this proposer dropped,
+    /// because their instances were too far ahead (see with_future_instance_policy).
+    pub fn num_of_future_messages_dropped(&self) -> usize {
+        self.num_of_future_messages_dropped
+    }
+
+    /// Returns the number of messages which this proposer refused to send, because they did not fit
+    /// in a datagram (see with_fragmentation).
+    pub fn num_of_oversized_messages(&self) -> usize {
+        self.node.num_of_oversized()
+    }
+
+    /// Returns the number of received datagrams which this proposer dropped, because they were
+    /// corrupted (see NetNode::num_of_corrupted).
+    pub fn num_of_corrupted_datagrams(&self) -> usize {
+        self.node.num_of_corrupted()
+    }
+
+    /// Returns the number of received datagrams which this proposer dropped, because they were sent
+    /// by the nodes of another cluster (see with_cluster_id).
+    pub fn num_of_foreign_datagrams(&self) -> usize {
+        self.node.num_of_foreign()
+    }
+
+    /// Returns the number of received datagrams which this proposer dropped, because it could not
+    /// decrypt them (see with_encryption_key).
+    #[cfg(feature = "encryption")]
+    pub fn num_of_undecryptable_datagrams(&self) -> usize {
+        self.node.num_of_undecryptable()
+    }
+
+    /// Makes this proposer recover the log (see recover_log) each time it has caught up with the
+    /// other proposers (see start_catch_up), i.e. when it takes over from them (e.g. after the
+    /// proposer which drove the instances failed, or after a restart).
+    ///
+    /// Panics if this proposer has no no-op (see with_noop).
+    pub fn with_log_recovery(mut self) -> Self {
+        assert!(
+            self.noop.is_some(),
+            "Expected a no-op (see with_noop) in order to recover the log"
+        );
+        self.log_recovery = true;
+        self
+    }
+
+    /// Makes this proposer propose according to policy in the instances which it completes without
+    /// a value of its own (see complete_instance), instead of stalling them.
+    pub fn with_unknown_value_policy(mut self, policy: UnknownValuePolicy<T>) -> Self {
+        self.on_unknown_value = policy;
+        self
+    }
+
+    /// Makes this proposer handle according to policy the Promise and the Acceptance messages of the
+    /// instances which it does not know, and which are beyond the highest instance it knows (see
+    /// FutureInstancePolicy), instead of dropping those more than 65536 instances beyond it.
+    pub fn with_future_instance_policy(mut self, policy: FutureInstancePolicy) -> Self {
+        self.on_future_instance = policy;
+        self
+    }
+
+    /// Makes this proposer stop starting new rounds, as usual, in an instance where its last
+    /// threshold rounds were all preempted by the rounds of other proposers, i.e. where it starves,
+    /// and handle the instance according to policy instead (see StarvationPolicy), so that a
+    /// proposer which keeps losing the race for an instance does not keep wasting messages on it.
+    ///
+    /// Panics if threshold is zero, or if the backoff_cap of StarvationPolicy::BackOff is smaller
+    /// than its backoff_base.
+    pub fn with_starvation_policy(mut self, threshold: usize, policy: StarvationPolicy) -> Self {
+        assert!(
+            threshold > 0,
+            "Expected a positive threshold of preempted rounds"
+        );
+
+        if let StarvationPolicy::BackOff {
+            backoff_base,
+            backoff_cap,
+        } = policy
+        {
+            assert!(
+                backoff_cap >= backoff_base,
+                "Expected a backoff_cap which is not smaller than the backoff_base"
+            );
+        }
+
+        self.starvation = Some((threshold, policy));
+        self
+    }
+
+    /// Returns the number of the instances where this proposer starved so far (see
+    /// with_starvation_policy).
+    pub fn num_of_starved_instances(&self) -> usize {
+        self.num_of_starved_instances
+    }
+
+    /// Returns the number of consecutive rounds of instance, driven by this proposer, which were
+    /// preempted by the rounds of other proposers so far.
+    pub fn consecutive_failures(&self, instance: usize) -> usize {
+        self.proposer_states
+            .get(&instance)
+            .map_or(0, |state| state.consecutive_failures)
+    }
+
+    /// Makes this proposer establish an epoch (see establish_epoch) when it runs, so that it runs
+    /// phase 1 once for the tail of the log, instead of once per instance, until the epoch is
+    /// invalidated. By default, it does not.
+    pub fn with_prepare_once_per_epoch(mut self) -> Self {
+        self.prepare_once_per_epoch = true;
+        self
+    }
+
+    /// Makes this proposer check, with validator, the value of each client request, for the instance
+    /// where it would be proposed, before proposing it. The values which are not valid are not
+    /// proposed, and, if the address of the clients is known (see with_clients_address), their
+    /// clients are told so with a Rejected message. See ValueValidator for what this guarantees.
+    /// validator is Send, like this proposer, which can thus run on its own thread.
+    pub fn with_value_validator<V>(mut self, validator: V) -> Self
+    where
+        V: ValueValidator<T> + Send + 'static,
+    {
+        self.validator = Some(Box::new(validator));
+        self
+    }
+
+    /// Makes this proposer answer the commands of the operators (e.g. status, or force-noop), sent
+    /// to a control socket which binds to admin_address (see the module admin). For safety,
+    /// admin_address should be a local address (e.g. 127.0.0.1:9000).
+    pub fn with_admin_address(mut self, admin_address: SocketAddrV4) -> io::Result<Self> {
+        self.admin = Some(AdminListener::bind(admin_address)?);
+        Ok(self)
+    }
+
+    /// Makes the other nodes send the replies addressed to this proposer (i.e. Promise and Report
+    /// messages) to advertised_address, which is the externally visible address of the socket this
+    /// proposer binds to (e.g. if it runs in a container or behind a NAT).
+    pub fn with_advertised_address(mut self, advertised_address: SocketAddrV4) -> Self {
+        self.advertised_address = Some(advertised_address);
+        self
+    }
+
+    /// Makes this proposer also receive its messages at unicast_address, where a second socket
+    /// binds to, and, unless it has another advertised address (see with_advertised_address) or
+    /// the IP of unicast_address is unspecified, makes the other nodes send the replies addressed
+    /// to this proposer there, so that the other proposers do not receive them too.
+    pub fn with_unicast_address(mut self, unicast_address: SocketAddrV4) -> Self {
+        self.node.set_unicast_address(unicast_address);
+
+        if !unicast_address.ip().is_unspecified() {
+            self.advertised_address.get_or_insert(unicast_address);
+        }
+
+        self
+    }
+
+    /// Makes this proposer also receive the client requests sent to the multicast group
+    /// requests_address (see Client::with_requests_address), which a further socket joins. The
+    /// client requests are then kept apart from the messages between the proposers and the other
+    /// nodes (e.g. the promises of the acceptors), so that only the proposers which serve the
+    /// clients receive them, and the kernel filters them out for the other ones (see
+    /// NetNode::join_multicast_group).
+    pub fn with_requests_address(mut self, requests_address: SocketAddrV4) -> Self {
+        self.node.join_multicast_group(requests_address);
+        self
+    }
+
+    /// Limits the number of instances, started by this proposer, which can be undecided at the
+    /// same time to max_in_flight. Once this limit is reached, the client requests are queued (up
+    /// to a fixed capacity, beyond which the ones with the lowest priority are dropped) until some
+    /// of these instances are decided. The queued requests are then proposed by priority (see
+    /// Client::request_with_priority).
+    ///
+    /// Panics if max_in_flight is zero.
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        assert!(
+            max_in_flight > 0,
+            "Expected a positive maximum of instances in flight"
+        );
+        self.max_in_flight = Some(max_in_flight);
+        self
+    }
+
+    /// Makes this proposer propose the queued client requests (see with_max_in_flight) of the same
+    /// priority in the order given by policy, instead of in the order they were received.
+    pub fn with_ordering_policy(mut self, policy: OrderingPolicy) -> Self {
+        self.ordering_policy = policy;
+        self
+    }
+
+    /// Makes this proposer send a Decided message to the clients (listening on clients_address)
+    /// once the values of their requests are decided (see Client::request_tracked).
+    pub fn with_clients_address(mut self, clients_address: SocketAddrV4) -> Self {
+        self.clients_address = Some(clients_address);
+        self
+    }
+
+    /// Makes this proposer allocate the instances of the values which it starts proposing with
+    /// allocator (by default, InstanceAllocator::Reservation), e.g. to share a counter with the
+    /// other proposers of the same process, so that they do not compete for the same instances.
+    pub fn with_instance_allocator(mut self, allocator: InstanceAllocator) -> Self {
+        self.instance_allocator = allocator;
+        self
+    }
+
+    /// Makes this proposer remember, for window, at most capacity of the last client requests which
+    /// it handled (by default, 1024 requests for 60 seconds), so that it ignores their duplicates,
+    /// e.g. the retransmissions of the clients. A duplicate which arrives after its request was
+    /// forgotten is handled as a new request, so its value may be decided twice: window must be
+    /// longer than the time during which the clients may send the same request again, and capacity
+    /// larger than the number of requests handled meanwhile.
+    ///
+    /// Panics if capacity is zero.
+    pub fn with_request_dedup(mut self, window: Duration, capacity: usize) -> Self {
+        self.recent_requests = RecentRequests::new(window, capacity);
+        self
+    }
+
+    /// Makes this proposer gossip with the other proposers every gossip_interval (see gossip).
+    pub fn with_gossip_interval(mut self, gossip_interval: Duration) -> Self {
+        self.gossip_interval = Some(gossip_interval);
+        self.gossip_jitter = self.jitter(gossip_interval);
+        self
+    }
+
+    /// Makes this proposer draw its random numbers (e.g. the jitter of its gossip) from random,
+    /// instead of a generator seeded from the entropy of the system, e.g. from a seeded generator,
+    /// so that a test is reproducible (see RandomSource). random is Send, like this proposer, which
+    /// can thus run on its own thread.
+    pub fn with_random_source<R: RandomSource + Send + 'static>(mut self, random: R) -> Self {
+        self.random = Box::new(random);
+        if let Some(gossip_interval) = self.gossip_interval {
+            self.gossip_jitter = self.jitter(gossip_interval);
+        }
+        self
+    }
+
+    /// Returns a random jitter for the given gossip interval (see GOSSIP_JITTER_FRACTION).
+    fn jitter(&mut self, gossip_interval: Duration) -> Duration {
+        let max_jitter = (gossip_interval / GOSSIP_JITTER_FRACTION).as_micros() as u64;
+
+        if max_jitter == 0 {
+            return Duration::from_millis(0);
+        }
+
+        Duration::from_micros(self.random.next_below(max_jitter))
+    }
+
+    /// Makes this proposer compute the first round which it starts in an instance with strategy
+    /// (by default, RoundStrategy::Sequential). With RoundStrategy::Generation, the generation is
+    /// durably incremented right away, so this must be called once per start of this proposer,
+    /// before it starts any round, and it fails if the generation cannot be stored.
+    ///
+    /// Panics if the epoch of RoundStrategy::StartingAt is zero.
+    pub fn with_round_strategy(mut self, strategy: RoundStrategy) -> io::Result<Self> {
+        match strategy {
+            RoundStrategy::Sequential => {
+                self.first_epoch = 1;
+                self.generation = None;
+            }
+            RoundStrategy::StartingAt(epoch) => {
+                assert!(epoch > 0, "Expected a positive epoch");
+                self.first_epoch = epoch;
+                self.generation = None;
+            }
+            RoundStrategy::Generation(path) => {
+                let generation = GenerationCounter::open(path)?;
+
+                self.first_epoch = generation
+                    .generation()
+                    .checked_mul(1 << GENERATION_SHIFT)
+                    .ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, "The generation is too high")
+                    })?;
+                self.generation = Some(generation);
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Makes this proposer send the Learning message of each instance again, every
+    /// retransmit_interval, until num_of_acks learners acknowledged it with a LearnAck message
+    /// (see Learner::with_learn_acks), so that a decided value is eventually delivered, even if
+    /// its Learning messages are lost. If fewer than num_of_acks learners acknowledge their
+    /// deliveries, the Learning messages are sent again forever.
+    pub fn with_learn_acks(mut self, num_of_acks: usize, retransmit_interval: Duration) -> Self {
+        assert!(
+            num_of_acks > 0,
+            "Expected a positive number of acknowledgments"
+        );
+        self.learn_acks = Some((num_of_acks, retransmit_interval));
+        self
+    }
+
+    /// Makes this proposer stop starting new instances while the learners are too far behind, i.e.
+    /// while they have max_lag instances (or more) started by this proposer, or by the others, left
+    /// to deliver, e.g. because of slow sinks (see Learner::with_sink). Which learners it waits for
+    /// is given by policy. The requests received meanwhile are queued, as when this proposer has
+    /// too many instances in flight (see with_max_in_flight), until the learners report that they
+    /// caught up (see Learner::with_progress_reports). The learners which never reported their
+    /// progress, or not recently, are not waited for.
+    ///
+    /// Panics if max_lag is zero, or if the policy is a quorum of zero learners.
+    pub fn with_backpressure(mut self, max_lag: usize, policy: BackpressurePolicy) -> Self {
+        assert!(max_lag > 0, "Expected a positive maximum lag");
+        assert!(
+            policy != BackpressurePolicy::Quorum(0),
+            "Expected a positive quorum of learners"
+        );
+        self.backpressure = Some((max_lag, policy));
+        self
+    }
+
+    /// Bounds the pipelining of this proposer with the window alpha: it does not start an instance
+    /// while an instance alpha instances (or more) before it is not known to be chosen, i.e. it
+    /// starts at most alpha instances beyond the chosen prefix of the log. The requests received
+    /// meanwhile are queued, as when this proposer has too many instances in flight (see
+    /// with_max_in_flight). The membership changes decided in an instance i (see
+    /// with_membership_changes) take effect in the instance i + alpha, so the membership of every
+    /// instance which this proposer starts is known. All the proposers must use the same alpha.
+    ///
+    /// Panics if alpha is zero.
+    pub fn with_alpha(mut self, alpha: usize) -> Self {
+        assert!(alpha > 0, "Expected a positive alpha");
+        self.alpha = Some(alpha);
+        self
+    }
+
+    /// Returns the window of the pipelining of this proposer (see with_alpha), if any. If the
+    /// membership can change, but no alpha was given, the window is 1 instance, i.e. an instance is
+    /// only started once all the previous ones are chosen.
+    fn alpha(&self) -> Option<usize> {
+        self.alpha.or_else(|| self.membership.map(|_| 1))
+    }
+
+    /// Makes this proposer start serving the clients right away, when it runs, without waiting to
+    /// catch up with the other proposers (see catch_up_blocking). This is meant for the first
+    /// proposer of the system, which has no other proposer to catch up with.
+    pub fn with_bootstrap(mut self) -> Self {
+        self.bootstrap = true;
+        self
+    }
+
+    /// Makes this proposer wait, when it runs, for a majority of the acceptors to be reachable (see
+    /// wait_for_quorum), before it starts serving. If they are not reachable within timeout, it
+    /// panics, instead of waiting forever for the instances it starts to be decided.
+    pub fn with_quorum_wait(mut self, timeout: Duration) -> Self {
+        self.quorum_wait = Some(timeout);
+        self
+    }
+
+    /// Makes this proposer wait at most timeout for its instances in flight to be decided, once it
+    /// drains (see drain), instead of DRAIN_TIMEOUT.
+    pub fn with_drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = timeout;
+        self
+    }
+
+    /// Returns the decided log known to this proposer, i.e. its learned values (including the
+    /// preloaded ones) keyed by their instances, in increasing order of instance, e.g. to back it
+    /// up with storage::write_log. It only reflects what this proposer knows: the instances which
+    /// it did not learn (yet) are missing, so it is best exported from a proposer which has caught
+    /// up with the others (see catch_up_blocking). To be restored with preload_log, its instances
+    /// must be contiguous, starting at 1.
+    pub fn export_log(&self) -> BTreeMap<usize, T> {
+        self.learned_values
+            .iter()
+            .map(|(&instance, &v)| (instance, v))
+            .collect()
+    }
+
+    /// Returns what this proposer knows about the decision of the given instance, i.e. whether its
+    /// value is chosen, from the Acceptance (or Learning) messages of a majority of the acceptors,
+    /// or only accepted by some of them, from the Acceptance messages received so far. Only a
+    /// chosen value is final: e.g. a linearizable read, or a monitoring tool, must not report an
+    /// accepted value as decided. As this only reflects the messages received by this proposer, a
+    /// value may be chosen although it is reported as accepted (or unknown) here.
+    pub fn decision_state(&self, instance: usize) -> DecisionState<T> {
+        if let Some(&v) = self.learned_values.get(&instance) {
+            return DecisionState::Chosen(v);
+        }
+
+        match self.proposer_states.get(&instance) {
+            Some(state) if !state.v_rnd_received.is_empty() => DecisionState::Accepted {
+                count: state.v_rnd_received.len(),
+            },
+            _ => DecisionState::Unknown,
+        }
+    }
+
+    /// Makes this proposer know the values of an existing log (e.g. of another cluster which is
+    /// migrated), as if they were already decided, so that it reports them to the learners (and to
+    /// the other proposers) and it starts the new instances after them. values are the pairs
+    /// (instance, value) of the log, whose instances must be contiguous, starting at 1. The same
+    /// log must be preloaded into the acceptors (see Acceptor::preload_log), before the nodes start.
+    ///
+    /// Panics if the instances of values are not contiguous, or if this proposer already knows
+    /// another value for one of them.
+    pub fn preload_log(&mut self, values: Vec<(usize, T)>) {
+        let values = validate_preloaded_log(values);
+
+        for &(instance, value) in &values {
+            if let Some(&v) = self.learned_values.get(&instance) {
+                assert_eq!(
+                    v, value,
+                    "The preloaded value of instance {:?} differs from the known one",
+                    instance
+                );
+            }
+
+            self.learned_values.insert(instance, value);
+            self.apply_membership_change(instance);
+            self.note_checkpoint(instance);
+        }
+
+        if values.len() > self.num_of_instances {
+            self.num_of_instances = values.len();
+        }
+
+        if log_enabled!(Level::Info) {
+            info!(
+                "[P={:?}] I preloaded the values of {:?} instances.",
+                self.id,
+                values.len()
+            );
+        }
+    }
+
+    /// Returns the histogram of the times this proposer took to decide the instances it started,
+    /// i.e. from the start of the first round of an instance to the receipt of the majority of the
+    /// Acceptance messages, including the time spent in the rounds which were retried.
+    pub fn latency_histogram(&self) -> &LatencyHistogram {
+        &self.latencies
+    }
+
+    /// Returns the instances started by this proposer which are not decided yet, in increasing
+    /// order.
+    pub fn in_flight_instances(&self) -> Vec<usize> {
+        let mut instances: Vec<usize> = self.in_flight.iter().cloned().collect();
+        instances.sort_unstable();
+        instances
+    }
+
+    /// Returns the number of the client requests which this proposer cancelled so far, when their
+    /// clients gave up on them (see Client::cancel), i.e. which it dropped from its queue, or whose
+    /// instance it stopped proposing their value in.
+    pub fn num_of_cancelled_requests(&self) -> usize {
+        self.num_of_cancelled_requests
+    }
+
+    /// Makes this proposer drain, e.g. before a rolling restart: it ignores the new client
+    /// requests, and drops the queued ones, which their clients must send again to another
+    /// proposer, but it keeps driving its instances in flight (and proposes again the values which
+    /// lose their instance), until they are all decided, or until its drain timeout elapses (see
+    /// with_drain_timeout). It then stops running (see is_stopped), so that no instance is left
+    /// half-decided, unless the timeout elapsed, in which case the instances which could not be
+    /// drained are logged (see in_flight_instances). Draining again has no effect.
+    pub fn drain(&mut self) {
+        if self.drain_deadline.is_some() {
+            return;
+        }
+
+        self.drain_deadline = Some(Instant::now() + self.drain_timeout);
+
+        let num_of_dropped = self.drop_pending_requests();
+
+        info!(
+            "[P={:?}] I am draining {} instances in flight, and I dropped {} queued requests.",
+            self.id,
+            self.in_flight.len(),
+            num_of_dropped
+        );
+    }
+
+    /// Makes this proposer step down, e.g. before a maintenance, without stopping: like when it
+    /// drains (see drain), it ignores the new client requests, and drops the queued ones, which the
+    /// other proposers serve instead (as they receive the same requests from the multicast group of
+    /// the proposers), but it keeps driving its instances in flight until they are decided, so that
+    /// stepping down in the middle of an instance is safe. Unlike when it drains, it keeps running
+    /// afterwards, e.g. it still learns the decided values, and reports them to the proposers which
+    /// catch up with it. Stepping down again has no effect.
+    pub fn step_down(&mut self) {
+        if self.stepped_down {
+            return;
+        }
+
+        self.stepped_down = true;
+
+        let num_of_dropped = self.drop_pending_requests();
+
+        info!(
+            "[P={:?}] I stepped down: I keep driving {} instances in flight, and I dropped {} queued requests.",
+            self.id,
+            self.in_flight.len(),
+            num_of_dropped
+        );
+    }
+
+    /// Returns whether this proposer stepped down (see step_down).
+    pub fn is_stepped_down(&self) -> bool {
+        self.stepped_down
+    }
+
+    /// Drops the client requests queued by this proposer (e.g. because it was busy), and returns
+    /// their number.
+    fn drop_pending_requests(&mut self) -> usize {
+        let num_of_dropped = self.pending_requests.values().map(VecDeque::len).sum();
+        self.pending_requests.clear();
+        num_of_dropped
+    }
+
+    /// Returns whether this proposer finished draining (see drain), i.e. whether all its instances
+    /// in flight were decided, or its drain timeout elapsed.
+    pub fn is_stopped(&self) -> bool {
+        match self.drain_deadline {
+            Some(deadline) => {
+                (self.in_flight.is_empty() && self.beyond_window.is_empty())
+                    || Instant::now() >= deadline
             }
-            self.propose(promise.rnd, promise.v_rnd, promise.v_val, promise.instance);
+            None => false,
+        }
+    }
+
+    /// Logs the outcome of the drain of this proposer, once it stopped (see drain).
+    fn report_drain(&self) {
+        let undrained = self.in_flight_instances();
+
+        if undrained.is_empty() && self.beyond_window.is_empty() {
+            info!("[P={:?}] I drained all my instances: I stop.", self.id);
         } else {
-            if log_enabled!(Level::Info) {
+            warn!(
+                "[P={:?}] I could not drain the instances {:?}, nor propose {} values, within {:?}: I stop anyway.",
+                self.id,
+                undrained,
+                self.beyond_window.len(),
+                self.drain_timeout
+            );
+        }
+    }
+
+    /// Makes the given (e.g. stuck) instance resolve, so that the learners can deliver the values
+    /// of the later instances. If this proposer knows that the instance was already decided, the
+    /// learned value is sent again to the learners. Otherwise, a new round of the instance is run
+    /// to propose the no-op (see with_noop). This is always safe: if some acceptor has already
+    /// voted for a value in the instance, that value is proposed instead of the no-op, as usual in
+    /// phase 2a, so a chosen value is never overridden. If this proposer was proposing the value of
+    /// a client request in the instance, that value is not lost: unless it is decided in the
+    /// instance anyway, it is proposed again in a new instance, once the instance is decided.
+    ///
+    /// Returns an error, without doing anything, if the instance must be proposed, but this
+    /// proposer has no no-op.
+    pub fn force_noop(&mut self, instance: usize) -> Result<(), ForceNoopError> {
+        if let Some(&v) = self.learned_values.get(&instance) {
+            self.learn(v, instance);
+            return Ok(());
+        }
+
+        let noop = self.noop.ok_or(ForceNoopError::NoNoop)?;
+
+        // The next instances started by this proposer must come after this one.
+        if instance > self.num_of_instances {
+            self.num_of_instances = instance;
+        }
+
+        if let Some(state) = self.proposer_states.get_mut(&instance) {
+            if let (true, Some(value)) = (state.client_request, state.value) {
+                state.displaced = Some((value, state.request_id.take()));
+            }
+        }
+
+        self.prepare_instance(Some(noop), InstanceId(instance), false, None);
+        Ok(())
+    }
+
+    /// Finalizes the log, e.g. when this proposer takes over from a failed proposer, by running a
+    /// new round of each instance, from the watermark of the chosen prefix of the log up to the
+    /// highest instance known to this proposer, whose value it does not know was decided (i.e. of
+    /// each gap). As usual in phase 2a, if some acceptor has already voted for a value in such an
+    /// instance, that value is adopted. Otherwise, this proposer proposes its own value for the
+    /// instance, if it has one, or else the no-op (see with_noop), so that no gap is left behind.
+    /// Until all these instances are decided, the client requests (and the NoopRequest messages)
+    /// are postponed, so that the new values are only proposed after the log. Returns the
+    /// instances which are recovered, in increasing order.
+    ///
+    /// Panics if there are gaps, but this proposer has no no-op.
+    pub fn recover_log(&mut self) -> Vec<usize> {
+        self.advance_chosen_watermark();
+
+        let gaps: Vec<usize> = (self.chosen_watermark + 1..=self.num_of_instances)
+            .filter(|instance| !self.learned_values.contains_key(instance))
+            .collect();
+
+        if gaps.is_empty() {
+            return gaps;
+        }
+
+        let noop = self
+            .noop
+            .expect("A no-op must be given (with with_noop) in order to recover the log");
+
+        if log_enabled!(Level::Info) {
+            info!(
+                "[P={:?}] I will recover the instances {:?} of the log.",
+                self.id, gaps
+            );
+        }
+
+        for &instance in &gaps {
+            let (value, client_request, request_id) = match self.proposer_states.get(&instance) {
+                Some(state) if state.value.is_some() => {
+                    (state.value, state.client_request, state.request_id)
+                }
+                _ => (Some(noop), false, None),
+            };
+
+            self.recovering.insert(instance);
+            self.prepare_instance(value, InstanceId(instance), client_request, request_id);
+        }
+
+        gaps
+    }
+
+    /// Returns whether this proposer is recovering the log (see recover_log), i.e. whether some of
+    /// the instances which it recovers are not decided yet.
+    pub fn is_recovering_log(&self) -> bool {
+        !self.recovering.is_empty()
+    }
+
+    /// Takes over the given instance, e.g. because the proposer which started it failed, in order
+    /// to have it decided, although this proposer may have no value for it (e.g. the value of the
+    /// client request may have been lost with the failed proposer). A new round of the instance is
+    /// run and, as usual in phase 2a, if some acceptor has already voted for a value in the
+    /// instance, that value is adopted. Otherwise, this proposer proposes its own value for the
+    /// instance, if it has one, or else the one chosen by its policy (see
+    /// with_unknown_value_policy). If this proposer knows that the instance was already decided,
+    /// the learned value is sent again to the learners.
+    pub fn complete_instance(&mut self, instance: usize) {
+        if let Some(&v) = self.learned_values.get(&instance) {
+            self.learn(v, instance);
+            return;
+        }
+
+        // The next instances started by this proposer must come after this one.
+        if instance > self.num_of_instances {
+            self.num_of_instances = instance;
+        }
+
+        let (value, client_request, request_id) = match self.proposer_states.get(&instance) {
+            Some(state) => (state.value, state.client_request, state.request_id),
+            None => (None, false, None),
+        };
+
+        self.prepare_instance(value, InstanceId(instance), client_request, request_id);
+    }
+
+    /// Takes over the given instance (e.g. because the proposer which was driving it failed), in
+    /// order to have value, requested by a client, decided. A new round of the instance is run and,
+    /// as usual in phase 2a, if some acceptor has already voted for a value in the instance (e.g.
+    /// the value of the failed proposer), that value is adopted. value is only proposed in this
+    /// instance if no acceptor has voted yet, otherwise it is proposed again in a new instance, once
+    /// the adopted value is decided. So, two different values can never be chosen in the instance,
+    /// and value is never lost.
+    pub fn repropose(&mut self, instance: usize, value: T) {
+        match self.learned_values.get(&instance) {
+            Some(&v) if v == value => self.learn(v, instance),
+            Some(_) => self.prepare(value, None),
+            None if !self.is_valid(instance, value, None) => {}
+            None => {
+                // The next instances started by this proposer must come after this one.
+                if instance > self.num_of_instances {
+                    self.num_of_instances = instance;
+                }
+
+                self.prepare_instance(Some(value), InstanceId(instance), true, None);
+            }
+        }
+    }
+
+    /// Establishes an epoch of this proposer, i.e. a round which the acceptors promise once for all
+    /// the instances of the tail of the log, from the first one which this proposer does not know
+    /// to be chosen, instead of once per instance. Phase 1 is run once, with a TailPreparation
+    /// message, and, once a majority of the acceptors promised the round, this proposer completes
+    /// the instances of the tail where they voted, or which it started, and it proposes the values
+    /// of its next instances at once, in phase 2 only. The epoch stays valid until bump_epoch is
+    /// called, or until an acceptor promises a higher round in the tail (e.g. the one of another
+    /// proposer), which this proposer learns from a Nack, or from an Acceptance of that round: its
+    /// next instances then go through phase 1 again.
+    ///
+    /// It does not wait for the acceptors: it returns the round of the epoch, which is only
+    /// established later (see epoch). Calling it again while the epoch is being established sends
+    /// the TailPreparation message again (e.g. if it was lost), and, once the epoch is established,
+    /// has no effect. Returns an error if this proposer does not start new instances anymore.
+    pub fn establish_epoch(&mut self) -> Result<Ballot, EpochError> {
+        self.admits_epoch()?;
+
+        let rnd = match self.epoch {
+            Some(ref epoch) if epoch.established => return Ok(epoch.rnd),
+            Some(ref epoch) => epoch.rnd,
+            None => self.start_epoch()?,
+        };
+
+        self.send_tail_preparation();
+
+        Ok(rnd)
+    }
+
+    /// Abandons the epoch of this proposer, if any (see establish_epoch), and establishes a new one,
+    /// in a higher round, e.g. to take the tail of the log over again, once another proposer
+    /// invalidated the epoch. Returns the round of the new epoch, or an error if this proposer does
+    /// not start new instances anymore.
+    pub fn bump_epoch(&mut self) -> Result<Ballot, EpochError> {
+        self.admits_epoch()?;
+
+        if let Some(epoch) = self.epoch.take() {
+            self.last_epoch_rnd = self.last_epoch_rnd.max(epoch.rnd);
+        }
+
+        self.establish_epoch()
+    }
+
+    /// Returns the round of the epoch of this proposer, if it is established, and still valid (see
+    /// establish_epoch).
+    pub fn epoch(&self) -> Option<Ballot> {
+        self.epoch
+            .as_ref()
+            .filter(|epoch| epoch.established)
+            .map(|epoch| epoch.rnd)
+    }
+
+    /// Returns the number of the epochs of this proposer which were invalidated so far, by the
+    /// higher rounds promised by the acceptors (see establish_epoch).
+    pub fn num_of_invalidated_epochs(&self) -> usize {
+        self.num_of_invalidated_epochs
+    }
+
+    /// Handles the message m, which was received by this proposer, by dispatching it to the
+    /// corresponding handler. Messages of other Paxos groups are ignored.
+    pub fn handle(&mut self, m: Message<T>) {
+        if m.group_id() != self.group_id {
+            if logs_messages() {
                 info!(
-                    "[P={:?}] I will ignore {:?} for {:?}.",
-                    self.id, promise, promise.receiver_uuid
+                    "[P={:?}] I will ignore {:?} of another group.",
+                    self.id,
+                    Logged(&m)
+                );
+            }
+            return;
+        }
+
+        if let Some(instance) = truncatable_instance(&m).filter(|&i| self.is_truncated(i)) {
+            if logs_messages() {
+                info!(
+                    "[P={:?}] Instance {:?} is truncated: I will ignore {:?}.",
+                    self.id,
+                    instance,
+                    Logged(&m)
                 );
             }
+            return;
+        }
+
+        if self.catching_up || self.is_recovering_log() {
+            if let Message::Phase0a(_) | Message::NoopRequest(_) | Message::CancelRequest(_) = m {
+                if logs_messages() {
+                    info!(
+                        "[P={:?}] I am catching up, or recovering the log: I will postpone {:?}.",
+                        self.id,
+                        Logged(&m)
+                    );
+                }
+                self.postponed.push(m);
+                return;
+            }
+        }
+
+        match m {
+            Message::Phase0a::<T>(request) => self.handle_request(request),
+            Message::Phase0b(catch_up) => self.handle_catch_up(catch_up),
+            Message::Phase0c::<T>(report) => self.handle_report(report),
+            Message::Phase1b::<T>(promise) => self.handle_promise(promise),
+            Message::Phase1c(nack) => self.handle_nack(nack),
+            Message::Phase2b::<T>(acceptance) => self.handle_acceptance(acceptance),
+            Message::Phase3::<T>(learning) => self.handle_learning(learning),
+            Message::NoopRequest(noop_request) => self.handle_noop_request(noop_request),
+            Message::GossipSummary(summary) => self.handle_gossip_summary(summary),
+            Message::GossipValues::<T>(values) => self.handle_gossip_values(values),
+            Message::LearnAck(learn_ack) => self.handle_learn_ack(learn_ack),
+            Message::Progress(progress) => self.handle_progress(progress),
+            Message::Truncation(truncation) => self.handle_truncation(truncation),
+            Message::TailPromise::<T>(tail_promise) => self.handle_tail_promise(tail_promise),
+            Message::TruncationRequest(request) => self.handle_truncation_request(request),
+            Message::CancelRequest(cancel_request) => self.handle_cancel_request(cancel_request),
+            Message::Ping(ping) => pong(&self.node, self.uuid, ping),
+            _ => info!(
+                "[P={:?}] Unexpected message received. I'll ignore it.",
+                self.id
+            ),
         }
     }
 
-    /// Handles the Acceptance message sent by an acceptor to this proposer.
-    fn handle_acceptance(&mut self, acceptance: Acceptance<T>) {
-        if log_enabled!(Level::Info) {
-            info!("[P={:?}] I will handle {:?}.", self.id, acceptance);
+    /// Handles the message contained in datagram, which was delivered to this proposer by the owner
+    /// of its in-memory channel. Corrupted datagrams are dropped.
+    #[cfg(feature = "testkit")]
+    pub(crate) fn deliver(&mut self, datagram: &[u8]) {
+        if let Some(m) = self.node.decode(datagram) {
+            self.handle(m);
+        }
+    }
+
+    /// Returns the id and the unique identifier of this proposer, so that the owner of its
+    /// in-memory channel can restart it with the same identity.
+    #[cfg(feature = "testkit")]
+    pub(crate) fn identity(&self) -> (usize, Uuid) {
+        (self.id, self.uuid)
+    }
+
+    // Handlers
+
+    /// Handles the Request message sent by a client to this proposer.
+    fn handle_request(&mut self, request: Request<T>) {
+        let request_id = (request.sender_uuid, request.request_seq);
+
+        // The value may be voted for in an instance driven by another proposer.
+        self.remember_value(request.value);
+
+        let now = Instant::now();
+        self.evict_recent_requests(now);
+
+        if self.recent_requests.contains(&request_id) {
+            if logs_messages() {
+                info!(
+                    "[P={:?}] I will ignore the duplicate {:?}.",
+                    self.id,
+                    Logged(&request)
+                );
+            }
+            return;
         }
 
-        match acceptance.v_val {
-            Some(v) => self.decide(acceptance.v_rnd, v, acceptance.instance),
-            _ => panic!("Logic error: contact the programmer."),
-        }
-    }
+        if self.drain_deadline.is_some() {
+            if logs_messages() {
+                info!(
+                    "[P={:?}] I am draining: I will ignore {:?}.",
+                    self.id,
+                    Logged(&request)
+                );
+            }
+            return;
+        }
+
+        if self.stepped_down {
+            if logs_messages() {
+                info!(
+                    "[P={:?}] I stepped down: I will ignore {:?}.",
+                    self.id,
+                    Logged(&request)
+                );
+            }
+            return;
+        }
+
+        if logs_messages() {
+            info!("[P={:?}] I will handle {:?}.", self.id, Logged(&request));
+        }
+
+        self.recent_requests.insert(request_id, now);
+
+        if let Some(prefix_from) = request.prefix_from {
+            self.prefix_requests.insert(request_id, prefix_from.0);
+        }
+
+        self.evict_recent_requests(now);
+
+        if self.is_busy() {
+            self.queue_request(request);
+            return;
+        }
+
+        self.last_served_client = Some(request.sender_uuid);
+        self.prepare(request.value, Some(request_id));
+    }
+
+    /// Handles the CancelRequest message sent by a client which gave up on one of its requests (see
+    /// Client::cancel). The cancelled request is remembered as a recent one, so that it is ignored
+    /// if it arrives after its cancellation (e.g. out of order).
+    fn handle_cancel_request(&mut self, cancel_request: CancelRequest) {
+        let request_id = (cancel_request.sender_uuid, cancel_request.request_seq);
+
+        if logs_messages() {
+            info!(
+                "[P={:?}] I will handle {:?}.",
+                self.id,
+                Logged(&cancel_request)
+            );
+        }
+
+        let now = Instant::now();
+        self.evict_recent_requests(now);
+        self.recent_requests.insert(request_id, now);
+
+        let batched = self.batched_requests.contains_key(&request_id)
+            || self
+                .batched_requests
+                .values()
+                .any(|batched| batched.contains(&request_id));
+
+        if batched {
+            if log_enabled!(Level::Info) {
+                info!(
+                    "[P={:?}] The request {:?} is batched with others: I will not cancel it.",
+                    self.id, request_id
+                );
+            }
+            return;
+        }
+
+        if self.cancel_queued(request_id) || self.cancel_in_flight(request_id) {
+            if log_enabled!(Level::Info) {
+                info!(
+                    "[P={:?}] I cancelled the request {:?}.",
+                    self.id, request_id
+                );
+            }
+
+            self.prefix_requests.remove(&request_id);
+            self.num_of_cancelled_requests += 1;
+        }
+    }
+
+    /// Drops the request request_id, if it is queued (or if its value waits for the window, see
+    /// with_alpha), and returns whether it was.
+    fn cancel_queued(&mut self, request_id: RequestId) -> bool {
+        for requests in self.pending_requests.values_mut() {
+            if let Some(i) = requests
+                .iter()
+                .position(|r| (r.sender_uuid, r.request_seq) == request_id)
+            {
+                requests.remove(i);
+                return true;
+            }
+        }
+
+        match self
+            .beyond_window
+            .iter()
+            .position(|&(_, id)| id == Some(request_id))
+        {
+            Some(i) => {
+                self.beyond_window.remove(i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stops proposing the value of the request request_id in its instance, if it is not known to
+    /// be decided yet, and returns whether it was: the value is not proposed again in another
+    /// instance, if it loses this one, and, if this proposer has a no-op (see with_noop), a new
+    /// round of the instance proposes the no-op instead, so that no gap is left in the log. As
+    /// usual in phase 2a, if an acceptor already voted for the value, the new round adopts it: the
+    /// value is then decided anyway, and its client is told so, as if it was not cancelled.
+    /// Without a no-op, the value keeps being proposed in its instance, for the same reason.
+    fn cancel_in_flight(&mut self, request_id: RequestId) -> bool {
+        let learned_values = &self.learned_values;
+
+        let instance = self
+            .proposer_states
+            .iter()
+            .find(|&(instance, state)| {
+                state.request_id == Some(request_id)
+                    && state.cancelled.is_none()
+                    && !learned_values.contains_key(instance)
+            })
+            .map(|(&instance, _)| instance);
+
+        let instance = match instance {
+            Some(instance) => instance,
+            None => return false,
+        };
+
+        let state = self.proposer_states.get_mut(&instance).unwrap();
+        state.cancelled = state.value;
+        state.client_request = false;
+
+        if let Some(noop) = self.noop {
+            state.value = Some(noop);
+
+            if self.in_flight.contains(&instance) {
+                self.prepare_instance(Some(noop), InstanceId(instance), false, Some(request_id));
+            }
+        }
+
+        true
+    }
+
+    /// Forgets the requests handled more than the dedup window before now, and the oldest ones
+    /// beyond the dedup capacity (see with_request_dedup), so that their duplicates, if any, are
+    /// handled as new requests.
+    fn evict_recent_requests(&mut self, now: Instant) {
+        for request_id in self.recent_requests.evict(now) {
+            self.prefix_requests.remove(&request_id);
+        }
+    }
+
+    /// Handles the CatchUp messages sent by the learners.
+    fn handle_catch_up(&mut self, catch_up: CatchUp) {
+        // If it was another proposer or a learner that sent the CatchUp message, then I will
+        // report, otherwise, because the sender is self, nothing is done. So, this avoids
+        // responding to a CatchUp message sent by itself: of course, this would be a useless
+        // operation, and actually it would only mess up with the answers from the other proposers.
+        if catch_up.sender_uuid != self.uuid {
+            if logs_messages() {
+                info!("[P={:?}] I will handle {:?}.", self.id, Logged(&catch_up));
+            }
+
+            self.report(
+                catch_up.sender_uuid,
+                catch_up.sender_type,
+                catch_up.reply_address,
+                catch_up.from_instance.0,
+                &catch_up.known_instances,
+            );
+        } else {
+            if logs_messages() {
+                info!(
+                    "[P={:?}] I will NOT handle {:?}.",
+                    self.id,
+                    Logged(&catch_up)
+                );
+            }
+        }
+    }
+
+    /// Handles the Report message sent by a proposer to this proposer.
+    fn handle_report(&mut self, report: Report<T>) {
+        // If the destination of the Report message, i.e. report.receiver_uid, is equal to self.uuid,
+        // then it means that this Report message was sent to this proposer.
+        if report.receiver_uuid == self.uuid {
+            if logs_messages() {
+                info!("[P={:?}] I will handle {:?}.", self.id, Logged(&report));
+            }
+
+            // The reports of several proposers (or of the same proposer, for several CatchUp
+            // messages) can be received, so the highest instance, and all learned values, reported
+            // by any of them are kept.
+            if report.num_of_instances > self.num_of_instances {
+                self.num_of_instances = report.num_of_instances;
+            }
+
+            if let Some(checkpoint) = report.checkpoint {
+                self.truncate_below(checkpoint.0);
+            }
+
+            // Different values of the same instance can only be reported if the proposers were
+            // preloaded with different logs (see preload_log).
+            for (instance, value) in report.learned_values {
+                if self.is_truncated(instance) {
+                    continue;
+                }
+
+                match self.learned_values.insert(instance, value) {
+                    Some(v) if v != value => error!(
+                        "[P={:?}] Inconsistent logs: {:?} was reported for instance {:?}, but I know {:?}.",
+                        self.id, value, instance, v
+                    ),
+                    _ => {}
+                }
+
+                self.apply_membership_change(instance);
+                self.note_checkpoint(instance);
+            }
+            self.caught_up = true;
+
+            self.advance_chosen_watermark();
+            self.truncate_log();
+
+            if self.catching_up {
+                self.finish_catch_up();
+            }
+        } else {
+            if logs_messages() {
+                info!("[P={:?}] I will NOT handle {:?}.", self.id, Logged(&report));
+            }
+        }
+    }
+
+    /// Handles the Promise message sent by an acceptor to this proposer.
+    fn handle_promise(&mut self, promise: Promise<T>) {
+        if promise.receiver_uuid == self.uuid {
+            if !self.admits_instance(promise.instance.0) {
+                return;
+            }
+
+            if logs_messages() {
+                info!("[P={:?}] I will handle {:?}.", self.id, Logged(&promise));
+            }
+            self.propose(promise);
+        } else {
+            if logs_messages() {
+                info!(
+                    "[P={:?}] I will ignore {:?} for {:?}.",
+                    self.id,
+                    Logged(&promise),
+                    promise.receiver_uuid
+                );
+            }
+        }
+    }
+
+    /// Handles the Learning message sent by another proposer to the learners, which this proposer
+    /// also receives if it listens on the address of the learners (e.g. if all roles share the same
+    /// multicast address). This proposer learns the decided value for free, e.g. if it missed the
+    /// Acceptance messages of the instance, as if it had decided it itself. A value which differs
+    /// from the one already known for the same instance is only logged (and ignored), as it can
+    /// only be sent if the logs of the proposers are inconsistent (see preload_log).
+    fn handle_learning(&mut self, learning: Learning<T>) {
+        // This proposer already knows the values it sends to the learners.
+        if learning.sender_uuid == self.uuid {
+            return;
+        }
+
+        let (instance, learned_value) = (learning.instance.0, learning.learned_value);
+
+        if self.knows_value(instance, learned_value) {
+            return;
+        }
+
+        if logs_messages() {
+            info!("[P={:?}] I will handle {:?}.", self.id, Logged(&learning));
+        }
+
+        self.adopt_learned_value(instance, learned_value);
+    }
+
+    /// Returns whether this proposer already knows the value decided in instance, which another
+    /// node claims to be learned_value. If it knows a different one, the logs are inconsistent,
+    /// which is logged as an error: learned_value must then be dropped.
+    fn knows_value(&self, instance: usize, learned_value: T) -> bool {
+        match self.learned_values.get(&instance) {
+            Some(&v) if v != learned_value => {
+                error!(
+                    "[P={:?}] Inconsistent logs: {:?} was learned in instance {:?}, but I know {:?}.",
+                    self.id, learned_value, instance, v
+                );
+                true
+            }
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    /// Keeps track of learned_value, which another proposer learned in instance, whose value this
+    /// proposer did not know. The client request which this proposer proposed in the instance, if
+    /// any, is settled: its client is told where its value was decided or, if another value was
+    /// decided, its value is proposed again in a new instance.
+    fn adopt_learned_value(&mut self, instance: usize, learned_value: T) {
+        self.remember_value(learned_value);
+        self.learned_values.insert(instance, learned_value);
+
+        #[cfg(feature = "metrics")]
+        telemetry::value_decided("proposer", instance, None);
+
+        // The instance is taken, so this proposer must not allocate it anymore (see prepare).
+        if instance > self.num_of_instances {
+            self.num_of_instances = instance;
+        }
+
+        // The learned values do not carry their requests, so an equal value is taken to be the one
+        // of the request of this proposer only if it proposed that request in the instance.
+        let (lost_value, decided_request) = match self.proposer_states.get_mut(&instance) {
+            Some(state) => {
+                let for_request =
+                    state.request_id.is_none() || state.c_val_request == state.request_id;
+                state.settle(learned_value, for_request)
+            }
+            None => (None, None),
+        };
+
+        self.finish_instance(instance);
+
+        if let Some((client_uuid, request_seq)) = decided_request {
+            self.ack(
+                learned_value,
+                client_uuid,
+                request_seq,
+                InstanceId(instance),
+            );
+        }
+
+        if let Some((value, request_id)) = lost_value {
+            if log_enabled!(Level::Info) {
+                info!(
+                    "[P={:?}] {:?} was not decided in instance {:?}: I will propose it again.",
+                    self.id, value, instance
+                );
+            }
+
+            self.prepare(value, request_id);
+        }
+    }
+
+    /// Handles the Nack message sent by an acceptor to this proposer, whose round of the instance
+    /// it rejected, as it already promised a higher round: this proposer is preempted, so it backs
+    /// off (see ProposerState::preempted_by), instead of waiting in vain for the acceptor.
+    fn handle_nack(&mut self, nack: Nack) {
+        if nack.receiver_uuid != self.uuid {
+            return;
+        }
+
+        if logs_messages() {
+            info!("[P={:?}] I will handle {:?}.", self.id, Logged(&nack));
+        }
+
+        let instance = nack.instance.0;
+
+        // The acceptors which promised the epoch of this proposer reject its previous rounds of the
+        // tail with the round of the epoch, which does not preempt them (see establish_epoch).
+        if matches!(self.epoch, Some(ref epoch) if epoch.rnd == nack.v_rnd) {
+            return;
+        }
+
+        self.invalidate_epoch(nack.v_rnd, instance);
+
+        if let Some(state) = self.proposer_states.get_mut(&instance) {
+            if state.classify(nack.v_rnd) == RoundClass::Higher {
+                if log_enabled!(Level::Info) {
+                    info!(
+                        "[P={:?}] Round {:?} of instance {:?} preempted mine: I will back off.",
+                        self.id, nack.v_rnd, instance
+                    );
+                }
+                state.preempt(nack.v_rnd);
+            }
+        }
+    }
+
+    /// Handles the Acceptance message sent by an acceptor to this proposer.
+    fn handle_acceptance(&mut self, acceptance: Acceptance<T>) {
+        if !self.admits_instance(acceptance.instance.0) {
+            return;
+        }
+
+        if logs_messages() {
+            info!("[P={:?}] I will handle {:?}.", self.id, Logged(&acceptance));
+        }
+
+        self.invalidate_epoch(acceptance.v_rnd, acceptance.instance.0);
+
+        match acceptance.v_val {
+            Some(v) => self.decide(
+                acceptance.v_rnd,
+                v,
+                acceptance.v_request,
+                acceptance.sender_uuid,
+                acceptance.instance,
+            ),
+            _ => panic!("Logic error: contact the programmer."),
+        }
+    }
+
+    /// Handles the TailPromise message sent by an acceptor to this proposer, whose epoch is
+    /// established once a majority of the acceptors promised it (see establish_epoch): the
+    /// instances of the tail where they voted, or which this proposer started, are then completed
+    /// in the epoch.
+    fn handle_tail_promise(&mut self, tail_promise: TailPromise<T>) {
+        if tail_promise.receiver_uuid != self.uuid {
+            return;
+        }
+
+        let majority = match self.epoch {
+            Some(ref epoch) => self.majority_of(epoch.from_instance),
+            None => return,
+        };
+
+        let epoch = match self.epoch {
+            Some(ref mut epoch) if !epoch.established && epoch.rnd == tail_promise.rnd => epoch,
+            _ => {
+                if logs_messages() {
+                    info!(
+                        "[P={:?}] I will ignore {:?}, as I am not establishing its epoch.",
+                        self.id,
+                        Logged(&tail_promise)
+                    );
+                }
+                return;
+            }
+        };
+
+        if logs_messages() {
+            info!(
+                "[P={:?}] I will handle {:?}.",
+                self.id,
+                Logged(&tail_promise)
+            );
+        }
+
+        epoch.promised_by.insert(tail_promise.sender_uuid);
+
+        // Only the vote of the highest round of each instance is kept (as usual in phase 2a).
+        // A vote without a request is one whose request is unknown (see send_proposal).
+        let v_requests = tail_promise
+            .v_requests
+            .into_iter()
+            .chain(iter::repeat(None));
+
+        for ((instance, v_rnd, v_val), v_request) in tail_promise.votes.into_iter().zip(v_requests)
+        {
+            let vote = epoch
+                .votes
+                .entry(instance.0)
+                .or_insert((v_rnd, v_val, v_request));
+            if v_rnd > vote.0 {
+                *vote = (v_rnd, v_val, v_request);
+            }
+        }
+
+        if epoch.promised_by.len() < majority {
+            return;
+        }
+
+        epoch.established = true;
+
+        let from_instance = epoch.from_instance;
+        let mut tail: BTreeSet<usize> = epoch.votes.keys().copied().collect();
+        tail.extend(
+            self.in_flight
+                .iter()
+                .filter(|&&instance| instance >= from_instance),
+        );
+
+        info!(
+            "[P={:?}] My epoch {:?} is established from instance {:?}: I will complete {} instances in it.",
+            self.id,
+            epoch.rnd,
+            from_instance,
+            tail.len()
+        );
+
+        for instance in tail {
+            if !self.learned_values.contains_key(&instance) {
+                self.complete_instance(instance);
+            }
+        }
+    }
+
+    /// Handles the NoopRequest message sent by a learner whose delivery is blocked by an instance.
+    fn handle_noop_request(&mut self, noop_request: NoopRequest) {
+        // The instance may have been decided, but the Learning messages may have been lost, in
+        // which case no no-op is needed.
+        if self.noop.is_none() && !self.learned_values.contains_key(&noop_request.instance.0) {
+            if logs_messages() {
+                info!(
+                    "[P={:?}] I will ignore {:?}, since I have no no-op.",
+                    self.id,
+                    Logged(&noop_request)
+                );
+            }
+            return;
+        }
+
+        if logs_messages() {
+            info!(
+                "[P={:?}] I will handle {:?}.",
+                self.id,
+                Logged(&noop_request)
+            );
+        }
+
+        if let Err(e) = self.force_noop(noop_request.instance.0) {
+            error!("[P={:?}] I could not force the no-op: {}.", self.id, e);
+        }
+    }
+
+    /// Handles the GossipSummary message sent by another proposer, by sending it the learned values
+    /// which it is missing (at most GOSSIP_MAX_VALUES of them, the ones of the lowest instances).
+    fn handle_gossip_summary(&mut self, summary: GossipSummary) {
+        if summary.sender_uuid == self.uuid {
+            return;
+        }
+
+        let mut missing: Vec<usize> = self
+            .learned_values
+            .keys()
+            .cloned()
+            .filter(|&i| !summary.known_instances.contains(i))
+            .collect();
+
+        if missing.is_empty() {
+            return;
+        }
+
+        missing.sort_unstable();
+        missing.truncate(GOSSIP_MAX_VALUES);
+
+        let m = Message::GossipValues::<T>(GossipValues {
+            learned_values: missing
+                .into_iter()
+                .map(|i| (i, self.learned_values[&i]))
+                .collect(),
+            sender_uuid: self.uuid,
+            receiver_uuid: summary.sender_uuid,
+            group_id: self.group_id,
+        });
+
+        if logs_messages() {
+            info!("[P={:?}] I will send {:?}.", self.id, Logged(&m));
+        }
+
+        self.node.send(m, &self.proposers_address);
+    }
+
+    /// Handles the GossipValues message sent by another proposer to this proposer.
+    fn handle_gossip_values(&mut self, values: GossipValues<T>) {
+        if values.receiver_uuid != self.uuid {
+            return;
+        }
+
+        if logs_messages() {
+            info!("[P={:?}] I will handle {:?}.", self.id, Logged(&values));
+        }
+
+        for (instance, learned_value) in values.learned_values {
+            if self.is_truncated(instance) {
+                continue;
+            }
+
+            if self.knows_value(instance, learned_value) {
+                continue;
+            }
+
+            self.adopt_learned_value(instance, learned_value);
+        }
+    }
+
+    /// Handles the Truncation message sent by another proposer, once the checkpoint of its instance
+    /// is stable (see with_checkpoint).
+    fn handle_truncation(&mut self, truncation: Truncation) {
+        if truncation.sender_uuid == self.uuid {
+            return;
+        }
+
+        if logs_messages() {
+            info!("[P={:?}] I will handle {:?}.", self.id, Logged(&truncation));
+        }
+
+        self.truncate_below(truncation.instance.0);
+    }
+
+    /// Handles the TruncationRequest message sent by an acceptor above its soft cap (see
+    /// Acceptor::with_memory_soft_cap), by sending the Truncation message of the last stable
+    /// checkpoint again, if the log of that acceptor is not truncated as far as the log of this
+    /// proposer.
+    fn handle_truncation_request(&mut self, request: TruncationRequest) {
+        if request.truncated_below.0 >= self.truncated_below {
+            if logs_messages() {
+                info!("[P={:?}] I will ignore {:?}.", self.id, Logged(&request));
+            }
+            return;
+        }
+
+        if logs_messages() {
+            info!("[P={:?}] I will handle {:?}.", self.id, Logged(&request));
+        }
+
+        self.send_truncation(self.truncated_below);
+    }
+
+    /// Handles the Progress message sent periodically by a learner, which tells the next instance
+    /// it must deliver. If this catches up enough, the queued requests are handled (see
+    /// with_backpressure).
+    fn handle_progress(&mut self, progress: Progress) {
+        if self.backpressure.is_none() {
+            return;
+        }
+
+        if logs_messages() {
+            info!("[P={:?}] I will handle {:?}.", self.id, Logged(&progress));
+        }
+
+        self.learner_progress.insert(
+            progress.sender_uuid,
+            (progress.next_to_deliver.0, Instant::now()),
+        );
+
+        self.serve_pending_requests();
+    }
+
+    /// Handles the LearnAck message sent by a learner, once it has delivered the learned value of
+    /// an instance. Once enough learners acknowledged it, the Learning message of the instance is
+    /// not sent again anymore (see with_learn_acks).
+    fn handle_learn_ack(&mut self, learn_ack: LearnAck) {
+        let num_of_acks = match self.learn_acks {
+            Some((num_of_acks, _)) => num_of_acks,
+            None => return,
+        };
+
+        // The acknowledgments of a checkpoint are counted, even if this proposer did not send its
+        // Learning message, as they can make it stable (see with_checkpoint).
+        if let Some(acknowledged_by) = self.checkpoints.get_mut(&learn_ack.instance.0) {
+            acknowledged_by.insert(learn_ack.sender_uuid);
+            self.truncate_log();
+        }
+
+        let unacknowledged = match self.unacknowledged.get_mut(&learn_ack.instance.0) {
+            Some(unacknowledged) => unacknowledged,
+            None => return,
+        };
+
+        if logs_messages() {
+            info!("[P={:?}] Received {:?}.", self.id, Logged(&learn_ack));
+        }
+
+        unacknowledged.acknowledged_by.insert(learn_ack.sender_uuid);
+
+        if unacknowledged.acknowledged_by.len() >= num_of_acks {
+            if log_enabled!(Level::Info) {
+                info!(
+                    "[P={:?}] Enough learners acknowledged instance {:?}.",
+                    self.id, learn_ack.instance.0
+                );
+            }
+            self.unacknowledged.remove(&learn_ack.instance.0);
+        }
+    }
+
+    /// Returns how long this proposer can wait for a message before it must gossip again, or None
+    /// if it does not gossip. Note that a timeout of zero is not allowed.
+    fn gossip_timeout(&self) -> Option<Duration> {
+        self.gossip_interval.map(|gossip_interval| {
+            (gossip_interval + self.gossip_jitter)
+                .checked_sub(self.last_gossip.elapsed())
+                .unwrap_or_default()
+                .max(Duration::from_millis(1))
+        })
+    }
+
+    /// Returns how long this proposer can wait for a message before it must gossip again, send an
+    /// unacknowledged Learning message again, or stop draining (see drain), or None if it never
+    /// has to. Note that a timeout of zero is not allowed.
+    fn receive_timeout(&self) -> Option<Duration> {
+        let learning_timeout = self.learn_acks.and_then(|(_, retransmit_interval)| {
+            self.unacknowledged
+                .values()
+                .map(|unacknowledged| {
+                    retransmit_interval
+                        .checked_sub(unacknowledged.sent_at.elapsed())
+                        .unwrap_or_default()
+                        .max(Duration::from_millis(1))
+                })
+                .min()
+        });
+
+        // A draining proposer stops at its deadline, even if no message comes.
+        let drain_timeout = self.drain_deadline.map(|deadline| {
+            deadline
+                .saturating_duration_since(Instant::now())
+                .max(Duration::from_millis(1))
+        });
+
+        // A deferred round starts once its wait is over (see with_starvation_policy).
+        let deferred_timeout = self.deferred_rounds.values().min().map(|&retry_at| {
+            retry_at
+                .saturating_duration_since(Instant::now())
+                .max(Duration::from_millis(1))
+        });
+
+        [
+            self.gossip_timeout(),
+            learning_timeout,
+            drain_timeout,
+            deferred_timeout,
+        ]
+        .iter()
+        .flatten()
+        .min()
+        .copied()
+    }
+
+    /// Sends again the unacknowledged Learning messages which were sent last at least a retransmit
+    /// interval ago (see with_learn_acks).
+    fn check_learnings(&mut self) {
+        if let Some((_, retransmit_interval)) = self.learn_acks {
+            let expired: Vec<(usize, T)> = self
+                .unacknowledged
+                .iter()
+                .filter(|(_, unacknowledged)| {
+                    unacknowledged.sent_at.elapsed() >= retransmit_interval
+                })
+                .map(|(&instance, unacknowledged)| (instance, unacknowledged.learned_value))
+                .collect();
+
+            for (instance, learned_value) in expired {
+                self.learn(learned_value, instance);
+            }
+        }
+    }
+
+    /// Starts the deferred rounds whose wait is over (see with_starvation_policy).
+    fn check_deferred_rounds(&mut self) {
+        let now = Instant::now();
+
+        let due: Vec<usize> = self
+            .deferred_rounds
+            .iter()
+            .filter(|(_, &retry_at)| retry_at <= now)
+            .map(|(&instance, _)| instance)
+            .collect();
+
+        for instance in due {
+            self.deferred_rounds.remove(&instance);
+            self.complete_instance(instance);
+        }
+    }
+
+    /// Gossips, if the gossip interval (and its jitter) has elapsed since the last time this
+    /// proposer gossiped.
+    fn check_gossip(&mut self) {
+        if let Some(gossip_interval) = self.gossip_interval {
+            if self.last_gossip.elapsed() >= gossip_interval + self.gossip_jitter {
+                self.gossip();
+            }
+        }
+    }
+
+    /// Returns true if this proposer cannot start a new instance, because it has already reached
+    /// the maximum number of undecided instances (see with_max_in_flight), because the learners
+    /// are too far behind (see with_backpressure), or because the next instance is beyond its
+    /// window (see with_alpha).
+    fn is_busy(&self) -> bool {
+        matches!(self.max_in_flight, Some(max_in_flight) if self.in_flight.len() >= max_in_flight)
+            || self.is_throttled()
+            || self.is_beyond_window(self.next_instance())
+    }
+
+    /// Returns true if instance is more than alpha instances after the chosen prefix of the log
+    /// known to this proposer (see with_alpha), so that its membership may not be known yet.
+    fn is_beyond_window(&self, instance: usize) -> bool {
+        matches!(self.alpha(), Some(alpha) if instance > self.chosen_watermark + alpha)
+    }
+
+    /// Returns the number of votes of the acceptors needed to choose a value in instance, i.e. the
+    /// majority of the membership of the acceptors in that instance (see with_membership_changes).
+    fn majority_of(&self, instance: usize) -> usize {
+        self.memberships
+            .range(..=instance)
+            .next_back()
+            .map_or(self.majority_of_acceptors, |(_, &majority)| majority)
+    }
+
+    /// If the value learned in instance changes the membership of the acceptors (see
+    /// with_membership_changes), makes the new membership take effect alpha instances later.
+    fn apply_membership_change(&mut self, instance: usize) {
+        let num_of_acceptors = match (self.membership, self.learned_values.get(&instance)) {
+            (Some(membership), Some(value)) => match membership(value) {
+                Some(num_of_acceptors) => num_of_acceptors,
+                None => return,
+            },
+            _ => return,
+        };
+
+        if num_of_acceptors == 0 {
+            error!(
+                "[P={:?}] The membership change of instance {:?} has no acceptors: I will ignore it.",
+                self.id, instance
+            );
+            return;
+        }
+
+        let first_instance = instance + self.alpha().unwrap_or(1);
+
+        if log_enabled!(Level::Info) {
+            info!(
+                "[P={:?}] The membership changed to {:?} acceptors in instance {:?}: it takes effect in instance {:?}.",
+                self.id, num_of_acceptors, instance, first_instance
+            );
+        }
+
+        self.memberships
+            .insert(first_instance, num_of_acceptors / 2 + 1);
+    }
+
+    /// Advances the watermark of the chosen prefix of the log over the instances whose learned
+    /// values are known to this proposer. Returns whether it advanced.
+    fn advance_chosen_watermark(&mut self) -> bool {
+        let watermark = self.chosen_watermark;
+
+        while self
+            .learned_values
+            .contains_key(&(self.chosen_watermark + 1))
+        {
+            self.chosen_watermark += 1;
+        }
+
+        self.chosen_watermark > watermark
+    }
+
+    /// Returns true if instance is below the last stable checkpoint (see with_checkpoint), i.e. if
+    /// its state was dropped, so it is never started again.
+    fn is_truncated(&self, instance: usize) -> bool {
+        instance < self.truncated_below
+    }
+
+    /// Returns false if the state of instance, which this proposer does not know, must not be
+    /// created for a Promise or an Acceptance message, because instance is too far ahead (see
+    /// FutureInstancePolicy). The message is then dropped, and this proposer catches up with the
+    /// other proposers if its policy says so.
+    fn admits_instance(&mut self, instance: usize) -> bool {
+        let window = match self.on_future_instance {
+            FutureInstancePolicy::CreateOnDemand => return true,
+            FutureInstancePolicy::RejectBeyondWatermark(window) => window,
+            FutureInstancePolicy::TriggerCatchUp(window) => window,
+        };
+
+        if self.proposer_states.contains_key(&instance)
+            || instance <= self.num_of_instances.saturating_add(window)
+        {
+            return true;
+        }
+
+        self.num_of_future_messages_dropped += 1;
+
+        warn!(
+            "[P={:?}] Instance {:?} is too far beyond the highest one I know, {:?}: I will drop its message.",
+            self.id, instance, self.num_of_instances
+        );
+
+        if let FutureInstancePolicy::TriggerCatchUp(_) = self.on_future_instance {
+            if instance > self.highest_future_instance {
+                self.highest_future_instance = instance;
+                self.catch_up();
+            }
+        }
+
+        false
+    }
+
+    /// Keeps track of the checkpoint (see with_checkpoint) learned in instance, if any, until it is
+    /// stable.
+    fn note_checkpoint(&mut self, instance: usize) {
+        let is_checkpoint = matches!(
+            (self.checkpoint, self.learned_values.get(&instance)),
+            (Some(checkpoint), Some(&v)) if v == checkpoint
+        );
+
+        if is_checkpoint && instance > self.truncated_below {
+            self.checkpoints.entry(instance).or_default();
+        }
+    }
+
+    /// Truncates the log below the last stable checkpoint (see with_checkpoint), if it is after the
+    /// current truncation point, and tells the acceptors and the other proposers to do the same.
+    fn truncate_log(&mut self) {
+        let num_of_acks = match self.learn_acks {
+            Some((num_of_acks, _)) => num_of_acks,
+            None => return,
+        };
+
+        let stable = self
+            .checkpoints
+            .range(..=self.chosen_watermark)
+            .rev()
+            .find(|(_, acknowledged_by)| acknowledged_by.len() >= num_of_acks)
+            .map(|(&instance, _)| instance);
+
+        if let Some(instance) = stable {
+            if log_enabled!(Level::Info) {
+                info!(
+                    "[P={:?}] The checkpoint of instance {:?} is stable.",
+                    self.id, instance
+                );
+            }
+
+            self.truncate_below(instance);
+            self.send_truncation(instance);
+        }
+    }
+
+    /// Drops the learned values and the state of the instances below instance, where the last
+    /// stable checkpoint known to this proposer was decided, if it is after the current truncation
+    /// point. All these instances are decided, so the watermark of the chosen prefix is moved to
+    /// the checkpoint.
+    fn truncate_below(&mut self, instance: usize) {
+        if instance <= self.truncated_below {
+            return;
+        }
+
+        if log_enabled!(Level::Info) {
+            info!(
+                "[P={:?}] I will truncate the log below instance {:?}.",
+                self.id, instance
+            );
+        }
+
+        self.learned_values.retain(|&i, _| i >= instance);
+        self.proposer_states.retain(|&i, _| i >= instance);
+        self.unacknowledged.retain(|&i, _| i >= instance);
+        self.checkpoints.retain(|&i, _| i > instance);
+        self.in_flight.retain(|&i| i >= instance);
+
+        let recovering = self.is_recovering_log();
+        self.recovering.retain(|&i| i >= instance);
+        if recovering && !self.is_recovering_log() {
+            self.handle_postponed();
+        }
+
+        self.truncated_below = instance;
+        self.chosen_watermark = self.chosen_watermark.max(instance - 1);
+        self.advance_chosen_watermark();
+
+        if instance > self.num_of_instances {
+            self.num_of_instances = instance;
+        }
+    }
+
+    /// Returns true if the learners which reported their progress recently have too many started
+    /// instances left to deliver, according to the policy of the backpressure of this proposer.
+    fn is_throttled(&self) -> bool {
+        let (max_lag, policy) = match self.backpressure {
+            Some(backpressure) => backpressure,
+            None => return false,
+        };
+
+        // The number of started instances which each learner has left to deliver.
+        let lags: Vec<usize> = self
+            .learner_progress
+            .values()
+            .filter(|(_, reported_at)| reported_at.elapsed() < PROGRESS_EXPIRY)
+            .map(|&(next_to_deliver, _)| {
+                (self.num_of_instances + 1).saturating_sub(next_to_deliver)
+            })
+            .collect();
+
+        let num_of_close = lags.iter().filter(|&&lag| lag < max_lag).count();
+
+        match policy {
+            BackpressurePolicy::Slowest => num_of_close < lags.len(),
+            BackpressurePolicy::Quorum(quorum) => num_of_close < quorum.min(lags.len()),
+        }
+    }
+
+    /// Queues request, until this proposer can start a new instance. If the queue is full, the most
+    /// recent of the queued requests with the lowest priority is dropped, provided that its
+    /// priority is lower than the one of request, otherwise request itself is dropped.
+    fn queue_request(&mut self, request: Request<T>) {
+        let priority = request.priority.unwrap_or(Priority::Normal);
+
+        let num_of_pending_requests: usize =
+            self.pending_requests.values().map(VecDeque::len).sum();
+
+        if num_of_pending_requests >= PENDING_REQUESTS_CAPACITY {
+            let dropped = self
+                .pending_requests
+                .range_mut(..priority)
+                .find_map(|(_, requests)| requests.pop_back());
+
+            match dropped {
+                Some(dropped) => {
+                    warn!("[P={:?}] I am busy: I will drop {:?}.", self.id, dropped);
+                }
+                None => {
+                    warn!("[P={:?}] I am busy: I will drop {:?}.", self.id, request);
+                    return;
+                }
+            }
+        }
+
+        if logs_messages() {
+            info!(
+                "[P={:?}] I am busy: I will queue {:?}.",
+                self.id,
+                Logged(&request)
+            );
+        }
+
+        self.pending_requests
+            .entry(priority)
+            .or_default()
+            .push_back(request);
+    }
+
+    /// Keeps track of the fact that instance has been decided, and applies its membership change,
+    /// if any. If this makes room for new instances, the queued client requests are handled.
+    fn finish_instance(&mut self, instance: usize) {
+        self.apply_membership_change(instance);
+        self.note_checkpoint(instance);
+
+        let advanced = self.advance_chosen_watermark();
+        let finished = self.in_flight.remove(&instance);
+        self.deferred_rounds.remove(&instance);
+
+        if advanced {
+            self.truncate_log();
+        }
+
+        // The client requests postponed while the log was recovered are only handled once all the
+        // recovered instances are decided.
+        if self.recovering.remove(&instance) && self.recovering.is_empty() {
+            if log_enabled!(Level::Info) {
+                info!("[P={:?}] I recovered the log.", self.id);
+            }
+
+            self.handle_postponed();
+        }
+
+        if !finished && !advanced {
+            return;
+        }
+
+        self.serve_pending_requests();
+    }
+
+    /// Proposes the values which waited for the window (see with_alpha), then handles the queued
+    /// client requests, by decreasing priority, as long as this proposer can start new instances.
+    fn serve_pending_requests(&mut self) {
+        while !self.is_beyond_window(self.next_instance()) {
+            match self.beyond_window.pop_front() {
+                Some((value, request_id)) => self.prepare(value, request_id),
+                None => break,
+            }
+        }
+
+        while !self.is_busy() {
+            let (policy, last_served_client) = (self.ordering_policy, self.last_served_client);
+
+            // The queue of the highest priority with any requests.
+            let next_request = self
+                .pending_requests
+                .values_mut()
+                .rev()
+                .find_map(|requests| next_request(requests, policy, last_served_client));
+
+            match next_request {
+                Some(request) => {
+                    self.last_served_client = Some(request.sender_uuid);
+
+                    let request_id = (request.sender_uuid, request.request_seq);
+                    let value = self.fill_batch(request.value, request_id);
+                    self.prepare(value, Some(request_id));
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Returns value, the value of the request request_id, merged with the values of the next
+    /// queued requests, as long as they fit in a batch, if this proposer merges them (see
+    /// with_batching). The merged requests are removed from the queues, and kept in
+    /// self.batched_requests.
+    fn fill_batch(&mut self, value: T, request_id: RequestId) -> T {
+        let merge = match self.merge {
+            Some(merge) => merge,
+            None => return value,
+        };
+
+        let mut batch = value;
+        let mut batched = Vec::new();
+
+        loop {
+            let (policy, last_served_client) = (self.ordering_policy, self.last_served_client);
+
+            let next_request = self
+                .pending_requests
+                .values_mut()
+                .rev()
+                .find_map(|requests| next_request(requests, policy, last_served_client));
+
+            let request = match next_request {
+                Some(request) => request,
+                None => break,
+            };
+
+            match merge(&batch, &request.value) {
+                Some(merged) => {
+                    batch = merged;
+                    self.last_served_client = Some(request.sender_uuid);
+                    batched.push((request.sender_uuid, request.request_seq));
+                }
+                None => {
+                    // The request is the next one to propose, in the next batch.
+                    self.pending_requests
+                        .entry(request.priority.unwrap_or(Priority::Normal))
+                        .or_default()
+                        .push_front(request);
+                    break;
+                }
+            }
+        }
+
+        if !batched.is_empty() {
+            self.batched_requests.insert(request_id, batched);
+        }
+
+        batch
+    }
+
+    // Senders
+
+    /// Sends to the other proposers a summary of the instances whose learned values this proposer
+    /// knows, so that they can send it the ones it is missing. The summary is compact (a list of
+    /// ranges of instances), so its size does not depend on the number of known instances, as
+    /// long as they are mostly contiguous.
+    pub fn gossip(&mut self) {
+        let m = Message::GossipSummary::<T>(GossipSummary {
+            known_instances: InstanceRanges::from_instances(self.learned_values.keys().cloned()),
+            sender_uuid: self.uuid,
+            group_id: self.group_id,
+        });
+
+        if logs_messages() {
+            info!("[P={:?}] I will send {:?}.", self.id, Logged(&m));
+        }
+
+        self.node.send(m, &self.proposers_address);
+        self.last_gossip = Instant::now();
+
+        if let Some(gossip_interval) = self.gossip_interval {
+            self.gossip_jitter = self.jitter(gossip_interval);
+        }
+    }
+
+    /// Sends again, right away, the Learning messages which have not been acknowledged by enough
+    /// learners yet (see with_learn_acks), e.g. because they, or the LearnAck messages of the
+    /// learners, were lost. This is otherwise done every retransmit interval, while this proposer
+    /// runs.
+    pub fn retransmit_learnings(&mut self) {
+        let unacknowledged: Vec<(usize, T)> = self
+            .unacknowledged
+            .iter()
+            .map(|(&instance, unacknowledged)| (instance, unacknowledged.learned_value))
+            .collect();
+
+        for (instance, learned_value) in unacknowledged {
+            self.learn(learned_value, instance);
+        }
+    }
+
+    /// Returns the instances whose Learning messages have not been acknowledged by enough learners
+    /// yet (see with_learn_acks), in increasing order.
+    pub fn unacknowledged_instances(&self) -> Vec<usize> {
+        self.unacknowledged.keys().cloned().collect()
+    }
+
+    /// A newly instantiated proposer can "catch up" the current state of the other proposers by
+    /// sending to them a CatchUp message.
+    pub(crate) fn catch_up(&self) {
+        let m = Message::Phase0b(CatchUp {
+            sender_uuid: self.uuid,
+            group_id: self.group_id,
+            sender_type: SenderType::Proposer,
+            reply_address: self.advertised_address,
+            from_instance: InstanceId(0),
+            known_instances: InstanceRanges::from_instances(self.learned_values.keys().cloned()),
+        });
+
+        if logs_messages() {
+            info!("[P={:?}] I will send {:?}.", self.id, Logged(&m));
+        }
+
+        self.node.send(m, &self.proposers_address);
+    }
+
+    /// Catches up with the other proposers, like catch_up, but the client requests (and the
+    /// NoopRequest messages) received from now on are postponed until a Report is received (or
+    /// finish_catch_up is called), so that this proposer knows the instances which were already
+    /// started before it starts new ones.
+    pub fn start_catch_up(&mut self) {
+        self.catching_up = true;
+        self.catch_up();
+    }
+
+    /// Stops catching up (see start_catch_up), recovers the log if this proposer does so (see
+    /// with_log_recovery), and handles the postponed messages.
+    pub fn finish_catch_up(&mut self) {
+        self.catching_up = false;
+
+        if log_enabled!(Level::Info) {
+            info!(
+                "[P={:?}] Caught up: {:?}. The next instance is {:?}.",
+                self.id,
+                self.caught_up,
+                self.num_of_instances + 1
+            );
+        }
+
+        if self.log_recovery {
+            self.recover_log();
+        }
+
+        self.handle_postponed();
+    }
+
+    /// Handles the messages postponed while this proposer was catching up, or recovering the log.
+    /// Those received while it is still recovering the log are postponed again.
+    fn handle_postponed(&mut self) {
+        for m in mem::take(&mut self.postponed) {
+            self.handle(m);
+        }
+    }
+
+    /// Catches up with the other proposers (see start_catch_up), and waits until a Report is
+    /// received before returning, while handling the other messages as usual. The CatchUp message
+    /// is sent again, every timeout, up to max_attempts times. Returns whether a Report was
+    /// received.
+    pub fn catch_up_blocking(&mut self, timeout: Duration, max_attempts: usize) -> bool {
+        for attempt in 0..max_attempts {
+            if attempt == 0 {
+                self.start_catch_up();
+            } else {
+                self.catch_up();
+            }
+
+            let deadline = Instant::now() + timeout;
+
+            while self.catching_up {
+                let now = Instant::now();
+                if now >= deadline {
+                    break;
+                }
+
+                if let Some(m) = self.node.receive_timeout(Some(deadline - now)) {
+                    self.handle(m);
+                }
+            }
+
+            if !self.catching_up {
+                break;
+            }
+        }
+
+        if self.catching_up {
+            self.finish_catch_up();
+        }
+
+        self.caught_up
+    }
+
+    /// Pings the acceptors, every QUORUM_PING_INTERVAL, and waits until a majority of them (i.e.
+    /// enough of them to decide a value) answers with a Pong message, while handling the other
+    /// messages as usual. Returns an error, of kind TimedOut, if they do not answer within timeout.
+    pub fn wait_for_quorum(&mut self, timeout: Duration) -> io::Result<()> {
+        let start = Instant::now();
+        let mut acceptors = HashSet::new();
+        let mut nonce = 0;
+
+        // The membership of the next instance which this proposer would start.
+        let majority = self.majority_of(self.next_instance());
+
+        while acceptors.len() < majority {
+            let remaining = match timeout.checked_sub(start.elapsed()) {
+                Some(remaining) if remaining > Duration::from_millis(0) => remaining,
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!(
+                            "Only {} of the {} acceptors needed answered within {:?}",
+                            acceptors.len(),
+                            majority,
+                            timeout
+                        ),
+                    ))
+                }
+            };
+
+            nonce += 1;
+
+            let m = Message::Ping::<T>(Ping {
+                nonce,
+                reply_address: self.advertised_address.unwrap_or(self.proposers_address),
+                sender_uuid: self.uuid,
+                group_id: self.group_id,
+            });
+
+            if logs_messages() {
+                info!("[P={:?}] I will send {:?}.", self.id, Logged(&m));
+            }
+
+            self.node.send(m, &self.acceptors_address);
+
+            let deadline = Instant::now() + QUORUM_PING_INTERVAL.min(remaining);
+
+            while acceptors.len() < majority {
+                let now = Instant::now();
+                if now >= deadline {
+                    break;
+                }
+
+                match self.node.receive_timeout(Some(deadline - now)) {
+                    Some(Message::Pong(pong))
+                        if pong.receiver_uuid == self.uuid && pong.group_id == self.group_id =>
+                    {
+                        acceptors.insert(pong.sender_uuid);
+                    }
+                    Some(m) => self.handle(m),
+                    None => {}
+                }
+            }
+        }
+
+        if log_enabled!(Level::Info) {
+            info!(
+                "[P={:?}] {:?} acceptors answered after {:?}.",
+                self.id,
+                acceptors.len(),
+                start.elapsed()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Sends a Report message to the learners which requested it using a CatchUp message. If
+    /// reply_address is not None, the message is sent there, instead of to the address of the role
+    /// of the receiver. Only the learned values of the instances from from_instance on, which are
+    /// not in known_instances (i.e. which the receiver does not know yet), are sent.
+    fn report(
+        &self,
+        sender_uid: Uuid,
+        sender_type: SenderType,
+        reply_address: Option<SocketAddrV4>,
+        from_instance: usize,
+        known_instances: &InstanceRanges,
+    ) {
+        let learned_values = self
+            .learned_values
+            .iter()
+            .filter(|&(&instance, _)| {
+                instance >= from_instance && !known_instances.contains(instance)
+            })
+            .map(|(&instance, &learned_value)| (instance, learned_value))
+            .collect();
+
+        let m = Message::Phase0c::<T>(Report {
+            num_of_instances: self.num_of_instances,
+            learned_values,
+            sender_uuid: self.uuid,
+            group_id: self.group_id,
+            receiver_uuid: sender_uid,
+            checkpoint: Some(InstanceId(self.truncated_below)).filter(|_| self.truncated_below > 1),
+        });
+
+        if logs_messages() {
+            info!("[P={:?}] I will send {:?}.", self.id, Logged(&m));
+        }
+
+        let destination_address = match (reply_address, sender_type) {
+            (Some(reply_address), _) => reply_address,
+            (None, SenderType::Learner) => self.learners_address,
+            (None, SenderType::Proposer) => self.proposers_address,
+        };
+
+        self.node.send(m, &destination_address);
+    }
+
+    /// Updates its internal, after having received a request by a client with a value, and sends a
+    /// Preparation message to all acceptors.
+    ///
+    /// The instance is allocated optimistically, as the one after the highest instance known to
+    /// this proposer, and phase 1 also serves to reserve it: if the Promise messages of a majority
+    /// of the acceptors show that a value has already been accepted in the instance (e.g. because
+    /// another proposer allocated the same instance concurrently), the instance is taken, so this
+    /// proposer completes it with that value and proposes value in a new instance (see propose).
+    /// As a consequence, two values never share an instance, and no value is lost.
+    fn prepare(&mut self, value: T, request_id: Option<(Uuid, usize)>) {
+        // The values which lost their instance are proposed again without going through the
+        // queue of the client requests, but not beyond the window either.
+        if self.is_beyond_window(self.next_instance()) {
+            self.beyond_window.push_back((value, request_id));
+            return;
+        }
+
+        if !self.is_valid(self.next_instance(), value, request_id) {
+            if let Some(request_id) = request_id {
+                self.batched_requests.remove(&request_id);
+            }
+            return;
+        }
+
+        // Every time this function is called, a new instance of the basic Paxos algorithm is
+        // (implicitly) started.
+        let instance = self.allocate_instance();
+
+        self.prepare_instance(Some(value), InstanceId(instance), true, request_id);
+    }
+
+    /// Returns the instance which the next value proposed by this proposer would be allocated, if
+    /// no other proposer sharing its counter allocates it before (see InstanceAllocator), i.e. the
+    /// frontier of the allocation, which comes after all the instances known to this proposer.
+    pub fn next_instance(&self) -> usize {
+        match self.instance_allocator {
+            InstanceAllocator::Reservation => self.num_of_instances + 1,
+            InstanceAllocator::LocalAtomic(ref counter) => {
+                counter
+                    .load(atomic::Ordering::SeqCst)
+                    .max(self.num_of_instances)
+                    + 1
+            }
+        }
+    }
+
+    /// Allocates the instance of the next value proposed by this proposer (see InstanceAllocator),
+    /// which comes after all the instances known to this proposer.
+    fn allocate_instance(&mut self) -> usize {
+        self.num_of_instances = match self.instance_allocator {
+            InstanceAllocator::Reservation => self.num_of_instances + 1,
+            InstanceAllocator::LocalAtomic(ref counter) => {
+                // The instances taken by the proposers which do not share the counter (e.g. of
+                // other processes) must be skipped too.
+                counter.fetch_max(self.num_of_instances, atomic::Ordering::SeqCst);
+                counter.fetch_add(1, atomic::Ordering::SeqCst) + 1
+            }
+        };
+
+        self.num_of_instances
+    }
+
+    /// Starts a new round, in which this proposer wants to propose value, if any (see
+    /// complete_instance), of the given instance, by sending a Preparation message to all
+    /// acceptors. client_request tells whether value was requested by a client, and request_id
+    /// which request it was, if known (see ProposerState).
+    fn prepare_instance(
+        &mut self,
+        value: Option<T>,
+        instance: InstanceId,
+        client_request: bool,
+        request_id: Option<(Uuid, usize)>,
+    ) {
+        if self.is_truncated(instance.0) {
+            warn!(
+                "[P={:?}] Instance {:?} is truncated: I will not start it again.",
+                self.id, instance
+            );
+            return;
+        }
+
+        self.in_flight.insert(instance.0);
+        if let Some(value) = value {
+            self.remember_value(value);
+        }
+
+        // Get the ProposerState associated with the instance of the basic Paxos algorithm which
+        // will be executed next.
+        let state = self.proposer_states.entry(instance.0).or_default();
+
+        state.value = value;
+        state.client_request = client_request;
+        state.request_id = request_id;
+        state.started_at.get_or_insert_with(Instant::now);
+
+        // A previous round which was not preempted (e.g. whose messages were lost) ends the streak
+        // of the preempted ones.
+        if state.preempted_by < state.c_rnd {
+            state.consecutive_failures = 0;
+        }
+
+        // Phase 1 of the instances of the tail was already run, for the whole epoch.
+        if let Some(rnd) = self.epoch_of(instance.0) {
+            self.propose_in_epoch(rnd, instance.0);
+            return;
+        }
+
+        if self.holds_back(instance.0) {
+            return;
+        }
+
+        let state = self.proposer_states.entry(instance.0).or_default();
+
+        // The answers received in a previous round of this instance (if any) must not be counted in
+        // the new one.
+        state.rnd_received.clear();
+        state.highest_v_rnd_received = Ballot::ZERO;
+        state.associated_v_val_received = None;
+        state.associated_v_summary_received = None;
+        state.associated_v_request_received = None;
+        state.v_rnd_received.clear();
+
+        // TODO: if self.id is not unique among the proposers, two of them can start the same
+        // TODO: ballot, and the algorithm may not work properly.
+        //
+        // The next ballot is computed with overflow-aware arithmetic: an overflowing epoch would
+        // wrap around to a smaller one, which would break the monotonicity of the rounds that Paxos
+        // relies on.
+        let last_rnd = state.c_rnd.max(state.preempted_by);
+        let first_rnd = Ballot {
+            epoch: self.first_epoch,
+            proposer_id: self.id,
+        };
+
+        let c_rnd = match last_rnd.next(self.id) {
+            Some(c_rnd) => c_rnd.max(first_rnd),
+            None => {
+                error!(
+                    "[P={:?}] The round of instance {:?} after {:?} would overflow: I will not drive it anymore.",
+                    self.id, instance, last_rnd
+                );
+
+                // As with StarvationPolicy::Yield, the instance is left to the round after which
+                // this proposer has none: the value of its client, if any, is proposed again in a
+                // new instance once another value is decided in this one.
+                self.in_flight.remove(&instance.0);
+                return;
+            }
+        };
+        state.c_rnd = c_rnd;
+
+        // The round may belong to a later generation than the one of this proposer (e.g. after a
+        // preemption by a proposer which restarted more times), so, after a restart, this proposer
+        // must start above that generation.
+        if let Some(ref mut generation) = self.generation {
+            generation
+                .record_used(state.c_rnd.epoch >> GENERATION_SHIFT)
+                .expect("Could not store the generation");
+        }
+
+        let m = Message::Phase1a::<T>(Preparation {
+            c_rnd: state.c_rnd,
+            sender_uuid: self.uuid,
+            group_id: self.group_id,
+            instance,
+            reply_address: self.advertised_address,
+            summaries: self.summarize.is_some() && !state.full_values,
+        });
+
+        if logs_messages() {
+            info!("[P={:?}] I will send {:?}.", self.id, Logged(&m));
+        }
+
+        self.node.send(m, &self.acceptors_address);
+    }
+
+    /// Returns true if this proposer must not start a new round of instance now, because it starves
+    /// in it (see with_starvation_policy): it either yields the instance, or waits before its next
+    /// round, which is then deferred until the wait is over.
+    fn holds_back(&mut self, instance: usize) -> bool {
+        let (threshold, policy) = match self.starvation {
+            Some(starvation) => starvation,
+            None => return false,
+        };
+
+        let state = match self.proposer_states.get_mut(&instance) {
+            Some(state) if state.consecutive_failures >= threshold => state,
+            _ => return false,
+        };
+
+        if !state.starved {
+            state.starved = true;
+            self.num_of_starved_instances += 1;
+
+            warn!(
+                "[P={:?}] My last {:?} rounds of instance {:?} were preempted: I starve in it.",
+                self.id, state.consecutive_failures, instance
+            );
+
+            #[cfg(feature = "metrics")]
+            telemetry::instance_starved("proposer");
+        }
+
+        match policy {
+            StarvationPolicy::BackOff {
+                backoff_base,
+                backoff_cap,
+            } => {
+                // The wait doubles with each preempted round beyond the threshold.
+                let exponent = (state.consecutive_failures - threshold).min(31) as u32;
+                let backoff = backoff_base
+                    .checked_mul(1 << exponent)
+                    .map_or(backoff_cap, |backoff| backoff.min(backoff_cap));
+
+                let retry_at = state
+                    .preempted_at
+                    .map(|preempted_at| preempted_at + backoff);
+
+                match retry_at {
+                    Some(retry_at) if retry_at > Instant::now() => {
+                        if log_enabled!(Level::Info) {
+                            info!(
+                                "[P={:?}] I will start the next round of instance {:?} in {:?}.",
+                                self.id, instance, backoff
+                            );
+                        }
+
+                        self.deferred_rounds.insert(instance, retry_at);
+                        true
+                    }
+                    _ => {
+                        self.deferred_rounds.remove(&instance);
+                        false
+                    }
+                }
+            }
+            StarvationPolicy::Yield => {
+                if log_enabled!(Level::Info) {
+                    info!(
+                        "[P={:?}] I yield instance {:?} to round {:?}.",
+                        self.id, instance, state.preempted_by
+                    );
+                }
+
+                // This proposer does not drive the instance anymore.
+                self.in_flight.remove(&instance);
+                true
+            }
+        }
+    }
+
+    /// Sends a Proposal message to the acceptors, if "enough" Promise messages have been received.
+    fn propose(&mut self, promise: Promise<T>) {
+        let Promise {
+            rnd,
+            v_rnd,
+            v_val,
+            v_summary,
+            v_request,
+            sender_uuid,
+            instance,
+            ..
+        } = promise;
+        let instance = instance.0;
+        let majority = self.majority_of(instance);
+        let state = self.proposer_states.entry(instance).or_default();
+
+        match state.classify(rnd) {
+            RoundClass::Stale => {
+                if log_enabled!(Level::Info) {
+                    info!(
+                        "[P={:?}] I will ignore the Promise of the stale round {:?} of instance {:?}.",
+                        self.id, rnd, instance
+                    );
+                }
+                return;
+            }
+            RoundClass::Higher => {
+                if log_enabled!(Level::Info) {
+                    info!(
+                        "[P={:?}] Round {:?} of instance {:?} preempted mine: I will back off.",
+                        self.id, rnd, instance
+                    );
+                }
+                state.preempt(rnd);
+                return;
+            }
+            // The round cannot succeed anymore, so no Proposal is sent in it.
+            RoundClass::Current if state.preempted_by > state.c_rnd => return,
+            RoundClass::Current => {}
+        }
+
+        state.rnd_received.insert(sender_uuid, rnd);
+
+        // We keep track of the highest v_rnd (and the associated v_val) received from any of the
+        // acceptors. See below the logic.
+        if v_rnd > state.highest_v_rnd_received {
+            state.highest_v_rnd_received = v_rnd;
+            state.associated_v_val_received = v_val;
+            state.associated_v_summary_received = v_summary;
+            state.associated_v_request_received = v_request;
+        }
+
+        if state.rnd_received.len() < majority {
+            return;
+        }
+
+        if log_enabled!(Level::Info) {
+            info!("[P={:?}] Majority of rnd received.", self.id);
+        }
+
+        // Furthermore, to proceed, the proposer must make sure that all rnd received are equal to
+        // the c_rnd associated with the current instance of the basic Paxos algorithm.
+        if state.rnd_received.values().all(|&n| n == state.c_rnd) {
+            if log_enabled!(Level::Info) {
+                info!("[P={:?}] All rnd received are equal to my c_rnd.", self.id);
+            }
+
+            // The value of this round was already picked, from a majority of the Promise messages.
+            if state.proposed_in == state.c_rnd {
+                if log_enabled!(Level::Info) {
+                    info!(
+                        "[P={:?}] I already proposed {:?} in round {:?} of instance {:?}: the late Promise does not change it.",
+                        self.id, state.c_val, state.c_rnd, instance
+                    );
+                }
+                return;
+            }
+
+            // It means that no acceptor has previously participated in any round of the current
+            // instance of the basic Paxos algorithm.
+            if state.highest_v_rnd_received == Ballot::ZERO {
+                // In that case, we use the value sent by the client in its request or, if this
+                // proposer has none (see complete_instance), the one chosen by its policy.
+                state.c_val = state.value.or(match self.on_unknown_value {
+                    UnknownValuePolicy::Stall => None,
+                    UnknownValuePolicy::Noop => self.noop,
+                    UnknownValuePolicy::Default(value) => Some(value),
+                });
+
+                if state.c_val.is_none() {
+                    if log_enabled!(Level::Info) {
+                        info!(
+                            "[P={:?}] I have no value to propose in instance {:?}: it stalls.",
+                            self.id, instance
+                        );
+                    }
+                    return;
+                }
+
+                state.c_val_request = state.request_id;
+            } else {
+                // Otherwise we use the value associated with the highest v_rnd received so far from
+                // any of the acceptors, which is materialized if only its summary was received.
+                state.c_val = match state.associated_v_summary_received {
+                    Some(v_summary) => self.known_values.get(&v_summary).cloned(),
+                    None => state.associated_v_val_received,
+                };
+
+                // If this proposer does not know the summarized value, it must not propose another
+                // one, so it starts a new round, in which it asks for the values themselves.
+                if state.c_val.is_none() {
+                    state.full_values = true;
+
+                    if log_enabled!(Level::Info) {
+                        info!(
+                            "[P={:?}] I do not know the value summarized by {:?} in instance {:?}.",
+                            self.id, state.associated_v_summary_received, instance
+                        );
+                    }
+
+                    let (value, client_request, request_id) =
+                        (state.value, state.client_request, state.request_id);
+                    self.prepare_instance(value, InstanceId(instance), client_request, request_id);
+                    return;
+                }
+
+                state.c_val_request = state.associated_v_request_received;
+            }
+
+            self.send_proposal(instance);
+        }
+
+        // TODO: verify that the following program logic is correct.
+        //
+        // If the execution arrives here, it means that we have received rnd values from the
+        // majority of the acceptors. These rnd values received are NOT necessarily ALL equal to
+        // c_rnd.
+        //
+        // We should not clear this buffer at the end of the previous if block, because, suppose
+        // that, at some point, rnd_received contains rnd values which are NOT ALL equal to c_rnd,
+        // and we have NOT yet received rnd from the majority of the acceptors. In that case, none
+        // of the if blocks above will be executed. Suppose that this function is called again and,
+        // at that point, we have received rnd values from the majority of the acceptors. Even
+        // though this may be the case, the previous if block will never be executed, because, even
+        // though, at that point, we will have received rnd values from the majority of the
+        // acceptors, they will NOT ALL be equal to c_rnd. After that point, we will possibly keep
+        // receiving more rnd values from acceptors, but that will not change anything, because, if
+        // we had "state.rnd_received.clear()" at the end of that if block, we would never clear
+        // rnd_received, and thus rnd_received would never contain all rnd values equal c_rnd, and
+        // so this proposer would never send back an answer to the acceptors (if this proposer is
+        // called to handle a "promise" message). Note that rnd_received is only modified in this
+        // function so far.
+        //
+        // If we clear this buffer here, we know that we have received rnd values from the majority
+        // of the acceptors, but EITHER they were all equal to c_rnd or (exclusive or) NOT. If they
+        // are all equal to c_rnd, then we have sent back an answer to the acceptors, otherwise we
+        // have not. By clearing the buffer here, we can process other "promise" messages from the
+        // acceptors. But, unless we need to send a new Preparation message to the acceptors, this
+        // is not necessary. Right now, this implementation still doesn't support the re-sending of
+        // Preparation messages in case a Nack is received.
+        // state.rnd_received.clear();
+    }
+
+    /// Sends a Proposal message to the acceptors, with c_val, the value picked for the round c_rnd of
+    /// instance.
+    fn send_proposal(&mut self, instance: usize) {
+        let state = self.proposer_states.entry(instance).or_default();
+
+        // If the instance is already taken by the value of another request (e.g. one proposed
+        // concurrently by another proposer), this proposer still completes the instance with that
+        // value, but its own value, if requested by a client, is immediately moved to a new
+        // instance (see prepare). Two requests can have equal values, so they are told apart by
+        // their identifiers, unless the request of c_val is unknown (e.g. a value driven again
+        // with repropose).
+        let taken = state.c_val != state.value || !state.is_for_request(state.c_val_request);
+
+        let moved_value = match state.value {
+            Some(value) if state.client_request && taken => {
+                state.client_request = false;
+                Some((value, state.request_id.take()))
+            }
+            _ => None,
+        };
+
+        state.proposed_in = state.c_rnd;
+
+        let m = Message::Phase2a::<T>(Proposal {
+            c_rnd: state.c_rnd,
+            c_val: state.c_val,
+            c_request: state.c_val_request,
+            sender_uuid: self.uuid,
+            group_id: self.group_id,
+            instance: InstanceId(instance),
+        });
+
+        if logs_messages() {
+            info!("[P={:?}] I will send {:?}.", self.id, Logged(&m));
+        }
+
+        self.node.send(m, &self.acceptors_address);
+
+        if let Some((value, request_id)) = moved_value {
+            if log_enabled!(Level::Info) {
+                info!(
+                    "[P={:?}] Instance {:?} is taken: I will propose {:?} in a new one.",
+                    self.id, instance, value
+                );
+            }
+
+            self.prepare(value, request_id);
+        }
+    }
+
+    /// Establishes an epoch, when this proposer runs, if it prepares once per epoch (see
+    /// with_prepare_once_per_epoch).
+    fn establish_epoch_if_configured(&mut self) {
+        if self.prepare_once_per_epoch {
+            if let Err(e) = self.establish_epoch() {
+                warn!("[P={:?}] I could not establish an epoch: {}.", self.id, e);
+            }
+        }
+    }
+
+    /// Returns an error if this proposer cannot establish an epoch, because it does not start new
+    /// instances anymore (see establish_epoch).
+    fn admits_epoch(&self) -> Result<(), EpochError> {
+        if self.drain_deadline.is_some() {
+            Err(EpochError::Draining)
+        } else if self.stepped_down {
+            Err(EpochError::SteppedDown)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Starts a new epoch of this proposer (see establish_epoch), in the round after all the rounds
+    /// which it knows, from the first instance which it does not know to be chosen, and returns the
+    /// round of the epoch, or an error if that round would overflow.
+    fn start_epoch(&mut self) -> Result<Ballot, EpochError> {
+        self.advance_chosen_watermark();
+
+        let last_rnd = self
+            .proposer_states
+            .values()
+            .map(|state| state.c_rnd.max(state.preempted_by))
+            .fold(self.last_epoch_rnd, Ballot::max);
+        let first_rnd = Ballot {
+            epoch: self.first_epoch,
+            proposer_id: self.id,
+        };
+
+        let rnd = match last_rnd.next(self.id) {
+            Some(rnd) => rnd.max(first_rnd),
+            None => {
+                error!(
+                    "[P={:?}] The round of the epoch after {:?} would overflow.",
+                    self.id, last_rnd
+                );
+                return Err(EpochError::RoundOverflow);
+            }
+        };
+
+        // As the other rounds of this proposer (see prepare_instance).
+        if let Some(ref mut generation) = self.generation {
+            generation
+                .record_used(rnd.epoch >> GENERATION_SHIFT)
+                .expect("Could not store the generation");
+        }
+
+        let from_instance = self.chosen_watermark + 1;
+
+        if log_enabled!(Level::Info) {
+            info!(
+                "[P={:?}] I will establish the epoch {:?} from instance {:?}.",
+                self.id, rnd, from_instance
+            );
+        }
+
+        self.epoch = Some(Epoch {
+            rnd,
+            from_instance,
+            promised_by: HashSet::new(),
+            established: false,
+            votes: BTreeMap::new(),
+        });
+
+        Ok(rnd)
+    }
+
+    /// Sends a TailPreparation message to all acceptors, so that they promise the round of the epoch
+    /// which this proposer is establishing, if any, in all the instances of its tail.
+    fn send_tail_preparation(&self) {
+        let epoch = match self.epoch {
+            Some(ref epoch) => epoch,
+            None => return,
+        };
+
+        let m = Message::TailPreparation::<T>(TailPreparation {
+            c_rnd: epoch.rnd,
+            from_instance: InstanceId(epoch.from_instance),
+            sender_uuid: self.uuid,
+            reply_address: self.advertised_address,
+            group_id: self.group_id,
+        });
+
+        if logs_messages() {
+            info!("[P={:?}] I will send {:?}.", self.id, Logged(&m));
+        }
+
+        self.node.send(m, &self.acceptors_address);
+    }
+
+    /// Returns the round of the established epoch of this proposer, if instance belongs to its tail.
+    fn epoch_of(&self, instance: usize) -> Option<Ballot> {
+        match self.epoch {
+            Some(ref epoch) if epoch.established && instance >= epoch.from_instance => {
+                Some(epoch.rnd)
+            }
+            _ => None,
+        }
+    }
+
+    /// Proposes a value in instance, in the round rnd of the established epoch of this proposer,
+    /// i.e. in phase 2 only: the value of the highest vote in the instance, which the acceptors
+    /// reported when they promised the epoch, if any, otherwise the value of this proposer, or the
+    /// one chosen by its policy (as in propose).
+    fn propose_in_epoch(&mut self, rnd: Ballot, instance: usize) {
+        let vote = self
+            .epoch
+            .as_ref()
+            .and_then(|epoch| epoch.votes.get(&instance))
+            .cloned();
+        let unknown_value = match self.on_unknown_value {
+            UnknownValuePolicy::Stall => None,
+            UnknownValuePolicy::Noop => self.noop,
+            UnknownValuePolicy::Default(value) => Some(value),
+        };
+
+        let state = self.proposer_states.entry(instance).or_default();
+
+        // The value of the round is only picked once, so the same Proposal is sent again.
+        if state.proposed_in != rnd {
+            state.rnd_received.clear();
+            state.highest_v_rnd_received = Ballot::ZERO;
+            state.associated_v_val_received = None;
+            state.associated_v_summary_received = None;
+            state.v_rnd_received.clear();
+
+            state.c_rnd = rnd;
+
+            match vote {
+                Some((_, v_val, v_request)) => {
+                    state.c_val = Some(v_val);
+                    state.c_val_request = v_request;
+                }
+                None => {
+                    state.c_val = state.value.or(unknown_value);
+                    state.c_val_request = state.request_id;
+                }
+            }
+        }
+
+        if state.c_val.is_none() {
+            if log_enabled!(Level::Info) {
+                info!(
+                    "[P={:?}] I have no value to propose in instance {:?}: it stalls.",
+                    self.id, instance
+                );
+            }
+            return;
+        }
+
+        self.send_proposal(instance);
+    }
+
+    /// Invalidates the epoch of this proposer, if any, once it learns that an acceptor promised rnd,
+    /// a higher round, in instance, if the instance belongs to the tail of the epoch: the acceptors
+    /// may reject the next proposals of the epoch, so the next instances of this proposer go
+    /// through phase 1 again, until it establishes a new epoch (see bump_epoch).
+    fn invalidate_epoch(&mut self, rnd: Ballot, instance: usize) {
+        match self.epoch {
+            Some(ref epoch) if rnd > epoch.rnd && instance >= epoch.from_instance => {}
+            _ => return,
+        }
+
+        if let Some(epoch) = self.epoch.take() {
+            warn!(
+                "[P={:?}] Round {:?} of instance {:?} invalidated my epoch {:?}.",
+                self.id, rnd, instance, epoch.rnd
+            );
+        }
+
+        self.last_epoch_rnd = self.last_epoch_rnd.max(rnd);
+        self.num_of_invalidated_epochs += 1;
+    }
+
+    /// Sends a Learning message to the learners, if "enough" Acceptance messages have been received
+    /// from the acceptors.
+    fn decide(
+        &mut self,
+        v_rnd: Ballot,
+        v_val: T,
+        v_request: Option<RequestId>,
+        sender_uuid: Uuid,
+        instance: InstanceId,
+    ) {
+        // The value may have been proposed by another proposer.
+        self.remember_value(v_val);
+
+        let majority = self.majority_of(instance.0);
+        let state = self.proposer_states.entry(instance.0).or_default();
+
+        match state.classify(v_rnd) {
+            RoundClass::Stale => {
+                if log_enabled!(Level::Info) {
+                    info!(
+                        "[P={:?}] I will ignore the Acceptance of the stale round {:?} of instance {:?}.",
+                        self.id, v_rnd, instance
+                    );
+                }
+                return;
+            }
+            // The votes of the higher round are still counted, so that this proposer learns the
+            // value decided in it.
+            RoundClass::Higher => {
+                if log_enabled!(Level::Info) {
+                    info!(
+                        "[P={:?}] Round {:?} of instance {:?} preempted mine: I will back off.",
+                        self.id, v_rnd, instance
+                    );
+                }
+                state.preempt(v_rnd);
+            }
+            RoundClass::Current => {}
+        }
+
+        // An acceptor only votes in increasing rounds, so only its vote in the highest round is
+        // kept, and a retransmitted (or reordered) Acceptance does not count it twice.
+        let vote = state.v_rnd_received.entry(sender_uuid).or_insert(v_rnd);
+        if v_rnd < *vote {
+            return;
+        }
+        *vote = v_rnd;
+
+        // A value is only chosen once a majority of the acceptors voted for it in the same round.
+        let num_of_votes = state
+            .v_rnd_received
+            .values()
+            .filter(|&&n| n == v_rnd)
+            .count();
+
+        if num_of_votes < majority {
+            return;
+        }
+
+        if log_enabled!(Level::Info) {
+            info!("[P={:?}] Majority of messages received.", self.id);
+        }
+
+        let latency = state
+            .started_at
+            .take()
+            .map(|started_at| started_at.elapsed());
+        if let Some(latency) = latency {
+            self.latencies.record(latency);
+        }
+
+        #[cfg(feature = "metrics")]
+        if !self.learned_values.contains_key(&instance.0) {
+            telemetry::value_decided("proposer", instance.0, latency);
+        }
+
+        // We keep track of the learned values so as to be able to answer to the CatchUp
+        // messages sent by the learners. We need to store v_val here and not inside the next if
+        // statement, because the next if statement may not be executed. Anyway, at this point,
+        // v_val needs to be a value which learners need to know: it can or not be equal to
+        // state.c_rnd.
+        if let Some(v) = self.learned_values.insert(instance.0, v_val) {
+            assert_eq!(
+                v, v_val,
+                "Bug: previously known v_val is not equal to current one for the same instance"
+            );
+        }
+
+        // The instance is taken, so this proposer must not allocate it anymore (see prepare).
+        if instance.0 > self.num_of_instances {
+            self.num_of_instances = instance.0;
+        }
+
+        let for_request = state.is_for_request(v_request);
+        let (lost_value, decided_request) = state.settle(v_val, for_request);
+
+        if v_rnd == state.c_rnd {
+            if log_enabled!(Level::Info) {
+                info!(
+                    "[P={:?}] A majority of v_rnd received are equal to my c_rnd.",
+                    self.id
+                );
+            }
+
+            assert_eq!(
+                v_val,
+                state.c_val.unwrap(),
+                "Bug: v_val should be equal to c_val to decide"
+            );
+
+            // We can send the message to the learners multiple times, because, once we have
+            // received the majority of the messages containing v_rnd (and all v_rnd == c_rnd), then
+            // all subsequent calls to this self.decide function will trigger this call too. Anyway,
+            // we just need the majority and thus to send this message once.
+            self.learn(v_val, instance.0);
+        }
+
+        // TODO: verify that this statement should be here.
+        // state.v_rnd_received.clear();
+
+        self.finish_instance(instance.0);
+
+        if let Some((client_uuid, request_seq)) = decided_request {
+            self.ack(v_val, client_uuid, request_seq, instance);
+        }
+
+        if let Some((value, request_id)) = lost_value {
+            if log_enabled!(Level::Info) {
+                info!(
+                    "[P={:?}] {:?} was not decided in instance {:?}: I will propose it again.",
+                    self.id, value, instance
+                );
+            }
+
+            self.prepare(value, request_id);
+        }
+    }
+
+    /// Returns whether value can be proposed in the given instance (see with_value_validator). If
+    /// not, the client which requested value, if known, is told so.
+    fn is_valid(&self, instance: usize, value: T, request_id: Option<(Uuid, usize)>) -> bool {
+        match self.validator {
+            Some(ref validator) if !validator.validate(instance, &value) => {
+                warn!(
+                    "[P={:?}] I will not propose {:?} in instance {:?}: it is not valid.",
+                    self.id, value, instance
+                );
+
+                if let Some((client_uuid, request_seq)) = request_id {
+                    self.reject(value, client_uuid, request_seq, InstanceId(instance));
+
+                    for &(client_uuid, request_seq) in self
+                        .batched_requests
+                        .get(&(client_uuid, request_seq))
+                        .into_iter()
+                        .flatten()
+                    {
+                        self.reject(value, client_uuid, request_seq, InstanceId(instance));
+                    }
+                }
+
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// Sends a Rejected message to the client which requested value, if the address of the clients
+    /// is known (see with_clients_address).
+    fn reject(&self, value: T, client_uuid: Uuid, request_seq: usize, instance: InstanceId) {
+        let clients_address = match self.clients_address {
+            Some(clients_address) => clients_address,
+            None => return,
+        };
+
+        let m = Message::Rejected::<T>(Rejected {
+            value,
+            request_seq,
+            instance,
+            sender_uuid: self.uuid,
+            receiver_uuid: client_uuid,
+            group_id: self.group_id,
+        });
+
+        if logs_messages() {
+            info!("[P={:?}] I will send {:?}.", self.id, Logged(&m));
+        }
+
+        self.node.send(m, &clients_address);
+    }
+
+    /// Sends a Decided message to the client which requested value, if the address of the clients
+    /// is known (see with_clients_address), with the watermark of the chosen prefix of the log and,
+    /// if the client asked for them, the chosen values of the prefix (see
+    /// Client::request_with_prefix).
+    fn ack(&mut self, value: T, client_uuid: Uuid, request_seq: usize, instance: InstanceId) {
+        // The clients of the requests merged into the same batch are told too (see with_batching).
+        if let Some(batched) = self.batched_requests.remove(&(client_uuid, request_seq)) {
+            for (client_uuid, request_seq) in batched {
+                self.ack(value, client_uuid, request_seq, instance);
+            }
+        }
+
+        let prefix_from = self.prefix_requests.remove(&(client_uuid, request_seq));
+
+        let clients_address = match self.clients_address {
+            Some(clients_address) => clients_address,
+            None => return,
+        };
+
+        self.advance_chosen_watermark();
+
+        let watermark = self.chosen_watermark;
+
+        let (prefix_from, prefix) = match prefix_from {
+            // The values of the truncated instances are not known anymore.
+            Some(prefix_from) => {
+                let prefix_from = prefix_from.max(self.truncated_below);
+                let last = watermark
+                    .min(instance.0)
+                    .min(prefix_from.saturating_add(PREFIX_MAX_VALUES - 1));
+
+                let prefix = (prefix_from..=last)
+                    .map(|i| self.learned_values[&i])
+                    .collect();
+
+                (prefix_from, prefix)
+            }
+            None => (0, Vec::new()),
+        };
+
+        let m = Message::Decided::<T>(Decided {
+            value,
+            request_seq,
+            instance,
+            sender_uuid: self.uuid,
+            receiver_uuid: client_uuid,
+            group_id: self.group_id,
+            watermark: InstanceId(watermark),
+            prefix_from: InstanceId(prefix_from),
+            prefix,
+        });
+
+        if logs_messages() {
+            info!("[P={:?}] I will send {:?}.", self.id, Logged(&m));
+        }
+
+        self.node.send(m, &clients_address);
+    }
+
+    /// Sends a Learning message, with the value decided in the given instance, to the learners. If
+    /// the learners acknowledge their deliveries (see with_learn_acks), the message is sent again
+    /// until enough of them acknowledge it.
+    fn learn(&mut self, learned_value: T, instance: usize) {
+        let m = Message::Phase3::<T>(Learning {
+            learned_value,
+            sender_uuid: self.uuid,
+            group_id: self.group_id,
+            instance: InstanceId(instance),
+        });
+
+        if logs_messages() {
+            info!("[P={:?}] I will send {:?}.", self.id, Logged(&m));
+        }
+
+        self.node.send(m, &self.learners_address);
+
+        if self.learn_acks.is_some() {
+            self.unacknowledged
+                .entry(instance)
+                .or_insert_with(|| UnacknowledgedLearning {
+                    learned_value,
+                    acknowledged_by: HashSet::new(),
+                    sent_at: Instant::now(),
+                })
+                .sent_at = Instant::now();
+        }
+    }
+
+    /// Sends a Truncation message to the acceptors and to the other proposers, so that they
+    /// truncate their logs below instance, where the last stable checkpoint was decided.
+    fn send_truncation(&self, instance: usize) {
+        let m = Message::Truncation::<T>(Truncation {
+            instance: InstanceId(instance),
+            sender_uuid: self.uuid,
+            group_id: self.group_id,
+        });
+
+        if logs_messages() {
+            info!("[P={:?}] I will send {:?}.", self.id, Logged(&m));
+        }
+
+        self.node.send(m.clone(), &self.acceptors_address);
+        self.node.send(m, &self.proposers_address);
+    }
+}
+
+impl<T> Proposer<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq + Summarize,
+{
+    /// Makes the acceptors send the summaries of their voted values (see Summarize), which this
+    /// proposer materializes from the values it has recently seen. If it does not know some
+    /// summarized value, it starts a new round of the instance, in which it asks for the values.
+    pub fn with_summaries(mut self) -> Self {
+        self.summarize = Some(T::summarize);
+        self
+    }
+}
+
+impl<T> Proposer<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq + Reconfigure,
+{
+    /// Makes this proposer apply the membership changes of the acceptors (see Reconfigure) which
+    /// are decided in the log: from the instance i + alpha on (see with_alpha), where i is the
+    /// instance of the change, a value is chosen by a majority of the acceptors of the new
+    /// membership. All the proposers must apply the membership changes. The learners which learn
+    /// from the acceptors directly (see Learner::with_direct_learning) do not apply them.
+    pub fn with_membership_changes(mut self) -> Self {
+        self.membership = Some(T::membership);
+        self
+    }
+}
+
+impl<T> Proposer<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq + Batch,
+{
+    /// Makes this proposer merge the values of the queued client requests (see with_max_in_flight)
+    /// into batches (see Batch): when it can start a new instance, it proposes the next queued
+    /// request, according to the priorities and the ordering policy, merged with the following
+    /// ones, as long as they fit in the batch. The clients of all the merged requests are told
+    /// where the batch is decided (see with_clients_address). The requests are only queued while
+    /// the instances in flight are at their limit, so batches only form under load, and a request
+    /// which arrives while this proposer is idle is proposed alone, without waiting for others.
+    pub fn with_batching(mut self) -> Self {
+        self.merge = Some(T::merge);
+        self
+    }
+}
+
+impl<T> Proposer<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    /// Answers the commands received on the control socket of this proposer, if any.
+    fn poll_admin(&mut self) {
+        if let Some(admin) = self.admin.take() {
+            admin.poll(|command| self.answer(command));
+            self.admin = Some(admin);
+        }
+    }
+
+    /// Answers the given command of an operator (see with_admin_address).
+    fn answer(&mut self, command: Command) -> Answer {
+        match command {
+            Command::Role => Ok(format!(
+                "role=proposer id={} uuid={} group={}",
+                self.id, self.uuid, self.group_id
+            )),
+            Command::Status => Ok(format!(
+                "instances={} in_flight={} pending={} learned={} caught_up={} next_instance={} committed_watermark={}",
+                self.num_of_instances,
+                self.in_flight.len(),
+                self.pending_requests
+                    .values()
+                    .map(VecDeque::len)
+                    .sum::<usize>(),
+                self.learned_values.len(),
+                self.caught_up,
+                self.next_instance(),
+                self.committed_watermark()
+            )),
+            Command::Instance(instance) => Ok(format!(
+                "instance={} {}",
+                instance,
+                self.describe_instance(instance)
+            )),
+            Command::Metrics => Ok(format!(
+                "latency_count={} latency_mean_ms={} latency_p99_ms={} latency_max_ms={} starved={}",
+                self.latencies.count(),
+                self.latencies.mean().as_millis(),
+                self.latencies.percentile(99.0).as_millis(),
+                self.latencies.max().as_millis(),
+                self.num_of_starved_instances
+            )),
+            Command::ForceNoop(instance) => match self.force_noop(instance) {
+                Ok(()) => Ok(format!("instance={} forced=true", instance)),
+                Err(ForceNoopError::NoNoop) => Err("this proposer has no no-op".to_string()),
+            },
+            Command::Log { from, count } => {
+                admin::describe_log(from, count, self.num_of_instances, |instance| {
+                    self.learned_values.get(&instance)
+                })
+            }
+            Command::Drain => {
+                self.drain();
+                Ok(format!(
+                    "draining=true in_flight={} timeout_ms={}",
+                    self.in_flight.len(),
+                    self.drain_timeout.as_millis()
+                ))
+            }
+            Command::StepDown => {
+                self.step_down();
+                Ok(format!(
+                    "stepped_down=true in_flight={}",
+                    self.in_flight.len()
+                ))
+            }
+        }
+    }
+
+    /// Returns what this proposer knows about the given instance, as `key=value` pairs.
+    fn describe_instance(&self, instance: usize) -> String {
+        if let Some(v) = self.learned_values.get(&instance) {
+            return format!("state=decided value={:?}", v);
+        }
+
+        match self.proposer_states.get(&instance) {
+            Some(state) if self.in_flight.contains(&instance) => format!(
+                "state=in_flight c_rnd={}.{} c_val={:?} failures={}",
+                state.c_rnd.epoch, state.c_rnd.proposer_id, state.c_val, state.consecutive_failures
+            ),
+            _ => "state=unknown".to_string(),
+        }
+    }
+}
+
+impl<T> EventLoop<T> for Proposer<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    fn net_node(&mut self) -> &mut NetNode<T> {
+        &mut self.node
+    }
+
+    fn read_timeout(&self) -> Option<Duration> {
+        // Without a gossip interval, nor unacknowledged Learning messages (nor a control socket),
+        // this proposer waits until a message is received.
+        admin::receive_timeout(&self.admin, self.receive_timeout())
+    }
+
+    fn log_waiting(&self) {
+        if log_enabled!(Level::Info) {
+            info!("[P={:?}] Proposer waiting...", self.id);
+        }
+    }
+
+    fn handle_message(&mut self, m: Message<T>) {
+        self.handle(m);
+    }
+
+    fn is_stopped(&self) -> bool {
+        Proposer::is_stopped(self)
+    }
+
+    fn on_idle(&mut self) {
+        self.check_gossip();
+        self.check_learnings();
+        self.check_deferred_rounds();
+        self.serve_pending_requests();
+        self.poll_admin();
+    }
+}
+
+impl<T> Runnable for Proposer<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    fn run(&mut self) {
+        if let Some(timeout) = self.quorum_wait {
+            if let Err(e) = self.wait_for_quorum(timeout) {
+                error!("[P={:?}] The acceptors are not reachable: {}.", self.id, e);
+                panic!("Could not reach a majority of the acceptors");
+            }
+        }
+
+        // Unless this is the first proposer, it must know the instances already started by the
+        // other proposers, before it starts new ones, otherwise it would start them again.
+        if self.bootstrap {
+            self.catch_up();
+        } else if !self.catch_up_blocking(CATCH_UP_TIMEOUT, CATCH_UP_ATTEMPTS) {
+            warn!(
+                "[P={:?}] No proposer answered my CatchUp: I assume that I am the first one.",
+                self.id
+            );
+        }
+
+        self.establish_epoch_if_configured();
+
+        self.serve(|_| {});
+        self.report_drain();
+    }
+}
+
+/// In the Multi-Paxos algorithm, an acceptor can participate in several instances of the basic
+/// Paxos algorithm (at the same time). Given that messages can be received out-of-order, we need to
+/// save the state of all those instances, in order to decide what to do depending on the instance
+/// and its associated values. This struct contains the values, of a single acceptor, which are
+/// associated with 1 instance of the basic Paxos algorithm.
+struct AcceptorState<T> {
+    // The highest-numbered round the acceptor has PARTICIPATED in. It is initially Ballot::ZERO.
+    // rnd is then set to the c_rnd, sent in a Preparation message by some Proposer, such that
+    // c_rnd > rnd. So, here, by "participate" we mean to send a Promise message to the proposals.
+    rnd: Ballot,
+
+    // The highest-numbered round the acceptor has CAST a vote. It is initially Ballot::ZERO, but it
+    // eventually corresponds to some c_rnd sent by a Proposer in a Proposal message, such that
+    // c_rnd > self.rnd. In other words, v_rnd will be a round which is greater than any round the
+    // acceptor has participated in. v_rnd is thus set only when the acceptor wants to send a
+    // Acceptance message to the proposers, after having received enough Proposals. So, here, by
+    // casting a vote we mean to send a Acceptance message to the proposers.
+    v_rnd: Ballot,
+
+    // The value voted by the acceptor in round v_rnd. It is initially None.
+    v_val: Option<T>,
+
+    // The client request whose value is v_val, if any (see Proposal::c_request).
+    v_request: Option<RequestId>,
+}
+
+// I had to implement Default manually. See https://github.com/rust-lang/rust/issues/45036.
+impl<T> Default for AcceptorState<T> {
+    fn default() -> Self {
+        AcceptorState {
+            rnd: Ballot::ZERO,
+            v_rnd: Ballot::ZERO,
+            v_val: None,
+            v_request: None,
+        }
+    }
+}
+
+/// The record which an acceptor durably stores, before answering a proposer, every time the
+/// AcceptorState<T> associated with an instance changes.
+#[derive(Serialize, Deserialize)]
+struct AcceptorRecord<T> {
+    instance: usize,
+
+    rnd: Ballot,
+
+    v_rnd: Ballot,
+
+    v_val: Option<T>,
+
+    v_request: Option<RequestId>,
+}
+
+/// The instance of the records which durably store the tail promise of an acceptor (see
+/// Acceptor::handle_tail_preparation). The instances start at 1, so no state is stored with it.
+const TAIL_RECORD_INSTANCE: usize = 0;
+
+/// The struct representing the acceptor in the Paxos algorithm.
+pub struct Acceptor<T> {
+    uuid: Uuid,
+
+    id: usize,
+
+    // The Paxos group (i.e. the replicated log) this node participates in. Messages belonging to
+    // other groups are ignored, so that several groups can share the same multicast addresses.
+    group_id: usize,
+
+    // Each instance of the Paxos algorithm, in the Multi-Paxos algorithm, is associated with 1
+    // AcceptorState<T>. This is a map from each instance (of a basic Paxos algorithm), which is a
+    // number, to the corresponding AcceptorState<T> needed to complete that instance.
+    acceptor_states: HashMap<usize, AcceptorState<T>>,
+
+    // The highest instance referenced by any message received by this acceptor so far. A restarted
+    // acceptor can use it to know how far the log has progressed. It is stored with the state of
+    // the instances, and max_instance_stored is the highest instance stored so far (see
+    // persist_max_instance_seen).
+    max_instance_seen: usize,
+    max_instance_stored: usize,
+
+    // The first instance of the tail of the log, and the round which this acceptor promised in all
+    // its instances, including the ones it has not seen yet, if a proposer established an epoch
+    // (see handle_tail_preparation).
+    tail_promise: Option<(usize, Ballot)>,
+
+    // If set, the changes of self.acceptor_states are durably stored here, so that this acceptor
+    // can recover its state after a restart.
+    storage: Option<Storage<AcceptorRecord<T>>>,
+
+    // The statistics about the load of this acceptor, and when it received its last message.
+    load: LoadStats,
+    last_arrival: Option<Instant>,
+
+    // If set, it decides which received messages are processed (see with_admission_control).
+    admission_control: Option<AdmissionControl<T>>,
+
+    // If set, the Promise messages carry the summary of the voted value, instead of the value
+    // itself, if the proposer accepts it (see with_summaries).
+    summarize: Option<fn(&T) -> u64>,
+
+    // If set, the replies to the recently received requests, so that their retransmissions are
+    // answered without being processed again (see with_reply_cache), and the number of
+    // retransmissions answered so.
+    reply_cache: Option<ReplyCache<T>>,
+    num_of_coalesced: usize,
+
+    // Whether this acceptor truncates its log when a proposer tells it so (see
+    // with_log_truncation), and the instance below which it is truncated.
+    log_truncation: bool,
+    truncated_below: usize,
+
+    // If set, the number of tracked instances above which this acceptor warns that its memory
+    // grows (see with_memory_soft_cap), and whether it is above it since it last warned.
+    memory_soft_cap: Option<usize>,
+    above_memory_soft_cap: bool,
+
+    // The number of times rnd or v_rnd of some instance would have decreased (see
+    // check_no_regression).
+    #[cfg(feature = "invariant-check")]
+    num_of_regressions: usize,
+
+    // If set, the control socket whose commands this acceptor answers (see with_admin_address).
+    admin: Option<AdminListener>,
+
+    node: NetNode<T>,
+
+    proposers_address: SocketAddrV4,
+
+    // If set, the Acceptance messages are also sent to the learners, at this address, so that they
+    // learn the chosen values directly (see with_learners_address).
+    learners_address: Option<SocketAddrV4>,
+}
+
+impl<T> Acceptor<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    pub fn new(
+        id: usize,
+        acceptors_address: SocketAddrV4,
+        proposers_address: SocketAddrV4,
+    ) -> Self {
+        Acceptor::with_node(NetNode::new(&acceptors_address), id, proposers_address)
+    }
+
+    /// Creates an acceptor which sends and receives its messages using node.
+    pub(crate) fn with_node(node: NetNode<T>, id: usize, proposers_address: SocketAddrV4) -> Self {
+        #[cfg(feature = "metrics")]
+        let node = node.with_role("acceptor");
+
+        Acceptor {
+            uuid: Uuid::new_v4(),
+            id,
+            group_id: 0,
+            acceptor_states: HashMap::new(),
+            max_instance_seen: 0,
+            max_instance_stored: 0,
+            tail_promise: None,
+            storage: None,
+            load: LoadStats::default(),
+            last_arrival: None,
+            admission_control: None,
+            summarize: None,
+            reply_cache: None,
+            num_of_coalesced: 0,
+            log_truncation: false,
+            truncated_below: 1,
+            memory_soft_cap: None,
+            above_memory_soft_cap: false,
+            #[cfg(feature = "invariant-check")]
+            num_of_regressions: 0,
+            admin: None,
+            node,
+            proposers_address,
+            learners_address: None,
+        }
+    }
+
+    /// Replaces the (randomly generated) unique identifier of this acceptor with uuid.
+    pub fn with_uuid(mut self, uuid: Uuid) -> Self {
+        self.uuid = uuid;
+        self
+    }
+
+    /// Makes this acceptor participate in the Paxos group group_id (by default, 0).
+    pub fn with_group_id(mut self, group_id: usize) -> Self {
+        self.group_id = group_id;
+        self
+    }
+
+    /// Writes every message sent or received by this acceptor to message_log.
+    pub fn with_message_log(mut self, message_log: MessageLog) -> Self {
+        self.node.set_message_log(message_log);
+        self
+    }
+
+    /// Makes this acceptor send its messages from the local address sender_address (e.g. to choose
+    /// the network interface which the multicast messages leave through).
+    pub fn with_sender_address(mut self, sender_address: Ipv4Addr) -> Self {
+        self.node.set_sender_address(sender_address);
+        self
+    }
+
+    /// Limits the rate of the messages sent by this acceptor to rate messages per second, with
+    /// bursts of at most burst messages (see Proposer::with_max_send_rate).
+    pub fn with_max_send_rate(mut self, rate: u32, burst: usize) -> Self {
+        self.node.set_max_send_rate(rate, burst);
+        self
+    }
+
+    /// Makes this acceptor send the messages larger than max_datagram_size bytes in fragments (see
+    /// Proposer::with_max_datagram_size).
+    pub fn with_max_datagram_size(mut self, max_datagram_size: usize) -> Self {
+        self.node.set_max_datagram_size(max_datagram_size);
+        self
+    }
+
+    /// Makes this acceptor refuse to send the messages which do not fit in a datagram, if fragmentation
+    /// is false, instead of fragmenting them (see Proposer::with_fragmentation).
+    pub fn with_fragmentation(mut self, fragmentation: bool) -> Self {
+        self.node.set_fragmentation(fragmentation);
+        self
+    }
+
+    /// Makes this acceptor also send its Acceptance messages to the learners, at learners_address,
+    /// and not only to the proposers, so that the learners which learn directly from the acceptors
+    /// (see Learner::with_direct_learning) know a chosen value as soon as a majority of the
+    /// acceptors voted for it, without waiting for the Learning message of a proposer. This costs
+    /// an extra message per vote, so, by default, the Acceptance messages are only sent to the
+    /// proposers.
+    pub fn with_learners_address(mut self, learners_address: SocketAddrV4) -> Self {
+        self.learners_address = Some(learners_address);
+        self
+    }
+
+    /// Makes this acceptor answer the commands of the operators (e.g. status), sent to a control
+    /// socket which binds to admin_address (see Proposer::with_admin_address).
+    pub fn with_admin_address(mut self, admin_address: SocketAddrV4) -> io::Result<Self> {
+        self.admin = Some(AdminListener::bind(admin_address)?);
+        Ok(self)
+    }
+
+    /// Makes this acceptor belong to the cluster named cluster_name (by default, the empty name), so
+    /// that it ignores the messages of the nodes of other clusters (see NetNode::set_cluster_id).
+    pub fn with_cluster_id(mut self, cluster_name: &str) -> Self {
+        self.node.set_cluster_id(cluster_name);
+        self
+    }
+
+    /// Makes this acceptor encrypt its messages with a key derived from secret, which must be shared by
+    /// all the nodes of the cluster, and drop the ones which it cannot decrypt (see
+    /// NetNode::set_encryption_key).
+    #[cfg(feature = "encryption")]
+    pub fn with_encryption_key(mut self, secret: &str) -> Self {
+        self.node.set_encryption_key(secret);
+        self
+    }
+
+    /// Makes a separate thread receive the messages of this acceptor, as soon as they arrive, and
+    /// queue them, up to capacity messages, until this acceptor handles them, so that a slow handler
+    /// does not make the socket drop the messages which arrive in the meantime.
+    pub fn with_receive_queue(mut self, capacity: usize) -> Self {
+        self.node.set_receive_queue(capacity);
+        self
+    }
+
+    /// Makes this acceptor durably store its state in the file at path. If the file already
+    /// contains the state of a previous execution of this acceptor, that state is recovered.
+    pub fn with_storage<P: AsRef<Path>>(mut self, path: P) -> io::Result<Self> {
+        let (storage, records) = Storage::<AcceptorRecord<T>>::open(path)?;
+
+        for record in records {
+            // The first instance of the tail is not stored, so the tail promise is restored for all
+            // the instances, i.e. this acceptor promises more than it did, which is safe.
+            if record.instance == TAIL_RECORD_INSTANCE {
+                self.tail_promise = Some((1, record.rnd));
+                continue;
+            }
+
+            if record.instance > self.max_instance_seen {
+                self.max_instance_seen = record.instance;
+                self.max_instance_stored = record.instance;
+            }
+
+            // A record without a promise nor a vote only stores that the instance was seen (see
+            // persist_max_instance_seen), so the state of the instance is left as it is.
+            if record.rnd == Ballot::ZERO && record.v_rnd == Ballot::ZERO && record.v_val.is_none()
+            {
+                continue;
+            }
+
+            let state = self.acceptor_states.entry(record.instance).or_default();
+
+            // The records of an instance are stored in the order its state changed, so a bad
+            // restore would show up as a regression.
+            #[cfg(feature = "invariant-check")]
+            for &(name, stored, new) in &[
+                ("rnd", state.rnd, record.rnd),
+                ("v_rnd", state.v_rnd, record.v_rnd),
+            ] {
+                check_no_regression(
+                    self.id,
+                    record.instance,
+                    name,
+                    stored,
+                    new,
+                    &mut self.num_of_regressions,
+                );
+            }
+
+            state.rnd = record.rnd;
+            state.v_rnd = record.v_rnd;
+            state.v_val = record.v_val;
+            state.v_request = record.v_request;
+        }
+
+        self.storage = Some(storage);
+
+        Ok(self)
+    }
+
+    /// Makes this acceptor consult admission_control before processing each received message, so
+    /// that, when overloaded, it can preferentially drop the less important messages (e.g. the
+    /// redundant retransmissions), instead of letting the kernel drop messages at random.
+    pub fn with_admission_control(mut self, admission_control: AdmissionControl<T>) -> Self {
+        self.admission_control = Some(admission_control);
+        self
+    }
+
+    /// Makes this acceptor remember, for window, the replies (i.e. the Promise and Acceptance
+    /// messages) which it sent to at most capacity of the last Preparation and Proposal messages
+    /// it received, so that it answers the retransmissions of these messages (i.e. with the same
+    /// instance, round and proposer) with the same reply, without processing them again (e.g.
+    /// without storing its state again).
+    pub fn with_reply_cache(mut self, window: Duration, capacity: usize) -> Self {
+        self.reply_cache = Some(ReplyCache::new(window, capacity));
+        self
+    }
+
+    /// Makes this acceptor drop its state of the instances below a stable checkpoint, when a
+    /// proposer tells it so with a Truncation message (see Proposer::with_checkpoint), and ignore
+    /// the messages about these instances from then on, as they are never started again. If this
+    /// acceptor has storage (see with_storage), the records of these instances stay in its file,
+    /// so, after a restart, their state is only dropped again at the next truncation.
+    pub fn with_log_truncation(mut self) -> Self {
+        self.log_truncation = true;
+        self
+    }
+
+    /// Returns the instance below which the log of this acceptor is truncated (see
+    /// with_log_truncation), or 1 if it is not truncated.
+    pub fn truncated_below(&self) -> usize {
+        self.truncated_below
+    }
+
+    /// Makes this acceptor warn when it tracks the state of more than max_instances instances (see
+    /// memory_report), as its state grows with the log until the log is truncated. If this acceptor
+    /// truncates its log (see with_log_truncation), it also asks the proposers, with a
+    /// TruncationRequest message, to send their last Truncation message again, so that it drops the
+    /// instances below the last stable checkpoint, which are safely decided, if it missed it. The
+    /// cap is soft: no other instance is dropped, and this acceptor keeps working above it. It warns
+    /// again only after it went back below the cap.
+    ///
+    /// Panics if max_instances is 0.
+    pub fn with_memory_soft_cap(mut self, max_instances: usize) -> Self {
+        assert!(max_instances > 0, "Expected a positive soft cap");
+
+        self.memory_soft_cap = Some(max_instances);
+        self
+    }
+
+    /// Returns the number of instances whose state this acceptor tracks, and an estimate of the
+    /// memory which it holds, so that operators can plan the capacity of the acceptors (see
+    /// with_memory_soft_cap).
+    pub fn memory_report(&self) -> MemoryReport {
+        // Each slot of the map holds an instance with its state, and a control byte.
+        let slot_size = mem::size_of::<(usize, AcceptorState<T>)>() + 1;
+
+        MemoryReport {
+            num_of_instances: self.acceptor_states.len(),
+            estimated_bytes: mem::size_of_val(&self.acceptor_states)
+                + self.acceptor_states.capacity() * slot_size,
+        }
+    }
+
+    /// Returns the number of the retransmissions which this acceptor answered from its reply cache
+    /// (see with_reply_cache), instead of processing them.
+    pub fn num_of_coalesced(&self) -> usize {
+        self.num_of_coalesced
+    }
+
+    /// Returns the number of received datagrams which this acceptor dropped, because they were
+    /// corrupted (see NetNode::num_of_corrupted).
+    pub fn num_of_corrupted_datagrams(&self) -> usize {
+        self.node.num_of_corrupted()
+    }
+
+    /// Returns the number of received datagrams which this acceptor dropped, because they were sent
+    /// by the nodes of another cluster (see with_cluster_id).
+    pub fn num_of_foreign_datagrams(&self) -> usize {
+        self.node.num_of_foreign()
+    }
+
+    /// Returns the number of received datagrams which this acceptor dropped, because it could not
+    /// decrypt them (see with_encryption_key).
+    #[cfg(feature = "encryption")]
+    pub fn num_of_undecryptable_datagrams(&self) -> usize {
+        self.node.num_of_undecryptable()
+    }
+
+    /// Makes this acceptor vote for the values of an existing log (e.g. of another cluster which is
+    /// migrated), in the ballot Ballot::PRELOADED, which no proposer can start, so that the values
+    /// are decided and can never be overwritten. values are the pairs (instance, value) of the log,
+    /// whose instances must be contiguous, starting at 1. The same log must be preloaded into the
+    /// proposers (see Proposer::preload_log), before the nodes start. If this acceptor has storage,
+    /// the preloaded values are stored too, so they can be preloaded again after a restart.
+    ///
+    /// Panics if the instances of values are not contiguous, or if this acceptor has already voted
+    /// for another value in one of them.
+    pub fn preload_log(&mut self, values: Vec<(usize, T)>) {
+        let values = validate_preloaded_log(values);
+
+        for &(instance, value) in &values {
+            let state = self.acceptor_states.entry(instance).or_default();
+
+            if let Some(v) = state.v_val {
+                assert_eq!(
+                    v, value,
+                    "The preloaded value of instance {:?} differs from the voted one",
+                    instance
+                );
+            }
+
+            if state.v_rnd == Ballot::PRELOADED {
+                continue;
+            }
+
+            state.rnd = Ballot::PRELOADED;
+            state.v_rnd = Ballot::PRELOADED;
+            state.v_val = Some(value);
+            state.v_request = None;
+
+            self.persist(instance);
+        }
+
+        if values.len() > self.max_instance_seen {
+            self.max_instance_seen = values.len();
+        }
+
+        if log_enabled!(Level::Info) {
+            info!(
+                "[A={:?}] I preloaded the values of {:?} instances.",
+                self.id,
+                values.len()
+            );
+        }
+    }
+
+    /// Returns the statistics about the load of this acceptor.
+    pub fn load(&self) -> LoadStats {
+        self.load
+    }
+
+    /// Returns the number of times rnd or v_rnd of some instance would have decreased, which would
+    /// compromise the safety of Paxos (e.g. because of a bug, or a bad restore of the state).
+    #[cfg(feature = "invariant-check")]
+    pub fn num_of_regressions(&self) -> usize {
+        self.num_of_regressions
+    }
+
+    /// Returns the highest instance this acceptor knows about. Note that, after a restart, this is
+    /// the highest instance which was stored (see with_storage), which includes the instances only
+    /// seen in a rejected message.
+    pub fn max_instance_seen(&self) -> usize {
+        self.max_instance_seen
+    }
+
+    /// Handles the message m, which was received by this acceptor, by dispatching it to the
+    /// corresponding handler. Messages of other Paxos groups are ignored.
+    pub fn handle(&mut self, m: Message<T>) {
+        let arrival = Instant::now();
+        self.load
+            .record_arrival(self.last_arrival.map(|last_arrival| arrival - last_arrival));
+        self.last_arrival = Some(arrival);
+
+        if m.group_id() != self.group_id {
+            if logs_messages() {
+                info!(
+                    "[A={:?}] I will ignore {:?} of another group.",
+                    self.id,
+                    Logged(&m)
+                );
+            }
+            return;
+        }
+
+        if let Some(ref admission_control) = self.admission_control {
+            if !admission_control(&m, &self.load) {
+                if logs_messages() {
+                    info!("[A={:?}] I will shed {:?}.", self.id, Logged(&m));
+                }
+                self.load.record_shed();
+                return;
+            }
+        }
+
+        if let Some(instance) = truncatable_instance(&m).filter(|&i| i < self.truncated_below) {
+            if logs_messages() {
+                info!(
+                    "[A={:?}] Instance {:?} is truncated: I will ignore {:?}.",
+                    self.id,
+                    instance,
+                    Logged(&m)
+                );
+            }
+            return;
+        }
+
+        match m {
+            Message::Phase1a::<T>(preparation) => self.handle_preparation(preparation),
+            Message::Phase2a::<T>(proposal) => self.handle_proposal(proposal),
+            Message::ValueRequest(value_request) => self.handle_value_request(value_request),
+            Message::TailPreparation(tail_preparation) => {
+                self.handle_tail_preparation(tail_preparation)
+            }
+            Message::Truncation(truncation) => self.handle_truncation(truncation),
+            Message::Ping(ping) => pong(&self.node, self.uuid, ping),
+            _ => info!(
+                "[A={:?}] Unexpected message received. I'll ignore it.",
+                self.id
+            ),
+        }
+
+        self.check_memory();
+        self.load.record_processing(arrival.elapsed());
+    }
+
+    /// Handles the message contained in datagram, which was delivered to this acceptor by the owner
+    /// of its in-memory channel. Corrupted datagrams are dropped.
+    #[cfg(feature = "testkit")]
+    pub(crate) fn deliver(&mut self, datagram: &[u8]) {
+        if let Some(m) = self.node.decode(datagram) {
+            self.handle(m);
+        }
+    }
+
+    /// Returns the id and the unique identifier of this acceptor, so that the owner of its
+    /// in-memory channel can restart it with the same identity.
+    #[cfg(feature = "testkit")]
+    pub(crate) fn identity(&self) -> (usize, Uuid) {
+        (self.id, self.uuid)
+    }
+
+    // Handlers
+
+    /// Handles the Preparation message sent by a proposer to this acceptor.
+    fn handle_preparation(&mut self, preparation: Preparation) {
+        if logs_messages() {
+            info!(
+                "[A={:?}] I will handle {:?}.",
+                self.id,
+                Logged(&preparation)
+            );
+        }
+
+        self.see_instance(preparation.instance.0);
+
+        let key = (
+            RequestKind::Preparation,
+            preparation.instance,
+            preparation.c_rnd,
+            preparation.sender_uuid,
+        );
+        if self.answer_from_cache(&key) {
+            return;
+        }
+
+        self.promise(
+            preparation.c_rnd,
+            preparation.sender_uuid,
+            preparation.instance,
+            preparation.reply_address,
+            preparation.summaries,
+        );
+
+        self.persist_max_instance_seen();
+    }
+
+    /// Handles the Proposal message sent by a proposer to this acceptor.
+    fn handle_proposal(&mut self, proposal: Proposal<T>) {
+        if logs_messages() {
+            info!("[A={:?}] I will handle {:?}.", self.id, Logged(&proposal));
+        }
+
+        self.see_instance(proposal.instance.0);
+
+        let key = (
+            RequestKind::Proposal,
+            proposal.instance,
+            proposal.c_rnd,
+            proposal.sender_uuid,
+        );
+        if self.answer_from_cache(&key) {
+            return;
+        }
+
+        match proposal.c_val {
+            Some(c_val) => self.accept(
+                proposal.c_rnd,
+                c_val,
+                proposal.c_request,
+                proposal.sender_uuid,
+                proposal.instance,
+            ),
+            _ => panic!("Logic error: contact the programmer."),
+        }
+
+        self.persist_max_instance_seen();
+    }
+
+    /// Handles the ValueRequest message sent by a learner to this acceptor, by telling it the value
+    /// (if any) this acceptor voted for in the requested instance, and the round of that vote.
+    fn handle_value_request(&mut self, value_request: ValueRequest) {
+        if logs_messages() {
+            info!(
+                "[A={:?}] I will handle {:?}.",
+                self.id,
+                Logged(&value_request)
+            );
+        }
+
+        let (v_rnd, v_val) = match self.acceptor_states.get(&value_request.instance.0) {
+            Some(state) => (state.v_rnd, state.v_val),
+            None => (Ballot::ZERO, None),
+        };
+
+        let m = Message::ValueReply(ValueReply {
+            instance: value_request.instance,
+            v_rnd,
+            v_val,
+            sender_uuid: self.uuid,
+            receiver_uuid: value_request.sender_uuid,
+            group_id: self.group_id,
+        });
+
+        if logs_messages() {
+            info!("[A={:?}] I will send {:?}.", self.id, Logged(&m));
+        }
+
+        self.node.send(m, &value_request.reply_address);
+    }
+
+    /// Handles the TailPreparation message sent by a proposer which establishes an epoch (see
+    /// Proposer::establish_epoch): unless this acceptor already promised a higher round in some
+    /// instance of the tail, it promises c_rnd in all of them, including the ones it has not seen
+    /// yet, and it answers with its votes in the tail. Otherwise, it sends a Nack.
+    fn handle_tail_preparation(&mut self, tail_preparation: TailPreparation) {
+        if logs_messages() {
+            info!(
+                "[A={:?}] I will handle {:?}.",
+                self.id,
+                Logged(&tail_preparation)
+            );
+        }
+
+        let (c_rnd, from_instance) = (tail_preparation.c_rnd, tail_preparation.from_instance.0);
+        let destination_address = tail_preparation
+            .reply_address
+            .unwrap_or(self.proposers_address);
+
+        // The highest round promised in the tail, if it is higher than c_rnd, with an instance where
+        // it was promised.
+        let higher_rnd = self
+            .acceptor_states
+            .iter()
+            .filter(|&(&instance, _)| instance >= from_instance)
+            .map(|(&instance, state)| (state.rnd, instance))
+            .chain(
+                self.tail_promise
+                    .map(|(tail_from, tail_rnd)| (tail_rnd, tail_from.max(from_instance))),
+            )
+            .filter(|&(rnd, _)| rnd > c_rnd)
+            .max();
+
+        if let Some((rnd, instance)) = higher_rnd {
+            self.nack(
+                rnd,
+                tail_preparation.sender_uuid,
+                InstanceId(instance),
+                destination_address,
+            );
+            return;
+        }
+
+        // The promise of the previous tail, if any, must be kept in its instances too.
+        let tail_from = self
+            .tail_promise
+            .map_or(from_instance, |(tail_from, _)| tail_from.min(from_instance));
+
+        if self.tail_promise != Some((tail_from, c_rnd)) {
+            self.tail_promise = Some((tail_from, c_rnd));
+            self.persist_tail_promise();
+        }
+
+        let mut voted: Vec<(usize, &AcceptorState<T>)> = self
+            .acceptor_states
+            .iter()
+            .filter(|&(&instance, state)| instance >= from_instance && state.v_val.is_some())
+            .map(|(&instance, state)| (instance, state))
+            .collect();
+        voted.sort_unstable_by_key(|&(instance, _)| instance);
+
+        let votes = voted
+            .iter()
+            .filter_map(|&(instance, state)| {
+                state
+                    .v_val
+                    .map(|v_val| (InstanceId(instance), state.v_rnd, v_val))
+            })
+            .collect();
+        let v_requests = voted.iter().map(|&(_, state)| state.v_request).collect();
+
+        let m = Message::TailPromise(TailPromise {
+            rnd: c_rnd,
+            from_instance: tail_preparation.from_instance,
+            votes,
+            sender_uuid: self.uuid,
+            receiver_uuid: tail_preparation.sender_uuid,
+            group_id: self.group_id,
+            v_requests,
+        });
+
+        if logs_messages() {
+            info!("[A={:?}] I will send {:?}.", self.id, Logged(&m));
+        }
+
+        self.node.send(m, &destination_address);
+    }
+
+    /// Handles the Truncation message sent by a proposer once the checkpoint of its instance is
+    /// stable, by dropping the state of the previous instances, if this acceptor truncates its log
+    /// (see with_log_truncation).
+    fn handle_truncation(&mut self, truncation: Truncation) {
+        let instance = truncation.instance.0;
+
+        if !self.log_truncation || instance <= self.truncated_below {
+            if logs_messages() {
+                info!("[A={:?}] I will ignore {:?}.", self.id, Logged(&truncation));
+            }
+            return;
+        }
+
+        if log_enabled!(Level::Info) {
+            info!(
+                "[A={:?}] I will truncate the log below instance {:?}.",
+                self.id, instance
+            );
+        }
+
+        self.acceptor_states.retain(|&i, _| i >= instance);
+        self.acceptor_states.shrink_to_fit();
+        self.truncated_below = instance;
+    }
+
+    /// Records the memory report of this acceptor (with the feature metrics), and, if it just went
+    /// above its soft cap (see with_memory_soft_cap), warns and asks the proposers to truncate its
+    /// log.
+    fn check_memory(&mut self) {
+        let report = self.memory_report();
+
+        #[cfg(feature = "metrics")]
+        telemetry::memory_reported("acceptor", report);
+
+        let max_instances = match self.memory_soft_cap {
+            Some(max_instances) => max_instances,
+            None => return,
+        };
+
+        if report.num_of_instances <= max_instances {
+            self.above_memory_soft_cap = false;
+            return;
+        }
+
+        if self.above_memory_soft_cap {
+            return;
+        }
+
+        self.above_memory_soft_cap = true;
+
+        warn!(
+            "[A={:?}] I track {} instances (about {} bytes), more than my soft cap of {}.",
+            self.id, report.num_of_instances, report.estimated_bytes, max_instances
+        );
+
+        if self.log_truncation {
+            self.send_truncation_request();
+        }
+    }
+
+    /// Sends a TruncationRequest message to the proposers, so that the ones whose log is truncated
+    /// further than the log of this acceptor send their Truncation message again.
+    fn send_truncation_request(&self) {
+        let m = Message::TruncationRequest::<T>(TruncationRequest {
+            truncated_below: InstanceId(self.truncated_below),
+            sender_uuid: self.uuid,
+            group_id: self.group_id,
+        });
+
+        if logs_messages() {
+            info!("[A={:?}] I will send {:?}.", self.id, Logged(&m));
+        }
+
+        self.node.send(m, &self.proposers_address);
+    }
+
+    /// Sends again the reply to the request key, if it is in the reply cache, i.e. if the request
+    /// is a retransmission of a request received recently. Returns whether it did.
+    fn answer_from_cache(&mut self, key: &RequestKey) -> bool {
+        let (m, destination_address) = match self.reply_cache {
+            Some(ref mut reply_cache) => match reply_cache.get(key) {
+                Some(reply) => reply,
+                None => return false,
+            },
+            None => return false,
+        };
+
+        if logs_messages() {
+            info!("[A={:?}] I will send again {:?}.", self.id, Logged(&m));
+        }
+
+        self.num_of_coalesced += 1;
+        self.node.send(m, &destination_address);
+
+        true
+    }
+
+    /// Caches the reply m to the request key, sent to destination_address, if this acceptor has a
+    /// reply cache.
+    fn cache_reply(&mut self, key: RequestKey, m: &Message<T>, destination_address: SocketAddrV4) {
+        if let Some(ref mut reply_cache) = self.reply_cache {
+            reply_cache.insert(key, m.clone(), destination_address);
+        }
+    }
+
+    /// Keeps track of the highest instance referenced by the received messages.
+    fn see_instance(&mut self, instance: usize) {
+        if instance > self.max_instance_seen {
+            self.max_instance_seen = instance;
+        }
+    }
+
+    /// Durably stores the state associated with instance, if this acceptor has a storage. This must
+    /// be done before answering to the proposers, otherwise, after a restart, this acceptor could
+    /// break the promises or forget the votes it sent.
+    fn persist(&mut self, instance: usize) {
+        if let Some(ref mut storage) = self.storage {
+            let state = &self.acceptor_states[&instance];
+
+            let record = AcceptorRecord {
+                instance,
+                rnd: state.rnd,
+                v_rnd: state.v_rnd,
+                v_val: state.v_val,
+                v_request: state.v_request,
+            };
+
+            storage
+                .append(&record)
+                .expect("Could not store the state of the acceptor");
+
+            self.max_instance_stored = self.max_instance_stored.max(instance);
+        }
+    }
+
+    /// Durably stores max_instance_seen, if this acceptor has a storage, and if it advanced beyond
+    /// the instances already stored (e.g. because it was only seen in a Preparation which was
+    /// rejected), with a record without a promise nor a vote, so that it is not forgotten after a
+    /// restart. Unlike the promises and the votes, it is not needed by the safety of Paxos, so it
+    /// is stored after answering the proposer.
+    fn persist_max_instance_seen(&mut self) {
+        if self.max_instance_seen <= self.max_instance_stored {
+            return;
+        }
+
+        if let Some(ref mut storage) = self.storage {
+            let record = AcceptorRecord {
+                instance: self.max_instance_seen,
+                rnd: Ballot::ZERO,
+                v_rnd: Ballot::ZERO,
+                v_val: None,
+                v_request: None,
+            };
+
+            storage
+                .append(&record)
+                .expect("Could not store the state of the acceptor");
+
+            self.max_instance_stored = self.max_instance_seen;
+        }
+    }
+
+    /// Durably stores the tail promise of this acceptor (see handle_tail_preparation), if it has a
+    /// storage, before answering the proposer which established the epoch.
+    fn persist_tail_promise(&mut self) {
+        if let (Some(ref mut storage), Some((_, rnd))) = (&mut self.storage, self.tail_promise) {
+            let record = AcceptorRecord {
+                instance: TAIL_RECORD_INSTANCE,
+                rnd,
+                v_rnd: Ballot::ZERO,
+                v_val: None,
+                v_request: None,
+            };
+
+            storage
+                .append(&record)
+                .expect("Could not store the state of the acceptor");
+        }
+    }
+
+    /// Makes the state associated with instance promise the round of the tail promise of this
+    /// acceptor too, if the instance belongs to the tail (see handle_tail_preparation).
+    fn apply_tail_promise(&mut self, instance: usize) {
+        if let Some((tail_from, tail_rnd)) = self.tail_promise {
+            if instance >= tail_from {
+                let state = self.acceptor_states.entry(instance).or_default();
+                state.rnd = state.rnd.max(tail_rnd);
+            }
+        }
+    }
+
+    // Senders
+
+    /// Sends a Promise message to one or more proposers, if c_rnd > rnd. If reply_address is not
+    /// None, the message is sent there, instead of to the address of the proposers.
+    fn promise(
+        &mut self,
+        c_rnd: Ballot,
+        sender_uid: Uuid,
+        instance: InstanceId,
+        reply_address: Option<SocketAddrV4>,
+        summaries: bool,
+    ) {
+        self.apply_tail_promise(instance.0);
+        let state = self.acceptor_states.entry(instance.0).or_default();
+
+        if c_rnd > state.rnd {
+            #[cfg(feature = "invariant-check")]
+            check_no_regression(
+                self.id,
+                instance.0,
+                "rnd",
+                state.rnd,
+                c_rnd,
+                &mut self.num_of_regressions,
+            );
+
+            // The promise.
+            state.rnd = c_rnd;
+
+            let (rnd, v_rnd, mut v_val, v_request) =
+                (state.rnd, state.v_rnd, state.v_val, state.v_request);
+
+            self.persist(instance.0);
+
+            // If the proposer accepts it, only the summary of the voted value is sent.
+            let mut v_summary = None;
+            if let (true, Some(summarize), Some(v)) = (summaries, self.summarize, v_val) {
+                v_summary = Some(summarize(&v));
+                v_val = None;
+            }
+
+            let m = Message::Phase1b::<T>(Promise {
+                rnd,
+                v_rnd,
+                v_val, // The value it last accepted. It can be None.
+                sender_uuid: self.uuid,
+                group_id: self.group_id,
+                receiver_uuid: sender_uid,
+                instance,
+                v_summary,
+                v_request,
+            });
+
+            if logs_messages() {
+                info!("[A={:?}] I will send {:?}.", self.id, Logged(&m));
+            }
+
+            let destination_address = reply_address.unwrap_or(self.proposers_address);
+
+            self.cache_reply(
+                (RequestKind::Preparation, instance, c_rnd, sender_uid),
+                &m,
+                destination_address,
+            );
+            self.node.send(m, &destination_address);
+        } else if c_rnd < state.rnd {
+            let rnd = state.rnd;
+            self.nack(
+                rnd,
+                sender_uid,
+                instance,
+                reply_address.unwrap_or(self.proposers_address),
+            );
+        }
+    }
+
+    /// Sends an Acceptance message to one or more proposers, if c_rnd >= rnd.
+    fn accept(
+        &mut self,
+        c_rnd: Ballot,
+        c_val: T,
+        c_request: Option<RequestId>,
+        sender_uid: Uuid,
+        instance: InstanceId,
+    ) {
+        self.apply_tail_promise(instance.0);
+        let state = self.acceptor_states.entry(instance.0).or_default();
+
+        // A round has a single proposer, which proposes a single value in it, so a different value
+        // proposed in the round this acceptor already voted in means that two proposers started
+        // the same round. Voting again would overwrite a vote which may have been counted already.
+        if c_rnd == state.v_rnd && state.v_val.is_some() && state.v_val != Some(c_val) {
+            error!(
+                "[A={:?}] Safety violation: {:?} proposed in round {:?} of instance {:?}, where I already voted for {:?}. I will drop it.",
+                self.id, c_val, c_rnd, instance, state.v_val
+            );
+            return;
+        }
+
+        if c_rnd >= state.rnd {
+            #[cfg(feature = "invariant-check")]
+            check_no_regression(
+                self.id,
+                instance.0,
+                "v_rnd",
+                state.v_rnd,
+                c_rnd,
+                &mut self.num_of_regressions,
+            );
+
+            state.v_rnd = c_rnd;
+            state.v_val = Some(c_val);
+            state.v_request = c_request;
+
+            let (v_rnd, v_val) = (state.v_rnd, state.v_val);
+
+            self.persist(instance.0);
+
+            let m = Message::Phase2b::<T>(Acceptance {
+                v_rnd,
+                v_val,
+                v_request: c_request,
+                sender_uuid: self.uuid,
+                group_id: self.group_id,
+                receiver_uuid: sender_uid,
+                instance,
+            });
+
+            if logs_messages() {
+                info!("[A={:?}] I will send {:?}.", self.id, Logged(&m));
+            }
+
+            let destination_address = self.proposers_address;
+
+            self.cache_reply(
+                (RequestKind::Proposal, instance, c_rnd, sender_uid),
+                &m,
+                destination_address,
+            );
+
+            if let Some(ref learners_address) = self.learners_address {
+                self.node.send(m.clone(), learners_address);
+            }
+
+            self.node.send(m, &destination_address);
+        } else {
+            let rnd = state.rnd;
+            self.nack(rnd, sender_uid, instance, self.proposers_address);
+        }
+    }
+
+    /// Tells the proposer sender_uid, with a Nack message sent to destination_address, that its
+    /// round of instance is rejected, because this acceptor already promised the higher round rnd.
+    /// Paxos does not need it to be safe, but the proposer can then back off at once, instead of
+    /// waiting for the answers which will never come.
+    fn nack(
+        &mut self,
+        rnd: Ballot,
+        sender_uid: Uuid,
+        instance: InstanceId,
+        destination_address: SocketAddrV4,
+    ) {
+        let m = Message::Phase1c::<T>(Nack {
+            v_rnd: rnd,
+            sender_uuid: self.uuid,
+            receiver_uuid: sender_uid,
+            instance,
+            group_id: self.group_id,
+        });
+
+        if logs_messages() {
+            info!("[A={:?}] I will send {:?}.", self.id, Logged(&m));
+        }
+
+        self.node.send(m, &destination_address);
+    }
+}
+
+impl<T> Acceptor<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq + Summarize,
+{
+    /// Makes this acceptor send the summary of its voted value (see Summarize), instead of the
+    /// value itself, in its Promise messages, to the proposers which accept it.
+    pub fn with_summaries(mut self) -> Self {
+        self.summarize = Some(T::summarize);
+        self
+    }
+}
+
+impl<T> Acceptor<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    /// Answers the commands received on the control socket of this acceptor, if any.
+    fn poll_admin(&mut self) {
+        if let Some(admin) = self.admin.take() {
+            admin.poll(|command| self.answer(command));
+            self.admin = Some(admin);
+        }
+    }
+
+    /// Answers the given command of an operator (see with_admin_address).
+    fn answer(&mut self, command: Command) -> Answer {
+        match command {
+            Command::Role => Ok(format!(
+                "role=acceptor id={} uuid={} group={}",
+                self.id, self.uuid, self.group_id
+            )),
+            Command::Status => Ok(format!(
+                "known_instances={} max_instance_seen={} coalesced={}",
+                self.acceptor_states.len(),
+                self.max_instance_seen,
+                self.num_of_coalesced
+            )),
+            Command::Instance(instance) => Ok(format!(
+                "instance={} {}",
+                instance,
+                self.describe_instance(instance)
+            )),
+            Command::Metrics => {
+                let memory = self.memory_report();
+
+                Ok(format!(
+                    "received={} shed={} utilization={:.3} queue_depth={:.3} overloaded={} tracked_instances={} tracked_bytes={}",
+                    self.load.num_of_received,
+                    self.load.num_of_shed,
+                    self.load.utilization(),
+                    self.load.estimated_queue_depth(),
+                    self.load.is_overloaded(),
+                    memory.num_of_instances,
+                    memory.estimated_bytes
+                ))
+            }
+            Command::ForceNoop(_) => Err("only a proposer can force a no-op".to_string()),
+            Command::Drain => Err("only a proposer can drain".to_string()),
+            Command::StepDown => Err("only a proposer can step down".to_string()),
+            Command::Log { .. } => {
+                Err("an acceptor does not know which values are decided".to_string())
+            }
+        }
+    }
+
+    /// Returns the state of this acceptor in the given instance, as `key=value` pairs.
+    fn describe_instance(&self, instance: usize) -> String {
+        match self.acceptor_states.get(&instance) {
+            Some(state) => format!(
+                "rnd={}.{} v_rnd={}.{} v_val={:?}",
+                state.rnd.epoch,
+                state.rnd.proposer_id,
+                state.v_rnd.epoch,
+                state.v_rnd.proposer_id,
+                state.v_val
+            ),
+            None => "rnd=0.0 v_rnd=0.0 v_val=None".to_string(),
+        }
+    }
+}
+
+impl<T> EventLoop<T> for Acceptor<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    fn net_node(&mut self) -> &mut NetNode<T> {
+        &mut self.node
+    }
+
+    fn read_timeout(&self) -> Option<Duration> {
+        // Without a control socket, this acceptor waits until a message is received.
+        admin::receive_timeout(&self.admin, None)
+    }
+
+    fn log_waiting(&self) {
+        if log_enabled!(Level::Info) {
+            info!("[A={:?}] Acceptor waiting...", self.id);
+        }
+    }
+
+    fn handle_message(&mut self, m: Message<T>) {
+        self.handle(m);
+    }
+
+    fn on_idle(&mut self) {
+        self.poll_admin();
+    }
+}
+
+impl<T> Runnable for Acceptor<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    fn run(&mut self) {
+        self.serve(|_| {});
+    }
+}
+
+/// Removes, from the queued requests, and returns the one which must be proposed next, according
+/// to policy, given the client of the request which was proposed last, if any.
+fn next_request<T>(
+    requests: &mut VecDeque<Request<T>>,
+    policy: OrderingPolicy,
+    last_served_client: Option<Uuid>,
+) -> Option<Request<T>> {
+    let position = match policy {
+        OrderingPolicy::Arrival => 0,
+        // The first request of the first client after the last served one (in the order of their
+        // unique identifiers), wrapping around to the first client.
+        OrderingPolicy::ByClient => requests
+            .iter()
+            .enumerate()
+            .min_by_key(|&(_, r)| {
+                let served_in_this_round = match last_served_client {
+                    Some(last_served_client) => r.sender_uuid <= last_served_client,
+                    None => false,
+                };
+                (served_in_this_round, r.sender_uuid)
+            })
+            .map(|(i, _)| i)?,
+        OrderingPolicy::BySequence => requests
+            .iter()
+            .enumerate()
+            .min_by_key(|&(_, r)| (r.request_seq, r.sender_uuid))
+            .map(|(i, _)| i)?,
+    };
+
+    requests.remove(position)
+}
+
+/// Returns the instance of m, if m is about the round of a single instance (e.g. a Proposal or a
+/// Learning message), which the proposers and the acceptors ignore once that instance is truncated
+/// (see Proposer::with_checkpoint).
+fn truncatable_instance<T>(m: &Message<T>) -> Option<usize> {
+    match m {
+        Message::Phase1a(preparation) => Some(preparation.instance.0),
+        Message::Phase1b(promise) => Some(promise.instance.0),
+        Message::Phase1c(nack) => Some(nack.instance.0),
+        Message::Phase2a(proposal) => Some(proposal.instance.0),
+        Message::Phase2b(acceptance) => Some(acceptance.instance.0),
+        Message::Phase3(learning) => Some(learning.instance.0),
+        Message::NoopRequest(noop_request) => Some(noop_request.instance.0),
+        Message::ValueRequest(value_request) => Some(value_request.instance.0),
+        _ => None,
+    }
+}
+
+/// Returns the pairs (instance, value) of a preloaded log (see Acceptor::preload_log), sorted by
+/// instance.
+///
+/// Panics if the instances are not contiguous, starting at 1.
+fn validate_preloaded_log<T>(mut values: Vec<(usize, T)>) -> Vec<(usize, T)> {
+    values.sort_by_key(|&(instance, _)| instance);
+
+    for (i, &(instance, _)) in values.iter().enumerate() {
+        assert_eq!(
+            instance,
+            i + 1,
+            "The instances of a preloaded log must be contiguous, starting at 1"
+        );
+    }
+
+    values
+}
+
+/// Checks that the round called name (i.e. rnd or v_rnd) of the given instance, of the acceptor
+/// with the given id, does not decrease from stored to new, as required by the safety of Paxos. A
+/// regression is logged and counted in num_of_regressions. It also panics, but only in debug
+/// builds, so that the tests catch it.
+#[cfg(feature = "invariant-check")]
+fn check_no_regression(
+    id: usize,
+    instance: usize,
+    name: &str,
+    stored: Ballot,
+    new: Ballot,
+    num_of_regressions: &mut usize,
+) {
+    if new < stored {
+        *num_of_regressions += 1;
+
+        error!(
+            "[A={:?}] Invariant violated: {} of instance {:?} would decrease from {:?} to {:?} ({:?} regressions so far).",
+            id, name, instance, stored, new, num_of_regressions
+        );
+
+        if cfg!(debug_assertions) {
+            panic!(
+                "Bug: {} of instance {:?} would decrease from {:?} to {:?}.",
+                name, instance, stored, new
+            );
+        }
+    }
+}
+
+/// A node which hosts both a proposer and an acceptor, which share the sockets (and thus the port)
+/// of this node. This is useful for small deployments, where each machine runs one proposer and
+/// one acceptor. The proposers and the acceptors must then have the same address, so that the
+/// messages addressed to either role are received by the shared socket.
+pub struct CoLocated<T> {
+    proposer: Proposer<T>,
+
+    acceptor: Acceptor<T>,
+
+    // The node which receives the messages addressed to both the proposer and the acceptor, which
+    // send their messages through (a copy of) it.
+    node: NetNode<T>,
+
+    // If set, the control socket whose commands this node answers (see with_admin_address).
+    admin: Option<AdminListener>,
+}
+
+impl<T> CoLocated<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    /// Creates a proposer and an acceptor, both with the given id, which receive their messages at
+    /// address, which is the address of both the proposers and the acceptors.
+    ///
+    /// Panics if num_of_acceptors is zero.
+    pub fn new(
+        id: usize,
+        address: SocketAddrV4,
+        learners_address: SocketAddrV4,
+        num_of_acceptors: usize,
+    ) -> Self {
+        let node = NetNode::new(&address);
+
+        // The messages received by the CoLocated node itself, before they are dispatched to its
+        // roles, are labeled with its own role.
+        #[cfg(feature = "metrics")]
+        let node = node.with_role("colocated");
+
+        CoLocated {
+            proposer: Proposer::with_node(
+                node.share(),
+                id,
+                address,
+                address,
+                learners_address,
+                num_of_acceptors,
+            ),
+            acceptor: Acceptor::with_node(node.share(), id, address),
+            node,
+            admin: None,
+        }
+    }
+
+    /// Configures the hosted proposer, e.g. with_proposer(|p| p.with_group_id(1)).
+    pub fn with_proposer<F: FnOnce(Proposer<T>) -> Proposer<T>>(self, f: F) -> Self {
+        CoLocated {
+            proposer: f(self.proposer),
+            ..self
+        }
+    }
+
+    /// Configures the hosted acceptor, e.g. with_acceptor(|a| a.with_group_id(1)).
+    pub fn with_acceptor<F: FnOnce(Acceptor<T>) -> Acceptor<T>>(self, f: F) -> Self {
+        CoLocated {
+            acceptor: f(self.acceptor),
+            ..self
+        }
+    }
+
+    /// Writes every message sent or received by this node (i.e. by its proposer or acceptor) to
+    /// message_log.
+    pub fn with_message_log(mut self, message_log: MessageLog) -> Self {
+        self.node.set_message_log(message_log);
+        self.share_node();
+        self
+    }
+
+    /// Makes this node send its messages from the local address sender_address (e.g. to choose the
+    /// network interface which the multicast messages leave through).
+    pub fn with_sender_address(mut self, sender_address: Ipv4Addr) -> Self {
+        self.node.set_sender_address(sender_address);
+        self.share_node();
+        self
+    }
+
+    /// Limits the rate of the messages sent by this node (i.e. by its proposer and its acceptor
+    /// together) to rate messages per second, with bursts of at most burst messages (see
+    /// Proposer::with_max_send_rate).
+    pub fn with_max_send_rate(mut self, rate: u32, burst: usize) -> Self {
+        self.node.set_max_send_rate(rate, burst);
+        self.share_node();
+        self
+    }
+
+    /// Makes this node send the messages larger than max_datagram_size bytes in fragments (see
+    /// Proposer::with_max_datagram_size).
+    pub fn with_max_datagram_size(mut self, max_datagram_size: usize) -> Self {
+        self.node.set_max_datagram_size(max_datagram_size);
+        self.share_node();
+        self
+    }
+
+    /// Makes this node refuse to send the messages which do not fit in a datagram, if fragmentation
+    /// is false, instead of fragmenting them (see Proposer::with_fragmentation).
+    pub fn with_fragmentation(mut self, fragmentation: bool) -> Self {
+        self.node.set_fragmentation(fragmentation);
+        self.share_node();
+        self
+    }
+
+    /// Makes this node belong to the cluster named cluster_name (see Acceptor::with_cluster_id).
+    pub fn with_cluster_id(mut self, cluster_name: &str) -> Self {
+        self.node.set_cluster_id(cluster_name);
+        self.share_node();
+        self
+    }
+
+    /// Makes this node encrypt its messages with a key derived from secret (see
+    /// Acceptor::with_encryption_key).
+    #[cfg(feature = "encryption")]
+    pub fn with_encryption_key(mut self, secret: &str) -> Self {
+        self.node.set_encryption_key(secret);
+        self.share_node();
+        self
+    }
+
+    /// Makes this node also receive its messages at unicast_address, and the replies addressed to
+    /// its proposer be sent there (see Proposer::with_unicast_address).
+    pub fn with_unicast_address(mut self, unicast_address: SocketAddrV4) -> Self {
+        self.node.set_unicast_address(unicast_address);
+        self.share_node();
+
+        if !unicast_address.ip().is_unspecified() {
+            self.proposer
+                .advertised_address
+                .get_or_insert(unicast_address);
+        }
+
+        self
+    }
+
+    /// Makes this node also receive the client requests sent to the multicast group
+    /// requests_address (see Proposer::with_requests_address).
+    pub fn with_requests_address(mut self, requests_address: SocketAddrV4) -> Self {
+        self.node.join_multicast_group(requests_address);
+        self.share_node();
+        self
+    }
+
+    /// Makes this node answer the commands of the operators (e.g. status), about both its proposer
+    /// and its acceptor, sent to a control socket which binds to admin_address (see
+    /// Proposer::with_admin_address).
+    pub fn with_admin_address(mut self, admin_address: SocketAddrV4) -> io::Result<Self> {
+        self.admin = Some(AdminListener::bind(admin_address)?);
+        Ok(self)
+    }
+
+    /// Makes a separate thread receive the messages of this node, as soon as they arrive, and
+    /// queue them, up to capacity messages, until the proposer or the acceptor handles them (see
+    /// Acceptor::with_receive_queue).
+    pub fn with_receive_queue(mut self, capacity: usize) -> Self {
+        self.node.set_receive_queue(capacity);
+        self
+    }
+
+    /// Preloads the values of an existing log into both the proposer and the acceptor (see
+    /// Acceptor::preload_log).
+    pub fn preload_log(&mut self, values: Vec<(usize, T)>) {
+        self.proposer.preload_log(values.clone());
+        self.acceptor.preload_log(values);
+    }
+
+    /// Returns the hosted proposer.
+    pub fn proposer(&self) -> &Proposer<T> {
+        &self.proposer
+    }
+
+    /// Returns the hosted acceptor.
+    pub fn acceptor(&self) -> &Acceptor<T> {
+        &self.acceptor
+    }
+
+    /// Handles the message m, which was received by this node, by dispatching it to either the
+    /// proposer or the acceptor, depending on its type (or to both, for a Truncation message, which
+    /// concerns both roles). Each message is handled only once by each role: in particular, the
+    /// messages which the hosted proposer sends to the acceptors are not forwarded internally to
+    /// the hosted acceptor, which handles them once they are received (like the other acceptors
+    /// do).
+    pub fn handle(&mut self, m: Message<T>) {
+        match m {
+            Message::Phase1a(_)
+            | Message::Phase2a(_)
+            | Message::ValueRequest(_)
+            | Message::TailPreparation(_) => self.acceptor.handle(m),
+            Message::Truncation(_) => {
+                self.acceptor.handle(m.clone());
+                self.proposer.handle(m);
+            }
+            _ => self.proposer.handle(m),
+        }
+    }
+
+    /// Makes the proposer and the acceptor use (a copy of) the reconfigured node of this node.
+    fn share_node(&mut self) {
+        self.proposer.node = self.node.share();
+        self.acceptor.node = self.node.share();
+    }
+}
+
+impl<T> CoLocated<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    /// Answers the commands received on the control socket of this node, if any.
+    fn poll_admin(&mut self) {
+        if let Some(admin) = self.admin.take() {
+            admin.poll(|command| self.answer(command));
+            self.admin = Some(admin);
+        }
+    }
+
+    /// Answers the given command of an operator (see with_admin_address), with the answers of both
+    /// the proposer and the acceptor.
+    fn answer(&mut self, command: Command) -> Answer {
+        match command {
+            Command::Role => Ok(format!(
+                "role=colocated id={} proposer_uuid={} acceptor_uuid={} group={}",
+                self.proposer.id, self.proposer.uuid, self.acceptor.uuid, self.proposer.group_id
+            )),
+            Command::Instance(instance) => Ok(format!(
+                "instance={} {} {}",
+                instance,
+                self.proposer.describe_instance(instance),
+                self.acceptor.describe_instance(instance)
+            )),
+            Command::ForceNoop(_) | Command::Log { .. } | Command::Drain | Command::StepDown => {
+                self.proposer.answer(command)
+            }
+            Command::Status | Command::Metrics => Ok(format!(
+                "{} {}",
+                self.proposer.answer(command)?,
+                self.acceptor.answer(command)?
+            )),
+        }
+    }
+}
+
+impl<T> EventLoop<T> for CoLocated<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    fn net_node(&mut self) -> &mut NetNode<T> {
+        &mut self.node
+    }
+
+    fn read_timeout(&self) -> Option<Duration> {
+        // Without a gossip interval, nor unacknowledged Learning messages (nor a control socket),
+        // this node waits until a message is received.
+        admin::receive_timeout(&self.admin, self.proposer.receive_timeout())
+    }
+
+    fn log_waiting(&self) {
+        if log_enabled!(Level::Info) {
+            info!(
+                "[P={:?}] Co-located proposer and acceptor waiting...",
+                self.proposer.id
+            );
+        }
+    }
+
+    fn handle_message(&mut self, m: Message<T>) {
+        self.handle(m);
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.proposer.is_stopped()
+    }
+
+    fn on_idle(&mut self) {
+        self.proposer.check_gossip();
+        self.proposer.check_learnings();
+        self.proposer.check_deferred_rounds();
+        self.proposer.serve_pending_requests();
+        self.poll_admin();
+    }
+}
+
+impl<T> Runnable for CoLocated<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    fn run(&mut self) {
+        self.proposer.catch_up();
+        self.proposer.establish_epoch_if_configured();
+
+        self.serve(|_| {});
+        self.proposer.report_drain();
+    }
+}
+
+/// How a learner knows the learned values.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LearningMode {
+    // The proposers send the learned values to the learners (using Learning messages), as soon as
+    // they are decided.
+    Push,
+
+    // The learner polls the proposers, at the given interval, for the values learned in the
+    // instances beyond its delivery watermark, and it ignores the Learning messages. This trades
+    // latency for reliability, e.g. if the Learning messages are often dropped.
+    Pull(Duration),
+
+    // Both of the above.
+    Both(Duration),
+}
+
+impl LearningMode {
+    /// Returns the interval at which the proposers are polled, if they are.
+    fn poll_interval(self) -> Option<Duration> {
+        match self {
+            LearningMode::Push => None,
+            LearningMode::Pull(poll_interval) | LearningMode::Both(poll_interval) => {
+                Some(poll_interval)
+            }
+        }
+    }
+}
+
+/// In which order a learner delivers (i.e. prints) the learned values.
+#[derive(Debug, Copy, Clone)]
+pub enum DeliveryPolicy<T> {
+    // In total order, i.e. in the order of their instances: a missing value (i.e. a gap) blocks
+    // the delivery of the values of the later instances.
+    TotalOrder,
+
+    // As soon as they are learned, so possibly out of order (but each one once), together with
+    // their instance. This trades the order for latency.
+    AsArrived,
+
+    // In the order of their instances, among the values with the same key, which is extracted by
+    // the given function, whereas the values with different keys are delivered independently.
+    // Note that the key of a missing value is not known, so the values are only ordered with
+    // respect to the (earlier) values with the same key already learned: a missing value which is
+    // learned after some later values with its key were delivered is delivered late (and a
+    // warning is logged). Use TotalOrder if that is not acceptable.
+    PerKey(fn(&T) -> u64),
+}
+
+/// A consumer of the values delivered by a learner (e.g. a database projection, an audit log or a
+/// cache), which is registered with Learner::with_sink. Each sink consumes the values of the
+/// gap-free prefix of the log, in total order, i.e. in the order of their instances, whatever the
+/// delivery policy of the learner is, so all the sinks of a learner see the same sequence of values
+/// (except for the values skipped by a failing sink, see SinkFailurePolicy).
+pub trait DeliverySink<T> {
+    /// Consumes value, the decided value of instance, or returns why it could not.
+    fn consume(&mut self, instance: usize, value: &T) -> io::Result<()>;
+}
+
+impl<T, F> DeliverySink<T> for F
+where
+    F: FnMut(usize, &T) -> io::Result<()>,
+{
+    fn consume(&mut self, instance: usize, value: &T) -> io::Result<()> {
+        self(instance, value)
+    }
+}
+
+/// What a learner does when one of its sinks (see DeliverySink) fails to consume a value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SinkFailurePolicy {
+    // The delivery stops at the value, for all the sinks (and the other consumers of the learner),
+    // and the value is offered again to the sink, periodically, until it consumes it. The sinks
+    // which already consumed the value do not consume it again.
+    Block,
+
+    // The sink misses the value (and a warning is logged), and the delivery goes on.
+    Skip,
+}
+
+/// A sink registered with a learner (see Learner::with_sink).
+struct Sink<T> {
+    sink: Box<dyn DeliverySink<T> + Send>,
+
+    on_failure: SinkFailurePolicy,
+
+    // The next instance whose value the sink must consume. The values of the previous instances
+    // were consumed (or skipped), e.g. before another sink blocked the delivery.
+    next_instance: usize,
+}
+
+/// The struct representing the learner in the Paxos algorithm.
+pub struct Learner<T> {
+    uuid: Uuid,
+
+    id: usize,
+
+    // The Paxos group (i.e. the replicated log) this node participates in. Messages belonging to
+    // other groups are ignored, so that several groups can share the same multicast addresses.
+    group_id: usize,
+
+    // A map between instance numbers (or ids) and the learned value during that instance.
+    learned_values: HashMap<usize, T>,
+
+    // The number of learned values printed to the standard output so far. This is used to print
+    // the learned values in total order, that is, according to the increasing number of the
+    // corresponding Paxos instance.
+    num_of_instances: usize,
+
+    // The gap-free prefix of the log delivered so far, in total order. It grows together with
+    // self.num_of_instances, so it always ends right before the delivery watermark. After a
+    // restart (see with_delivery_watermark), it starts right after the recovered watermark, and,
+    // after the instances before the start of a tail are skipped (see tail_from), it goes on right
+    // after them.
+    delivered: Vec<(usize, T)>,
+
+    // If set (see deliveries), the values of the gap-free prefix of the log are also sent here, in
+    // total order, as they become deliverable.
+    deliveries: Option<SyncSender<(usize, T)>>,
+
+    // If set (see ReplicatedStateMachine), the values of the gap-free prefix of the log are also
+    // queued here, in total order, as they become deliverable, until they are applied. The values
+    // of a learner which feeds a state machine are not printed.
+    to_apply: Option<VecDeque<(usize, T)>>,
+
+    // If set, the delivered values are batches, whose commands are printed one after the other
+    // (see with_batching), instead of the values themselves.
+    print_commands: Option<fn(&T)>,
+
+    // The sinks which consume the values of the gap-free prefix of the log, in total order, in the
+    // order they were registered (see with_sink).
+    sinks: Vec<Sink<T>>,
+
+    // In which order the learned values are delivered. Unless it is DeliveryPolicy::TotalOrder,
+    // the values of the instances from self.num_of_instances on can be delivered before the gap at
+    // the delivery watermark is filled: these instances are kept in self.delivered_ahead, so that
+    // they are not delivered again, and, for DeliveryPolicy::PerKey, the highest instance
+    // delivered for each key is kept in self.highest_delivered_per_key.
+    policy: DeliveryPolicy<T>,
+    delivered_ahead: HashSet<usize>,
+    highest_delivered_per_key: HashMap<u64, usize>,
+
+    // If set, at most (twice) this many delivered values are kept in memory (see
+    // with_max_retained_values). The learned values of all instances below self.pruned_below have
+    // been dropped, so the ones received again (e.g. in a Report) are ignored.
+    max_retained_values: Option<usize>,
+    pruned_below: usize,
+
+    // If set, the value which marks a checkpoint of the application in the log: once this learner
+    // delivers it, it drops the values of the previous instances (see with_checkpoint).
+    checkpoint: Option<T>,
+
+    // If set, the delivery watermark is durably stored here, before each value is delivered.
+    watermark: Option<DeliveryWatermark>,
+
+    // The highest instance whose learned value is known by this learner. If it is greater than or
+    // equal to self.num_of_instances, there is a gap at the delivery watermark.
+    highest_learned: usize,
+
+    // If set, a gap which persists for longer than this duration is actively recovered (see
+    // check_gap). Otherwise, this learner waits for the missing learned value indefinitely.
+    gap_timeout: Option<Duration>,
+
+    // When the current gap at the delivery watermark was detected, if there is one.
+    gap_detected_at: Option<Instant>,
+
+    // The instance at the delivery watermark, once its gap has persisted for longer than the gap
+    // timeout, until its learned value is delivered. If it is still stuck a gap timeout later,
+    // i.e. after the proposers (and the acceptors) were all given the time to report its value,
+    // it was apparently never decided.
+    stuck_instance: Option<usize>,
+
+    // Whether this learner uses the Learning messages, or polls the proposers, or both, and when
+    // it last polled them.
+    mode: LearningMode,
+    last_poll: Instant,
+
+    // The address, if any, where the proposers must send the replies addressed to this learner,
+    // instead of the address of the learners (see with_advertised_address).
+    advertised_address: Option<SocketAddrV4>,
+
+    // If set, the accepted values of a stuck instance are also requested directly from the
+    // acceptors, which can be contacted at this address (see with_acceptors).
+    acceptors_address: Option<SocketAddrV4>,
+
+    majority_of_acceptors: usize,
+
+    // Whether the Acceptance messages, which the acceptors also send to the learners (see
+    // Acceptor::with_learners_address), are counted as the votes of the acceptors (see
+    // with_direct_learning).
+    direct_learning: bool,
+
+    // Whether this learner sends a LearnAck message to the proposers for each value it delivers
+    // (see with_learn_acks).
+    learn_acks: bool,
+
+    // If set, this learner reports its progress to the proposers, at this interval (see
+    // with_progress_reports), and it last reported it at self.last_progress.
+    progress_interval: Option<Duration>,
+    last_progress: Instant,
+
+    // A map between the instances whose accepted values were requested from (or sent by) the
+    // acceptors and the votes received so far, i.e. the (v_rnd, v_val) reported by each acceptor.
+    value_replies: HashMap<usize, HashMap<Uuid, (Ballot, Option<T>)>>,
+
+    // If set, the control socket whose commands this learner answers (see with_admin_address).
+    admin: Option<AdminListener>,
+
+    node: NetNode<T>,
+
+    learners_address: SocketAddrV4,
+
+    // A learner needs to contact the proposers to ask them about previously executed basic Paxos
+    // instances, in order to deliver the related learned values, before the future Paxos
+    // instances that are eventually executed.
+    proposers_address: SocketAddrV4,
+}
+
+impl<T> Learner<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    /// Creates a learner. If gap_timeout is set, a gap at the delivery watermark which persists for
+    /// longer than it is actively recovered (see with_gap_timeout), otherwise this learner waits
+    /// for the missing value indefinitely.
+    pub fn new(
+        id: usize,
+        learners_address: SocketAddrV4,
+        proposers_address: SocketAddrV4,
+        mode: LearningMode,
+        policy: DeliveryPolicy<T>,
+        gap_timeout: Option<Duration>,
+    ) -> Self {
+        let mut learner = Learner::with_node(
+            NetNode::new(&learners_address),
+            id,
+            learners_address,
+            proposers_address,
+            mode,
+            policy,
+        );
+        learner.gap_timeout = gap_timeout;
+        learner
+    }
+
+    /// Creates a learner which sends and receives its messages using node.
+    pub(crate) fn with_node(
+        node: NetNode<T>,
+        id: usize,
+        learners_address: SocketAddrV4,
+        proposers_address: SocketAddrV4,
+        mode: LearningMode,
+        policy: DeliveryPolicy<T>,
+    ) -> Self {
+        #[cfg(feature = "metrics")]
+        let node = node.with_role("learner");
+
+        Learner {
+            uuid: Uuid::new_v4(),
+            id,
+            group_id: 0,
+            learned_values: HashMap::new(),
+            num_of_instances: 1,
+            delivered: Vec::new(),
+            deliveries: None,
+            to_apply: None,
+            print_commands: None,
+            sinks: Vec::new(),
+            policy,
+            delivered_ahead: HashSet::new(),
+            highest_delivered_per_key: HashMap::new(),
+            max_retained_values: None,
+            pruned_below: 1,
+            checkpoint: None,
+            watermark: None,
+            highest_learned: 0,
+            gap_timeout: None,
+            gap_detected_at: None,
+            stuck_instance: None,
+            mode,
+            last_poll: Instant::now(),
+            advertised_address: None,
+            acceptors_address: None,
+            majority_of_acceptors: 0,
+            direct_learning: false,
+            learn_acks: false,
+            progress_interval: None,
+            last_progress: Instant::now(),
+            value_replies: HashMap::new(),
+            admin: None,
+            node,
+            learners_address,
+            proposers_address,
+        }
+    }
+
+    /// Replaces the (randomly generated) unique identifier of this learner with uuid.
+    pub fn with_uuid(mut self, uuid: Uuid) -> Self {
+        self.uuid = uuid;
+        self
+    }
+
+    /// Makes this learner participate in the Paxos group group_id (by default, 0).
+    pub fn with_group_id(mut self, group_id: usize) -> Self {
+        self.group_id = group_id;
+        self
+    }
+
+    /// Writes every message sent or received by this learner to message_log.
+    pub fn with_message_log(mut self, message_log: MessageLog) -> Self {
+        self.node.set_message_log(message_log);
+        self
+    }
+
+    /// Makes this learner send its messages from the local address sender_address (e.g. to choose
+    /// the network interface which the multicast messages leave through).
+    pub fn with_sender_address(mut self, sender_address: Ipv4Addr) -> Self {
+        self.node.set_sender_address(sender_address);
+        self
+    }
+
+    /// Limits the rate of the messages sent by this learner to rate messages per second, with
+    /// bursts of at most burst messages (see Proposer::with_max_send_rate).
+    pub fn with_max_send_rate(mut self, rate: u32, burst: usize) -> Self {
+        self.node.set_max_send_rate(rate, burst);
+        self
+    }
+
+    /// Makes this learner send the messages larger than max_datagram_size bytes in fragments (see
+    /// Proposer::with_max_datagram_size).
+    pub fn with_max_datagram_size(mut self, max_datagram_size: usize) -> Self {
+        self.node.set_max_datagram_size(max_datagram_size);
+        self
+    }
+
+    /// Makes this learner refuse to send the messages which do not fit in a datagram, if fragmentation
+    /// is false, instead of fragmenting them (see Proposer::with_fragmentation).
+    pub fn with_fragmentation(mut self, fragmentation: bool) -> Self {
+        self.node.set_fragmentation(fragmentation);
+        self
+    }
+
+    /// Makes this learner belong to the cluster named cluster_name (by default, the empty name), so
+    /// that it ignores the messages of the nodes of other clusters (see NetNode::set_cluster_id).
+    pub fn with_cluster_id(mut self, cluster_name: &str) -> Self {
+        self.node.set_cluster_id(cluster_name);
+        self
+    }
+
+    /// Makes this learner encrypt its messages with a key derived from secret, which must be shared by
+    /// all the nodes of the cluster, and drop the ones which it cannot decrypt (see
+    /// NetNode::set_encryption_key).
+    #[cfg(feature = "encryption")]
+    pub fn with_encryption_key(mut self, secret: &str) -> Self {
+        self.node.set_encryption_key(secret);
+        self
+    }
+
+    /// Makes a separate thread receive the messages of this learner, as soon as they arrive, and
+    /// queue them, up to capacity messages, until this learner handles them, so that a slow handler
+    /// does not make the socket drop the messages which arrive in the meantime.
+    pub fn with_receive_queue(mut self, capacity: usize) -> Self {
+        self.node.set_receive_queue(capacity);
+        self
+    }
+
+    /// Makes this learner recover from a gap at its delivery watermark (i.e. an instance whose
+    /// learned value is missing, while the ones of later instances are known) which persists for
+    /// longer than gap_timeout, like the parameter gap_timeout of new (e.g. for the learners of a
+    /// testkit::Cluster). This learner then catches up and, if none of the proposers (nor the
+    /// acceptors, see with_acceptors) reported the value within another gap timeout, i.e. if the
+    /// instance was apparently never decided, asks them to propose a no-op (see
+    /// Proposer::with_noop).
+    pub fn with_gap_timeout(mut self, gap_timeout: Duration) -> Self {
+        self.gap_timeout = Some(gap_timeout);
+        self
+    }
+
+    /// Makes the proposers send the Report messages addressed to this learner to
+    /// advertised_address, which is the externally visible address of the socket this learner
+    /// binds to (e.g. if it runs in a container or behind a NAT).
+    pub fn with_advertised_address(mut self, advertised_address: SocketAddrV4) -> Self {
+        self.advertised_address = Some(advertised_address);
+        self
+    }
+
+    /// Makes this learner also receive its messages at unicast_address, where a second socket binds
+    /// to, and, unless it has another advertised address or the IP of unicast_address is
+    /// unspecified, makes the proposers send the replies addressed to this learner there (see
+    /// Proposer::with_unicast_address).
+    pub fn with_unicast_address(mut self, unicast_address: SocketAddrV4) -> Self {
+        self.node.set_unicast_address(unicast_address);
+
+        if !unicast_address.ip().is_unspecified() {
+            self.advertised_address.get_or_insert(unicast_address);
+        }
+
+        self
+    }
+
+    /// Makes this learner answer the commands of the operators (e.g. status), sent to a control
+    /// socket which binds to admin_address (see Proposer::with_admin_address).
+    pub fn with_admin_address(mut self, admin_address: SocketAddrV4) -> io::Result<Self> {
+        self.admin = Some(AdminListener::bind(admin_address)?);
+        Ok(self)
+    }
+
+    /// Makes this learner durably store its delivery watermark (i.e. the last instance whose learned
+    /// value it delivered) in the file at path. If the file already contains the watermark of a
+    /// previous execution of this learner, the delivery resumes strictly after it, so that no value
+    /// is delivered twice. Note that the watermark is stored before the value is delivered, so a
+    /// crash in between loses that value, rather than delivering it twice.
+    pub fn with_delivery_watermark<P: AsRef<Path>>(mut self, path: P) -> io::Result<Self> {
+        let watermark = DeliveryWatermark::open(path)?;
+
+        self.num_of_instances = watermark.last_delivered() + 1;
+        self.pruned_below = self.num_of_instances;
+        if watermark.last_delivered() > self.highest_learned {
+            self.highest_learned = watermark.last_delivered();
+        }
+
+        self.watermark = Some(watermark);
+
+        Ok(self)
+    }
+
+    /// Bounds the memory used by this learner, which keeps only the max_retained_values most
+    /// recently delivered values: once twice as many are kept, the oldest ones are dropped (both
+    /// from learned_value and total_order_prefix). The dropped values are never delivered again,
+    /// as they are below the delivery watermark, which, if this learner has one (see
+    /// with_delivery_watermark), also survives a restart.
+    pub fn with_max_retained_values(mut self, max_retained_values: usize) -> Self {
+        self.max_retained_values = Some(max_retained_values);
+        self
+    }
+
+    /// Makes this learner drop the values of the instances before each checkpoint (see
+    /// Proposer::with_checkpoint) which it delivers, both from learned_value and
+    /// total_order_prefix. The application must snapshot its state when it consumes the checkpoint
+    /// (e.g. in a sink, see with_sink), durably, before it returns, as this learner then
+    /// acknowledges the delivery (see with_learn_acks), which lets the proposers truncate their logs
+    /// below it.
+    pub fn with_checkpoint(mut self, checkpoint: T) -> Self {
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+
+    /// Makes this learner also ask the num_of_acceptors acceptors, at acceptors_address, for the
+    /// values they accepted in a stuck instance (see with_gap_timeout). The value accepted by a
+    /// majority of them in the same round is the chosen one, so it can be learned even if the
+    /// proposers do not know it (anymore).
+    ///
+    /// Panics if num_of_acceptors is zero.
+    pub fn with_acceptors(
+        mut self,
+        acceptors_address: SocketAddrV4,
+        num_of_acceptors: usize,
+    ) -> Self {
+        assert!(num_of_acceptors > 0, "Expected at least 1 acceptor");
+        self.acceptors_address = Some(acceptors_address);
+        self.majority_of_acceptors = num_of_acceptors / 2 + 1;
+        self
+    }
+
+    /// Makes this learner learn the values chosen by the acceptors given with with_acceptors
+    /// directly from their Acceptance messages, once a majority of them voted for the same value in
+    /// the same round, as well as from the Learning messages of the proposers (if they are not
+    /// ignored, see LearningMode). The acceptors must send their Acceptance messages to the learners
+    /// too (see Acceptor::with_learners_address). This saves the delay of a message, and a value is
+    /// still learned if the proposer which had it chosen fails before sending its Learning message.
+    pub fn with_direct_learning(mut self) -> Self {
+        self.direct_learning = true;
+        self
+    }
+
+    /// Makes this learner acknowledge each value it delivers with a LearnAck message to the
+    /// proposers, which send the Learning messages again until enough learners acknowledged them
+    /// (see Proposer::with_learn_acks). A Learning message of a value which was already delivered
+    /// is acknowledged again, as its first acknowledgment may have been lost.
+    pub fn with_learn_acks(mut self) -> Self {
+        self.learn_acks = true;
+        self
+    }
+
+    /// Makes this learner report its progress, i.e. the next instance it must deliver, to the
+    /// proposers, every interval, with a Progress message, so that the proposers with backpressure
+    /// stop starting new instances while it is too far behind (see Proposer::with_backpressure).
+    pub fn with_progress_reports(mut self, interval: Duration) -> Self {
+        self.progress_interval = Some(interval);
+        self
+    }
+
+    /// Reports the progress of this learner to the proposers right away (see
+    /// with_progress_reports). The run loop calls this every progress interval.
+    pub fn report_progress(&mut self) {
+        let m = Message::Progress::<T>(Progress {
+            next_to_deliver: InstanceId(self.num_of_instances),
+            sender_uuid: self.uuid,
+            group_id: self.group_id,
+        });
+
+        if logs_messages() {
+            info!("[L={:?}] I will send {:?}.", self.id, Logged(&m));
+        }
+
+        self.node.send(m, &self.proposers_address);
+        self.last_progress = Instant::now();
+    }
+
+    /// Makes this learner deliver the learned values from the instance start on, and skip the ones
+    /// of the previous instances, e.g. for a consumer which durably stores its own offset, and only
+    /// needs the tail of the log. The proposers are asked right away for the learned values from
+    /// start on (see catch_up), so, if start is far behind the log, its values are delivered in
+    /// bulk, as soon as their Report arrives, and then the values learned afterwards, as usual. If
+    /// start is beyond the log, this learner waits for its instance to be decided, ignoring the
+    /// values of the previous ones. If start is not beyond the delivery watermark, the values which
+    /// were already delivered are not delivered again, so this learner only catches up.
+    ///
+    /// The skipped instances count as delivered: the delivery watermark, if any (see
+    /// with_delivery_watermark), is moved right before start.
+    pub fn tail_from(&mut self, start: usize) {
+        self.skip_to(start);
+        self.catch_up();
+    }
+
+    /// Skips the instances from the delivery watermark up to start (excluded), if start is beyond
+    /// it, as if their values had been delivered, and delivers the known values from start on.
+    fn skip_to(&mut self, start: usize) {
+        if start > self.num_of_instances {
+            if log_enabled!(Level::Info) {
+                info!(
+                    "[L={:?}] I will skip the instances {:?} to {:?}.",
+                    self.id,
+                    self.num_of_instances,
+                    start - 1
+                );
+            }
+
+            if let Some(ref mut watermark) = self.watermark {
+                watermark
+                    .skip_to(start - 1)
+                    .expect("Could not store the delivery watermark");
+            }
+
+            self.learned_values.retain(|&instance, _| instance >= start);
+            self.delivered_ahead.retain(|&instance| instance >= start);
+            self.value_replies.retain(|&instance, _| instance >= start);
 
-    // Senders
+            self.num_of_instances = start;
+            self.pruned_below = start;
+            self.see_instance(start - 1);
 
-    /// A newly instantiated proposer can "catch up" the current state of the other proposers by
-    /// sending to them a CatchUp message.
-    fn catch_up(&self) {
-        let m = Message::Phase0b(CatchUp {
-            sender_uuid: self.uuid,
-            sender_type: 'p',
-        });
+            // A gap before start is not a gap anymore.
+            self.stuck_instance = None;
+            self.gap_detected_at = None;
 
-        if log_enabled!(Level::Info) {
-            info!("[P={:?}] I will send {:?}.", self.id, m);
+            self.print_learned_values();
         }
+    }
 
-        self.node.send(m, &self.proposers_address);
+    /// Returns the contiguous (gap-free) sequence of delivered values, together with their
+    /// instance numbers, in total order. These are exactly the values which have been printed so
+    /// far (since the last restart, if this learner has a delivery watermark), so they can be
+    /// safely applied to a deterministic state machine. With a delivery policy other than
+    /// DeliveryPolicy::TotalOrder, these are instead the delivered values in delivery order.
+    pub fn total_order_prefix(&self) -> &[(usize, T)] {
+        &self.delivered
     }
 
-    /// Sends a Report message to the learners which requested it using a CatchUp message.
-    fn report(&self, sender_uid: Uuid, sender_type: char) {
-        let m = Message::Phase0c::<T>(Report {
-            num_of_instances: self.num_of_instances,
-            learned_values: self.learned_values.clone(),
-            sender_uuid: self.uuid,
-            receiver_uuid: sender_uid,
+    /// Registers sink, which consumes the values of the gap-free prefix of the log, in total order,
+    /// after the sinks registered before it, and before the values are printed (and sent to the
+    /// other consumers of this learner, see deliveries). If sink fails to consume a value,
+    /// on_failure tells whether the delivery stops until it does, or whether it misses the value.
+    /// sink is Send, like this learner, which can thus run on its own thread.
+    pub fn with_sink<S>(mut self, sink: S, on_failure: SinkFailurePolicy) -> Self
+    where
+        S: DeliverySink<T> + Send + 'static,
+    {
+        self.sinks.push(Sink {
+            sink: Box::new(sink),
+            on_failure,
+            next_instance: 0,
         });
+        self
+    }
 
-        if log_enabled!(Level::Info) {
-            info!("[P={:?}] I will send {:?}.", self.id, m);
+    /// Offers again the value at which the delivery is blocked to the sink which failed to consume
+    /// it (see SinkFailurePolicy::Block), if any, and resumes the delivery if it consumes it now.
+    /// The run loop calls this periodically.
+    pub fn retry_sinks(&mut self) {
+        if self.is_blocked_by_sink() {
+            self.print_learned_values();
         }
+    }
 
-        let destination_address = if sender_type == 'l' {
-            self.learners_address
-        } else {
-            self.proposers_address
-        };
+    /// Returns a receiver, whose (blocking) iterator yields the learned values of this learner,
+    /// together with their instance numbers, in total order, as they become deliverable, i.e. it
+    /// never yields a value past a gap, whatever the delivery policy is. It is meant to be consumed
+    /// by another thread, while this learner runs (see run).
+    ///
+    /// The values are sent over a channel which holds at most capacity of them: if the consumer
+    /// is slower than this learner, the receive loop of this learner blocks until the consumer
+    /// catches up. If the receiver is dropped, this learner stops sending the values.
+    pub fn deliveries(&mut self, capacity: usize) -> Receiver<(usize, T)> {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        self.deliveries = Some(sender);
+        receiver
+    }
 
-        self.node.send(m, &destination_address);
+    /// Runs this learner (see run), and calls after_each after each message it handles, or each
+    /// timeout, e.g. so that its owner can act on the values it just delivered.
+    pub(crate) fn run_with<F: FnMut(&mut Self)>(&mut self, after_each: F) {
+        self.catch_up();
+        self.serve(after_each);
     }
 
-    /// Updates its internal, after having received a request by a client with a value, and sends a
-    /// Preparation message to all acceptors.
-    fn prepare(&mut self, value: T) {
-        // Every time this function is called, a new instance of the basic Paxos algorithm is
-        // (implicitly) started.
-        self.num_of_instances += 1;
+    /// Makes this learner queue the values of the gap-free prefix of the log, in total order, for
+    /// a state machine (see take_to_apply), instead of printing them.
+    pub(crate) fn feed_state_machine(&mut self) {
+        self.to_apply = Some(VecDeque::new());
+    }
 
-        // Get the ProposerState associated with the last or new instance of the basic Paxos
-        // algorithm, which will be executed next.
-        let state = self
-            .proposer_states
-            .entry(self.num_of_instances)
-            .or_default();
+    /// Returns the values queued for a state machine (see feed_state_machine) since the last call.
+    pub(crate) fn take_to_apply(&mut self) -> VecDeque<(usize, T)> {
+        self.to_apply.as_mut().map(mem::take).unwrap_or_default()
+    }
 
-        state.value = Some(value);
+    /// Returns the value learned during the given instance, if this learner knows it yet (and did
+    /// not drop it, see with_max_retained_values).
+    pub fn learned_value(&self, instance: usize) -> Option<T> {
+        self.learned_values.get(&instance).cloned()
+    }
 
-        // TODO: if self.id is not unique among all processes for an instance of Paxos, the
-        // TODO: algorithm may not work properly. So, it should not rely on a unique
-        // TODO: generation/increment of c_rnd based on self.id
-        //
-        // TODO: note that so far, prepare is called only once for each proposer for the same
-        // TODO: instance. Therefore, (state.c_rnd + 1) * self.id should be unique, provided id is
-        // TODO: also unique among the proposers (at least).
-        state.c_rnd = (state.c_rnd + 1) * self.id;
+    /// Returns the decided log known to this learner, i.e. its learned values, including the ones
+    /// not delivered yet, keyed by their instances, in increasing order of instance (see
+    /// Proposer::export_log). It only reflects what this learner knows, and it lacks the values
+    /// which it dropped (see with_max_retained_values), so it is best exported from a learner
+    /// which keeps every value and has no gap (see total_order_prefix).
+    pub fn export_log(&self) -> BTreeMap<usize, T> {
+        self.learned_values
+            .iter()
+            .map(|(&instance, &v)| (instance, v))
+            .collect()
+    }
 
-        let m = Message::Phase1a::<T>(Preparation {
-            c_rnd: state.c_rnd,
-            sender_uuid: self.uuid,
-            instance: self.num_of_instances,
-        });
+    /// Returns the number of learned values currently kept in memory by this learner, including
+    /// the ones not delivered yet.
+    pub fn num_of_learned_values(&self) -> usize {
+        self.learned_values.len()
+    }
 
-        if log_enabled!(Level::Info) {
-            info!("[P={:?}] I will send {:?}.", self.id, m);
-        }
+    /// Returns the number of received datagrams which this learner dropped, because they were
+    /// corrupted (see NetNode::num_of_corrupted).
+    pub fn num_of_corrupted_datagrams(&self) -> usize {
+        self.node.num_of_corrupted()
+    }
 
-        self.node.send(m, &self.acceptors_address);
+    /// Returns the number of received datagrams which this learner dropped, because they were sent
+    /// by the nodes of another cluster (see with_cluster_id).
+    pub fn num_of_foreign_datagrams(&self) -> usize {
+        self.node.num_of_foreign()
     }
 
-    /// Sends a Proposal message to the acceptors, if "enough" Promise messages have been received.
-    fn propose(&mut self, rnd: usize, v_rnd: usize, v_val: Option<T>, instance: usize) {
-        let state = self.proposer_states.entry(instance).or_default();
+    /// Returns the number of received datagrams which this learner dropped, because it could not
+    /// decrypt them (see with_encryption_key).
+    #[cfg(feature = "encryption")]
+    pub fn num_of_undecryptable_datagrams(&self) -> usize {
+        self.node.num_of_undecryptable()
+    }
 
-        state.rnd_received.push(rnd);
+    /// Returns an estimate, in bytes, of the memory used by the learned and delivered values
+    /// currently kept by this learner (excluding the overhead of their collections).
+    pub fn memory_footprint(&self) -> usize {
+        self.learned_values.len() * mem::size_of::<(usize, T)>()
+            + self.delivered.len() * mem::size_of::<(usize, T)>()
+    }
 
-        // We keep track of the highest v_rnd (and the associated v_val) received from any of the
-        // acceptors. See below the logic.
-        if v_rnd > state.highest_v_rnd_received {
-            state.highest_v_rnd_received = v_rnd;
-            state.associated_v_val_received = v_val;
-        }
+    /// Drops the oldest delivered values, if more than twice the maximum number of retained
+    /// values are kept (see with_max_retained_values), so that only the maximum number remains.
+    fn prune(&mut self) {
+        if let Some(max_retained_values) = self.max_retained_values {
+            let num_of_retained = self.num_of_instances - self.pruned_below;
 
-        if state.rnd_received.len() < self.majority_of_acceptors {
-            return;
+            if num_of_retained > 2 * max_retained_values {
+                let pruned_below = self.num_of_instances - max_retained_values;
+
+                for instance in self.pruned_below..pruned_below {
+                    self.learned_values.remove(&instance);
+                }
+
+                let num_of_pruned = self.delivered.len().saturating_sub(max_retained_values);
+                self.delivered.drain(..num_of_pruned);
+
+                if log_enabled!(Level::Info) {
+                    info!(
+                        "[L={:?}] Dropped the values of instances {:?} to {:?}.",
+                        self.id,
+                        self.pruned_below,
+                        pruned_below - 1
+                    );
+                }
+
+                self.pruned_below = pruned_below;
+            }
         }
+    }
 
+    /// Drops the values of the instances before instance, whose checkpoint (see with_checkpoint) was
+    /// just delivered.
+    fn drop_before_checkpoint(&mut self, instance: usize) {
         if log_enabled!(Level::Info) {
-            info!("[P={:?}] Majority of rnd received.", self.id);
+            info!(
+                "[L={:?}] I delivered the checkpoint of instance {:?}: I will drop the values before it.",
+                self.id, instance
+            );
         }
 
-        // Furthermore, to proceed, the proposer must make sure that all rnd received are equal to
-        // the c_rnd associated with the current instance of the basic Paxos algorithm.
-        if state.rnd_received.iter().all(|&n| n == state.c_rnd) {
-            if log_enabled!(Level::Info) {
-                info!("[P={:?}] All rnd received are equal to my c_rnd.", self.id);
+        self.learned_values.retain(|&i, _| i >= instance);
+        self.delivered.retain(|&(i, _)| i >= instance);
+        self.pruned_below = self.pruned_below.max(instance);
+    }
+
+    /// Tries to print the learned values that can be already printed, that is, the ones received in
+    /// total order and, unless the delivery policy is DeliveryPolicy::TotalOrder, the ones which
+    /// it allows to be printed before the gap at the delivery watermark (if any) is filled.
+    fn print_learned_values(&mut self) {
+        while let Some(&v) = self.learned_values.get(&self.num_of_instances) {
+            if !self.feed_sinks(self.num_of_instances, &v) {
+                break;
             }
 
-            // It means that no acceptor has previously participated in any round of the current
-            // instance of the basic Paxos algorithm.
-            if state.highest_v_rnd_received == 0 {
-                // In that case, we use the value sent by the client in its request.
-                state.c_val = state.value;
-            } else {
-                // Otherwise we use the value associated with the highest v_rnd received so far from
-                // any of the acceptors.
-                state.c_val = state.associated_v_val_received;
+            if let Some(ref mut watermark) = self.watermark {
+                watermark
+                    .advance(self.num_of_instances)
+                    .expect("Could not store the delivery watermark");
             }
 
-            let m = Message::Phase2a::<T>(Proposal {
-                c_rnd: state.c_rnd,
-                c_val: state.c_val,
-                sender_uuid: self.uuid,
-                instance,
-            });
+            if !self.delivered_ahead.remove(&self.num_of_instances) {
+                self.deliver_value(self.num_of_instances, v);
+            }
 
-            if log_enabled!(Level::Info) {
-                info!("[P={:?}] I will send {:?}.", self.id, m);
+            if let Some(ref sender) = self.deliveries {
+                if sender.send((self.num_of_instances, v)).is_err() {
+                    if log_enabled!(Level::Info) {
+                        info!(
+                            "[L={:?}] The receiver of the deliveries was dropped.",
+                            self.id
+                        );
+                    }
+                    self.deliveries = None;
+                }
             }
 
-            self.node.send(m, &self.acceptors_address);
-        }
+            if let Some(ref mut to_apply) = self.to_apply {
+                to_apply.push_back((self.num_of_instances, v));
+            }
 
-        // TODO: verify that the following program logic is correct.
-        //
-        // If the execution arrives here, it means that we have received rnd values from the
-        // majority of the acceptors. These rnd values received are NOT necessarily ALL equal to
-        // c_rnd.
-        //
-        // We should not clear this buffer at the end of the previous if block, because, suppose
-        // that, at some point, rnd_received contains rnd values which are NOT ALL equal to c_rnd,
-        // and we have NOT yet received rnd from the majority of the acceptors. In that case, none
-        // of the if blocks above will be executed. Suppose that this function is called again and,
-        // at that point, we have received rnd values from the majority of the acceptors. Even
-        // though this may be the case, the previous if block will never be executed, because, even
-        // though, at that point, we will have received rnd values from the majority of the
-        // acceptors, they will NOT ALL be equal to c_rnd. After that point, we will possibly keep
-        // receiving more rnd values from acceptors, but that will not change anything, because, if
-        // we had "state.rnd_received.clear()" at the end of that if block, we would never clear
-        // rnd_received, and thus rnd_received would never contain all rnd values equal c_rnd, and
-        // so this proposer would never send back an answer to the acceptors (if this proposer is
-        // called to handle a "promise" message). Note that rnd_received is only modified in this
-        // function so far.
-        //
-        // If we clear this buffer here, we know that we have received rnd values from the majority
-        // of the acceptors, but EITHER they were all equal to c_rnd or (exclusive or) NOT. If they
-        // are all equal to c_rnd, then we have sent back an answer to the acceptors, otherwise we
-        // have not. By clearing the buffer here, we can process other "promise" messages from the
-        // acceptors. But, unless we need to send a new Preparation message to the acceptors, this
-        // is not necessary. Right now, this implementation still doesn't support the re-sending of
-        // Preparation messages in case a Nack is received.
-        // state.rnd_received.clear();
-    }
+            self.value_replies.remove(&self.num_of_instances);
 
-    /// Sends a Learning message to the learners, if "enough" Acceptance messages have been received
-    /// from the acceptors.
-    fn decide(&mut self, v_rnd: usize, v_val: T, instance: usize) {
-        let state = self.proposer_states.entry(instance).or_default();
+            #[cfg(feature = "metrics")]
+            telemetry::value_delivered("learner", self.num_of_instances);
 
-        state.v_rnd_received.push(v_rnd);
+            if self.checkpoint == Some(v) {
+                self.drop_before_checkpoint(self.num_of_instances);
+            }
 
-        if state.v_rnd_received.len() < self.majority_of_acceptors {
-            return;
+            self.num_of_instances += 1;
         }
 
-        if log_enabled!(Level::Info) {
-            info!("[P={:?}] Majority of messages received.", self.id);
+        if let DeliveryPolicy::AsArrived | DeliveryPolicy::PerKey(_) = self.policy {
+            self.deliver_ahead();
         }
 
-        // We keep track of the learned values so as to be able to answer to the CatchUp
-        // messages sent by the learners. We need to store v_val here and not inside the next if
-        // statement, because the next if statement may not be executed. Anyway, at this point,
-        // v_val needs to be a value which learners need to know: it can or not be equal to
-        // state.c_rnd.
-        if let Some(v) = self.learned_values.insert(instance, v_val) {
-            assert_eq!(
-                v, v_val,
-                "Bug: previously known v_val is not equal to current one for the same instance"
-            );
+        self.prune();
+
+        if let Some(stuck_instance) = self.stuck_instance {
+            if stuck_instance < self.num_of_instances {
+                self.stuck_instance = None;
+            }
         }
 
-        if state.v_rnd_received.iter().all(|&n| n == state.c_rnd) {
+        // The delivery blocked by a sink is not a gap, as the value is known.
+        if self.highest_learned < self.num_of_instances || self.is_blocked_by_sink() {
+            self.gap_detected_at = None;
+        } else if self.gap_detected_at.is_none() {
             if log_enabled!(Level::Info) {
                 info!(
-                    "[P={:?}] All v_rnd received are equal to my c_rnd.",
-                    self.id
+                    "[L={:?}] Gap detected at instance {:?}.",
+                    self.id, self.num_of_instances
                 );
             }
+            self.gap_detected_at = Some(Instant::now());
+        }
+    }
 
-            assert_eq!(
-                v_val,
-                state.c_val.unwrap(),
-                "Bug: v_val should be equal to c_val to decide"
-            );
+    /// Offers v, the learned value of instance, to the sinks which did not consume it yet, in the
+    /// order they were registered, and returns false if one of them failed to consume it and blocks
+    /// the delivery (see SinkFailurePolicy::Block), in which case the following sinks are not
+    /// offered the value yet either.
+    fn feed_sinks(&mut self, instance: usize, v: &T) -> bool {
+        for (i, sink) in self.sinks.iter_mut().enumerate() {
+            if sink.next_instance > instance {
+                continue;
+            }
 
-            let m = Message::Phase3::<T>(Learning {
-                learned_value: v_val,
-                sender_uuid: self.uuid,
-                instance,
-            });
+            match sink.sink.consume(instance, v) {
+                Ok(()) => {}
+                Err(e) if sink.on_failure == SinkFailurePolicy::Block => {
+                    warn!(
+                        "[L={:?}] Sink {} could not consume {:?} of instance {:?} ({}): I will offer it again.",
+                        self.id, i, v, instance, e
+                    );
+                    return false;
+                }
+                Err(e) => {
+                    warn!(
+                        "[L={:?}] Sink {} could not consume {:?} of instance {:?} ({}): I will skip it.",
+                        self.id, i, v, instance, e
+                    );
+                }
+            }
 
-            if log_enabled!(Level::Info) {
-                info!("[P={:?}] I will send {:?}.", self.id, m);
+            sink.next_instance = instance + 1;
+        }
+
+        true
+    }
+
+    /// Returns true if the delivery is blocked by a sink which failed to consume the value at the
+    /// delivery watermark (see SinkFailurePolicy::Block).
+    fn is_blocked_by_sink(&self) -> bool {
+        !self.sinks.is_empty() && self.learned_values.contains_key(&self.num_of_instances)
+    }
+
+    /// Prints v, the learned value of instance.
+    fn deliver_value(&mut self, instance: usize, v: T) {
+        if let DeliveryPolicy::PerKey(key_of) = self.policy {
+            let highest_delivered = self
+                .highest_delivered_per_key
+                .entry(key_of(&v))
+                .or_insert(0);
+
+            if instance < *highest_delivered {
+                warn!(
+                    "[L={:?}] Delivering {:?} of instance {:?} after instance {:?} with its key.",
+                    self.id, v, instance, highest_delivered
+                );
+            } else {
+                *highest_delivered = instance;
             }
+        }
 
-            // We can send the message to the learners multiple times, because, once we have
-            // received the majority of the messages containing v_rnd (and all v_rnd == c_rnd), then
-            // all subsequent calls to this self.decide function will trigger this call too. Anyway,
-            // we just need the majority and thus to send this message once.
-            self.node.send(m, &self.learners_address);
+        if self.to_apply.is_none() {
+            match self.print_commands {
+                Some(print_commands) => print_commands(&v),
+                None => println!("{:?}", v),
+            }
         }
+        self.delivered.push((instance, v));
 
-        // TODO: verify that this statement should be here.
-        // state.v_rnd_received.clear();
+        if self.learn_acks {
+            self.acknowledge(InstanceId(instance));
+        }
     }
-}
 
-impl<T> Runnable for Proposer<T>
-where
-    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
-{
-    fn run(&mut self) {
-        self.catch_up();
+    /// Prints, in the order of their instances, the learned values beyond the delivery watermark
+    /// which were not printed yet. Note that, with the delivery watermark, the learned values
+    /// printed ahead of it are printed again after a restart.
+    fn deliver_ahead(&mut self) {
+        let ahead: Vec<(usize, T)> = (self.num_of_instances + 1..=self.highest_learned)
+            .filter(|instance| !self.delivered_ahead.contains(instance))
+            .filter_map(|instance| self.learned_values.get(&instance).map(|&v| (instance, v)))
+            .collect();
+
+        for (instance, v) in ahead {
+            self.delivered_ahead.insert(instance);
+            self.deliver_value(instance, v);
+        }
+    }
 
-        loop {
-            if log_enabled!(Level::Info) {
-                info!("[P={:?}] Proposer waiting...", self.id);
+    /// Keeps track of the highest instance whose learned value is known.
+    fn see_instance(&mut self, instance: usize) {
+        if instance > self.highest_learned {
+            self.highest_learned = instance;
+        }
+    }
+
+    /// Catches up, if the gap at the delivery watermark has persisted for longer than the gap
+    /// timeout (see with_gap_timeout). The gap timer is then restarted, so that this is repeated
+    /// until the gap is filled. If the instance is still stuck a gap timeout after this learner
+    /// caught up, i.e. no proposer knew its value, the proposers are also asked to decide a no-op
+    /// in it. A single Report without the value is not enough, as another proposer may know it.
+    /// The run loop calls this periodically.
+    pub fn check_gap(&mut self) {
+        if let (Some(gap_timeout), Some(gap_detected_at)) = (self.gap_timeout, self.gap_detected_at)
+        {
+            if gap_detected_at.elapsed() >= gap_timeout {
+                let instance = self.num_of_instances;
+
+                if log_enabled!(Level::Info) {
+                    info!("[L={:?}] Instance {:?} is stuck.", self.id, instance);
+                }
+
+                if self.stuck_instance == Some(instance) {
+                    self.request_noop(instance);
+                }
+
+                self.stuck_instance = Some(instance);
+                self.catch_up();
+                self.request_value(instance);
+                self.gap_detected_at = Some(Instant::now());
             }
+        }
+    }
 
-            let m = self.node.receive();
+    /// Returns how long this learner can wait for a message before it must check the gap at its
+    /// delivery watermark, poll the proposers, report its progress or offer a value again to a
+    /// blocking sink, or None if it never has to. Note that a timeout
+    /// of zero is not allowed.
+    fn receive_timeout(&self) -> Option<Duration> {
+        let poll_timeout = self.mode.poll_interval().map(|poll_interval| {
+            poll_interval
+                .checked_sub(self.last_poll.elapsed())
+                .unwrap_or_default()
+        });
 
-            match m {
-                Message::Phase0a::<T>(request) => self.handle_request(request),
-                Message::Phase0b(catch_up) => self.handle_catch_up(catch_up),
-                Message::Phase0c::<T>(report) => self.handle_report(report),
-                Message::Phase1b::<T>(promise) => self.handle_promise(promise),
-                Message::Phase2b::<T>(acceptance) => self.handle_acceptance(acceptance),
-                _ => info!(
-                    "[P={:?}] Unexpected message received. I'll ignore it.",
-                    self.id
-                ),
+        let progress_timeout = self.progress_interval.map(|progress_interval| {
+            progress_interval
+                .checked_sub(self.last_progress.elapsed())
+                .unwrap_or_default()
+        });
+
+        let retry_timeout = Some(SINK_RETRY_INTERVAL).filter(|_| self.is_blocked_by_sink());
+
+        [
+            self.gap_timeout,
+            poll_timeout,
+            progress_timeout,
+            retry_timeout,
+        ]
+        .iter()
+        .flatten()
+        .min()
+        .map(|&timeout| timeout.max(Duration::from_millis(1)))
+    }
+
+    /// Polls the proposers for the values learned beyond the delivery watermark, if the poll
+    /// interval has elapsed since the last time this learner polled them.
+    fn check_poll(&mut self) {
+        if let Some(poll_interval) = self.mode.poll_interval() {
+            if self.last_poll.elapsed() >= poll_interval {
+                self.catch_up();
+                self.last_poll = Instant::now();
             }
         }
     }
-}
 
-/// In the Multi-Paxos algorithm, an acceptor can participate in several instances of the basic
-/// Paxos algorithm (at the same time). Given that messages can be received out-of-order, we need to
-/// save the state of all those instances, in order to decide what to do depending on the instance
-/// and its associated values. This struct contains the values, of a single acceptor, which are
-/// associated with 1 instance of the basic Paxos algorithm.
-struct AcceptorState<T> {
-    // The highest-numbered round the acceptor has PARTICIPATED in. It is initially 0. rnd is then
-    // set to the c_rnd, sent in a Preparation message by some Proposer, such that c_rnd > rnd. So,
-    // here, by "participate" we mean to send a Promise message to the proposals.
-    rnd: usize,
-
-    // The highest-numbered round the acceptor has CAST a vote. It is initially 0, but it eventually
-    // corresponds to some c_rnd sent by a Proposer in a Proposal message, such that
-    // c_rnd > self.rnd. In other words, v_rnd will be a number which is greater than any round the
-    // acceptor has participated in. v_rnd is thus set only when the acceptor wants to send a
-    // Acceptance message to the proposers, after having received enough Proposals. So, here, by
-    // casting a vote we mean to send a Acceptance message to the proposers.
-    v_rnd: usize,
+    /// Reports the progress of this learner to the proposers, if the progress interval has elapsed
+    /// since the last time this learner reported it (see with_progress_reports).
+    fn check_progress(&mut self) {
+        if let Some(progress_interval) = self.progress_interval {
+            if self.last_progress.elapsed() >= progress_interval {
+                self.report_progress();
+            }
+        }
+    }
 
-    // The value voted by the acceptor in round v_rnd. It is initially None.
-    v_val: Option<T>,
-}
+    /// Handles the message m, which was received by this learner, by dispatching it to the
+    /// corresponding handler. Messages of other Paxos groups are ignored.
+    pub fn handle(&mut self, m: Message<T>) {
+        if m.group_id() != self.group_id {
+            if logs_messages() {
+                info!(
+                    "[L={:?}] I will ignore {:?} of another group.",
+                    self.id,
+                    Logged(&m)
+                );
+            }
+            return;
+        }
 
-// I had to implement Default manually. See https://github.com/rust-lang/rust/issues/45036.
-impl<T> Default for AcceptorState<T> {
-    fn default() -> Self {
-        AcceptorState {
-            rnd: 0,
-            v_rnd: 0,
-            v_val: None,
+        match m {
+            Message::Phase0c::<T>(report) => self.handle_report(report),
+            Message::Phase3::<T>(learning) => self.handle_learning(learning),
+            Message::ValueReply(value_reply) => self.handle_value_reply(value_reply),
+            Message::Phase2b::<T>(acceptance) => self.handle_acceptance(acceptance),
+            Message::Ping(ping) => pong(&self.node, self.uuid, ping),
+            _ => info!(
+                "[L={:?}] Unexpected message received. I'll ignore it.",
+                self.id
+            ),
         }
     }
-}
 
-/// The struct representing the acceptor in the Paxos algorithm.
-pub struct Acceptor<T> {
-    uuid: Uuid,
+    /// Handles the message contained in datagram, which was delivered to this learner by the owner
+    /// of its in-memory channel. Corrupted datagrams are dropped.
+    #[cfg(feature = "testkit")]
+    pub(crate) fn deliver(&mut self, datagram: &[u8]) {
+        if let Some(m) = self.node.decode(datagram) {
+            self.handle(m);
+        }
+    }
 
-    id: usize,
+    // Handlers
 
-    // Each instance of the Paxos algorithm, in the Multi-Paxos algorithm, is associated with 1
-    // AcceptorState<T>. This is a map from each instance (of a basic Paxos algorithm), which is a
-    // number, to the corresponding AcceptorState<T> needed to complete that instance.
-    acceptor_states: HashMap<usize, AcceptorState<T>>,
+    /// Handles the Report message sent by a proposer to this learner.
+    fn handle_report(&mut self, report: Report<T>) {
+        if report.receiver_uuid == self.uuid {
+            if logs_messages() {
+                info!("[L={:?}] Received {:?}.", self.id, Logged(&report));
+            }
+
+            // The values of the instances before the last stable checkpoint of the proposer are
+            // lost, so the state of the application must be restored from its snapshot.
+            if let Some(checkpoint) = report.checkpoint {
+                if checkpoint.0 > self.num_of_instances {
+                    warn!(
+                        "[L={:?}] The log is truncated below instance {:?}: I will skip the instances from {:?}.",
+                        self.id, checkpoint.0, self.num_of_instances
+                    );
+                    self.skip_to(checkpoint.0);
+                }
+            }
+
+            for (instance, learned_value) in report.learned_values {
+                // The values of the pruned instances were already delivered.
+                if instance < self.pruned_below {
+                    continue;
+                }
+
+                // It is possible that we receive the learned value associated with an instance from
+                // more than one proposer.
+                self.learned_values.insert(instance, learned_value);
+                self.see_instance(instance);
+            }
+
+            self.print_learned_values();
+        }
+    }
+
+    /// Handles the receipt of a Learning message sent by a proposer.
+    fn handle_learning(&mut self, learning: Learning<T>) {
+        // The proposer sends the Learning message again if the LearnAck of this learner was lost.
+        let delivered = learning.instance.0 < self.num_of_instances
+            || self.delivered_ahead.contains(&learning.instance.0);
+
+        if self.learn_acks && delivered {
+            self.acknowledge(learning.instance);
+        }
+
+        if let LearningMode::Pull(_) = self.mode {
+            if logs_messages() {
+                info!(
+                    "[L={:?}] I poll the proposers: I will ignore {:?}.",
+                    self.id,
+                    Logged(&learning)
+                );
+            }
+            return;
+        }
 
-    node: NetNode<T>,
+        if logs_messages() {
+            info!("[L={:?}] Received {:?}.", self.id, Logged(&learning));
+        }
 
-    proposers_address: SocketAddrV4,
-}
+        // The values of the pruned instances were already delivered.
+        if learning.instance.0 < self.pruned_below {
+            return;
+        }
 
-impl<T> Acceptor<T>
-where
-    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
-{
-    pub fn new(
-        id: usize,
-        acceptors_address: SocketAddrV4,
-        proposers_address: SocketAddrV4,
-    ) -> Self {
-        Acceptor {
-            uuid: Uuid::new_v4(),
-            id,
-            acceptor_states: HashMap::new(),
-            node: NetNode::new(&acceptors_address),
-            proposers_address,
+        if let Some(v) = self
+            .learned_values
+            .insert(learning.instance.0, learning.learned_value)
+        {
+            // All proposers must learn the same value and send the same value to the learners.
+            assert_eq!(
+                v, learning.learned_value,
+                "Bug: previously learned value is not equal to just learned one."
+            );
         }
+
+        self.see_instance(learning.instance.0);
+        self.print_learned_values();
     }
 
-    // Handlers
+    /// Handles the ValueReply message sent by an acceptor to this learner. Once a majority of the
+    /// acceptors report the same value, voted in the same round, that value is learned.
+    fn handle_value_reply(&mut self, value_reply: ValueReply<T>) {
+        if value_reply.receiver_uuid != self.uuid
+            || value_reply.instance.0 < self.pruned_below
+            || self.learned_values.contains_key(&value_reply.instance.0)
+        {
+            return;
+        }
 
-    /// Handles the Preparation message sent by a proposer to this acceptor.
-    fn handle_preparation(&mut self, preparation: Preparation) {
-        if log_enabled!(Level::Info) {
-            info!("[A={:?}] I will handle {:?}.", self.id, preparation);
+        if logs_messages() {
+            info!("[L={:?}] Received {:?}.", self.id, Logged(&value_reply));
         }
 
-        self.promise(
-            preparation.c_rnd,
-            preparation.sender_uuid,
-            preparation.instance,
+        self.count_vote(
+            value_reply.instance.0,
+            value_reply.sender_uuid,
+            (value_reply.v_rnd, value_reply.v_val),
         );
     }
 
-    /// Handles the Proposal message sent by a proposer to this acceptor.
-    fn handle_proposal(&mut self, proposal: Proposal<T>) {
-        if log_enabled!(Level::Info) {
-            info!("[A={:?}] I will handle {:?}.", self.id, proposal);
+    /// Handles the Acceptance message sent by an acceptor to the proposers and, with direct
+    /// learning (see with_direct_learning), to the learners too.
+    fn handle_acceptance(&mut self, acceptance: Acceptance<T>) {
+        if !self.direct_learning || self.majority_of_acceptors == 0 {
+            if logs_messages() {
+                info!(
+                    "[L={:?}] I do not learn from the acceptors: I will ignore {:?}.",
+                    self.id,
+                    Logged(&acceptance)
+                );
+            }
+            return;
         }
 
-        match proposal.c_val {
-            Some(c_val) => self.accept(
-                proposal.c_rnd,
-                c_val,
-                proposal.sender_uuid,
-                proposal.instance,
-            ),
-            _ => panic!("Logic error: contact the programmer."),
+        if acceptance.instance.0 < self.pruned_below
+            || self.learned_values.contains_key(&acceptance.instance.0)
+        {
+            return;
+        }
+
+        if logs_messages() {
+            info!("[L={:?}] Received {:?}.", self.id, Logged(&acceptance));
+        }
+
+        self.count_vote(
+            acceptance.instance.0,
+            acceptance.sender_uuid,
+            (acceptance.v_rnd, acceptance.v_val),
+        );
+    }
+
+    /// Records the vote, i.e. the (v_rnd, v_val), of the acceptor sender_uuid in instance. Once a
+    /// majority of the acceptors report the same value, voted in the same round, that value is
+    /// learned.
+    fn count_vote(&mut self, instance: usize, sender_uuid: Uuid, vote: (Ballot, Option<T>)) {
+        let replies = self.value_replies.entry(instance).or_default();
+        replies.insert(sender_uuid, vote);
+
+        if let (_, Some(v_val)) = vote {
+            let num_of_votes = replies.values().filter(|&&other| other == vote).count();
+
+            if num_of_votes >= self.majority_of_acceptors {
+                if log_enabled!(Level::Info) {
+                    info!(
+                        "[L={:?}] A majority of acceptors voted for {:?} in instance {:?}.",
+                        self.id, v_val, instance
+                    );
+                }
+
+                self.value_replies.remove(&instance);
+                self.learned_values.insert(instance, v_val);
+                self.see_instance(instance);
+                self.print_learned_values();
+            }
         }
     }
 
     // Senders
 
-    /// Sends a Promise message to one or more proposers, if c_rnd > rnd.
-    fn promise(&mut self, c_rnd: usize, sender_uid: Uuid, instance: usize) {
-        let state = self.acceptor_states.entry(instance).or_default();
+    /// Asks the proposers about previously executed basic Paxos instances and thus learned values.
+    /// A learner, which is instantiated after some basic Paxos instances have been executed, must
+    /// first know the learned values associated with these previously executed Paxos instances, so
+    /// as to "deliver" the associated values before the values associated with the future Paxos
+    /// instances that can eventually be executed. Only the learned values of the instances from the
+    /// delivery watermark on are asked for.
+    pub(crate) fn catch_up(&self) {
+        let m = Message::Phase0b(CatchUp {
+            sender_uuid: self.uuid,
+            group_id: self.group_id,
+            sender_type: SenderType::Learner,
+            reply_address: self.advertised_address,
+            from_instance: InstanceId(self.num_of_instances),
+            known_instances: InstanceRanges::from_instances(
+                self.learned_values
+                    .keys()
+                    .cloned()
+                    .filter(|&instance| instance >= self.num_of_instances),
+            ),
+        });
 
-        if c_rnd > state.rnd {
-            // The promise.
-            state.rnd = c_rnd;
+        if logs_messages() {
+            info!("[L={:?}] I will send {:?}.", self.id, Logged(&m));
+        }
 
-            let m = Message::Phase1b::<T>(Promise {
-                rnd: state.rnd,
-                v_rnd: state.v_rnd,
-                v_val: state.v_val, // The value it last accepted. It can be None.
+        self.node.send(m, &self.proposers_address);
+    }
+
+    /// Asks the acceptors, if they are known, which value they accepted in the given instance.
+    fn request_value(&self, instance: usize) {
+        if let Some(ref acceptors_address) = self.acceptors_address {
+            let m = Message::ValueRequest::<T>(ValueRequest {
+                instance: InstanceId(instance),
+                reply_address: self.advertised_address.unwrap_or(self.learners_address),
                 sender_uuid: self.uuid,
-                receiver_uuid: sender_uid,
-                instance,
+                group_id: self.group_id,
             });
 
-            if log_enabled!(Level::Info) {
-                info!("[A={:?}] I will send {:?}.", self.id, m);
+            if logs_messages() {
+                info!("[L={:?}] I will send {:?}.", self.id, Logged(&m));
             }
 
-            self.node.send(m, &self.proposers_address);
-        } else {
-            // TODO: send a NACK. Note that, to send a nack and handle nacks, we may need to change
-            // TODO: the logic in several places. For example, we may need to clear buffers, once
-            // TODO: a new preparation message is sent from the proposers to the acceptors.
-            // TODO: note: sending and handling nacks should not be necessary for Paxos to work.
+            self.node.send(m, acceptors_address);
         }
     }
 
-    /// Sends an Acceptance message to one or more proposers, if c_rnd >= rnd.
-    fn accept(&mut self, c_rnd: usize, c_val: T, sender_uid: Uuid, instance: usize) {
-        let state = self.acceptor_states.entry(instance).or_default();
+    /// Tells the proposers that the learned value of the given instance has been delivered.
+    fn acknowledge(&self, instance: InstanceId) {
+        let m = Message::LearnAck::<T>(LearnAck {
+            instance,
+            sender_uuid: self.uuid,
+            group_id: self.group_id,
+        });
 
-        if c_rnd >= state.rnd {
-            state.v_rnd = c_rnd;
-            state.v_val = Some(c_val);
+        if logs_messages() {
+            info!("[L={:?}] I will send {:?}.", self.id, Logged(&m));
+        }
 
-            let m = Message::Phase2b::<T>(Acceptance {
-                v_rnd: state.v_rnd,
-                v_val: state.v_val,
-                sender_uuid: self.uuid,
-                receiver_uuid: sender_uid,
-                instance,
-            });
+        self.node.send(m, &self.proposers_address);
+    }
 
-            if log_enabled!(Level::Info) {
-                info!("[A={:?}] I will send {:?}.", self.id, m);
-            }
+    /// Asks the proposers to decide a no-op in the given instance, which blocks the delivery of
+    /// the learned values of the later instances.
+    fn request_noop(&self, instance: usize) {
+        let m = Message::NoopRequest::<T>(NoopRequest {
+            instance: InstanceId(instance),
+            sender_uuid: self.uuid,
+            group_id: self.group_id,
+        });
 
-            self.node.send(m, &self.proposers_address);
-        } else {
-            // TODO: send a NACK. Note that, to send a nack and handle nacks, we may need to change
-            // TODO: the logic in several places. For example, we may need to clear buffers, once
-            // TODO: a new preparation message is sent from the proposers to the acceptors.
-            // TODO: note: sending and handling nacks should not be necessary for Paxos to work.
+        if logs_messages() {
+            info!("[L={:?}] I will send {:?}.", self.id, Logged(&m));
         }
+
+        self.node.send(m, &self.proposers_address);
     }
 }
 
-impl<T> Runnable for Acceptor<T>
+impl<T> Learner<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq + Batch,
+{
+    /// Makes this learner deliver (i.e. print) the commands of each delivered batch (see Batch),
+    /// one per line, in order, before the ones of the next instance, instead of the batch itself.
+    /// The sinks, the deliveries and the state machine fed by this learner (see with_sink,
+    /// deliveries and ReplicatedStateMachine) still receive whole batches, whose commands they
+    /// must apply in the same order.
+    pub fn with_batching(mut self) -> Self {
+        self.print_commands = Some(print_commands::<T>);
+        self
+    }
+}
+
+/// Prints the commands of batch, one per line, in order (see Learner::with_batching).
+fn print_commands<T: Batch>(batch: &T) {
+    for command in batch.commands() {
+        println!("{:?}", command);
+    }
+}
+
+impl<T> Learner<T>
 where
     T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
 {
-    fn run(&mut self) {
-        loop {
-            if log_enabled!(Level::Info) {
-                info!("[A={:?}] Acceptor waiting...", self.id);
+    /// Answers the commands received on the control socket of this learner, if any.
+    fn poll_admin(&mut self) {
+        if let Some(admin) = self.admin.take() {
+            admin.poll(|command| self.answer(command));
+            self.admin = Some(admin);
+        }
+    }
+
+    /// Answers the given command of an operator (see with_admin_address).
+    fn answer(&mut self, command: Command) -> Answer {
+        match command {
+            Command::Role => Ok(format!(
+                "role=learner id={} uuid={} group={}",
+                self.id, self.uuid, self.group_id
+            )),
+            Command::Status => Ok(format!(
+                "next_to_deliver={} highest_learned={} stuck_instance={:?}",
+                self.num_of_instances, self.highest_learned, self.stuck_instance
+            )),
+            Command::Instance(instance) => Ok(match self.learned_values.get(&instance) {
+                Some(v) => format!("instance={} state=learned value={:?}", instance, v),
+                None if instance < self.pruned_below => {
+                    format!("instance={} state=dropped", instance)
+                }
+                None => format!("instance={} state=unknown", instance),
+            }),
+            Command::Metrics => Ok(format!(
+                "retained_values={} memory_footprint={}",
+                self.learned_values.len(),
+                self.memory_footprint()
+            )),
+            Command::ForceNoop(_) => Err("only a proposer can force a no-op".to_string()),
+            Command::Drain => Err("only a proposer can drain".to_string()),
+            Command::StepDown => Err("only a proposer can step down".to_string()),
+            Command::Log { from, count } => {
+                admin::describe_log(from, count, self.highest_learned, |instance| {
+                    self.learned_values.get(&instance)
+                })
             }
+        }
+    }
+}
+
+impl<T> EventLoop<T> for Learner<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    fn net_node(&mut self) -> &mut NetNode<T> {
+        &mut self.node
+    }
 
-            let m = self.node.receive();
+    fn read_timeout(&self) -> Option<Duration> {
+        // Without a gap timeout, nor polling (nor a control socket), this learner waits until a
+        // message is received.
+        admin::receive_timeout(&self.admin, self.receive_timeout())
+    }
 
-            match m {
-                Message::Phase1a::<T>(preparation) => self.handle_preparation(preparation),
-                Message::Phase2a::<T>(proposal) => self.handle_proposal(proposal),
-                _ => info!(
-                    "[A={:?}] Unexpected message received. I'll ignore it.",
-                    self.id
-                ),
-            }
+    fn log_waiting(&self) {
+        if log_enabled!(Level::Info) {
+            info!("[L={:?}] Learner waiting...", self.id);
         }
     }
+
+    fn handle_message(&mut self, m: Message<T>) {
+        self.handle(m);
+    }
+
+    fn on_idle(&mut self) {
+        self.check_gap();
+        self.check_poll();
+        self.check_progress();
+        self.retry_sinks();
+        self.poll_admin();
+    }
 }
 
-/// The struct representing the learner in the Paxos algorithm.
-pub struct Learner<T> {
-    uuid: Uuid,
+impl<T> Runnable for Learner<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    fn run(&mut self) {
+        self.run_with(|_| {});
+    }
+}
 
+/// The function which an observer calls with each decided value which it observes, together with
+/// its instance (see Observer). It is Send, like the observer, which can thus run on its own thread.
+pub type OnDecision<T> = Box<dyn FnMut(usize, &T) + Send>;
+
+/// A passive, read-only node, which taps the live decisions of a Paxos group (e.g. for a monitoring
+/// tool), i.e. the Learning messages which the proposers send to the learners, as they arrive.
+/// Unlike a learner, an observer never sends any message: it does not catch up with the values
+/// decided before it joined (it ignores the Report messages addressed to the learners), it does
+/// not recover the lost values, and it does not buffer the values to deliver them in total order.
+/// So it makes no claim about the completeness of the log: it may miss the values decided before
+/// it joined, and the ones whose Learning messages it did not receive, and it observes the other
+/// ones in the order they arrive, i.e. possibly out of order.
+pub struct Observer<T> {
     id: usize,
 
-    // A map between instance numbers (or ids) and the learned value during that instance.
-    learned_values: HashMap<usize, T>,
+    // The Paxos group (i.e. the replicated log) this node observes. Messages belonging to other
+    // groups are ignored.
+    group_id: usize,
 
-    // The number of learned values printed to the standard output so far. This is used to print
-    // the learned values in total order, that is, according to the increasing number of the
-    // corresponding Paxos instance.
-    num_of_instances: usize,
+    // The function which is called with each observed value, and its instance.
+    on_decision: OnDecision<T>,
 
-    node: NetNode<T>,
+    // The most recent instances whose decided values were observed, up to
+    // OBSERVED_INSTANCES_CAPACITY of them, so that they are observed once, although their
+    // Learning messages can be received several times (e.g. when a proposer sends them again, see
+    // Proposer::with_learn_acks). The instances below self.forgotten_below are ignored.
+    observed: BTreeSet<usize>,
+    forgotten_below: usize,
 
-    // A learner needs to contact the proposers to ask them about previously executed basic Paxos
-    // instances, in order to deliver the related learned values, before the future Paxos
-    // instances that are eventually executed.
-    proposers_address: SocketAddrV4,
+    // The number of values observed so far.
+    num_of_observed: usize,
+
+    node: NetNode<T>,
 }
 
-impl<T> Learner<T>
+impl<T> Observer<T>
 where
     T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
 {
-    pub fn new(id: usize, learners_address: SocketAddrV4, proposers_address: SocketAddrV4) -> Self {
-        Learner {
-            uuid: Uuid::new_v4(),
+    /// Creates an observer which joins the multicast group of the learners, learners_address, and
+    /// calls on_decision with each decided value which it observes, and its instance.
+    pub fn new(id: usize, learners_address: SocketAddrV4, on_decision: OnDecision<T>) -> Self {
+        Observer::with_node(NetNode::new(&learners_address), id, on_decision)
+    }
+
+    /// Creates an observer which receives its messages using node.
+    pub(crate) fn with_node(node: NetNode<T>, id: usize, on_decision: OnDecision<T>) -> Self {
+        #[cfg(feature = "metrics")]
+        let node = node.with_role("observer");
+
+        Observer {
             id,
-            learned_values: HashMap::new(),
-            num_of_instances: 1,
-            node: NetNode::new(&learners_address),
-            proposers_address,
+            group_id: 0,
+            on_decision,
+            observed: BTreeSet::new(),
+            forgotten_below: 1,
+            num_of_observed: 0,
+            node,
         }
     }
 
-    /// Tries to print the learned values that can be already printed, that is, the ones received in
-    /// total order.
-    fn print_learned_values(&mut self) {
-        while self.learned_values.contains_key(&self.num_of_instances) {
-            println!(
-                "{:?}",
-                self.learned_values.get(&self.num_of_instances).unwrap()
-            );
-            self.num_of_instances += 1;
-        }
+    /// Makes this observer observe the Paxos group group_id (by default, 0).
+    pub fn with_group_id(mut self, group_id: usize) -> Self {
+        self.group_id = group_id;
+        self
     }
 
-    // Handlers
+    /// Writes every message received by this observer to message_log.
+    pub fn with_message_log(mut self, message_log: MessageLog) -> Self {
+        self.node.set_message_log(message_log);
+        self
+    }
 
-    /// Handles the Report message sent by a proposer to this learner.
-    fn handle_report(&mut self, report: Report<T>) {
-        if report.receiver_uuid == self.uuid {
-            if log_enabled!(Level::Info) {
-                info!("[L={:?}] Received {:?}.", self.id, report);
-            }
+    /// Makes this observer belong to the cluster named cluster_name (by default, the empty name), so
+    /// that it ignores the messages of the nodes of other clusters (see NetNode::set_cluster_id).
+    pub fn with_cluster_id(mut self, cluster_name: &str) -> Self {
+        self.node.set_cluster_id(cluster_name);
+        self
+    }
 
-            for (instance, learned_value) in report.learned_values {
-                // It is possible that we receive the learned value associated with an instance from
-                // more than one proposer.
-                self.learned_values.insert(instance, learned_value);
-            }
+    /// Makes this observer decrypt its messages with a key derived from secret, which must be
+    /// shared by all the nodes of the cluster, and drop the ones which it cannot decrypt (see
+    /// NetNode::set_encryption_key).
+    #[cfg(feature = "encryption")]
+    pub fn with_encryption_key(mut self, secret: &str) -> Self {
+        self.node.set_encryption_key(secret);
+        self
+    }
 
-            self.print_learned_values();
+    /// Makes a separate thread receive the messages of this observer, as soon as they arrive, and
+    /// queue them, up to capacity messages, until this observer handles them, so that a slow
+    /// on_decision does not make the socket drop the messages which arrive in the meantime.
+    pub fn with_receive_queue(mut self, capacity: usize) -> Self {
+        self.node.set_receive_queue(capacity);
+        self
+    }
+
+    /// Returns the number of decided values which this observer observed so far.
+    pub fn num_of_observed(&self) -> usize {
+        self.num_of_observed
+    }
+
+    /// Returns the highest instance whose decided value this observer observed, if any.
+    pub fn highest_observed(&self) -> Option<usize> {
+        self.observed.iter().next_back().copied()
+    }
+
+    /// Handles the message m. Only the Learning messages are observed: all the other messages sent
+    /// to the learners (e.g. the Report messages) are ignored.
+    pub fn handle(&mut self, m: Message<T>) {
+        if m.group_id() != self.group_id {
+            return;
+        }
+
+        if let Message::Phase3::<T>(learning) = m {
+            self.handle_learning(learning);
         }
     }
 
-    /// Handles the receipt of a Learning message sent by a proposer.
+    /// Handles the message contained in datagram, which was delivered to this observer by the owner
+    /// of its in-memory channel. Corrupted datagrams are dropped.
+    #[cfg(feature = "testkit")]
+    pub(crate) fn deliver(&mut self, datagram: &[u8]) {
+        if let Some(m) = self.node.decode(datagram) {
+            self.handle(m);
+        }
+    }
+
+    /// Handles the receipt of a Learning message sent by a proposer, whose value is observed, unless
+    /// it was already.
     fn handle_learning(&mut self, learning: Learning<T>) {
-        if log_enabled!(Level::Info) {
-            info!("[L={:?}] Received {:?}.", self.id, learning);
+        let instance = learning.instance.0;
+
+        if instance < self.forgotten_below || !self.observed.insert(instance) {
+            return;
         }
 
-        if let Some(v) = self
-            .learned_values
-            .insert(learning.instance, learning.learned_value)
-        {
-            // All proposers must learn the same value and send the same value to the learners.
-            assert_eq!(
-                v, learning.learned_value,
-                "Bug: previously learned value is not equal to just learned one."
-            );
+        if logs_messages() {
+            info!("[O={:?}] Observed {:?}.", self.id, Logged(&learning));
         }
 
-        self.print_learned_values();
+        if self.observed.len() > OBSERVED_INSTANCES_CAPACITY {
+            if let Some(oldest) = self.observed.pop_first() {
+                self.forgotten_below = oldest + 1;
+            }
+        }
+
+        self.num_of_observed += 1;
+        (self.on_decision)(instance, &learning.learned_value);
     }
+}
 
-    // Senders
+impl<T> EventLoop<T> for Observer<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    fn net_node(&mut self) -> &mut NetNode<T> {
+        &mut self.node
+    }
 
-    /// Asks the proposers about previously executed basic Paxos instances and thus learned values.
-    /// A learner, which is instantiated after some basic Paxos instances have been executed, must
-    /// first know the learned values associated with these previously executed Paxos instances, so
-    /// as to "deliver" the associated values before the values associated with the future Paxos
-    /// instances that can eventually be executed.
-    fn catch_up(&self) {
-        let m = Message::Phase0b(CatchUp {
-            sender_uuid: self.uuid,
-            sender_type: 'l',
-        });
+    fn read_timeout(&self) -> Option<Duration> {
+        // An observer has no periodic maintenance.
+        None
+    }
 
+    fn log_waiting(&self) {
         if log_enabled!(Level::Info) {
-            info!("[L={:?}] I will send {:?}.", self.id, m);
+            info!("[O={:?}] Observer waiting...", self.id);
         }
+    }
 
-        self.node.send(m, &self.proposers_address);
+    fn handle_message(&mut self, m: Message<T>) {
+        self.handle(m);
     }
+
+    fn on_idle(&mut self) {}
 }
 
-impl<T> Runnable for Learner<T>
+impl<T> Runnable for Observer<T>
 where
     T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
 {
     fn run(&mut self) {
-        self.catch_up();
-
-        loop {
-            if log_enabled!(Level::Info) {
-                info!("[L={:?}] Learner waiting...", self.id);
-            }
-
-            let m = self.node.receive();
-
-            match m {
-                Message::Phase0c::<T>(report) => self.handle_report(report),
-                Message::Phase3::<T>(learning) => self.handle_learning(learning),
-                _ => info!(
-                    "[L={:?}] Unexpected message received. I'll ignore it.",
-                    self.id
-                ),
-            }
-        }
+        self.serve(|_| {});
     }
 }
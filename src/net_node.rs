@@ -1,22 +1,173 @@
 //! A module which contains the definition of a struct which can be used to send or receive messages
-//! using a UDP socket.
+//! using a UDP socket (or a Unix datagram socket, see the module unix_transport, or, for testing,
+//! an in-memory channel).
 
-use std::fmt::Debug;
+#[cfg(feature = "encryption")]
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+#[cfg(feature = "testkit")]
+use std::collections::VecDeque;
+use std::convert::TryInto;
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::io::{self, ErrorKind};
 use std::marker::PhantomData;
-use std::net::{Ipv4Addr, SocketAddrV4, UdpSocket};
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::os::unix::net::UnixDatagram;
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use bincode::{deserialize, serialize};
-use net2::UdpBuilder;
+#[cfg(feature = "encryption")]
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+#[cfg(feature = "encryption")]
+use aes_gcm::{Aes256Gcm, Nonce};
+use bincode::{deserialize, serialize, serialized_size};
+use crc32fast::hash;
+use net2::{UdpBuilder, UdpSocketExt};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+#[cfg(feature = "encryption")]
+use sha2::{Digest, Sha256};
 
+use crate::fragmentation::{self, Reassembler, Reassembly, FRAGMENT_HEADER_SIZE};
 use crate::message::Message;
+use crate::message_log::{Direction, MessageLog};
+use crate::rate_limit::{SendQueue, TokenBucket};
+#[cfg(feature = "metrics")]
+use crate::telemetry;
+use crate::unix_transport::{self, UnixTransport};
+use crate::verbosity::Summarized;
+
+/// The number of bytes of the CRC32 checksum which is appended to every datagram.
+const CHECKSUM_SIZE: usize = 4;
+
+/// The number of bytes of the identifier of the cluster which is prepended to every datagram.
+const CLUSTER_ID_SIZE: usize = 4;
+
+/// The number of bytes of the (random) nonce which is prepended to every encrypted payload.
+#[cfg(feature = "encryption")]
+const NONCE_SIZE: usize = 12;
+
+/// The number of bytes of the authentication tag which is appended to every encrypted payload.
+#[cfg(feature = "encryption")]
+const TAG_SIZE: usize = 16;
+
+// TODO: what's the required size of data_received?
+/// The size of the buffer which the datagrams are received into, which is also the largest maximum
+/// size of the sent datagrams, and the default one over the transports other than UDP (see
+/// set_max_datagram_size).
+const RECEIVE_BUFFER_SIZE: usize = 16384;
+
+/// The default maximum size of the datagrams sent over UDP, which, with the IP and UDP headers,
+/// stays under the MTU of most networks (e.g. 1500 bytes over Ethernet), so that IP does not
+/// fragment them (see set_max_datagram_size).
+const DEFAULT_MAX_DATAGRAM_SIZE: usize = 1400;
+
+/// The smallest maximum size of the sent datagrams (see set_max_datagram_size), i.e. the size of a
+/// fragment which carries a single byte of its message.
+const MIN_DATAGRAM_SIZE: usize = CLUSTER_ID_SIZE + FRAGMENT_HEADER_SIZE + 1 + CHECKSUM_SIZE;
+
+/// The shortest time that receive_timeout waits for a datagram.
+const MIN_RECEIVE_TIMEOUT: Duration = Duration::from_millis(1);
+
+/// The capacity of the receive queue which is created when a unicast socket is added to a NetNode
+/// without one (see set_unicast_address).
+const DEFAULT_RECEIVE_QUEUE_CAPACITY: usize = 1024;
+
+/// The datagrams which have been sent over an in-memory channel, but not yet delivered, in the
+/// order they were sent. Each datagram is paired with its destination address.
+#[cfg(feature = "testkit")]
+pub(crate) type Bus = Arc<Mutex<VecDeque<(SocketAddrV4, Vec<u8>)>>>;
+
+/// The medium over which a NetNode sends and receives datagrams.
+enum Transport {
+    Udp {
+        udp_socket_sender: UdpSocket,
+
+        udp_socket_receiver: UdpSocket,
+
+        // If set, the socket which receives the datagrams sent directly to this NetNode (see
+        // set_unicast_address), besides the one which receives the multicast datagrams.
+        udp_socket_unicast: Option<UdpSocket>,
+
+        // The sockets which receive the datagrams of the other multicast groups which this NetNode
+        // joined (see join_multicast_group), one per group.
+        udp_sockets_groups: Vec<UdpSocket>,
+    },
+
+    // The multicast groups are emulated over Unix datagram sockets, for the nodes which all run on
+    // the same host (see the module unix_transport).
+    Unix(UnixTransport),
+
+    // The sent datagrams are queued in the Bus shared by all nodes of a simulated cluster, which is
+    // responsible for delivering them (see the module testkit).
+    #[cfg(feature = "testkit")]
+    Channel(Bus),
+}
 
 /// A struct which can be used to send to or receive from a UDP socket.
 pub struct NetNode<T> {
-    udp_socket_sender: UdpSocket,
+    transport: Transport,
+
+    // The number of received datagrams which have been dropped because their checksum did not
+    // match their payload (i.e. they were corrupted in transit).
+    num_of_corrupted: Cell<usize>,
+
+    // The identifier of the cluster (i.e. the CRC32 hash of its name) which this NetNode belongs
+    // to, and the number of received datagrams which have been dropped because they were sent by
+    // the nodes of another cluster (see set_cluster_id).
+    cluster_id: u32,
+    num_of_foreign: Cell<usize>,
+
+    // The maximum size of the sent datagrams, beyond which the messages are fragmented, the
+    // fragments of the received messages which are not complete yet, and the number of messages
+    // which were dropped because some of their fragments were lost (see set_max_datagram_size).
+    max_datagram_size: usize,
+    reassembler: RefCell<Reassembler>,
+    num_of_incomplete: Cell<usize>,
+
+    // Whether the messages larger than max_datagram_size are fragmented, rather than refused, and
+    // the number of messages which were refused (see set_fragmentation).
+    fragmentation: bool,
+    num_of_oversized: Cell<usize>,
+
+    // If set, the payload of every datagram is encrypted and authenticated with this cipher, and
+    // the number of received datagrams which have been dropped because they could not be
+    // decrypted (e.g. because they were not encrypted with the same key) (see
+    // set_encryption_key).
+    #[cfg(feature = "encryption")]
+    cipher: Option<Aes256Gcm>,
+    #[cfg(feature = "encryption")]
+    num_of_undecryptable: Cell<usize>,
+
+    // If set, every message sent or received by this NetNode is also written to this log, which
+    // is shared with the NetNodes obtained by calling share.
+    message_log: Option<Arc<Mutex<MessageLog>>>,
+
+    // If set, the messages sent by this NetNode (and by the NetNodes obtained by calling share)
+    // take their tokens from this bucket, and the ones which find it empty wait for a token in
+    // the send queue (see set_max_send_rate).
+    rate_limiter: Option<Arc<Mutex<TokenBucket>>>,
+    send_queue: Option<Arc<Mutex<SendQueue<T>>>>,
+
+    // If set, the datagrams are received from the socket by a separate thread, which queues them
+    // here, with their source address, if known (see set_receive_queue).
+    receive_queue: Option<Receiver<(Vec<u8>, Option<SocketAddr>)>>,
+
+    // The sending end of the receive queue, if any, so that the threads which receive from the
+    // sockets added later can also queue their datagrams there.
+    receive_queue_sender: Option<SyncSender<(Vec<u8>, Option<SocketAddr>)>>,
+
+    // How long receive_or_idle waits for a message, or None if it blocks until one is received (see
+    // set_read_timeout).
+    read_timeout: Option<Duration>,
 
-    udp_socket_receiver: UdpSocket,
+    // The role of the node which owns this NetNode, with which the metrics of its messages are
+    // labeled (see set_role and the module telemetry).
+    #[cfg(feature = "metrics")]
+    role: &'static str,
 
     // Dummy data that is associated with the type of the value that a client initially proposes.
     value: PhantomData<T>,
@@ -27,53 +178,1033 @@ impl<T> NetNode<T>
 {
     // TODO: verify that this can be deployed on several distributed machines.
     pub fn new(multicast_address_v4: &SocketAddrV4) -> Self {
-        // Create the UdpSocket to send messages to other sockets. This socket does not have to bind
-        // to a specific port, but just to one available, hence we use 0 as the port, which is used
-        // to do that.
-        let udp_socket_sender = UdpSocket::bind("0.0.0.0:0").expect("Could not bind to address");
-
-        // TODO: do I need this?
-        udp_socket_sender.set_multicast_loop_v4(true).expect("set_multicast_loop_v4 call failed");
-
-        // Create a UdpSocket to receive messages from other sockets on the same address as the
-        // multicast group one.
-        let udp_socket_receiver = UdpBuilder::new_v4()
-            .expect("Could not construct UdpBuilder")
-            // Multiple sockets could bind to the same multicast group address, so we need this.
-            .reuse_address(true)
-            .expect("Could not reuse address")
-            // Bind the receiver socket to the same host as the multicast group.
-            .bind(multicast_address_v4)
-            .expect("Could not bind to address");
-
-        // Let the socket that wants to receive messages join its corresponding multicast group.
-        udp_socket_receiver
-            .join_multicast_v4(&multicast_address_v4.ip(), &Ipv4Addr::UNSPECIFIED)
-            .expect("Could not join multicast group");
-
-        NetNode { udp_socket_sender, udp_socket_receiver, value: PhantomData }
-    }
-
-    /// Sends the message m to the socket with address destination_address.
+        // The nodes of this process may use Unix datagram sockets instead (see
+        // unix_transport::use_unix_sockets).
+        if let Some(socket_dir) = unix_transport::socket_dir() {
+            let mut node = NetNode::with_transport(Transport::Unix(UnixTransport::bind(&socket_dir, multicast_address_v4)));
+
+            // The queue of a Unix datagram socket is much shorter than the buffer of a UDP socket,
+            // so a thread receives the datagrams as soon as they arrive.
+            node.set_receive_queue(DEFAULT_RECEIVE_QUEUE_CAPACITY);
+
+            return node;
+        }
+
+        let udp_socket_sender = bind_sender(Ipv4Addr::UNSPECIFIED);
+
+        let udp_socket_receiver = bind_receiver(multicast_address_v4);
+
+        NetNode::with_transport(Transport::Udp {
+            udp_socket_sender,
+            udp_socket_receiver,
+            udp_socket_unicast: None,
+            udp_sockets_groups: Vec::new(),
+        })
+    }
+
+    /// Creates a NetNode which sends its datagrams to the in-memory channel bus, instead of a UDP
+    /// socket. Such a NetNode cannot receive by itself: the owner of bus delivers the datagrams.
+    #[cfg(feature = "testkit")]
+    pub(crate) fn channel(bus: &Bus) -> Self {
+        NetNode::with_transport(Transport::Channel(bus.clone()))
+    }
+
+    fn with_transport(transport: Transport) -> Self {
+        // Only the datagrams sent over UDP have to fit in the MTU of the network.
+        let max_datagram_size = match transport {
+            Transport::Udp { .. } => DEFAULT_MAX_DATAGRAM_SIZE,
+            _ => RECEIVE_BUFFER_SIZE,
+        };
+
+        NetNode {
+            transport,
+            num_of_corrupted: Cell::new(0),
+            cluster_id: 0,
+            num_of_foreign: Cell::new(0),
+            max_datagram_size,
+            reassembler: RefCell::new(Reassembler::new()),
+            num_of_incomplete: Cell::new(0),
+            fragmentation: true,
+            num_of_oversized: Cell::new(0),
+            #[cfg(feature = "encryption")]
+            cipher: None,
+            #[cfg(feature = "encryption")]
+            num_of_undecryptable: Cell::new(0),
+            message_log: None,
+            rate_limiter: None,
+            send_queue: None,
+            receive_queue: None,
+            receive_queue_sender: None,
+            read_timeout: None,
+            #[cfg(feature = "metrics")]
+            role: "node",
+            value: PhantomData,
+        }
+    }
+
+    /// Makes this NetNode send its datagrams from the local address sender_address, instead of
+    /// from an address chosen by the operating system, so that the multicast datagrams leave
+    /// through the corresponding interface (e.g. on hosts with several network interfaces).
+    pub fn set_sender_address(&mut self, sender_address: Ipv4Addr) {
+        match self.transport {
+            Transport::Udp {
+                ref mut udp_socket_sender,
+                ..
+            } => *udp_socket_sender = bind_sender(sender_address),
+            Transport::Unix(_) => {}
+            #[cfg(feature = "testkit")]
+            Transport::Channel(_) => {}
+        }
+    }
+
+    /// Makes this NetNode belong to the cluster named cluster_name (e.g. a UUID or any other string),
+    /// instead of to the default one, whose name is empty. Every datagram carries the identifier of
+    /// the cluster of its sender, and the received datagrams of the other clusters (e.g. configured
+    /// on the same multicast addresses by mistake) are dropped (and counted).
+    pub fn set_cluster_id(&mut self, cluster_name: &str) {
+        self.cluster_id = hash(cluster_name.as_bytes());
+    }
+
+    /// Makes this NetNode encrypt the payload of every datagram it sends, and decrypt the ones it
+    /// receives, with AES-256-GCM, whose key is the SHA-256 hash of secret, which must thus be
+    /// hard to guess (e.g. randomly generated), and shared by all the nodes of the cluster. As the
+    /// cipher also authenticates the payloads (and the identifier of the cluster), the received
+    /// datagrams which cannot be decrypted with this key are dropped (and counted).
+    #[cfg(feature = "encryption")]
+    pub fn set_encryption_key(&mut self, secret: &str) {
+        let key = Sha256::digest(secret.as_bytes());
+        self.cipher = Some(Aes256Gcm::new(&key));
+    }
+
+    /// Makes this NetNode also receive the datagrams sent directly to unicast_address, which a
+    /// second socket binds to, so that the other nodes can send the replies addressed to this node
+    /// only there, instead of to its multicast group, where all the nodes of its role receive them.
+    /// As a thread is needed to receive from each socket, this also creates the receive queue, if
+    /// there is none yet (see set_receive_queue).
+    pub fn set_unicast_address(&mut self, unicast_address: SocketAddrV4) {
+        match self.transport {
+            Transport::Udp {
+                ref mut udp_socket_unicast,
+                ..
+            } => {
+                *udp_socket_unicast = Some(
+                    UdpSocket::bind(unicast_address).expect("Could not bind to the unicast address"),
+                );
+            }
+            Transport::Unix(ref mut unix_transport) => unix_transport.bind_unicast(&unicast_address),
+            #[cfg(feature = "testkit")]
+            Transport::Channel(_) => return,
+        }
+
+        match self.receive_queue_sender {
+            Some(ref sender) => {
+                let udp_socket_unicast = self.unicast_socket().expect("Expected a unicast socket");
+                spawn_receiver(udp_socket_unicast, sender.clone());
+            }
+            None => self.set_receive_queue(DEFAULT_RECEIVE_QUEUE_CAPACITY),
+        }
+    }
+
+    /// Makes this NetNode also receive the datagrams sent to the multicast group multicast_address_v4,
+    /// which a further socket joins, besides the multicast group of its role, e.g. so that the
+    /// proposers receive the client requests in a group of their own (see
+    /// Proposer::with_requests_address). The port of multicast_address_v4 must differ from the one
+    /// of the other groups of this NetNode: as the sockets bind to all the interfaces of this host,
+    /// those which share a port receive the datagrams of each other's groups, and so the kernel
+    /// would not filter them out. As a thread is needed to receive from each socket, this also
+    /// creates the receive queue, if there is none yet (see set_receive_queue).
+    pub fn join_multicast_group(&mut self, multicast_address_v4: SocketAddrV4) {
+        match self.transport {
+            Transport::Udp {
+                ref mut udp_sockets_groups,
+                ..
+            } => udp_sockets_groups.push(bind_receiver(&multicast_address_v4)),
+            Transport::Unix(ref mut unix_transport) => unix_transport.join(&multicast_address_v4),
+            #[cfg(feature = "testkit")]
+            Transport::Channel(_) => return,
+        }
+
+        match self.receive_queue_sender {
+            Some(ref sender) => {
+                let udp_socket_group = self.group_sockets().pop().expect("Expected a group socket");
+                spawn_receiver(udp_socket_group, sender.clone());
+            }
+            None => self.set_receive_queue(DEFAULT_RECEIVE_QUEUE_CAPACITY),
+        }
+    }
+
+    /// Starts writing every message sent or received by this NetNode to message_log.
+    pub fn set_message_log(&mut self, message_log: MessageLog) {
+        self.message_log = Some(Arc::new(Mutex::new(message_log)));
+    }
+
+    /// Limits the rate of the messages sent by this NetNode (and by the NetNodes which share its
+    /// sockets) to rate messages per second, on average, with bursts of at most burst messages.
+    /// The messages sent beyond this rate wait for a token in a queue of at most burst messages,
+    /// where the replies of the acceptors and the Learning messages come first (see SendQueue),
+    /// and the ones which do not fit are dropped (and counted), as if they had been lost, so that
+    /// a misbehaving node cannot flood the multicast groups of the cluster. The essential messages
+    /// (see is_essential) are always sent at once, at the expense of the other ones. The queue is
+    /// flushed as the tokens are added, while this NetNode sends or waits for messages.
+    pub fn set_max_send_rate(&mut self, rate: u32, burst: usize) {
+        self.rate_limiter = Some(Arc::new(Mutex::new(TokenBucket::new(rate, burst))));
+        self.send_queue = Some(Arc::new(Mutex::new(SendQueue::new(burst))));
+    }
+
+    /// Makes this NetNode send the messages which do not fit in a datagram of max_datagram_size
+    /// bytes (e.g. because their values are large) in several datagrams, i.e. in fragments, which
+    /// the receivers reassemble, instead of in a single one, e.g. to fit in the MTU of the network.
+    /// The default is DEFAULT_MAX_DATAGRAM_SIZE over UDP, and, over the other transports, which
+    /// have no MTU, the size of the buffer which the datagrams are received into, which is the
+    /// largest size allowed. If a fragment is lost, the whole message is dropped, as if it had been
+    /// sent in a single datagram, so the large messages are more likely to be lost (see the module
+    /// fragmentation).
+    ///
+    /// Panics if max_datagram_size is too small to carry a fragment, or larger than the receive
+    /// buffer.
+    pub fn set_max_datagram_size(&mut self, max_datagram_size: usize) {
+        assert!(
+            (MIN_DATAGRAM_SIZE..=RECEIVE_BUFFER_SIZE).contains(&max_datagram_size),
+            "Expected a maximum datagram size from {} to {} bytes",
+            MIN_DATAGRAM_SIZE,
+            RECEIVE_BUFFER_SIZE
+        );
+        self.max_datagram_size = max_datagram_size;
+    }
+
+    /// Makes this NetNode fragment the messages which do not fit in a datagram (see
+    /// set_max_datagram_size), if enabled, which is the default, or otherwise refuse to send them,
+    /// e.g. if the receivers may not reassemble them. A refused message is dropped (and counted),
+    /// and send logs it, with its size, as an error (see try_send).
+    pub fn set_fragmentation(&mut self, enabled: bool) {
+        self.fragmentation = enabled;
+    }
+
+    /// Returns the number of messages which this NetNode refused to send, because they did not fit
+    /// in a datagram, and the fragmentation was disabled (see set_fragmentation).
+    pub fn num_of_oversized(&self) -> usize {
+        self.num_of_oversized.get()
+    }
+
+    /// Returns the number of received datagrams which this NetNode dropped, because their checksum
+    /// did not match their payload (i.e. they were corrupted in transit).
+    pub fn num_of_corrupted(&self) -> usize {
+        self.num_of_corrupted.get()
+    }
+
+    /// Returns the number of received datagrams which this NetNode dropped, because they were sent
+    /// by the nodes of another cluster (see set_cluster_id).
+    pub fn num_of_foreign(&self) -> usize {
+        self.num_of_foreign.get()
+    }
+
+    /// Returns the number of received datagrams which this NetNode dropped, because it could not
+    /// decrypt them (see set_encryption_key).
+    #[cfg(feature = "encryption")]
+    pub fn num_of_undecryptable(&self) -> usize {
+        self.num_of_undecryptable.get()
+    }
+
+    /// Returns this NetNode, whose metrics of the messages sent and received are labeled with role
+    /// (see the module telemetry).
+    #[cfg(feature = "metrics")]
+    pub(crate) fn with_role(mut self, role: &'static str) -> Self {
+        self.role = role;
+        self
+    }
+
+    /// Makes receive_or_idle give up waiting for a message once timeout has elapsed, or, if timeout
+    /// is None, wait until a message is received, which is the default. The nodes set it before
+    /// each wait, to the time left before their periodic maintenance is due.
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) {
+        self.read_timeout = timeout;
+    }
+
+    /// Makes a separate thread receive the datagrams from the socket, as soon as they arrive, and
+    /// queue them (up to capacity datagrams) until they are handled, i.e. returned by
+    /// receive_timeout, so that a slow handler does not make the socket drop the datagrams which
+    /// arrive in the meantime. The datagrams which arrive while the queue is full are dropped (and counted).
+    pub fn set_receive_queue(&mut self, capacity: usize) {
+        let socket_receiver: Box<dyn ReceiverSocket> = match self.transport {
+            Transport::Udp {
+                ref udp_socket_receiver,
+                ..
+            } => Box::new(udp_socket_receiver
+                .try_clone()
+                .expect("Could not clone the receiver socket")),
+            Transport::Unix(ref unix_transport) => Box::new(unix_transport
+                .receiver
+                .try_clone()
+                .expect("Could not clone the receiver socket")),
+            #[cfg(feature = "testkit")]
+            Transport::Channel(_) => return,
+        };
+
+        let (sender, receiver) = sync_channel(capacity);
+
+        spawn_receiver(socket_receiver, sender.clone());
+
+        if let Some(socket_unicast) = self.unicast_socket() {
+            spawn_receiver(socket_unicast, sender.clone());
+        }
+
+        for socket_group in self.group_sockets() {
+            spawn_receiver(socket_group, sender.clone());
+        }
+
+        self.receive_queue = Some(receiver);
+        self.receive_queue_sender = Some(sender);
+    }
+
+    /// Returns a clone of the unicast socket, if any (see set_unicast_address).
+    fn unicast_socket(&self) -> Option<Box<dyn ReceiverSocket>> {
+        match self.transport {
+            Transport::Udp {
+                udp_socket_unicast: Some(ref udp_socket_unicast),
+                ..
+            } => Some(Box::new(
+                udp_socket_unicast
+                    .try_clone()
+                    .expect("Could not clone the unicast socket"),
+            )),
+            Transport::Unix(UnixTransport {
+                unicast: Some(ref unix_socket_unicast),
+                ..
+            }) => Some(Box::new(
+                unix_socket_unicast
+                    .try_clone()
+                    .expect("Could not clone the unicast socket"),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Returns clones of the sockets of the other multicast groups joined by this NetNode, in the
+    /// order they were joined (see join_multicast_group).
+    fn group_sockets(&self) -> Vec<Box<dyn ReceiverSocket>> {
+        match self.transport {
+            Transport::Udp {
+                ref udp_sockets_groups,
+                ..
+            } => udp_sockets_groups
+                .iter()
+                .map(|udp_socket_group| -> Box<dyn ReceiverSocket> {
+                    Box::new(udp_socket_group.try_clone().expect("Could not clone the group socket"))
+                })
+                .collect(),
+            Transport::Unix(ref unix_transport) => unix_transport
+                .groups
+                .iter()
+                .map(|unix_socket_group| -> Box<dyn ReceiverSocket> {
+                    Box::new(unix_socket_group.try_clone().expect("Could not clone the group socket"))
+                })
+                .collect(),
+            #[cfg(feature = "testkit")]
+            Transport::Channel(_) => Vec::new(),
+        }
+    }
+
+    /// Returns a NetNode which uses the same sockets (or in-memory channel) and message log as this
+    /// one, so that several nodes hosted by the same process can share them (see CoLocated). The
+    /// receive queue, if any, is not shared.
+    pub(crate) fn share(&self) -> Self {
+        let transport = match self.transport {
+            Transport::Udp {
+                ref udp_socket_sender,
+                ref udp_socket_receiver,
+                ref udp_socket_unicast,
+                ref udp_sockets_groups,
+            } => Transport::Udp {
+                udp_socket_sender: udp_socket_sender
+                    .try_clone()
+                    .expect("Could not share the sender socket"),
+                udp_socket_receiver: udp_socket_receiver
+                    .try_clone()
+                    .expect("Could not share the receiver socket"),
+                udp_socket_unicast: udp_socket_unicast.as_ref().map(|udp_socket_unicast| {
+                    udp_socket_unicast
+                        .try_clone()
+                        .expect("Could not share the unicast socket")
+                }),
+                udp_sockets_groups: udp_sockets_groups
+                    .iter()
+                    .map(|udp_socket_group| udp_socket_group.try_clone().expect("Could not share the group socket"))
+                    .collect(),
+            },
+            Transport::Unix(ref unix_transport) => {
+                Transport::Unix(unix_transport.try_clone().expect("Could not share the Unix sockets"))
+            }
+            #[cfg(feature = "testkit")]
+            Transport::Channel(ref bus) => Transport::Channel(bus.clone()),
+        };
+
+        NetNode {
+            transport,
+            num_of_corrupted: Cell::new(0),
+            cluster_id: self.cluster_id,
+            num_of_foreign: Cell::new(0),
+            max_datagram_size: self.max_datagram_size,
+            reassembler: RefCell::new(Reassembler::new()),
+            num_of_incomplete: Cell::new(0),
+            fragmentation: self.fragmentation,
+            num_of_oversized: Cell::new(0),
+            #[cfg(feature = "encryption")]
+            cipher: self.cipher.clone(),
+            #[cfg(feature = "encryption")]
+            num_of_undecryptable: Cell::new(0),
+            message_log: self.message_log.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            send_queue: self.send_queue.clone(),
+            receive_queue: None,
+            receive_queue_sender: None,
+            read_timeout: None,
+            #[cfg(feature = "metrics")]
+            role: self.role,
+            value: PhantomData,
+        }
+    }
+
+    /// Sends the message m to the socket with address destination_address. The identifier of the
+    /// cluster of this NetNode is prepended to the serialized (and, if this NetNode has an
+    /// encryption key, encrypted) message, and a CRC32 checksum of both is appended to the
+    /// datagram, so that the receiver can detect corruption. The messages which are refused because
+    /// they do not fit in a datagram (see try_send) are logged as errors.
     pub fn send(&self, m: Message<T>, destination_address: &SocketAddrV4) {
-        let encoded: Vec<u8> = serialize(&m).expect("Could not serialize the message m");
+        if let Err(e) = self.try_send(m, destination_address) {
+            error!("Could not send a message to {:?}: {}.", destination_address, e);
+        }
+    }
+
+    /// Sends the message m as send does, unless it does not fit in a datagram, and the
+    /// fragmentation is disabled (see set_fragmentation), in which case m is dropped (and counted),
+    /// and the error identifies m and its size. The size of m is checked before it is queued, if
+    /// this NetNode has a maximum send rate (see set_max_send_rate).
+    pub fn try_send(&self, m: Message<T>, destination_address: &SocketAddrV4) -> Result<(), OversizedMessage> {
+        let size = self.datagram_size(&m);
+        if size > self.max_datagram_size && !self.fragmentation {
+            self.num_of_oversized.set(self.num_of_oversized.get() + 1);
+            #[cfg(feature = "metrics")]
+            telemetry::datagram_dropped(self.role, "oversized");
+
+            return Err(OversizedMessage {
+                message: format!("{:?}", Summarized(&m)),
+                size,
+                max_datagram_size: self.max_datagram_size,
+            });
+        }
+
+        let (rate_limiter, send_queue) = match (&self.rate_limiter, &self.send_queue) {
+            (Some(rate_limiter), Some(send_queue)) => (rate_limiter, send_queue),
+            _ => {
+                self.transmit(m, destination_address);
+                return Ok(());
+            }
+        };
+
+        if is_essential(&m) {
+            rate_limiter.lock().unwrap().acquire(true);
+            self.transmit(m, destination_address);
+            return Ok(());
+        }
+
+        let dropped = send_queue.lock().unwrap().push(m, *destination_address);
+        if let Some((dropped, destination_address)) = dropped {
+            warn!(
+                "Dropped {:?} to {:?}: the maximum send rate is exceeded ({:?} dropped so far).",
+                dropped,
+                destination_address,
+                send_queue.lock().unwrap().num_of_dropped()
+            );
+        }
+
+        self.flush_send_queue();
 
-        self.udp_socket_sender
-            .send_to(&encoded[..], destination_address)
-            .expect("Could not send data");
+        Ok(())
+    }
+
+    /// Returns the size of the datagram which would carry the message m, if it were sent in a single
+    /// one (see transmit).
+    fn datagram_size(&self, m: &Message<T>) -> usize {
+        #[allow(unused_mut)]
+        let mut size = serialized_size(m).expect("Could not serialize the message m") as usize;
+
+        #[cfg(feature = "encryption")]
+        {
+            if self.cipher.is_some() {
+                size += NONCE_SIZE + TAG_SIZE;
+            }
+        }
+
+        CLUSTER_ID_SIZE + size + CHECKSUM_SIZE
+    }
+
+    /// Sends the queued messages (see set_max_send_rate) for which tokens are available.
+    fn flush_send_queue(&self) {
+        if let (Some(rate_limiter), Some(send_queue)) = (&self.rate_limiter, &self.send_queue) {
+            while !send_queue.lock().unwrap().is_empty()
+                && rate_limiter.lock().unwrap().acquire(false)
+            {
+                let queued = send_queue.lock().unwrap().pop();
+
+                if let Some((m, destination_address)) = queued {
+                    self.transmit(m, &destination_address);
+                }
+            }
+        }
+    }
+
+    /// Returns how long the first queued message (see set_max_send_rate), if any, has to wait for
+    /// a token.
+    fn send_queue_delay(&self) -> Option<Duration> {
+        match (&self.rate_limiter, &self.send_queue) {
+            (Some(rate_limiter), Some(send_queue)) if !send_queue.lock().unwrap().is_empty() => {
+                Some(rate_limiter.lock().unwrap().time_to_next_token())
+            }
+            _ => None,
+        }
+    }
+
+    /// Sends the message m right away, i.e. regardless of the maximum send rate, if any.
+    fn transmit(&self, m: Message<T>, destination_address: &SocketAddrV4) {
+        if let Some(ref message_log) = self.message_log {
+            message_log.lock().unwrap().record(Direction::Sent, &m);
+        }
+
+        #[cfg(feature = "metrics")]
+        telemetry::message_sent(self.role, &m);
+
+        let sealed = self.seal(serialize(&m).expect("Could not serialize the message m"));
+
+        if CLUSTER_ID_SIZE + sealed.len() + CHECKSUM_SIZE <= self.max_datagram_size {
+            return self.transmit_datagram(self.envelope(&sealed), destination_address);
+        }
+
+        let chunk_size = self.max_datagram_size - MIN_DATAGRAM_SIZE + 1;
+        for fragment in fragmentation::fragment(&sealed, chunk_size) {
+            self.transmit_datagram(self.envelope(&fragment), destination_address);
+        }
+    }
+
+    /// Returns the datagram which carries payload, i.e. payload preceded by the identifier of the
+    /// cluster of this NetNode, and followed by the CRC32 checksum of both.
+    fn envelope(&self, payload: &[u8]) -> Vec<u8> {
+        let mut encoded: Vec<u8> = self.cluster_id.to_le_bytes().to_vec();
+        encoded.extend_from_slice(payload);
+        let checksum = hash(&encoded);
+        encoded.extend_from_slice(&checksum.to_le_bytes());
+        encoded
+    }
+
+    /// Sends encoded, a datagram (see envelope), to the socket with address destination_address.
+    fn transmit_datagram(&self, encoded: Vec<u8>, destination_address: &SocketAddrV4) {
+        match self.transport {
+            Transport::Udp {
+                ref udp_socket_sender,
+                ..
+            } => {
+                udp_socket_sender
+                    .send_to(&encoded[..], destination_address)
+                    .expect("Could not send data");
+            }
+            Transport::Unix(ref unix_transport) => unix_transport.send_to(&encoded, destination_address),
+            #[cfg(feature = "testkit")]
+            Transport::Channel(ref bus) => {
+                bus.lock()
+                    .unwrap()
+                    .push_back((*destination_address, encoded));
+            }
+        }
     }
 
     /// Receives a message using the socket which listens on the address multicast_address_v4, given
-    /// as parameter to the new function.
-    pub fn receive(&self) -> Message<T> {
-        // TODO: what's the required size of data_received?
-        let mut data_received = vec![0; 16384];
+    /// as parameter to the new function, or the unicast socket, if any (see set_unicast_address),
+    /// whichever receives a datagram first. Datagrams whose checksum does not match their payload, or
+    /// which were sent by the nodes of another cluster, are dropped (and counted), so this function
+    /// only returns once an intact message of this cluster is received, or, if timeout is not None,
+    /// once timeout has elapsed without receiving any, in which case it returns None. Meanwhile, the
+    /// messages which wait for tokens in the send queue (see set_max_send_rate) are sent as soon as
+    /// the tokens are available.
+    pub fn receive_timeout(&self, timeout: Option<Duration>) -> Option<Message<T>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        loop {
+            self.flush_send_queue();
+
+            let remaining = deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            let delay = self.send_queue_delay();
+
+            // A timeout of zero is not accepted by the sockets.
+            let wait = match (remaining, delay) {
+                (Some(remaining), Some(delay)) => Some(remaining.min(delay)),
+                (remaining, None) => remaining,
+                (None, delay) => delay,
+            }
+            .map(|wait| wait.max(MIN_RECEIVE_TIMEOUT));
+
+            if let Some(m) = self.receive_within(wait) {
+                return Some(m);
+            }
+
+            if delay.is_none() || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return None;
+            }
+        }
+    }
+
+    /// Like receive_timeout, with the read timeout of this NetNode (see set_read_timeout), i.e. it
+    /// returns None if this NetNode is idle, so that its owner can run its periodic maintenance.
+    pub fn receive_or_idle(&self) -> Option<Message<T>> {
+        self.receive_timeout(self.read_timeout)
+    }
+
+    /// Like receive_timeout, but regardless of the send queue.
+    fn receive_within(&self, timeout: Option<Duration>) -> Option<Message<T>> {
+        if let Some(ref receive_queue) = self.receive_queue {
+            loop {
+                let received = match timeout {
+                    Some(timeout) => match receive_queue.recv_timeout(timeout) {
+                        Ok(received) => received,
+                        Err(RecvTimeoutError::Timeout) => return None,
+                        Err(RecvTimeoutError::Disconnected) => {
+                            panic!("The thread which receives the datagrams is gone")
+                        }
+                    },
+                    None => receive_queue
+                        .recv()
+                        .expect("The thread which receives the datagrams is gone"),
+                };
+
+                let (datagram, src_addr) = received;
+
+                if let Some(m) = self.decode_from(&datagram, src_addr) {
+                    return Some(m);
+                }
+            }
+        }
+
+        let socket_receiver: &dyn ReceiverSocket = match self.transport {
+            Transport::Udp {
+                ref udp_socket_receiver,
+                ..
+            } => udp_socket_receiver,
+            Transport::Unix(ref unix_transport) => &unix_transport.receiver,
+            #[cfg(feature = "testkit")]
+            Transport::Channel(_) => {
+                panic!("A NetNode over a channel cannot receive: its datagrams are delivered")
+            }
+        };
+
+        socket_receiver
+            .set_read_timeout(timeout)
+            .expect("Could not set the read timeout");
+
+        let mut data_received = vec![0; RECEIVE_BUFFER_SIZE];
+
+        loop {
+            let (number_of_bytes, src_addr) = match socket_receiver.receive_from(&mut data_received)
+            {
+                Ok(received) => received,
+                Err(ref e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {
+                    return None;
+                }
+                Err(e) => panic!("Could not receive data: {}", e),
+            };
+
+            if let Some(m) = self.decode_from(&data_received[..number_of_bytes], src_addr) {
+                return Some(m);
+            }
+        }
+    }
+
+    /// Returns the message contained in the received datagram, or None if the datagram is
+    /// corrupted, or if it was sent by a node of another cluster, in which case it is counted.
+    #[cfg(feature = "testkit")]
+    pub fn decode(&self, datagram: &[u8]) -> Option<Message<T>> {
+        self.decode_from(datagram, None)
+    }
+
+    /// Like decode, but src_addr, if known, is the address which the datagram was received from.
+    fn decode_from(&self, datagram: &[u8], src_addr: Option<SocketAddr>) -> Option<Message<T>> {
+        let payload = match split_envelope(datagram) {
+            Some((cluster_id, payload)) if cluster_id == self.cluster_id => payload,
+            Some((cluster_id, _)) => {
+                self.num_of_foreign.set(self.num_of_foreign.get() + 1);
+                #[cfg(feature = "metrics")]
+                telemetry::datagram_dropped(self.role, "foreign");
+                warn!(
+                    "Dropped a datagram from {:?} of the foreign cluster {:?} (mine is {:?}): is \
+                     another cluster configured on the same addresses? ({:?} dropped so far).",
+                    src_addr,
+                    cluster_id,
+                    self.cluster_id,
+                    self.num_of_foreign.get()
+                );
+                return None;
+            }
+            None => return self.drop_corrupted(src_addr),
+        };
+
+        // The fragments are only decoded once their message is reassembled.
+        let reassembled;
+        let payload = if fragmentation::is_fragment(payload) {
+            reassembled = self.reassemble(payload, src_addr)?;
+            &reassembled[..]
+        } else {
+            payload
+        };
+
+        #[cfg(feature = "encryption")]
+        let opened = match self.open(payload) {
+            Some(opened) => opened,
+            None => {
+                self.num_of_undecryptable.set(self.num_of_undecryptable.get() + 1);
+                #[cfg(feature = "metrics")]
+                telemetry::datagram_dropped(self.role, "undecryptable");
+                warn!(
+                    "Dropped a datagram from {:?} which could not be decrypted: was it encrypted \
+                     with another key? ({:?} dropped so far).",
+                    src_addr,
+                    self.num_of_undecryptable.get()
+                );
+                return None;
+            }
+        };
+        #[cfg(feature = "encryption")]
+        let payload: &[u8] = &opened;
+
+        let m = match deserialize(payload) {
+            Ok(m) => m,
+            Err(_) => return self.drop_corrupted(src_addr),
+        };
+
+        if let Some(ref message_log) = self.message_log {
+            message_log.lock().unwrap().record(Direction::Received, &m);
+        }
+
+        #[cfg(feature = "metrics")]
+        telemetry::message_received(self.role, &m);
+
+        Some(m)
+    }
+
+    /// Adds the fragment received from src_addr to its message, and returns the message, if it is
+    /// complete. The messages whose fragments were lost meanwhile are dropped (and counted).
+    fn reassemble(&self, fragment: &[u8], src_addr: Option<SocketAddr>) -> Option<Vec<u8>> {
+        let now = Instant::now();
+        let mut reassembler = self.reassembler.borrow_mut();
+
+        let num_of_expired = reassembler.expire(now);
+        if num_of_expired > 0 {
+            self.num_of_incomplete.set(self.num_of_incomplete.get() + num_of_expired);
+            #[cfg(feature = "metrics")]
+            for _ in 0..num_of_expired {
+                telemetry::datagram_dropped(self.role, "incomplete");
+            }
+            warn!(
+                "Dropped {:?} messages whose fragments were lost ({:?} dropped so far).",
+                num_of_expired,
+                self.num_of_incomplete.get()
+            );
+        }
+
+        match reassembler.add(fragment, now) {
+            Reassembly::Complete(payload) => Some(payload),
+            Reassembly::Incomplete => None,
+            Reassembly::Malformed => {
+                self.drop_corrupted(src_addr);
+                None
+            }
+        }
+    }
+
+    /// Counts the corrupted datagram received from src_addr, which is dropped.
+    fn drop_corrupted(&self, src_addr: Option<SocketAddr>) -> Option<Message<T>> {
+        self.num_of_corrupted.set(self.num_of_corrupted.get() + 1);
+        #[cfg(feature = "metrics")]
+        telemetry::datagram_dropped(self.role, "corrupted");
+        warn!(
+            "Dropped a corrupted datagram from {:?} ({:?} dropped so far).",
+            src_addr,
+            self.num_of_corrupted.get()
+        );
+        None
+    }
+
+    /// Returns payload encrypted, preceded by the random nonce used, if this NetNode has an
+    /// encryption key (see set_encryption_key), otherwise payload itself. The identifier of the
+    /// cluster is authenticated together with payload.
+    fn seal(&self, payload: Vec<u8>) -> Vec<u8> {
+        #[cfg(feature = "encryption")]
+        {
+            if let Some(ref cipher) = self.cipher {
+                // The sealed payload must not be taken for a fragment.
+                let nonce: [u8; NONCE_SIZE] = loop {
+                    let nonce: [u8; NONCE_SIZE] = rand::random();
+                    if !fragmentation::is_fragment(&nonce) {
+                        break nonce;
+                    }
+                };
+                let payload = Payload {
+                    msg: &payload,
+                    aad: &self.cluster_id.to_le_bytes(),
+                };
+
+                let mut sealed = nonce.to_vec();
+                sealed.extend(
+                    cipher
+                        .encrypt(Nonce::from_slice(&nonce), payload)
+                        .expect("Could not encrypt the message"),
+                );
+                return sealed;
+            }
+        }
+
+        payload
+    }
+
+    /// Returns sealed decrypted (see seal), if this NetNode has an encryption key, otherwise
+    /// sealed itself, or None if it cannot be decrypted (or authenticated).
+    #[cfg(feature = "encryption")]
+    fn open<'a>(&self, sealed: &'a [u8]) -> Option<Cow<'a, [u8]>> {
+        let cipher = match self.cipher {
+            Some(ref cipher) => cipher,
+            None => return Some(Cow::Borrowed(sealed)),
+        };
+
+        if sealed.len() < NONCE_SIZE {
+            return None;
+        }
+
+        let (nonce, ciphertext) = sealed.split_at(NONCE_SIZE);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: &self.cluster_id.to_le_bytes(),
+        };
+
+        cipher
+            .decrypt(Nonce::from_slice(nonce), payload)
+            .ok()
+            .map(Cow::Owned)
+    }
+}
+
+/// A message which a NetNode refused to send, because it does not fit in a datagram, and the
+/// fragmentation is disabled (see NetNode::set_fragmentation).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OversizedMessage {
+    /// The summary of the message, i.e. its phase, its instance and its round (see the module
+    /// verbosity).
+    pub message: String,
+
+    /// The size of the datagram which would carry the message, in bytes.
+    pub size: usize,
+
+    /// The maximum size of the datagrams of the NetNode (see NetNode::set_max_datagram_size).
+    pub max_datagram_size: usize,
+}
+
+impl Display for OversizedMessage {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} takes {} bytes, more than the maximum datagram size of {} bytes, and the fragmentation is disabled",
+            self.message, self.size, self.max_datagram_size
+        )
+    }
+}
+
+impl Error for OversizedMessage {}
+
+/// A socket which a NetNode receives its datagrams from, i.e. a UDP socket or a Unix datagram
+/// socket (see the module unix_transport).
+trait ReceiverSocket: Send {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+
+    /// Receives a datagram into buf, and returns its size and, if known, its source address (the
+    /// sockets which send to the Unix datagram sockets are not bound).
+    fn receive_from(&self, buf: &mut [u8]) -> io::Result<(usize, Option<SocketAddr>)>;
+}
+
+impl ReceiverSocket for UdpSocket {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        UdpSocket::set_read_timeout(self, timeout)
+    }
+
+    fn receive_from(&self, buf: &mut [u8]) -> io::Result<(usize, Option<SocketAddr>)> {
+        self.recv_from(buf).map(|(number_of_bytes, src_addr)| (number_of_bytes, Some(src_addr)))
+    }
+}
+
+impl ReceiverSocket for UnixDatagram {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        UnixDatagram::set_read_timeout(self, timeout)
+    }
+
+    fn receive_from(&self, buf: &mut [u8]) -> io::Result<(usize, Option<SocketAddr>)> {
+        self.recv(buf).map(|number_of_bytes| (number_of_bytes, None))
+    }
+}
+
+/// Spawns a thread which receives the datagrams from socket_receiver, as soon as they arrive, and
+/// queues them, with their source address, if known, to sender, until the receiving end of the
+/// queue is gone. The datagrams which arrive while the queue is full are dropped (and counted).
+fn spawn_receiver(socket_receiver: Box<dyn ReceiverSocket>, sender: SyncSender<(Vec<u8>, Option<SocketAddr>)>) {
+    socket_receiver
+        .set_read_timeout(None)
+        .expect("Could not set the read timeout");
+
+    thread::spawn(move || {
+        let mut data_received = vec![0; RECEIVE_BUFFER_SIZE];
+        let mut num_of_dropped: usize = 0;
+
+        loop {
+            let (number_of_bytes, src_addr) = socket_receiver
+                .receive_from(&mut data_received)
+                .expect("Could not receive data");
+
+            match sender.try_send((data_received[..number_of_bytes].to_vec(), src_addr)) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    num_of_dropped += 1;
+                    warn!(
+                        "Dropped a datagram from {:?}: the receive queue is full ({:?} dropped so far).",
+                        src_addr, num_of_dropped
+                    );
+                }
+                // The NetNode which handles the queued datagrams is gone.
+                Err(TrySendError::Disconnected(_)) => return,
+            }
+        }
+    });
+}
+
+/// Creates the sockets which a NetNode would use to receive from multicast_address_v4, and to send
+/// from sender_address (see set_sender_address) and receive at unicast_address (see
+/// set_unicast_address), if given, and closes them right away, to check the network setup of a
+/// node before starting it. Unlike them, it returns the error of the first socket which cannot be
+/// created (e.g. because its address is already in use, or because it is not permitted to join the
+/// multicast group), instead of panicking.
+pub(crate) fn check_sockets(
+    multicast_address_v4: &SocketAddrV4,
+    sender_address: Option<Ipv4Addr>,
+    unicast_address: Option<SocketAddrV4>,
+) -> io::Result<()> {
+    // Prepends what the socket could not do to the description of the error.
+    let context = |what: String| move |e: io::Error| io::Error::new(e.kind(), format!("{}: {}", what, e));
+
+    let sender_address = sender_address.unwrap_or(Ipv4Addr::UNSPECIFIED);
+    let udp_socket_sender = UdpSocket::bind(SocketAddrV4::new(sender_address, 0))
+        .map_err(context(format!("Could not bind the sender socket to {}", sender_address)))?;
+
+    if !sender_address.is_unspecified() {
+        udp_socket_sender
+            .set_multicast_if_v4(&sender_address)
+            .map_err(context(format!("Could not send the multicast datagrams from {}", sender_address)))?;
+    }
+
+    let receiver_address = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, multicast_address_v4.port());
+    let udp_socket_receiver = UdpBuilder::new_v4()
+        .and_then(|builder| {
+            builder.reuse_address(true)?;
+            builder.bind(receiver_address)
+        })
+        .map_err(context(format!("Could not bind the receiver socket to {}", receiver_address)))?;
+
+    udp_socket_receiver
+        .join_multicast_v4(multicast_address_v4.ip(), &Ipv4Addr::UNSPECIFIED)
+        .map_err(context(format!("Could not join the multicast group {}", multicast_address_v4.ip())))?;
+
+    if let Some(unicast_address) = unicast_address {
+        UdpSocket::bind(unicast_address)
+            .map_err(context(format!("Could not bind the unicast socket to {}", unicast_address)))?;
+    }
+
+    Ok(())
+}
+
+/// Returns whether m enables or announces a decision (i.e. it is an Acceptance or a Learning
+/// message), so that it is sent even if the maximum send rate is exceeded (see
+/// NetNode::set_max_send_rate): dropping it would waste the rounds which led to it, and trigger
+/// more retransmissions.
+fn is_essential<T>(m: &Message<T>) -> bool {
+    matches!(m, Message::Phase2b(_) | Message::Phase3(_))
+}
+
+/// Creates the UdpSocket to receive the messages sent to the multicast group multicast_address_v4,
+/// and joins it to the group.
+fn bind_receiver(multicast_address_v4: &SocketAddrV4) -> UdpSocket {
+    // Create a UdpSocket to receive messages from other sockets on the same port as the
+    // multicast group one. The socket binds to all the interfaces of this host (rather than to
+    // the multicast address), so that it also receives the replies which the other nodes send
+    // directly to this node (e.g. to its advertised address, when it is behind a NAT).
+    let udp_socket_receiver = UdpBuilder::new_v4()
+        .expect("Could not construct UdpBuilder")
+        // Multiple sockets could bind to the same multicast group address, so we need this.
+        .reuse_address(true)
+        .expect("Could not reuse address")
+        .bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, multicast_address_v4.port()))
+        .expect("Could not bind to address");
+
+    // Let the socket that wants to receive messages join its corresponding multicast group.
+    udp_socket_receiver
+        .join_multicast_v4(multicast_address_v4.ip(), &Ipv4Addr::UNSPECIFIED)
+        .expect("Could not join multicast group");
+
+    udp_socket_receiver
+}
+
+/// Creates the UdpSocket to send messages to other sockets, bound to the local address
+/// sender_address (which can be unspecified, i.e. 0.0.0.0).
+fn bind_sender(sender_address: Ipv4Addr) -> UdpSocket {
+    // This socket does not have to bind to a specific port, but just to one available, hence we use
+    // 0 as the port, which is used to do that.
+    let udp_socket_sender = UdpSocket::bind(SocketAddrV4::new(sender_address, 0))
+        .expect("Could not bind to address");
+
+    // TODO: do I need this?
+    udp_socket_sender.set_multicast_loop_v4(true).expect("set_multicast_loop_v4 call failed");
+
+    // The multicast datagrams must leave through the interface of sender_address.
+    if !sender_address.is_unspecified() {
+        udp_socket_sender
+            .set_multicast_if_v4(&sender_address)
+            .expect("set_multicast_if_v4 call failed");
+    }
+
+    udp_socket_sender
+}
+
+/// Returns the message contained in the (unencrypted) datagram, or None if the datagram is
+/// corrupted.
+#[cfg(feature = "testkit")]
+pub(crate) fn decode_datagram<T: DeserializeOwned>(datagram: &[u8]) -> Option<Message<T>> {
+    split_envelope(datagram).and_then(|(_, payload)| deserialize(payload).ok())
+}
+
+/// Returns the identifier of the cluster of the sender of the datagram and the (serialized, and
+/// possibly encrypted) message contained in it, or None if the datagram is corrupted.
+fn split_envelope(datagram: &[u8]) -> Option<(u32, &[u8])> {
+    match verify_checksum(datagram) {
+        Some(payload) if payload.len() >= CLUSTER_ID_SIZE => {
+            let (cluster_id, payload) = payload.split_at(CLUSTER_ID_SIZE);
+            Some((u32::from_le_bytes(cluster_id.try_into().unwrap()), payload))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the payload of the datagram, if its trailing CRC32 checksum matches it, otherwise None.
+fn verify_checksum(datagram: &[u8]) -> Option<&[u8]> {
+    if datagram.len() < CHECKSUM_SIZE {
+        return None;
+    }
 
-        let (number_of_bytes, _src_addr) = self
-            .udp_socket_receiver
-            .recv_from(&mut data_received)
-            .expect("Could not receive data");
+    let (payload, checksum) = datagram.split_at(datagram.len() - CHECKSUM_SIZE);
+    let checksum = u32::from_le_bytes(checksum.try_into().unwrap());
 
-        deserialize(&data_received[..number_of_bytes]).expect("Could not deserialize received data")
+    if hash(payload) == checksum {
+        Some(payload)
+    } else {
+        None
     }
 }
@@ -0,0 +1,187 @@
+//! A module which contains the definition of a Pinger, which measures the round-trip time (RTT) to
+//! the nodes of a role, using Ping and Pong messages. This is independent of the Paxos algorithm,
+//! but it allows to distinguish slow nodes from a network which drops packets (e.g. when some
+//! instances get stuck).
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::net::SocketAddrV4;
+use std::time::{Duration, Instant};
+
+use log::Level;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::message::{Message, Ping, Pong};
+use crate::net_node::NetNode;
+
+/// The round-trip times measured to a node.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RttStats {
+    // The number of Pong messages received from the node.
+    pub num_of_pongs: usize,
+
+    pub last: Duration,
+
+    pub min: Duration,
+
+    pub max: Duration,
+
+    // The average of all the round-trip times measured so far.
+    pub avg: Duration,
+}
+
+impl RttStats {
+    fn new(rtt: Duration) -> Self {
+        RttStats {
+            num_of_pongs: 1,
+            last: rtt,
+            min: rtt,
+            max: rtt,
+            avg: rtt,
+        }
+    }
+
+    fn record(&mut self, rtt: Duration) {
+        self.num_of_pongs += 1;
+        self.last = rtt;
+        self.min = self.min.min(rtt);
+        self.max = self.max.max(rtt);
+        self.avg = (self.avg * (self.num_of_pongs - 1) as u32 + rtt) / self.num_of_pongs as u32;
+    }
+}
+
+/// A node which sends Ping messages to the nodes of a role and measures the round-trip times from
+/// the Pong messages they send back.
+pub struct Pinger<T> {
+    uuid: Uuid,
+
+    // The Paxos group whose nodes are probed (by default, 0).
+    group_id: usize,
+
+    // The number of Ping messages sent so far. It is also the nonce of the last one.
+    num_of_pings: u64,
+
+    // The round-trip times measured so far, keyed by the unique identifier of the probed node.
+    rtts: HashMap<Uuid, RttStats>,
+
+    node: NetNode<T>,
+
+    // The address which this pinger listens on, where the Pong messages are sent to.
+    address: SocketAddrV4,
+}
+
+impl<T> Pinger<T>
+where
+    T: Serialize + DeserializeOwned + Clone + Debug,
+{
+    pub fn new(address: SocketAddrV4) -> Self {
+        Pinger {
+            uuid: Uuid::new_v4(),
+            group_id: 0,
+            num_of_pings: 0,
+            rtts: HashMap::new(),
+            node: NetNode::new(&address),
+            address,
+        }
+    }
+
+    /// Makes this pinger probe the nodes of the Paxos group group_id (by default, 0).
+    pub fn with_group_id(mut self, group_id: usize) -> Self {
+        self.group_id = group_id;
+        self
+    }
+
+    /// Makes this pinger belong to the cluster named cluster_name (see NetNode::set_cluster_id),
+    /// since only the nodes of its own cluster answer.
+    pub fn with_cluster_id(mut self, cluster_name: &str) -> Self {
+        self.node.set_cluster_id(cluster_name);
+        self
+    }
+
+    /// Makes this pinger encrypt its messages with a key derived from secret (see
+    /// NetNode::set_encryption_key), since only the nodes with the same key can decrypt them.
+    #[cfg(feature = "encryption")]
+    pub fn with_encryption_key(mut self, secret: &str) -> Self {
+        self.node.set_encryption_key(secret);
+        self
+    }
+
+    /// Sends a Ping message to the nodes listening on destination_address and waits, for timeout,
+    /// for their Pong messages. Returns the round-trip times measured to the nodes which answered.
+    pub fn ping(
+        &mut self,
+        destination_address: SocketAddrV4,
+        timeout: Duration,
+    ) -> HashMap<Uuid, Duration> {
+        self.num_of_pings += 1;
+        let nonce = self.num_of_pings;
+
+        let m = Message::Ping::<T>(Ping {
+            nonce,
+            reply_address: self.address,
+            sender_uuid: self.uuid,
+            group_id: self.group_id,
+        });
+
+        let start = Instant::now();
+        self.node.send(m, &destination_address);
+
+        let mut rtts = HashMap::new();
+
+        while let Some(remaining) = timeout.checked_sub(start.elapsed()) {
+            // Note that a timeout of zero is not allowed.
+            let m = self
+                .node
+                .receive_timeout(Some(remaining.max(Duration::from_millis(1))));
+
+            if let Some(Message::Pong(pong)) = m {
+                if pong.nonce == nonce && pong.receiver_uuid == self.uuid {
+                    rtts.insert(pong.sender_uuid, start.elapsed());
+                }
+            }
+        }
+
+        for (&uuid, &rtt) in &rtts {
+            self.rtts
+                .entry(uuid)
+                .and_modify(|stats| stats.record(rtt))
+                .or_insert_with(|| RttStats::new(rtt));
+        }
+
+        rtts
+    }
+
+    /// Returns the number of Ping messages sent so far. Comparing it with the number of Pong
+    /// messages received from a node gives an estimate of the packet loss to that node.
+    pub fn num_of_pings(&self) -> u64 {
+        self.num_of_pings
+    }
+
+    /// Returns the round-trip times measured so far, keyed by the unique identifier of the probed
+    /// node.
+    pub fn rtts(&self) -> &HashMap<Uuid, RttStats> {
+        &self.rtts
+    }
+}
+
+/// Answers the Ping message ping, received by the node with the given unique identifier, with a
+/// Pong message.
+pub(crate) fn pong<T>(node: &NetNode<T>, uuid: Uuid, ping: Ping)
+where
+    T: Serialize + DeserializeOwned + Clone + Debug,
+{
+    let m = Message::Pong::<T>(Pong {
+        nonce: ping.nonce,
+        sender_uuid: uuid,
+        receiver_uuid: ping.sender_uuid,
+        group_id: ping.group_id,
+    });
+
+    if log_enabled!(Level::Info) {
+        info!("I will send {:?}.", m);
+    }
+
+    node.send(m, &ping.reply_address);
+}
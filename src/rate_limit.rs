@@ -0,0 +1,173 @@
+//! A module which contains the definition of a token bucket, which limits the rate of the messages
+//! sent by a node (see NetNode::set_max_send_rate), so that a misbehaving node (e.g. a buggy client,
+//! or a node caught in a retry storm) cannot flood the multicast groups shared by the cluster, and
+//! of the queue which holds the messages waiting for a token, by priority.
+
+use std::collections::VecDeque;
+use std::net::SocketAddrV4;
+use std::time::{Duration, Instant};
+
+use crate::message::Message;
+
+/// A token bucket, which holds at most burst tokens, and is refilled with rate tokens per second.
+/// Each sent message takes a token, so, on average, at most rate messages are sent per second,
+/// with bursts of at most burst messages.
+pub(crate) struct TokenBucket {
+    rate: f64,
+    burst: f64,
+
+    // The tokens available when the bucket was refilled last. It is negative while the essential
+    // messages sent when the bucket was empty are paid back (see acquire).
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    /// Creates a full token bucket.
+    pub(crate) fn new(rate: u32, burst: usize) -> Self {
+        assert!(rate > 0, "Expected a positive rate");
+        assert!(burst > 0, "Expected a positive burst");
+
+        TokenBucket {
+            rate: f64::from(rate),
+            burst: burst as f64,
+            tokens: burst as f64,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    /// Takes a token for a message, if one is available, and returns whether the message can be
+    /// sent. If the message is essential, it is sent anyway, and its token is paid back with the
+    /// next ones (up to burst tokens), at the expense of the messages which are not essential.
+    pub(crate) fn acquire(&mut self, essential: bool) -> bool {
+        self.refill();
+
+        if self.tokens >= 1.0 || essential {
+            self.tokens = (self.tokens - 1.0).max(-self.burst);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns how long it takes for a token to be available.
+    pub(crate) fn time_to_next_token(&mut self) -> Duration {
+        self.refill();
+
+        Duration::from_secs_f64(((1.0 - self.tokens) / self.rate).max(0.0))
+    }
+
+    /// Adds the tokens accumulated since the bucket was refilled last.
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.refilled_at = now;
+    }
+}
+
+/// The priority of a message which waits for a token (see SendQueue), from the lowest to the
+/// highest.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Priority {
+    /// The messages which start (a round of) an instance, i.e. the Preparation and Proposal
+    /// messages: delaying them only delays the new work.
+    Initiating,
+
+    /// The other messages (e.g. the requests of the clients, or the messages of the catch-up).
+    Normal,
+
+    /// The replies of the acceptors to the proposers, and the Learning messages, which let the
+    /// started instances finish.
+    Finishing,
+}
+
+impl Priority {
+    /// Returns the priority of m.
+    pub(crate) fn of<T>(m: &Message<T>) -> Self {
+        match m {
+            Message::Phase1a(_) | Message::Phase2a(_) | Message::TailPreparation(_) => {
+                Priority::Initiating
+            }
+            Message::Phase1b(_)
+            | Message::Phase1c(_)
+            | Message::TailPromise(_)
+            | Message::Phase2b(_)
+            | Message::Phase3(_) => Priority::Finishing,
+            _ => Priority::Normal,
+        }
+    }
+}
+
+/// The messages of a rate-limited node which wait for a token, with their destinations. They are
+/// sent by decreasing priority, and in the order they were queued within a priority, so that, under
+/// load, the instances in flight keep progressing instead of being starved by new ones. The queue
+/// holds at most capacity messages: beyond that, the newest message of the lowest priority is
+/// dropped (and counted), as if it had been lost.
+pub(crate) struct SendQueue<T> {
+    // The queued messages, by priority (see Priority), oldest first.
+    tiers: [VecDeque<(Message<T>, SocketAddrV4)>; 3],
+    capacity: usize,
+
+    // The number of messages which have not been sent, because the queue was full.
+    num_of_dropped: usize,
+}
+
+impl<T> SendQueue<T> {
+    /// Creates an empty queue, which holds at most capacity messages.
+    pub(crate) fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Expected a positive capacity");
+
+        SendQueue {
+            tiers: [VecDeque::new(), VecDeque::new(), VecDeque::new()],
+            capacity,
+            num_of_dropped: 0,
+        }
+    }
+
+    /// Queues m, to be sent to destination_address, and returns the message which is dropped to
+    /// make room for it (possibly m itself), if the queue is full.
+    pub(crate) fn push(
+        &mut self,
+        m: Message<T>,
+        destination_address: SocketAddrV4,
+    ) -> Option<(Message<T>, SocketAddrV4)> {
+        let priority = Priority::of(&m);
+        let mut dropped = None;
+
+        if self.len() >= self.capacity {
+            self.num_of_dropped += 1;
+
+            match self.tiers.iter().position(|tier| !tier.is_empty()) {
+                Some(lowest) if lowest < priority as usize => {
+                    dropped = self.tiers[lowest].pop_back();
+                }
+                _ => return Some((m, destination_address)),
+            }
+        }
+
+        self.tiers[priority as usize].push_back((m, destination_address));
+        dropped
+    }
+
+    /// Removes and returns the oldest message of the highest priority, if any.
+    pub(crate) fn pop(&mut self) -> Option<(Message<T>, SocketAddrV4)> {
+        self.tiers.iter_mut().rev().find_map(VecDeque::pop_front)
+    }
+
+    /// Returns the number of queued messages.
+    pub(crate) fn len(&self) -> usize {
+        self.tiers.iter().map(VecDeque::len).sum()
+    }
+
+    /// Returns true if no message is queued.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.tiers.iter().all(VecDeque::is_empty)
+    }
+
+    /// Returns the number of messages which have not been sent so far, because the queue was full.
+    pub(crate) fn num_of_dropped(&self) -> usize {
+        self.num_of_dropped
+    }
+}
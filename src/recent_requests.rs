@@ -0,0 +1,72 @@
+//! A module which contains the definition of the cache of the client requests which a proposer
+//! recently handled, so that it recognizes (and ignores) their duplicates, e.g. the retransmissions
+//! of a client, instead of proposing their values again.
+//!
+//! The cache is bounded both in time and in size: a request is forgotten once it was handled more
+//! than window ago, or once capacity more recent requests were handled. A duplicate which arrives
+//! after its request was forgotten is thus treated as a new request, and its value may be decided
+//! twice. The window must therefore be longer than the time during which a client may send the same
+//! request again (e.g. its retransmission timeout, times the number of its attempts), and the
+//! capacity larger than the number of requests which the proposers handle meanwhile.
+
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::message::RequestId;
+
+/// The requests handled at most window ago, up to capacity of them.
+pub(crate) struct RecentRequests {
+    window: Duration,
+    capacity: usize,
+
+    // The cached requests, for fast lookup.
+    requests: HashSet<RequestId>,
+
+    // The cached requests, in the order they were handled, with the time they were handled.
+    handled_at: VecDeque<(RequestId, Instant)>,
+}
+
+impl RecentRequests {
+    pub(crate) fn new(window: Duration, capacity: usize) -> Self {
+        assert!(capacity > 0, "Expected a positive capacity");
+
+        RecentRequests {
+            window,
+            capacity,
+            requests: HashSet::new(),
+            handled_at: VecDeque::new(),
+        }
+    }
+
+    /// Returns whether the request id is cached, i.e. whether it was handled recently (provided
+    /// that the expired requests have been evicted, see evict).
+    pub(crate) fn contains(&self, id: &RequestId) -> bool {
+        self.requests.contains(id)
+    }
+
+    /// Caches the request id, which was handled at now. The requests beyond capacity are only
+    /// evicted by the next call to evict.
+    pub(crate) fn insert(&mut self, id: RequestId, now: Instant) {
+        if self.requests.insert(id) {
+            self.handled_at.push_back((id, now));
+        }
+    }
+
+    /// Evicts the requests handled more than window before now, and the oldest ones beyond
+    /// capacity, and returns them.
+    pub(crate) fn evict(&mut self, now: Instant) -> Vec<RequestId> {
+        let mut evicted = Vec::new();
+
+        while let Some(&(id, handled_at)) = self.handled_at.front() {
+            if self.handled_at.len() <= self.capacity && now - handled_at <= self.window {
+                break;
+            }
+
+            self.handled_at.pop_front();
+            self.requests.remove(&id);
+            evicted.push(id);
+        }
+
+        evicted
+    }
+}
@@ -0,0 +1,88 @@
+//! A module which contains the definition of a cache of the replies which an acceptor recently
+//! sent, so that it can answer the retransmissions of the same request (e.g. a Proposal sent again
+//! by a proposer whose timeout expired) without processing them again, i.e. without storing its
+//! state and building the reply again.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddrV4;
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+use crate::message::{Ballot, InstanceId, Message};
+
+/// The kind of a request which an acceptor answers.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum RequestKind {
+    Preparation,
+    Proposal,
+}
+
+/// A request which an acceptor answers, identified by its kind, its instance, its round (c_rnd)
+/// and the unique identifier of the proposer which sent it.
+pub(crate) type RequestKey = (RequestKind, InstanceId, Ballot, Uuid);
+
+/// The replies sent for the requests received at most window ago, up to capacity of them.
+pub(crate) struct ReplyCache<T> {
+    window: Duration,
+    capacity: usize,
+
+    // The reply sent for each cached request, and its destination address.
+    replies: HashMap<RequestKey, (Message<T>, SocketAddrV4)>,
+
+    // The cached requests, in the order they were received, with the time they were received.
+    received_at: VecDeque<(RequestKey, Instant)>,
+}
+
+impl<T: Clone> ReplyCache<T> {
+    pub(crate) fn new(window: Duration, capacity: usize) -> Self {
+        assert!(capacity > 0, "Expected a positive capacity");
+
+        ReplyCache {
+            window,
+            capacity,
+            replies: HashMap::new(),
+            received_at: VecDeque::new(),
+        }
+    }
+
+    /// Returns the reply sent for the request key, and its destination address, if the request was
+    /// received at most window ago (and not evicted since).
+    pub(crate) fn get(&mut self, key: &RequestKey) -> Option<(Message<T>, SocketAddrV4)> {
+        self.evict(Instant::now());
+        self.replies.get(key).cloned()
+    }
+
+    /// Caches the reply sent for the request key to destination_address, evicting the oldest
+    /// cached requests, if needed.
+    pub(crate) fn insert(
+        &mut self,
+        key: RequestKey,
+        reply: Message<T>,
+        destination_address: SocketAddrV4,
+    ) {
+        let now = Instant::now();
+
+        if self
+            .replies
+            .insert(key, (reply, destination_address))
+            .is_none()
+        {
+            self.received_at.push_back((key, now));
+        }
+
+        self.evict(now);
+    }
+
+    /// Evicts the requests received more than window ago, and the oldest ones beyond capacity.
+    fn evict(&mut self, now: Instant) {
+        while let Some(&(key, received_at)) = self.received_at.front() {
+            if self.received_at.len() <= self.capacity && now - received_at <= self.window {
+                break;
+            }
+
+            self.received_at.pop_front();
+            self.replies.remove(&key);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! A module which contains the definition of a ReplicatedStateMachine, which embeds a learner in an
+//! application, and applies the decided values, in total order, to a state of the application
+//! (e.g. a counter or a key-value store). All the replicas built the same way, from learners of the
+//! same cluster and the same initial state, go through the same states, provided that the apply
+//! function is deterministic.
+//!
+//! The state is only kept in memory: a replica which is restarted must start again from the
+//! initial state, and its learner must deliver the log again from the instance 0, so it must not be
+//! given a delivery watermark (see Learner::with_delivery_watermark), which would skip the values
+//! delivered before the restart.
+
+use std::fmt::Debug;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::message::Message;
+use crate::multi_paxos::{Learner, Runnable};
+
+/// The function which applies a decided value to the state of a replicated state machine.
+pub type Apply<S, T> = Box<dyn FnMut(&mut S, &T)>;
+
+/// A state machine, replicated with Multi-Paxos, i.e. a learner whose delivered values are applied,
+/// one after the other, in the order of their instances, to a state, instead of being printed.
+pub struct ReplicatedStateMachine<S, T> {
+    learner: Learner<T>,
+
+    state: S,
+
+    apply: Apply<S, T>,
+
+    // The instance of the last value applied to the state, if any.
+    last_applied: Option<usize>,
+}
+
+impl<S, T> ReplicatedStateMachine<S, T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    /// Creates a state machine, whose initial state is state, and which applies the values
+    /// delivered by learner with apply. The values are always applied in total order, without
+    /// gaps, whatever the delivery policy of learner is, so learner should use
+    /// DeliveryPolicy::TotalOrder (with another policy, its deliveries would be reordered).
+    pub fn new(mut learner: Learner<T>, state: S, apply: Apply<S, T>) -> Self {
+        learner.feed_state_machine();
+
+        ReplicatedStateMachine {
+            learner,
+            state,
+            apply,
+            last_applied: None,
+        }
+    }
+
+    /// Returns the current state, i.e. the initial state to which all the decided values of the
+    /// instances up to last_applied have been applied.
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    /// Returns the instance of the last value applied to the state, if any.
+    pub fn last_applied(&self) -> Option<usize> {
+        self.last_applied
+    }
+
+    /// Returns the embedded learner, e.g. to inspect its learned values.
+    pub fn learner(&self) -> &Learner<T> {
+        &self.learner
+    }
+
+    /// Handles the given message with the embedded learner, and applies the values which this
+    /// made deliverable, if any.
+    pub fn handle(&mut self, m: Message<T>) {
+        self.learner.handle(m);
+        self.apply_delivered();
+    }
+
+    /// Applies the values delivered by the embedded learner since the last call to the state.
+    fn apply_delivered(&mut self) {
+        apply_delivered(
+            &mut self.learner,
+            &mut self.state,
+            &mut self.apply,
+            &mut self.last_applied,
+        );
+    }
+}
+
+/// Applies the values delivered by learner since the last call to state, with apply, and records
+/// the instance of the last of them in last_applied.
+fn apply_delivered<S, T>(
+    learner: &mut Learner<T>,
+    state: &mut S,
+    apply: &mut Apply<S, T>,
+    last_applied: &mut Option<usize>,
+) where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    for (instance, v) in learner.take_to_apply() {
+        apply(state, &v);
+        *last_applied = Some(instance);
+    }
+}
+
+impl<S, T> Runnable for ReplicatedStateMachine<S, T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    /// Runs the embedded learner, and applies the values which it delivers as soon as they are
+    /// deliverable.
+    fn run(&mut self) {
+        let ReplicatedStateMachine {
+            learner,
+            state,
+            apply,
+            last_applied,
+        } = self;
+
+        learner.run_with(|learner| apply_delivered(learner, state, apply, last_applied));
+    }
+}
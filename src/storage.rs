@@ -0,0 +1,290 @@
+//! A module which contains the definition of a simple durable storage, where a node can append
+//! records describing changes of its state, so that it can rebuild that state after a restart.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Cursor, Read, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use bincode::{deserialize_from, serialize, serialize_into, ErrorKind};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// An append-only file of records of type R.
+pub struct Storage<R> {
+    file: File,
+
+    // Dummy data that is associated with the type of the records.
+    record: PhantomData<R>,
+}
+
+impl<R> Storage<R>
+where
+    R: Serialize + DeserializeOwned,
+{
+    /// Opens (or creates, if it does not exist) the file at path and returns the storage, together
+    /// with the records previously appended to it, in the order they were appended.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<(Self, Vec<R>)> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+
+        let mut reader = Cursor::new(&contents[..]);
+        let mut records = Vec::new();
+        let mut end_of_last_record = 0;
+
+        loop {
+            match deserialize_from(&mut reader) {
+                Ok(record) => {
+                    records.push(record);
+                    end_of_last_record = reader.position();
+                }
+                Err(e) => match *e {
+                    // The end of the file has been reached. The last record may have been only
+                    // partially written (e.g. because of a crash), but, in that case, it was never
+                    // acted upon, so it is discarded, so that the next records are appended right
+                    // after the last complete one.
+                    ErrorKind::Io(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                    _ => return Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+                },
+            }
+        }
+
+        file.set_len(end_of_last_record)?;
+
+        let storage = Storage {
+            file,
+            record: PhantomData,
+        };
+
+        Ok((storage, records))
+    }
+
+    /// Durably appends record to this storage, that is, this function only returns after the
+    /// record has been written to the disk.
+    pub fn append(&mut self, record: &R) -> io::Result<()> {
+        let encoded = serialize(record).map_err(io::Error::other)?;
+        self.file.write_all(&encoded)?;
+        self.file.sync_data()
+    }
+}
+
+/// The durably stored delivery watermark of a learner, that is, the last instance whose learned
+/// value was delivered (e.g. printed or applied to a state machine), so that, after a restart, the
+/// learner resumes the delivery strictly after it, and does not deliver any value twice.
+pub struct DeliveryWatermark {
+    storage: Storage<usize>,
+
+    last_delivered: usize,
+}
+
+impl DeliveryWatermark {
+    /// Opens (or creates, if it does not exist) the file at path and returns the watermark stored
+    /// there, which is 0 (i.e. nothing was delivered yet) if the file is new.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let (storage, records) = Storage::open(path)?;
+        let last_delivered = records.into_iter().max().unwrap_or(0);
+
+        Ok(DeliveryWatermark {
+            storage,
+            last_delivered,
+        })
+    }
+
+    /// Returns the last instance whose learned value was delivered, or 0 if none was.
+    pub fn last_delivered(&self) -> usize {
+        self.last_delivered
+    }
+
+    /// Durably moves the watermark to instance, which must be the one right after the last
+    /// delivered instance, so that no instance is skipped.
+    pub fn advance(&mut self, instance: usize) -> io::Result<()> {
+        assert_eq!(
+            instance,
+            self.last_delivered + 1,
+            "Bug: the delivery watermark must advance one instance at a time."
+        );
+
+        self.storage.append(&instance)?;
+        self.last_delivered = instance;
+
+        Ok(())
+    }
+
+    /// Durably moves the watermark forward to instance, as if the learned values of the instances
+    /// up to it had been delivered (see Learner::tail_from).
+    pub fn skip_to(&mut self, instance: usize) -> io::Result<()> {
+        assert!(
+            instance > self.last_delivered,
+            "Bug: the delivery watermark can only be moved forward."
+        );
+
+        self.storage.append(&instance)?;
+        self.last_delivered = instance;
+
+        Ok(())
+    }
+}
+
+/// The durably stored generation of a proposer, that is, the number of times it has started, so
+/// that the rounds which it starts can be made higher than the ones it started before a restart
+/// (see RoundStrategy::Generation). The highest generation whose rounds the proposer used (e.g.
+/// because it was preempted by a proposer of a later generation) is stored too, and the generation
+/// of the next start is always above it.
+pub struct GenerationCounter {
+    storage: Storage<usize>,
+
+    generation: usize,
+
+    highest_used: usize,
+}
+
+impl GenerationCounter {
+    /// Opens (or creates, if it does not exist) the file at path and durably increments the
+    /// generation stored there, i.e. the next generation is above every generation stored so far.
+    /// The generation is 1 if the file is new.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let (mut storage, records): (_, Vec<usize>) = Storage::open(path)?;
+
+        let generation = records
+            .into_iter()
+            .max()
+            .unwrap_or(0)
+            .checked_add(1)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Generation overflow"))?;
+
+        storage.append(&generation)?;
+
+        Ok(GenerationCounter {
+            storage,
+            generation,
+            highest_used: generation,
+        })
+    }
+
+    /// Returns the generation of this start.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Durably records that the rounds of generation (which may be later than the one of this
+    /// start) were used, so that the generation of the next start is above it.
+    pub fn record_used(&mut self, generation: usize) -> io::Result<()> {
+        if generation > self.highest_used {
+            self.storage.append(&generation)?;
+            self.highest_used = generation;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes log, i.e. decided values keyed by their instances (e.g. exported with
+/// Proposer::export_log), to the file at path, which is created (or truncated), as a sequence of
+/// (instance, value) records, in increasing order of instance, so that it can be read back with
+/// read_log (e.g. to restore it with Proposer::preload_log and Acceptor::preload_log). This
+/// function only returns after the log has been written to the disk.
+pub fn write_log<T, P>(path: P, log: &BTreeMap<usize, T>) -> io::Result<()>
+where
+    T: Serialize,
+    P: AsRef<Path>,
+{
+    let mut encoded = Vec::new();
+
+    for record in log {
+        serialize_into(&mut encoded, &record).map_err(io::Error::other)?;
+    }
+
+    let mut file = File::create(path)?;
+    file.write_all(&encoded)?;
+    file.sync_all()
+}
+
+/// Reads the log which was written to the file at path by write_log. Unlike the records of a
+/// storage, a record which was only partially written is an error, as the log would be incomplete.
+pub fn read_log<T, P>(path: P) -> io::Result<BTreeMap<usize, T>>
+where
+    T: DeserializeOwned,
+    P: AsRef<Path>,
+{
+    let contents = fs::read(path)?;
+    let mut reader = Cursor::new(&contents[..]);
+    let mut log = BTreeMap::new();
+
+    while (reader.position() as usize) < contents.len() {
+        let (instance, value) = deserialize_from(&mut reader)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        log.insert(instance, value);
+    }
+
+    Ok(log)
+}
+
+/// Two different values found for the same instance in two logs (see merge_logs), i.e. a
+/// violation of the agreement of the learners.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Conflict<T> {
+    // The instance which has two different values.
+    pub instance: usize,
+
+    // The value of the instance in the first log.
+    pub ours: T,
+
+    // The value of the instance in the second log.
+    pub theirs: T,
+}
+
+impl<T: Debug> Display for Conflict<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "instance {} was decided as {:?} and as {:?}",
+            self.instance, self.ours, self.theirs
+        )
+    }
+}
+
+/// Merges two logs, i.e. decided values keyed by their instances (e.g. exported with
+/// Learner::export_log by different learners, or read with read_log), into a log which contains
+/// the values of the instances of both of them. As any two learners must agree on the value of an
+/// instance, the logs must have the same value in the instances which they both contain: if they
+/// do not, the conflicts, in increasing order of instance, are returned instead. This can be used
+/// to combine the logs of different nodes, or to pinpoint the instances where they diverge, after
+/// the fact.
+pub fn merge_logs<T>(
+    ours: &BTreeMap<usize, T>,
+    theirs: &BTreeMap<usize, T>,
+) -> Result<BTreeMap<usize, T>, Vec<Conflict<T>>>
+where
+    T: Clone + PartialEq,
+{
+    let mut merged = ours.clone();
+    let mut conflicts = Vec::new();
+
+    for (&instance, theirs) in theirs {
+        match merged.get(&instance) {
+            Some(ours) if ours != theirs => conflicts.push(Conflict {
+                instance,
+                ours: ours.clone(),
+                theirs: theirs.clone(),
+            }),
+            Some(_) => {}
+            None => {
+                merged.insert(instance, theirs.clone());
+            }
+        }
+    }
+
+    if conflicts.is_empty() {
+        Ok(merged)
+    } else {
+        Err(conflicts)
+    }
+}
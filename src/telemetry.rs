@@ -0,0 +1,129 @@
+//! A module which contains the definition of the metrics emitted by the nodes, with the feature
+//! `metrics`, through the facade of the crate metrics. The metrics are recorded by the recorder
+//! which the application installs (e.g. an exporter to Prometheus or statsd), if any, otherwise
+//! they are discarded.
+//!
+//! The metrics are labeled with the role of the node (`client`, `proposer`, `acceptor`, `learner`
+//! or `colocated`, for the messages received by a CoLocated node) and, for the messages, with their
+//! phase, i.e. the name of their kind on the wire (e.g. `preparation` or `learning`, see the module
+//! message). The instances are not labels, as their number grows forever, which most backends do
+//! not support: the last decided (or delivered) instance is a gauge instead.
+
+use std::time::Duration;
+
+use metrics::{counter, gauge, histogram};
+
+use crate::load::MemoryReport;
+use crate::message::Message;
+
+/// The number of the messages sent, labeled with role and phase.
+pub const MESSAGES_SENT: &str = "multi_paxos_messages_sent_total";
+
+/// The number of the messages received (and not dropped), labeled with role and phase.
+pub const MESSAGES_RECEIVED: &str = "multi_paxos_messages_received_total";
+
+/// The number of the received datagrams which were dropped, labeled with role and reason, i.e.
+/// `corrupted`, `foreign` (sent by another cluster) or `undecryptable`, or of the received messages
+/// whose fragments were lost, with the reason `incomplete`.
+pub const DATAGRAMS_DROPPED: &str = "multi_paxos_datagrams_dropped_total";
+
+/// The number of the instances whose value a proposer learned was chosen, labeled with role.
+pub const DECISIONS: &str = "multi_paxos_decisions_total";
+
+/// The last instance whose value a proposer learned was chosen, labeled with role.
+pub const DECIDED_INSTANCE: &str = "multi_paxos_decided_instance";
+
+/// The time, in seconds, a proposer took to decide the instances it started, from the Preparation
+/// message to the Acceptance messages of a majority of the acceptors, labeled with role.
+pub const DECISION_LATENCY: &str = "multi_paxos_decision_latency_seconds";
+
+/// The number of the instances where a proposer starved, i.e. where its last rounds were all
+/// preempted (see Proposer::with_starvation_policy), labeled with role.
+pub const STARVED_INSTANCES: &str = "multi_paxos_starved_instances_total";
+
+/// The number of the instances whose state an acceptor tracks, labeled with role.
+pub const TRACKED_INSTANCES: &str = "multi_paxos_tracked_instances";
+
+/// The estimated number of bytes held by the state of the instances which an acceptor tracks (see
+/// Acceptor::memory_report), labeled with role.
+pub const TRACKED_BYTES: &str = "multi_paxos_tracked_bytes";
+
+/// The number of the values delivered by a learner, labeled with role.
+pub const DELIVERIES: &str = "multi_paxos_deliveries_total";
+
+/// The last instance whose value a learner delivered, labeled with role.
+pub const DELIVERED_INSTANCE: &str = "multi_paxos_delivered_instance";
+
+/// Records that the node with the given role sent m.
+pub(crate) fn message_sent<T>(role: &'static str, m: &Message<T>) {
+    counter!(MESSAGES_SENT, "role" => role, "phase" => phase(m)).increment(1);
+}
+
+/// Records that the node with the given role received m.
+pub(crate) fn message_received<T>(role: &'static str, m: &Message<T>) {
+    counter!(MESSAGES_RECEIVED, "role" => role, "phase" => phase(m)).increment(1);
+}
+
+/// Records that the node with the given role dropped a datagram, for the given reason.
+pub(crate) fn datagram_dropped(role: &'static str, reason: &'static str) {
+    counter!(DATAGRAMS_DROPPED, "role" => role, "reason" => reason).increment(1);
+}
+
+/// Records that the node with the given role learned the decision of instance, which it took
+/// latency to decide, if it started it.
+pub(crate) fn value_decided(role: &'static str, instance: usize, latency: Option<Duration>) {
+    counter!(DECISIONS, "role" => role).increment(1);
+    gauge!(DECIDED_INSTANCE, "role" => role).set(instance as f64);
+
+    if let Some(latency) = latency {
+        histogram!(DECISION_LATENCY, "role" => role).record(latency.as_secs_f64());
+    }
+}
+
+/// Records that the node with the given role started to starve in an instance.
+pub(crate) fn instance_starved(role: &'static str) {
+    counter!(STARVED_INSTANCES, "role" => role).increment(1);
+}
+
+/// Records the memory report of the acceptor with the given role.
+pub(crate) fn memory_reported(role: &'static str, report: MemoryReport) {
+    gauge!(TRACKED_INSTANCES, "role" => role).set(report.num_of_instances as f64);
+    gauge!(TRACKED_BYTES, "role" => role).set(report.estimated_bytes as f64);
+}
+
+/// Records that the node with the given role delivered the value of instance.
+pub(crate) fn value_delivered(role: &'static str, instance: usize) {
+    counter!(DELIVERIES, "role" => role).increment(1);
+    gauge!(DELIVERED_INSTANCE, "role" => role).set(instance as f64);
+}
+
+/// Returns the phase of m, i.e. the name of its kind on the wire.
+fn phase<T>(m: &Message<T>) -> &'static str {
+    match m {
+        Message::Phase0a(_) => "request",
+        Message::Phase0b(_) => "catch_up",
+        Message::Phase0c(_) => "report",
+        Message::Phase1a(_) => "preparation",
+        Message::Phase1b(_) => "promise",
+        Message::Phase1c(_) => "nack",
+        Message::Phase2a(_) => "proposal",
+        Message::Phase2b(_) => "acceptance",
+        Message::Phase3(_) => "learning",
+        Message::NoopRequest(_) => "noop_request",
+        Message::GossipSummary(_) => "gossip_summary",
+        Message::GossipValues(_) => "gossip_values",
+        Message::Decided(_) => "decided",
+        Message::Ping(_) => "ping",
+        Message::Pong(_) => "pong",
+        Message::ValueRequest(_) => "value_request",
+        Message::ValueReply(_) => "value_reply",
+        Message::Rejected(_) => "rejected",
+        Message::LearnAck(_) => "learn_ack",
+        Message::Progress(_) => "progress",
+        Message::Truncation(_) => "truncation",
+        Message::TailPreparation(_) => "tail_preparation",
+        Message::TailPromise(_) => "tail_promise",
+        Message::TruncationRequest(_) => "truncation_request",
+        Message::CancelRequest(_) => "cancel_request",
+    }
+}
@@ -0,0 +1,928 @@
+//! A module which contains helpers to write deterministic (i.e. non-flaky) tests of the Multi-Paxos
+//! algorithm. Instead of UDP sockets, the nodes of a simulated Cluster exchange their messages over
+//! an in-memory channel, and the messages are delivered one at a time, in the order they were sent,
+//! by explicitly calling Cluster::step.
+//!
+//! The messages delivered in a Cluster can be recorded by a TraceCollector (see
+//! Cluster::with_trace_collector), so that tests can assert the exact message trace of a run.
+//!
+//! All the randomness of a Cluster (e.g. the unique identifiers of its nodes) is derived from a
+//! single seed, so that a failing test can be replayed exactly by creating the Cluster with the same
+//! seed (see Cluster::with_seed).
+//!
+//! The messages can also be delivered out of order, or dropped, at random (see Cluster::step_random).
+//! The Choices made so form a schedule, which can be replayed exactly (see Cluster::step_with) and,
+//! if it makes a test fail, shrunk to a minimal one which still does (see minimize_schedule).
+//!
+//! The test scripts among the examples print the outcome of each of their tests with report, which
+//! is the output kept by tests/run_example.sh.
+//!
+//! This module is only available if the feature testkit is enabled.
+
+use std::collections::{HashSet, VecDeque};
+use std::env;
+use std::fmt::Debug;
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use uuid::{Builder, Uuid, Variant, Version};
+
+use crate::message::Message;
+use crate::multi_paxos::{
+    Acceptor, Client, DeliveryPolicy, Learner, LearningMode, Observer, OnDecision, Proposer,
+};
+use crate::net_node::{decode_datagram, Bus, NetNode};
+use crate::state_machine::{Apply, ReplicatedStateMachine};
+
+/// The seed of the clusters created with Cluster::new.
+const DEFAULT_SEED: u64 = 0;
+
+/// The interval of the retransmissions of the Learning messages which the learners did not
+/// acknowledge, in the clusters with a checkpoint (see Cluster::with_checkpoint). It is long
+/// enough for the messages not to be sent again while a test runs, unless it asks for it.
+const LEARNING_RETRANSMIT_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// The roles whose nodes receive messages, i.e. the possible destinations of a message.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Role {
+    Clients,
+    Proposers,
+    Acceptors,
+    Learners,
+}
+
+/// What a Cluster does at a step (see Cluster::step_with). The index of a pending message is its
+/// position among the pending messages, from the oldest (0) to the newest, modulo their number, so
+/// that any sequence of choices (i.e. any schedule) can be replayed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Choice {
+    /// Delivers the pending message with the given index.
+    Deliver(usize),
+
+    /// Drops the pending message with the given index, without delivering it.
+    Drop(usize),
+}
+
+/// A message delivered in a Cluster, as recorded by a TraceCollector.
+#[derive(Debug, Clone)]
+pub struct TraceEntry<T> {
+    // The unique identifier of the node which sent the message.
+    pub sender_uuid: Uuid,
+
+    // The role of the nodes which the message was delivered to.
+    pub receiver: Role,
+
+    // The message itself. Its variant is the phase of the algorithm which it belongs to.
+    pub message: Message<T>,
+}
+
+/// The messages delivered in a Cluster, in delivery order. Corrupted datagrams are not recorded.
+pub struct TraceCollector<T> {
+    entries: Vec<TraceEntry<T>>,
+}
+
+impl<T> TraceCollector<T> {
+    pub fn entries(&self) -> &[TraceEntry<T>] {
+        &self.entries
+    }
+
+    /// Returns the recorded messages which satisfy predicate, in delivery order. For example,
+    /// `filter(|e| matches!(e.message, Message::Phase2a(ref p) if p.instance == InstanceId(1)))`
+    /// returns the Proposal messages of instance 1.
+    pub fn filter<P>(&self, predicate: P) -> Vec<&TraceEntry<T>>
+    where
+        P: Fn(&TraceEntry<T>) -> bool,
+    {
+        self.entries.iter().filter(|e| predicate(e)).collect()
+    }
+
+    /// Returns the number of recorded messages which satisfy predicate.
+    pub fn count<P>(&self, predicate: P) -> usize
+    where
+        P: Fn(&TraceEntry<T>) -> bool,
+    {
+        self.entries.iter().filter(|e| predicate(e)).count()
+    }
+
+    /// Returns the position, in delivery order, of the first recorded message which satisfies
+    /// predicate, if any. This can be used to assert that a message was delivered before another.
+    pub fn position<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: Fn(&TraceEntry<T>) -> bool,
+    {
+        self.entries.iter().position(predicate)
+    }
+}
+
+/// A cluster of clients, proposers, acceptors and learners, whose messages are exchanged over an
+/// in-memory channel. The addresses of the roles are only used to route the messages, as if they
+/// were multicast groups: a message sent to the address of a role is delivered to all nodes of
+/// that role (including the sender itself).
+pub struct Cluster<T> {
+    bus: Bus,
+
+    seed: u64,
+
+    // The source of all the randomness of this cluster, seeded with self.seed.
+    rng: StdRng,
+
+    clients: Vec<Client<T>>,
+
+    proposers: Vec<Proposer<T>>,
+
+    acceptors: Vec<Acceptor<T>>,
+
+    learners: Vec<Learner<T>>,
+
+    // The observers, which are delivered the messages sent to the learners (see add_observer).
+    observers: Vec<Observer<T>>,
+
+    clients_address: SocketAddrV4,
+
+    proposers_address: SocketAddrV4,
+
+    acceptors_address: SocketAddrV4,
+
+    learners_address: SocketAddrV4,
+
+    // If set, every delivered message is recorded here.
+    trace_collector: Option<TraceCollector<T>>,
+
+    // If set, the client requests are only delivered to the proposer with this index (see
+    // with_leader).
+    leader: Option<usize>,
+
+    // If set, the acceptors durably store their state in this directory (see
+    // with_acceptor_storage).
+    acceptor_storage: Option<PathBuf>,
+
+    // Whether the acceptors also send their Acceptance messages to the learners, which learn from
+    // them (see with_direct_learning).
+    direct_learning: bool,
+
+    // If set, the checkpoint of the log of this cluster, below which its nodes truncate their logs
+    // (see with_checkpoint).
+    checkpoint: Option<T>,
+
+    // The indexes of the acceptors which are crashed, i.e. which no message is delivered to (see
+    // crash_acceptor).
+    crashed_acceptors: HashSet<usize>,
+
+    // The number of replicas created so far (see new_replica), whose learners are not among
+    // self.learners.
+    num_of_replicas: usize,
+}
+
+impl<T> Cluster<T>
+where
+    T: Serialize + DeserializeOwned + Copy + Clone + Debug + PartialEq,
+{
+    /// Creates a cluster with the given number of nodes for each role, using a fixed seed (see
+    /// with_seed).
+    pub fn new(
+        num_of_clients: usize,
+        num_of_proposers: usize,
+        num_of_acceptors: usize,
+        num_of_learners: usize,
+    ) -> Self {
+        Cluster::with_seed(
+            DEFAULT_SEED,
+            num_of_clients,
+            num_of_proposers,
+            num_of_acceptors,
+            num_of_learners,
+        )
+    }
+
+    /// Creates a cluster with the given number of nodes for each role. The ids of the nodes are
+    /// unique and assigned in the order clients, proposers, acceptors and learners, starting from 1.
+    /// The unique identifiers (UUIDs) of the nodes are generated from seed, so two clusters created
+    /// with the same seed are identical. The proposers and the learners are started, i.e. their
+    /// CatchUp messages are already sent (but not yet delivered).
+    ///
+    /// Panics if num_of_acceptors is zero, unless there are no proposers nor learners.
+    pub fn with_seed(
+        seed: u64,
+        num_of_clients: usize,
+        num_of_proposers: usize,
+        num_of_acceptors: usize,
+        num_of_learners: usize,
+    ) -> Self {
+        let bus: Bus = Arc::new(Mutex::new(VecDeque::new()));
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let host = Ipv4Addr::new(239, 0, 0, 1);
+        let clients_address = SocketAddrV4::new(host, 5000);
+        let proposers_address = SocketAddrV4::new(host, 6000);
+        let acceptors_address = SocketAddrV4::new(host, 7000);
+        let learners_address = SocketAddrV4::new(host, 8000);
+
+        let mut uid = 0;
+        let mut next_uid = || {
+            uid += 1;
+            uid
+        };
+
+        let clients = (0..num_of_clients)
+            .map(|_| {
+                Client::with_node(NetNode::channel(&bus), next_uid(), proposers_address)
+                    .with_uuid(random_uuid(&mut rng))
+            })
+            .collect();
+
+        let proposers: Vec<Proposer<T>> = (0..num_of_proposers)
+            .map(|_| {
+                Proposer::with_node(
+                    NetNode::channel(&bus),
+                    next_uid(),
+                    proposers_address,
+                    acceptors_address,
+                    learners_address,
+                    num_of_acceptors,
+                )
+                .with_uuid(random_uuid(&mut rng))
+                .with_random_source(StdRng::seed_from_u64(rng.gen()))
+                .with_clients_address(clients_address)
+            })
+            .collect();
+
+        let acceptors = (0..num_of_acceptors)
+            .map(|_| {
+                Acceptor::with_node(NetNode::channel(&bus), next_uid(), proposers_address)
+                    .with_uuid(random_uuid(&mut rng))
+            })
+            .collect();
+
+        let learners: Vec<Learner<T>> = (0..num_of_learners)
+            .map(|_| {
+                Learner::with_node(
+                    NetNode::channel(&bus),
+                    next_uid(),
+                    learners_address,
+                    proposers_address,
+                    LearningMode::Push,
+                    DeliveryPolicy::TotalOrder,
+                )
+                .with_uuid(random_uuid(&mut rng))
+                .with_acceptors(acceptors_address, num_of_acceptors)
+            })
+            .collect();
+
+        proposers.iter().for_each(|p| p.catch_up());
+        learners.iter().for_each(|l| l.catch_up());
+
+        Cluster {
+            bus,
+            seed,
+            rng,
+            clients,
+            proposers,
+            acceptors,
+            learners,
+            observers: Vec::new(),
+            clients_address,
+            proposers_address,
+            acceptors_address,
+            learners_address,
+            trace_collector: None,
+            leader: None,
+            acceptor_storage: None,
+            direct_learning: false,
+            checkpoint: None,
+            crashed_acceptors: HashSet::new(),
+            num_of_replicas: 0,
+        }
+    }
+
+    /// Attaches a TraceCollector to this cluster, which records the messages delivered from now on.
+    pub fn with_trace_collector(mut self) -> Self {
+        self.trace_collector = Some(TraceCollector {
+            entries: Vec::new(),
+        });
+        self
+    }
+
+    /// Designates the i-th proposer as the leader (i.e. the distinguished proposer) of this cluster:
+    /// the client requests are only delivered to it, so that the other proposers never compete
+    /// with it, which is what Paxos needs to guarantee progress.
+    pub fn with_leader(mut self, i: usize) -> Self {
+        self.leader = Some(i);
+        self
+    }
+
+    /// Configures every client of this cluster, e.g. with_clients(|c| c.with_group_id(1)).
+    pub fn with_clients<F: Fn(Client<T>) -> Client<T>>(mut self, f: F) -> Self {
+        self.clients = self.clients.drain(..).map(f).collect();
+        self
+    }
+
+    /// Configures every proposer of this cluster, e.g. with_proposers(|p| p.with_max_in_flight(1)).
+    pub fn with_proposers<F: Fn(Proposer<T>) -> Proposer<T>>(mut self, f: F) -> Self {
+        self.proposers = self.proposers.drain(..).map(f).collect();
+        self
+    }
+
+    /// Configures every acceptor of this cluster, e.g. with_acceptors(|a| a.with_reply_cache(window,
+    /// capacity)). The acceptors which are restarted later are not configured again.
+    pub fn with_acceptors<F: Fn(Acceptor<T>) -> Acceptor<T>>(mut self, f: F) -> Self {
+        self.acceptors = self.acceptors.drain(..).map(f).collect();
+        self
+    }
+
+    /// Configures every learner of this cluster, e.g. with_learners(|l| l.with_learn_acks()).
+    pub fn with_learners<F: Fn(Learner<T>) -> Learner<T>>(mut self, f: F) -> Self {
+        self.learners = self.learners.drain(..).map(f).collect();
+        self
+    }
+
+    /// Makes the acceptors of this cluster durably store their state in the directory dir (see
+    /// Acceptor::with_storage), so that they can recover it when they are restarted (see
+    /// restart_acceptor). The files of the acceptors are created if they do not exist.
+    pub fn with_acceptor_storage<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        let dir = dir.as_ref().to_path_buf();
+
+        self.acceptors = self
+            .acceptors
+            .drain(..)
+            .enumerate()
+            .map(|(i, acceptor)| {
+                acceptor
+                    .with_storage(acceptor_storage_path(&dir, i))
+                    .expect("Could not open the storage of the acceptor")
+            })
+            .collect();
+
+        self.acceptor_storage = Some(dir);
+        self
+    }
+
+    /// Makes the acceptors of this cluster also send their Acceptance messages to the learners, which
+    /// learn the chosen values from them (see Acceptor::with_learners_address and
+    /// Learner::with_direct_learning), as well as from the Learning messages of the proposers.
+    pub fn with_direct_learning(mut self) -> Self {
+        let learners_address = self.learners_address;
+
+        self.acceptors = self
+            .acceptors
+            .drain(..)
+            .map(|acceptor| acceptor.with_learners_address(learners_address))
+            .collect();
+        self.learners = self
+            .learners
+            .drain(..)
+            .map(|learner| learner.with_direct_learning())
+            .collect();
+
+        self.direct_learning = true;
+        self
+    }
+
+    /// Makes the nodes of this cluster truncate their logs below the stable checkpoints, i.e. the
+    /// instances where checkpoint is decided (see Proposer::with_checkpoint,
+    /// Acceptor::with_log_truncation and Learner::with_checkpoint), including the nodes which are
+    /// added or restarted later. The learners acknowledge their deliveries, and a checkpoint is
+    /// only stable once a majority of them acknowledged it (see Proposer::with_learn_acks).
+    pub fn with_checkpoint(mut self, checkpoint: T) -> Self {
+        let num_of_acks = self.learners.len() / 2 + 1;
+
+        self.proposers = self
+            .proposers
+            .drain(..)
+            .map(|proposer| {
+                proposer
+                    .with_learn_acks(num_of_acks, LEARNING_RETRANSMIT_INTERVAL)
+                    .with_checkpoint(checkpoint)
+            })
+            .collect();
+        self.acceptors = self
+            .acceptors
+            .drain(..)
+            .map(|acceptor| acceptor.with_log_truncation())
+            .collect();
+        self.learners = self
+            .learners
+            .drain(..)
+            .map(|learner| learner.with_learn_acks().with_checkpoint(checkpoint))
+            .collect();
+
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+
+    /// Returns the TraceCollector attached to this cluster, if any.
+    pub fn trace_collector(&self) -> Option<&TraceCollector<T>> {
+        self.trace_collector.as_ref()
+    }
+
+    /// Returns the number of the messages delivered so far for which predicate returns true.
+    ///
+    /// Panics if this cluster has no TraceCollector (see with_trace_collector).
+    pub fn count_delivered<P>(&self, predicate: P) -> usize
+    where
+        P: Fn(&Message<T>) -> bool,
+    {
+        self.trace_collector
+            .as_ref()
+            .expect("Expected a trace collector")
+            .count(|e| predicate(&e.message))
+    }
+
+    /// The seed which this cluster was created with. Print it when a test fails, so that the test
+    /// can be replayed.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The random number generator of this cluster. A test must draw all its random choices (e.g.
+    /// which messages to drop) from it, so that it can be replayed with the same seed.
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+
+    /// Adds a proposer which joins this cluster late, e.g. after some instances have been decided.
+    /// It starts catching up (see Proposer::start_catch_up), so it does not start new instances
+    /// until the Report of another proposer is delivered to it. Returns the index of the proposer.
+    pub fn add_proposer(&mut self) -> usize {
+        let mut proposer = Proposer::with_node(
+            NetNode::channel(&self.bus),
+            self.next_uid(),
+            self.proposers_address,
+            self.acceptors_address,
+            self.learners_address,
+            self.acceptors.len(),
+        )
+        .with_uuid(random_uuid(&mut self.rng))
+        .with_random_source(StdRng::seed_from_u64(self.rng.gen()))
+        .with_clients_address(self.clients_address);
+
+        if let Some(checkpoint) = self.checkpoint {
+            proposer = proposer
+                .with_learn_acks(self.learners.len() / 2 + 1, LEARNING_RETRANSMIT_INTERVAL)
+                .with_checkpoint(checkpoint);
+        }
+
+        proposer.start_catch_up();
+        self.proposers.push(proposer);
+
+        self.proposers.len() - 1
+    }
+
+    /// Adds a learner which joins this cluster late, e.g. after some instances have been decided.
+    /// Its CatchUp message is sent (but not yet delivered), so, unless it is configured before the
+    /// messages are delivered (e.g. with Learner::tail_from), it delivers the whole log. Returns
+    /// the index of the learner.
+    pub fn add_learner(&mut self) -> usize {
+        let learner = self.new_learner();
+        self.learners.push(learner);
+
+        self.learners.len() - 1
+    }
+
+    /// Adds an observer which joins this cluster, e.g. after some instances have been decided. It
+    /// is delivered the messages sent to the learners, and calls on_decision with the values of
+    /// the Learning messages, from now on (see Observer). Returns the index of the observer.
+    pub fn add_observer(&mut self, on_decision: OnDecision<T>) -> usize {
+        let observer =
+            Observer::with_node(NetNode::channel(&self.bus), self.next_uid(), on_decision);
+        self.observers.push(observer);
+
+        self.observers.len() - 1
+    }
+
+    /// Creates a replica of a state machine, whose initial state is state, and which applies the
+    /// values delivered by its learner with apply (see ReplicatedStateMachine). Its learner joins
+    /// this cluster as the ones added with add_learner, but, unlike them, it is not delivered the
+    /// messages sent to the learners by step_with: the caller hands them over to the replica (see
+    /// ReplicatedStateMachine::handle), e.g. from the TraceCollector of this cluster.
+    pub fn new_replica<S>(&mut self, state: S, apply: Apply<S, T>) -> ReplicatedStateMachine<S, T> {
+        let learner = self.new_learner();
+        self.num_of_replicas += 1;
+
+        ReplicatedStateMachine::new(learner, state, apply)
+    }
+
+    /// Returns a new learner of this cluster, whose CatchUp message is sent, but which is not added
+    /// to self.learners.
+    fn new_learner(&mut self) -> Learner<T> {
+        let mut learner = Learner::with_node(
+            NetNode::channel(&self.bus),
+            self.next_uid(),
+            self.learners_address,
+            self.proposers_address,
+            LearningMode::Push,
+            DeliveryPolicy::TotalOrder,
+        )
+        .with_uuid(random_uuid(&mut self.rng))
+        .with_acceptors(self.acceptors_address, self.acceptors.len());
+
+        if self.direct_learning {
+            learner = learner.with_direct_learning();
+        }
+
+        if let Some(checkpoint) = self.checkpoint {
+            learner = learner.with_learn_acks().with_checkpoint(checkpoint);
+        }
+
+        learner.catch_up();
+        learner
+    }
+
+    /// Returns the id of the next node added to this cluster, which follows the ids of all its
+    /// nodes, including the observers and the learners of the replicas.
+    fn next_uid(&self) -> usize {
+        self.clients.len()
+            + self.proposers.len()
+            + self.acceptors.len()
+            + self.learners.len()
+            + self.observers.len()
+            + self.num_of_replicas
+            + 1
+    }
+
+    /// Restarts the i-th proposer, with the same identity, but without any of its state (e.g. its
+    /// rounds and its learned values), as if it crashed and started again: it is configured with f
+    /// (e.g. to give it the same round strategy, see Proposer::with_round_strategy), and its
+    /// CatchUp message is sent (but not yet delivered).
+    pub fn restart_proposer<F: FnOnce(Proposer<T>) -> Proposer<T>>(&mut self, i: usize, f: F) {
+        let (id, uuid) = self.proposers[i].identity();
+
+        let mut proposer = Proposer::with_node(
+            NetNode::channel(&self.bus),
+            id,
+            self.proposers_address,
+            self.acceptors_address,
+            self.learners_address,
+            self.acceptors.len(),
+        )
+        .with_uuid(uuid)
+        .with_random_source(StdRng::seed_from_u64(self.rng.gen()))
+        .with_clients_address(self.clients_address);
+
+        if let Some(checkpoint) = self.checkpoint {
+            proposer = proposer
+                .with_learn_acks(self.learners.len() / 2 + 1, LEARNING_RETRANSMIT_INTERVAL)
+                .with_checkpoint(checkpoint);
+        }
+
+        let proposer = f(proposer);
+        proposer.catch_up();
+        self.proposers[i] = proposer;
+    }
+
+    /// Crashes the i-th acceptor: no message is delivered to it anymore (including the ones which
+    /// are already pending), until it is restarted (see restart_acceptor).
+    pub fn crash_acceptor(&mut self, i: usize) {
+        self.crashed_acceptors.insert(i);
+    }
+
+    /// Restarts the i-th acceptor, with the same identity, but with the state which it stored (see
+    /// with_acceptor_storage), if any, i.e. its state in memory is lost, as after a real crash.
+    pub fn restart_acceptor(&mut self, i: usize) {
+        let (id, uuid) = self.acceptors[i].identity();
+
+        let mut acceptor =
+            Acceptor::with_node(NetNode::channel(&self.bus), id, self.proposers_address)
+                .with_uuid(uuid);
+
+        if let Some(ref dir) = self.acceptor_storage {
+            acceptor = acceptor
+                .with_storage(acceptor_storage_path(dir, i))
+                .expect("Could not open the storage of the acceptor");
+        }
+
+        if self.direct_learning {
+            acceptor = acceptor.with_learners_address(self.learners_address);
+        }
+
+        if self.checkpoint.is_some() {
+            acceptor = acceptor.with_log_truncation();
+        }
+
+        self.acceptors[i] = acceptor;
+        self.crashed_acceptors.remove(&i);
+    }
+
+    pub fn client(&mut self, i: usize) -> &mut Client<T> {
+        &mut self.clients[i]
+    }
+
+    pub fn proposer(&mut self, i: usize) -> &mut Proposer<T> {
+        &mut self.proposers[i]
+    }
+
+    pub fn acceptor(&mut self, i: usize) -> &mut Acceptor<T> {
+        &mut self.acceptors[i]
+    }
+
+    pub fn learner(&mut self, i: usize) -> &mut Learner<T> {
+        &mut self.learners[i]
+    }
+
+    pub fn observer(&mut self, i: usize) -> &mut Observer<T> {
+        &mut self.observers[i]
+    }
+
+    /// Returns the values delivered so far by the i-th learner, in the order it delivered them
+    /// (see Learner::total_order_prefix), without their instances.
+    pub fn delivered_values(&self, i: usize) -> Vec<T> {
+        self.learners[i]
+            .total_order_prefix()
+            .iter()
+            .map(|&(_, v)| v)
+            .collect()
+    }
+
+    /// The number of messages which have been sent, but not yet delivered.
+    pub fn num_of_pending(&self) -> usize {
+        self.bus.lock().unwrap().len()
+    }
+
+    /// Drops the pending messages for which predicate, given the role of the nodes which they are
+    /// sent to, returns true, e.g. to simulate the loss of some messages. Returns the number of
+    /// dropped messages. Corrupted datagrams are kept.
+    pub fn drop_pending<P>(&mut self, mut predicate: P) -> usize
+    where
+        P: FnMut(Role, &Message<T>) -> bool,
+    {
+        let mut bus = self.bus.lock().unwrap();
+        let num_of_pending = bus.len();
+
+        bus.retain(|(destination_address, datagram)| {
+            match (
+                self.role_of(destination_address),
+                decode_datagram::<T>(datagram),
+            ) {
+                (Some(receiver), Some(message)) => !predicate(receiver, &message),
+                _ => true,
+            }
+        });
+
+        num_of_pending - bus.len()
+    }
+
+    /// Delivers the oldest pending message to all nodes listening on its destination address.
+    /// Returns false if there was no pending message.
+    pub fn step(&mut self) -> bool {
+        self.step_with(Choice::Deliver(0))
+    }
+
+    /// Delivers the pending messages, oldest first, and the ones which they trigger, until there
+    /// are none.
+    pub fn run_until_quiet(&mut self) {
+        while self.step() {}
+    }
+
+    /// Makes the i-th client request value, and delivers the messages until there are none (see
+    /// run_until_quiet), so that value is decided, unless its messages are dropped.
+    pub fn request(&mut self, i: usize, value: T) {
+        self.clients[i].request(value);
+        self.run_until_quiet();
+    }
+
+    /// Delivers a random pending message, or drops it with probability drop_probability, using
+    /// the randomness of this cluster (see rng). Returns the choice made, so that the run can be
+    /// replayed (see step_with), or None if there was no pending message.
+    pub fn step_random(&mut self, drop_probability: f64) -> Option<Choice> {
+        let num_of_pending = self.num_of_pending();
+
+        if num_of_pending == 0 {
+            return None;
+        }
+
+        let index = self.rng.gen_range(0, num_of_pending);
+
+        let choice = if self.rng.gen_bool(drop_probability) {
+            Choice::Drop(index)
+        } else {
+            Choice::Deliver(index)
+        };
+
+        self.step_with(choice);
+
+        Some(choice)
+    }
+
+    /// Delivers (to all nodes listening on its destination address) or drops the pending message
+    /// chosen by choice. Returns false if there was no pending message.
+    pub fn step_with(&mut self, choice: Choice) -> bool {
+        let (index, deliver) = match choice {
+            Choice::Deliver(index) => (index, true),
+            Choice::Drop(index) => (index, false),
+        };
+
+        let next = {
+            let mut bus = self.bus.lock().unwrap();
+
+            match bus.len() {
+                0 => None,
+                num_of_pending => bus.remove(index % num_of_pending),
+            }
+        };
+
+        let (destination_address, datagram) = match next {
+            Some(next) => next,
+            None => return false,
+        };
+
+        if !deliver {
+            return true;
+        }
+
+        let receiver = match self.role_of(&destination_address) {
+            Some(receiver) => receiver,
+            None => return true,
+        };
+
+        if let Some(ref mut trace_collector) = self.trace_collector {
+            if let Some(message) = decode_datagram::<T>(&datagram) {
+                trace_collector.entries.push(TraceEntry {
+                    sender_uuid: message.sender_uuid(),
+                    receiver,
+                    message,
+                });
+            }
+        }
+
+        match receiver {
+            Role::Clients => self.clients.iter_mut().for_each(|c| c.deliver(&datagram)),
+            Role::Proposers => match self.leader {
+                Some(leader) if is_request::<T>(&datagram) => {
+                    self.proposers[leader].deliver(&datagram)
+                }
+                _ => self.proposers.iter_mut().for_each(|p| p.deliver(&datagram)),
+            },
+            Role::Acceptors => {
+                let crashed_acceptors = &self.crashed_acceptors;
+
+                self.acceptors
+                    .iter_mut()
+                    .enumerate()
+                    .filter(|(i, _)| !crashed_acceptors.contains(i))
+                    .for_each(|(_, a)| a.deliver(&datagram))
+            }
+            Role::Learners => {
+                self.learners.iter_mut().for_each(|l| l.deliver(&datagram));
+                self.observers.iter_mut().for_each(|o| o.deliver(&datagram));
+            }
+        }
+
+        true
+    }
+
+    /// Returns the role of the nodes listening on address, if any.
+    fn role_of(&self, address: &SocketAddrV4) -> Option<Role> {
+        if *address == self.clients_address {
+            Some(Role::Clients)
+        } else if *address == self.proposers_address {
+            Some(Role::Proposers)
+        } else if *address == self.acceptors_address {
+            Some(Role::Acceptors)
+        } else if *address == self.learners_address {
+            Some(Role::Learners)
+        } else {
+            None
+        }
+    }
+
+    /// Delivers messages until some learner has learned the value of the given instance, which is
+    /// then returned. None is returned if the timeout elapses, or if there are no more messages to
+    /// deliver, before the instance is decided.
+    pub fn wait_for_decision(&mut self, instance: usize, timeout: Duration) -> Option<T> {
+        let start = Instant::now();
+
+        loop {
+            if let Some(v) = self.learners.iter().find_map(|l| l.learned_value(instance)) {
+                return Some(v);
+            }
+
+            if start.elapsed() >= timeout || !self.step() {
+                return None;
+            }
+        }
+    }
+}
+
+/// Shrinks schedule, which makes a test fail (i.e. fails returns true when it is given schedule),
+/// to a schedule which still makes it fail, but which is minimal: removing any of its choices, or
+/// delivering or dropping the oldest pending message instead of another one, makes the test pass.
+/// fails must replay the given schedule on a new Cluster, with the same seed (and the same
+/// requests), e.g. with step_with, and then check the failure. The choices are removed in chunks,
+/// from the largest to single ones (i.e. delta debugging), so that a schedule of thousands of
+/// choices is shrunk with a number of replays which is roughly proportional to its length.
+///
+/// Panics if schedule does not make the test fail.
+pub fn minimize_schedule<F>(schedule: &[Choice], mut fails: F) -> Vec<Choice>
+where
+    F: FnMut(&[Choice]) -> bool,
+{
+    let mut schedule = schedule.to_vec();
+
+    assert!(fails(&schedule), "The schedule to minimize must fail");
+
+    loop {
+        let mut shrunk = false;
+
+        let mut chunk = (schedule.len() / 2).max(1);
+
+        loop {
+            let mut start = 0;
+
+            while start < schedule.len() {
+                let end = (start + chunk).min(schedule.len());
+
+                let candidate: Vec<Choice> = schedule[..start]
+                    .iter()
+                    .chain(&schedule[end..])
+                    .cloned()
+                    .collect();
+
+                if fails(&candidate) {
+                    schedule = candidate;
+                    shrunk = true;
+                } else {
+                    start = end;
+                }
+            }
+
+            if chunk == 1 {
+                break;
+            }
+
+            chunk /= 2;
+        }
+
+        for i in 0..schedule.len() {
+            let simpler = match schedule[i] {
+                Choice::Deliver(index) if index > 0 => Choice::Deliver(0),
+                Choice::Drop(index) if index > 0 => Choice::Drop(0),
+                _ => continue,
+            };
+
+            let mut candidate = schedule.clone();
+            candidate[i] = simpler;
+
+            if fails(&candidate) {
+                schedule = candidate;
+                shrunk = true;
+            }
+        }
+
+        if !shrunk {
+            return schedule;
+        }
+    }
+}
+
+/// Prints the outcome of the test with the given description, and returns ok, so that the outcomes
+/// of the tests of a script can be combined.
+pub fn report(test: &str, ok: bool) -> bool {
+    println!("{}", test);
+
+    if ok {
+        println!("  > OK");
+    } else {
+        println!("  > Failed!");
+    }
+
+    ok
+}
+
+/// Returns the first argument of a test script (e.g. its number of values), or default if it is
+/// not given.
+///
+/// Panics if the argument is not a valid what (e.g. "number of values").
+pub fn arg_or<N: FromStr>(what: &str, default: N) -> N {
+    match env::args().nth(1) {
+        Some(arg) => arg
+            .parse()
+            .unwrap_or_else(|_| panic!("Expected the {}", what)),
+        None => default,
+    }
+}
+
+/// Returns a random (version 4) UUID drawn from rng.
+fn random_uuid(rng: &mut StdRng) -> Uuid {
+    let mut bytes = [0; 16];
+    rng.fill(&mut bytes);
+    Builder::from_bytes(bytes)
+        .set_variant(Variant::RFC4122)
+        .set_version(Version::Random)
+        .build()
+}
+
+/// Returns the path of the file, in the directory dir, where the i-th acceptor stores its state.
+fn acceptor_storage_path(dir: &Path, i: usize) -> PathBuf {
+    dir.join(format!("acceptor{}.log", i))
+}
+
+/// Returns true if datagram contains a (non-corrupted) client request.
+fn is_request<T: DeserializeOwned>(datagram: &[u8]) -> bool {
+    matches!(decode_datagram::<T>(datagram), Some(Message::Phase0a(_)))
+}
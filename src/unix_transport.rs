@@ -0,0 +1,176 @@
+//! A module which contains the definition of a transport over Unix datagram sockets, which the nodes
+//! of a cluster running on a single host (e.g. for testing, or for development on a machine where
+//! multicast is not available) can use instead of UDP multicast (see use_unix_sockets).
+//!
+//! The multicast groups are emulated with directories: the address of a group (e.g. the one of a
+//! role) is mapped to a directory, under the socket directory, where each node which receives the
+//! datagrams of the group binds a socket, and a datagram sent to the group is sent to each socket
+//! of its directory. As with UDP multicast, a node thus receives its own datagrams, and a datagram
+//! which cannot be queued at a receiver within SEND_TIMEOUT (e.g. because the node is stuck) is
+//! lost. The sockets left
+//! by the nodes which are gone are removed by the first node which fails to send to them. The
+//! datagrams are framed exactly as over UDP (see the module net_node).
+
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::net::SocketAddrV4;
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long a datagram waits for room in the queue of a receiver, which is much shorter than the
+/// receive buffer of a UDP socket (see /proc/sys/net/unix/max_dgram_qlen), before it is dropped.
+const SEND_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// The directory under which the nodes of this process bind their Unix datagram sockets, if they
+/// use them instead of UDP multicast (see use_unix_sockets).
+static SOCKET_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// The number of sockets bound by this process so far, which makes the name of each of them unique.
+static NUM_OF_SOCKETS: AtomicUsize = AtomicUsize::new(0);
+
+/// Makes the nodes created from now on by this process (e.g. with Proposer::new) send and receive
+/// their messages over Unix datagram sockets, bound under socket_dir, instead of over UDP
+/// multicast (see the module documentation). All the nodes of the cluster must use the same
+/// directory, and the addresses of the configuration file only name the groups. As the path of a
+/// socket is limited to about 100 bytes, socket_dir should be short (e.g. under /tmp).
+pub fn use_unix_sockets<P: Into<PathBuf>>(socket_dir: P) {
+    *SOCKET_DIR.lock().unwrap() = Some(socket_dir.into());
+}
+
+/// Returns the directory of the Unix datagram sockets, if the nodes of this process use them (see
+/// use_unix_sockets).
+pub(crate) fn socket_dir() -> Option<PathBuf> {
+    SOCKET_DIR.lock().unwrap().clone()
+}
+
+/// The sockets of a node which sends and receives its datagrams over Unix datagram sockets. They
+/// mirror the UDP sockets of a NetNode: the receiver socket is bound in the group of the role of the
+/// node, and the other ones, if any, in the directories of its unicast address and of the other
+/// groups which it joined.
+pub(crate) struct UnixTransport {
+    socket_dir: PathBuf,
+
+    sender: UnixDatagram,
+
+    pub(crate) receiver: UnixDatagram,
+
+    pub(crate) unicast: Option<UnixDatagram>,
+
+    pub(crate) groups: Vec<UnixDatagram>,
+}
+
+impl UnixTransport {
+    /// Creates the sockets of a node which receives the datagrams sent to group, under socket_dir.
+    pub(crate) fn bind(socket_dir: &Path, group: &SocketAddrV4) -> Self {
+        let sender = UnixDatagram::unbound().expect("Could not create the sender socket");
+
+        // A receiver which cannot keep up loses the datagrams, as over UDP, instead of blocking
+        // its senders for long.
+        sender
+            .set_write_timeout(Some(SEND_TIMEOUT))
+            .expect("Could not set the write timeout");
+
+        UnixTransport {
+            socket_dir: socket_dir.to_path_buf(),
+            sender,
+            receiver: bind_socket(socket_dir, group).expect("Could not bind the receiver socket"),
+            unicast: None,
+            groups: Vec::new(),
+        }
+    }
+
+    /// Binds the socket which receives the datagrams sent to unicast_address.
+    pub(crate) fn bind_unicast(&mut self, unicast_address: &SocketAddrV4) {
+        self.unicast = Some(
+            bind_socket(&self.socket_dir, unicast_address)
+                .expect("Could not bind the unicast socket"),
+        );
+    }
+
+    /// Binds a socket which receives the datagrams sent to group, besides the other ones.
+    pub(crate) fn join(&mut self, group: &SocketAddrV4) {
+        self.groups
+            .push(bind_socket(&self.socket_dir, group).expect("Could not bind the group socket"));
+    }
+
+    /// Sends datagram to each socket bound in the directory of group. The sockets whose nodes are
+    /// gone are removed.
+    pub(crate) fn send_to(&self, datagram: &[u8], group: &SocketAddrV4) {
+        let entries = match fs::read_dir(group_dir(&self.socket_dir, group)) {
+            Ok(entries) => entries,
+            // No node receives the datagrams of the group yet.
+            Err(ref e) if e.kind() == ErrorKind::NotFound => return,
+            Err(e) => panic!("Could not list the sockets of {}: {}", group, e),
+        };
+
+        for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            match self.sender.send_to(datagram, &path) {
+                Ok(_) => {}
+                Err(ref e) if e.kind() == ErrorKind::ConnectionRefused => {
+                    let _ = fs::remove_file(&path);
+                }
+                Err(ref e)
+                    if e.kind() == ErrorKind::WouldBlock
+                        || e.kind() == ErrorKind::TimedOut
+                        || e.kind() == ErrorKind::NotFound => {}
+                Err(e) => warn!("Could not send a datagram to {:?}: {}", path, e),
+            }
+        }
+    }
+
+    /// Returns sockets which are clones of the sockets of this transport.
+    pub(crate) fn try_clone(&self) -> io::Result<Self> {
+        Ok(UnixTransport {
+            socket_dir: self.socket_dir.clone(),
+            sender: self.sender.try_clone()?,
+            receiver: self.receiver.try_clone()?,
+            unicast: self
+                .unicast
+                .as_ref()
+                .map(UnixDatagram::try_clone)
+                .transpose()?,
+            groups: self
+                .groups
+                .iter()
+                .map(UnixDatagram::try_clone)
+                .collect::<io::Result<_>>()?,
+        })
+    }
+}
+
+/// Binds a socket in the directory of group, under socket_dir, which is created if needed. The
+/// name of the socket is unique among the nodes of the host.
+pub(crate) fn bind_socket(socket_dir: &Path, group: &SocketAddrV4) -> io::Result<UnixDatagram> {
+    let dir = group_dir(socket_dir, group);
+    fs::create_dir_all(&dir)?;
+
+    let name = format!(
+        "{}-{}.sock",
+        process::id(),
+        NUM_OF_SOCKETS.fetch_add(1, Ordering::SeqCst)
+    );
+
+    UnixDatagram::bind(dir.join(name))
+}
+
+/// Binds a socket in the directory of group, under socket_dir, and closes it (and removes it) right
+/// away, to check the setup of a node before starting it (see configurations::check_node).
+pub(crate) fn check_socket(socket_dir: &Path, group: &SocketAddrV4) -> io::Result<()> {
+    let socket = bind_socket(socket_dir, group)?;
+
+    if let Some(path) = socket.local_addr()?.as_pathname() {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the directory, under socket_dir, where the nodes which receive the datagrams sent to
+/// group bind their sockets.
+fn group_dir(socket_dir: &Path, group: &SocketAddrV4) -> PathBuf {
+    socket_dir.join(format!("{}_{}", group.ip(), group.port()))
+}
@@ -0,0 +1,391 @@
+//! A module which contains the verbosity of the logs of the messages, which is distinct from the
+//! level of the logs. At the level info, the nodes log every message which they send or handle, and
+//! the full bodies of the messages (e.g. all the learned values of a Report) can make the logs
+//! enormous, and writing them a bottleneck, in high-throughput runs. With the verbosity Summary,
+//! only the phase, the instance and the round of each message are logged, and, with the verbosity
+//! Off, the messages are not logged at all (the other info logs are kept).
+//!
+//! The verbosity is given by the environment variable MULTI_PAXOS_LOG_MESSAGES (i.e. `off`,
+//! `summary` or `full`), unless it is set explicitly (see set_log_verbosity), e.g. from the
+//! configuration file. By default, it is Full.
+
+use std::env;
+use std::fmt::{self, Debug, Formatter};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use log::Level;
+
+use crate::message::{
+    Acceptance, Ballot, CancelRequest, CatchUp, Decided, GossipSummary, GossipValues, LearnAck,
+    Learning, Message, Nack, NoopRequest, Ping, Pong, Preparation, Progress, Promise, Proposal,
+    Rejected, Report, Request, TailPreparation, TailPromise, Truncation, TruncationRequest,
+    ValueReply, ValueRequest,
+};
+
+/// The environment variable which gives the verbosity of the logs of the messages, unless it is
+/// set explicitly (see set_log_verbosity).
+pub const LOG_VERBOSITY_VAR: &str = "MULTI_PAXOS_LOG_MESSAGES";
+
+// The verbosity, as the index of its variant plus 1, or 0 if it is not known yet.
+static VERBOSITY: AtomicUsize = AtomicUsize::new(0);
+
+/// How much of the messages sent or handled by the nodes is logged, at the level info.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LogVerbosity {
+    /// The messages are not logged.
+    Off,
+
+    /// Only the phase, the instance and the round (or the corresponding fields) of each message
+    /// are logged, but not its values.
+    Summary,
+
+    /// The full bodies of the messages are logged.
+    Full,
+}
+
+impl FromStr for LogVerbosity {
+    type Err = String;
+
+    fn from_str(verbosity: &str) -> Result<Self, Self::Err> {
+        match verbosity {
+            "off" => Ok(LogVerbosity::Off),
+            "summary" => Ok(LogVerbosity::Summary),
+            "full" => Ok(LogVerbosity::Full),
+            _ => Err(format!(
+                "Expected off, summary or full as the verbosity of the logs of the messages, but got {:?}",
+                verbosity
+            )),
+        }
+    }
+}
+
+/// Sets the verbosity of the logs of the messages of all the nodes of this process, which
+/// overrides the environment variable LOG_VERBOSITY_VAR.
+pub fn set_log_verbosity(verbosity: LogVerbosity) {
+    VERBOSITY.store(verbosity as usize + 1, Ordering::Relaxed);
+}
+
+/// Returns the verbosity of the logs of the messages (see the module documentation).
+pub fn log_verbosity() -> LogVerbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        1 => LogVerbosity::Off,
+        2 => LogVerbosity::Summary,
+        3 => LogVerbosity::Full,
+        _ => {
+            let verbosity = match env::var(LOG_VERBOSITY_VAR) {
+                Ok(verbosity) => verbosity.parse().unwrap_or_else(|e| {
+                    warn!("{}: the full messages will be logged.", e);
+                    LogVerbosity::Full
+                }),
+                Err(_) => LogVerbosity::Full,
+            };
+
+            // Another thread may have set the verbosity explicitly in the meantime.
+            let _ = VERBOSITY.compare_exchange(
+                0,
+                verbosity as usize + 1,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            );
+
+            log_verbosity()
+        }
+    }
+}
+
+/// Returns true if the messages are logged, i.e. if the level info is enabled and the verbosity of
+/// the logs of the messages is not Off.
+pub(crate) fn logs_messages() -> bool {
+    log_enabled!(Level::Info) && log_verbosity() != LogVerbosity::Off
+}
+
+/// A message (or the body of a message) as it must be logged, i.e. in full or summarized,
+/// depending on the verbosity of the logs of the messages.
+pub(crate) struct Logged<'a, M>(pub(crate) &'a M);
+
+impl<'a, M: Debug + Summarize> Debug for Logged<'a, M> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match log_verbosity() {
+            LogVerbosity::Summary => self.0.summarize(f),
+            _ => self.0.fmt(f),
+        }
+    }
+}
+
+/// A message (or the body of a message) which is always formatted as its summary, regardless of the
+/// verbosity of the logs of the messages, e.g. in an error.
+pub(crate) struct Summarized<'a, M>(pub(crate) &'a M);
+
+impl<'a, M: Summarize> Debug for Summarized<'a, M> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.0.summarize(f)
+    }
+}
+
+/// A message (or the body of a message) which can be logged as a summary, i.e. as its phase, its
+/// instance and its round (or the corresponding fields), but without its values.
+pub(crate) trait Summarize {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result;
+}
+
+/// Formats ballot as `epoch.proposer_id`.
+fn round(ballot: Ballot) -> String {
+    format!("{}.{}", ballot.epoch, ballot.proposer_id)
+}
+
+impl<T> Summarize for Message<T> {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Message::Phase0a(m) => m.summarize(f),
+            Message::Phase0b(m) => m.summarize(f),
+            Message::Phase0c(m) => m.summarize(f),
+            Message::Phase1a(m) => m.summarize(f),
+            Message::Phase1b(m) => m.summarize(f),
+            Message::Phase1c(m) => m.summarize(f),
+            Message::Phase2a(m) => m.summarize(f),
+            Message::Phase2b(m) => m.summarize(f),
+            Message::Phase3(m) => m.summarize(f),
+            Message::NoopRequest(m) => m.summarize(f),
+            Message::GossipSummary(m) => m.summarize(f),
+            Message::GossipValues(m) => m.summarize(f),
+            Message::Decided(m) => m.summarize(f),
+            Message::Ping(m) => m.summarize(f),
+            Message::Pong(m) => m.summarize(f),
+            Message::ValueRequest(m) => m.summarize(f),
+            Message::ValueReply(m) => m.summarize(f),
+            Message::Rejected(m) => m.summarize(f),
+            Message::LearnAck(m) => m.summarize(f),
+            Message::Progress(m) => m.summarize(f),
+            Message::Truncation(m) => m.summarize(f),
+            Message::TailPreparation(m) => m.summarize(f),
+            Message::TailPromise(m) => m.summarize(f),
+            Message::TruncationRequest(m) => m.summarize(f),
+            Message::CancelRequest(m) => m.summarize(f),
+        }
+    }
+}
+
+impl<T> Summarize for Request<T> {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Request {{ request_seq: {} }}", self.request_seq)
+    }
+}
+
+impl Summarize for CatchUp {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "CatchUp {{ from_instance: {} }}", self.from_instance)
+    }
+}
+
+impl<T> Summarize for Report<T> {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Report {{ num_of_instances: {}, num_of_learned_values: {} }}",
+            self.num_of_instances,
+            self.learned_values.len()
+        )
+    }
+}
+
+impl Summarize for Preparation {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Preparation {{ instance: {}, c_rnd: {} }}",
+            self.instance,
+            round(self.c_rnd)
+        )
+    }
+}
+
+impl<T> Summarize for Promise<T> {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Promise {{ instance: {}, rnd: {}, v_rnd: {} }}",
+            self.instance,
+            round(self.rnd),
+            round(self.v_rnd)
+        )
+    }
+}
+
+impl Summarize for Nack {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Nack {{ instance: {}, v_rnd: {} }}",
+            self.instance,
+            round(self.v_rnd)
+        )
+    }
+}
+
+impl<T> Summarize for Proposal<T> {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Proposal {{ instance: {}, c_rnd: {} }}",
+            self.instance,
+            round(self.c_rnd)
+        )
+    }
+}
+
+impl<T> Summarize for Acceptance<T> {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Acceptance {{ instance: {}, v_rnd: {} }}",
+            self.instance,
+            round(self.v_rnd)
+        )
+    }
+}
+
+impl<T> Summarize for Learning<T> {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Learning {{ instance: {} }}", self.instance)
+    }
+}
+
+impl Summarize for NoopRequest {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "NoopRequest {{ instance: {} }}", self.instance)
+    }
+}
+
+impl Summarize for GossipSummary {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "GossipSummary {{ num_of_ranges: {} }}",
+            self.known_instances.ranges().len()
+        )
+    }
+}
+
+impl<T> Summarize for GossipValues<T> {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "GossipValues {{ num_of_learned_values: {} }}",
+            self.learned_values.len()
+        )
+    }
+}
+
+impl<T> Summarize for Decided<T> {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Decided {{ instance: {}, request_seq: {}, watermark: {}, num_of_prefix_values: {} }}",
+            self.instance,
+            self.request_seq,
+            self.watermark,
+            self.prefix.len()
+        )
+    }
+}
+
+impl Summarize for Ping {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Ping {{ nonce: {} }}", self.nonce)
+    }
+}
+
+impl Summarize for Pong {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Pong {{ nonce: {} }}", self.nonce)
+    }
+}
+
+impl Summarize for ValueRequest {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "ValueRequest {{ instance: {} }}", self.instance)
+    }
+}
+
+impl<T> Summarize for ValueReply<T> {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ValueReply {{ instance: {}, v_rnd: {} }}",
+            self.instance,
+            round(self.v_rnd)
+        )
+    }
+}
+
+impl<T> Summarize for Rejected<T> {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Rejected {{ instance: {}, request_seq: {} }}",
+            self.instance, self.request_seq
+        )
+    }
+}
+
+impl Summarize for LearnAck {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "LearnAck {{ instance: {} }}", self.instance)
+    }
+}
+
+impl Summarize for Progress {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Progress {{ next_to_deliver: {} }}",
+            self.next_to_deliver
+        )
+    }
+}
+
+impl Summarize for Truncation {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Truncation {{ instance: {} }}", self.instance)
+    }
+}
+
+impl Summarize for TailPreparation {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "TailPreparation {{ from_instance: {}, c_rnd: {} }}",
+            self.from_instance,
+            round(self.c_rnd)
+        )
+    }
+}
+
+impl<T> Summarize for TailPromise<T> {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "TailPromise {{ from_instance: {}, rnd: {}, num_of_votes: {} }}",
+            self.from_instance,
+            round(self.rnd),
+            self.votes.len()
+        )
+    }
+}
+
+impl Summarize for TruncationRequest {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "TruncationRequest {{ truncated_below: {} }}",
+            self.truncated_below
+        )
+    }
+}
+
+impl Summarize for CancelRequest {
+    fn summarize(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "CancelRequest {{ request_seq: {} }}", self.request_seq)
+    }
+}
@@ -0,0 +1,16 @@
+#!/usr/bin/env bash
+
+EXAMPLE="$1"
+
+if [[ x$EXAMPLE == "x" ]]; then
+	echo "Usage: $0 <example> [<number of values, runs, ...>]"
+    exit 1
+fi
+
+shift
+
+echo "Tests of the example $EXAMPLE (over an in-memory channel, so no sockets are used)."
+
+# The learners print the learned values, so only the outcomes of the tests are kept.
+set -o pipefail
+cargo run --features testkit --example $EXAMPLE -- "$@" | grep -A1 "^Test"
@@ -1,4 +1,4 @@
 #!/bin/bash
 
 #RUST_LOG=multi_paxos=info cargo run --example start_proposer -- $1 $2
-cargo run --example start_proposer -- "$1" "$2"
\ No newline at end of file
+cargo run --features "$FEATURES" --example start_proposer -- "$1" "$2"
\ No newline at end of file
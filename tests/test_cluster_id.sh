@@ -0,0 +1,73 @@
+#!/usr/bin/env bash
+
+echo "Tests for two clusters configured on the same addresses, but with different cluster ids."
+
+STARTERS="$1"
+CONFIG=`pwd`/../Config.toml
+FOREIGN_CONFIG=`pwd`/foreign.toml
+N="$2"
+
+if [[ x$STARTERS == "x" || x$N == "x" ]]; then
+	echo "Usage: $0 <starter scripts folder> <number of values per proposer>"
+    exit 1
+fi
+
+# The foreign cluster uses a copy of the configuration file, with another cluster id.
+cp $CONFIG $FOREIGN_CONFIG
+printf '\n[cluster]\nid = "foreign"\n' >> $FOREIGN_CONFIG
+
+# following line kills processes that have the config file in its cmdline
+KILLCMD="pkill -f $CONFIG"
+FOREIGN_KILLCMD="pkill -f $FOREIGN_CONFIG"
+
+$KILLCMD
+$FOREIGN_KILLCMD
+
+cd $STARTERS
+
+echo "Generating $N random proposals (which are numbers) for each client..."
+
+../generate.sh $N > ../prop1
+../generate.sh $N > ../prop2
+../generate.sh $N > ../prop3
+
+echo "Starting 3 acceptors..."
+
+./acceptor.sh 1 $CONFIG &
+./acceptor.sh 2 $CONFIG &
+./acceptor.sh 3 $CONFIG &
+
+sleep 1
+
+echo "Starting 2 learners..."
+
+./learner.sh 4 $CONFIG > ../learn1 &
+./learner.sh 5 $CONFIG > ../learn2 &
+
+sleep 1
+
+echo "Starting 2 proposers and 1 proposer of the foreign cluster..."
+
+./proposer.sh 6 $CONFIG &
+./proposer.sh 7 $CONFIG &
+./proposer.sh 10 $FOREIGN_CONFIG &
+
+echo "Waiting 10 seconds before starting clients..."
+sleep 10
+
+echo "Starting 2 clients and 1 client of the foreign cluster..."
+
+./client.sh 8 $CONFIG < ../prop1 &
+./client.sh 9 $CONFIG < ../prop2 &
+./client.sh 11 $FOREIGN_CONFIG < ../prop3 &
+
+sleep 5
+
+$KILLCMD
+$FOREIGN_KILLCMD
+wait
+
+cd ..
+
+# The values of the foreign client must not be learned (see Test 2 of check_all.sh).
+rm $FOREIGN_CONFIG prop3
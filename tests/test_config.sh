@@ -0,0 +1,39 @@
+#!/usr/bin/env bash
+
+echo "Tests for a missing configuration file, and for the built-in configuration (no sockets are kept open)."
+
+MISSING=`pwd`/missing.toml
+NOT_FOUND="Config file '$MISSING' not found"
+
+rm -f $MISSING
+
+echo "Building the starters..."
+cargo build --example start_proposer --example start_acceptor --example start_learner 2> /dev/null
+
+TEST="Test 1 - The dry run of a node given a missing configuration file reports that the file is not found"
+echo "$TEST"
+OUTPUT=`cargo run --example start_acceptor -- --dry-run 1 $MISSING 2>&1`
+if [[ $? -ne 0 ]] && grep -qF "$NOT_FOUND" <<< "$OUTPUT"; then
+	echo "  > OK"
+else
+	echo "  > Failed!"
+fi
+
+TEST="Test 2 - A node given a missing configuration file stops with the same error"
+echo "$TEST"
+OUTPUT=`cargo run --example start_learner -- 1 $MISSING 2>&1 < /dev/null`
+if [[ $? -ne 0 ]] && grep -qF "$NOT_FOUND" <<< "$OUTPUT"; then
+	echo "  > OK"
+else
+	echo "  > Failed!"
+fi
+
+TEST="Test 3 - The built-in configuration is valid for the proposers, the acceptors and the learners"
+echo "$TEST"
+if cargo run --example start_proposer -- --dry-run 1 builtin > /dev/null 2>&1 &&
+	cargo run --example start_acceptor -- --dry-run 2 builtin > /dev/null 2>&1 &&
+	cargo run --example start_learner -- --dry-run 3 builtin > /dev/null 2>&1; then
+	echo "  > OK"
+else
+	echo "  > Failed!"
+fi
@@ -0,0 +1,83 @@
+#!/usr/bin/env bash
+
+echo "Tests for a cluster whose messages are encrypted, and an intruder which does not know its secret."
+
+STARTERS="$1"
+CONFIG=`pwd`/../Config.toml
+CLUSTER_CONFIG=`pwd`/encrypted.toml
+INTRUDER_CONFIG=`pwd`/intruder.toml
+N="$2"
+
+if [[ x$STARTERS == "x" || x$N == "x" ]]; then
+	echo "Usage: $0 <starter scripts folder> <number of values per proposer>"
+    exit 1
+fi
+
+# The cluster and the intruder use copies of the configuration file, with different secrets.
+cp $CONFIG $CLUSTER_CONFIG
+printf '\n[cluster]\nsecret = "8f1c0e5d2a7b4f3e9d6c1b0a5e4f3d2c"\n' >> $CLUSTER_CONFIG
+cp $CONFIG $INTRUDER_CONFIG
+printf '\n[cluster]\nsecret = "not the secret of the cluster"\n' >> $INTRUDER_CONFIG
+
+# following line kills processes that have the config files in its cmdline
+KILLCMD="pkill -f $CLUSTER_CONFIG"
+INTRUDER_KILLCMD="pkill -f $INTRUDER_CONFIG"
+
+$KILLCMD
+$INTRUDER_KILLCMD
+
+cd $STARTERS
+
+# The starters build the nodes with this feature.
+export FEATURES=encryption
+
+echo "Building the nodes with encryption..."
+
+cargo build --features $FEATURES --examples
+
+echo "Generating $N random proposals (which are numbers) for each client..."
+
+../generate.sh $N > ../prop1
+../generate.sh $N > ../prop2
+../generate.sh $N > ../prop3
+
+echo "Starting 3 acceptors..."
+
+./acceptor.sh 1 $CLUSTER_CONFIG &
+./acceptor.sh 2 $CLUSTER_CONFIG &
+./acceptor.sh 3 $CLUSTER_CONFIG &
+
+sleep 1
+
+echo "Starting 2 learners..."
+
+./learner.sh 4 $CLUSTER_CONFIG > ../learn1 &
+./learner.sh 5 $CLUSTER_CONFIG > ../learn2 &
+
+sleep 1
+
+echo "Starting 2 proposers and 1 proposer of the intruder..."
+
+./proposer.sh 6 $CLUSTER_CONFIG &
+./proposer.sh 7 $CLUSTER_CONFIG &
+./proposer.sh 10 $INTRUDER_CONFIG &
+
+echo "Waiting 10 seconds before starting clients..."
+sleep 10
+
+echo "Starting 2 clients and 1 client of the intruder..."
+
+./client.sh 8 $CLUSTER_CONFIG < ../prop1 &
+./client.sh 9 $CLUSTER_CONFIG < ../prop2 &
+./client.sh 11 $INTRUDER_CONFIG < ../prop3 &
+
+sleep 5
+
+$KILLCMD
+$INTRUDER_KILLCMD
+wait
+
+cd ..
+
+# The values of the client of the intruder must not be learned (see Test 2 of check_all.sh).
+rm $CLUSTER_CONFIG $INTRUDER_CONFIG prop3
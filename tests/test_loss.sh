@@ -6,7 +6,6 @@ echo "Tests which attempt to verify the correctness of this Atomic Broadcast imp
 LOSS=0.1
 
 STARTERS="$1"
-CONFIG=`pwd`/../Config.toml
 N="$2"
 
 if [[ x$STARTERS == "x" || x$N == "x" ]]; then
@@ -14,6 +13,15 @@ if [[ x$STARTERS == "x" || x$N == "x" ]]; then
     exit 1
 fi
 
+# The learners use a copy of the configuration file with a gap timeout, so that they recover the
+# lost learned values (without it, they wait for them forever). It is kept in a temporary folder
+# (rather than in a temporary file, whose name would not end in .toml), which is removed on exit.
+CONFIG_DIR=`mktemp -d`
+trap 'rm -r "$CONFIG_DIR"' EXIT
+CONFIG=$CONFIG_DIR/loss.toml
+cat `pwd`/../Config.toml > $CONFIG
+printf '\n[tunables]\ngap_timeout_ms = "1000"\n' >> $CONFIG
+
 # following line kills processes that have the config file in its cmdline
 KILLCMD="pkill -f $CONFIG"
 
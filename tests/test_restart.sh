@@ -0,0 +1,75 @@
+#!/usr/bin/env bash
+
+echo "Tests for the delivery of the learned values, in total order, across a restart of a learner."
+
+STARTERS="$1"
+CONFIG=`pwd`/../Config.toml
+N="$2"
+
+if [[ x$STARTERS == "x" || x$N == "x" ]]; then
+	echo "Usage: $0 <starter scripts folder> <number of values per proposer>"
+    exit 1
+fi
+
+# following line kills processes that have the config file in its cmdline
+KILLCMD="pkill -f $CONFIG"
+
+$KILLCMD
+
+rm -f learn1.log learn1.watermark
+
+cd $STARTERS
+
+echo "Generating $N random proposals (which are numbers) for each client..."
+
+../generate.sh $N > ../prop1
+../generate.sh $N > ../prop2
+
+echo "Starting 3 acceptors..."
+
+./acceptor.sh 1 $CONFIG &
+./acceptor.sh 2 $CONFIG &
+./acceptor.sh 3 $CONFIG &
+
+sleep 1
+
+echo "Starting 2 learners (learner 1 stores its delivery watermark)..."
+
+./learner.sh 4 $CONFIG ../learn1.log ../learn1.watermark > ../learn1 &
+./learner.sh 5 $CONFIG > ../learn2 &
+
+sleep 1
+
+echo "Starting 2 proposers..."
+
+./proposer.sh 6 $CONFIG &
+./proposer.sh 7 $CONFIG &
+
+echo "Waiting 10 seconds before starting clients..."
+sleep 10
+
+echo "Starting client 1..."
+
+./client.sh 8 $CONFIG < ../prop1 &
+
+sleep 1
+
+echo "Killing learner 1..."
+pkill -f "start_learner -- 4 $CONFIG"
+
+echo "Starting client 2..."
+./client.sh 9 $CONFIG < ../prop2 &
+
+sleep 1
+
+# The restarted learner must print neither the values it already printed nor skip any of them, so
+# its output is appended to the one before the restart.
+echo "Restarting learner 1..."
+./learner.sh 4 $CONFIG ../learn1.log ../learn1.watermark >> ../learn1 &
+
+sleep 5
+
+$KILLCMD
+wait
+
+cd ..
@@ -0,0 +1,83 @@
+#!/usr/bin/env bash
+
+echo "Tests for the replicated state machines, i.e. the learners embedded in an application (see examples/replicated_counter.rs)."
+
+STARTERS="$1"
+CONFIG=`pwd`/../Config.toml
+N="$2"
+
+if [[ x$STARTERS == "x" || x$N == "x" ]]; then
+	echo "Usage: $0 <starter scripts folder> <number of values per client>"
+    exit 1
+fi
+
+# following line kills processes that have the config file in its cmdline
+KILLCMD="pkill -f $CONFIG"
+
+$KILLCMD
+
+echo "Building the replicated counter..."
+cargo build --example replicated_counter 2> /dev/null
+
+cd $STARTERS
+
+echo "Generating $N random proposals (which are numbers) for each client..."
+
+../generate.sh $N > ../prop1
+../generate.sh $N > ../prop2
+
+echo "Starting 3 acceptors..."
+
+./acceptor.sh 1 $CONFIG &
+./acceptor.sh 2 $CONFIG &
+./acceptor.sh 3 $CONFIG &
+
+sleep 1
+
+echo "Starting 1 learner and 2 replicas of a counter..."
+
+./learner.sh 4 $CONFIG > ../learn1 &
+cargo run --example replicated_counter -- 5 $CONFIG 2> /dev/null > ../counter1 &
+cargo run --example replicated_counter -- 6 $CONFIG 2> /dev/null > ../counter2 &
+
+sleep 1
+
+echo "Starting 1 proposer..."
+
+./proposer.sh 7 $CONFIG &
+
+echo "Waiting 10 seconds before starting clients..."
+sleep 10
+
+echo "Starting 2 clients..."
+
+./client.sh 8 $CONFIG < ../prop1 &
+./client.sh 9 $CONFIG < ../prop2 &
+
+sleep 5
+
+$KILLCMD
+wait
+
+cd ..
+
+TEST="Test 1 - The replicas applied the values learned by the learner, in the same order"
+echo "$TEST"
+cut -d ' ' -f 1 counter1 > applied1
+cut -d ' ' -f 1 counter2 > applied2
+if [[ -s learn1 ]] && diff -q learn1 applied1 > /dev/null && diff -q learn1 applied2 > /dev/null; then
+	echo "  > OK"
+else
+	echo "  > Failed!"
+fi
+
+TEST="Test 2 - The replicas reached the same total, which is the sum of the learned values"
+echo "$TEST"
+SUM=`awk '{ sum += $1 } END { print sum }' learn1`
+if [[ `tail -n 1 counter1` == "`tail -n 1 learn1` total=$SUM" ]] && diff -q counter1 counter2 > /dev/null; then
+	echo "  > OK"
+else
+	echo "  > Failed!"
+fi
+
+rm prop1 prop2 learn1 counter1 counter2 applied1 applied2
@@ -0,0 +1,76 @@
+#!/usr/bin/env bash
+
+echo "Tests for proposers and learners which also receive the replies addressed to them at a unicast address."
+
+STARTERS="$1"
+CONFIG=`pwd`/../Config.toml
+N="$2"
+
+if [[ x$STARTERS == "x" || x$N == "x" ]]; then
+	echo "Usage: $0 <starter scripts folder> <number of values per proposer>"
+    exit 1
+fi
+
+# Each proposer and learner uses a copy of the configuration file, with its own unicast port.
+unicast_config() {
+    local file=`pwd`/unicast$2.toml
+    sed "/^\[$1\]/a unicast_host = \"127.0.0.1\"\nunicast_port = \"$2\"" $CONFIG > $file
+    echo $file
+}
+
+PROPOSER1_CONFIG=`unicast_config proposers 6001`
+PROPOSER2_CONFIG=`unicast_config proposers 6002`
+LEARNER1_CONFIG=`unicast_config learners 8001`
+LEARNER2_CONFIG=`unicast_config learners 8002`
+
+# following line kills processes that have the config files in its cmdline
+KILLCMD="pkill -f $CONFIG"
+UNICAST_KILLCMD="pkill -f `pwd`/unicast"
+
+$KILLCMD
+$UNICAST_KILLCMD
+
+cd $STARTERS
+
+echo "Generating $N random proposals (which are numbers) for each client..."
+
+../generate.sh $N > ../prop1
+../generate.sh $N > ../prop2
+
+echo "Starting 3 acceptors..."
+
+./acceptor.sh 1 $CONFIG &
+./acceptor.sh 2 $CONFIG &
+./acceptor.sh 3 $CONFIG &
+
+sleep 1
+
+echo "Starting 2 learners with a unicast address..."
+
+./learner.sh 4 $LEARNER1_CONFIG > ../learn1 &
+./learner.sh 5 $LEARNER2_CONFIG > ../learn2 &
+
+sleep 1
+
+echo "Starting 2 proposers with a unicast address..."
+
+./proposer.sh 6 $PROPOSER1_CONFIG &
+./proposer.sh 7 $PROPOSER2_CONFIG &
+
+echo "Waiting 10 seconds before starting clients..."
+sleep 10
+
+echo "Starting 2 clients..."
+
+./client.sh 8 $CONFIG < ../prop1 &
+./client.sh 9 $CONFIG < ../prop2 &
+
+sleep 5
+
+$KILLCMD
+$UNICAST_KILLCMD
+wait
+
+cd ..
+
+rm $PROPOSER1_CONFIG $PROPOSER2_CONFIG $LEARNER1_CONFIG $LEARNER2_CONFIG
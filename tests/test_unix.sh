@@ -0,0 +1,67 @@
+#!/usr/bin/env bash
+
+echo "Tests for a cluster whose nodes send their messages over Unix datagram sockets instead of UDP multicast."
+
+STARTERS="$1"
+CONFIG=`pwd`/../Config.toml
+UNIX_CONFIG=`pwd`/unix.toml
+SOCKET_DIR=`mktemp -d`
+N="$2"
+
+if [[ x$STARTERS == "x" || x$N == "x" ]]; then
+	echo "Usage: $0 <starter scripts folder> <number of values per proposer>"
+    exit 1
+fi
+
+# The nodes use a copy of the configuration file, with the Unix transport.
+cp $CONFIG $UNIX_CONFIG
+printf '\n[cluster]\ntransport = "unix"\nsocket_dir = "%s"\n' $SOCKET_DIR >> $UNIX_CONFIG
+
+# following line kills processes that have the config file in its cmdline
+KILLCMD="pkill -f $UNIX_CONFIG"
+
+$KILLCMD
+
+cd $STARTERS
+
+echo "Generating $N random proposals (which are numbers) for each client..."
+
+../generate.sh $N > ../prop1
+../generate.sh $N > ../prop2
+
+echo "Starting 3 acceptors..."
+
+./acceptor.sh 1 $UNIX_CONFIG &
+./acceptor.sh 2 $UNIX_CONFIG &
+./acceptor.sh 3 $UNIX_CONFIG &
+
+sleep 1
+
+echo "Starting 2 learners..."
+
+./learner.sh 4 $UNIX_CONFIG > ../learn1 &
+./learner.sh 5 $UNIX_CONFIG > ../learn2 &
+
+sleep 1
+
+echo "Starting 2 proposers..."
+
+./proposer.sh 6 $UNIX_CONFIG &
+./proposer.sh 7 $UNIX_CONFIG &
+
+echo "Waiting 10 seconds before starting clients..."
+sleep 10
+
+echo "Starting 2 clients..."
+
+./client.sh 8 $UNIX_CONFIG < ../prop1 &
+./client.sh 9 $UNIX_CONFIG < ../prop2 &
+
+sleep 5
+
+$KILLCMD
+wait
+
+cd ..
+
+rm -r $UNIX_CONFIG $SOCKET_DIR
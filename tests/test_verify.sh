@@ -0,0 +1,98 @@
+#!/usr/bin/env bash
+
+echo "Tests for the verification of the agreement of running nodes, through their control sockets (see examples/verify.rs)."
+
+STARTERS="$1"
+CLUSTER_CONFIG=`pwd`/verified.toml
+FOREIGN_CONFIG=`pwd`/foreign.toml
+N="$2"
+
+if [[ x$STARTERS == "x" || x$N == "x" ]]; then
+	echo "Usage: $0 <starter scripts folder> <number of values per client>"
+    exit 1
+fi
+
+# Each cluster uses a copy of the configuration file, with control sockets for its proposer and its
+# learner. The foreign cluster has another cluster id, so it decides other values in the same
+# instances, on the same addresses.
+cp ../Config.toml $CLUSTER_CONFIG
+sed -i -e '/^\[proposers\]/a admin_port = "9101"' -e '/^\[learners\]/a admin_port = "9102"' $CLUSTER_CONFIG
+
+cp ../Config.toml $FOREIGN_CONFIG
+sed -i -e '/^\[proposers\]/a admin_port = "9201"' -e '/^\[learners\]/a admin_port = "9202"' $FOREIGN_CONFIG
+printf '\n[cluster]\nid = "foreign"\n' >> $FOREIGN_CONFIG
+
+# following line kills processes that have the config file in its cmdline
+KILLCMD="pkill -f $CLUSTER_CONFIG"
+FOREIGN_KILLCMD="pkill -f $FOREIGN_CONFIG"
+
+$KILLCMD
+$FOREIGN_KILLCMD
+
+echo "Building the verification script..."
+cargo build --example verify 2> /dev/null
+
+cd $STARTERS
+
+echo "Generating $N random proposals (which are numbers) for each client..."
+
+../generate.sh $N > ../prop1
+../generate.sh $N > ../prop2
+
+echo "Starting 3 acceptors for each cluster..."
+
+for id in 1 2 3; do
+	./acceptor.sh $id $CLUSTER_CONFIG &
+	./acceptor.sh $id $FOREIGN_CONFIG &
+done
+
+sleep 1
+
+echo "Starting 1 learner for each cluster..."
+
+./learner.sh 4 $CLUSTER_CONFIG > /dev/null &
+./learner.sh 4 $FOREIGN_CONFIG > /dev/null &
+
+sleep 1
+
+echo "Starting 1 proposer for each cluster..."
+
+./proposer.sh 5 $CLUSTER_CONFIG &
+./proposer.sh 5 $FOREIGN_CONFIG &
+
+echo "Waiting 10 seconds before starting clients..."
+sleep 10
+
+echo "Starting 1 client for each cluster..."
+
+./client.sh 6 $CLUSTER_CONFIG < ../prop1 &
+./client.sh 6 $FOREIGN_CONFIG < ../prop2 &
+
+sleep 5
+
+cd ..
+
+TEST="Test 1 - The proposer and the learner of a cluster agree"
+echo "$TEST"
+if cargo run --example verify -- 127.0.0.1:9101 127.0.0.1:9102 2> /dev/null > verify.out; then
+	echo "  > OK"
+else
+	cat verify.out
+	echo "  > Failed!"
+fi
+
+TEST="Test 2 - The nodes of two different clusters diverge"
+echo "$TEST"
+cargo run --example verify -- 127.0.0.1:9101 127.0.0.1:9202 2> /dev/null > verify.out
+if [[ $? == 1 ]]; then
+	echo "  > OK"
+else
+	cat verify.out
+	echo "  > Failed!"
+fi
+
+$KILLCMD
+$FOREIGN_KILLCMD
+wait
+
+rm $CLUSTER_CONFIG $FOREIGN_CONFIG prop1 prop2 verify.out
@@ -0,0 +1,252 @@
+//! Tests of the truncation of the logs below a checkpoint, of the epochs, of the fragmentation and
+//! of the rate limiting, over the deterministic in-memory transport of the module testkit, and
+//! static checks that the nodes can be moved to another thread. The examples (see the file
+//! README.md of this folder) check the same features in more scenarios.
+//!
+//! You can run these tests as follows
+//!     cargo test --features testkit --test testkit
+
+extern crate multi_paxos;
+
+use multi_paxos::message::Message;
+use multi_paxos::multi_paxos::{Acceptor, Client, Learner, Observer, Proposer};
+use multi_paxos::testkit::{Choice, Cluster, Role};
+
+/// The value which marks a checkpoint of the application.
+const CHECKPOINT: usize = usize::MAX;
+
+/// The number of values requested by each test.
+const NUM_OF_VALUES: usize = 10;
+
+/// A value of 1 KiB, which does not fit in a datagram of MAX_DATAGRAM_SIZE bytes.
+type Blob = [[u8; 32]; 32];
+
+/// The maximum datagram size of the nodes in the tests of the fragmentation.
+const MAX_DATAGRAM_SIZE: usize = 256;
+
+/// A rate of messages per second low enough that no token is added during a test.
+const LOW_RATE: u32 = 1;
+
+/// Returns a cluster with 1 client, 1 proposer, 3 acceptors and 1 learner, which truncate their
+/// logs below CHECKPOINT, in which the values from 1 to NUM_OF_VALUES, and then CHECKPOINT, were
+/// requested, but where the LearnAck message of CHECKPOINT was lost.
+fn cluster_with_unacknowledged_checkpoint() -> Cluster<usize> {
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1).with_checkpoint(CHECKPOINT);
+    cluster.run_until_quiet();
+
+    (1..=NUM_OF_VALUES).for_each(|v| cluster.request(0, v));
+
+    cluster.client(0).request(CHECKPOINT);
+    while {
+        cluster.drop_pending(|_, m| match m {
+            Message::LearnAck(learn_ack) => learn_ack.instance.0 == NUM_OF_VALUES + 1,
+            _ => false,
+        });
+        cluster.step()
+    } {}
+
+    cluster
+}
+
+/// Returns whether the proposer and the acceptors of cluster truncated their logs below instance.
+fn truncated_below(cluster: &mut Cluster<usize>, instance: usize) -> bool {
+    cluster.proposer(0).truncated_below() == instance
+        && (0..3).all(|i| cluster.acceptor(i).truncated_below() == instance)
+}
+
+/// Returns a cluster with 1 client, 1 proposer, 3 acceptors and 1 learner, whose clients, proposers
+/// and learners send datagrams of at most MAX_DATAGRAM_SIZE bytes.
+fn cluster_with_small_datagrams() -> Cluster<Blob> {
+    let mut cluster = Cluster::<Blob>::new(1, 1, 3, 1)
+        .with_clients(|c| c.with_max_datagram_size(MAX_DATAGRAM_SIZE))
+        .with_proposers(|p| p.with_max_datagram_size(MAX_DATAGRAM_SIZE))
+        .with_learners(|l| l.with_max_datagram_size(MAX_DATAGRAM_SIZE));
+    cluster.run_until_quiet();
+    cluster
+}
+
+/// Returns the value of 1 KiB generated from seed.
+fn blob(seed: u8) -> Blob {
+    let mut blob = [[0; 32]; 32];
+
+    for (i, row) in blob.iter_mut().enumerate() {
+        for (j, byte) in row.iter_mut().enumerate() {
+            *byte = seed.wrapping_add((i * 32 + j) as u8);
+        }
+    }
+
+    blob
+}
+
+fn assert_send<T: Send>() {}
+
+#[test]
+fn checkpoint_is_not_stable_until_acknowledged() {
+    let mut cluster = cluster_with_unacknowledged_checkpoint();
+
+    assert!(truncated_below(&mut cluster, 1));
+    assert_eq!(cluster.proposer(0).export_log().len(), NUM_OF_VALUES + 1);
+}
+
+#[test]
+fn checkpoint_truncates_the_logs_once_acknowledged() {
+    let checkpoint = NUM_OF_VALUES + 1;
+    let mut cluster = cluster_with_unacknowledged_checkpoint();
+
+    cluster.proposer(0).retransmit_learnings();
+    cluster.run_until_quiet();
+
+    assert!(truncated_below(&mut cluster, checkpoint));
+
+    let log = cluster.proposer(0).export_log();
+    assert_eq!(
+        log.keys().cloned().collect::<Vec<usize>>(),
+        vec![checkpoint]
+    );
+    assert_eq!(log[&checkpoint], CHECKPOINT);
+}
+
+#[test]
+fn late_learner_skips_the_truncated_instances() {
+    let checkpoint = NUM_OF_VALUES + 1;
+    let mut cluster = cluster_with_unacknowledged_checkpoint();
+
+    cluster.proposer(0).retransmit_learnings();
+    cluster.run_until_quiet();
+    cluster.request(0, checkpoint + 1);
+
+    let i = cluster.add_learner();
+    cluster.run_until_quiet();
+
+    let delivered: Vec<usize> = cluster
+        .learner(i)
+        .total_order_prefix()
+        .iter()
+        .map(|&(instance, _)| instance)
+        .collect();
+    assert_eq!(delivered, vec![checkpoint, checkpoint + 1]);
+}
+
+#[test]
+fn epoch_prepares_the_tail_once() {
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1).with_trace_collector();
+    cluster.run_until_quiet();
+
+    let rnd = cluster.proposer(0).establish_epoch().unwrap();
+    cluster.run_until_quiet();
+
+    (1..=NUM_OF_VALUES).for_each(|v| cluster.request(0, v));
+
+    assert_eq!(cluster.proposer(0).epoch(), Some(rnd));
+    assert_eq!(
+        cluster.count_delivered(|m| matches!(m, Message::TailPreparation(_))),
+        1
+    );
+    assert_eq!(
+        cluster.count_delivered(|m| matches!(m, Message::Phase1a(_))),
+        0
+    );
+    assert_eq!(
+        cluster.delivered_values(0),
+        (1..=NUM_OF_VALUES).collect::<Vec<usize>>()
+    );
+}
+
+#[test]
+fn epoch_completes_the_instances_voted_in_the_tail() {
+    let mut cluster = Cluster::<usize>::new(1, 2, 3, 1).with_leader(0);
+    cluster.run_until_quiet();
+
+    // The acceptors vote for the value of the first proposer, which never knows it.
+    cluster.client(0).request(7);
+    while cluster.step() {
+        cluster.drop_pending(|_, m| matches!(m, Message::Phase2b(_)));
+    }
+    assert_eq!(cluster.learner(0).learned_value(1), None);
+
+    cluster.proposer(1).establish_epoch().unwrap();
+    cluster.run_until_quiet();
+
+    assert_eq!(cluster.learner(0).learned_value(1), Some(7));
+}
+
+#[test]
+fn fragments_are_reassembled() {
+    let mut cluster = cluster_with_small_datagrams();
+    let values: Vec<Blob> = (1..=NUM_OF_VALUES as u8).map(blob).collect();
+
+    for &value in &values {
+        cluster.client(0).request(value);
+        assert!(cluster.num_of_pending() > 1);
+        cluster.run_until_quiet();
+    }
+
+    assert!(cluster.delivered_values(0) == values);
+}
+
+#[test]
+fn message_whose_fragment_is_lost_is_dropped() {
+    let mut cluster = cluster_with_small_datagrams();
+
+    cluster.client(0).request(blob(1));
+    cluster.step_with(Choice::Drop(0));
+    cluster.run_until_quiet();
+
+    assert!(cluster.delivered_values(0).is_empty());
+
+    cluster.request(0, blob(1));
+
+    assert!(cluster.delivered_values(0) == vec![blob(1)]);
+}
+
+#[test]
+fn rate_limited_client_sends_a_burst() {
+    let burst = NUM_OF_VALUES / 2;
+
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1)
+        .with_clients(|c| c.with_max_send_rate(LOW_RATE, burst))
+        .with_trace_collector();
+    cluster.run_until_quiet();
+
+    (1..=NUM_OF_VALUES).for_each(|v| cluster.client(0).request(v));
+    cluster.run_until_quiet();
+
+    assert_eq!(
+        cluster.count_delivered(|m| matches!(m, Message::Phase0a(_))),
+        burst
+    );
+    assert_eq!(
+        cluster.delivered_values(0),
+        (1..=burst).collect::<Vec<usize>>()
+    );
+}
+
+#[test]
+fn rate_limited_proposer_still_sends_its_learnings() {
+    // The CatchUp and the Report sent by the proposer when it starts, then the Preparation and the
+    // Proposal of the instance, take all its tokens.
+    let mut cluster = Cluster::<usize>::new(1, 1, 3, 1)
+        .with_proposers(|p| p.with_max_send_rate(LOW_RATE, 4))
+        .with_trace_collector();
+    cluster.run_until_quiet();
+
+    cluster.request(0, 1);
+
+    assert!(
+        cluster
+            .trace_collector()
+            .unwrap()
+            .count(|e| e.receiver == Role::Learners && matches!(e.message, Message::Phase3(_)))
+            > 0
+    );
+    assert_eq!(cluster.delivered_values(0), vec![1]);
+}
+
+#[test]
+fn nodes_are_send() {
+    assert_send::<Client<usize>>();
+    assert_send::<Proposer<usize>>();
+    assert_send::<Acceptor<usize>>();
+    assert_send::<Learner<usize>>();
+    assert_send::<Observer<usize>>();
+}